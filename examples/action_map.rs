@@ -0,0 +1,141 @@
+use tetra::graphics::{self, Color, DrawParams, Texture};
+use tetra::input::{self, ActionMap, AxisBinding, Binding, GamepadAxis, GamepadButton, Key};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Control {
+    MoveX,
+    MoveY,
+    Reset,
+}
+
+struct GameState {
+    texture: Texture,
+    position: Vec2<f32>,
+    controls: ActionMap<Control>,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let mut controls = ActionMap::new();
+
+        // Each axis can be controlled by WASD, the arrow keys, the D-pad or the left
+        // stick of the first gamepad - whichever is moved the furthest wins.
+        controls
+            .bind_axis(
+                Control::MoveX,
+                AxisBinding::Keys {
+                    negative: Key::A,
+                    positive: Key::D,
+                },
+            )
+            .bind_axis(
+                Control::MoveX,
+                AxisBinding::Keys {
+                    negative: Key::Left,
+                    positive: Key::Right,
+                },
+            )
+            .bind_axis(
+                Control::MoveX,
+                AxisBinding::GamepadButtons {
+                    gamepad_id: 0,
+                    negative: GamepadButton::Left,
+                    positive: GamepadButton::Right,
+                },
+            )
+            .bind_axis(
+                Control::MoveX,
+                AxisBinding::GamepadAxis {
+                    gamepad_id: 0,
+                    axis: GamepadAxis::LeftStickX,
+                },
+            );
+
+        controls
+            .bind_axis(
+                Control::MoveY,
+                AxisBinding::Keys {
+                    negative: Key::W,
+                    positive: Key::S,
+                },
+            )
+            .bind_axis(
+                Control::MoveY,
+                AxisBinding::Keys {
+                    negative: Key::Up,
+                    positive: Key::Down,
+                },
+            )
+            .bind_axis(
+                Control::MoveY,
+                AxisBinding::GamepadButtons {
+                    gamepad_id: 0,
+                    negative: GamepadButton::Up,
+                    positive: GamepadButton::Down,
+                },
+            )
+            .bind_axis(
+                Control::MoveY,
+                AxisBinding::GamepadAxis {
+                    gamepad_id: 0,
+                    axis: GamepadAxis::LeftStickY,
+                },
+            );
+
+        controls
+            .bind_action(Control::Reset, Binding::Key(Key::Space))
+            .bind_action(
+                Control::Reset,
+                Binding::GamepadButton {
+                    gamepad_id: 0,
+                    button: GamepadButton::A,
+                },
+            );
+
+        Ok(GameState {
+            texture: Texture::new(ctx, "./examples/resources/player.png")?,
+            position: Vec2::new(320.0, 240.0),
+            controls,
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        let movement = Vec2::new(
+            input::get_axis(ctx, &self.controls, Control::MoveX),
+            input::get_axis(ctx, &self.controls, Control::MoveY),
+        );
+
+        self.position += movement * 4.0;
+
+        if input::is_action_pressed(ctx, &self.controls, Control::Reset) {
+            self.position = Vec2::new(320.0, 240.0);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.769, 0.812, 0.631));
+
+        self.texture.draw(
+            ctx,
+            DrawParams::new()
+                .position(self.position)
+                .origin(Vec2::new(8.0, 8.0))
+                .scale(Vec2::new(2.0, 2.0)),
+        );
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Action Mapping", 640, 480)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -0,0 +1,127 @@
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, Color, DrawParams, Shader, Texture, WarmUpItem};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+const TEXTURE_PATHS: [&str; 6] = [
+    "./examples/resources/player.png",
+    "./examples/resources/backdrop.png",
+    "./examples/resources/block.png",
+    "./examples/resources/panel.png",
+    "./examples/resources/tiles.png",
+    "./examples/resources/overlay.png",
+];
+
+enum Scene {
+    // While loading, one asset is loaded and warmed up per frame, so that
+    // the progress can be displayed.
+    Loading {
+        textures: Vec<Texture>,
+        shader: Option<Shader>,
+    },
+
+    Ready {
+        textures: Vec<Texture>,
+        shader: Shader,
+    },
+}
+
+struct GameState {
+    scene: Scene,
+    text: Text,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        Ok(GameState {
+            scene: Scene::Loading {
+                textures: Vec::new(),
+                shader: None,
+            },
+            text: Text::new(
+                "",
+                Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 32.0)?,
+            ),
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if let Scene::Loading { textures, shader } = &mut self.scene {
+            if textures.len() < TEXTURE_PATHS.len() {
+                let texture = Texture::new(ctx, TEXTURE_PATHS[textures.len()])?;
+
+                // Warming up the texture means the first real draw won't cause a hitch.
+                graphics::warm_up(ctx, &[WarmUpItem::Texture(&texture)])?;
+
+                textures.push(texture);
+            } else if shader.is_none() {
+                let new_shader =
+                    Shader::from_fragment_file(ctx, "./examples/resources/disco.frag")?;
+
+                new_shader.set_uniform(ctx, "u_overlay", &textures[5])?;
+                new_shader.set_uniform(ctx, "u_red", 1.0)?;
+                new_shader.set_uniform(ctx, "u_green", 0.5)?;
+                new_shader.set_uniform(ctx, "u_blue", 1.0)?;
+
+                new_shader.warm_up(ctx)?;
+
+                *shader = Some(new_shader);
+            } else {
+                self.scene = Scene::Ready {
+                    textures: std::mem::take(textures),
+                    shader: shader.take().unwrap(),
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
+
+        match &self.scene {
+            Scene::Loading { textures, shader } => {
+                let loaded = textures.len() + usize::from(shader.is_some());
+                let total = TEXTURE_PATHS.len() + 1;
+
+                self.text
+                    .set_content(format!("Loading... {}/{}", loaded, total));
+
+                self.text.draw(ctx, Vec2::new(16.0, 16.0));
+            }
+
+            Scene::Ready { textures, shader } => {
+                graphics::set_shader(ctx, shader);
+
+                for (i, texture) in textures.iter().enumerate() {
+                    texture.draw(
+                        ctx,
+                        DrawParams::new()
+                            .position(Vec2::new(16.0 + i as f32 * 128.0, 16.0))
+                            .scale(Vec2::new(
+                                96.0 / texture.width() as f32,
+                                96.0 / texture.height() as f32,
+                            )),
+                    );
+                }
+
+                graphics::reset_shader(ctx);
+
+                self.text.set_content("Done!");
+                self.text.draw(ctx, Vec2::new(16.0, 128.0));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Warming Up Assets", 1280, 720)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -0,0 +1,89 @@
+// This example demonstrates how to generate a sound at runtime, rather than loading it from
+// a file.
+//
+// Press Space to play a beep, or press 1-3 to change its pitch.
+
+use std::f32::consts::TAU;
+
+use tetra::audio::{self, Sound};
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, Color};
+use tetra::input::{self, Key};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+const SAMPLE_RATE: u32 = 44100;
+const BEEP_LENGTH: f32 = 0.25;
+
+/// Generates a sine wave with the given frequency, fading out towards the end
+/// to avoid a click when it stops.
+fn beep(frequency: f32) -> Sound {
+    let sample_count = (SAMPLE_RATE as f32 * BEEP_LENGTH) as usize;
+
+    let samples: Vec<f32> = (0..sample_count)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = 1.0 - (i as f32 / sample_count as f32);
+
+            (t * frequency * TAU).sin() * envelope
+        })
+        .collect();
+
+    Sound::from_samples(SAMPLE_RATE, 1, &samples)
+}
+
+struct GameState {
+    text: Text,
+    sound: Sound,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        audio::set_master_volume(ctx, 0.4);
+
+        Ok(GameState {
+            text: Text::new(
+                "Press Space to play a beep.\nPress 1, 2 or 3 to change its pitch.",
+                Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 16.0)?,
+            ),
+            sound: beep(440.0),
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_pressed(ctx, Key::Num1) {
+            self.sound = beep(440.0);
+        }
+
+        if input::is_key_pressed(ctx, Key::Num2) {
+            self.sound = beep(660.0);
+        }
+
+        if input::is_key_pressed(ctx, Key::Num3) {
+            self.sound = beep(880.0);
+        }
+
+        if input::is_key_pressed(ctx, Key::Space) {
+            self.sound.play(ctx)?;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.094, 0.11, 0.16));
+
+        self.text.draw(ctx, Vec2::new(16.0, 16.0));
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Generating Audio", 640, 480)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -1,5 +1,7 @@
 /// Based on https://github.com/openfl/openfl-samples/tree/master/demos/BunnyMark
 /// Original BunnyMark (and sprite) by Iain Lobb
+use std::time::Duration;
+
 use rand::rngs::ThreadRng;
 use rand::{self, Rng};
 use tetra::graphics::{self, Color, Texture};
@@ -40,6 +42,8 @@ struct GameState {
 
     auto_spawn: bool,
     spawn_timer: i32,
+
+    stats_timer: Duration,
 }
 
 impl GameState {
@@ -59,6 +63,8 @@ impl GameState {
 
             auto_spawn: false,
             spawn_timer: 0,
+
+            stats_timer: Duration::ZERO,
         })
     }
 }
@@ -118,9 +124,20 @@ impl State for GameState {
             self.texture.draw(ctx, bunny.position);
         }
 
+        // The stats are reset when the frame is presented, so they need to be read
+        // at the end of `draw` to see the totals for the whole frame.
+        self.stats_timer += time::get_delta_time(ctx);
+
+        if self.stats_timer >= Duration::from_secs(1) {
+            self.stats_timer = Duration::ZERO;
+
+            graphics::flush(ctx);
+            println!("{:?}", graphics::get_stats(ctx));
+        }
+
         window::set_title(
             ctx,
-            &format!(
+            format!(
                 "BunnyMark - {} bunnies - {:.0} FPS",
                 self.bunnies.len(),
                 time::get_fps(ctx)
@@ -133,6 +150,8 @@ impl State for GameState {
 
 fn main() -> tetra::Result {
     ContextBuilder::new("BunnyMark", 1280, 720)
+        // All of the bunnies share a texture, so a bigger batch size means fewer draw calls.
+        .sprite_batch_size(16384)
         .quit_on_escape(true)
         .build()?
         .run(GameState::new)
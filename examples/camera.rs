@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
 use tetra::graphics::{self, Camera, Color, DrawParams, Texture};
 use tetra::input::{self, Key};
@@ -10,6 +12,7 @@ const ZOOM_SPEED: f32 = 0.1;
 
 struct GameState {
     texture: Texture,
+    tiles: Texture,
     scaler: ScreenScaler,
     camera: Camera,
 }
@@ -18,6 +21,11 @@ impl GameState {
     fn new(ctx: &mut Context) -> tetra::Result<GameState> {
         Ok(GameState {
             texture: Texture::new(ctx, "./examples/resources/player.png")?,
+
+            // The tiles are drawn at a small scale, so they are loaded with mipmaps
+            // and trilinear filtering to stop them from shimmering when zoomed out:
+            tiles: Texture::with_mipmaps(ctx, "./examples/resources/tiles.png")?,
+
             scaler: ScreenScaler::with_window_size(
                 ctx,
                 640,
@@ -66,7 +74,12 @@ impl State for GameState {
             self.camera.scale -= ZOOM_SPEED;
         }
 
-        self.camera.update();
+        if input::is_key_pressed(ctx, Key::Space) {
+            self.camera.shake(8.0, Duration::from_millis(500));
+        }
+
+        // This updates the camera's shake effect, and then recalculates its matrix.
+        self.camera.advance(ctx);
 
         Ok(())
     }
@@ -80,6 +93,13 @@ impl State for GameState {
         graphics::set_transform_matrix(ctx, self.camera.as_matrix());
 
         // Now all drawing operations will be transformed:
+        self.tiles.draw(
+            ctx,
+            DrawParams::new()
+                .origin(Vec2::new(256.0, 256.0))
+                .scale(Vec2::new(0.25, 0.25)),
+        );
+
         self.texture.draw(
             ctx,
             DrawParams::new()
@@ -100,9 +120,13 @@ impl State for GameState {
         Ok(())
     }
 
-    fn event(&mut self, _: &mut Context, event: Event) -> tetra::Result {
-        if let Event::Resized { width, height } = event {
-            self.scaler.set_outer_size(width, height);
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        if let Event::WindowMetricsChanged {
+            logical_size: (width, height),
+            ..
+        } = event
+        {
+            self.scaler.set_outer_size(ctx, width, height)?;
         }
 
         Ok(())
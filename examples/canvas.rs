@@ -68,9 +68,9 @@ impl State for GameState {
 
         graphics::set_shader(ctx, &self.shader);
 
-        self.shader.set_uniform(ctx, "u_red", self.red);
-        self.shader.set_uniform(ctx, "u_green", self.green);
-        self.shader.set_uniform(ctx, "u_blue", self.blue);
+        self.shader.set_uniform(ctx, "u_red", self.red)?;
+        self.shader.set_uniform(ctx, "u_green", self.green)?;
+        self.shader.set_uniform(ctx, "u_blue", self.blue)?;
 
         self.canvas.draw(ctx, Vec2::zero());
 
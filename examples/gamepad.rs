@@ -182,8 +182,10 @@ impl State for GameState {
             }
 
             self.axis_info.set_content(format!(
-                "Gamepad: {}\nLeft Stick: ({}, {}) | Right Stick: ({}, {}) | Left Trigger: {} | Right Trigger: {}",
+                "Gamepad: {} ({:?}, power: {:?})\nLeft Stick: ({}, {}) | Right Stick: ({}, {}) | Left Trigger: {} | Right Trigger: {}",
                 input::get_gamepad_name(ctx, 0).unwrap(),
+                input::get_gamepad_type(ctx, 0).unwrap(),
+                input::get_gamepad_power_info(ctx, 0),
                 input::get_gamepad_axis_position(ctx, 0, GamepadAxis::LeftStickX),
                 input::get_gamepad_axis_position(ctx, 0, GamepadAxis::LeftStickY),
                 input::get_gamepad_axis_position(ctx, 0, GamepadAxis::RightStickX),
@@ -0,0 +1,114 @@
+use tetra::graphics::mesh::{BufferUsage, Instance, InstanceBuffer, Mesh, ShapeStyle};
+use tetra::graphics::{self, Color, DrawParams};
+use tetra::math::Vec2;
+use tetra::time;
+use tetra::{Context, ContextBuilder, State};
+
+const SCREEN_WIDTH: i32 = 1280;
+const SCREEN_HEIGHT: i32 = 720;
+
+const BLADE_COUNT: usize = 100_000;
+
+struct Blade {
+    position: Vec2<f32>,
+    phase: f32,
+    color: Color,
+}
+
+struct GameState {
+    mesh: Mesh,
+    instance_buffer: InstanceBuffer,
+    blades: Vec<Blade>,
+    instances: Vec<Instance>,
+    elapsed: f32,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let mut mesh = Mesh::polygon(
+            ctx,
+            ShapeStyle::Fill,
+            &[
+                Vec2::new(-2.0, 0.0),
+                Vec2::new(2.0, 0.0),
+                Vec2::new(0.0, -16.0),
+            ],
+        )?;
+
+        mesh.set_backface_culling(false);
+
+        // A simple LCG is enough to scatter the grass around - we don't need
+        // anything fancy here.
+        let mut seed: u32 = 12345;
+        let mut random = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+
+        let blades: Vec<Blade> = (0..BLADE_COUNT)
+            .map(|_| Blade {
+                position: Vec2::new(
+                    random() * SCREEN_WIDTH as f32,
+                    random() * SCREEN_HEIGHT as f32 + 8.0,
+                ),
+                phase: random() * std::f32::consts::TAU,
+                color: Color::rgb(0.2 + random() * 0.2, 0.5 + random() * 0.4, 0.2),
+            })
+            .collect();
+
+        let instances = vec![Instance::default(); BLADE_COUNT];
+        let instance_buffer = InstanceBuffer::with_usage(ctx, &instances, BufferUsage::Stream)?;
+
+        mesh.set_instance_buffer(instance_buffer.clone());
+
+        Ok(GameState {
+            mesh,
+            instance_buffer,
+            blades,
+            instances,
+            elapsed: 0.0,
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        self.elapsed += time::get_delta_time(ctx).as_secs_f32();
+
+        for (blade, instance) in self.blades.iter().zip(&mut self.instances) {
+            let sway = (self.elapsed * 2.0 + blade.position.x * 0.01 + blade.phase).sin();
+
+            *instance = Instance::from_params(
+                DrawParams::new()
+                    .position(blade.position)
+                    .rotation(sway * 0.3)
+                    .color(blade.color),
+            );
+        }
+
+        self.instance_buffer.set_data(ctx, &self.instances, 0);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.094, 0.11, 0.16));
+
+        // All of the grass is drawn in a single draw call.
+        self.mesh
+            .draw_instanced(ctx, self.instance_buffer.len(), DrawParams::new());
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new(
+        "Rendering with Instance Buffers",
+        SCREEN_WIDTH,
+        SCREEN_HEIGHT,
+    )
+    .show_mouse(true)
+    .build()?
+    .run(GameState::new)
+}
@@ -26,7 +26,7 @@ impl GameState {
         }
 
         let shader = Shader::from_vertex_file(ctx, "./examples/resources/instanced.vert")?;
-        shader.set_uniform(ctx, "u_offsets", offsets.as_slice());
+        shader.set_uniform(ctx, "u_offsets", offsets.as_slice())?;
 
         graphics::set_shader(ctx, &shader);
 
@@ -35,11 +35,15 @@ impl State for GameState {
             self.position.y += 4.0;
         }
 
-        let mut pressed = input::get_keys_pressed(ctx).peekable();
+        let mut pressed = input::get_keys_pressed(ctx)
+            .map(|key| (key, input::get_key_label(ctx, key)))
+            .peekable();
+
         if pressed.peek().is_some() {
             println!(
-                "Keys pressed this update: {:?}",
-                pressed.collect::<Vec<_>>()
+                "Keys pressed this update (with labels): {:?}, modifiers: {:?}",
+                pressed.collect::<Vec<_>>(),
+                input::get_key_modifier_state(ctx)
             );
         }
 
@@ -0,0 +1,51 @@
+// This example shows how to drive Tetra's game loop manually, instead of
+// handing control over to `Context::run`. This can be useful if you need
+// to integrate your game into an application that owns the main loop.
+
+use tetra::graphics::{self, Color};
+use tetra::input::{self, Key};
+use tetra::{Context, ContextBuilder, LoopStatus, State};
+
+struct GameState {
+    color: Color,
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_pressed(ctx, Key::Space) {
+            self.color = Color::rgb(1.0 - self.color.r, 1.0 - self.color.g, 1.0 - self.color.b);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, self.color);
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    let mut ctx = ContextBuilder::new("Driving the game loop manually", 1280, 720)
+        .quit_on_escape(true)
+        .build()?;
+
+    let mut state = GameState {
+        color: Color::rgb(0.392, 0.584, 0.929),
+    };
+
+    let mut frames = 0;
+
+    loop {
+        // The host application can do whatever work it needs to between ticks.
+        frames += 1;
+
+        if let LoopStatus::Quit = ctx.tick(&mut state)? {
+            break;
+        }
+    }
+
+    println!("Ran for {} frames", frames);
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use tetra::graphics::mesh::{Mesh, ShapeStyle};
+use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, Camera, Color, DrawParams, Rectangle};
+use tetra::input::{self, Key};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, Event, State};
+
+const SCREEN_WIDTH: i32 = 320;
+const SCREEN_HEIGHT: i32 = 180;
+const TILE_SIZE: f32 = 16.0;
+const GRID_SIZE: i32 = 16;
+
+const MOVEMENT_SPEED: f32 = 2.0;
+const ROTATION_SPEED: f32 = 0.02;
+const ZOOM_SPEED: f32 = 0.1;
+
+struct GameState {
+    scaler: ScreenScaler,
+    camera: Camera,
+    tile: Mesh,
+    text: Text,
+    hovered: Option<(i32, i32)>,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let mut camera = Camera::new(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        camera.position = Vec2::broadcast(GRID_SIZE as f32 * TILE_SIZE / 2.0);
+        camera.update();
+
+        Ok(GameState {
+            scaler: ScreenScaler::with_window_size(
+                ctx,
+                SCREEN_WIDTH,
+                SCREEN_HEIGHT,
+                ScalingMode::ShowAllPixelPerfect,
+            )?,
+            camera,
+            tile: Mesh::rectangle(
+                ctx,
+                ShapeStyle::Fill,
+                Rectangle::new(0.0, 0.0, TILE_SIZE - 1.0, TILE_SIZE - 1.0),
+            )?,
+            text: Text::new(
+                "",
+                Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 8.0)?,
+            ),
+            hovered: None,
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_down(ctx, Key::W) {
+            self.camera.position.y -= MOVEMENT_SPEED;
+        }
+
+        if input::is_key_down(ctx, Key::S) {
+            self.camera.position.y += MOVEMENT_SPEED;
+        }
+
+        if input::is_key_down(ctx, Key::A) {
+            self.camera.position.x -= MOVEMENT_SPEED;
+        }
+
+        if input::is_key_down(ctx, Key::D) {
+            self.camera.position.x += MOVEMENT_SPEED;
+        }
+
+        if input::is_key_down(ctx, Key::Q) {
+            self.camera.rotation -= ROTATION_SPEED;
+        }
+
+        if input::is_key_down(ctx, Key::E) {
+            self.camera.rotation += ROTATION_SPEED;
+        }
+
+        if input::is_mouse_scrolled_up(ctx) {
+            self.camera.scale += ZOOM_SPEED;
+        }
+
+        if input::is_mouse_scrolled_down(ctx) {
+            self.camera.scale = (self.camera.scale - ZOOM_SPEED).map(|s| s.max(ZOOM_SPEED));
+        }
+
+        self.camera.update();
+
+        // The mouse position goes through both the scaler and the camera, so
+        // we end up with a position in the world:
+        let screen = self.scaler.mouse_position(ctx);
+        let world = self.camera.mouse_position_from(ctx, &self.scaler);
+
+        // Clicks in the letterbox bars project outside of the scaler's screen,
+        // so we can ignore them:
+        let screen_rect = Rectangle::new(0.0, 0.0, SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32);
+        let in_bounds = screen_rect.contains_point(screen);
+
+        let tile_x = (world.x / TILE_SIZE).floor() as i32;
+        let tile_y = (world.y / TILE_SIZE).floor() as i32;
+
+        self.hovered =
+            if in_bounds && (0..GRID_SIZE).contains(&tile_x) && (0..GRID_SIZE).contains(&tile_y) {
+                Some((tile_x, tile_y))
+            } else {
+                None
+            };
+
+        self.text.set_content(match (in_bounds, self.hovered) {
+            (false, _) => "Outside of the screen".to_string(),
+            (true, Some((x, y))) => {
+                format!("World: {:.0}, {:.0}\nTile: {}, {}", world.x, world.y, x, y)
+            }
+            (true, None) => format!("World: {:.0}, {:.0}", world.x, world.y),
+        });
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::set_canvas(ctx, self.scaler.canvas());
+        graphics::clear(ctx, Color::rgb(0.094, 0.11, 0.16));
+
+        graphics::set_transform_matrix(ctx, self.camera.as_matrix());
+
+        for y in 0..GRID_SIZE {
+            for x in 0..GRID_SIZE {
+                let color = if self.hovered == Some((x, y)) {
+                    Color::rgb(1.0, 0.8, 0.2)
+                } else {
+                    Color::rgb(0.3, 0.35, 0.45)
+                };
+
+                self.tile.draw(
+                    ctx,
+                    DrawParams::new()
+                        .position(Vec2::new(x as f32, y as f32) * TILE_SIZE)
+                        .color(color),
+                );
+            }
+        }
+
+        graphics::reset_transform_matrix(ctx);
+
+        self.text.draw(ctx, Vec2::new(4.0, 4.0));
+
+        graphics::reset_canvas(ctx);
+        graphics::clear(ctx, Color::BLACK);
+
+        self.scaler.draw(ctx);
+
+        Ok(())
+    }
+
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        if let Event::WindowMetricsChanged {
+            logical_size: (width, height),
+            ..
+        } = event
+        {
+            self.scaler.set_outer_size(ctx, width, height)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Mouse Picking", 1280, 720)
+        .resizable(true)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -0,0 +1,81 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use tetra::graphics::particles::ParticleEmitter;
+use tetra::graphics::{self, Color, Texture, TextureFormat};
+use tetra::input::{self, MouseButton};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+const SCREEN_WIDTH: i32 = 1280;
+const SCREEN_HEIGHT: i32 = 720;
+
+struct GameState {
+    fountain: ParticleEmitter,
+    sparks: ParticleEmitter,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let texture = Texture::from_data(ctx, 4, 4, TextureFormat::Rgba8, &[255; 4 * 4 * 4])?;
+
+        let mut fountain = ParticleEmitter::new(
+            texture.clone(),
+            Vec2::new(SCREEN_WIDTH as f32 / 2.0, SCREEN_HEIGHT as f32 - 64.0),
+        );
+
+        fountain.set_spawn_rate(400.0);
+        fountain.set_lifetime(Duration::from_millis(1500), Duration::from_millis(2500));
+        fountain.set_speed(400.0, 550.0);
+        fountain.set_spread(0.3);
+        fountain.set_gravity(Vec2::new(0.0, 400.0));
+        fountain.set_colors(Color::rgb(0.4, 0.7, 1.0), Color::rgba(0.1, 0.2, 0.8, 0.0));
+        fountain.set_scales(Vec2::broadcast(1.5), Vec2::broadcast(0.5));
+
+        let mut sparks = ParticleEmitter::new(texture, Vec2::zero());
+
+        sparks.set_emitting(false);
+        sparks.set_lifetime(Duration::from_millis(300), Duration::from_millis(800));
+        sparks.set_speed(100.0, 400.0);
+        sparks.set_spread(TAU);
+        sparks.set_gravity(Vec2::new(0.0, 200.0));
+        sparks.set_colors(Color::rgb(1.0, 0.9, 0.4), Color::rgba(1.0, 0.2, 0.0, 0.0));
+
+        // Using a fixed seed means the effect will look the same every time the
+        // example is run.
+        sparks.set_seed(12345);
+
+        Ok(GameState { fountain, sparks })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_mouse_button_pressed(ctx, MouseButton::Left) {
+            self.sparks.set_position(input::get_mouse_position(ctx));
+            self.sparks.emit_burst(200);
+        }
+
+        self.fountain.update(ctx);
+        self.sparks.update(ctx);
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.094, 0.11, 0.16));
+
+        self.fountain.draw(ctx);
+        self.sparks.draw(ctx);
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Particles", SCREEN_WIDTH, SCREEN_HEIGHT)
+        .show_mouse(true)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -0,0 +1,122 @@
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, Canvas, Color, DrawParams, PostProcessChain, Shader, Texture};
+use tetra::input::{self, Key};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+const SCREEN_WIDTH: i32 = 1280;
+const SCREEN_HEIGHT: i32 = 720;
+
+struct GameState {
+    scene: Canvas,
+    chain: PostProcessChain,
+    blur: Shader,
+    grayscale: Shader,
+
+    texture: Texture,
+    text: Text,
+
+    blur_passes: u32,
+    grayscale_enabled: bool,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        Ok(GameState {
+            scene: Canvas::new(ctx, SCREEN_WIDTH, SCREEN_HEIGHT)?,
+            chain: PostProcessChain::new(ctx, SCREEN_WIDTH, SCREEN_HEIGHT)?,
+            blur: Shader::from_fragment_file(ctx, "./examples/resources/blur.frag")?,
+            grayscale: Shader::from_fragment_file(ctx, "./examples/resources/grayscale.frag")?,
+
+            texture: Texture::new(ctx, "./examples/resources/player.png")?,
+            text: Text::new(
+                "",
+                Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 32.0)?,
+            ),
+
+            blur_passes: 1,
+            grayscale_enabled: false,
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_pressed(ctx, Key::Up) {
+            self.blur_passes += 1;
+        }
+
+        if input::is_key_pressed(ctx, Key::Down) {
+            self.blur_passes = self.blur_passes.saturating_sub(1);
+        }
+
+        if input::is_key_pressed(ctx, Key::Space) {
+            self.grayscale_enabled = !self.grayscale_enabled;
+        }
+
+        self.text.set_content(format!(
+            "Blur passes: {} (Up/Down)\nGrayscale: {} (Space)",
+            self.blur_passes, self.grayscale_enabled
+        ));
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        // First, draw the scene to a canvas, as normal:
+        graphics::set_canvas(ctx, &self.scene);
+        graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
+
+        self.texture.draw(
+            ctx,
+            DrawParams::new()
+                .position(Vec2::new(640.0, 360.0))
+                .origin(Vec2::new(8.0, 8.0))
+                .scale(Vec2::new(24.0, 24.0)),
+        );
+
+        graphics::reset_canvas(ctx);
+
+        // Then run it through the chain. Each blur pass is split into a horizontal
+        // and a vertical step, with the uniform being updated in between.
+        let mut passes = Vec::new();
+
+        for _ in 0..self.blur_passes {
+            passes.push((&self.blur, Some(Vec2::new(1.0, 0.0))));
+            passes.push((&self.blur, Some(Vec2::new(0.0, 1.0))));
+        }
+
+        if self.grayscale_enabled {
+            passes.push((&self.grayscale, None));
+        }
+
+        for (i, (shader, direction)) in passes.into_iter().enumerate() {
+            if let Some(direction) = direction {
+                shader.set_uniform(ctx, "u_direction", direction)?;
+            }
+
+            if i == 0 {
+                self.chain.apply(ctx, shader, self.scene.texture());
+            } else {
+                self.chain.apply_to_result(ctx, shader);
+            }
+        }
+
+        if self.blur_passes == 0 && !self.grayscale_enabled {
+            self.scene.draw(ctx, Vec2::zero());
+        } else {
+            self.chain.draw(ctx, Vec2::zero());
+        }
+
+        self.text.draw(ctx, Vec2::new(16.0, 16.0));
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Post-Processing", SCREEN_WIDTH, SCREEN_HEIGHT)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -0,0 +1,23 @@
+#version 150
+
+in vec2 v_uv;
+in vec4 v_color;
+
+uniform sampler2D u_texture;
+uniform vec2 u_direction;
+
+out vec4 o_color;
+
+const float WEIGHTS[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+    vec2 texel = u_direction / vec2(textureSize(u_texture, 0));
+    vec4 result = texture(u_texture, v_uv) * WEIGHTS[0];
+
+    for (int i = 1; i < 5; i++) {
+        result += texture(u_texture, v_uv + texel * float(i)) * WEIGHTS[i];
+        result += texture(u_texture, v_uv - texel * float(i)) * WEIGHTS[i];
+    }
+
+    o_color = v_color * result;
+}
@@ -22,8 +22,11 @@ struct GameState {
 
 impl GameState {
     fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let mut scaler = ScreenScaler::with_window_size(ctx, 640, 480, ScalingMode::Fixed)?;
+        scaler.set_letterbox_color(Color::rgb(0.1, 0.1, 0.15));
+
         Ok(GameState {
-            scaler: ScreenScaler::with_window_size(ctx, 640, 480, ScalingMode::Fixed)?,
+            scaler,
             panel_texture: Texture::new(ctx, "./examples/resources/panel.png")?,
             panel_config: NineSlice::with_border(Rectangle::new(0.0, 0.0, 32.0, 32.0), 4.0),
             text: Text::new(
@@ -33,9 +36,11 @@ impl GameState {
         })
     }
 
-    fn set_mode(&mut self, mode: ScalingMode) {
-        self.scaler.set_mode(mode);
+    fn set_mode(&mut self, ctx: &mut Context, mode: ScalingMode) -> tetra::Result {
+        self.scaler.set_mode(ctx, mode)?;
         self.text.set_content(format!("{}\n{:?}", LABEL, mode));
+
+        Ok(())
     }
 }
 
@@ -48,11 +53,13 @@ impl State for GameState {
                 ScalingMode::ShowAll => ScalingMode::ShowAllPixelPerfect,
                 ScalingMode::ShowAllPixelPerfect => ScalingMode::Crop,
                 ScalingMode::Crop => ScalingMode::CropPixelPerfect,
-                ScalingMode::CropPixelPerfect => ScalingMode::Fixed,
+                ScalingMode::CropPixelPerfect => ScalingMode::FitHeight,
+                ScalingMode::FitHeight => ScalingMode::FitWidth,
+                ScalingMode::FitWidth => ScalingMode::Fixed,
                 _ => ScalingMode::Fixed,
             };
 
-            self.set_mode(next);
+            self.set_mode(ctx, next)?;
         }
 
         Ok(())
@@ -72,16 +79,21 @@ impl State for GameState {
         self.text.draw(ctx, Vec2::new(PANEL_X + 8.0, PANEL_Y + 8.0));
 
         graphics::reset_canvas(ctx);
-        graphics::clear(ctx, Color::BLACK);
 
+        // The scaler fills the letterbox bars itself, so the window doesn't need
+        // to be cleared first:
         self.scaler.draw(ctx);
 
         Ok(())
     }
 
-    fn event(&mut self, _: &mut Context, event: Event) -> tetra::Result {
-        if let Event::Resized { width, height } = event {
-            self.scaler.set_outer_size(width, height);
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        if let Event::WindowMetricsChanged {
+            logical_size: (width, height),
+            ..
+        } = event
+        {
+            self.scaler.set_outer_size(ctx, width, height)?;
         }
 
         Ok(())
@@ -1,6 +1,8 @@
 use tetra::graphics::text::{Font, Text};
 use tetra::graphics::{self, Color, DrawParams, Shader, Texture};
+use tetra::input::{self, Key};
 use tetra::math::Vec2;
+use tetra::time;
 use tetra::{Context, ContextBuilder, State};
 
 struct GameState {
@@ -8,7 +10,6 @@ struct GameState {
     shader: Shader,
     text: Text,
 
-    timer: f32,
     red: f32,
     green: f32,
     blue: f32,
@@ -20,7 +21,7 @@ impl GameState {
         let overlay = Texture::new(ctx, "./examples/resources/overlay.png")?;
 
         let shader = Shader::from_fragment_file(ctx, "./examples/resources/disco.frag")?;
-        shader.set_uniform(ctx, "u_overlay", overlay);
+        shader.set_uniform(ctx, "u_overlay", overlay)?;
 
         let text = Text::new(
             "",
@@ -32,7 +33,6 @@ impl GameState {
             shader,
             text,
 
-            timer: 0.0,
             red: 0.0,
             green: 0.0,
             blue: 0.0,
@@ -41,12 +41,20 @@ impl GameState {
 }
 
 impl State for GameState {
-    fn update(&mut self, _ctx: &mut Context) -> tetra::Result {
-        self.timer += 1.0;
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        // Try editing `disco.frag` while the example is running, and then
+        // press F5 to see your changes!
+        if input::is_key_pressed(ctx, Key::F5) {
+            if let Err(e) = self.shader.reload(ctx) {
+                eprintln!("Failed to reload shader: {}", e);
+            }
+        }
 
-        self.red = ((self.timer / 10.0).sin() + 1.0) / 2.0;
-        self.green = ((self.timer / 100.0).sin() + 1.0) / 2.0;
-        self.blue = ((self.timer / 1000.0).sin() + 1.0) / 2.0;
+        let elapsed = time::get_elapsed_time(ctx).as_secs_f32();
+
+        self.red = ((elapsed * 6.0).sin() + 1.0) / 2.0;
+        self.green = ((elapsed * 0.6).sin() + 1.0) / 2.0;
+        self.blue = ((elapsed * 0.06).sin() + 1.0) / 2.0;
 
         self.text.set_content(format!(
             "Red: {:.2}\nGreen: {:.2}\nBlue: {:.2}",
@@ -61,9 +69,9 @@ impl State for GameState {
 
         graphics::set_shader(ctx, &self.shader);
 
-        self.shader.set_uniform(ctx, "u_red", self.red);
-        self.shader.set_uniform(ctx, "u_green", self.green);
-        self.shader.set_uniform(ctx, "u_blue", self.blue);
+        self.shader.set_uniform(ctx, "u_red", self.red)?;
+        self.shader.set_uniform(ctx, "u_green", self.green)?;
+        self.shader.set_uniform(ctx, "u_blue", self.blue)?;
 
         self.texture.draw(
             ctx,
@@ -2,11 +2,13 @@
 // The scene stack implementation is inspired by Amethyst's state system
 // and the ggez-goodies scene stack.
 
+use std::time::Duration;
+
 use rand::{self, Rng};
 use tetra::audio::Sound;
 use tetra::graphics::scaling::{ScalingMode, ScreenScaler};
 use tetra::graphics::text::{Font, Text, VectorFontBuilder};
-use tetra::graphics::{self, Color, DrawParams, Texture};
+use tetra::graphics::{self, Color, DrawParams, Texture, TransitionStyle};
 use tetra::input::{self, Key};
 use tetra::math::Vec2;
 use tetra::window;
@@ -95,6 +97,11 @@ struct GameState {
     scenes: Vec<Box<dyn Scene>>,
     scaler: ScreenScaler,
     assets: Assets,
+
+    // Tetra's built-in transition type is used to fade between scenes. This is
+    // a different thing to the `Transition` enum above, which is why we refer to
+    // it by its full path.
+    screen_transition: graphics::Transition,
 }
 
 impl GameState {
@@ -111,22 +118,39 @@ impl GameState {
                 ScalingMode::ShowAllPixelPerfect,
             )?,
             assets,
+            screen_transition: graphics::Transition::new(ctx, TransitionStyle::CircleIris)?,
         })
     }
+
+    fn apply_transition(&mut self, transition: Transition) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(s) => {
+                self.scenes.push(s);
+                self.screen_transition.start(Duration::from_millis(500));
+            }
+            Transition::Pop => {
+                self.scenes.pop();
+                self.screen_transition.start(Duration::from_millis(500));
+            }
+        }
+    }
 }
 
 impl State for GameState {
     fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        self.screen_transition.update(ctx);
+
+        // The new scene shouldn't respond to input until it's fully visible.
+        if self.screen_transition.is_blocking_input() {
+            return Ok(());
+        }
+
         match self.scenes.last_mut() {
-            Some(active_scene) => match active_scene.update(ctx, &self.assets)? {
-                Transition::None => {}
-                Transition::Push(s) => {
-                    self.scenes.push(s);
-                }
-                Transition::Pop => {
-                    self.scenes.pop();
-                }
-            },
+            Some(active_scene) => {
+                let transition = active_scene.update(ctx, &self.assets)?;
+                self.apply_transition(transition);
+            }
             None => window::quit(ctx),
         }
 
@@ -137,18 +161,16 @@ impl State for GameState {
         graphics::set_canvas(ctx, self.scaler.canvas());
 
         match self.scenes.last_mut() {
-            Some(active_scene) => match active_scene.draw(ctx, &self.assets)? {
-                Transition::None => {}
-                Transition::Push(s) => {
-                    self.scenes.push(s);
-                }
-                Transition::Pop => {
-                    self.scenes.pop();
-                }
-            },
+            Some(active_scene) => {
+                let transition = active_scene.draw(ctx, &self.assets)?;
+                self.apply_transition(transition);
+            }
             None => window::quit(ctx),
         }
 
+        // This has to be drawn last, so that it appears over the top of the scene.
+        self.screen_transition.draw(ctx)?;
+
         graphics::reset_canvas(ctx);
         graphics::clear(ctx, Color::BLACK);
 
@@ -157,9 +179,13 @@ impl State for GameState {
         Ok(())
     }
 
-    fn event(&mut self, _: &mut Context, event: Event) -> tetra::Result {
-        if let Event::Resized { width, height } = event {
-            self.scaler.set_outer_size(width, height);
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        if let Event::WindowMetricsChanged {
+            logical_size: (width, height),
+            ..
+        } = event
+        {
+            self.scaler.set_outer_size(ctx, width, height)?;
         }
 
         Ok(())
@@ -492,15 +518,11 @@ impl Scene for GameScene {
         let next_move = self.move_queue.pop();
 
         match next_move {
-            Some(Move::Left) => {
-                if !self.collides(-1, 0) {
-                    self.block.x -= 1;
-                }
+            Some(Move::Left) if !self.collides(-1, 0) => {
+                self.block.x -= 1;
             }
-            Some(Move::Right) => {
-                if !self.collides(1, 0) {
-                    self.block.x += 1;
-                }
+            Some(Move::Right) if !self.collides(1, 0) => {
+                self.block.x += 1;
             }
             Some(Move::RotateCcw) => {
                 self.block.rotate_ccw();
@@ -570,7 +592,7 @@ impl Scene for GameScene {
 
                 self.block = Block::new();
             }
-            None => {}
+            _ => {}
         }
 
         Ok(Transition::None)
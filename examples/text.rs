@@ -1,4 +1,4 @@
-use tetra::graphics::text::{Font, Text};
+use tetra::graphics::text::{Font, Text, TextAlignment, TextOverflow};
 use tetra::graphics::{self, Color};
 use tetra::math::Vec2;
 use tetra::{Context, ContextBuilder, State};
@@ -8,6 +8,8 @@ const TEXT_OFFSET: Vec2<f32> = Vec2::new(16.0, 16.0);
 struct GameState {
     vector_text: Text,
     bitmap_text: Text,
+    outlined_text: Text,
+    truncated_text: Text,
 }
 
 impl GameState {
@@ -22,9 +24,30 @@ impl GameState {
             Font::bmfont(ctx, "./examples/resources/DejaVuSansMono.fnt")?,
         );
 
+        let mut outlined_text = Text::new(
+            "This text has an outline and a drop shadow.",
+            Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 24.0)?,
+        );
+
+        outlined_text.set_outline(2.0, Color::BLACK);
+        outlined_text.set_shadow(Vec2::new(3.0, 3.0), Color::rgba(0.0, 0.0, 0.0, 0.5));
+
+        let mut truncated_text = Text::wrapped(
+            "This text is centered within its max width, and it is cut off with an ellipsis \
+             when it runs out of vertical space, rather than spilling out of the box.",
+            Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 16.0)?,
+            320.0,
+        );
+
+        truncated_text.set_alignment(TextAlignment::Center);
+        truncated_text.set_max_height(Some(64.0));
+        truncated_text.set_overflow(TextOverflow::Ellipsis);
+
         Ok(GameState {
             vector_text,
             bitmap_text,
+            outlined_text,
+            truncated_text,
         })
     }
 }
@@ -36,6 +59,10 @@ impl State for GameState {
         self.vector_text.draw(ctx, TEXT_OFFSET);
         self.bitmap_text
             .draw(ctx, TEXT_OFFSET + Vec2::new(0.0, 128.0));
+        self.outlined_text
+            .draw(ctx, TEXT_OFFSET + Vec2::new(0.0, 256.0));
+        self.truncated_text
+            .draw(ctx, TEXT_OFFSET + Vec2::new(0.0, 384.0));
 
         Ok(())
     }
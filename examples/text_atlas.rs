@@ -0,0 +1,102 @@
+// This example demonstrates how sharing a `FontAtlas` between fonts allows text to be batched.
+//
+// Each `Font` normally has its own texture, so drawing text that alternates between two fonts
+// forces the renderer to switch textures (and therefore issue a new draw call) for every
+// piece of text. When both fonts are created in the same atlas, all of the text can be drawn
+// in one batch.
+//
+// Press SPACE to toggle between separate textures and a shared atlas.
+
+use tetra::graphics::text::{Font, FontAtlas, Text, VectorFontBuilder};
+use tetra::graphics::{self, Color};
+use tetra::input::{self, Key};
+use tetra::math::Vec2;
+use tetra::{Context, ContextBuilder, State};
+
+const TEXT_COUNT: usize = 50;
+
+struct GameState {
+    separate_texts: Vec<Text>,
+    shared_texts: Vec<Text>,
+    use_shared_atlas: bool,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let builder = VectorFontBuilder::new("./examples/resources/DejaVuSansMono.ttf")?;
+
+        let separate_fonts = [builder.with_size(ctx, 16.0)?, builder.with_size(ctx, 24.0)?];
+
+        let atlas = FontAtlas::new(ctx)?;
+        let shared_fonts = [
+            builder.with_size_in_atlas(16.0, &atlas),
+            builder.with_size_in_atlas(24.0, &atlas),
+        ];
+
+        Ok(GameState {
+            separate_texts: create_texts(&separate_fonts),
+            shared_texts: create_texts(&shared_fonts),
+            use_shared_atlas: false,
+        })
+    }
+}
+
+fn create_texts(fonts: &[Font; 2]) -> Vec<Text> {
+    (0..TEXT_COUNT)
+        .map(|i| Text::new(format!("Text {}", i), fonts[i % 2].clone()))
+        .collect()
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_pressed(ctx, Key::Space) {
+            self.use_shared_atlas = !self.use_shared_atlas;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
+
+        let texts = if self.use_shared_atlas {
+            &mut self.shared_texts
+        } else {
+            &mut self.separate_texts
+        };
+
+        for (i, text) in texts.iter_mut().enumerate() {
+            let column = (i % 5) as f32;
+            let row = (i / 5) as f32;
+
+            text.draw(ctx, Vec2::new(16.0 + column * 160.0, 16.0 + row * 40.0));
+        }
+
+        // Flushing here means the last batch is included in the stats:
+        graphics::flush(ctx);
+
+        let mode = if self.use_shared_atlas {
+            "shared atlas"
+        } else {
+            "separate textures"
+        };
+
+        tetra::window::set_title(
+            ctx,
+            format!(
+                "Shared Font Atlas ({}, {} draw calls)",
+                mode,
+                graphics::get_stats(ctx).draw_calls
+            ),
+        );
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Shared Font Atlas", 1280, 720)
+        .quit_on_escape(true)
+        .build()?
+        .run(GameState::new)
+}
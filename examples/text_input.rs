@@ -1,11 +1,17 @@
 use tetra::graphics::text::{Font, Text};
-use tetra::graphics::{self, Color};
+use tetra::graphics::{self, Color, DrawParams, Rectangle};
 use tetra::input::{self, Key, KeyModifier};
 use tetra::math::Vec2;
-use tetra::{Context, ContextBuilder, State};
+use tetra::{Context, ContextBuilder, Event, State};
+
+const TEXT_POSITION: Vec2<f32> = Vec2::new(16.0, 16.0);
 
 struct GameState {
     text: Text,
+
+    // If the player is using an IME, this will contain the text that they are
+    // in the middle of composing.
+    composition: Text,
 }
 
 impl GameState {
@@ -13,7 +19,8 @@ impl GameState {
         let font = Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 32.0)?;
 
         Ok(GameState {
-            text: Text::new("", font),
+            text: Text::new("", font.clone()),
+            composition: Text::new("", font),
         })
     }
 }
@@ -48,7 +55,28 @@ impl State for GameState {
     fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
         graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
 
-        self.text.draw(ctx, Vec2::new(16.0, 16.0));
+        self.text.draw(ctx, TEXT_POSITION);
+
+        let end = TEXT_POSITION + self.text.end_position(ctx);
+
+        self.composition.draw(
+            ctx,
+            DrawParams::new()
+                .position(end)
+                .color(Color::rgb(0.8, 0.8, 0.8)),
+        );
+
+        Ok(())
+    }
+
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        if let Event::TextEditing { text, .. } = event {
+            self.composition.set_content(text);
+
+            // Let the IME know where the text is, so it doesn't cover it up.
+            let end = TEXT_POSITION + self.text.end_position(ctx);
+            input::set_text_input_rect(ctx, Rectangle::new(end.x as i32, end.y as i32, 1, 32));
+        }
 
         Ok(())
     }
@@ -0,0 +1,63 @@
+use tetra::graphics::text::{Font, Text};
+use tetra::graphics::{self, Color};
+use tetra::input::{self, Key};
+use tetra::math::Vec2;
+use tetra::ui::TextInput;
+use tetra::{Context, ContextBuilder, Event, State};
+
+const INPUT_POSITION: Vec2<f32> = Vec2::new(16.0, 16.0);
+const OUTPUT_POSITION: Vec2<f32> = Vec2::new(16.0, 96.0);
+
+struct GameState {
+    input: TextInput,
+    output: Text,
+}
+
+impl GameState {
+    fn new(ctx: &mut Context) -> tetra::Result<GameState> {
+        let font = Font::vector(ctx, "./examples/resources/DejaVuSansMono.ttf", 32.0)?;
+
+        let mut input = TextInput::new(ctx, font.clone())?;
+        input.set_max_length(Some(32));
+
+        Ok(GameState {
+            input,
+            output: Text::new("Type something, then press Enter!", font),
+        })
+    }
+}
+
+impl State for GameState {
+    fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+        if input::is_key_pressed(ctx, Key::Enter) {
+            self.output
+                .set_content(format!("You said: {}", self.input.value()));
+            self.input.set_value("");
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
+        graphics::clear(ctx, Color::rgb(0.392, 0.584, 0.929));
+
+        self.input.draw(ctx, INPUT_POSITION);
+        self.output.draw(ctx, OUTPUT_POSITION);
+
+        Ok(())
+    }
+
+    fn event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result {
+        self.input.handle_event(ctx, &event)?;
+
+        Ok(())
+    }
+}
+
+fn main() -> tetra::Result {
+    ContextBuilder::new("Text Input Widget", 640, 480)
+        .quit_on_escape(true)
+        .key_repeat(true)
+        .build()?
+        .run(GameState::new)
+}
@@ -1,13 +1,18 @@
 //! Functions and types relating to audio playback.
 
-use std::io::Cursor;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
 use std::path::Path;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
+use rodio::cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use rodio::cpal::SampleFormat;
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
 use rodio::source::Buffered;
-use rodio::{Decoder, OutputStream, OutputStreamHandle, PlayError, Sample, Source};
+use rodio::{cpal, Decoder, Sample, Source};
 
 use crate::error::{Result, TetraError};
 use crate::fs;
@@ -33,11 +38,18 @@ use crate::Context;
 /// | MP3 | `audio_mp3` | Yes |
 /// | FLAC | `audio_flac` | No |
 ///
+/// Sounds can also be generated at runtime from raw samples, via
+/// [`from_samples`](Sound::from_samples) and [`from_samples_i16`](Sound::from_samples_i16).
+///
 /// # Performance
 ///
 /// When you create an instance of `Sound`, the audio data is loaded into memory. It is not
 /// decoded until playback begins.
 ///
+/// For long sounds (such as music), keeping the whole file in memory and decoding it all
+/// up front can be expensive. Sounds created via [`Sound::stream`] are instead decoded on
+/// the fly, reading from the file as playback progresses.
+///
 /// You can clone a sound cheaply, as it is [reference-counted](https://doc.rust-lang.org/std/rc/struct.Rc.html)
 /// internally. The underlying data will be shared by all of the clones (and, by extension,
 /// all of the `SoundInstance`s created from them).
@@ -48,7 +60,28 @@ use crate::Context;
 /// example demonstrates how to play several different kinds of sound.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Sound {
-    pub(crate) data: Arc<[u8]>,
+    pub(crate) data: SoundData,
+}
+
+/// Where the audio data for a [`Sound`] comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum SoundData {
+    /// Encoded data that is stored in memory, and buffered as it is decoded.
+    Buffered(Arc<[u8]>),
+
+    /// A file that is decoded on the fly during playback.
+    Streamed(Arc<Path>),
+
+    /// Raw samples, which can be played back without decoding.
+    Samples(Arc<SampleData>),
+}
+
+/// Interleaved 16-bit PCM data.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct SampleData {
+    sample_rate: u32,
+    channels: u16,
+    samples: Box<[i16]>,
 }
 
 impl Sound {
@@ -65,7 +98,47 @@ impl Sound {
         P: AsRef<Path>,
     {
         Ok(Sound {
-            data: fs::read(path)?.into(),
+            data: SoundData::Buffered(fs::read(path)?.into()),
+        })
+    }
+
+    /// Creates a new sound that will be streamed from the given file.
+    ///
+    /// Rather than loading the whole file into memory, each instance of the sound will
+    /// open the file and decode it on the fly during playback. This uses a lot less
+    /// memory than [`Sound::new`] for long sounds (such as music), and avoids the cost of
+    /// decoding the whole sound when it is first played. However, it is not a good fit for
+    /// short sounds that are played frequently, as each instance has to read the file
+    /// separately.
+    ///
+    /// Streamed sounds support all of the same controls as other sounds, with a few
+    /// differences:
+    ///
+    /// * Looping back to the start of a [loop region](SoundInstance::set_loop_region), or
+    ///   resuming after the audio device changes, requires seeking in the file. Depending
+    ///   on the file format, this may be less precise than it is for other sounds, or it
+    ///   may require decoding the file up to that point.
+    /// * Once all of the [`SoundInstance`] handles for a streamed sound have been dropped,
+    ///   playback will stop and the file will be closed.
+    ///
+    /// Note that the data is not decoded until playback begins, so this function will not
+    /// validate that the data being read is formatted correctly.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the file could not be opened.
+    pub fn stream<P>(path: P) -> Result<Sound>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+
+        // The file isn't actually read until playback starts, but it's better to report a
+        // missing file as early as possible.
+        fs::open(path)?;
+
+        Ok(Sound {
+            data: SoundData::Streamed(path.into()),
         })
     }
 
@@ -78,7 +151,74 @@ impl Sound {
     /// Note that the data is not decoded until playback begins, so this function will not
     /// validate that the data being read is formatted correctly.
     pub fn from_encoded(data: &[u8]) -> Sound {
-        Sound { data: data.into() }
+        Sound {
+            data: SoundData::Buffered(data.into()),
+        }
+    }
+
+    /// Creates a new sound from raw samples.
+    ///
+    /// The samples should be interleaved (i.e. for stereo audio, each sample for the left
+    /// channel should be followed by the corresponding sample for the right channel), and
+    /// should be in the range of `-1.0` to `1.0`. Values outside of this range will be
+    /// clamped.
+    ///
+    /// This is useful for generating sounds at runtime - for example, procedural sound
+    /// effects. The samples are converted to 16-bit and stored in memory, so the sound
+    /// can be played back without any decoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample rate or the number of channels is zero.
+    pub fn from_samples(sample_rate: u32, channels: u16, samples: &[f32]) -> Sound {
+        let samples = samples
+            .iter()
+            .map(|s| (s.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16)
+            .collect();
+
+        Sound::from_sample_data(sample_rate, channels, samples)
+    }
+
+    /// Creates a new sound from raw 16-bit samples.
+    ///
+    /// The samples should be interleaved (i.e. for stereo audio, each sample for the left
+    /// channel should be followed by the corresponding sample for the right channel).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the sample rate or the number of channels is zero.
+    pub fn from_samples_i16(sample_rate: u32, channels: u16, samples: &[i16]) -> Sound {
+        Sound::from_sample_data(sample_rate, channels, samples.into())
+    }
+
+    fn from_sample_data(sample_rate: u32, channels: u16, samples: Box<[i16]>) -> Sound {
+        assert!(sample_rate > 0, "sample rate must be greater than zero");
+        assert!(channels > 0, "channel count must be greater than zero");
+
+        Sound {
+            data: SoundData::Samples(Arc::new(SampleData {
+                sample_rate,
+                channels,
+                samples,
+            })),
+        }
+    }
+
+    /// Returns the size of the sound's encoded data, in bytes.
+    ///
+    /// This data is shared between all clones of the sound, and all of the instances that
+    /// are playing it. It does not include any memory used while decoding the sound
+    /// during playback.
+    ///
+    /// For [streamed](Sound::stream) sounds, this will always be zero, as the data is
+    /// read from the file during playback. For sounds created from
+    /// [raw samples](Sound::from_samples), this is the size of the 16-bit sample data.
+    pub fn data_size(&self) -> usize {
+        match &self.data {
+            SoundData::Buffered(data) => data.len(),
+            SoundData::Streamed(_) => 0,
+            SoundData::Samples(data) => std::mem::size_of_val(&*data.samples),
+        }
     }
 
     /// Plays the sound.
@@ -86,10 +226,13 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn play(&self, ctx: &Context) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), true, false, 1.0, 1.0)
+            .play_sound(self.data.clone(), AudioControls::new(true, false, 1.0, 1.0))
             .map(|controls| SoundInstance { controls })
     }
 
@@ -98,10 +241,13 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn repeat(&self, ctx: &Context) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), true, true, 1.0, 1.0)
+            .play_sound(self.data.clone(), AudioControls::new(true, true, 1.0, 1.0))
             .map(|controls| SoundInstance { controls })
     }
 
@@ -110,10 +256,16 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn spawn(&self, ctx: &Context) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), false, false, 1.0, 1.0)
+            .play_sound(
+                self.data.clone(),
+                AudioControls::new(false, false, 1.0, 1.0),
+            )
             .map(|controls| SoundInstance { controls })
     }
 
@@ -122,10 +274,16 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn play_with(&self, ctx: &Context, volume: f32, speed: f32) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), true, false, volume, speed)
+            .play_sound(
+                self.data.clone(),
+                AudioControls::new(true, false, volume, speed),
+            )
             .map(|controls| SoundInstance { controls })
     }
 
@@ -134,10 +292,16 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn repeat_with(&self, ctx: &Context, volume: f32, speed: f32) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), true, true, volume, speed)
+            .play_sound(
+                self.data.clone(),
+                AudioControls::new(true, true, volume, speed),
+            )
             .map(|controls| SoundInstance { controls })
     }
 
@@ -146,10 +310,45 @@ impl Sound {
     /// # Errors
     ///
     /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
     /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
     pub fn spawn_with(&self, ctx: &Context, volume: f32, speed: f32) -> Result<SoundInstance> {
         ctx.audio
-            .play_sound(Arc::clone(&self.data), false, false, volume, speed)
+            .play_sound(
+                self.data.clone(),
+                AudioControls::new(false, false, volume, speed),
+            )
+            .map(|controls| SoundInstance { controls })
+    }
+
+    /// Plays the sound repeatedly, looping over the given region.
+    ///
+    /// The sound will play from the beginning, and then jump back to `start` each time
+    /// it reaches `end` (or the end of the sound, if `end` is `None`). This is useful for
+    /// music that has an intro section which should only play once.
+    ///
+    /// See [`SoundInstance::set_loop_region`] for more details on how the region is handled.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
+    /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
+    pub fn play_with_loop_region(
+        &self,
+        ctx: &Context,
+        start: Duration,
+        end: Option<Duration>,
+    ) -> Result<SoundInstance> {
+        let controls = AudioControls::new(true, true, 1.0, 1.0);
+        controls.set_loop_region(start, end);
+
+        ctx.audio
+            .play_sound(self.data.clone(), controls)
             .map(|controls| SoundInstance { controls })
     }
 }
@@ -165,7 +364,20 @@ impl Sound {
 /// Note that dropping a `SoundInstance` does not stop playback, and the underlying
 /// data will not be freed until playback has finished. This means that dropping a
 /// [repeating](SoundInstance::set_repeating) `SoundInstance` without stopping it
-/// first will cause the sound to loop forever.
+/// first will cause the sound to loop forever. The exception to this is
+/// [streamed](Sound::stream) sounds, which stop once all of their handles have been
+/// dropped.
+///
+/// # Audio Device Changes
+///
+/// If the system's default audio device changes while the game is running (e.g. because
+/// headphones were plugged in), Tetra will switch playback over to the new device, and
+/// an [`Event::AudioDeviceChanged`](crate::Event::AudioDeviceChanged) will be fired.
+///
+/// Any instances that still have a `SoundInstance` handle will resume from roughly the
+/// point where they were interrupted (to within the polling interval described above),
+/// keeping their current state. Instances with no remaining handles (i.e. sounds that
+/// were played in a 'fire and forget' manner) will be stopped.
 #[derive(Debug, Clone)]
 pub struct SoundInstance {
     controls: Arc<AudioControls>,
@@ -190,6 +402,9 @@ impl SoundInstance {
     }
 
     /// Returns the current state of playback.
+    ///
+    /// This can be used to tell whether a sound was [stopped manually](SoundState::Stopped)
+    /// or [reached the end of its data](SoundState::Finished).
     pub fn state(&self) -> SoundState {
         self.controls.state()
     }
@@ -199,16 +414,92 @@ impl SoundInstance {
     /// In most cases, using the [`play`](SoundInstance::play), [`stop`](SoundInstance::stop) and
     /// [`pause`](SoundInstance::pause) methods is easier than explicitly setting a state, but
     /// this may be useful when, for example, defining transitions from one state to another.
+    ///
+    /// Setting the state to [`Finished`](SoundState::Finished) will stop the sound in the same
+    /// way as [`Stopped`](SoundState::Stopped), but will not set the
+    /// [finished flag](SoundInstance::set_finished_flag).
     pub fn set_state(&self, state: SoundState) {
         self.controls.set_state(state)
     }
 
+    /// Sets a flag that will be set to `true` when the sound reaches the end of its
+    /// audio data, or clears the flag if `None` is passed.
+    ///
+    /// This allows game logic to be triggered when a sound ends, without having to poll
+    /// the [state](SoundInstance::state) of the instance. The flag is set from the audio
+    /// thread, once each time that playback runs out - it is never set while the sound is
+    /// [repeating](SoundInstance::set_repeating), or when the sound is stopped manually.
+    /// Tetra will never reset the flag to `false`, so you can do this yourself (e.g. via
+    /// [`AtomicBool::swap`](std::sync::atomic::AtomicBool::swap)) once you have handled it.
+    ///
+    /// The flag will still be set if all of the `SoundInstance` handles are dropped
+    /// before the sound ends, unless the sound is [streamed](Sound::stream) (as detached
+    /// streamed sounds are stopped straight away).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::sync::Arc;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use tetra::Context;
+    /// # use tetra::audio::Sound;
+    /// # fn run(ctx: &mut Context) -> tetra::Result {
+    /// let jingle = Sound::new("./examples/resources/powerup.ogg")?;
+    /// let finished = Arc::new(AtomicBool::new(false));
+    ///
+    /// let instance = jingle.play(ctx)?;
+    /// instance.set_finished_flag(Some(Arc::clone(&finished)));
+    ///
+    /// // Later, in your update method:
+    /// if finished.swap(false, Ordering::SeqCst) {
+    ///     // The jingle has ended, so input can be unlocked.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_finished_flag(&self, flag: Option<Arc<AtomicBool>>) {
+        *self
+            .controls
+            .finished_flag
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = flag;
+    }
+
     /// Sets the volume of the sound.
     ///
     /// The parameter is used as a multiplier - for example, `1.0` would result in the
     /// sound being played back at its original volume.
+    ///
+    /// If the sound is currently [fading](SoundInstance::fade_to), the fade will be
+    /// cancelled.
     pub fn set_volume(&self, volume: f32) {
-        self.controls.set_volume(volume);
+        self.controls.fade(volume, Duration::ZERO, false);
+    }
+
+    /// Gradually changes the volume of the sound, over the given duration.
+    ///
+    /// The fade is processed on the audio thread, so it will progress smoothly even if the
+    /// game's update loop stalls. It only progresses while the sound is playing - if the
+    /// sound is paused (or has not been started yet), the fade will begin when playback
+    /// resumes. This means that you can fade a sound in by setting its volume to `0.0`
+    /// and then calling this method before playing it.
+    ///
+    /// Starting a new fade (or calling [`set_volume`](SoundInstance::set_volume)) will
+    /// cancel any fade that is already in progress, with the new fade starting from the
+    /// current volume.
+    pub fn fade_to(&self, volume: f32, duration: Duration) {
+        self.controls.fade(volume, duration, false);
+    }
+
+    /// Gradually fades the sound out over the given duration, and then stops it.
+    ///
+    /// Once the sound has stopped, its volume will be reset to what it was when the fade
+    /// started, so that it can be played again without needing to restore the volume
+    /// manually.
+    ///
+    /// This follows the same rules as [`fade_to`](SoundInstance::fade_to).
+    pub fn fade_out(&self, duration: Duration) {
+        self.controls.fade(0.0, duration, true);
     }
 
     /// Sets the speed (and by extension, the pitch) of the sound.
@@ -228,9 +519,213 @@ impl SoundInstance {
     pub fn toggle_repeating(&self) {
         self.controls.set_repeating(!self.controls.repeating());
     }
+
+    /// Sets the region of the sound that will be looped over while it is
+    /// [repeating](SoundInstance::set_repeating).
+    ///
+    /// When playback reaches `end` (or the end of the sound, if `end` is `None`), it will
+    /// jump back to `start`, rather than to the beginning of the sound. Stopping the
+    /// sound will still rewind it all the way back to the beginning. The region has no
+    /// effect if the sound is not repeating.
+    ///
+    /// If the sound is already playing, the new region will take effect the next time
+    /// that playback wraps around (or straight away, if playback is already past the
+    /// new `end`).
+    ///
+    /// An `end` that is past the end of the sound will be clamped to the end of the sound,
+    /// and an `end` that is not after `start` will be ignored. If `start` is past the end
+    /// of the sound, the sound will loop from the beginning instead.
+    pub fn set_loop_region(&self, start: Duration, end: Option<Duration>) {
+        self.controls.set_loop_region(start, end);
+    }
+}
+
+/// What a [`SoundPool`] should do when a sound is played while the maximum number of
+/// instances are already playing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SoundPoolPolicy {
+    /// The oldest instance will be restarted from the beginning, with the new settings.
+    StealOldest,
+
+    /// The sound will not be played.
+    Refuse,
+}
+
+/// Limits how many instances of a [`Sound`] can play at the same time.
+///
+/// Playing the same sound lots of times in a short space of time (e.g. for gunshots or
+/// coin pickups) can cause the mix to clip, and wastes CPU time mixing sounds that can't
+/// really be heard. A `SoundPool` keeps track of the instances that it has started, and
+/// once the limit is reached, it will either restart the oldest instance or refuse to
+/// play the sound, depending on its [`SoundPoolPolicy`].
+///
+/// An instance stops counting towards the limit once it has [finished](SoundState::Finished)
+/// or been [stopped](SoundState::Stopped) manually.
+/// Paused instances still count towards the limit.
+///
+/// The [`SoundInstance`] handles returned by the pool can be used as normal. If an
+/// instance is restarted because of the [`StealOldest`](SoundPoolPolicy::StealOldest)
+/// policy, any handles to it will stay valid, and will control the restarted sound.
+#[derive(Debug, Clone)]
+pub struct SoundPool {
+    sound: Sound,
+    instances: Vec<SoundInstance>,
+    max_instances: usize,
+    policy: SoundPoolPolicy,
+}
+
+/// How a [`SoundPool`] should handle a request to play its sound.
+#[derive(Debug)]
+enum PoolSlot {
+    New,
+    Stolen(SoundInstance),
+    Refused,
+}
+
+impl SoundPool {
+    /// Creates a new pool, which will allow up to `max_instances` instances of the sound
+    /// to play at once.
+    pub fn new(sound: Sound, max_instances: usize, policy: SoundPoolPolicy) -> SoundPool {
+        SoundPool {
+            sound,
+            instances: Vec::with_capacity(max_instances),
+            max_instances,
+            policy,
+        }
+    }
+
+    /// Plays the sound, if the pool's limit allows it.
+    ///
+    /// Returns `None` if the limit has been reached and the policy is
+    /// [`Refuse`](SoundPoolPolicy::Refuse), or if the maximum number of instances is zero.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
+    /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
+    pub fn play(&mut self, ctx: &Context) -> Result<Option<SoundInstance>> {
+        self.play_with(ctx, 1.0, 1.0)
+    }
+
+    /// Plays the sound with the provided settings, if the pool's limit allows it.
+    ///
+    /// Returns `None` if the limit has been reached and the policy is
+    /// [`Refuse`](SoundPoolPolicy::Refuse), or if the maximum number of instances is zero.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::NoAudioDevice`] will be returned if no audio device is active.
+    /// * [`TetraError::AudioDeviceLost`] will be returned if the audio device has stopped working.
+    /// * [`TetraError::InvalidSound`] will be returned if the sound data could not be decoded.
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the sound is
+    ///   [streamed](Sound::stream) and its file could not be opened.
+    pub fn play_with(
+        &mut self,
+        ctx: &Context,
+        volume: f32,
+        speed: f32,
+    ) -> Result<Option<SoundInstance>> {
+        match self.claim_slot() {
+            PoolSlot::New => {
+                let instance = self.sound.play_with(ctx, volume, speed)?;
+                self.instances.push(instance.clone());
+
+                Ok(Some(instance))
+            }
+
+            PoolSlot::Stolen(instance) => {
+                // Stopping and then playing rewinds the sound back to the beginning.
+                instance.stop();
+                instance.set_volume(volume);
+                instance.set_speed(speed);
+                instance.play();
+
+                self.instances.push(instance.clone());
+
+                Ok(Some(instance))
+            }
+
+            PoolSlot::Refused => Ok(None),
+        }
+    }
+
+    /// Works out whether a new instance can be played, freeing up space if the policy
+    /// allows it.
+    fn claim_slot(&mut self) -> PoolSlot {
+        // Dropping the handles to stopped instances also allows the audio thread to free them.
+        self.instances
+            .retain(|instance| !instance.state().is_stopped());
+
+        if self.instances.len() < self.max_instances {
+            return PoolSlot::New;
+        }
+
+        match self.policy {
+            SoundPoolPolicy::StealOldest if self.max_instances > 0 => {
+                // If the limit was lowered, there may be more instances than are allowed.
+                let excess = self.instances.len() - self.max_instances;
+
+                for instance in self.instances.drain(..excess) {
+                    instance.stop();
+                }
+
+                PoolSlot::Stolen(self.instances.remove(0))
+            }
+
+            _ => PoolSlot::Refused,
+        }
+    }
+
+    /// Stops all of the instances that are playing from the pool.
+    pub fn stop_all(&mut self) {
+        for instance in self.instances.drain(..) {
+            instance.stop();
+        }
+    }
+
+    /// Returns the number of instances from the pool that have not stopped yet.
+    pub fn active_count(&self) -> usize {
+        self.instances
+            .iter()
+            .filter(|instance| !instance.state().is_stopped())
+            .count()
+    }
+
+    /// Returns a reference to the sound that the pool plays.
+    pub fn sound(&self) -> &Sound {
+        &self.sound
+    }
+
+    /// Returns the maximum number of instances that can play at once.
+    pub fn max_instances(&self) -> usize {
+        self.max_instances
+    }
+
+    /// Sets the maximum number of instances that can play at once.
+    ///
+    /// Any instances that are already playing will be allowed to continue. If the policy
+    /// is [`StealOldest`](SoundPoolPolicy::StealOldest), the oldest instances will be
+    /// stopped to get back under the limit the next time that the sound is played.
+    pub fn set_max_instances(&mut self, max_instances: usize) {
+        self.max_instances = max_instances;
+    }
+
+    /// Returns what the pool does when the limit is reached.
+    pub fn policy(&self) -> SoundPoolPolicy {
+        self.policy
+    }
+
+    /// Sets what the pool does when the limit is reached.
+    pub fn set_policy(&mut self, policy: SoundPoolPolicy) {
+        self.policy = policy;
+    }
 }
 
 /// The states that playback of a [`SoundInstance`] can be in.
+#[non_exhaustive]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SoundState {
     /// The sound is currently playing.
@@ -247,47 +742,243 @@ pub enum SoundState {
     /// it will be in this state initially.
     Paused,
 
-    /// The sound has stopped, either manually or as a result of it reaching
-    /// the end of the audio data. If playback is resumed, it will start
-    /// over from the beginning of the sound.
+    /// The sound was stopped manually (including via [`SoundInstance::fade_out`]). If
+    /// playback is resumed, it will start over from the beginning of the sound.
+    Stopped,
+
+    /// The sound stopped as a result of it reaching the end of the audio data. If
+    /// playback is resumed, it will start over from the beginning of the sound.
     ///
     /// This state will never occur while a [`SoundInstance`] is set
     /// to be [`repeating`](SoundInstance::set_repeating).
-    Stopped,
+    Finished,
+}
+
+impl SoundState {
+    fn is_stopped(self) -> bool {
+        matches!(self, SoundState::Stopped | SoundState::Finished)
+    }
 }
 
 /// Sets the master volume for the game.
 ///
 /// The parameter is used as a multiplier - for example, `1.0` would result in
 /// sounds being played back at their original volume.
+///
+/// If the master volume is currently [fading](fade_master_volume), the fade will be
+/// cancelled.
 pub fn set_master_volume(ctx: &mut Context, volume: f32) {
     ctx.audio.set_master_volume(volume);
 }
 
+/// Gradually changes the master volume for the game, over the given duration.
+///
+/// Unlike fades on a [`SoundInstance`], the master volume fades based on time rather than
+/// on playback, so it will progress even if no sounds are playing. Starting a new fade
+/// will cancel any fade that is already in progress, with the new fade starting from the
+/// current volume.
+pub fn fade_master_volume(ctx: &mut Context, volume: f32, duration: Duration) {
+    ctx.audio.fade_master_volume(volume, duration);
+}
+
 /// Gets the master volume for the game.
+///
+/// If the master volume is currently fading, this will return its current level,
+/// rather than the level that it is fading towards.
 pub fn get_master_volume(ctx: &mut Context) -> f32 {
     ctx.audio.master_volume()
 }
 
+/// Returns whether an audio device is currently available for playback.
+///
+/// This will return `false` if no audio device could be found when the game started,
+/// or if the device stopped working while the game was running (e.g. because it was
+/// unplugged). Tetra will automatically try to switch to a new device when the system's
+/// audio devices change, but you can also do so manually via [`reset_device`].
+pub fn is_device_available(ctx: &Context) -> bool {
+    ctx.audio.is_available()
+}
+
+/// Reopens the audio output on the system's default device.
+///
+/// Any sounds that are currently playing will be moved over to the new device,
+/// picking up where they left off.
+///
+/// # Errors
+///
+/// * [`TetraError::NoAudioDevice`] will be returned if the device could not be opened.
+pub fn reset_device(ctx: &mut Context) -> Result {
+    ctx.audio.reset()
+}
+
+/// The master volume, which can be faded over time.
+///
+/// Fades are stored as a start time and a duration, so that each source can calculate
+/// the current volume for itself, without any one of them being responsible for moving
+/// the fade forward.
+#[derive(Debug)]
+struct MasterVolume {
+    epoch: Instant,
+    target: AtomicU32,
+    fade_from: AtomicU32,
+    fade_start: AtomicU64,
+    fade_duration: AtomicU64,
+}
+
+impl MasterVolume {
+    fn new(volume: f32) -> MasterVolume {
+        MasterVolume {
+            epoch: Instant::now(),
+            target: AtomicU32::new(volume.to_bits()),
+            fade_from: AtomicU32::new(volume.to_bits()),
+            fade_start: AtomicU64::new(0),
+            fade_duration: AtomicU64::new(0),
+        }
+    }
+
+    fn get(&self) -> f32 {
+        self.get_at(Instant::now())
+    }
+
+    fn get_at(&self, now: Instant) -> f32 {
+        let target = f32::from_bits(self.target.load(Ordering::SeqCst));
+        let duration = self.fade_duration.load(Ordering::SeqCst);
+
+        if duration == 0 {
+            return target;
+        }
+
+        let from = f32::from_bits(self.fade_from.load(Ordering::SeqCst));
+        let elapsed = self.nanos_since_epoch(now) - self.fade_start.load(Ordering::SeqCst) as i128;
+
+        if elapsed >= duration as i128 {
+            target
+        } else {
+            let t = elapsed.max(0) as f32 / duration as f32;
+            from + (target - from) * t
+        }
+    }
+
+    fn set(&self, volume: f32) {
+        self.fade_duration.store(0, Ordering::SeqCst);
+        self.target.store(volume.to_bits(), Ordering::SeqCst);
+    }
+
+    fn fade(&self, volume: f32, duration: Duration, now: Instant) {
+        let from = self.get_at(now);
+
+        // The duration is cleared first, so that a source reading the volume half way
+        // through will see the old target rather than a mix of the old and new fades.
+        self.fade_duration.store(0, Ordering::SeqCst);
+        self.fade_from.store(from.to_bits(), Ordering::SeqCst);
+        self.fade_start
+            .store(self.nanos_since_epoch(now) as u64, Ordering::SeqCst);
+        self.target.store(volume.to_bits(), Ordering::SeqCst);
+        self.fade_duration
+            .store(duration_to_nanos(duration), Ordering::SeqCst);
+    }
+
+    fn nanos_since_epoch(&self, now: Instant) -> i128 {
+        now.saturating_duration_since(self.epoch).as_nanos() as i128
+    }
+}
+
 #[derive(Debug)]
 struct AudioControls {
     playing: AtomicBool,
     repeating: AtomicBool,
     rewind: AtomicBool,
+    finished: AtomicBool,
+    finished_flag: Mutex<Option<Arc<AtomicBool>>>,
     volume: AtomicU32,
     speed: AtomicU32,
+    position: AtomicUsize,
+    loop_start: AtomicU64,
+    loop_end: AtomicU64,
+    fade: FadeControls,
+}
+
+/// A request for the source to fade to a new volume.
+///
+/// The source picks up the request the next time it polls the controls, by swapping
+/// `pending` back to `false`. `active` stays set until the fade completes, so that the
+/// fade can be restarted if the source is rebuilt for a new audio device.
+#[derive(Debug, Default)]
+struct FadeControls {
+    pending: AtomicBool,
+    active: AtomicBool,
+    target: AtomicU32,
+    duration: AtomicU64,
+    stop: AtomicBool,
+    restore: AtomicU32,
 }
 
 impl AudioControls {
-    fn set_volume(&self, volume: f32) {
-        self.volume.store(volume.to_bits(), Ordering::SeqCst);
+    fn new(playing: bool, repeating: bool, volume: f32, speed: f32) -> AudioControls {
+        AudioControls {
+            playing: AtomicBool::new(playing),
+            repeating: AtomicBool::new(repeating),
+            rewind: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            finished_flag: Mutex::new(None),
+            volume: AtomicU32::new(volume.to_bits()),
+            speed: AtomicU32::new(speed.to_bits()),
+            position: AtomicUsize::new(0),
+            loop_start: AtomicU64::new(0),
+            loop_end: AtomicU64::new(u64::MAX),
+            fade: FadeControls::default(),
+        }
+    }
+
+    fn set_loop_region(&self, start: Duration, end: Option<Duration>) {
+        self.loop_start
+            .store(duration_to_nanos(start), Ordering::SeqCst);
+        self.loop_end
+            .store(end.map_or(u64::MAX, duration_to_nanos), Ordering::SeqCst);
+    }
+
+    fn fade(&self, volume: f32, duration: Duration, stop: bool) {
+        let fade = &self.fade;
+        let current = self.volume.load(Ordering::SeqCst);
+
+        if duration.is_zero() {
+            // An instant fade is applied straight away, so that sources which are not
+            // currently playing (or that get rebuilt before seeing the request) still
+            // end up in the right state. A request is still sent to the source, to
+            // cancel any fade that it is currently running.
+            let volume = if stop { current } else { volume.to_bits() };
+
+            fade.target.store(volume, Ordering::SeqCst);
+            fade.duration.store(0, Ordering::SeqCst);
+            fade.stop.store(false, Ordering::SeqCst);
+            fade.active.store(false, Ordering::SeqCst);
+
+            self.volume.store(volume, Ordering::SeqCst);
+
+            if stop {
+                self.set_state(SoundState::Stopped);
+            }
+        } else {
+            fade.target.store(volume.to_bits(), Ordering::SeqCst);
+            fade.duration
+                .store(duration_to_nanos(duration), Ordering::SeqCst);
+            fade.stop.store(stop, Ordering::SeqCst);
+            fade.restore.store(current, Ordering::SeqCst);
+            fade.active.store(true, Ordering::SeqCst);
+        }
+
+        fade.pending.store(true, Ordering::SeqCst);
     }
 
     fn state(&self) -> SoundState {
         if self.playing.load(Ordering::SeqCst) {
             SoundState::Playing
         } else if self.rewind.load(Ordering::SeqCst) {
-            SoundState::Stopped
+            if self.finished.load(Ordering::SeqCst) {
+                SoundState::Finished
+            } else {
+                SoundState::Stopped
+            }
         } else {
             SoundState::Paused
         }
@@ -296,18 +987,30 @@ impl AudioControls {
     fn set_state(&self, state: SoundState) {
         match state {
             SoundState::Playing => {
+                self.finished.store(false, Ordering::SeqCst);
                 self.playing.store(true, Ordering::SeqCst);
             }
             SoundState::Paused => {
                 self.playing.store(false, Ordering::SeqCst);
             }
-            SoundState::Stopped => {
+            SoundState::Stopped | SoundState::Finished => {
+                self.finished
+                    .store(state == SoundState::Finished, Ordering::SeqCst);
                 self.playing.store(false, Ordering::SeqCst);
                 self.rewind.store(true, Ordering::SeqCst);
             }
         }
     }
 
+    /// Called from the audio thread when playback reaches the end of the data.
+    fn finish(&self) {
+        self.set_state(SoundState::Finished);
+
+        if let Some(flag) = &*self.finished_flag.lock().unwrap_or_else(|e| e.into_inner()) {
+            flag.store(true, Ordering::SeqCst);
+        }
+    }
+
     fn set_speed(&self, speed: f32) {
         self.speed.store(speed.to_bits(), Ordering::SeqCst);
     }
@@ -321,101 +1024,538 @@ impl AudioControls {
     }
 }
 
-struct AudioStream {
-    _stream: OutputStream,
-    handle: OutputStreamHandle,
+fn duration_to_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
 }
 
-pub(crate) struct AudioDevice {
-    stream: Option<AudioStream>,
-    master_volume: Arc<AtomicU32>,
+/// An output stream, which mixes together all of the sounds that are playing.
+///
+/// This is equivalent to rodio's `OutputStream`, but it also keeps track of any errors
+/// that occur while the stream is running (e.g. because the device was unplugged),
+/// rather than just logging them.
+struct AudioStream {
+    // This is only `None` when the stream isn't backed by a real device (i.e. in tests).
+    _stream: Option<cpal::Stream>,
+    mixer: Arc<DynamicMixerController<f32>>,
+    error: Arc<Mutex<Option<String>>>,
 }
 
-impl AudioDevice {
-    pub(crate) fn new() -> AudioDevice {
-        let stream_and_handle = OutputStream::try_default();
+impl AudioStream {
+    fn try_default() -> std::result::Result<AudioStream, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| String::from("no output device is available"))?;
+
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+
+        let (mixer, source) =
+            dynamic_mixer::mixer::<f32>(config.channels(), config.sample_rate().0);
+
+        let error = Arc::new(Mutex::new(None));
+
+        let stream_config = config.config();
+
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, source, &error),
+            SampleFormat::F64 => build_stream::<f64>(&device, &stream_config, source, &error),
+            SampleFormat::I8 => build_stream::<i8>(&device, &stream_config, source, &error),
+            SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, source, &error),
+            SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, source, &error),
+            SampleFormat::I64 => build_stream::<i64>(&device, &stream_config, source, &error),
+            SampleFormat::U8 => build_stream::<u8>(&device, &stream_config, source, &error),
+            SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, source, &error),
+            SampleFormat::U32 => build_stream::<u32>(&device, &stream_config, source, &error),
+            SampleFormat::U64 => build_stream::<u64>(&device, &stream_config, source, &error),
+            format => return Err(format!("unsupported sample format: {:?}", format)),
+        }
+        .map_err(|e| e.to_string())?;
 
-        let stream = match stream_and_handle {
-            Ok((_stream, handle)) => Some(AudioStream { _stream, handle }),
-            Err(_) => None,
-        };
+        stream.play().map_err(|e| e.to_string())?;
 
-        AudioDevice {
-            stream,
-            master_volume: Arc::new(AtomicU32::new(1.0f32.to_bits())),
-        }
+        Ok(AudioStream {
+            _stream: Some(stream),
+            mixer,
+            error,
+        })
     }
 
-    fn master_volume(&self) -> f32 {
-        f32::from_bits(self.master_volume.load(Ordering::SeqCst))
+    /// Returns the error that stopped the stream, if there was one.
+    fn error(&self) -> Option<String> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
-    fn set_master_volume(&self, volume: f32) {
-        self.master_volume.store(volume.to_bits(), Ordering::SeqCst);
+    fn play<S>(&self, source: S)
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        self.mixer.add(source);
     }
+}
 
-    fn play_sound(
-        &self,
-        data: Arc<[u8]>,
-        playing: bool,
-        repeating: bool,
-        volume: f32,
-        speed: f32,
-    ) -> Result<Arc<AudioControls>> {
-        let controls = Arc::new(AudioControls {
-            playing: AtomicBool::new(playing),
-            repeating: AtomicBool::new(repeating),
-            rewind: AtomicBool::new(false),
-            volume: AtomicU32::new(volume.to_bits()),
-            speed: AtomicU32::new(speed.to_bits()),
-        });
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut source: DynamicMixer<f32>,
+    error: &Arc<Mutex<Option<String>>>,
+) -> std::result::Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    let error = Arc::clone(error);
+
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for sample in data.iter_mut() {
+                *sample = source.next().map(T::from_sample_).unwrap_or(T::EQUILIBRIUM);
+            }
+        },
+        move |e| record_stream_error(&error, e.to_string()),
+        None,
+    )
+}
 
-        let master_volume = f32::from_bits(self.master_volume.load(Ordering::SeqCst));
+fn record_stream_error(error: &Mutex<Option<String>>, message: String) {
+    let mut error = error.lock().unwrap_or_else(|e| e.into_inner());
 
-        let data = Decoder::new(Cursor::new(data))
-            .map_err(TetraError::InvalidSound)?
-            .buffered();
+    // The first error is the one that caused the problem - anything after that
+    // is likely to just be a side effect.
+    if error.is_none() {
+        *error = Some(message);
+    }
+}
 
-        let source = TetraSource {
-            repeat_source: data.clone(),
-            data,
+/// The name and sample rate of an output device, used to detect when the
+/// default device changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeviceInfo {
+    name: String,
+    sample_rate: u32,
+}
 
-            remote_master_volume: Arc::clone(&self.master_volume),
-            remote_controls: Arc::clone(&controls),
-            time_till_update: 220,
+impl DeviceInfo {
+    fn default_output() -> Option<DeviceInfo> {
+        let device = cpal::default_host().default_output_device()?;
+        let name = device.name().ok()?;
+        let config = device.default_output_config().ok()?;
 
-            detached: false,
-            playing,
-            repeating,
-            rewind: false,
-            master_volume,
-            volume,
-            speed,
-        };
+        Some(DeviceInfo {
+            name,
+            sample_rate: config.sample_rate().0,
+        })
+    }
+}
+
+/// How often to check whether the default output device has changed.
+///
+/// Not every device change is reported by SDL (e.g. Windows switching the default
+/// output between two devices that are both still plugged in), so this has to be
+/// polled - but querying the device can be slow on some platforms, so it isn't done
+/// every frame.
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct ActiveSound {
+    data: SoundData,
+    controls: Weak<AudioControls>,
+}
 
-        let stream = self.stream.as_ref().ok_or(TetraError::NoAudioDevice)?;
+pub(crate) struct AudioDevice {
+    stream: std::result::Result<AudioStream, String>,
+    device_info: Option<DeviceInfo>,
+    master_volume: Arc<MasterVolume>,
+    active_sounds: RefCell<Vec<ActiveSound>>,
+    last_device_check: Instant,
+}
+
+impl AudioDevice {
+    pub(crate) fn new() -> AudioDevice {
+        AudioDevice {
+            stream: AudioStream::try_default(),
+            device_info: DeviceInfo::default_output(),
+            master_volume: Arc::new(MasterVolume::new(1.0)),
+            active_sounds: RefCell::new(Vec::new()),
+            last_device_check: Instant::now(),
+        }
+    }
+
+    /// Called once per tick, to move playback over to a new device if the current
+    /// stream has stopped working or the default output device has changed.
+    ///
+    /// Stream errors are picked up straight away, as checking for them is cheap, but
+    /// the default device is only checked every [`DEVICE_CHECK_INTERVAL`].
+    ///
+    /// Returns `true` if the device changed, or if playback was restored after the
+    /// device stopped working.
+    pub(crate) fn poll_device(&mut self, now: Instant) -> bool {
+        self.poll_device_with(now, DeviceInfo::default_output, AudioStream::try_default)
+    }
+
+    fn poll_device_with(
+        &mut self,
+        now: Instant,
+        default_output: impl FnOnce() -> Option<DeviceInfo>,
+        open: impl FnOnce() -> std::result::Result<AudioStream, String>,
+    ) -> bool {
+        let lost = matches!(&self.stream, Ok(stream) if stream.error().is_some());
+        let due = now.saturating_duration_since(self.last_device_check) >= DEVICE_CHECK_INTERVAL;
+
+        if !lost && !due {
+            return false;
+        }
+
+        self.last_device_check = now;
+        self.refresh_device_with(default_output(), open)
+    }
+
+    /// Checks whether the default output device has changed (or the current one has
+    /// stopped working), and if so, moves playback over to the new device.
+    ///
+    /// Returns `true` if the device changed, or if playback was restored after the
+    /// device stopped working.
+    pub(crate) fn refresh_device(&mut self) -> bool {
+        self.refresh_device_with(DeviceInfo::default_output(), AudioStream::try_default)
+    }
+
+    fn refresh_device_with(
+        &mut self,
+        default_output: Option<DeviceInfo>,
+        open: impl FnOnce() -> std::result::Result<AudioStream, String>,
+    ) -> bool {
+        let changed = default_output != self.device_info;
+
+        if !changed && self.is_available() {
+            return false;
+        }
+
+        let reset = self.reset_with(open, default_output).is_ok();
+
+        changed || reset
+    }
+
+    /// Returns whether the output stream is open and running.
+    fn is_available(&self) -> bool {
+        match &self.stream {
+            Ok(stream) => stream.error().is_none(),
+            Err(_) => false,
+        }
+    }
+
+    /// Reopens the output stream on the default device, and moves any sounds that
+    /// are playing over to it.
+    fn reset(&mut self) -> Result {
+        self.reset_with(AudioStream::try_default, DeviceInfo::default_output())
+    }
+
+    fn reset_with(
+        &mut self,
+        open: impl FnOnce() -> std::result::Result<AudioStream, String>,
+        device_info: Option<DeviceInfo>,
+    ) -> Result {
+        // The old stream has to be dropped before we try to reattach the sounds, as
+        // this is what frees any sources that no longer have a `SoundInstance`.
+        self.stream = Err(String::from("the audio device is being reset"));
+        self.stream = open();
+        self.device_info = device_info;
+
+        let mut active_sounds = self.active_sounds.borrow_mut();
+
+        active_sounds.retain(|sound| sound.controls.strong_count() > 0);
+
+        let stream = self
+            .stream
+            .as_ref()
+            .map_err(|e| TetraError::NoAudioDevice(e.clone()))?;
+
+        for sound in active_sounds.iter() {
+            if let Some(controls) = sound.controls.upgrade() {
+                // The data was decoded successfully when the sound was first played,
+                // so this should never fail in practice.
+                if let Ok(source) = TetraSource::new(
+                    sound.data.clone(),
+                    controls,
+                    Arc::clone(&self.master_volume),
+                ) {
+                    stream.play(source.convert_samples());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn master_volume(&self) -> f32 {
+        self.master_volume.get()
+    }
+
+    fn set_master_volume(&self, volume: f32) {
+        self.master_volume.set(volume);
+    }
+
+    fn fade_master_volume(&self, volume: f32, duration: Duration) {
+        self.master_volume.fade(volume, duration, Instant::now());
+    }
+
+    fn play_sound(&self, data: SoundData, controls: AudioControls) -> Result<Arc<AudioControls>> {
+        let controls = Arc::new(controls);
+
+        let source = TetraSource::new(
+            data.clone(),
+            Arc::clone(&controls),
+            Arc::clone(&self.master_volume),
+        )?;
+
+        let stream = self
+            .stream
+            .as_ref()
+            .map_err(|e| TetraError::NoAudioDevice(e.clone()))?;
+
+        if let Some(error) = stream.error() {
+            return Err(TetraError::AudioDeviceLost(error));
+        }
 
-        stream
-            .handle
-            .play_raw(source.convert_samples())
-            .map_err(|e| match e {
-                PlayError::DecoderError(e) => TetraError::InvalidSound(e),
-                PlayError::NoDevice => TetraError::NoAudioDevice,
-            })?;
+        stream.play(source.convert_samples());
+
+        let mut active_sounds = self.active_sounds.borrow_mut();
+
+        active_sounds.retain(|sound| sound.controls.strong_count() > 0);
+        active_sounds.push(ActiveSound {
+            data,
+            controls: Arc::downgrade(&controls),
+        });
 
         Ok(controls)
     }
 }
 
-type TetraSourceData = Buffered<Decoder<Cursor<Arc<[u8]>>>>;
+type BufferedData = Buffered<Decoder<Cursor<Arc<[u8]>>>>;
+type StreamedData = Decoder<BufReader<File>>;
+
+/// Plays back raw samples from a [`SampleData`].
+#[derive(Clone)]
+struct SampleSource {
+    data: Arc<SampleData>,
+    position: usize,
+}
+
+impl Iterator for SampleSource {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.data.samples.get(self.position).copied()?;
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl Source for SampleSource {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.data.samples.len() - self.position)
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        self.data.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        self.data.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        let frames = self.data.samples.len() / usize::from(self.data.channels);
+
+        Some(Duration::from_secs_f64(
+            frames as f64 / f64::from(self.data.sample_rate),
+        ))
+    }
+}
+
+/// The decoded audio that a [`TetraSource`] reads its samples from.
+enum SourceData {
+    Buffered(BufferedData),
+    Streamed(Box<StreamedData>),
+    Samples(SampleSource),
+}
+
+impl SourceData {
+    /// Returns a copy of the data, if it is stored in memory (and can therefore be
+    /// cloned cheaply).
+    fn try_clone(&self) -> Option<SourceData> {
+        match self {
+            SourceData::Buffered(data) => Some(SourceData::Buffered(data.clone())),
+            SourceData::Streamed(_) => None,
+            SourceData::Samples(data) => Some(SourceData::Samples(data.clone())),
+        }
+    }
+
+    /// Moves the data forward to the given sample position, returning the position that
+    /// was actually reached (which will be earlier than requested if the data ran out).
+    ///
+    /// Streamed data will seek if the decoder supports it, which may not be sample
+    /// accurate - everything else has to decode each sample in turn.
+    fn skip_to(&mut self, position: usize) -> usize {
+        if let SourceData::Samples(data) = self {
+            data.position = position.min(data.data.samples.len());
+            return data.position;
+        }
+
+        if let SourceData::Streamed(data) = self {
+            let channels = usize::from(data.channels()).max(1);
+            let frame = position / channels;
+
+            // Seeking to the middle of the frame avoids landing on the previous one if the
+            // decoder rounds the time down.
+            let time =
+                Duration::from_secs_f64((frame as f64 + 0.5) / f64::from(data.sample_rate()));
+
+            // Some decoders saturate when seeking past the end, so this has to be checked
+            // up front to be able to tell that the data ran out.
+            let in_bounds = match data.total_duration() {
+                Some(total) => time < total,
+                None => true,
+            };
+
+            if in_bounds && data.try_seek(time).is_ok() {
+                return frame * channels;
+            }
+        }
+
+        let mut reached = 0;
+
+        while reached < position && self.next().is_some() {
+            reached += 1;
+        }
+
+        reached
+    }
+}
+
+impl Iterator for SourceData {
+    type Item = i16;
+
+    #[inline]
+    fn next(&mut self) -> Option<i16> {
+        match self {
+            SourceData::Buffered(data) => data.next(),
+            SourceData::Streamed(data) => data.next(),
+            SourceData::Samples(data) => data.next(),
+        }
+    }
+}
+
+impl Source for SourceData {
+    #[inline]
+    fn current_frame_len(&self) -> Option<usize> {
+        match self {
+            SourceData::Buffered(data) => data.current_frame_len(),
+            SourceData::Streamed(data) => data.current_frame_len(),
+            SourceData::Samples(data) => data.current_frame_len(),
+        }
+    }
+
+    #[inline]
+    fn channels(&self) -> u16 {
+        match self {
+            SourceData::Buffered(data) => data.channels(),
+            SourceData::Streamed(data) => data.channels(),
+            SourceData::Samples(data) => data.channels(),
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> u32 {
+        match self {
+            SourceData::Buffered(data) => data.sample_rate(),
+            SourceData::Streamed(data) => data.sample_rate(),
+            SourceData::Samples(data) => data.sample_rate(),
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        match self {
+            SourceData::Buffered(data) => data.total_duration(),
+            SourceData::Streamed(data) => data.total_duration(),
+            SourceData::Samples(data) => data.total_duration(),
+        }
+    }
+}
+
+/// Where a [`TetraSource`] gets its data from when it needs to go back to the start.
+enum SourceOrigin {
+    /// A copy of the buffered data, positioned at the start of the sound.
+    Buffered(BufferedData),
+
+    /// The file that the data is streamed from.
+    Streamed(Arc<Path>),
+
+    /// Raw samples, which can be played from the start without decoding.
+    Samples(Arc<SampleData>),
+}
+
+impl SourceOrigin {
+    fn new(data: SoundData) -> Result<SourceOrigin> {
+        match data {
+            SoundData::Buffered(data) => Ok(SourceOrigin::Buffered(
+                Decoder::new(Cursor::new(data))
+                    .map_err(TetraError::InvalidSound)?
+                    .buffered(),
+            )),
+
+            SoundData::Streamed(path) => Ok(SourceOrigin::Streamed(path)),
+
+            SoundData::Samples(data) => Ok(SourceOrigin::Samples(data)),
+        }
+    }
+
+    /// Creates a new copy of the data, positioned at the start of the sound.
+    fn start(&self) -> Result<SourceData> {
+        match self {
+            SourceOrigin::Buffered(data) => Ok(SourceData::Buffered(data.clone())),
+
+            SourceOrigin::Streamed(path) => {
+                let file = fs::open(path)?;
+
+                let decoder =
+                    Decoder::new(BufReader::new(file)).map_err(TetraError::InvalidSound)?;
+
+                Ok(SourceData::Streamed(Box::new(decoder)))
+            }
+
+            SourceOrigin::Samples(data) => Ok(SourceData::Samples(SampleSource {
+                data: Arc::clone(data),
+                position: 0,
+            })),
+        }
+    }
+}
 
 struct TetraSource {
-    data: TetraSourceData,
-    repeat_source: TetraSourceData,
+    data: SourceData,
+    origin: SourceOrigin,
+
+    /// The format of the start of the sound, which is used once the data has run out.
+    start_channels: u16,
+    start_sample_rate: u32,
 
-    remote_master_volume: Arc<AtomicU32>,
+    remote_master_volume: Arc<MasterVolume>,
     remote_controls: Arc<AudioControls>,
     time_till_update: u32,
+    fade: Option<Fade>,
+
+    loop_start: usize,
+    loop_end: Option<usize>,
+
+    /// The position of the start of the loop region, along with a copy of the data
+    /// positioned there (if it is stored in memory).
+    ///
+    /// This gets captured as playback passes through the start of the loop, so that
+    /// wrapping around doesn't require decoding up to that point again.
+    loop_source: Option<(usize, Option<SourceData>)>,
 
     detached: bool,
     playing: bool,
@@ -424,6 +1564,217 @@ struct TetraSource {
     master_volume: f32,
     volume: f32,
     speed: f32,
+    position: usize,
+}
+
+impl TetraSource {
+    /// Creates a new source, using the current state of the provided controls.
+    ///
+    /// If the controls indicate that the sound was part way through playback, the source
+    /// will skip ahead to that position.
+    fn new(
+        data: SoundData,
+        remote_controls: Arc<AudioControls>,
+        remote_master_volume: Arc<MasterVolume>,
+    ) -> Result<TetraSource> {
+        let origin = SourceOrigin::new(data)?;
+        let mut data = origin.start()?;
+
+        let start_channels = data.channels();
+        let start_sample_rate = data.sample_rate();
+
+        let rewind = remote_controls.rewind.load(Ordering::SeqCst);
+
+        let position = if rewind {
+            0
+        } else {
+            data.skip_to(remote_controls.position.load(Ordering::SeqCst))
+        };
+
+        // Progress through a fade isn't tracked remotely, so if the source is rebuilt
+        // mid-fade, the fade restarts from the current volume.
+        if remote_controls.fade.active.load(Ordering::SeqCst) {
+            remote_controls.fade.pending.store(true, Ordering::SeqCst);
+        }
+
+        let mut source = TetraSource {
+            data,
+            origin,
+
+            start_channels,
+            start_sample_rate,
+
+            master_volume: remote_master_volume.get(),
+            playing: remote_controls.playing.load(Ordering::SeqCst),
+            repeating: remote_controls.repeating.load(Ordering::SeqCst),
+            rewind,
+            volume: f32::from_bits(remote_controls.volume.load(Ordering::SeqCst)),
+            speed: f32::from_bits(remote_controls.speed.load(Ordering::SeqCst)),
+            position,
+
+            remote_master_volume,
+            remote_controls,
+            time_till_update: 220,
+            fade: None,
+
+            loop_start: 0,
+            loop_end: None,
+            loop_source: None,
+
+            detached: false,
+        };
+
+        source.update_loop_region();
+
+        Ok(source)
+    }
+
+    /// Converts the loop region from the controls into sample positions.
+    fn update_loop_region(&mut self) {
+        let sample_rate = f64::from(self.start_sample_rate);
+        let channels = usize::from(self.start_channels);
+
+        let to_samples = |nanos: u64| {
+            let frames = Duration::from_nanos(nanos).as_secs_f64() * sample_rate;
+            (frames.round() as usize).saturating_mul(channels)
+        };
+
+        let start = to_samples(self.remote_controls.loop_start.load(Ordering::SeqCst));
+
+        let end = match self.remote_controls.loop_end.load(Ordering::SeqCst) {
+            u64::MAX => None,
+            end => Some(to_samples(end)).filter(|&end| end > start),
+        };
+
+        if start != self.loop_start {
+            self.loop_source = None;
+        }
+
+        self.loop_start = start;
+        self.loop_end = end;
+    }
+
+    /// Jumps back to the start of the loop region.
+    fn wrap(&mut self) {
+        match &self.loop_source {
+            Some((position, Some(data))) => {
+                self.data = data
+                    .try_clone()
+                    .expect("loop source should only store data that can be cloned");
+
+                self.position = *position;
+            }
+
+            Some((position, None)) => {
+                let position = *position;
+                self.seek_from_start(position);
+            }
+
+            None => {
+                // Playback hasn't passed through the start of the loop yet (e.g. because
+                // the region was changed), so we have to skip ahead manually.
+                self.seek_from_start(self.loop_start);
+
+                // If the start is past the end of the sound, loop from the beginning instead.
+                if self.position < self.loop_start {
+                    self.seek_from_start(0);
+                }
+
+                self.loop_source = Some((self.position, self.data.try_clone()));
+            }
+        }
+    }
+
+    /// Goes back to the start of the data, and then skips ahead to the given position.
+    fn seek_from_start(&mut self, position: usize) {
+        // This runs on the audio thread, so streamed data is rewound in place if possible,
+        // rather than reopening the file and probing it again.
+        if let SourceData::Streamed(data) = &mut self.data {
+            if data.try_seek(Duration::ZERO).is_ok() {
+                self.position = self.data.skip_to(position);
+                return;
+            }
+        }
+
+        // If a streamed file can no longer be opened, playback carries on from where it
+        // was, which will usually mean that the sound ends.
+        if let Ok(mut data) = self.origin.start() {
+            self.position = data.skip_to(position);
+            self.data = data;
+        }
+    }
+
+    /// Starts a fade, based on the request that was stored in the controls.
+    fn start_fade(&mut self) {
+        let fade = &self.remote_controls.fade;
+
+        let target = f32::from_bits(fade.target.load(Ordering::SeqCst));
+        let duration = Duration::from_nanos(fade.duration.load(Ordering::SeqCst));
+
+        // Every fade lasts for at least one sample, so that instant fades still go
+        // through the same path when they complete.
+        let samples_per_second = self.sample_rate() as f64 * f64::from(self.channels());
+        let remaining = ((duration.as_secs_f64() * samples_per_second).round() as u64).max(1);
+
+        self.fade = Some(Fade {
+            target,
+            step: (target - self.volume) / remaining as f32,
+            remaining,
+            stop: fade.stop.load(Ordering::SeqCst),
+            restore: f32::from_bits(fade.restore.load(Ordering::SeqCst)),
+        });
+    }
+
+    /// Moves the current fade forward by one sample.
+    fn advance_fade(&mut self) {
+        let fade = match &mut self.fade {
+            Some(fade) if fade.remaining > 1 => {
+                fade.remaining -= 1;
+                self.volume += fade.step;
+                return;
+            }
+
+            Some(_) => self.fade.take().expect("fade should be active"),
+            None => return,
+        };
+
+        let controls = &self.remote_controls;
+
+        if fade.stop {
+            self.playing = false;
+            self.rewind = true;
+            self.volume = fade.restore;
+
+            controls.set_state(SoundState::Stopped);
+        } else {
+            self.volume = fade.target;
+        }
+
+        controls
+            .volume
+            .store(self.volume.to_bits(), Ordering::SeqCst);
+
+        // If another fade has been requested in the meantime, that one is still active.
+        if !controls.fade.pending.load(Ordering::SeqCst) {
+            controls.fade.active.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// A fade that is in progress on a [`TetraSource`].
+#[derive(Debug, Clone, Copy)]
+struct Fade {
+    target: f32,
+    step: f32,
+
+    /// The number of samples left until the fade completes.
+    remaining: u64,
+
+    /// Whether the sound should stop once the fade completes.
+    stop: bool,
+
+    /// The volume to reset to if the sound is stopped.
+    restore: f32,
 }
 
 impl Iterator for TetraSource {
@@ -438,21 +1789,52 @@ impl Iterator for TetraSource {
         self.time_till_update -= 1;
 
         if self.time_till_update == 0 {
-            self.master_volume = f32::from_bits(self.remote_master_volume.load(Ordering::SeqCst));
+            self.master_volume = self.remote_master_volume.get();
             self.playing = self.remote_controls.playing.load(Ordering::SeqCst);
 
             // If we're not playing, we don't really care about updating the rest of the state.
             if self.playing {
                 self.repeating = self.remote_controls.repeating.load(Ordering::SeqCst);
                 self.rewind = self.remote_controls.rewind.load(Ordering::SeqCst);
-                self.volume = f32::from_bits(self.remote_controls.volume.load(Ordering::SeqCst));
                 self.speed = f32::from_bits(self.remote_controls.speed.load(Ordering::SeqCst));
+
+                self.update_loop_region();
+
+                if self
+                    .remote_controls
+                    .fade
+                    .pending
+                    .swap(false, Ordering::SeqCst)
+                {
+                    self.start_fade();
+                }
+
+                // While a fade is running, the source owns the volume, so it reports its
+                // progress back instead of reading it.
+                if self.fade.is_some() {
+                    self.remote_controls
+                        .volume
+                        .store(self.volume.to_bits(), Ordering::SeqCst);
+                } else {
+                    self.volume =
+                        f32::from_bits(self.remote_controls.volume.load(Ordering::SeqCst));
+                }
             }
 
+            // This allows the sound to be resumed if the audio device changes.
+            self.remote_controls
+                .position
+                .store(self.position, Ordering::SeqCst);
+
             // If the strong count ever hits 1, that means all of the SoundInstances have been
             // dropped, so we can free this Source if/when it finishes playing.
             if Arc::strong_count(&self.remote_controls) == 1 {
                 self.detached = true;
+
+                // Streamed sounds are stopped straight away, so that the file gets closed.
+                if let SourceOrigin::Streamed(_) = self.origin {
+                    return None;
+                }
             }
 
             self.time_till_update = 220;
@@ -463,36 +1845,49 @@ impl Iterator for TetraSource {
         }
 
         if self.rewind {
-            self.data = self.repeat_source.clone();
+            self.seek_from_start(0);
             self.rewind = false;
 
             self.remote_controls.rewind.store(false, Ordering::SeqCst);
         }
 
+        if self.repeating {
+            if matches!(self.loop_end, Some(end) if self.position >= end) {
+                self.wrap();
+            } else if self.position == self.loop_start && self.loop_source.is_none() {
+                self.loop_source = Some((self.position, self.data.try_clone()));
+            }
+        }
+
         self.data
             .next()
             .or_else(|| {
                 if self.repeating {
-                    self.data = self.repeat_source.clone();
+                    self.wrap();
                     self.data.next()
                 } else {
                     None
                 }
             })
-            .map(|v| v.amplify(self.volume).amplify(self.master_volume))
+            .map(|v| {
+                self.position += 1;
+
+                let sample = v.amplify(self.volume).amplify(self.master_volume);
+                self.advance_fade();
+                sample
+            })
             .or_else(|| {
+                // Report that the sound has finished.
+                if !self.rewind {
+                    self.playing = false;
+                    self.rewind = true;
+
+                    self.remote_controls.finish();
+                }
+
                 if self.detached {
                     None
                 } else {
-                    // Report that the sound has finished.
-                    if !self.rewind {
-                        self.playing = false;
-                        self.rewind = true;
-
-                        self.remote_controls.playing.store(false, Ordering::SeqCst);
-                        self.remote_controls.rewind.store(true, Ordering::SeqCst);
-                    }
-
                     Some(0)
                 }
             })
@@ -508,7 +1903,11 @@ impl Source for TetraSource {
     #[inline]
     fn current_frame_len(&self) -> Option<usize> {
         match self.data.current_frame_len() {
-            Some(0) => self.repeat_source.current_frame_len(),
+            Some(0) => match &self.origin {
+                SourceOrigin::Buffered(data) => data.current_frame_len(),
+                SourceOrigin::Streamed(_) => None,
+                SourceOrigin::Samples(data) => Some(data.samples.len()),
+            },
             a => a,
         }
     }
@@ -516,7 +1915,7 @@ impl Source for TetraSource {
     #[inline]
     fn channels(&self) -> u16 {
         match self.data.current_frame_len() {
-            Some(0) => self.repeat_source.channels(),
+            Some(0) => self.start_channels,
             _ => self.data.channels(),
         }
     }
@@ -524,7 +1923,7 @@ impl Source for TetraSource {
     #[inline]
     fn sample_rate(&self) -> u32 {
         match self.data.current_frame_len() {
-            Some(0) => (self.repeat_source.sample_rate() as f32 * self.speed) as u32,
+            Some(0) => (self.start_sample_rate as f32 * self.speed) as u32,
             _ => (self.data.sample_rate() as f32 * self.speed) as u32,
         }
     }
@@ -534,3 +1933,681 @@ impl Source for TetraSource {
         None
     }
 }
+
+#[cfg(all(test, feature = "audio_wav"))]
+mod tests {
+    use super::*;
+
+    /// Builds a mono 16-bit WAV file, where each sample's value is its index.
+    fn wav_bytes(sample_count: u16) -> Vec<u8> {
+        let data_len = u32::from(sample_count) * 2;
+
+        let mut data = Vec::new();
+
+        data.extend_from_slice(b"RIFF");
+        data.extend_from_slice(&(36 + data_len).to_le_bytes());
+        data.extend_from_slice(b"WAVE");
+
+        data.extend_from_slice(b"fmt ");
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        data.extend_from_slice(&1u16.to_le_bytes()); // Channels
+        data.extend_from_slice(&44100u32.to_le_bytes()); // Sample rate
+        data.extend_from_slice(&(44100u32 * 2).to_le_bytes()); // Byte rate
+        data.extend_from_slice(&2u16.to_le_bytes()); // Block align
+        data.extend_from_slice(&16u16.to_le_bytes()); // Bits per sample
+
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&data_len.to_le_bytes());
+
+        for i in 0..sample_count {
+            data.extend_from_slice(&(i as i16).to_le_bytes());
+        }
+
+        data
+    }
+
+    fn wav_data(sample_count: u16) -> SoundData {
+        SoundData::Buffered(wav_bytes(sample_count).into())
+    }
+
+    fn controls(playing: bool) -> Arc<AudioControls> {
+        Arc::new(AudioControls::new(playing, false, 1.0, 1.0))
+    }
+
+    #[test]
+    fn source_resumes_after_rebuild() {
+        let data = wav_data(2000);
+        let controls = controls(true);
+        let master_volume = Arc::new(MasterVolume::new(1.0));
+
+        let mut source = TetraSource::new(
+            data.clone(),
+            Arc::clone(&controls),
+            Arc::clone(&master_volume),
+        )
+        .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        // Simulate the stream being torn down and rebuilt for a new device.
+        drop(source);
+
+        let position = controls.position.load(Ordering::SeqCst);
+        assert!(position > 0 && position <= 1000);
+
+        let mut source = TetraSource::new(data, controls, master_volume).unwrap();
+
+        assert_eq!(source.next(), Some(position as i16));
+    }
+
+    #[test]
+    fn stopped_source_restarts_after_rebuild() {
+        let data = wav_data(2000);
+        let controls = controls(true);
+        let master_volume = Arc::new(MasterVolume::new(1.0));
+
+        let mut source = TetraSource::new(
+            data.clone(),
+            Arc::clone(&controls),
+            Arc::clone(&master_volume),
+        )
+        .unwrap();
+
+        for _ in 0..1000 {
+            source.next();
+        }
+
+        drop(source);
+
+        controls.set_state(SoundState::Stopped);
+        controls.set_state(SoundState::Playing);
+
+        let mut source = TetraSource::new(data, controls, master_volume).unwrap();
+
+        assert_eq!(source.next(), Some(0));
+    }
+
+    /// Creates a stream that isn't attached to a real device, along with the mixer
+    /// output that would normally be pulled from by the audio thread.
+    fn fake_stream() -> (AudioStream, DynamicMixer<f32>) {
+        let (mixer, output) = dynamic_mixer::mixer::<f32>(1, 44100);
+
+        let stream = AudioStream {
+            _stream: None,
+            mixer,
+            error: Arc::new(Mutex::new(None)),
+        };
+
+        (stream, output)
+    }
+
+    fn device_info(name: &str, sample_rate: u32) -> DeviceInfo {
+        DeviceInfo {
+            name: name.into(),
+            sample_rate,
+        }
+    }
+
+    fn fake_device(stream: AudioStream, now: Instant) -> AudioDevice {
+        AudioDevice {
+            stream: Ok(stream),
+            device_info: Some(device_info("Speakers", 44100)),
+            master_volume: Arc::new(MasterVolume::new(1.0)),
+            active_sounds: RefCell::new(Vec::new()),
+            last_device_check: now,
+        }
+    }
+
+    fn next_sample(output: &mut DynamicMixer<f32>) -> Option<i16> {
+        output
+            .next()
+            .map(|sample| (sample * 32768.0).round() as i16)
+    }
+
+    #[test]
+    fn stream_is_rebuilt_after_error() {
+        let now = Instant::now();
+
+        let (stream, mut output) = fake_stream();
+        let error = Arc::clone(&stream.error);
+        let mut device = fake_device(stream, now);
+
+        let controls = device
+            .play_sound(wav_data(2000), AudioControls::new(true, false, 1.0, 1.0))
+            .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(next_sample(&mut output), Some(i));
+        }
+
+        // Nothing has gone wrong yet, and the device check isn't due.
+        assert!(!device.poll_device_with(
+            now,
+            || unreachable!("device should not be queried"),
+            || unreachable!("stream should not be reopened"),
+        ));
+
+        // Simulate the device being switched over to one with a different sample rate,
+        // without the platform reporting it: the stream's error callback fires, and
+        // the audio thread stops pulling from the old mixer.
+        record_stream_error(&error, String::from("device invalidated"));
+        drop(output);
+
+        assert!(!device.is_available());
+        assert!(matches!(
+            device.play_sound(wav_data(10), AudioControls::new(true, false, 1.0, 1.0)),
+            Err(TetraError::AudioDeviceLost(_))
+        ));
+
+        let (stream, mut output) = fake_stream();
+
+        // The error is picked up on the next tick, even though the device check isn't due.
+        assert!(device.poll_device_with(
+            now,
+            || Some(device_info("Headset", 48000)),
+            || Ok(stream)
+        ));
+
+        assert!(device.is_available());
+        assert_eq!(device.device_info, Some(device_info("Headset", 48000)));
+
+        let position = controls.position.load(Ordering::SeqCst);
+        assert!(position > 0 && position <= 1000);
+
+        assert_eq!(next_sample(&mut output), Some(position as i16));
+    }
+
+    #[test]
+    fn default_device_is_checked_periodically() {
+        let now = Instant::now();
+
+        let (stream, _output) = fake_stream();
+        let mut device = fake_device(stream, now);
+
+        assert!(!device.poll_device_with(
+            now + DEVICE_CHECK_INTERVAL / 2,
+            || unreachable!("device should not be queried"),
+            || unreachable!("stream should not be reopened"),
+        ));
+
+        // The check is due, but the device hasn't changed.
+        assert!(!device.poll_device_with(
+            now + DEVICE_CHECK_INTERVAL,
+            || Some(device_info("Speakers", 44100)),
+            || unreachable!("stream should not be reopened"),
+        ));
+
+        let (stream, _output) = fake_stream();
+
+        assert!(device.poll_device_with(
+            now + DEVICE_CHECK_INTERVAL * 2,
+            || Some(device_info("Headset", 48000)),
+            || Ok(stream)
+        ));
+
+        assert_eq!(device.device_info, Some(device_info("Headset", 48000)));
+    }
+
+    /// The duration of the given number of samples from `wav_data`.
+    fn samples(count: u32) -> Duration {
+        Duration::from_secs_f64(f64::from(count) / 44100.0)
+    }
+
+    fn source(controls: &Arc<AudioControls>) -> TetraSource {
+        TetraSource::new(
+            wav_data(4000),
+            Arc::clone(controls),
+            Arc::new(MasterVolume::new(1.0)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn fade_progresses_per_sample() {
+        let controls = controls(true);
+        let mut source = source(&controls);
+
+        controls.fade(0.0, samples(1000), false);
+
+        // The fade is picked up the next time the source polls the controls.
+        for i in 0..220 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        for _ in 0..499 {
+            source.next();
+        }
+
+        let halfway = source.next().unwrap();
+        assert!((halfway - 720 / 2).abs() <= 1, "{}", halfway);
+
+        for _ in 0..500 {
+            source.next();
+        }
+
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(f32::from_bits(controls.volume.load(Ordering::SeqCst)), 0.0);
+        assert!(!controls.fade.active.load(Ordering::SeqCst));
+        assert_eq!(controls.state(), SoundState::Playing);
+    }
+
+    #[test]
+    fn fade_out_stops_and_restores_volume() {
+        let controls = controls(true);
+        let mut source = source(&controls);
+
+        controls.fade(0.0, samples(500), true);
+
+        for _ in 0..1000 {
+            source.next();
+        }
+
+        assert_eq!(controls.state(), SoundState::Stopped);
+        assert_eq!(f32::from_bits(controls.volume.load(Ordering::SeqCst)), 1.0);
+    }
+
+    #[test]
+    fn new_fade_cancels_existing_fade() {
+        let controls = controls(true);
+        let mut source = source(&controls);
+
+        controls.fade(0.0, samples(10000), true);
+
+        for _ in 0..300 {
+            source.next();
+        }
+
+        controls.fade(0.5, Duration::ZERO, false);
+
+        for _ in 0..220 {
+            source.next();
+        }
+
+        assert!(source.fade.is_none());
+        assert_eq!(source.volume, 0.5);
+        assert_eq!(controls.state(), SoundState::Playing);
+    }
+
+    #[test]
+    fn instant_fade_out_stops_immediately() {
+        let controls = controls(true);
+
+        controls.fade(0.0, Duration::ZERO, true);
+
+        assert_eq!(controls.state(), SoundState::Stopped);
+        assert_eq!(f32::from_bits(controls.volume.load(Ordering::SeqCst)), 1.0);
+    }
+
+    #[test]
+    fn finishing_sets_state_and_flag() {
+        let controls = controls(true);
+        let mut source = source(&controls);
+
+        let flag = Arc::new(AtomicBool::new(false));
+        *controls.finished_flag.lock().unwrap() = Some(Arc::clone(&flag));
+
+        for _ in 0..4000 {
+            source.next();
+        }
+
+        assert!(!flag.load(Ordering::SeqCst));
+        assert_eq!(source.next(), Some(0));
+        assert_eq!(controls.state(), SoundState::Finished);
+        assert!(flag.swap(false, Ordering::SeqCst));
+
+        // The flag is only set once each time that playback runs out.
+        for _ in 0..1000 {
+            source.next();
+        }
+
+        assert!(!flag.load(Ordering::SeqCst));
+
+        controls.set_state(SoundState::Playing);
+        assert_eq!(controls.state(), SoundState::Playing);
+
+        controls.set_state(SoundState::Stopped);
+        assert_eq!(controls.state(), SoundState::Stopped);
+    }
+
+    #[test]
+    fn detached_source_sets_finished_flag() {
+        let controls = controls(true);
+
+        let flag = Arc::new(AtomicBool::new(false));
+        *controls.finished_flag.lock().unwrap() = Some(Arc::clone(&flag));
+
+        let mut source =
+            TetraSource::new(wav_data(1000), controls, Arc::new(MasterVolume::new(1.0))).unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        assert_eq!(source.next(), None);
+        assert!(flag.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn master_volume_fades_over_time() {
+        let volume = MasterVolume::new(1.0);
+        let start = Instant::now();
+
+        volume.fade(0.0, Duration::from_secs(2), start);
+
+        assert_eq!(volume.get_at(start), 1.0);
+        assert_eq!(volume.get_at(start + Duration::from_secs(1)), 0.5);
+        assert_eq!(volume.get_at(start + Duration::from_secs(3)), 0.0);
+
+        // Starting a new fade begins from the current level.
+        volume.fade(1.0, Duration::from_secs(1), start + Duration::from_secs(1));
+
+        assert_eq!(volume.get_at(start + Duration::from_secs(1)), 0.5);
+        assert_eq!(volume.get_at(start + Duration::from_secs(2)), 1.0);
+
+        volume.set(0.25);
+
+        assert_eq!(volume.get_at(start), 0.25);
+    }
+
+    fn looping_controls(start: u32, end: Option<u32>) -> Arc<AudioControls> {
+        let controls = Arc::new(AudioControls::new(true, true, 1.0, 1.0));
+        controls.set_loop_region(samples(start), end.map(samples));
+        controls
+    }
+
+    #[test]
+    fn loop_region_wraps_to_start() {
+        let controls = looping_controls(500, Some(1000));
+        let mut source = source(&controls);
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        for _ in 0..2 {
+            for i in 500..1000 {
+                assert_eq!(source.next(), Some(i));
+            }
+        }
+    }
+
+    #[test]
+    fn loop_region_end_is_clamped() {
+        let controls = looping_controls(3000, Some(10000));
+        let mut source = source(&controls);
+
+        for _ in 0..4000 {
+            source.next();
+        }
+
+        assert_eq!(source.next(), Some(3000));
+    }
+
+    #[test]
+    fn loop_region_start_past_end_loops_from_beginning() {
+        let controls = looping_controls(5000, None);
+        let mut source = source(&controls);
+
+        for _ in 0..4000 {
+            source.next();
+        }
+
+        assert_eq!(source.next(), Some(0));
+    }
+
+    #[test]
+    fn loop_region_changes_on_next_wrap() {
+        let controls = looping_controls(500, None);
+        let mut source = source(&controls);
+
+        for _ in 0..1500 {
+            source.next();
+        }
+
+        controls.set_loop_region(samples(100), None);
+
+        // Playback continues as normal until the end of the sound is reached.
+        for i in 1500..4000 {
+            assert_eq!(source.next(), Some(i as i16));
+        }
+
+        assert_eq!(source.next(), Some(100));
+    }
+
+    /// Writes a WAV file from `wav_bytes` to a temporary location, so that it can be streamed.
+    fn streamed_wav(name: &str, sample_count: u16) -> (std::path::PathBuf, SoundData) {
+        let path = std::env::temp_dir().join(format!("tetra-{}-{}.wav", std::process::id(), name));
+        std::fs::write(&path, wav_bytes(sample_count)).unwrap();
+
+        let data = SoundData::Streamed(path.as_path().into());
+
+        (path, data)
+    }
+
+    #[test]
+    fn streamed_source_loops() {
+        let (path, data) = streamed_wav("streamed_loops", 4000);
+        let controls = looping_controls(500, Some(1000));
+
+        let mut source = TetraSource::new(
+            data,
+            Arc::clone(&controls),
+            Arc::new(MasterVolume::new(1.0)),
+        )
+        .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        for _ in 0..2 {
+            for i in 500..1000 {
+                assert_eq!(source.next(), Some(i));
+            }
+        }
+
+        drop(source);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn streamed_source_loops_without_reopening_file() {
+        let (path, data) = streamed_wav("streamed_no_reopen", 2000);
+        let controls = looping_controls(500, None);
+
+        let mut source = TetraSource::new(
+            data,
+            Arc::clone(&controls),
+            Arc::new(MasterVolume::new(1.0)),
+        )
+        .unwrap();
+
+        // If the source tried to reopen the file to loop, the sound would end.
+        std::fs::remove_file(path).unwrap();
+
+        for i in 0..2000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        for _ in 0..2 {
+            for i in 500..2000 {
+                assert_eq!(source.next(), Some(i));
+            }
+        }
+    }
+
+    #[test]
+    fn streamed_source_resumes_after_rebuild() {
+        let (path, data) = streamed_wav("streamed_resumes", 2000);
+        let controls = controls(true);
+        let master_volume = Arc::new(MasterVolume::new(1.0));
+
+        let mut source = TetraSource::new(
+            data.clone(),
+            Arc::clone(&controls),
+            Arc::clone(&master_volume),
+        )
+        .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        drop(source);
+
+        let position = controls.position.load(Ordering::SeqCst);
+        let mut source = TetraSource::new(data, controls, master_volume).unwrap();
+
+        assert_eq!(source.next(), Some(position as i16));
+
+        drop(source);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn streamed_source_stops_when_detached() {
+        let (path, data) = streamed_wav("streamed_detached", 2000);
+        let controls = controls(true);
+
+        let mut source = TetraSource::new(
+            data,
+            Arc::clone(&controls),
+            Arc::new(MasterVolume::new(1.0)),
+        )
+        .unwrap();
+
+        drop(controls);
+
+        // The source notices that it has been detached the next time it polls the controls.
+        assert!((0..220).any(|_| source.next().is_none()));
+
+        drop(source);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn pool_with_instances(policy: SoundPoolPolicy, count: usize) -> SoundPool {
+        let mut pool = SoundPool::new(Sound::from_encoded(&[]), 2, policy);
+
+        for _ in 0..count {
+            pool.instances.push(SoundInstance {
+                controls: controls(true),
+            });
+        }
+
+        pool
+    }
+
+    #[test]
+    fn pool_frees_stopped_instances() {
+        let mut pool = pool_with_instances(SoundPoolPolicy::Refuse, 2);
+
+        assert!(matches!(pool.claim_slot(), PoolSlot::Refused));
+
+        pool.instances[0].stop();
+
+        assert_eq!(pool.active_count(), 1);
+        assert!(matches!(pool.claim_slot(), PoolSlot::New));
+        assert_eq!(pool.instances.len(), 1);
+    }
+
+    #[test]
+    fn pool_steals_oldest_instance() {
+        let mut pool = pool_with_instances(SoundPoolPolicy::StealOldest, 2);
+        let oldest = pool.instances[0].clone();
+
+        match pool.claim_slot() {
+            PoolSlot::Stolen(instance) => {
+                assert!(Arc::ptr_eq(&instance.controls, &oldest.controls))
+            }
+            slot => panic!("expected a stolen instance, got {:?}", slot),
+        }
+
+        // Paused instances still count towards the limit.
+        let mut pool = pool_with_instances(SoundPoolPolicy::StealOldest, 2);
+        pool.instances[1].pause();
+
+        assert!(matches!(pool.claim_slot(), PoolSlot::Stolen(_)));
+    }
+
+    #[test]
+    fn pool_stops_excess_instances() {
+        let mut pool = pool_with_instances(SoundPoolPolicy::StealOldest, 2);
+        let oldest = pool.instances[0].clone();
+
+        pool.set_max_instances(1);
+
+        assert!(matches!(pool.claim_slot(), PoolSlot::Stolen(_)));
+        assert_eq!(oldest.state(), SoundState::Stopped);
+        assert!(pool.instances.is_empty());
+
+        pool.set_max_instances(0);
+
+        assert!(matches!(pool.claim_slot(), PoolSlot::Refused));
+    }
+
+    #[test]
+    fn sample_source_plays_and_loops() {
+        let samples: Vec<i16> = (0..4000).collect();
+        let data = Sound::from_samples_i16(44100, 1, &samples).data;
+
+        let controls = looping_controls(500, Some(1000));
+
+        let mut source = TetraSource::new(
+            data.clone(),
+            Arc::clone(&controls),
+            Arc::new(MasterVolume::new(1.0)),
+        )
+        .unwrap();
+
+        for i in 0..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        for i in 500..1000 {
+            assert_eq!(source.next(), Some(i));
+        }
+
+        drop(source);
+
+        // Rebuilding the source skips straight to the saved position.
+        let position = controls.position.load(Ordering::SeqCst);
+        let mut source =
+            TetraSource::new(data, controls, Arc::new(MasterVolume::new(1.0))).unwrap();
+
+        assert_eq!(source.next(), Some(position as i16));
+    }
+
+    #[test]
+    fn float_samples_are_converted() {
+        let sound = Sound::from_samples(44100, 2, &[0.0, 1.0, -1.0, 2.0]);
+
+        match &sound.data {
+            SoundData::Samples(data) => {
+                assert_eq!(&*data.samples, &[0, i16::MAX, -i16::MAX, i16::MAX]);
+            }
+            _ => panic!("expected sample data"),
+        }
+
+        assert_eq!(8, sound.data_size());
+    }
+
+    #[test]
+    fn stopping_rewinds_past_loop_region() {
+        let controls = looping_controls(500, Some(1000));
+        let mut source = source(&controls);
+
+        for _ in 0..1200 {
+            source.next();
+        }
+
+        controls.set_state(SoundState::Stopped);
+        controls.set_state(SoundState::Playing);
+
+        // The change is picked up the next time the controls are polled.
+        assert!((0..220).any(|_| source.next() == Some(0)));
+        assert_eq!(source.next(), Some(1));
+    }
+}
@@ -5,7 +5,8 @@ use std::time::{Duration, Instant};
 use crate::graphics::{self, GraphicsContext};
 use crate::input::{self, InputContext};
 use crate::platform::{self, GraphicsDevice, Window};
-use crate::time::{self, TimeContext, Timestep};
+use crate::time::{self, ProfileScope, TimeContext, Timestep};
+use crate::window::VsyncMode;
 use crate::{Result, State, TetraError};
 
 #[cfg(feature = "audio")]
@@ -22,6 +23,7 @@ pub struct Context {
     pub(crate) time: TimeContext,
 
     pub(crate) running: bool,
+    pub(crate) quit_cancelled: bool,
     pub(crate) quit_on_escape: bool,
     pub(crate) fps_limit: bool,
 }
@@ -44,9 +46,18 @@ impl Context {
             println!("GLSL Version: {}", device_info.glsl_version);
         }
 
-        let graphics = GraphicsContext::new(&mut device, window_width, window_height)?;
+        let graphics = GraphicsContext::new(
+            &mut device,
+            window_width,
+            window_height,
+            settings.sprite_batch_size,
+        )?;
         let input = InputContext::new();
-        let time = TimeContext::new(settings.timestep);
+        let time = TimeContext::new(
+            settings.timestep,
+            settings.frame_time_history,
+            settings.max_frame_rate,
+        );
 
         Ok(Context {
             window,
@@ -59,6 +70,7 @@ impl Context {
             time,
 
             running: false,
+            quit_cancelled: false,
             quit_on_escape: settings.quit_on_escape,
 
             fps_limit: settings.fps_limit,
@@ -76,6 +88,9 @@ impl Context {
     /// type returned by your [`State`] methods. This limitation may be lifted
     /// in the future.
     ///
+    /// After the state has been created, this is equivalent to calling [`tick`](Self::tick)
+    /// in a loop until it returns [`LoopStatus::Quit`].
+    ///
     /// # Errors
     ///
     /// If the [`State`] returns an error from [`update`](State::update), [`draw`](State::draw)
@@ -114,77 +129,198 @@ impl Context {
     {
         let state = &mut init(self)?;
 
-        time::reset(self);
+        while self.tick(state)? == LoopStatus::Continue {}
 
-        self.running = true;
-        self.window.set_visible(true);
+        Ok(())
+    }
 
-        let mut output = Ok(());
+    /// Runs a single iteration of the game loop, and then returns control to the caller.
+    ///
+    /// This can be used instead of [`run`](Self::run) when you need to integrate
+    /// Tetra into an event loop that you do not control (e.g. when embedding your
+    /// game into an editor). Each call will process any pending events, run
+    /// zero or more updates (depending on the [`Timestep`] and how much time has
+    /// passed since the last tick), draw a frame and then present it to the screen -
+    /// exactly the same as one iteration of the loop inside `run`.
+    ///
+    /// The first call to this method will start the game, showing the window. Once
+    /// the game has been told to quit (either via [`window::quit`](crate::window::quit)
+    /// or by the user closing the window), the window will be hidden again, and
+    /// [`LoopStatus::Quit`] will be returned. Calling this method after that point
+    /// will restart the game.
+    ///
+    /// # Errors
+    ///
+    /// If the [`State`] returns an error from [`update`](State::update), [`draw`](State::draw)
+    /// or [`event`](State::event), the game will stop running and this method will
+    /// return the error.
+    ///
+    /// # Examples
+    ///
+    /// The [`manual_loop`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/manual_loop.rs)
+    /// example demonstrates how to drive the game loop manually.
+    pub fn tick<S, E>(&mut self, state: &mut S) -> result::Result<LoopStatus, E>
+    where
+        S: State<E>,
+        E: From<TetraError>,
+    {
+        self.tick_impl(state, true)
+    }
 
-        if let Err(e) = self.game_loop(state) {
-            output = Err(e);
+    /// Runs a single iteration of the game loop, without presenting the frame to
+    /// the screen.
+    ///
+    /// This works the same as [`tick`](Self::tick), but leaves it up to the caller to
+    /// present the frame (or not). This can be useful when the event loop that Tetra
+    /// is embedded in wants to control when buffers are swapped - for example, to draw
+    /// its own content on top of the game's output before presenting.
+    ///
+    /// Any pending draw calls will be flushed before this method returns, so the frame
+    /// can be presented by calling [`graphics::present`](crate::graphics::present).
+    ///
+    /// # Errors
+    ///
+    /// If the [`State`] returns an error from [`update`](State::update), [`draw`](State::draw)
+    /// or [`event`](State::event), the game will stop running and this method will
+    /// return the error.
+    pub fn tick_without_present<S, E>(&mut self, state: &mut S) -> result::Result<LoopStatus, E>
+    where
+        S: State<E>,
+        E: From<TetraError>,
+    {
+        self.tick_impl(state, false)
+    }
+
+    fn tick_impl<S, E>(&mut self, state: &mut S, present: bool) -> result::Result<LoopStatus, E>
+    where
+        S: State<E>,
+        E: From<TetraError>,
+    {
+        // The window is only shown when the game starts (or restarts after quitting),
+        // rather than on every tick.
+        if !self.running {
+            time::reset(self);
+
+            self.running = true;
+            self.window.set_visible(true);
         }
 
-        self.running = false;
-        self.window.set_visible(false);
+        if let Err(e) = self.step(state, present) {
+            self.running = false;
+            self.window.set_visible(false);
 
-        output
+            return Err(e);
+        }
+
+        if self.running {
+            Ok(LoopStatus::Continue)
+        } else {
+            self.window.set_visible(false);
+
+            Ok(LoopStatus::Quit)
+        }
     }
 
-    pub(crate) fn game_loop<S, E>(&mut self, state: &mut S) -> result::Result<(), E>
+    fn step<S, E>(&mut self, state: &mut S, present: bool) -> result::Result<(), E>
     where
         S: State<E>,
         E: From<TetraError>,
     {
-        let mut last_time = Instant::now();
+        let curr_time = Instant::now();
+        let diff_time = curr_time - self.time.last_time;
+        self.time.last_time = curr_time;
 
-        while self.running {
-            let curr_time = Instant::now();
-            let diff_time = curr_time - last_time;
-            last_time = curr_time;
+        self.time.fps_tracker.push(diff_time);
 
-            self.time.fps_tracker.push(diff_time);
+        platform::handle_events(self, state)?;
 
-            platform::handle_events(self, state)?;
+        // Not every device change is reported by the platform, so this is polled too.
+        #[cfg(feature = "audio")]
+        if self.audio.poll_device(curr_time) {
+            state.event(self, crate::Event::AudioDeviceChanged)?;
+        }
 
-            match self.time.tick_rate {
-                Some(tick_rate) => {
+        match self.time.tick_rate {
+            Some(tick_rate) => {
+                self.time.accumulator =
+                    time::accumulate(self.time.accumulator, diff_time, tick_rate);
+
+                // The tick rate is checked again after every update, as the game may
+                // have changed the timestep.
+                while let Some(tick_rate) = self
+                    .time
+                    .tick_rate
+                    .filter(|&tick_rate| self.time.accumulator >= tick_rate)
+                {
                     self.time.delta_time = tick_rate;
-                    self.time.accumulator = (self.time.accumulator + diff_time).min(tick_rate * 8);
 
-                    while self.time.accumulator >= tick_rate {
-                        state.update(self)?;
-                        input::clear(self);
+                    let _scope = ProfileScope::new(self, "update");
 
-                        self.time.accumulator -= tick_rate;
-                    }
+                    state.update(self)?;
+                    input::clear(self);
 
-                    self.time.delta_time = diff_time;
+                    self.time.tick_count += 1;
+
+                    if let Some(tick_rate) = self.time.tick_rate {
+                        self.time.accumulator = self.time.accumulator.saturating_sub(tick_rate);
+                    }
                 }
 
-                None => {
-                    self.time.delta_time = diff_time;
+                self.time.delta_time = diff_time;
+            }
 
-                    state.update(self)?;
-                    input::clear(self);
-                }
+            None => {
+                self.time.delta_time = diff_time;
+
+                let _scope = ProfileScope::new(self, "update");
+
+                state.update(self)?;
+                input::clear(self);
+
+                self.time.tick_count += 1;
             }
+        }
 
+        {
+            let _scope = ProfileScope::new(self, "draw");
             state.draw(self)?;
+        }
+
+        self.time.frame_count += 1;
 
+        if present {
+            let _scope = ProfileScope::new(self, "present");
             graphics::present(self);
+        } else {
+            graphics::flush(self);
+        }
 
-            // This provides a sensible FPS limit when running without vsync, and
-            // avoids CPU usage skyrocketing on some systems.
-            if self.fps_limit {
-                thread::sleep(Duration::from_millis(1));
-            }
+        self.time.profiler.borrow_mut().finish_frame();
+
+        let limited = time::limit_frame_rate(self);
+
+        // This provides a sensible FPS limit when running without vsync, and
+        // avoids CPU usage skyrocketing on some systems.
+        if self.fps_limit && !limited {
+            thread::sleep(Duration::from_millis(1));
         }
 
         Ok(())
     }
 }
 
+/// The status of the game loop, as returned by [`Context::tick`].
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoopStatus {
+    /// The game is still running, and `tick` should be called again.
+    Continue,
+
+    /// The game has been told to quit, and `tick` should not be called again
+    /// (unless you want to restart the game).
+    Quit,
+}
+
 /// Settings that can be configured when starting up a game.
 ///
 /// # Serde
@@ -201,17 +337,20 @@ impl Context {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ContextBuilder {
     pub(crate) title: String,
+    pub(crate) app_id: Option<String>,
     pub(crate) window_width: i32,
     pub(crate) window_height: i32,
-    pub(crate) vsync: bool,
+    pub(crate) vsync: VsyncMode,
     pub(crate) timestep: Timestep,
     pub(crate) fullscreen: bool,
     pub(crate) maximized: bool,
     pub(crate) minimized: bool,
     pub(crate) resizable: bool,
+    pub(crate) aspect_ratio: Option<(f32, f32)>,
     pub(crate) borderless: bool,
     pub(crate) multisampling: u8,
     pub(crate) stencil_buffer: bool,
+    pub(crate) depth_buffer: bool,
     pub(crate) high_dpi: bool,
     pub(crate) screen_saver_enabled: bool,
     pub(crate) key_repeat: bool,
@@ -220,6 +359,10 @@ pub struct ContextBuilder {
     pub(crate) relative_mouse_mode: bool,
     pub(crate) quit_on_escape: bool,
     pub(crate) fps_limit: bool,
+    pub(crate) max_frame_rate: Option<f64>,
+    pub(crate) frame_time_history: usize,
+    pub(crate) sprite_batch_size: usize,
+    pub(crate) headless: bool,
     pub(crate) debug_info: bool,
 }
 
@@ -249,6 +392,27 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets the application ID of the game.
+    ///
+    /// On Linux, this is used as the app ID on Wayland and the `WM_CLASS` on X11, which
+    /// allows the desktop environment to associate the window with your game's
+    /// `.desktop` file (e.g. to display the correct icon). It should be set to the name
+    /// of that file, without the `.desktop` extension. This setting is ignored on
+    /// other platforms.
+    ///
+    /// This has to be set before the window is created, so it cannot be changed at runtime.
+    /// It is passed to SDL via the `SDL_VIDEO_X11_WMCLASS` and `SDL_VIDEO_WAYLAND_WMCLASS`
+    /// environment variables - if either of these are already set, they will take priority.
+    ///
+    /// Defaults to `None`, which will use SDL's default.
+    pub fn app_id<S>(&mut self, app_id: S) -> &mut ContextBuilder
+    where
+        S: Into<String>,
+    {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
     /// Sets the size of the window.
     ///
     /// Defaults to `1280` by `720`.
@@ -260,14 +424,25 @@ impl ContextBuilder {
 
     /// Enables or disables vsync.
     ///
-    /// Setting this flag does not guarantee that the requested vsync mode will be used -
-    /// some platforms do not support vsync, and others *enforce* vsync. If you want to
-    /// find out which vsync mode was actually chosen, you can call
-    /// [`window::is_vsync_enabled`](crate::window::is_vsync_enabled).
+    /// This is a shortcut for calling [`vsync_mode`](Self::vsync_mode) with
+    /// [`VsyncMode::On`] or [`VsyncMode::Off`].
     ///
     /// Defaults to `true`.
     pub fn vsync(&mut self, vsync: bool) -> &mut ContextBuilder {
-        self.vsync = vsync;
+        self.vsync = if vsync { VsyncMode::On } else { VsyncMode::Off };
+        self
+    }
+
+    /// Sets how the window should be synchronized with the monitor's refresh rate.
+    ///
+    /// Setting this does not guarantee that the requested vsync mode will be used -
+    /// some platforms do not support vsync (or adaptive vsync), and others *enforce* vsync.
+    /// If you want to find out which vsync mode was actually chosen, you can call
+    /// [`window::get_vsync_mode`](crate::window::get_vsync_mode).
+    ///
+    /// Defaults to [`VsyncMode::On`].
+    pub fn vsync_mode(&mut self, mode: VsyncMode) -> &mut ContextBuilder {
+        self.vsync = mode;
         self
     }
 
@@ -283,6 +458,42 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets the maximum number of frames that will be drawn per second.
+    ///
+    /// See [`time::set_max_frame_rate`](crate::time::set_max_frame_rate) for more details.
+    ///
+    /// Defaults to `None`.
+    pub fn max_frame_rate(&mut self, max_frame_rate: Option<f64>) -> &mut ContextBuilder {
+        self.max_frame_rate = max_frame_rate;
+        self
+    }
+
+    /// Sets the number of frames that should be stored in the
+    /// [frame time history](crate::time::get_frame_time_history).
+    ///
+    /// This is also the number of frames that [`time::get_fps`](crate::time::get_fps)
+    /// is averaged across. It must be at least 1.
+    ///
+    /// Defaults to `200`.
+    pub fn frame_time_history(&mut self, frames: usize) -> &mut ContextBuilder {
+        self.frame_time_history = frames;
+        self
+    }
+
+    /// Sets the maximum number of sprites that can be drawn in a single batch.
+    ///
+    /// When this many quads (e.g. textures, text glyphs) have been queued up, the
+    /// renderer will be [flushed](crate::graphics::flush), even if nothing else has
+    /// changed. Increasing this can reduce the number of draw calls in scenes with lots of
+    /// sprites that share a texture, at the cost of using more memory. The batch size is
+    /// clamped to be at least 1.
+    ///
+    /// Defaults to `2048`.
+    pub fn sprite_batch_size(&mut self, sprite_batch_size: usize) -> &mut ContextBuilder {
+        self.sprite_batch_size = sprite_batch_size;
+        self
+    }
+
     /// Sets the game's timestep.
     ///
     /// Defaults to `Timestep::Fixed(60.0)`.
@@ -323,6 +534,21 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets the minimum and maximum aspect ratio (width divided by height) of the window.
+    ///
+    /// If the initial size of the window does not fit within this range, it will be
+    /// shrunk to fit. See [`window::set_aspect_ratio`](crate::window::set_aspect_ratio)
+    /// for more details on how the constraint is applied.
+    ///
+    /// If `min` or `max` is invalid, a [`TetraError::InvalidAspectRatio`](crate::TetraError::InvalidAspectRatio)
+    /// error will be returned when the context is built.
+    ///
+    /// Defaults to `None`.
+    pub fn aspect_ratio(&mut self, min: f32, max: f32) -> &mut ContextBuilder {
+        self.aspect_ratio = Some((min, max));
+        self
+    }
+
     /// Sets whether or not the window should be borderless.
     ///
     /// Defaults to `false`.
@@ -359,6 +585,20 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets whether or not the window should have a depth buffer.
+    ///
+    /// If this is enabled, you can use [`graphics::set_depth_test`](crate::graphics::set_depth_test)
+    /// when rendering to the main backbuffer.
+    ///
+    /// Note that this setting only applies to the main backbuffer - to create a canvas with
+    /// a depth buffer, use [`Canvas::builder`](crate::graphics::Canvas::builder).
+    ///
+    /// Defaults to `false`.
+    pub fn depth_buffer(&mut self, depth_buffer: bool) -> &mut ContextBuilder {
+        self.depth_buffer = depth_buffer;
+        self
+    }
+
     /// Sets whether or not the window should use a high-DPI backbuffer, on platforms
     /// that support it (e.g. MacOS with a retina display).
     ///
@@ -446,6 +686,28 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets whether or not the game should run without a visible window.
+    ///
+    /// In headless mode, the game uses SDL's offscreen video driver, which creates an
+    /// OpenGL context (via EGL) without needing a display. This allows code that requires
+    /// a [`Context`] to run on machines without a display, such as CI servers. Resources
+    /// such as textures and canvases can be created and drawn to as normal, but nothing
+    /// will ever be shown on screen - the game loop runs as usual, but
+    /// [`graphics::present`](crate::graphics::present) has no effect.
+    ///
+    /// The window functions in [`window`](crate::window) can still be called, and
+    /// will act on the hidden window.
+    ///
+    /// The offscreen driver requires SDL 2.0.22 or later, built with EGL support. If you
+    /// need to use a different driver, you can override it by setting the
+    /// `SDL_VIDEODRIVER` environment variable.
+    ///
+    /// Defaults to `false`.
+    pub fn headless(&mut self, headless: bool) -> &mut ContextBuilder {
+        self.headless = headless;
+        self
+    }
+
     /// Sets whether or not the game should print out debug info at startup.
     /// Please include this if you're submitting a bug report!
     pub fn debug_info(&mut self, debug_info: bool) -> &mut ContextBuilder {
@@ -467,17 +729,20 @@ impl Default for ContextBuilder {
     fn default() -> ContextBuilder {
         ContextBuilder {
             title: "Tetra".into(),
+            app_id: None,
             window_width: 1280,
             window_height: 720,
-            vsync: true,
+            vsync: VsyncMode::On,
             timestep: Timestep::Fixed(60.0),
             fullscreen: false,
             maximized: false,
             minimized: false,
             resizable: false,
+            aspect_ratio: None,
             borderless: false,
             multisampling: 0,
             stencil_buffer: false,
+            depth_buffer: false,
             high_dpi: false,
             screen_saver_enabled: false,
             key_repeat: false,
@@ -486,7 +751,148 @@ impl Default for ContextBuilder {
             relative_mouse_mode: false,
             quit_on_escape: false,
             fps_limit: true,
+            max_frame_rate: None,
+            frame_time_history: 200,
+            sprite_batch_size: 2048,
+            headless: false,
             debug_info: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_id_defaults_to_none() {
+        let builder = ContextBuilder::new("Test", 640, 480);
+
+        assert_eq!(builder.app_id, None);
+    }
+
+    #[test]
+    fn app_id_can_be_set() {
+        let mut builder = ContextBuilder::new("Test", 640, 480);
+
+        builder.app_id("com.example.Game");
+        assert_eq!(builder.app_id.as_deref(), Some("com.example.Game"));
+
+        builder.app_id(String::from("com.example.OtherGame"));
+        assert_eq!(builder.app_id.as_deref(), Some("com.example.OtherGame"));
+    }
+
+    /// A state that counts how many times it is called, and quits after a fixed
+    /// number of frames.
+    struct CountingState {
+        updates: u32,
+        draws: u32,
+        quit_after: u32,
+        fail_update: bool,
+    }
+
+    impl CountingState {
+        fn new(quit_after: u32) -> CountingState {
+            CountingState {
+                updates: 0,
+                draws: 0,
+                quit_after,
+                fail_update: false,
+            }
+        }
+    }
+
+    impl State for CountingState {
+        fn update(&mut self, _: &mut Context) -> Result {
+            if self.fail_update {
+                return Err(TetraError::PlatformError("update failed".into()));
+            }
+
+            self.updates += 1;
+            Ok(())
+        }
+
+        fn draw(&mut self, ctx: &mut Context) -> Result {
+            self.draws += 1;
+
+            if self.draws == self.quit_after {
+                crate::window::quit(ctx);
+            }
+
+            Ok(())
+        }
+    }
+
+    fn test_context(title: &str) -> Context {
+        ContextBuilder::new(title, 64, 64)
+            .headless(true)
+            .timestep(Timestep::Variable)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL context"]
+    fn tick_runs_update_and_draw() {
+        let mut ctx = test_context("tick_runs_update_and_draw");
+        let mut state = CountingState::new(3);
+
+        assert_eq!(
+            ctx.tick_impl(&mut state, false).unwrap(),
+            LoopStatus::Continue
+        );
+        assert_eq!((state.updates, state.draws), (1, 1));
+
+        assert_eq!(
+            ctx.tick_impl(&mut state, true).unwrap(),
+            LoopStatus::Continue
+        );
+        assert_eq!(ctx.tick_impl(&mut state, true).unwrap(), LoopStatus::Quit);
+
+        assert_eq!((state.updates, state.draws), (3, 3));
+        assert_eq!(time::get_frame_count(&ctx), 3);
+        assert!(!ctx.running);
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL context"]
+    fn headless_context_can_tick() {
+        let mut ctx = ContextBuilder::new("headless_context_can_tick", 64, 64)
+            .headless(true)
+            .build()
+            .unwrap();
+
+        let mut state = CountingState::new(2);
+
+        assert_eq!(ctx.tick(&mut state).unwrap(), LoopStatus::Continue);
+
+        // The window is normally shown when the game starts running.
+        assert!(!crate::window::is_visible(&mut ctx));
+
+        assert_eq!(ctx.tick(&mut state).unwrap(), LoopStatus::Quit);
+        assert_eq!(state.draws, 2);
+
+        // Rendering still works, even though nothing is shown.
+        let canvas = graphics::Canvas::new(&mut ctx, 8, 8).unwrap();
+
+        graphics::set_canvas(&mut ctx, &canvas);
+        graphics::clear(&mut ctx, graphics::Color::RED);
+        graphics::reset_canvas(&mut ctx);
+
+        let data = canvas.get_data(&mut ctx);
+        assert_eq!(data.get_pixel_color((0, 0).into()), graphics::Color::RED);
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL context"]
+    fn tick_stops_on_error() {
+        let mut ctx = test_context("tick_stops_on_error");
+
+        let mut state = CountingState::new(3);
+        state.fail_update = true;
+
+        assert!(ctx.tick_impl(&mut state, true).is_err());
+        assert_eq!(state.draws, 0);
+        assert!(!ctx.running);
+    }
+}
@@ -10,6 +10,8 @@ use image::ImageError;
 
 use lyon_tessellation::TessellationError;
 
+use crate::graphics::{TextureFormat, UniformType};
+
 #[cfg(feature = "audio")]
 use rodio::decoder::DecoderError;
 
@@ -37,18 +39,44 @@ pub enum TetraError {
         path: PathBuf,
     },
 
+    /// Returned when your game fails to save a file. This is usually caused by an
+    /// incorrect file path, or some form of permission issues.
+    FailedToSaveAsset {
+        /// The underlying reason for the error.
+        reason: io::Error,
+
+        /// The path that the file was being saved to.
+        path: PathBuf,
+    },
+
     /// Returned when a color is invalid.
     InvalidColor,
 
     /// Returned when a texture's data is invalid.
     InvalidTexture(ImageError),
 
+    /// Returned when image data could not be encoded (e.g. because the requested
+    /// file format is not supported).
+    FailedToEncodeImage(ImageError),
+
     /// Returned when a shader fails to compile.
     InvalidShader(String),
 
     /// Returned when a font could not be read.
     InvalidFont,
 
+    /// Returned when more values are provided for a uniform than it can hold.
+    InvalidUniform {
+        /// The name of the uniform.
+        name: String,
+
+        /// The number of values that the uniform can hold.
+        expected: usize,
+
+        /// The number of values that were provided.
+        actual: usize,
+    },
+
     /// Returned when a sound cannot be decoded.
     #[cfg(feature = "audio")]
     InvalidSound(DecoderError),
@@ -65,7 +93,15 @@ pub enum TetraError {
     },
 
     /// Returned when trying to play back audio without an available device.
-    NoAudioDevice,
+    /// Contains the error that was reported when trying to open the device.
+    NoAudioDevice(String),
+
+    /// Returned when trying to play back audio after the audio device has stopped
+    /// working (e.g. because it was unplugged). Contains the error that was reported
+    /// by the device.
+    ///
+    /// Calling `audio::reset_device` will attempt to switch to a new device.
+    AudioDeviceLost(String),
 
     /// Returned when your game tried to change the display settings (e.g. fullscreen, vsync)
     /// but was unable to do so.
@@ -77,6 +113,64 @@ pub enum TetraError {
 
     /// Returned when a shape cannot be tessellated.
     TessellationError(TessellationError),
+
+    /// Returned when an image does not fit into a texture atlas.
+    /// Contains the name of the image.
+    AtlasFull(String),
+
+    /// Returned when a resource is given a size that it cannot have (e.g. a width
+    /// or height of zero).
+    InvalidSize {
+        /// The width that was requested.
+        width: i32,
+
+        /// The height that was requested.
+        height: i32,
+    },
+
+    /// Returned when a texture cannot be bound to a shader's texture unit, either because
+    /// the unit is reserved or already in use, or because the graphics hardware does not
+    /// support that many units.
+    InvalidTextureUnit {
+        /// The name of the uniform.
+        name: String,
+
+        /// The texture unit that was requested.
+        unit: u32,
+    },
+
+    /// Returned when data is written to a texture in a format that cannot be
+    /// converted to the texture's format without losing information.
+    TextureFormatMismatch {
+        /// The format of the texture.
+        expected: TextureFormat,
+
+        /// The format of the data that was provided.
+        actual: TextureFormat,
+    },
+
+    /// Returned when a value is provided for a shader uniform that does not match the
+    /// type that the uniform was declared with.
+    UniformTypeMismatch {
+        /// The name of the uniform.
+        name: String,
+
+        /// The type of the uniform.
+        expected: UniformType,
+
+        /// The type of the value that was provided.
+        actual: UniformType,
+    },
+
+    /// Returned when an aspect ratio constraint is not valid (e.g. if the minimum is
+    /// greater than the maximum).
+    InvalidAspectRatio {
+        /// The minimum aspect ratio that was requested.
+        min: f32,
+
+        /// The maximum aspect ratio that was requested.
+        max: f32,
+    },
 }
 
 impl Display for TetraError {
@@ -88,10 +182,23 @@ impl Display for TetraError {
             TetraError::FailedToLoadAsset { path, .. } => {
                 write!(f, "Failed to load asset from {}", path.to_string_lossy())
             }
+            TetraError::FailedToSaveAsset { path, .. } => {
+                write!(f, "Failed to save asset to {}", path.to_string_lossy())
+            }
             TetraError::InvalidColor => write!(f, "Invalid color"),
             TetraError::InvalidTexture(_) => write!(f, "Invalid texture data"),
+            TetraError::FailedToEncodeImage(_) => write!(f, "Failed to encode image data"),
             TetraError::InvalidShader(msg) => write!(f, "Invalid shader source: {}", msg),
             TetraError::InvalidFont => write!(f, "Invalid font data"),
+            TetraError::InvalidUniform {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Too many values were provided for uniform '{}' - expected at most {}, found {}.",
+                name, expected, actual
+            ),
             #[cfg(feature = "audio")]
             TetraError::InvalidSound(_) => write!(f, "Invalid sound data"),
             TetraError::NotEnoughData { expected, actual } => write!(
@@ -105,10 +212,43 @@ impl Display for TetraError {
             TetraError::FailedToChangeDisplayMode(msg) => {
                 write!(f, "Failed to change display mode: {}", msg)
             }
-            TetraError::NoAudioDevice => write!(f, "No audio device available for playback"),
+            TetraError::NoAudioDevice(msg) => {
+                write!(f, "No audio device available for playback: {}", msg)
+            }
+            TetraError::AudioDeviceLost(msg) => {
+                write!(f, "The audio device stopped working: {}", msg)
+            }
             TetraError::TessellationError(_) => {
                 write!(f, "An error occurred while tessellating a shape")
             }
+            TetraError::AtlasFull(name) => {
+                write!(f, "Image '{}' does not fit in the texture atlas", name)
+            }
+            TetraError::InvalidSize { width, height } => {
+                write!(f, "Invalid size: {}x{}", width, height)
+            }
+            TetraError::InvalidTextureUnit { name, unit } => write!(
+                f,
+                "Texture unit {} cannot be used for uniform '{}'",
+                unit, name
+            ),
+            TetraError::TextureFormatMismatch { expected, actual } => write!(
+                f,
+                "Texture format mismatch - expected {:?}, found {:?}.",
+                expected, actual
+            ),
+            TetraError::UniformTypeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Uniform type mismatch for '{}' - expected {:?}, found {:?}.",
+                name, expected, actual
+            ),
+            TetraError::InvalidAspectRatio { min, max } => {
+                write!(f, "Invalid aspect ratio range: {} to {}", min, max)
+            }
         }
     }
 }
@@ -118,17 +258,27 @@ impl Error for TetraError {
         match self {
             TetraError::PlatformError(_) => None,
             TetraError::FailedToLoadAsset { reason, .. } => Some(reason),
+            TetraError::FailedToSaveAsset { reason, .. } => Some(reason),
             TetraError::InvalidColor => None,
             TetraError::InvalidTexture(reason) => Some(reason),
+            TetraError::FailedToEncodeImage(reason) => Some(reason),
             TetraError::InvalidShader(_) => None,
             TetraError::InvalidFont => None,
+            TetraError::InvalidUniform { .. } => None,
             #[cfg(feature = "audio")]
             TetraError::InvalidSound(reason) => Some(reason),
             TetraError::NotEnoughData { .. } => None,
-            TetraError::NoAudioDevice => None,
+            TetraError::NoAudioDevice(_) => None,
+            TetraError::AudioDeviceLost(_) => None,
             TetraError::FailedToGetRefreshRate(_) => None,
             TetraError::FailedToChangeDisplayMode(_) => None,
             TetraError::TessellationError(reason) => Some(reason),
+            TetraError::AtlasFull(_) => None,
+            TetraError::InvalidSize { .. } => None,
+            TetraError::InvalidTextureUnit { .. } => None,
+            TetraError::TextureFormatMismatch { .. } => None,
+            TetraError::UniformTypeMismatch { .. } => None,
+            TetraError::InvalidAspectRatio { .. } => None,
         }
     }
 }
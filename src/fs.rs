@@ -6,7 +6,7 @@
 use std::fs;
 use std::path::Path;
 
-use image::{self, DynamicImage, ImageError};
+use image::{self, DynamicImage, ImageError, ImageFormat};
 
 use crate::error::{Result, TetraError};
 
@@ -37,6 +37,31 @@ where
     })
 }
 
+pub(crate) fn write_image<P>(path: P, image: &DynamicImage) -> Result
+where
+    P: AsRef<Path>,
+{
+    let path_ref = path.as_ref();
+
+    let format = ImageFormat::from_path(path_ref).map_err(TetraError::FailedToEncodeImage)?;
+
+    // Some encoders (e.g. JPEG) do not support an alpha channel.
+    let result = match format {
+        ImageFormat::Jpeg => {
+            DynamicImage::ImageRgb8(image.to_rgb8()).save_with_format(path_ref, format)
+        }
+        _ => image.save_with_format(path_ref, format),
+    };
+
+    result.map_err(|e| match e {
+        ImageError::IoError(inner) => TetraError::FailedToSaveAsset {
+            reason: inner,
+            path: path_ref.to_owned(),
+        },
+        _ => TetraError::FailedToEncodeImage(e),
+    })
+}
+
 pub(crate) fn read_to_string<P>(path: P) -> Result<String>
 where
     P: AsRef<Path>,
@@ -48,3 +73,15 @@ where
         path: path_ref.to_owned(),
     })
 }
+
+pub(crate) fn open<P>(path: P) -> Result<fs::File>
+where
+    P: AsRef<Path>,
+{
+    let path_ref = path.as_ref();
+
+    fs::File::open(path_ref).map_err(|e| TetraError::FailedToLoadAsset {
+        reason: e,
+        path: path_ref.to_owned(),
+    })
+}
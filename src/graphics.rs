@@ -9,35 +9,48 @@ pub mod animation;
 mod camera;
 mod canvas;
 mod color;
+pub mod debug;
 mod drawparams;
 mod image_data;
 pub mod mesh;
+pub mod particles;
+mod post_process;
 mod rectangle;
 pub mod scaling;
 mod shader;
+mod sprite_batch;
 pub mod text;
 mod texture;
+mod texture_atlas;
+pub mod tonemap;
+mod transition;
 
 pub use camera::*;
 pub use canvas::*;
 pub use color::*;
 pub use drawparams::*;
 pub use image_data::*;
+pub use post_process::*;
 pub use rectangle::*;
 pub use shader::*;
+pub use sprite_batch::*;
 pub use texture::*;
+pub use texture_atlas::*;
+pub use transition::*;
 
 use crate::error::Result;
-use crate::math::{FrustumPlanes, Mat4, Vec2};
+use crate::math::{Mat4, Vec2};
 use crate::platform::{GraphicsDevice, RawIndexBuffer, RawVertexBuffer};
 use crate::window;
 use crate::Context;
 
 use self::mesh::{BufferUsage, Vertex, VertexWinding};
 
-const MAX_SPRITES: usize = 2048;
-const MAX_VERTICES: usize = MAX_SPRITES * 4; // Cannot be greater than 32767!
-const MAX_INDICES: usize = MAX_SPRITES * 6;
+/// The largest batch size that can be addressed by the `u32` index buffer.
+///
+/// Each sprite takes up six indices, so this is what limits the size (rather than the
+/// four vertices, which only need to be addressable by the index values).
+const MAX_BATCH_SIZE: usize = u32::MAX as usize / 6;
 const INDEX_ARRAY: [u32; 6] = [0, 1, 2, 2, 3, 0];
 
 pub(crate) struct GraphicsContext {
@@ -47,19 +60,26 @@ pub(crate) struct GraphicsContext {
     texture: Option<Texture>,
     default_texture: Texture,
     default_filter_mode: FilterMode,
+    filter_mode: Option<FilterMode>,
+    default_wrap_mode: WrapMode,
 
     shader: Option<Shader>,
     default_shader: Shader,
+    default_instanced_shader: Shader,
 
     canvas: Option<Canvas>,
+    viewport: Option<Rectangle<i32>>,
 
     projection_matrix: Mat4<f32>,
     transform_matrix: Mat4<f32>,
 
     vertex_data: Vec<Vertex>,
     element_count: usize,
+    max_indices: usize,
 
     blend_state: BlendState,
+
+    warm_up_canvas: Option<Canvas>,
 }
 
 impl GraphicsContext {
@@ -67,14 +87,19 @@ impl GraphicsContext {
         device: &mut GraphicsDevice,
         window_width: i32,
         window_height: i32,
+        batch_size: usize,
     ) -> Result<GraphicsContext> {
-        let vertex_buffer = device.new_vertex_buffer(MAX_VERTICES, BufferUsage::Dynamic)?;
-        let index_buffer = device.new_index_buffer(MAX_INDICES, BufferUsage::Static)?;
+        let batch_size = batch_size.clamp(1, MAX_BATCH_SIZE);
+        let max_vertices = batch_size * 4;
+        let max_indices = batch_size * 6;
+
+        let vertex_buffer = device.new_vertex_buffer(max_vertices, BufferUsage::Dynamic)?;
+        let index_buffer = device.new_index_buffer(max_indices, BufferUsage::Static)?;
 
         let indices: Vec<u32> = INDEX_ARRAY
             .iter()
             .cycle()
-            .take(MAX_INDICES)
+            .take(max_indices)
             .enumerate()
             .map(|(i, vertex)| vertex + i as u32 / 6 * 4)
             .collect();
@@ -88,6 +113,7 @@ impl GraphicsContext {
             &[255, 255, 255, 255],
             TextureFormat::Rgba8,
             FilterMode::Nearest,
+            WrapMode::Clamp,
         )?;
 
         let default_filter_mode = FilterMode::Nearest;
@@ -98,6 +124,12 @@ impl GraphicsContext {
             shader::DEFAULT_FRAGMENT_SHADER,
         )?;
 
+        let default_instanced_shader = Shader::with_device(
+            device,
+            shader::DEFAULT_INSTANCED_VERTEX_SHADER,
+            shader::DEFAULT_FRAGMENT_SHADER,
+        )?;
+
         Ok(GraphicsContext {
             vertex_buffer,
             index_buffer,
@@ -105,19 +137,27 @@ impl GraphicsContext {
             texture: None,
             default_texture,
             default_filter_mode,
+            filter_mode: None,
+            default_wrap_mode: WrapMode::Clamp,
 
             shader: None,
             default_shader,
+            default_instanced_shader,
 
             canvas: None,
+            viewport: None,
 
-            projection_matrix: ortho(window_width as f32, window_height as f32, false),
+            projection_matrix: TargetOrigin::BottomLeft
+                .projection(window_width as f32, window_height as f32),
             transform_matrix: Mat4::identity(),
 
-            vertex_data: Vec::with_capacity(MAX_VERTICES),
+            vertex_data: Vec::with_capacity(max_vertices),
             element_count: 0,
+            max_indices,
 
             blend_state: BlendState::default(),
+
+            warm_up_canvas: None,
         })
     }
 }
@@ -130,6 +170,83 @@ pub fn clear(ctx: &mut Context, color: Color) {
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn push_quad(
     ctx: &mut Context,
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+    u1: f32,
+    v1: f32,
+    u2: f32,
+    v2: f32,
+    params: &DrawParams,
+) {
+    // TODO: This function really needs cleaning up before it can be exposed publicly.
+
+    let vertices = quad_vertices(x1, y1, x2, y2, u1, v1, u2, v2, params);
+
+    push_vertices(ctx, &vertices, params);
+}
+
+/// Pushes a quad that displays a region of the currently bound texture, sized to
+/// match the region.
+pub(crate) fn push_region(
+    ctx: &mut Context,
+    texture_size: (i32, i32),
+    region: Rectangle,
+    params: &DrawParams,
+) {
+    let vertices = region_vertices(texture_size, region, params);
+
+    push_vertices(ctx, &vertices, params);
+}
+
+fn push_vertices(ctx: &mut Context, vertices: &[Vertex; 4], params: &DrawParams) {
+    if ctx.graphics.element_count + 6 > ctx.graphics.max_indices {
+        flush(ctx);
+    }
+
+    // Changing the filter mode requires the sampler state to change, so it's treated
+    // the same way as switching textures.
+    if params.filter_mode != ctx.graphics.filter_mode {
+        flush(ctx);
+        ctx.graphics.filter_mode = params.filter_mode;
+    }
+
+    ctx.graphics.vertex_data.extend_from_slice(vertices);
+
+    ctx.graphics.element_count += 6;
+    ctx.device.render_stats_mut().quads += 1;
+}
+
+/// Calculates the vertices for a quad that displays a region of a texture, transformed
+/// by the given params.
+///
+/// The quad is the same size as the region, so the origin and rotation are relative to
+/// the region rather than the full texture.
+pub(crate) fn region_vertices(
+    texture_size: (i32, i32),
+    region: Rectangle,
+    params: &DrawParams,
+) -> [Vertex; 4] {
+    let texture_width = texture_size.0 as f32;
+    let texture_height = texture_size.1 as f32;
+
+    quad_vertices(
+        0.0,
+        0.0,
+        region.width,
+        region.height,
+        region.x / texture_width,
+        region.y / texture_height,
+        region.right() / texture_width,
+        region.bottom() / texture_height,
+        params,
+    )
+}
+
+/// Calculates the vertices for a quad, transformed by the given params.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn quad_vertices(
     x1: f32,
     y1: f32,
     x2: f32,
@@ -139,16 +256,10 @@ pub(crate) fn push_quad(
     mut u2: f32,
     mut v2: f32,
     params: &DrawParams,
-) {
+) -> [Vertex; 4] {
     // This function is a bit hairy, but it's more performant than doing the matrix math every
     // frame by a *lot* (at least going by the BunnyMark example). The logic is roughly based
     // on how FNA and LibGDX implement their spritebatches.
-    //
-    // TODO: This function really needs cleaning up before it can be exposed publicly.
-
-    if ctx.graphics.element_count + 6 > MAX_INDICES {
-        flush(ctx);
-    }
 
     let mut fx = (x1 - params.origin.x) * params.scale.x;
     let mut fy = (y1 - params.origin.y) * params.scale.y;
@@ -165,8 +276,29 @@ pub(crate) fn push_quad(
         std::mem::swap(&mut v1, &mut v2);
     }
 
-    // Branching here might be a bit of a premature optimization...
-    let (ox1, oy1, ox2, oy2, ox3, oy3, ox4, oy4) = if params.rotation == 0.0 {
+    // Skewing is rare enough that it's not worth specializing for, so it goes through
+    // the full affine transform (skew, then rotation) instead of the fast paths below.
+    let (ox1, oy1, ox2, oy2, ox3, oy3, ox4, oy4) = if let Some(skew) = params.skew {
+        let sin = params.rotation.sin();
+        let cos = params.rotation.cos();
+
+        let a = cos - (sin * skew.y);
+        let b = (cos * skew.x) - sin;
+        let c = sin + (cos * skew.y);
+        let d = (sin * skew.x) + cos;
+
+        (
+            params.position.x + (a * fx) + (b * fy),
+            params.position.y + (c * fx) + (d * fy),
+            params.position.x + (a * fx) + (b * fy2),
+            params.position.y + (c * fx) + (d * fy2),
+            params.position.x + (a * fx2) + (b * fy2),
+            params.position.y + (c * fx2) + (d * fy2),
+            params.position.x + (a * fx2) + (b * fy),
+            params.position.y + (c * fx2) + (d * fy),
+        )
+    } else if params.rotation == 0.0 {
+        // Branching here might be a bit of a premature optimization...
         (
             params.position.x + fx,
             params.position.y + fy,
@@ -192,14 +324,19 @@ pub(crate) fn push_quad(
         )
     };
 
-    ctx.graphics.vertex_data.extend_from_slice(&[
-        Vertex::new(Vec2::new(ox1, oy1), Vec2::new(u1, v1), params.color),
-        Vertex::new(Vec2::new(ox2, oy2), Vec2::new(u1, v2), params.color),
-        Vertex::new(Vec2::new(ox3, oy3), Vec2::new(u2, v2), params.color),
-        Vertex::new(Vec2::new(ox4, oy4), Vec2::new(u2, v1), params.color),
-    ]);
+    let vertex = |x, y, u, v| Vertex {
+        position: Vec2::new(x, y),
+        uv: Vec2::new(u, v),
+        color: params.color,
+        depth: params.depth,
+    };
 
-    ctx.graphics.element_count += 6;
+    [
+        vertex(ox1, oy1, u1, v1),
+        vertex(ox2, oy2, u1, v2),
+        vertex(ox3, oy3, u2, v2),
+        vertex(ox4, oy4, u2, v1),
+    ]
 }
 
 pub(crate) fn set_texture(ctx: &mut Context, texture: &Texture) {
@@ -210,6 +347,7 @@ pub(crate) fn set_texture_ex(ctx: &mut Context, texture: Option<&Texture>) {
     if texture != ctx.graphics.texture.as_ref() {
         flush(ctx);
         ctx.graphics.texture = texture.cloned();
+        ctx.device.render_stats_mut().texture_switches += 1;
     }
 }
 
@@ -249,6 +387,7 @@ pub(crate) fn set_shader_ex(ctx: &mut Context, shader: Option<&Shader>) {
     if shader != ctx.graphics.shader.as_ref() {
         flush(ctx);
         ctx.graphics.shader = shader.cloned();
+        ctx.device.render_stats_mut().shader_switches += 1;
     }
 }
 
@@ -272,30 +411,16 @@ pub(crate) fn set_canvas_ex(ctx: &mut Context, canvas: Option<&Canvas>) {
 
         ctx.graphics.canvas = canvas.cloned();
 
-        match &ctx.graphics.canvas {
-            None => {
-                let (width, height) = window::get_size(ctx);
-                let (physical_width, physical_height) = window::get_physical_size(ctx);
-
-                ctx.graphics.projection_matrix = ortho(width as f32, height as f32, false);
-                ctx.device.viewport(0, 0, physical_width, physical_height);
-
-                ctx.device.set_canvas(None);
-            }
-
-            Some(r) => {
-                let (width, height) = r.size();
+        ctx.device
+            .set_canvas(ctx.graphics.canvas.as_ref().map(|c| &*c.handle));
 
-                ctx.graphics.projection_matrix = ortho(width as f32, height as f32, true);
-                ctx.device.viewport(0, 0, width, height);
+        ctx.device.render_stats_mut().canvas_switches += 1;
 
-                ctx.device.set_canvas(Some(&r.handle));
-            }
-        }
+        apply_viewport(ctx);
     }
 }
 
-fn resolve_canvas(ctx: &mut Context) {
+pub(crate) fn resolve_canvas(ctx: &mut Context) {
     if let Some(c) = &ctx.graphics.canvas {
         if c.multisample.is_some() {
             ctx.device.resolve(&c.handle, &c.texture.data.handle);
@@ -303,6 +428,83 @@ fn resolve_canvas(ctx: &mut Context) {
     }
 }
 
+/// Submits a single draw call to the graphics hardware, using buffers that you are
+/// managing yourself.
+///
+/// This will [`flush`] any queued data first, and then draw the provided buffers
+/// immediately, without batching. Any fields of the [`DrawCall`](mesh::DrawCall)
+/// that are `None` will fall back to the same defaults as [`Mesh::draw`](mesh::Mesh::draw).
+///
+/// The params will be applied to the geometry as a whole, in the same way as when
+/// drawing a mesh.
+pub fn draw_buffers<P>(ctx: &mut Context, call: mesh::DrawCall<'_>, params: P)
+where
+    P: Into<DrawParams>,
+{
+    flush(ctx);
+
+    let texture = call.texture.unwrap_or(&ctx.graphics.default_texture);
+
+    let default_shader = if call.instance_buffer.is_some() {
+        &ctx.graphics.default_instanced_shader
+    } else {
+        &ctx.graphics.default_shader
+    };
+
+    let shader = call
+        .shader
+        .or(ctx.graphics.shader.as_ref())
+        .unwrap_or(default_shader);
+
+    let params = params.into();
+    let model_matrix = params.to_matrix();
+
+    // TODO: Failing to apply the defaults should be handled more gracefully than this,
+    // but we can't do that without breaking changes.
+    let _ = shader.set_default_uniforms(
+        &mut ctx.device,
+        ctx.graphics.projection_matrix * ctx.graphics.transform_matrix * model_matrix,
+        params.color,
+    );
+
+    ctx.device.cull_face(call.backface_culling);
+
+    ctx.device
+        .front_face(target_origin(ctx).front_face(call.winding));
+
+    let (start, count) = call.resolve_range();
+    let instances = call.resolve_instances();
+
+    if instances == 0 {
+        return;
+    }
+
+    let filter_override = params
+        .filter_mode
+        .filter(|&mode| mode != texture.filter_mode());
+
+    if let Some(mode) = filter_override {
+        ctx.device
+            .set_texture_filter_mode(&texture.data.handle, mode);
+    }
+
+    ctx.device.draw_instanced(
+        &call.vertex_buffer.handle,
+        call.index_buffer.map(|i| &*i.handle),
+        call.instance_buffer.map(|i| &*i.handle),
+        &texture.data.handle,
+        &shader.data.handle.borrow(),
+        start,
+        count,
+        instances,
+    );
+
+    if filter_override.is_some() {
+        ctx.device
+            .set_texture_filter_mode(&texture.data.handle, texture.filter_mode());
+    }
+}
+
 /// Sends queued data to the graphics hardware.
 ///
 /// You usually will not have to call this manually, as the graphics API will
@@ -332,12 +534,8 @@ pub fn flush(ctx: &mut Context) {
 
         ctx.device.cull_face(true);
 
-        // Because canvas rendering is effectively done upside-down, the winding order is the opposite
-        // of what you'd expect in that case.
-        ctx.device.front_face(match &ctx.graphics.canvas {
-            None => VertexWinding::CounterClockwise,
-            Some(_) => VertexWinding::Clockwise,
-        });
+        ctx.device
+            .front_face(target_origin(ctx).front_face(VertexWinding::CounterClockwise));
 
         ctx.device.set_vertex_buffer_data(
             &ctx.graphics.vertex_buffer,
@@ -345,17 +543,34 @@ pub fn flush(ctx: &mut Context) {
             0,
         );
 
+        let filter_override = ctx
+            .graphics
+            .filter_mode
+            .filter(|&mode| mode != texture.filter_mode());
+
+        if let Some(mode) = filter_override {
+            ctx.device
+                .set_texture_filter_mode(&texture.data.handle, mode);
+        }
+
         ctx.device.draw(
             &ctx.graphics.vertex_buffer,
             Some(&ctx.graphics.index_buffer),
             &texture.data.handle,
-            &shader.data.handle,
+            &shader.data.handle.borrow(),
             0,
             ctx.graphics.element_count,
         );
 
+        if filter_override.is_some() {
+            ctx.device
+                .set_texture_filter_mode(&texture.data.handle, texture.filter_mode());
+        }
+
         ctx.graphics.vertex_data.clear();
         ctx.graphics.element_count = 0;
+
+        ctx.device.render_stats_mut().flushes += 1;
     }
 }
 
@@ -365,11 +580,167 @@ pub fn flush(ctx: &mut Context) {
 /// don't rely on the state of one render carrying over to the next!
 ///
 /// You usually will not have to call this manually, as it is called for you at the end of every
-/// frame. Note that calling it will trigger a [`flush`] to the graphics hardware.
+/// frame. Note that calling it will trigger a [`flush`] to the graphics hardware, and will
+/// reset the [rendering statistics](get_stats).
 pub fn present(ctx: &mut Context) {
     flush(ctx);
 
     ctx.window.swap_buffers();
+
+    ctx.device.reset_render_stats();
+}
+
+/// Reads the current contents of the screen back from the GPU.
+///
+/// The returned [`ImageData`] will be the [physical size](window::get_physical_size) of
+/// the window, with the top row first and a format of [`TextureFormat::Rgba8`]. This
+/// is the case even if a canvas is currently active - use [`Canvas::get_data`] to read
+/// from a canvas instead.
+///
+/// This should be called at the end of [`State::draw`](crate::State::draw), after
+/// everything else has been rendered - the contents of the screen are undefined once
+/// the frame has been presented. Any pending draw calls will be flushed first.
+///
+/// This is a fairly slow operation, so avoid doing it too often!
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the underlying graphics API encounters an error.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tetra::graphics;
+/// # use tetra::input::{self, Key};
+/// # use tetra::Context;
+/// # fn draw(ctx: &mut Context) -> tetra::Result {
+/// if input::is_key_pressed(ctx, Key::F12) {
+///     graphics::read_screen(ctx)?.save("screenshot.png")?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_screen(ctx: &mut Context) -> Result<ImageData> {
+    if ctx.graphics.canvas.is_none() {
+        flush(ctx);
+    }
+
+    let (width, height) = window::get_physical_size(ctx);
+
+    // We copy into a texture rather than reading the backbuffer directly, as this
+    // also takes care of resolving it if it's multisampled.
+    let texture = Texture::with_device_empty(
+        &mut ctx.device,
+        width,
+        height,
+        FilterMode::Nearest,
+        WrapMode::Clamp,
+    )?;
+
+    ctx.device
+        .copy_to_texture(None, &texture.data.handle, false);
+
+    let mut data = texture.get_data(ctx);
+    data.flip_vertical();
+
+    Ok(data)
+}
+
+/// A resource that can be prepared for drawing via [`warm_up`].
+#[derive(Debug, Clone, Copy)]
+pub enum WarmUpItem<'a> {
+    /// A texture.
+    Texture(&'a Texture),
+
+    /// A shader.
+    Shader(&'a Shader),
+}
+
+impl WarmUpItem<'_> {
+    fn is_warmed_up(&self) -> bool {
+        match self {
+            WarmUpItem::Texture(texture) => texture.data.warmed_up.get(),
+            WarmUpItem::Shader(shader) => shader.data.warmed_up.get(),
+        }
+    }
+}
+
+/// Prepares resources for drawing, so that using them for the first time does not cause
+/// a hitch.
+///
+/// Graphics drivers often defer some of the work of uploading textures and compiling
+/// shaders until the first time they are used for drawing. This function forces that
+/// work to happen up front, by drawing each resource to a tiny off-screen canvas. This
+/// is useful for loading screens - the [`assets`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/assets.rs)
+/// example shows how the work can be spread across multiple frames, so that progress
+/// can be displayed.
+///
+/// Resources that have already been warmed up will be skipped, so it is safe to call
+/// this function multiple times with the same items. The current canvas, shader and
+/// transform matrix will be restored afterwards.
+///
+/// This function will trigger a [`flush`] to the graphics hardware.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the underlying graphics API encounters an error.
+pub fn warm_up(ctx: &mut Context, items: &[WarmUpItem<'_>]) -> Result {
+    if items.iter().all(WarmUpItem::is_warmed_up) {
+        return Ok(());
+    }
+
+    let canvas = match &ctx.graphics.warm_up_canvas {
+        Some(canvas) => canvas.clone(),
+        None => {
+            let canvas = Canvas::new(ctx, 1, 1)?;
+            ctx.graphics.warm_up_canvas = Some(canvas.clone());
+            canvas
+        }
+    };
+
+    let previous_canvas = ctx.graphics.canvas.clone();
+    let previous_shader = ctx.graphics.shader.clone();
+    let previous_transform = ctx.graphics.transform_matrix;
+
+    set_canvas(ctx, &canvas);
+    reset_transform_matrix(ctx);
+
+    for item in items.iter().filter(|item| !item.is_warmed_up()) {
+        match item {
+            WarmUpItem::Texture(texture) => {
+                reset_shader(ctx);
+
+                texture.draw(
+                    ctx,
+                    DrawParams::new().scale(Vec2::new(
+                        1.0 / texture.width() as f32,
+                        1.0 / texture.height() as f32,
+                    )),
+                );
+
+                flush(ctx);
+                texture.data.warmed_up.set(true);
+            }
+
+            WarmUpItem::Shader(shader) => {
+                set_shader(ctx, shader);
+
+                let texture = ctx.graphics.default_texture.clone();
+                texture.draw(ctx, Vec2::zero());
+
+                flush(ctx);
+                shader.data.warmed_up.set(true);
+            }
+        }
+    }
+
+    set_transform_matrix(ctx, previous_transform);
+    set_shader_ex(ctx, previous_shader.as_ref());
+    set_canvas_ex(ctx, previous_canvas.as_ref());
+
+    Ok(())
 }
 
 /// Returns the filter mode that will be used by newly created textures and canvases.
@@ -382,6 +753,16 @@ pub fn set_default_filter_mode(ctx: &mut Context, filter_mode: FilterMode) {
     ctx.graphics.default_filter_mode = filter_mode;
 }
 
+/// Returns the wrap mode that will be used by newly created textures and canvases.
+pub fn get_default_wrap_mode(ctx: &Context) -> WrapMode {
+    ctx.graphics.default_wrap_mode
+}
+
+/// Sets the wrap mode that will be used by newly created textures and canvases.
+pub fn set_default_wrap_mode(ctx: &mut Context, wrap_mode: WrapMode) {
+    ctx.graphics.default_wrap_mode = wrap_mode;
+}
+
 /// Information about the device currently being used to render graphics.
 #[derive(Debug, Clone)]
 pub struct GraphicsDeviceInfo {
@@ -406,6 +787,122 @@ pub fn get_device_info(ctx: &Context) -> GraphicsDeviceInfo {
     ctx.device.get_info()
 }
 
+/// Statistics about the GPU resources that are currently alive.
+///
+/// Byte counts are estimates, based on the size and format of each resource. Graphics
+/// drivers usually add some overhead on top of this (e.g. for padding or mipmaps), so
+/// the real amount of GPU memory used may be higher.
+///
+/// Resources created internally by Tetra (such as the buffers used for batching, or the
+/// glyph cache for a [`Font`](text::Font)) are included in these counts.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceStats {
+    /// The number of textures, including the textures used by canvases.
+    pub textures: usize,
+
+    /// The estimated size of all of the textures, in bytes.
+    pub texture_bytes: usize,
+
+    /// The number of vertex buffers.
+    pub vertex_buffers: usize,
+
+    /// The estimated size of all of the vertex buffers, in bytes.
+    pub vertex_buffer_bytes: usize,
+
+    /// The number of instance buffers.
+    pub instance_buffers: usize,
+
+    /// The estimated size of all of the instance buffers, in bytes.
+    pub instance_buffer_bytes: usize,
+
+    /// The number of index buffers.
+    pub index_buffers: usize,
+
+    /// The estimated size of all of the index buffers, in bytes.
+    pub index_buffer_bytes: usize,
+
+    /// The number of renderbuffers (used for multisampling and stencil buffers).
+    pub renderbuffers: usize,
+
+    /// The estimated size of all of the renderbuffers, in bytes.
+    pub renderbuffer_bytes: usize,
+
+    /// The number of canvases.
+    pub canvases: usize,
+
+    /// The number of shaders.
+    pub shaders: usize,
+}
+
+impl ResourceStats {
+    /// Returns the estimated size of all of the resources, in bytes.
+    pub fn total_bytes(&self) -> usize {
+        self.texture_bytes
+            + self.vertex_buffer_bytes
+            + self.instance_buffer_bytes
+            + self.index_buffer_bytes
+            + self.renderbuffer_bytes
+    }
+}
+
+/// Returns statistics about the GPU resources that are currently alive.
+///
+/// Resources are counted from when they are created until the last handle to them is
+/// dropped. This can be useful for detecting leaks, or for building a debug overlay.
+pub fn get_resource_stats(ctx: &Context) -> ResourceStats {
+    ctx.device.resource_stats()
+}
+
+/// Statistics about the rendering work that has been done during the current frame.
+///
+/// These counters are reset automatically when the frame is [presented](present), so
+/// they should be read at the end of [`State::draw`](crate::State::draw) to get the
+/// totals for a full frame. They can also be reset manually via [`reset_stats`], if
+/// you want to measure a smaller section of your rendering code.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of draw calls that have been sent to the graphics hardware.
+    pub draw_calls: usize,
+
+    /// The number of times that the sprite batch has been [flushed](flush).
+    pub flushes: usize,
+
+    /// The number of quads that have been added to the sprite batch.
+    pub quads: usize,
+
+    /// The number of times that the active texture has changed.
+    pub texture_switches: usize,
+
+    /// The number of times that the active shader has changed.
+    pub shader_switches: usize,
+
+    /// The number of times that the active canvas has changed.
+    pub canvas_switches: usize,
+
+    /// The number of bytes of vertex data that have been uploaded to the graphics hardware.
+    pub vertex_bytes_uploaded: usize,
+}
+
+/// Returns statistics about the rendering work that has been done since the last
+/// time that the frame was presented (or since [`reset_stats`] was called).
+///
+/// This can be useful for finding out why a frame is slow - for example, a large
+/// number of texture switches usually means that a scene could be batched more
+/// efficiently.
+pub fn get_stats(ctx: &Context) -> RenderStats {
+    ctx.device.render_stats()
+}
+
+/// Resets the rendering statistics returned by [`get_stats`].
+///
+/// You usually will not have to call this manually, as the statistics are reset
+/// every time the frame is [presented](present).
+pub fn reset_stats(ctx: &mut Context) {
+    ctx.device.reset_render_stats();
+}
+
 /// Returns the current transform matrix.
 pub fn get_transform_matrix(ctx: &Context) -> Mat4<f32> {
     ctx.graphics.transform_matrix
@@ -440,32 +937,14 @@ pub fn reset_transform_matrix(ctx: &mut Context) {
 pub fn set_scissor(ctx: &mut Context, scissor_rect: Rectangle<i32>) {
     flush(ctx);
 
-    match &ctx.graphics.canvas {
-        None => {
-            let physical_height = window::get_physical_height(ctx);
-
-            // OpenGL uses bottom-left co-ordinates, while Tetra uses
-            // top-left co-ordinates - to present a consistent API, we
-            // flip the Y component here.
-            ctx.device.scissor(
-                scissor_rect.x,
-                physical_height - (scissor_rect.y + scissor_rect.height),
-                scissor_rect.width,
-                scissor_rect.height,
-            );
-        }
+    let target_height = match &ctx.graphics.canvas {
+        None => window::get_physical_height(ctx),
+        Some(canvas) => canvas.height(),
+    };
 
-        Some(_) => {
-            // Canvas rendering is effectively done upside-down, so we don't
-            // need to flip the co-ordinates here.
-            ctx.device.scissor(
-                scissor_rect.x,
-                scissor_rect.y,
-                scissor_rect.width,
-                scissor_rect.height,
-            );
-        }
-    }
+    let rect = target_origin(ctx).to_device_rect(scissor_rect, target_height);
+
+    ctx.device.scissor(rect.x, rect.y, rect.width, rect.height);
 
     ctx.device.scissor_test(true);
 }
@@ -502,6 +981,41 @@ pub fn clear_stencil(ctx: &mut Context, value: u8) {
     ctx.device.clear_stencil(value);
 }
 
+/// Sets the global depth test behavior.
+///
+/// The depth buffer stores how close each pixel is to the screen. While a depth test
+/// is active, each pixel that is drawn will be compared against the value in the
+/// depth buffer, and discarded if it fails the test. This allows graphics to be
+/// sorted on the GPU, rather than having to draw them in back-to-front order.
+///
+/// The depth of a graphic can be set via [`DrawParams::depth`], or via the
+/// [`depth`](mesh::Vertex::depth) of each vertex when drawing a [`Mesh`](mesh::Mesh).
+///
+/// In order to use depth testing, you must be rendering to a target that was
+/// created with a depth buffer attached. To enable this for the main
+/// backbuffer, set [`ContextBuilder::depth_buffer`](crate::ContextBuilder::depth_buffer)
+/// to `true` when creating your context. To enable this for a canvas,
+/// initialize it via [`Canvas::builder`], with [`depth_buffer`](CanvasBuilder::depth_buffer)
+/// set to true.
+///
+/// Depth testing is [`Off`](DepthTest::Off) by default.
+///
+/// This function will trigger a [`flush`] to the graphics hardware.
+pub fn set_depth_test(ctx: &mut Context, test: DepthTest) {
+    flush(ctx);
+    ctx.device.set_depth_test(test);
+}
+
+/// Clears the depth buffer to the specified value.
+///
+/// The value will be clamped between `0.0` (closest to the screen) and `1.0` (furthest
+/// from the screen). When using [`DepthTest::Less`] or [`DepthTest::LessOrEqual`], you
+/// will usually want to clear to `1.0`.
+pub fn clear_depth(ctx: &mut Context, value: f32) {
+    flush(ctx);
+    ctx.device.clear_depth(value);
+}
+
 /// Sets which color components are drawn to the screen.
 ///
 /// This is useful in conjunction with [`set_stencil_state`]
@@ -512,29 +1026,127 @@ pub fn set_color_mask(ctx: &mut Context, red: bool, green: bool, blue: bool, alp
     ctx.device.set_color_mask(red, green, blue, alpha);
 }
 
+/// Sets the viewport, restricting rendering to a rectangle of the screen (or the current
+/// canvas, if one is active).
+///
+/// While a viewport is set, co-ordinate `(0, 0)` will be at the top-left of the viewport
+/// rectangle, and anything drawn outside of the viewport will be cut off. This can be
+/// useful for split-screen games, where each player's view is drawn to a different part
+/// of the window.
+///
+/// The rectangle is specified in the same co-ordinates as drawing - for the window, this
+/// means that it will be scaled to account for the [DPI scale](crate::window::get_dpi_scale).
+/// The viewport stays active when switching between the screen and canvases (in which
+/// case it is relative to whichever target is active), and if the window is resized.
+///
+/// Note that the viewport does not affect [`clear`], which always clears the whole target,
+/// or the position of the mouse. If you need to clear the area of the viewport, you can
+/// combine this with [`set_scissor`].
+///
+/// This function will trigger a [`flush`] to the graphics hardware.
+pub fn set_viewport(ctx: &mut Context, viewport: Rectangle<i32>) {
+    flush(ctx);
+
+    ctx.graphics.viewport = Some(viewport);
+    apply_viewport(ctx);
+}
+
+/// Resets the viewport, so that the whole screen (or the current canvas, if one is active)
+/// is rendered to.
+///
+/// This function will trigger a [`flush`] to the graphics hardware.
+pub fn reset_viewport(ctx: &mut Context) {
+    flush(ctx);
+
+    ctx.graphics.viewport = None;
+    apply_viewport(ctx);
+}
+
+/// Returns the current viewport, if one has been set via [`set_viewport`].
+pub fn get_viewport(ctx: &Context) -> Option<Rectangle<i32>> {
+    ctx.graphics.viewport
+}
+
 pub(crate) fn set_viewport_size(ctx: &mut Context) {
     if ctx.graphics.canvas.is_none() {
-        let (width, height) = window::get_size(ctx);
-        let (physical_width, physical_height) = window::get_physical_size(ctx);
+        apply_viewport(ctx);
+    }
+}
+
+/// Updates the projection matrix and the device's viewport to match the current
+/// render target and viewport.
+pub(crate) fn apply_viewport(ctx: &mut Context) {
+    let (size, device_size, origin) = match &ctx.graphics.canvas {
+        None => (
+            window::get_size(ctx),
+            window::get_physical_size(ctx),
+            TargetOrigin::BottomLeft,
+        ),
+
+        Some(canvas) => (canvas.size(), canvas.size(), canvas.origin),
+    };
+
+    let viewport = ctx
+        .graphics
+        .viewport
+        .unwrap_or_else(|| Rectangle::new(0, 0, size.0, size.1));
+
+    let device_rect =
+        origin.to_device_rect(scale_viewport(viewport, size, device_size), device_size.1);
+
+    ctx.graphics.projection_matrix =
+        origin.projection(viewport.width as f32, viewport.height as f32);
+
+    ctx.device.viewport(
+        device_rect.x,
+        device_rect.y,
+        device_rect.width,
+        device_rect.height,
+    );
+}
 
-        ctx.graphics.projection_matrix = ortho(width as f32, height as f32, false);
-        ctx.device.viewport(0, 0, physical_width, physical_height);
+/// Scales a viewport from the target's logical size to its size on the device.
+///
+/// The edges are scaled rather than the size, so that viewports which share an edge
+/// will still line up after rounding.
+fn scale_viewport(
+    viewport: Rectangle<i32>,
+    size: (i32, i32),
+    device_size: (i32, i32),
+) -> Rectangle<i32> {
+    if size == device_size {
+        return viewport;
     }
+
+    let scale_x = device_size.0 as f32 / size.0.max(1) as f32;
+    let scale_y = device_size.1 as f32 / size.1.max(1) as f32;
+
+    let left = (viewport.x as f32 * scale_x).round() as i32;
+    let top = (viewport.y as f32 * scale_y).round() as i32;
+    let right = ((viewport.x + viewport.width) as f32 * scale_x).round() as i32;
+    let bottom = ((viewport.y + viewport.height) as f32 * scale_y).round() as i32;
+
+    Rectangle::new(left, top, right - left, bottom - top)
+}
+
+/// Returns the origin of the current render target.
+///
+/// This will be [`TargetOrigin::BottomLeft`] when rendering to the screen, and the
+/// [`origin`](Canvas::origin) of the active canvas otherwise.
+pub fn get_target_origin(ctx: &Context) -> TargetOrigin {
+    target_origin(ctx)
 }
 
-pub(crate) fn ortho(width: f32, height: f32, flipped: bool) -> Mat4<f32> {
-    Mat4::orthographic_rh_no(FrustumPlanes {
-        left: 0.0,
-        right: width,
-        bottom: if flipped { 0.0 } else { height },
-        top: if flipped { height } else { 0.0 },
-        near: -1.0,
-        far: 1.0,
-    })
+pub(crate) fn target_origin(ctx: &Context) -> TargetOrigin {
+    match &ctx.graphics.canvas {
+        None => TargetOrigin::BottomLeft,
+        Some(canvas) => canvas.origin,
+    }
 }
 
 /// Defines a formula for blending two color or alpha values.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendOperation {
     /// Blends by adding the source and the destination together.
     ///
@@ -564,6 +1176,7 @@ pub enum BlendOperation {
 
 /// Defines a multiplier that will be applied to a color or alpha value before blending it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlendFactor {
     /// Each component will be multiplied by zero.
     ///
@@ -700,7 +1313,13 @@ pub enum BlendFactor {
 ///
 /// For a more in-depth explanation of blending, see this page on
 /// [Learn OpenGL](https://learnopengl.com/Advanced-OpenGL/Blending).
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlendState {
     /// The operation that should be applied to the RGB components of
     /// the source and destination colors.
@@ -885,6 +1504,50 @@ pub enum StencilTest {
     Always,
 }
 
+/// The test for whether a pixel is visible when using
+/// a depth buffer.
+///
+/// Each test compares the depth of the pixel being drawn against
+/// the value that is already in the depth buffer. If the pixel passes,
+/// it is drawn and its depth is written to the buffer.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepthTest {
+    /// Depth testing is disabled, and the depth buffer is not modified.
+    #[default]
+    Off,
+
+    /// The pixel is never visible.
+    Never,
+
+    /// The pixel is visible if its depth is less than the
+    /// value in the depth buffer.
+    Less,
+
+    /// The pixel is visible if its depth is less than or equal
+    /// to the value in the depth buffer.
+    LessOrEqual,
+
+    /// The pixel is visible if its depth is equal to the value
+    /// in the depth buffer.
+    Equal,
+
+    /// The pixel is visible if its depth is not equal to the
+    /// value in the depth buffer.
+    NotEqual,
+
+    /// The pixel is visible if its depth is greater than the
+    /// value in the depth buffer.
+    Greater,
+
+    /// The pixel is visible if its depth is greater than or equal
+    /// to the value in the depth buffer.
+    GreaterOrEqual,
+
+    /// The pixel is always visible.
+    Always,
+}
+
 /// How drawing operations should modify the stencil buffer.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -995,3 +1658,203 @@ impl StencilState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Vec3, Vec4};
+
+    #[test]
+    fn viewport_is_unchanged_without_scaling() {
+        let viewport = Rectangle::new(10, 20, 30, 40);
+
+        assert_eq!(scale_viewport(viewport, (640, 480), (640, 480)), viewport);
+    }
+
+    #[test]
+    fn viewport_scales_with_dpi() {
+        let viewport = Rectangle::new(320, 0, 320, 480);
+
+        assert_eq!(
+            scale_viewport(viewport, (640, 480), (1280, 960)),
+            Rectangle::new(640, 0, 640, 960)
+        );
+    }
+
+    #[test]
+    fn adjacent_viewports_share_edges_after_scaling() {
+        let left = scale_viewport(Rectangle::new(0, 0, 333, 100), (1000, 100), (1500, 150));
+        let right = scale_viewport(Rectangle::new(333, 0, 667, 100), (1000, 100), (1500, 150));
+
+        assert_eq!(left.x + left.width, right.x);
+        assert_eq!(right.x + right.width, 1500);
+    }
+
+    #[test]
+    fn viewport_is_flipped_for_bottom_left_targets() {
+        let viewport = scale_viewport(Rectangle::new(0, 0, 640, 240), (640, 480), (1280, 960));
+
+        assert_eq!(
+            TargetOrigin::BottomLeft.to_device_rect(viewport, 960),
+            Rectangle::new(0, 480, 1280, 480)
+        );
+    }
+
+    #[test]
+    fn region_vertices_match_equivalent_quad() {
+        let region = Rectangle::new(32.0, 16.0, 16.0, 32.0);
+
+        let params = DrawParams::new()
+            .position(Vec2::new(100.0, 50.0))
+            .origin(Vec2::new(8.0, 16.0))
+            .scale(Vec2::new(2.0, -1.0));
+
+        // The region is 32x32 once scaled, centered on the position. The negative Y scale
+        // flips it, so the top of the quad shows the bottom of the region.
+        let expected = [
+            (Vec2::new(84.0, 34.0), Vec2::new(0.5, 0.375)),
+            (Vec2::new(84.0, 66.0), Vec2::new(0.5, 0.125)),
+            (Vec2::new(116.0, 66.0), Vec2::new(0.75, 0.125)),
+            (Vec2::new(116.0, 34.0), Vec2::new(0.75, 0.375)),
+        ];
+
+        let vertices = region_vertices((64, 128), region, &params);
+
+        for (vertex, (position, uv)) in vertices.iter().zip(expected) {
+            assert_eq!(vertex.position, position);
+            assert_eq!(vertex.uv, uv);
+        }
+
+        // Rotating a quarter turn clockwise moves each corner along by one.
+        let vertices = region_vertices(
+            (64, 128),
+            region,
+            &params.rotation(std::f32::consts::FRAC_PI_2),
+        );
+
+        let expected = [
+            (Vec2::new(116.0, 34.0), Vec2::new(0.5, 0.375)),
+            (Vec2::new(84.0, 34.0), Vec2::new(0.5, 0.125)),
+            (Vec2::new(84.0, 66.0), Vec2::new(0.75, 0.125)),
+            (Vec2::new(116.0, 66.0), Vec2::new(0.75, 0.375)),
+        ];
+
+        for (vertex, (position, uv)) in vertices.iter().zip(expected) {
+            assert!(vertex.position.distance(position) < 0.001);
+            assert_eq!(vertex.uv, uv);
+        }
+    }
+
+    #[test]
+    fn region_origin_is_relative_to_region() {
+        let region = Rectangle::new(32.0, 0.0, 32.0, 32.0);
+
+        // Rotating a full turn around the center of the region should leave it in place.
+        let params = DrawParams::new()
+            .position(Vec2::new(16.0, 16.0))
+            .origin(Vec2::new(16.0, 16.0));
+
+        let unrotated = region_vertices((64, 32), region, &params);
+        let rotated = region_vertices((64, 32), region, &params.rotation(std::f32::consts::TAU));
+
+        for (a, b) in unrotated.iter().zip(rotated.iter()) {
+            assert!(a.position.distance(b.position) < 0.001);
+            assert_eq!(a.uv, b.uv);
+        }
+
+        let positions: Vec<Vec2<f32>> = unrotated.iter().map(|v| v.position).collect();
+
+        assert!(positions.contains(&Vec2::new(0.0, 0.0)));
+        assert!(positions.contains(&Vec2::new(32.0, 32.0)));
+    }
+
+    #[test]
+    fn skewed_quad_vertices_match_matrix() {
+        let params = DrawParams::new()
+            .position(Vec2::new(100.0, 50.0))
+            .origin(Vec2::new(8.0, 16.0))
+            .scale(Vec2::new(2.0, -1.0))
+            .skew(Vec2::new(0.5, -0.25))
+            .rotation(0.75);
+
+        // Origin, then scale, then skew, then rotation, then position.
+        let mut reference = Mat4::<f32>::translation_2d(-params.origin);
+        reference = Mat4::<f32>::scaling_3d(Vec3::<f32>::new(2.0, -1.0, 1.0)) * reference;
+        reference = Mat4::new(
+            1.0, 0.5, 0.0, 0.0, //
+            -0.25, 1.0, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            0.0, 0.0, 0.0, 1.0,
+        ) * reference;
+        reference = Mat4::rotation_z(0.75) * reference;
+        reference = Mat4::<f32>::translation_2d(params.position) * reference;
+
+        let vertices = quad_vertices(0.0, 0.0, 16.0, 32.0, 0.0, 0.0, 1.0, 1.0, &params);
+
+        for vertex in &vertices {
+            let local = Vec2::new(vertex.uv.x * 16.0, vertex.uv.y * 32.0);
+            let expected = (reference * Vec4::new(local.x, local.y, 0.0, 1.0)).xy();
+
+            assert!(vertex.position.distance(expected) < 0.001);
+
+            let from_params = (params.to_matrix() * Vec4::new(local.x, local.y, 0.0, 1.0)).xy();
+
+            assert!(vertex.position.distance(from_params) < 0.001);
+        }
+    }
+
+    #[test]
+    fn zero_skew_matches_fast_path() {
+        let params = DrawParams::new()
+            .position(Vec2::new(100.0, 50.0))
+            .origin(Vec2::new(8.0, 16.0))
+            .rotation(0.5);
+
+        let fast = quad_vertices(0.0, 0.0, 16.0, 32.0, 0.0, 0.0, 1.0, 1.0, &params);
+        let skewed = quad_vertices(
+            0.0,
+            0.0,
+            16.0,
+            32.0,
+            0.0,
+            0.0,
+            1.0,
+            1.0,
+            &params.clone().skew(Vec2::zero()),
+        );
+
+        for (a, b) in fast.iter().zip(skewed.iter()) {
+            assert!(a.position.distance(b.position) < 0.001);
+            assert_eq!(a.uv, b.uv);
+        }
+    }
+
+    #[test]
+    fn quad_vertices_use_params_depth() {
+        let vertices = quad_vertices(0.0, 0.0, 16.0, 16.0, 0.0, 0.0, 1.0, 1.0, &DrawParams::new());
+
+        assert!(vertices.iter().all(|v| v.depth == 0.0));
+
+        let params = DrawParams::new().depth(-0.5);
+        let vertices = quad_vertices(0.0, 0.0, 16.0, 16.0, 0.0, 0.0, 1.0, 1.0, &params);
+
+        assert!(vertices.iter().all(|v| v.depth == -0.5));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn draw_state_serde_round_trip() {
+        let blend = BlendState::add(true);
+        let json = serde_json::to_string(&blend).unwrap();
+        assert_eq!(blend, serde_json::from_str::<BlendState>(&json).unwrap());
+
+        let params = DrawParams::new()
+            .position(Vec2::new(10.0, 20.0))
+            .rotation(1.5)
+            .color(Color::RED)
+            .filter_mode(FilterMode::Linear);
+
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(params, serde_json::from_str::<DrawParams>(&json).unwrap());
+    }
+}
@@ -2,17 +2,25 @@
 
 use std::time::Duration;
 
-use crate::graphics::texture::Texture;
+use crate::graphics::texture::{region_fits, Texture, TextureRegion};
 use crate::graphics::{DrawParams, Rectangle};
 use crate::time;
 use crate::Context;
 
-/// An animation, cycling between regions of a texture at a regular interval.
+/// An animation, cycling between regions of a texture.
 ///
 /// Calling [`advance`](Self::advance) or [`advance`](Self::advance_by) within [`State::draw`](crate::State::draw)
 /// will drive the animation, switching the texture region once the specified
 /// time has passed.
 ///
+/// By default, every frame of the animation is displayed for the same amount of time.
+/// If you need some frames to last longer than others (e.g. for the wind-up of an
+/// attack), you can create the animation via [`from_frames`](Self::from_frames), or
+/// set the length of each frame via [`set_frame_lengths`](Self::set_frame_lengths).
+///
+/// Animations play their frames in order by default, but they can also be played in
+/// reverse, or back and forth, by setting an [`AnimationMode`].
+///
 /// # Examples
 ///
 /// The [`animation`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/animation.rs)
@@ -25,37 +33,158 @@ use crate::Context;
 pub struct Animation {
     texture: Texture,
     frames: Vec<Rectangle>,
-    frame_length: Duration,
+    frame_lengths: Vec<Duration>,
 
     current_frame: usize,
     timer: Duration,
     repeating: bool,
+    mode: AnimationMode,
+    reversed: bool,
 }
 
 impl Animation {
     /// Creates a new looping animation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the frames are outside the bounds of the texture.
     pub fn new(texture: Texture, frames: Vec<Rectangle>, frame_length: Duration) -> Animation {
+        check_frames(&frames, texture.size());
+
         Animation {
             texture,
+            frame_lengths: vec![frame_length; frames.len()],
             frames,
-            frame_length,
 
             current_frame: 0,
             timer: Duration::from_secs(0),
             repeating: true,
+            mode: AnimationMode::Forward,
+            reversed: false,
         }
     }
 
+    /// Creates a new looping animation from a list of texture regions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list of regions is empty, or if the regions do not all belong
+    /// to the same texture.
+    pub fn from_regions(regions: &[TextureRegion], frame_length: Duration) -> Animation {
+        let texture = regions
+            .first()
+            .expect("animation should have at least one frame")
+            .texture()
+            .clone();
+
+        let frames = regions
+            .iter()
+            .map(|region| {
+                assert!(
+                    region.texture() == &texture,
+                    "animation frames should all belong to the same texture"
+                );
+
+                region.bounds()
+            })
+            .collect();
+
+        Animation::new(texture, frames, frame_length)
+    }
+
     /// Creates a new animation that does not repeat once all of the frames have been displayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the frames are outside the bounds of the texture.
     pub fn once(texture: Texture, frames: Vec<Rectangle>, frame_length: Duration) -> Animation {
+        check_frames(&frames, texture.size());
+
         Animation {
             texture,
+            frame_lengths: vec![frame_length; frames.len()],
             frames,
-            frame_length,
 
             current_frame: 0,
             timer: Duration::from_secs(0),
             repeating: false,
+            mode: AnimationMode::Forward,
+            reversed: false,
+        }
+    }
+
+    /// Creates a new looping animation, where each frame is displayed for its own length
+    /// of time.
+    ///
+    /// To create an animation that does not repeat, call
+    /// [`set_repeating(false)`](Self::set_repeating) on the result.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the frames are outside the bounds of the texture.
+    pub fn from_frames(texture: Texture, frames: Vec<(Rectangle, Duration)>) -> Animation {
+        let (frames, frame_lengths): (Vec<_>, _) = frames.into_iter().unzip();
+
+        check_frames(&frames, texture.size());
+
+        Animation {
+            texture,
+            frames,
+            frame_lengths,
+
+            current_frame: 0,
+            timer: Duration::from_secs(0),
+            repeating: true,
+            mode: AnimationMode::Forward,
+            reversed: false,
+        }
+    }
+
+    /// Creates a new animation from a plain-data description of its frames.
+    ///
+    /// This can be used alongside [`to_data`](Self::to_data) to load animations from
+    /// a save file or level format, as [`AnimationData`] does not contain the texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of frame lengths does not match the number of frames, or if
+    /// any of the frames are outside the bounds of the texture.
+    pub fn from_data(texture: Texture, data: AnimationData) -> Animation {
+        assert_eq!(
+            data.frame_lengths.len(),
+            data.frames.len(),
+            "animation should have one length per frame"
+        );
+
+        check_frames(&data.frames, texture.size());
+
+        let mut animation = Animation {
+            texture,
+            frames: data.frames,
+            frame_lengths: data.frame_lengths,
+
+            current_frame: 0,
+            timer: Duration::from_secs(0),
+            repeating: data.repeating,
+            mode: data.mode,
+            reversed: false,
+        };
+
+        animation.restart();
+        animation
+    }
+
+    /// Returns a plain-data description of the animation's frames, which does not
+    /// include the texture.
+    ///
+    /// The playback state of the animation (i.e. the current frame and timer) is
+    /// not included.
+    pub fn to_data(&self) -> AnimationData {
+        AnimationData {
+            frames: self.frames.clone(),
+            frame_lengths: self.frame_lengths.clone(),
+            repeating: self.repeating,
+            mode: self.mode,
         }
     }
 
@@ -69,6 +198,16 @@ impl Animation {
         self.texture.draw_region(ctx, frame, params);
     }
 
+    /// Returns the region of the texture that is currently being displayed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current frame is no longer within the bounds of the texture (e.g.
+    /// because the texture belongs to a canvas that has since been resized).
+    pub fn current_region(&self) -> TextureRegion {
+        self.texture.region(self.frames[self.current_frame])
+    }
+
     /// Advances the animation's timer, switching the texture region if required.
     ///
     /// This method uses the current [delta time](crate::time::get_delta_time)
@@ -80,26 +219,35 @@ impl Animation {
     /// Advances the animation's timer by a specified amount, switching the texture
     /// region if required.
     ///
-    /// If the specified duration is longer than the frame length, frames will be
-    /// skipped.
+    /// If the specified duration is longer than the current frame's length, frames will be
+    /// skipped. If the animation is not repeating, it will stop on the final frame of the
+    /// cycle (see [`AnimationMode`] for details).
     pub fn advance_by(&mut self, duration: Duration) {
-        self.timer += duration;
-
-        let frames_remaining = self.has_frames_remaining();
+        let (frame, reversed, timer) = advance_frames(
+            &self.frame_lengths,
+            self.current_frame,
+            self.reversed,
+            self.timer + duration,
+            self.mode,
+            self.repeating,
+        );
 
-        if frames_remaining || self.repeating {
-            while self.timer >= self.frame_length {
-                self.current_frame = (self.current_frame + 1) % self.frames.len();
-                self.timer -= self.frame_length;
-            }
-        } else if self.timer > self.frame_length {
-            self.timer = self.frame_length;
-        }
+        self.current_frame = frame;
+        self.reversed = reversed;
+        self.timer = timer;
     }
 
-    /// Restarts the animation from the first frame.
+    /// Restarts the animation from the beginning.
+    ///
+    /// For most modes, this is the first frame, but for [`AnimationMode::Reverse`],
+    /// it is the last frame.
     pub fn restart(&mut self) {
-        self.current_frame = 0;
+        self.reversed = self.mode == AnimationMode::Reverse;
+        self.current_frame = if self.reversed {
+            self.frames.len().saturating_sub(1)
+        } else {
+            0
+        };
         self.timer = Duration::from_secs(0);
     }
 
@@ -113,7 +261,13 @@ impl Animation {
     /// This method will not change the frame definitions or current state of the animation,
     /// so it can be used for e.g. swapping spritesheets. If you need to change the slicing
     /// for the new texture, call [`set_frames`](Self::set_frames).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the existing frames are outside the bounds of the new texture.
     pub fn set_texture(&mut self, texture: Texture) {
+        check_frames(&self.frames, texture.size());
+
         self.texture = texture;
     }
 
@@ -124,21 +278,87 @@ impl Animation {
 
     /// Sets the sections of the texture being displayed for each frame of the animation.
     ///
+    /// If the number of frames changes, the existing frame lengths are kept for the frames
+    /// that still exist, and any new frames are given the same length as the current last
+    /// frame. Use [`set_frames_with_lengths`](Self::set_frames_with_lengths) if you need
+    /// to change both at once.
+    ///
     /// This method will reset the animation back to frame zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the frames are outside the bounds of the texture.
     pub fn set_frames(&mut self, new_frames: Vec<Rectangle>) {
+        check_frames(&new_frames, self.texture.size());
+
+        let last_length = self.frame_lengths.last().copied().unwrap_or_default();
+
+        self.frame_lengths.resize(new_frames.len(), last_length);
         self.frames = new_frames;
 
         self.restart();
     }
 
-    /// Gets the amount of time that each frame of the animation lasts for.
+    /// Sets the sections of the texture being displayed for each frame of the animation,
+    /// along with how long each frame lasts for.
+    ///
+    /// This method will reset the animation back to frame zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the frames are outside the bounds of the texture.
+    pub fn set_frames_with_lengths(&mut self, new_frames: Vec<(Rectangle, Duration)>) {
+        let (frames, frame_lengths): (Vec<_>, _) = new_frames.into_iter().unzip();
+
+        check_frames(&frames, self.texture.size());
+
+        self.frames = frames;
+        self.frame_lengths = frame_lengths;
+
+        self.restart();
+    }
+
+    /// Gets the amount of time that the current frame of the animation lasts for.
+    ///
+    /// If all of the frames are the same length (e.g. because the animation was created
+    /// via [`new`](Self::new)), this is the length of every frame. Otherwise, use
+    /// [`frame_lengths`](Self::frame_lengths) to get the length of each individual frame.
     pub fn frame_length(&self) -> Duration {
-        self.frame_length
+        self.frame_lengths
+            .get(self.current_frame)
+            .copied()
+            .unwrap_or_default()
     }
 
     /// Sets the amount of time that each frame of the animation lasts for.
+    ///
+    /// This will replace any lengths that were set for individual frames.
     pub fn set_frame_length(&mut self, new_frame_length: Duration) {
-        self.frame_length = new_frame_length;
+        self.frame_lengths.fill(new_frame_length);
+    }
+
+    /// Gets the amount of time that each individual frame of the animation lasts for.
+    ///
+    /// The lengths are in the same order as the [`frames`](Self::frames).
+    pub fn frame_lengths(&self) -> &[Duration] {
+        &self.frame_lengths
+    }
+
+    /// Sets the amount of time that each individual frame of the animation lasts for.
+    ///
+    /// The lengths should be in the same order as the [`frames`](Self::frames).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of lengths does not match the number of frames.
+    pub fn set_frame_lengths(&mut self, new_frame_lengths: Vec<Duration>) {
+        assert_eq!(
+            new_frame_lengths.len(),
+            self.frames.len(),
+            "animation should have one length per frame"
+        );
+
+        self.frame_lengths = new_frame_lengths;
     }
 
     /// Gets whether or not the animation is currently set to repeat when it reaches the end
@@ -148,10 +368,28 @@ impl Animation {
     }
 
     /// Sets whether or not the animation should repeat when it reaches the end of the frames.
+    ///
+    /// If this is set to `false` while the animation is running, it will continue until
+    /// it reaches the final frame, and then stop there.
+    ///
+    /// This has no effect when using [`AnimationMode::PingPongOnce`], which never repeats.
     pub fn set_repeating(&mut self, repeating: bool) {
         self.repeating = repeating;
     }
 
+    /// Gets the order in which the animation's frames are played.
+    pub fn mode(&self) -> AnimationMode {
+        self.mode
+    }
+
+    /// Sets the order in which the animation's frames are played.
+    ///
+    /// This method will [restart](Self::restart) the animation.
+    pub fn set_mode(&mut self, mode: AnimationMode) {
+        self.mode = mode;
+        self.restart();
+    }
+
     /// Gets the index of the frame that is currently being displayed.
     ///
     /// This index is zero-based, and can be used in combination with the [`frames`](Self::frames)
@@ -181,7 +419,7 @@ impl Animation {
     /// Gets the duration that the current frame has been visible.
     ///
     /// This can be used in combination with the [`frame_length`](Self::frame_length) method
-    /// in order to track the progress of the animation.
+    /// in order to track the progress of the current frame.
     pub fn current_frame_time(&self) -> Duration {
         self.timer
     }
@@ -194,21 +432,420 @@ impl Animation {
     ///
     /// The animation will not advance past the end of the current frame until the next call
     /// to [`advance`](Self::advance) or [`advance`](Self::advance_by). If a value is given
-    /// that is larger than [`frame_length`](Self::frame_length), this animation may
-    /// skip frames.
+    /// that is larger than the current [`frame_length`](Self::frame_length), this animation
+    /// may skip frames.
     pub fn set_current_frame_time(&mut self, duration: Duration) {
         self.timer = duration;
     }
 
     /// Returns true if this animation will no longer advance.
     ///
-    /// Will always be false for repeating animations.
+    /// Will always be false for repeating animations, unless the mode is
+    /// [`AnimationMode::PingPongOnce`].
     pub fn is_finished(&self) -> bool {
-        !self.repeating && !self.has_frames_remaining()
+        !self.mode.repeats(self.repeating) && !self.has_frames_remaining()
     }
 
     /// Returns true if there are any frames remaining in the current cycle.
     pub fn has_frames_remaining(&self) -> bool {
-        self.current_frame < self.frames.len() - 1
+        next_frame(
+            self.current_frame,
+            self.frames.len() - 1,
+            self.reversed,
+            self.mode,
+            false,
+        )
+        .is_some()
+    }
+}
+
+/// The order in which an [`Animation`]'s frames are played.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[non_exhaustive]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnimationMode {
+    /// The frames are played from first to last.
+    ///
+    /// If the animation is not repeating, it will stop on the last frame.
+    #[default]
+    Forward,
+
+    /// The frames are played from last to first.
+    ///
+    /// If the animation is not repeating, it will stop on the first frame.
+    Reverse,
+
+    /// The frames are played from first to last, and then back again (e.g. `0, 1, 2, 1, 0, 1, ...`).
+    ///
+    /// If the animation is not repeating, it will stop once it returns to the first frame.
+    PingPong,
+
+    /// The frames are played from first to last, and then back again, stopping once the
+    /// animation returns to the first frame.
+    ///
+    /// This is equivalent to [`PingPong`](Self::PingPong) on a non-repeating animation, and
+    /// will never repeat, regardless of the animation's [`repeating`](Animation::repeating)
+    /// setting.
+    PingPongOnce,
+}
+
+impl AnimationMode {
+    fn repeats(self, repeating: bool) -> bool {
+        repeating && self != AnimationMode::PingPongOnce
+    }
+}
+
+/// A plain-data description of an [`Animation`], without the texture.
+///
+/// This can be created via [`Animation::to_data`], and turned back into an animation
+/// via [`Animation::from_data`].
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnimationData {
+    /// The sections of the texture being displayed for each frame of the animation.
+    pub frames: Vec<Rectangle>,
+
+    /// The amount of time that each frame of the animation lasts for.
+    ///
+    /// This should contain one entry per frame.
+    pub frame_lengths: Vec<Duration>,
+
+    /// Whether or not the animation repeats when it reaches the end of the frames.
+    pub repeating: bool,
+
+    /// The order in which the animation's frames are played.
+    ///
+    /// When deserializing, this defaults to [`AnimationMode::Forward`] if it is not present.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub mode: AnimationMode,
+}
+
+/// Moves an animation through its frames, until the timer is within the
+/// length of the current frame.
+///
+/// Returns the new frame index, whether the animation is now playing backwards,
+/// and the amount of time that has been spent on the frame.
+fn advance_frames(
+    frame_lengths: &[Duration],
+    mut frame: usize,
+    mut reversed: bool,
+    mut timer: Duration,
+    mode: AnimationMode,
+    repeating: bool,
+) -> (usize, bool, Duration) {
+    let repeating = mode.repeats(repeating);
+
+    // If every frame has a length of zero, a repeating animation would never
+    // settle on a frame.
+    if repeating && frame_lengths.iter().all(Duration::is_zero) {
+        return (frame, reversed, Duration::ZERO);
+    }
+
+    let last = frame_lengths.len().saturating_sub(1);
+
+    while let Some(&length) = frame_lengths.get(frame) {
+        if timer < length {
+            break;
+        }
+
+        match next_frame(frame, last, reversed, mode, repeating) {
+            Some((next, next_reversed)) => {
+                frame = next;
+                reversed = next_reversed;
+            }
+
+            None => {
+                // Non-repeating animations stop at the end of the final frame.
+                timer = length;
+                break;
+            }
+        }
+
+        timer -= length;
+    }
+
+    (frame, reversed, timer)
+}
+
+/// Returns the frame that comes after the given frame, and whether the animation will
+/// be playing backwards once it gets there.
+///
+/// Returns `None` if the end of the cycle has been reached and the animation does
+/// not repeat.
+fn next_frame(
+    frame: usize,
+    last: usize,
+    reversed: bool,
+    mode: AnimationMode,
+    repeating: bool,
+) -> Option<(usize, bool)> {
+    match mode {
+        AnimationMode::Forward if frame < last => Some((frame + 1, false)),
+        AnimationMode::Forward if repeating => Some((0, false)),
+
+        AnimationMode::Reverse if frame > 0 => Some((frame - 1, true)),
+        AnimationMode::Reverse if repeating => Some((last, true)),
+
+        AnimationMode::PingPong | AnimationMode::PingPongOnce => {
+            if !reversed && frame < last {
+                Some((frame + 1, false))
+            } else if frame > 0 {
+                Some((frame - 1, true))
+            } else if repeating {
+                // The first frame is only shown once per cycle, so the next cycle
+                // starts from the second frame (if there is one).
+                Some((usize::min(1, last), false))
+            } else {
+                None
+            }
+        }
+
+        _ => None,
+    }
+}
+
+/// Checks that all of the frames lie within a texture of the given size.
+///
+/// This is done when the animation is built, so that bad frame data is caught
+/// straight away, rather than being drawn incorrectly.
+fn check_frames(frames: &[Rectangle], (width, height): (i32, i32)) {
+    assert!(
+        frames
+            .iter()
+            .all(|&frame| region_fits(frame, width as f32, height as f32)),
+        "animation frames should be within the bounds of the texture"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn forward(
+        lengths: &[Duration],
+        frame: usize,
+        timer: Duration,
+        repeating: bool,
+    ) -> (usize, Duration) {
+        let (frame, _, timer) = advance_frames(
+            lengths,
+            frame,
+            false,
+            timer,
+            AnimationMode::Forward,
+            repeating,
+        );
+
+        (frame, timer)
+    }
+
+    /// Advances from the start of an animation in steps of `step`, returning the frame
+    /// that is displayed after each step.
+    fn simulate(
+        frames: usize,
+        mode: AnimationMode,
+        repeating: bool,
+        step: Duration,
+        steps: usize,
+    ) -> Vec<usize> {
+        let lengths = vec![ms(100); frames];
+
+        let mut reversed = mode == AnimationMode::Reverse;
+        let mut frame = if reversed { frames - 1 } else { 0 };
+        let mut timer = Duration::ZERO;
+
+        (0..steps)
+            .map(|_| {
+                (frame, reversed, timer) =
+                    advance_frames(&lengths, frame, reversed, timer + step, mode, repeating);
+
+                frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn advance_across_variable_length_frames() {
+        let lengths = [ms(500), ms(50), ms(50), ms(100)];
+
+        assert_eq!(forward(&lengths, 0, ms(499), true), (0, ms(499)));
+        assert_eq!(forward(&lengths, 0, ms(500), true), (1, ms(0)));
+
+        // A single large step can move through several frames at once.
+        assert_eq!(forward(&lengths, 0, ms(620), true), (3, ms(20)));
+
+        // Time carries over from a partially complete frame.
+        assert_eq!(forward(&lengths, 1, ms(90), true), (2, ms(40)));
+    }
+
+    #[test]
+    fn advance_wraps_repeating_animations() {
+        let lengths = [ms(500), ms(50), ms(50), ms(100)];
+
+        assert_eq!(forward(&lengths, 3, ms(100), true), (0, ms(0)));
+        assert_eq!(forward(&lengths, 0, ms(1410), true), (0, ms(10)));
+    }
+
+    #[test]
+    fn advance_clamps_non_repeating_animations() {
+        let lengths = [ms(500), ms(50), ms(50), ms(100)];
+
+        assert_eq!(forward(&lengths, 0, ms(620), false), (3, ms(20)));
+        assert_eq!(forward(&lengths, 0, ms(5000), false), (3, ms(100)));
+        assert_eq!(forward(&lengths, 3, ms(150), false), (3, ms(100)));
+    }
+
+    #[test]
+    fn advance_with_zero_length_frames() {
+        let lengths = [ms(100), ms(0), ms(100)];
+
+        assert_eq!(forward(&lengths, 0, ms(150), true), (2, ms(50)));
+
+        let zero = [ms(0), ms(0)];
+
+        assert_eq!(forward(&zero, 0, ms(150), true), (0, ms(0)));
+        assert_eq!(forward(&zero, 0, ms(150), false), (1, ms(0)));
+    }
+
+    #[test]
+    fn advance_in_reverse() {
+        assert_eq!(
+            simulate(3, AnimationMode::Reverse, true, ms(100), 5),
+            [1, 0, 2, 1, 0]
+        );
+
+        assert_eq!(
+            simulate(3, AnimationMode::Reverse, false, ms(100), 4),
+            [1, 0, 0, 0]
+        );
+
+        // Larger steps skip frames.
+        assert_eq!(
+            simulate(3, AnimationMode::Reverse, true, ms(250), 3),
+            [0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn advance_ping_pong() {
+        assert_eq!(
+            simulate(3, AnimationMode::PingPong, true, ms(100), 9),
+            [1, 2, 1, 0, 1, 2, 1, 0, 1]
+        );
+
+        // A step larger than a full cycle (0, 1, 2, 1) wraps around.
+        assert_eq!(
+            simulate(3, AnimationMode::PingPong, true, ms(500), 3),
+            [1, 2, 1]
+        );
+
+        assert_eq!(
+            simulate(2, AnimationMode::PingPong, true, ms(100), 4),
+            [1, 0, 1, 0]
+        );
+    }
+
+    #[test]
+    fn non_repeating_ping_pong_finishes_on_first_frame() {
+        assert_eq!(
+            simulate(3, AnimationMode::PingPongOnce, true, ms(100), 6),
+            [1, 2, 1, 0, 0, 0]
+        );
+
+        assert_eq!(
+            simulate(3, AnimationMode::PingPong, false, ms(100), 6),
+            [1, 2, 1, 0, 0, 0]
+        );
+
+        let lengths = [ms(100); 3];
+
+        assert_eq!(
+            advance_frames(
+                &lengths,
+                0,
+                false,
+                ms(5000),
+                AnimationMode::PingPongOnce,
+                true
+            ),
+            (0, true, ms(100))
+        );
+    }
+
+    #[test]
+    fn ping_pong_with_single_frame() {
+        assert_eq!(
+            simulate(1, AnimationMode::PingPong, true, ms(250), 2),
+            [0, 0]
+        );
+        assert_eq!(
+            simulate(1, AnimationMode::PingPongOnce, true, ms(250), 2),
+            [0, 0]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn animation_data_round_trip() {
+        let data = AnimationData {
+            frames: vec![
+                Rectangle::new(0.0, 0.0, 16.0, 16.0),
+                Rectangle::new(16.0, 0.0, 16.0, 16.0),
+            ],
+            frame_lengths: vec![ms(100), ms(250)],
+            repeating: false,
+            mode: AnimationMode::PingPong,
+        };
+
+        let json = serde_json::to_string(&data).unwrap();
+        let result: AnimationData = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(data, result);
+    }
+
+    #[test]
+    fn frames_within_texture_are_accepted() {
+        check_frames(&[], (64, 64));
+
+        check_frames(
+            &[
+                Rectangle::new(0.0, 0.0, 64.0, 64.0),
+                Rectangle::new(16.0, 16.0, 16.0, 16.0),
+                Rectangle::new(48.0, 48.0, 16.0, 16.0),
+            ],
+            (64, 64),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn frames_overlapping_texture_edge_are_rejected() {
+        check_frames(
+            &[
+                Rectangle::new(0.0, 0.0, 16.0, 16.0),
+                Rectangle::new(48.0, -8.0, 32.0, 32.0),
+            ],
+            (64, 64),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn frames_outside_texture_are_rejected() {
+        check_frames(&[Rectangle::new(128.0, 16.0, 16.0, 16.0)], (64, 64));
     }
 }
@@ -1,6 +1,11 @@
+use std::time::Duration;
+
+use super::particles::Rng;
+use super::scaling::ScreenScaler;
 use super::Rectangle;
 use crate::input;
 use crate::math::{Mat4, Vec2, Vec3};
+use crate::time;
 use crate::window;
 use crate::Context;
 
@@ -13,6 +18,16 @@ use crate::Context;
 /// The camera's matrix is cached internally as an optimization. After adjusting parameters
 /// on the camera, you can call the `update` method to recalculate the matrix.
 ///
+/// # Effects
+///
+/// The camera also has built-in support for some common effects:
+///
+/// * [`shake`](Self::shake) will shake the view for a period of time. This requires
+///   [`advance`](Self::advance) or [`advance_by`](Self::advance_by) to be called every update.
+/// * [`follow`](Self::follow) will smoothly move the camera towards a target.
+/// * [`set_bounds`](Self::set_bounds) will stop the camera from showing anything outside of an
+///   area of the world.
+///
 /// # Examples
 ///
 /// The [`camera`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/camera.rs)
@@ -54,6 +69,13 @@ pub struct Camera {
     pub viewport_height: f32,
 
     matrix: Mat4<f32>,
+    bounds: Option<Rectangle>,
+
+    shake_magnitude: f32,
+    shake_duration: Duration,
+    shake_trauma: f32,
+    shake_offset: Vec2<f32>,
+    rng: Rng,
 }
 
 impl Camera {
@@ -70,6 +92,13 @@ impl Camera {
             viewport_height,
 
             matrix: Mat4::translation_2d(Vec2::new(viewport_width / 2.0, viewport_height / 2.0)),
+            bounds: None,
+
+            shake_magnitude: 0.0,
+            shake_duration: Duration::ZERO,
+            shake_trauma: 0.0,
+            shake_offset: Vec2::zero(),
+            rng: Rng::new(0),
         }
     }
 
@@ -100,7 +129,20 @@ impl Camera {
 
     /// Recalculates the transformation matrix, based on the data currently contained
     /// within the camera.
+    ///
+    /// If [bounds](Self::set_bounds) have been set, the camera's position will also be
+    /// clamped, so that the visible area stays within them.
     pub fn update(&mut self) {
+        if let Some(bounds) = self.bounds {
+            let visible = self.visible_rect();
+
+            self.position = clamp_to_bounds(
+                self.position,
+                Vec2::new(visible.width.abs(), visible.height.abs()),
+                bounds,
+            );
+        }
+
         self.matrix = Mat4::translation_2d(-self.position);
         self.matrix.rotate_z(self.rotation);
         self.matrix
@@ -109,6 +151,111 @@ impl Camera {
             self.viewport_width / 2.0,
             self.viewport_height / 2.0,
         ));
+        self.matrix.translate_2d(self.shake_offset);
+    }
+
+    /// Starts shaking the camera.
+    ///
+    /// The `magnitude` is the maximum distance (in screen pixels) that the view will be offset by.
+    /// The shake will fade out over the given `duration`, replacing any shake that is already in
+    /// progress.
+    ///
+    /// The shake is driven by [`advance`](Self::advance) or [`advance_by`](Self::advance_by), which
+    /// should be called every update. It only affects the [matrix](Self::as_matrix) - the camera's
+    /// `position` is not changed, and the results of [`project`](Self::project) and
+    /// [`unproject`](Self::unproject) do not include the shake, so that mouse picking does not jitter.
+    pub fn shake(&mut self, magnitude: f32, duration: Duration) {
+        self.shake_magnitude = magnitude;
+        self.shake_duration = duration;
+        self.shake_trauma = 1.0;
+    }
+
+    /// Stops any shake that is in progress.
+    pub fn stop_shake(&mut self) {
+        self.shake_trauma = 0.0;
+        self.shake_offset = Vec2::zero();
+    }
+
+    /// Returns true if the camera is currently shaking.
+    pub fn is_shaking(&self) -> bool {
+        self.shake_trauma > 0.0
+    }
+
+    /// Returns the offset that the current shake is applying to the view, in screen pixels.
+    pub fn shake_offset(&self) -> Vec2<f32> {
+        self.shake_offset
+    }
+
+    /// Advances the camera's effects, and then recalculates the matrix.
+    ///
+    /// This method uses the current [delta time](crate::time::get_delta_time)
+    /// to calculate how much time has passed.
+    pub fn advance(&mut self, ctx: &Context) {
+        self.advance_by(time::get_delta_time(ctx));
+    }
+
+    /// Advances the camera's effects by a specified amount, and then recalculates
+    /// the matrix.
+    pub fn advance_by(&mut self, duration: Duration) {
+        if self.shake_trauma > 0.0 {
+            let decay = if self.shake_duration.is_zero() {
+                1.0
+            } else {
+                duration.as_secs_f32() / self.shake_duration.as_secs_f32()
+            };
+
+            self.shake_trauma = f32::max(self.shake_trauma - decay, 0.0);
+
+            // Squaring the trauma makes the shake fall off more naturally than
+            // a linear fade.
+            let strength = self.shake_magnitude * self.shake_trauma * self.shake_trauma;
+
+            self.shake_offset = if strength > 0.0 {
+                Vec2::new(
+                    self.rng.range(-strength, strength),
+                    self.rng.range(-strength, strength),
+                )
+            } else {
+                Vec2::zero()
+            };
+        }
+
+        self.update();
+    }
+
+    /// Moves the camera towards a target position.
+    ///
+    /// This should be called every update, and will move the camera a fraction of the remaining
+    /// distance each time. The `smoothing` controls how large that fraction is - `0.0` will move
+    /// the camera straight to the target, and values closer to `1.0` will make the camera follow
+    /// more slowly. Values are clamped between `0.0` and `1.0`.
+    ///
+    /// As with any other change to the camera's parameters, you will need to call
+    /// [`update`](Self::update) (or [`advance`](Self::advance)) afterwards to recalculate the matrix.
+    pub fn follow(&mut self, target: Vec2<f32>, smoothing: f32) {
+        let amount = 1.0 - smoothing.clamp(0.0, 1.0);
+
+        self.position += (target - self.position) * amount;
+    }
+
+    /// Gets the area of the world that the camera is restricted to, if one has been set.
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.bounds
+    }
+
+    /// Restricts the camera to an area of the world.
+    ///
+    /// When [`update`](Self::update) is called, the camera's position will be clamped so that
+    /// the [visible area](Self::visible_rect) stays within the bounds, taking into account the
+    /// viewport size and scale. If the visible area is larger than the bounds, the camera will
+    /// be centered on them instead.
+    pub fn set_bounds(&mut self, bounds: Rectangle) {
+        self.bounds = Some(bounds);
+    }
+
+    /// Removes any restriction on the area that the camera can show.
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
     }
 
     /// Returns the current transformation matrix.
@@ -147,11 +294,42 @@ impl Camera {
         unproj
     }
 
+    /// Projects a point from window co-ordinates to camera co-ordinates, for a camera that
+    /// is being used to draw to a [`ScreenScaler`].
+    ///
+    /// This is a shortcut for calling [`camera.project(scaler.project(point))`](Self::project),
+    /// and can be used to convert the position of the mouse into a position in your game's
+    /// world.
+    ///
+    /// The scaler's letterboxing is taken into account, and points are not clamped to the
+    /// scaler's screen. This means that points which fall within the letterbox bars will be
+    /// projected to positions outside of the area that the camera can see.
+    pub fn project_from(&self, point: Vec2<f32>, scaler: &ScreenScaler) -> Vec2<f32> {
+        self.project(scaler.project(point))
+    }
+
+    /// Projects a point from camera co-ordinates to window co-ordinates, for a camera that
+    /// is being used to draw to a [`ScreenScaler`].
+    ///
+    /// This is the inverse of [`project_from`](Self::project_from).
+    pub fn unproject_to(&self, point: Vec2<f32>, scaler: &ScreenScaler) -> Vec2<f32> {
+        scaler.unproject(self.unproject(point))
+    }
+
+    /// Returns the mouse's position in camera co-ordinates, for a camera that is being
+    /// used to draw to a [`ScreenScaler`].
+    ///
+    /// This is a shortcut for calling [`project_from(input::get_mouse_position(ctx), scaler)`](Self::project_from).
+    pub fn mouse_position_from(&self, ctx: &Context, scaler: &ScreenScaler) -> Vec2<f32> {
+        self.project_from(input::get_mouse_position(ctx), scaler)
+    }
+
     /// Returns the mouse's position in camera co-ordinates.
     ///
     /// This is a shortcut for calling [`project(input::get_mouse_position(ctx))`](Self::project).
     /// As such, it does not take into account any other transformations
-    /// being made to the view (e.g. screen scaling).
+    /// being made to the view (e.g. screen scaling) - if you are using a [`ScreenScaler`],
+    /// use [`mouse_position_from`](Self::mouse_position_from) instead.
     pub fn mouse_position(&self, ctx: &Context) -> Vec2<f32> {
         self.project(input::get_mouse_position(ctx))
     }
@@ -228,9 +406,26 @@ impl Camera {
     }
 }
 
+/// Clamps the center of a view of the given size, so that the view stays within the bounds.
+fn clamp_to_bounds(position: Vec2<f32>, view_size: Vec2<f32>, bounds: Rectangle) -> Vec2<f32> {
+    let clamp_axis = |position: f32, view_size: f32, min: f32, size: f32| {
+        if view_size >= size {
+            min + size / 2.0
+        } else {
+            position.clamp(min + view_size / 2.0, min + size - view_size / 2.0)
+        }
+    };
+
+    Vec2::new(
+        clamp_axis(position.x, view_size.x, bounds.x, bounds.width),
+        clamp_axis(position.y, view_size.y, bounds.y, bounds.height),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::Vec4;
 
     #[test]
     fn point_projections() {
@@ -323,4 +518,87 @@ mod tests {
         assert!(rect.width - 150.0 < 0.001);
         assert!(rect.height - 400.0 < 0.001);
     }
+
+    #[test]
+    fn shake_does_not_affect_projection() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.position = Vec2::new(100.0, 50.0);
+
+        let before = camera.project(Vec2::new(10.0, 20.0));
+
+        camera.shake(8.0, Duration::from_secs(1));
+        camera.advance_by(Duration::from_millis(100));
+
+        assert!(camera.is_shaking());
+        assert_ne!(camera.shake_offset(), Vec2::zero());
+        assert_eq!(camera.position, Vec2::new(100.0, 50.0));
+        assert_eq!(camera.project(Vec2::new(10.0, 20.0)), before);
+
+        // The offset is applied to the matrix instead.
+        let center = camera.as_matrix() * Vec4::new(100.0, 50.0, 0.0, 1.0);
+        let expected = Vec2::new(400.0, 300.0) + camera.shake_offset();
+
+        assert!(center.xy().distance(expected) < 0.001);
+    }
+
+    #[test]
+    fn shake_decays_over_duration() {
+        let mut camera = Camera::new(800.0, 600.0);
+
+        camera.shake(8.0, Duration::from_millis(500));
+        camera.advance_by(Duration::from_millis(250));
+
+        // At half of the duration, the trauma is 0.5, so the maximum offset is a quarter
+        // of the magnitude.
+        assert!(camera.shake_offset().x.abs() <= 2.0);
+        assert!(camera.shake_offset().y.abs() <= 2.0);
+
+        camera.advance_by(Duration::from_millis(250));
+
+        assert!(!camera.is_shaking());
+        assert_eq!(camera.shake_offset(), Vec2::zero());
+    }
+
+    #[test]
+    fn follow_moves_towards_target() {
+        let mut camera = Camera::new(800.0, 600.0);
+
+        camera.follow(Vec2::new(100.0, -100.0), 0.75);
+        assert_eq!(camera.position, Vec2::new(25.0, -25.0));
+
+        camera.follow(Vec2::new(100.0, -100.0), 0.75);
+        assert_eq!(camera.position, Vec2::new(43.75, -43.75));
+
+        camera.follow(Vec2::new(100.0, -100.0), 0.0);
+        assert_eq!(camera.position, Vec2::new(100.0, -100.0));
+    }
+
+    #[test]
+    fn bounds_clamp_visible_area() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_bounds(Rectangle::new(0.0, 0.0, 2000.0, 1000.0));
+
+        camera.update();
+        assert_eq!(camera.position, Vec2::new(400.0, 300.0));
+
+        camera.position = Vec2::new(5000.0, 500.0);
+        camera.update();
+        assert_eq!(camera.position, Vec2::new(1600.0, 500.0));
+
+        // Zooming in shrinks the visible area, so the camera can get closer to the edge.
+        camera.scale = Vec2::new(2.0, 2.0);
+        camera.position = Vec2::new(5000.0, -5000.0);
+        camera.update();
+        assert_eq!(camera.position, Vec2::new(1800.0, 150.0));
+
+        // If the visible area is larger than the bounds, the camera is centered.
+        camera.scale = Vec2::new(0.25, 0.25);
+        camera.update();
+        assert_eq!(camera.position, Vec2::new(1000.0, 500.0));
+
+        camera.clear_bounds();
+        camera.position = Vec2::new(5000.0, 500.0);
+        camera.update();
+        assert_eq!(camera.position, Vec2::new(5000.0, 500.0));
+    }
 }
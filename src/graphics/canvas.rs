@@ -1,7 +1,9 @@
 use std::rc::Rc;
 
-use crate::error::Result;
-use crate::graphics::{DrawParams, FilterMode, Texture};
+use crate::error::{Result, TetraError};
+use crate::graphics::mesh::VertexWinding;
+use crate::graphics::{self, DrawParams, FilterMode, Rectangle, Texture, WrapMode};
+use crate::math::{FrustumPlanes, Mat4};
 use crate::platform::{RawCanvas, RawRenderbuffer};
 use crate::Context;
 
@@ -19,6 +21,8 @@ pub struct CanvasBuilder {
     texture_format: TextureFormat,
     samples: u8,
     stencil_buffer: bool,
+    depth_buffer: bool,
+    wrap_mode: Option<WrapMode>,
 }
 
 impl CanvasBuilder {
@@ -33,6 +37,8 @@ impl CanvasBuilder {
             texture_format: TextureFormat::Rgba8,
             samples: 0,
             stencil_buffer: false,
+            depth_buffer: false,
+            wrap_mode: None,
         }
     }
 
@@ -54,7 +60,8 @@ impl CanvasBuilder {
     /// In order to actually display a multisampled canvas, it first has to be downsampled (or 'resolved'). This is
     /// done automatically once you switch to a different canvas/the backbuffer. Until this step takes place,
     /// your rendering will *not* be reflected in the canvas' underlying [`texture`](Canvas::texture) (and by
-    /// extension, in the output of [`draw`](Canvas::draw) and [`get_data`](Canvas::get_data)).
+    /// extension, in the output of [`draw`](Canvas::draw)). The exception to this is
+    /// [`get_data`](Canvas::get_data), which will resolve the canvas itself if needed.
     pub fn samples(&mut self, samples: u8) -> &mut CanvasBuilder {
         self.samples = samples;
         self
@@ -69,6 +76,26 @@ impl CanvasBuilder {
         self
     }
 
+    /// Sets whether the canvas should have a depth buffer.
+    ///
+    /// Setting this to `true` allows you to use [depth testing](graphics::set_depth_test) while
+    /// rendering to the canvas, at the cost of some extra video RAM usage.
+    ///
+    /// Depth and stencil data are stored in the same buffer, so enabling both of these options
+    /// costs no more memory than enabling one of them.
+    pub fn depth_buffer(&mut self, enabled: bool) -> &mut CanvasBuilder {
+        self.depth_buffer = enabled;
+        self
+    }
+
+    /// Sets the wrap mode that should be used for the canvas' underlying [`Texture`].
+    ///
+    /// Defaults to the value of [`graphics::get_default_wrap_mode`](super::get_default_wrap_mode).
+    pub fn wrap_mode(&mut self, wrap_mode: WrapMode) -> &mut CanvasBuilder {
+        self.wrap_mode = Some(wrap_mode);
+        self
+    }
+
     /// Builds the canvas.
     ///
     /// # Errors
@@ -76,20 +103,28 @@ impl CanvasBuilder {
     /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the underlying
     ///   graphics API encounters an error.
     pub fn build(&self, ctx: &mut Context) -> Result<Canvas> {
+        let wrap_mode = self.wrap_mode.unwrap_or(ctx.graphics.default_wrap_mode);
+
         let attachments = ctx.device.new_canvas(
             self.width,
             self.height,
             self.texture_format,
             ctx.graphics.default_filter_mode,
+            wrap_mode,
             self.samples,
-            self.stencil_buffer,
+            self.stencil_buffer || self.depth_buffer,
         )?;
 
         Ok(Canvas {
             handle: Rc::new(attachments.canvas),
-            texture: Texture::from_raw(attachments.color, ctx.graphics.default_filter_mode),
-            stencil_buffer: attachments.depth_stencil.map(Rc::new),
+            texture: Texture::from_raw(
+                attachments.color,
+                ctx.graphics.default_filter_mode,
+                wrap_mode,
+            ),
+            depth_stencil: attachments.depth_stencil.map(Rc::new),
             multisample: attachments.multisample_color.map(Rc::new),
+            origin: TargetOrigin::TopLeft,
         })
     }
 }
@@ -116,6 +151,22 @@ impl CanvasBuilder {
 /// handle to a GPU resource. However, this does mean that modifying a canvas (e.g.
 /// drawing to it) will also affect any clones that exist of it.
 ///
+/// # Orientation
+///
+/// Canvases store their pixel data with the top row first, in the same way as every other
+/// [`Texture`] - this is represented by [`TargetOrigin::TopLeft`]. The screen, on the other
+/// hand, stores its bottom row first ([`TargetOrigin::BottomLeft`]), as this is what
+/// OpenGL expects.
+///
+/// Tetra accounts for this difference when rendering, so you do not need to worry about it
+/// in most cases. Drawing into a canvas, drawing that canvas into another canvas, and then
+/// drawing the result to the screen will always give the same output as drawing directly
+/// to the screen, without needing to flip anything manually. Similarly, the data returned
+/// by [`get_data`](Canvas::get_data) will always be the right way up.
+///
+/// The origin mainly matters if you are writing shaders that use `gl_FragCoord`, as its
+/// Y axis will point down when rendering to a canvas, and up when rendering to the screen.
+///
 /// # Examples
 ///
 /// The [`canvas`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/canvas.rs)
@@ -125,8 +176,9 @@ impl CanvasBuilder {
 pub struct Canvas {
     pub(crate) handle: Rc<RawCanvas>,
     pub(crate) texture: Texture,
-    pub(crate) stencil_buffer: Option<Rc<RawRenderbuffer>>,
+    pub(crate) depth_stencil: Option<Rc<RawRenderbuffer>>,
     pub(crate) multisample: Option<Rc<RawRenderbuffer>>,
+    pub(crate) origin: TargetOrigin,
 }
 
 impl Canvas {
@@ -150,6 +202,50 @@ impl Canvas {
         CanvasBuilder::new(width, height)
     }
 
+    /// Resizes the canvas.
+    ///
+    /// The canvas' underlying texture and buffers are reallocated at the new size, but
+    /// they keep the same settings, and any clones of the canvas (or of its
+    /// [texture](Canvas::texture)) will see the change. This means you do not need to
+    /// hand out a new canvas to everything that is holding onto the old one.
+    ///
+    /// The contents of the canvas will be cleared. If this is the currently active
+    /// canvas, any pending draw calls will be flushed first, and the projection will be
+    /// updated to match the new size.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::InvalidSize`](crate::TetraError::InvalidSize) will be returned if the
+    ///   width or height is zero or negative.
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   underlying graphics API encounters an error.
+    pub fn resize(&self, ctx: &mut Context, width: i32, height: i32) -> Result {
+        if width <= 0 || height <= 0 {
+            return Err(TetraError::InvalidSize { width, height });
+        }
+
+        let is_active = ctx.graphics.canvas.as_ref() == Some(self);
+
+        if is_active {
+            graphics::flush(ctx);
+        }
+
+        ctx.device.resize_canvas(
+            &self.handle,
+            &self.texture.data.handle,
+            self.multisample.as_deref(),
+            self.depth_stencil.as_deref(),
+            width,
+            height,
+        )?;
+
+        if is_active {
+            graphics::apply_viewport(ctx);
+        }
+
+        Ok(())
+    }
+
     /// Draws the canvas to the screen (or to another canvas, if one is enabled).
     pub fn draw<P>(&self, ctx: &mut Context, params: P)
     where
@@ -158,6 +254,17 @@ impl Canvas {
         self.texture.draw(ctx, params)
     }
 
+    /// Draws a region of the canvas to the screen (or to another canvas, if one is enabled).
+    ///
+    /// This works the same way as [`Texture::draw_region`] - the origin, scale and rotation
+    /// are relative to the region, rather than the full canvas.
+    pub fn draw_region<P>(&self, ctx: &mut Context, region: Rectangle, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        self.texture.draw_region(ctx, region, params)
+    }
+
     /// Returns the width of the canvas.
     pub fn width(&self) -> i32 {
         self.texture.width()
@@ -173,6 +280,13 @@ impl Canvas {
         self.texture.size()
     }
 
+    /// Returns where the first row of the canvas' pixel data is stored.
+    ///
+    /// See the [orientation](#orientation) section of the docs for more details.
+    pub fn origin(&self) -> TargetOrigin {
+        self.origin
+    }
+
     /// Returns the filter mode being used by the canvas.
     pub fn filter_mode(&self) -> FilterMode {
         self.texture.filter_mode()
@@ -183,18 +297,35 @@ impl Canvas {
         self.texture.set_filter_mode(ctx, filter_mode);
     }
 
+    /// Returns the wrap mode being used by the canvas.
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.texture.wrap_mode()
+    }
+
+    /// Sets the wrap mode that should be used by the canvas.
+    pub fn set_wrap_mode(&mut self, ctx: &mut Context, wrap_mode: WrapMode) {
+        self.texture.set_wrap_mode(ctx, wrap_mode);
+    }
+
     /// Gets the canvas' data from the GPU.
     ///
     /// This can be useful if you need to do some image processing on the CPU,
     /// or if you want to output the image data somewhere. This is a fairly
     /// slow operation, so avoid doing it too often!
     ///
-    /// If this is the currently active canvas, you should unbind it or call
-    /// [`graphics::flush`](super::flush) before calling this method, to ensure all
-    /// pending draw calls are reflected in the output. Similarly, if the canvas is
-    /// multisampled, it must be [resolved](#resolving) before
-    /// changes will be reflected in this method's output.
+    /// If this is the currently active canvas, any pending draw calls will be flushed
+    /// first, and the canvas will be [resolved](CanvasBuilder::samples) if it is
+    /// multisampled. This means that the output will always reflect everything that
+    /// has been drawn so far.
+    ///
+    /// The returned [`ImageData`] will have the same format as the canvas' underlying
+    /// texture, with the top row first.
     pub fn get_data(&self, ctx: &mut Context) -> ImageData {
+        if ctx.graphics.canvas.as_ref() == Some(self) {
+            graphics::flush(ctx);
+            graphics::resolve_canvas(ctx);
+        }
+
         self.texture.get_data(ctx)
     }
 
@@ -262,4 +393,325 @@ impl Canvas {
     pub fn texture(&self) -> &Texture {
         &self.texture
     }
+
+    /// Returns an estimate of how much GPU memory is used by the canvas, in bytes.
+    ///
+    /// This includes the canvas' texture, as well as any multisample or depth/stencil
+    /// buffers that it owns. The actual amount used may be higher, depending on how
+    /// the graphics driver stores the data.
+    pub fn gpu_memory_estimate(&self) -> usize {
+        let buffers = self
+            .multisample
+            .iter()
+            .chain(&self.depth_stencil)
+            .map(|buffer| buffer.size_in_bytes())
+            .sum::<usize>();
+
+        self.texture.gpu_memory_estimate() + buffers
+    }
+}
+
+/// Where the first row of a render target's pixel data is stored.
+///
+/// See the [orientation](Canvas#orientation) section of the [`Canvas`] docs for
+/// more details.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetOrigin {
+    /// The first row of pixel data is at the top of the target.
+    ///
+    /// This is used by canvases, and matches the way that texture data is stored.
+    TopLeft,
+
+    /// The first row of pixel data is at the bottom of the target.
+    ///
+    /// This is used by the screen.
+    BottomLeft,
+}
+
+impl TargetOrigin {
+    /// Creates a projection matrix that maps Tetra's top-left co-ordinate system
+    /// onto a target with this origin.
+    pub(crate) fn projection(self, width: f32, height: f32) -> Mat4<f32> {
+        let (bottom, top) = match self {
+            TargetOrigin::TopLeft => (0.0, height),
+            TargetOrigin::BottomLeft => (height, 0.0),
+        };
+
+        Mat4::orthographic_rh_no(FrustumPlanes {
+            left: 0.0,
+            right: width,
+            bottom,
+            top,
+            near: -1.0,
+            far: 1.0,
+        })
+    }
+
+    /// Returns the winding that OpenGL will see for geometry with the given winding,
+    /// once it has been projected onto a target with this origin.
+    pub(crate) fn front_face(self, winding: VertexWinding) -> VertexWinding {
+        match self {
+            TargetOrigin::TopLeft => winding.flipped(),
+            TargetOrigin::BottomLeft => winding,
+        }
+    }
+
+    /// Converts a rectangle in Tetra's top-left co-ordinate system into the
+    /// co-ordinates that OpenGL expects for a target with this origin.
+    pub(crate) fn to_device_rect(self, rect: Rectangle<i32>, target_height: i32) -> Rectangle<i32> {
+        match self {
+            TargetOrigin::TopLeft => rect,
+            TargetOrigin::BottomLeft => Rectangle::new(
+                rect.x,
+                target_height - (rect.y + rect.height),
+                rect.width,
+                rect.height,
+            ),
+        }
+    }
+
+    /// Returns `true` if the rows of the target need to be flipped in order to be
+    /// copied into a texture.
+    pub(crate) fn needs_flip(self) -> bool {
+        self == TargetOrigin::BottomLeft
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graphics::Color;
+    use crate::math::{Vec2, Vec4};
+    use crate::ContextBuilder;
+
+    use super::*;
+
+    /// Returns which row of the target's pixel data a point will be rendered to.
+    fn rendered_row(origin: TargetOrigin, y: f32, height: f32) -> f32 {
+        let clip = origin.projection(height, height) * Vec4::new(0.0, y, 0.0, 1.0);
+
+        // OpenGL's window co-ordinates start at the first row of pixel data.
+        (clip.y + 1.0) / 2.0 * height
+    }
+
+    #[test]
+    fn canvas_stores_top_row_first() {
+        assert_eq!(0.0, rendered_row(TargetOrigin::TopLeft, 0.0, 100.0));
+        assert_eq!(25.0, rendered_row(TargetOrigin::TopLeft, 25.0, 100.0));
+    }
+
+    #[test]
+    fn screen_stores_bottom_row_first() {
+        assert_eq!(100.0, rendered_row(TargetOrigin::BottomLeft, 0.0, 100.0));
+        assert_eq!(75.0, rendered_row(TargetOrigin::BottomLeft, 25.0, 100.0));
+    }
+
+    /// Returns which row of the target's pixel data a row of pixels will be rendered to.
+    fn stored_row(origin: TargetOrigin, y: usize, height: usize) -> usize {
+        rendered_row(origin, y as f32 + 0.5, height as f32).floor() as usize
+    }
+
+    /// Returns which row of a texture's pixel data will be sampled for a row of pixels,
+    /// when the texture is drawn at the origin with its default UVs.
+    fn sampled_row(y: usize, height: usize) -> usize {
+        // A `v` of zero is at the top of the quad, and the first row of the data.
+        let v = (y as f32 + 0.5) / height as f32;
+        (v * height as f32).floor() as usize
+    }
+
+    /// Returns which row of the read back image data a row of a target will end up in.
+    fn read_back_row(origin: TargetOrigin, row: usize, height: usize) -> usize {
+        if origin.needs_flip() {
+            height - 1 - row
+        } else {
+            row
+        }
+    }
+
+    #[test]
+    fn canvas_chain_matches_direct_rendering_to_screen() {
+        let height = 32;
+
+        for y in 0..height {
+            let direct = stored_row(TargetOrigin::BottomLeft, y, height);
+
+            // Render to a canvas, and then draw it into two more canvases.
+            let mut row = stored_row(TargetOrigin::TopLeft, y, height);
+
+            for _ in 0..2 {
+                row = stored_row(TargetOrigin::TopLeft, sampled_row(row, height), height);
+            }
+
+            assert_eq!(y, read_back_row(TargetOrigin::TopLeft, row, height));
+
+            let chained = stored_row(TargetOrigin::BottomLeft, sampled_row(row, height), height);
+
+            assert_eq!(direct, chained);
+            assert_eq!(y, read_back_row(TargetOrigin::BottomLeft, direct, height));
+        }
+    }
+
+    #[test]
+    fn device_rect_matches_projection() {
+        let rect = Rectangle::new(10, 20, 30, 40);
+
+        for origin in [TargetOrigin::TopLeft, TargetOrigin::BottomLeft] {
+            let device = origin.to_device_rect(rect, 100);
+
+            let top = rendered_row(origin, rect.y as f32, 100.0).round() as i32;
+            let bottom = rendered_row(origin, rect.bottom() as f32, 100.0).round() as i32;
+
+            assert_eq!(device.y, top.min(bottom));
+            assert_eq!(device.bottom(), top.max(bottom));
+        }
+    }
+
+    #[test]
+    #[ignore = "requires an OpenGL context"]
+    fn canvas_chain_matches_direct_rendering() {
+        let mut ctx = ContextBuilder::new("canvas_chain_matches_direct_rendering", 32, 32)
+            .headless(true)
+            .build()
+            .unwrap();
+
+        // Every pixel is a different color, so that any flip or offset will show up in
+        // the comparison.
+        let pixels: Vec<u8> = (0..4)
+            .flat_map(|y| (0..4).flat_map(move |x| [x * 60, y * 60, 255, 255]))
+            .collect();
+
+        let texture = Texture::from_data(&mut ctx, 4, 4, TextureFormat::Rgba8, &pixels).unwrap();
+
+        let draw_scene = |ctx: &mut Context| {
+            graphics::clear(ctx, Color::BLACK);
+
+            texture.draw(
+                ctx,
+                DrawParams::new()
+                    .position(Vec2::new(4.0, 8.0))
+                    .scale(Vec2::new(4.0, 2.0)),
+            );
+        };
+
+        draw_scene(&mut ctx);
+
+        let expected = graphics::read_screen(&mut ctx).unwrap();
+
+        assert_eq!((32, 32), expected.size());
+
+        assert_eq!(
+            Color::rgb8(0, 0, 255),
+            expected.get_pixel_color(Vec2::new(4, 8))
+        );
+
+        let chain = [
+            Canvas::new(&mut ctx, 32, 32).unwrap(),
+            Canvas::builder(32, 32)
+                .samples(4)
+                .stencil_buffer(true)
+                .build(&mut ctx)
+                .unwrap(),
+            Canvas::builder(32, 32)
+                .stencil_buffer(true)
+                .build(&mut ctx)
+                .unwrap(),
+        ];
+
+        graphics::set_canvas(&mut ctx, &chain[0]);
+        draw_scene(&mut ctx);
+
+        for pair in chain.windows(2) {
+            graphics::set_canvas(&mut ctx, &pair[1]);
+            graphics::clear(&mut ctx, Color::BLACK);
+            pair[0].draw(&mut ctx, Vec2::zero());
+        }
+
+        graphics::reset_canvas(&mut ctx);
+
+        assert_eq!(expected.as_bytes(), chain[2].get_data(&mut ctx).as_bytes());
+
+        graphics::clear(&mut ctx, Color::BLACK);
+        chain[2].draw(&mut ctx, Vec2::zero());
+
+        let actual = graphics::read_screen(&mut ctx).unwrap();
+
+        assert_eq!(expected.as_bytes(), actual.as_bytes());
+    }
+
+    #[test]
+    fn winding_is_flipped_for_top_left_targets() {
+        assert_eq!(
+            VertexWinding::Clockwise,
+            TargetOrigin::TopLeft.front_face(VertexWinding::CounterClockwise)
+        );
+
+        assert_eq!(
+            VertexWinding::CounterClockwise,
+            TargetOrigin::BottomLeft.front_face(VertexWinding::CounterClockwise)
+        );
+    }
+
+    #[test]
+    fn device_rect_is_flipped_for_bottom_left_targets() {
+        let rect = Rectangle::new(10, 20, 30, 40);
+
+        assert_eq!(rect, TargetOrigin::TopLeft.to_device_rect(rect, 100));
+
+        assert_eq!(
+            Rectangle::new(10, 40, 30, 40),
+            TargetOrigin::BottomLeft.to_device_rect(rect, 100)
+        );
+    }
+
+    #[test]
+    #[ignore = "requires a display and an OpenGL context"]
+    fn get_data_round_trips_clear_color() {
+        let mut ctx = ContextBuilder::new("get_data_round_trips_clear_color", 64, 64)
+            .build()
+            .unwrap();
+
+        let color = Color::rgba(0.2, 0.4, 0.6, 0.8);
+
+        for format in [TextureFormat::Rgba8, TextureFormat::Rgba16F] {
+            for samples in [0, 4] {
+                let canvas = Canvas::builder(8, 8)
+                    .texture_format(format)
+                    .samples(samples)
+                    .build(&mut ctx)
+                    .unwrap();
+
+                graphics::set_canvas(&mut ctx, &canvas);
+                graphics::clear(&mut ctx, color);
+
+                // Read back while the canvas is still active, to make sure it gets
+                // flushed and resolved.
+                let data = canvas.get_data(&mut ctx);
+
+                graphics::reset_canvas(&mut ctx);
+
+                assert_eq!(format, data.format());
+                assert_eq!((8, 8), data.size());
+
+                for position in [Vec2::new(0, 0), Vec2::new(7, 7)] {
+                    let actual = data.get_pixel_color(position);
+
+                    let diff = [
+                        actual.r - color.r,
+                        actual.g - color.g,
+                        actual.b - color.b,
+                        actual.a - color.a,
+                    ];
+
+                    assert!(
+                        diff.iter().all(|d| d.abs() <= 1.0 / 255.0),
+                        "{:?} with {} samples: expected {:?}, got {:?}",
+                        format,
+                        samples,
+                        color,
+                        actual
+                    );
+                }
+            }
+        }
+    }
 }
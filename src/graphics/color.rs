@@ -3,7 +3,7 @@
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
 
 use crate::error::{Result, TetraError};
-use crate::math::Vec4;
+use crate::math::{Lerp, Vec4};
 
 /// An RGBA color.
 ///
@@ -119,6 +119,76 @@ impl Color {
         Ok(Color::rgba8(r, g, b, a))
     }
 
+    /// Creates a new `Color`, with the specified HSV values and the alpha set to 1.0.
+    ///
+    /// The hue is specified in degrees, and will wrap around if it is outside of the
+    /// range `0.0` to `360.0`. The saturation and value should be between `0.0` and `1.0`.
+    pub fn hsv(h: f32, s: f32, v: f32) -> Color {
+        let chroma = v * s;
+        let (r, g, b) = hue_to_rgb(h, chroma);
+        let m = v - chroma;
+
+        Color::rgb(r + m, g + m, b + m)
+    }
+
+    /// Creates a new `Color`, with the specified HSL values and the alpha set to 1.0.
+    ///
+    /// The hue is specified in degrees, and will wrap around if it is outside of the
+    /// range `0.0` to `360.0`. The saturation and lightness should be between `0.0` and `1.0`.
+    pub fn hsl(h: f32, s: f32, l: f32) -> Color {
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, chroma);
+        let m = l - chroma / 2.0;
+
+        Color::rgb(r + m, g + m, b + m)
+    }
+
+    /// Converts the color to HSV.
+    ///
+    /// The format is (hue, saturation, value), with the hue in degrees between `0.0` and
+    /// `360.0`. The alpha component is ignored.
+    ///
+    /// If the color is a shade of grey, the hue will be `0.0`.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let s = if max == 0.0 { 0.0 } else { delta / max };
+
+        (rgb_to_hue(self, max, delta), s, max)
+    }
+
+    /// Converts the color to HSL.
+    ///
+    /// The format is (hue, saturation, lightness), with the hue in degrees between `0.0` and
+    /// `360.0`. The alpha component is ignored.
+    ///
+    /// If the color is a shade of grey, the hue will be `0.0`.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        let delta = max - min;
+
+        let l = (max + min) / 2.0;
+
+        let s = if delta == 0.0 {
+            0.0
+        } else {
+            delta / (1.0 - (2.0 * l - 1.0).abs())
+        };
+
+        (rgb_to_hue(self, max, delta), s, l)
+    }
+
+    /// Returns the color with its hue rotated by the specified number of degrees.
+    ///
+    /// The saturation, value and alpha of the color will be preserved.
+    pub fn with_hue_shifted(self, degrees: f32) -> Self {
+        let (h, s, v) = self.to_hsv();
+        Color::hsv(h + degrees, s, v).with_alpha(self.a)
+    }
+
     /// Returns the color with the red component set to the specified value.
     pub const fn with_red(self, r: f32) -> Self {
         Self { r, ..self }
@@ -164,6 +234,77 @@ impl Color {
         }
     }
 
+    /// Converts the color from sRGB to linear RGB, using the piecewise sRGB transfer function.
+    ///
+    /// Colors in Tetra are usually specified in sRGB, which is not perceptually linear - doing
+    /// math on linear colors (e.g. for blending or lighting) will usually give better results.
+    /// [`from_linear`](Self::from_linear) can be used to convert the result back.
+    ///
+    /// The alpha component is not affected.
+    pub fn to_linear(self) -> Color {
+        Color {
+            r: srgb_to_linear(self.r),
+            g: srgb_to_linear(self.g),
+            b: srgb_to_linear(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Converts a color from linear RGB to sRGB, using the piecewise sRGB transfer function.
+    ///
+    /// This is the inverse of [`to_linear`](Self::to_linear).
+    ///
+    /// The alpha component is not affected.
+    pub fn from_linear(linear: Color) -> Color {
+        Color {
+            r: linear_to_srgb(linear.r),
+            g: linear_to_srgb(linear.g),
+            b: linear_to_srgb(linear.b),
+            a: linear.a,
+        }
+    }
+
+    /// Linearly interpolates between this color and another.
+    ///
+    /// A `t` of `0.0` will return this color, and a `t` of `1.0` will return `other`.
+    /// Values outside of that range will extrapolate. This is the same as
+    /// [`Lerp::lerp_unclamped`] - if you want `t` to be clamped, call [`Lerp::lerp`] instead.
+    ///
+    /// The interpolation is carried out on the sRGB values, which can give muddy results
+    /// for some gradients - [`lerp_linear`](Self::lerp_linear) can be used instead to
+    /// interpolate in linear space.
+    pub fn lerp(self, other: Color, t: f32) -> Color {
+        Color {
+            r: lerp_f32(self.r, other.r, t),
+            g: lerp_f32(self.g, other.g, t),
+            b: lerp_f32(self.b, other.b, t),
+            a: lerp_f32(self.a, other.a, t),
+        }
+    }
+
+    /// Linearly interpolates between this color and another, in linear RGB space.
+    ///
+    /// This works the same as [`lerp`](Self::lerp), but the RGB components are
+    /// [converted to linear](Self::to_linear) before interpolating, and then converted
+    /// back to sRGB afterwards. This gives more even gradients and fades, at the cost of
+    /// being slightly more expensive. The alpha component is always interpolated directly.
+    pub fn lerp_linear(self, other: Color, t: f32) -> Color {
+        // The transfer functions don't round trip exactly, so the endpoints are
+        // special-cased to make sure they come back unchanged.
+        if t == 0.0 {
+            return self;
+        }
+
+        if t == 1.0 {
+            return other;
+        }
+
+        let from = self.to_linear();
+        let to = other.to_linear();
+
+        Color::from_linear(from.lerp(to, t)).with_alpha(lerp_f32(self.a, other.a, t))
+    }
+
     // These constants should remain at the bottom of the impl block to keep
     // the docs readable - don't want to have to scroll through a load of colors
     // to get to the methods!
@@ -188,6 +329,58 @@ fn clamp_f32(val: f32) -> f32 {
     val.clamp(0.0, 1.0)
 }
 
+fn lerp_f32(from: f32, to: f32, t: f32) -> f32 {
+    // This form is exact at both endpoints, unlike `from + (to - from) * t`.
+    from * (1.0 - t) + to * t
+}
+
+fn srgb_to_linear(val: f32) -> f32 {
+    if val <= 0.04045 {
+        val / 12.92
+    } else {
+        ((val + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(val: f32) -> f32 {
+    if val <= 0.0031308 {
+        val * 12.92
+    } else {
+        1.055 * val.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Converts a hue (in degrees) and chroma into RGB values, without any lightness applied.
+fn hue_to_rgb(h: f32, chroma: f32) -> (f32, f32, f32) {
+    let segment = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (segment % 2.0 - 1.0).abs());
+
+    // `rem_euclid` can round up to exactly 360.0 for tiny negative inputs, so the last
+    // arm also has to handle a segment of 6.
+    match segment as i32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    }
+}
+
+/// Calculates the hue (in degrees) of an RGB color, given its largest component and
+/// its chroma.
+fn rgb_to_hue(color: Color, max: f32, delta: f32) -> f32 {
+    if delta == 0.0 {
+        0.0
+    } else if max == color.r {
+        60.0 * ((color.g - color.b) / delta).rem_euclid(6.0)
+    } else if max == color.g {
+        60.0 * ((color.b - color.r) / delta + 2.0)
+    } else {
+        60.0 * ((color.r - color.g) / delta + 4.0)
+    }
+}
+
 impl From<Color> for Vec4<f32> {
     fn from(color: Color) -> Vec4<f32> {
         Vec4::new(color.r, color.g, color.b, color.a)
@@ -231,6 +424,14 @@ impl From<[u8; 4]> for Color {
     }
 }
 
+impl Lerp<f32> for Color {
+    type Output = Color;
+
+    fn lerp_unclamped(from: Self, to: Self, factor: f32) -> Self::Output {
+        from.lerp(to, factor)
+    }
+}
+
 impl Add for Color {
     type Output = Color;
 
@@ -410,6 +611,7 @@ impl DivAssign<f32> for Color {
 #[cfg(test)]
 mod tests {
     use super::Color;
+    use crate::math::Lerp;
 
     #[test]
     fn rgb8_creation() {
@@ -540,6 +742,176 @@ mod tests {
         });
     }
 
+    #[test]
+    fn hsv_creation() {
+        // Each of the 60 degree segment boundaries lands on a primary or secondary color.
+        let expected = [
+            Color::rgb(1.0, 0.0, 0.0),
+            Color::rgb(1.0, 1.0, 0.0),
+            Color::rgb(0.0, 1.0, 0.0),
+            Color::rgb(0.0, 1.0, 1.0),
+            Color::rgb(0.0, 0.0, 1.0),
+            Color::rgb(1.0, 0.0, 1.0),
+            Color::rgb(1.0, 0.0, 0.0),
+        ];
+
+        for (i, color) in expected.iter().enumerate() {
+            let h = i as f32 * 60.0;
+
+            assert!(roughly_same_color(*color, Color::hsv(h, 1.0, 1.0)));
+            assert!(roughly_same_color(*color, Color::hsl(h, 1.0, 0.5)));
+        }
+
+        assert!(roughly_same_color(
+            Color::rgb(0.2, 0.4, 0.6),
+            Color::hsv(210.0, 2.0 / 3.0, 0.6)
+        ));
+
+        assert!(roughly_same_color(
+            Color::rgb(0.2, 0.4, 0.6),
+            Color::hsl(210.0, 0.5, 0.4)
+        ));
+    }
+
+    #[test]
+    fn hue_wraps_around() {
+        assert!(roughly_same_color(
+            Color::hsv(300.0, 1.0, 1.0),
+            Color::hsv(-60.0, 1.0, 1.0)
+        ));
+
+        assert!(roughly_same_color(
+            Color::hsl(60.0, 1.0, 0.5),
+            Color::hsl(420.0, 1.0, 0.5)
+        ));
+
+        assert!(roughly_same_color(
+            Color::RED,
+            Color::hsv(-f32::EPSILON, 1.0, 1.0)
+        ));
+    }
+
+    #[test]
+    fn achromatic_conversion() {
+        let grey = Color::rgb(0.5, 0.5, 0.5);
+
+        assert_eq!(grey.to_hsv(), (0.0, 0.0, 0.5));
+        assert_eq!(grey.to_hsl(), (0.0, 0.0, 0.5));
+        assert_eq!(Color::BLACK.to_hsv(), (0.0, 0.0, 0.0));
+        assert_eq!(Color::WHITE.to_hsl(), (0.0, 0.0, 1.0));
+
+        // The hue has no effect when there is no saturation.
+        assert!(roughly_same_color(grey, Color::hsv(123.0, 0.0, 0.5)));
+        assert!(roughly_same_color(grey, Color::hsl(123.0, 0.0, 0.5)));
+    }
+
+    #[test]
+    fn hsv_hsl_round_trip() {
+        let colors = [
+            Color::rgb(1.0, 0.0, 0.0),
+            Color::rgb(1.0, 1.0, 0.0),
+            Color::rgb(0.0, 1.0, 1.0),
+            Color::rgb(1.0, 0.0, 1.0),
+            Color::rgb(0.2, 0.4, 0.6),
+            Color::rgb(0.9, 0.1, 0.3),
+            Color::rgb(0.25, 0.75, 0.5),
+            Color::rgb(0.5, 0.5, 0.5),
+        ];
+
+        for color in colors {
+            let (h, s, v) = color.to_hsv();
+            assert!(roughly_same_color(color, Color::hsv(h, s, v)));
+
+            let (h, s, l) = color.to_hsl();
+            assert!(roughly_same_color(color, Color::hsl(h, s, l)));
+        }
+
+        let (h, s, v) = Color::rgb(0.2, 0.4, 0.6).to_hsv();
+        assert!((h - 210.0).abs() < 1e-4);
+        assert!((s - 2.0 / 3.0).abs() < 1e-5);
+        assert!((v - 0.6).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hue_shift() {
+        let shifted = Color::rgba(1.0, 0.0, 0.0, 0.5).with_hue_shifted(120.0);
+        assert!(roughly_same_color(Color::rgba(0.0, 1.0, 0.0, 0.5), shifted));
+
+        let shifted = Color::rgb(0.0, 0.0, 1.0).with_hue_shifted(-600.0);
+        assert!(roughly_same_color(Color::rgb(1.0, 0.0, 0.0), shifted));
+    }
+
+    #[test]
+    fn linear_conversion() {
+        assert_eq!(Color::BLACK, Color::BLACK.to_linear());
+        assert!(roughly_same_color(Color::WHITE, Color::WHITE.to_linear()));
+
+        // Below the breakpoints, the curve is a straight line.
+        assert!((Color::rgb(0.04045, 0.0, 0.0).to_linear().r - 0.04045 / 12.92).abs() < 1e-7);
+        assert!((Color::from_linear(Color::rgb(0.0031308, 0.0, 0.0)).r - 0.04045).abs() < 1e-5);
+
+        // Both sides of the piecewise curve should meet at the breakpoint.
+        let below = Color::rgb(0.04045, 0.0, 0.0).to_linear().r;
+        let above = Color::rgb(0.04046, 0.0, 0.0).to_linear().r;
+        assert!(above > below && above - below < 1e-5);
+
+        // Mid grey is much darker in linear space - this wouldn't hold for a pure power curve.
+        let grey = Color::rgba(0.5, 0.5, 0.5, 0.25).to_linear();
+        assert!((grey.r - 0.21404).abs() < 1e-4);
+        assert_eq!(grey.a, 0.25);
+
+        for val in [0.0, 0.001, 0.02, 0.04045, 0.1, 0.5, 0.9, 1.0] {
+            let color = Color::rgb(val, val, val);
+            assert!(roughly_same_color(
+                color,
+                Color::from_linear(color.to_linear())
+            ));
+        }
+    }
+
+    #[test]
+    fn lerp_endpoints() {
+        let a = Color::rgba(0.1, 0.2, 0.3, 0.4);
+        let b = Color::rgba(0.7, 0.3, 0.9, 1.0);
+
+        assert_eq!(a, a.lerp(b, 0.0));
+        assert_eq!(b, a.lerp(b, 1.0));
+        assert_eq!(a, a.lerp_linear(b, 0.0));
+        assert_eq!(b, a.lerp_linear(b, 1.0));
+    }
+
+    #[test]
+    fn lerp_midpoint() {
+        let mid = Color::rgba(0.0, 0.0, 0.0, 0.0).lerp(Color::WHITE, 0.5);
+        assert!(roughly_same_color(Color::rgba(0.5, 0.5, 0.5, 0.5), mid));
+
+        // Interpolating in linear space gives a brighter midpoint, but the alpha is
+        // still interpolated directly.
+        let mid = Color::rgba(0.0, 0.0, 0.0, 0.0).lerp_linear(Color::WHITE, 0.5);
+        assert!((mid.r - 0.73536).abs() < 1e-4);
+        assert!((mid.a - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn lerp_trait_matches_inherent_method() {
+        let a = Color::rgba(0.1, 0.2, 0.3, 0.4);
+        let b = Color::rgba(0.7, 0.3, 0.9, 1.0);
+
+        for t in [0.0, 0.25, 1.0, 1.5, -0.5] {
+            assert_eq!(a.lerp(b, t), Lerp::lerp_unclamped(a, b, t));
+        }
+
+        assert_eq!(b, Lerp::lerp(a, b, 1.5));
+        assert_eq!(a, Lerp::lerp(a, b, -0.5));
+    }
+
+    fn roughly_same_color(a: Color, b: Color) -> bool {
+        (a.r - b.r).abs() < 1e-5
+            && (a.g - b.g).abs() < 1e-5
+            && (a.b - b.b).abs() < 1e-5
+            && (a.a - b.a).abs() < 1e-5
+    }
+
     fn same_color(a: Color, b: Color) -> bool {
         (a.r - b.r).abs() < f32::EPSILON
             && (a.g - b.g).abs() < f32::EPSILON
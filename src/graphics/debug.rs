@@ -0,0 +1,160 @@
+//! Functions for drawing debugging information.
+
+use std::time::Duration;
+
+use crate::graphics::{self, Color, DrawParams};
+use crate::math::Vec2;
+use crate::time;
+use crate::Context;
+
+/// The size of each pixel of the overlay's built-in font.
+const PIXEL_SIZE: f32 = 2.0;
+
+/// The size of the gap between the contents of the overlay and its edges.
+const MARGIN: f32 = 4.0;
+
+/// The maximum number of frames that will be shown in the overlay's graph.
+const GRAPH_FRAMES: usize = 120;
+
+/// The height of the overlay's graph.
+const GRAPH_HEIGHT: f32 = 32.0;
+
+/// The frame time that reaches the top of the overlay's graph (equivalent to 30 FPS).
+const GRAPH_MAX_FRAME_TIME: f32 = 1.0 / 30.0;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const TEXT_COLOR: Color = Color::WHITE;
+const FAST_COLOR: Color = Color::rgb(0.3, 0.85, 0.3);
+const SLOW_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+const VERY_SLOW_COLOR: Color = Color::rgb(0.95, 0.3, 0.25);
+
+/// Draws an overlay showing the current frame rate, the 99th percentile frame time, and a
+/// graph of the [recent frame times](time::get_frame_time_history).
+///
+/// Each bar of the graph represents a single frame. Bars are colored green if the frame
+/// took less than 1/60th of a second, yellow if it took less than 1/30th of a second,
+/// and red otherwise.
+///
+/// The overlay uses a tiny built-in font, so it can be used without loading any assets.
+/// Like any other drawing operation, it will be affected by the current
+/// [transform matrix](graphics::set_transform_matrix), so you may want to draw it after
+/// resetting the transform.
+pub fn draw_fps_overlay(ctx: &mut Context, position: Vec2<f32>) {
+    let fps = format!("{:.0} FPS", time::get_fps(ctx));
+
+    let worst = format!(
+        "{:.1} MS",
+        time::get_frame_time_percentile(ctx, 0.99).as_secs_f64() * 1000.0
+    );
+
+    let history = time::get_frame_time_history(ctx);
+    let skipped = history.len().saturating_sub(GRAPH_FRAMES);
+    let frames: Vec<Duration> = history.skip(skipped).collect();
+
+    let line_height = (GLYPH_HEIGHT + 1) as f32 * PIXEL_SIZE;
+    let text_width = usize::max(fps.len(), worst.len()) as f32 * GLYPH_ADVANCE;
+    let graph_width = GRAPH_FRAMES as f32;
+
+    let width = f32::max(text_width, graph_width) + MARGIN * 2.0;
+    let height = line_height * 2.0 + GRAPH_HEIGHT + MARGIN * 3.0;
+
+    let texture = ctx.graphics.default_texture.clone();
+    graphics::set_texture(ctx, &texture);
+
+    draw_rect(ctx, position.x, position.y, width, height, BACKGROUND_COLOR);
+
+    let text_x = position.x + MARGIN;
+    let text_y = position.y + MARGIN;
+
+    draw_text(ctx, &fps, text_x, text_y);
+    draw_text(ctx, &worst, text_x, text_y + line_height);
+
+    let graph_x = position.x + MARGIN;
+    let graph_bottom = position.y + height - MARGIN;
+
+    for (i, frame_time) in frames.iter().enumerate() {
+        let secs = frame_time.as_secs_f32();
+
+        let bar_height = f32::min(secs / GRAPH_MAX_FRAME_TIME, 1.0) * GRAPH_HEIGHT;
+
+        let color = if secs < 1.0 / 60.0 {
+            FAST_COLOR
+        } else if secs < 1.0 / 30.0 {
+            SLOW_COLOR
+        } else {
+            VERY_SLOW_COLOR
+        };
+
+        draw_rect(
+            ctx,
+            graph_x + i as f32,
+            graph_bottom - bar_height,
+            1.0,
+            bar_height,
+            color,
+        );
+    }
+}
+
+fn draw_rect(ctx: &mut Context, x: f32, y: f32, width: f32, height: f32, color: Color) {
+    graphics::push_quad(
+        ctx,
+        x,
+        y,
+        x + width,
+        y + height,
+        0.0,
+        0.0,
+        1.0,
+        1.0,
+        &DrawParams::new().color(color),
+    );
+}
+
+fn draw_text(ctx: &mut Context, text: &str, x: f32, y: f32) {
+    for (i, ch) in text.chars().enumerate() {
+        let glyph_x = x + i as f32 * GLYPH_ADVANCE;
+
+        for (row, bits) in glyph(ch).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                    draw_rect(
+                        ctx,
+                        glyph_x + col as f32 * PIXEL_SIZE,
+                        y + row as f32 * PIXEL_SIZE,
+                        PIXEL_SIZE,
+                        PIXEL_SIZE,
+                        TEXT_COLOR,
+                    );
+                }
+            }
+        }
+    }
+}
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+const GLYPH_ADVANCE: f32 = (GLYPH_WIDTH + 1) as f32 * PIXEL_SIZE;
+
+/// Returns the rows of the built-in font's glyph for a character, from top to bottom.
+///
+/// Only the characters needed by the overlay are supported - anything else is blank.
+fn glyph(ch: char) -> [u8; GLYPH_HEIGHT] {
+    match ch {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' | 'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
@@ -1,11 +1,17 @@
-use crate::graphics::Color;
+use crate::graphics::{Color, FilterMode};
 use crate::math::{Mat4, Vec2, Vec3};
 
 /// Parameters that can be manipulated when drawing an object.
 ///
 /// You can either use this as a builder by calling [`DrawParams::new`] and then chaining methods, or
 /// construct it manually - whichever you find more pleasant to write.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DrawParams {
     /// The position that the graphic should be drawn at. Defaults to `(0.0, 0.0)`.
     pub position: Vec2<f32>,
@@ -25,8 +31,37 @@ pub struct DrawParams {
     /// The rotation of the graphic, in radians. Defaults to `0.0`.
     pub rotation: f32,
 
+    /// The skew (or shear) of the graphic. Defaults to `None`.
+    ///
+    /// The `x` component shifts each point horizontally by `x` times its vertical distance from
+    /// the origin, and the `y` component shifts each point vertically by `y` times its
+    /// horizontal distance from the origin. For example, a skew of `(0.5, 0.0)` turns a square
+    /// into a parallelogram that leans to the right as it goes down.
+    ///
+    /// The transformations are applied in the following order: origin, scale, skew, rotation,
+    /// position. This means that the skew is applied along the graphic's own axes, and then
+    /// rotated along with it.
+    ///
+    /// Drawing with a skew is slightly slower than drawing without one, so this should be left
+    /// as `None` if it is not needed.
+    pub skew: Option<Vec2<f32>>,
+
     /// A color to multiply the graphic by. Defaults to [`Color::WHITE`].
     pub color: Color,
+
+    /// The filter mode to use when drawing the graphic. Defaults to `None`.
+    ///
+    /// If this is set, it will override the texture's own filter mode for this draw only.
+    /// If it is `None`, the texture's filter mode will be used as normal.
+    pub filter_mode: Option<FilterMode>,
+
+    /// The depth that the graphic should be drawn at, between `-1.0` and `1.0`. Defaults to `0.0`.
+    ///
+    /// This is only used if a [depth test](crate::graphics::set_depth_test) is active, in which
+    /// case lower values are treated as being closer to the screen. When drawing a
+    /// [`Mesh`](crate::graphics::mesh::Mesh), the depth of each [`Vertex`](crate::graphics::mesh::Vertex)
+    /// is used instead.
+    pub depth: f32,
 }
 
 impl DrawParams {
@@ -59,19 +94,43 @@ impl DrawParams {
         self
     }
 
+    /// Sets the skew of the graphic.
+    pub fn skew(mut self, skew: Vec2<f32>) -> DrawParams {
+        self.skew = Some(skew);
+        self
+    }
+
     /// Sets the color to multiply the graphic by.
     pub fn color(mut self, color: Color) -> DrawParams {
         self.color = color;
         self
     }
 
+    /// Sets the filter mode to use when drawing the graphic, overriding the texture's
+    /// own filter mode.
+    pub fn filter_mode(mut self, filter_mode: FilterMode) -> DrawParams {
+        self.filter_mode = Some(filter_mode);
+        self
+    }
+
+    /// Sets the depth that the graphic should be drawn at.
+    pub fn depth(mut self, depth: f32) -> DrawParams {
+        self.depth = depth;
+        self
+    }
+
     /// Creates a new transformation matrix equivalent to this set of params.
     ///
-    /// This method does not take into account `color`, as it cannot
-    /// be represented via a matrix.
+    /// This method does not take into account `color`, `filter_mode` or `depth`, as they
+    /// cannot be represented via a matrix.
     pub fn to_matrix(&self) -> Mat4<f32> {
         let mut matrix = Mat4::translation_2d(-self.origin);
         matrix.scale_3d(Vec3::from(self.scale));
+
+        if let Some(skew) = self.skew {
+            matrix = skew_matrix(skew) * matrix;
+        }
+
         matrix.rotate_z(self.rotation);
         matrix.translate_2d(self.position);
         matrix
@@ -85,7 +144,10 @@ impl Default for DrawParams {
             scale: Vec2::new(1.0, 1.0),
             origin: Vec2::new(0.0, 0.0),
             rotation: 0.0,
+            skew: None,
             color: Color::WHITE,
+            filter_mode: None,
+            depth: 0.0,
         }
     }
 }
@@ -99,6 +161,18 @@ impl From<Vec2<f32>> for DrawParams {
     }
 }
 
+fn skew_matrix(skew: Vec2<f32>) -> Mat4<f32> {
+    #[rustfmt::skip]
+    let matrix = Mat4::new(
+        1.0,    skew.x, 0.0, 0.0,
+        skew.y, 1.0,    0.0, 0.0,
+        0.0,    0.0,    1.0, 0.0,
+        0.0,    0.0,    0.0, 1.0,
+    );
+
+    matrix
+}
+
 impl From<DrawParams> for Mat4<f32> {
     fn from(params: DrawParams) -> Self {
         params.to_matrix()
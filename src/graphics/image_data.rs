@@ -1,11 +1,12 @@
 use std::path::Path;
 
 use half::f16;
+use image::{DynamicImage, RgbaImage};
 
 use crate::error::{Result, TetraError};
 use crate::fs;
-use crate::graphics::{Color, Rectangle, Texture, TextureFormat};
-use crate::math::Vec2;
+use crate::graphics::{Color, FilterMode, Rectangle, Texture, TextureFormat};
+use crate::math::{Vec2, Vec3};
 use crate::Context;
 
 /// Raw image data.
@@ -31,6 +32,10 @@ use crate::Context;
 /// | ICO | `texture_ico` | No |
 /// | PNM | `texture_pnm` | No |
 /// | DDS/DXT | `texture_dds` | No |
+///
+/// Images can also be encoded and written to a file via the [`save`](ImageData::save)
+/// method. This is supported for PNG, JPEG, BMP, GIF, TIFF, TGA, WebP, ICO and PNM
+/// files, as long as the corresponding feature is enabled.
 #[derive(Debug, Clone)]
 pub struct ImageData {
     data: Vec<u8>,
@@ -108,6 +113,25 @@ impl ImageData {
         })
     }
 
+    /// Creates an `ImageData` where every pixel is set to the given color.
+    ///
+    /// Any channels of the color that are not supported by the format will be
+    /// ignored, as described in [`set_pixel_color`](Self::set_pixel_color).
+    pub fn filled(width: i32, height: i32, format: TextureFormat, color: Color) -> ImageData {
+        let width = width.max(0) as usize;
+        let height = height.max(0) as usize;
+
+        let mut pixel = vec![0; format.stride()];
+        write_color(format, color, &mut pixel);
+
+        ImageData {
+            data: pixel.repeat(width * height),
+            width,
+            height,
+            format,
+        }
+    }
+
     /// Decodes image data that is encoded in one of Tetra's supported
     /// file formats (except for TGA).
     ///
@@ -137,6 +161,54 @@ impl ImageData {
         })
     }
 
+    /// Encodes the image data and saves it to the given file.
+    ///
+    /// The format will be determined based on the file extension. If the image is not
+    /// in [`TextureFormat::Rgba8`], it will be [converted](ImageData::to_format_dithered)
+    /// before being encoded, using [`DitherMode::Ordered8x8`] to avoid banding in high
+    /// precision data. Formats that do not support transparency (e.g. JPEG) will have
+    /// their alpha channel discarded.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::FailedToSaveAsset`] will be returned if the file could not be written.
+    /// * [`TetraError::FailedToEncodeImage`] will be returned if the file format is not
+    ///   supported, or if the image data could not be encoded.
+    pub fn save<P>(&self, path: P) -> Result
+    where
+        P: AsRef<Path>,
+    {
+        self.save_dithered(path, DitherMode::Ordered8x8)
+    }
+
+    /// Encodes the image data and saves it to the given file, using the specified
+    /// dithering when converting it to [`TextureFormat::Rgba8`].
+    ///
+    /// This behaves the same as [`save`](ImageData::save), other than the choice of
+    /// dithering. 8-bit data will not be changed by the dithering.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::FailedToSaveAsset`] will be returned if the file could not be written.
+    /// * [`TetraError::FailedToEncodeImage`] will be returned if the file format is not
+    ///   supported, or if the image data could not be encoded.
+    pub fn save_dithered<P>(&self, path: P, dither: DitherMode) -> Result
+    where
+        P: AsRef<Path>,
+    {
+        let data = match self.format {
+            TextureFormat::Rgba8 => self.data.clone(),
+            _ => self
+                .to_format_dithered(TextureFormat::Rgba8, dither)
+                .into_bytes(),
+        };
+
+        let image = RgbaImage::from_raw(self.width as u32, self.height as u32, data)
+            .expect("buffer should be exact size for image");
+
+        fs::write_image(path, &DynamicImage::ImageRgba8(image))
+    }
+
     /// Returns the width of the image.
     pub fn width(&self) -> i32 {
         self.width as i32
@@ -217,6 +289,225 @@ impl ImageData {
         }
     }
 
+    /// Draws another image on top of this one, with its top-left corner at the
+    /// given position.
+    ///
+    /// The source image will be alpha blended onto this image, treating both
+    /// images as having non-premultiplied alpha. Use [`blit_with_mode`](Self::blit_with_mode)
+    /// if you want to replace the existing pixels instead.
+    ///
+    /// Any part of the source image that falls outside of this image's bounds will be
+    /// ignored. If the images have different [`TextureFormat`]s, the source pixels will
+    /// be converted as described in [`to_format`](Self::to_format).
+    pub fn blit(&mut self, source: &ImageData, position: Vec2<i32>) {
+        self.blit_with_mode(source, position, BlitMode::AlphaBlend);
+    }
+
+    /// Draws another image on top of this one, with its top-left corner at the
+    /// given position, using the specified [`BlitMode`].
+    ///
+    /// Any part of the source image that falls outside of this image's bounds will be
+    /// ignored. If the images have different [`TextureFormat`]s, the source pixels will
+    /// be converted as described in [`to_format`](Self::to_format).
+    pub fn blit_with_mode(&mut self, source: &ImageData, position: Vec2<i32>, mode: BlitMode) {
+        let x = i64::from(position.x);
+        let y = i64::from(position.y);
+
+        let left = x.max(0);
+        let top = y.max(0);
+        let right = (x + source.width as i64).min(self.width as i64);
+        let bottom = (y + source.height as i64).min(self.height as i64);
+
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let source_x = (left - x) as usize;
+        let source_y = (top - y) as usize;
+        let target_x = left as usize;
+        let target_y = top as usize;
+        let width = (right - left) as usize;
+        let height = (bottom - top) as usize;
+
+        let source_stride = source.format.stride();
+        let target_stride = self.format.stride();
+
+        for row in 0..height {
+            let source_start = ((source_y + row) * source.width + source_x) * source_stride;
+            let target_start = ((target_y + row) * self.width + target_x) * target_stride;
+
+            let source_row = &source.data[source_start..source_start + width * source_stride];
+            let target_row = &mut self.data[target_start..target_start + width * target_stride];
+
+            if mode == BlitMode::Overwrite && source.format == self.format {
+                target_row.copy_from_slice(source_row);
+                continue;
+            }
+
+            for (source_pixel, target_pixel) in source_row
+                .chunks_exact(source_stride)
+                .zip(target_row.chunks_exact_mut(target_stride))
+            {
+                let color = read_color(source.format, source_pixel);
+
+                let output = match mode {
+                    BlitMode::Overwrite => color,
+                    BlitMode::AlphaBlend => {
+                        blend_over(color, read_color(self.format, target_pixel))
+                    }
+                };
+
+                write_color(self.format, output, target_pixel);
+            }
+        }
+    }
+
+    /// Flips the image horizontally, in place.
+    pub fn flip_horizontal(&mut self) {
+        let stride = self.format.stride();
+        let row_len = self.width * stride;
+
+        if row_len == 0 {
+            return;
+        }
+
+        for row in self.data.chunks_exact_mut(row_len) {
+            for i in 0..self.width / 2 {
+                let (left, right) = row.split_at_mut((self.width - i - 1) * stride);
+                left[i * stride..(i + 1) * stride].swap_with_slice(&mut right[..stride]);
+            }
+        }
+    }
+
+    /// Flips the image vertically, in place.
+    pub fn flip_vertical(&mut self) {
+        let row_len = self.width * self.format.stride();
+
+        for i in 0..self.height / 2 {
+            let (top, bottom) = self.data.split_at_mut((self.height - i - 1) * row_len);
+            top[i * row_len..(i + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+
+    /// Rotates the image clockwise by the given number of quarter turns, in place.
+    ///
+    /// If the number of turns is odd, the width and height of the image will be swapped.
+    pub fn rotate90(&mut self, turns: u8) {
+        match turns % 4 {
+            0 => {}
+            2 => {
+                self.flip_horizontal();
+                self.flip_vertical();
+            }
+            turns => {
+                let stride = self.format.stride();
+                let mut buffer = vec![0; self.data.len()];
+
+                // After an odd number of turns, the old height becomes the new width.
+                let new_width = self.height;
+
+                for y in 0..self.height {
+                    for x in 0..self.width {
+                        let (new_x, new_y) = if turns == 1 {
+                            (self.height - y - 1, x)
+                        } else {
+                            (y, self.width - x - 1)
+                        };
+
+                        let src = (x + y * self.width) * stride;
+                        let dst = (new_x + new_y * new_width) * stride;
+
+                        buffer[dst..dst + stride].copy_from_slice(&self.data[src..src + stride]);
+                    }
+                }
+
+                self.data = buffer;
+                std::mem::swap(&mut self.width, &mut self.height);
+            }
+        }
+    }
+
+    /// Creates a new `ImageData` by scaling the image to the given size.
+    ///
+    /// [`FilterMode::Nearest`] will copy the pixel closest to each sample point, while
+    /// [`FilterMode::Linear`] will blend between the four closest pixels. Samples are
+    /// taken from the center of each pixel, and the edges of the image are clamped.
+    ///
+    /// This will copy the data into a new buffer - as such, calling this function
+    /// can be expensive!
+    ///
+    /// # Panics
+    ///
+    /// Panics if either of the dimensions are negative.
+    pub fn resized(&self, width: i32, height: i32, filter_mode: FilterMode) -> ImageData {
+        assert!(
+            width >= 0 && height >= 0,
+            "image dimensions cannot be negative"
+        );
+
+        let width = width as usize;
+        let height = height as usize;
+        let stride = self.format.stride();
+
+        let mut data = vec![0; width * height * stride];
+
+        if self.width == 0 || self.height == 0 {
+            return ImageData {
+                data,
+                width,
+                height,
+                format: self.format,
+            };
+        }
+
+        let scale_x = self.width as f32 / width as f32;
+        let scale_y = self.height as f32 / height as f32;
+
+        for (i, target) in data.chunks_exact_mut(stride).enumerate() {
+            let src_x = ((i % width) as f32 + 0.5) * scale_x;
+            let src_y = ((i / width) as f32 + 0.5) * scale_y;
+
+            match filter_mode {
+                FilterMode::Nearest => {
+                    let x = usize::min(src_x as usize, self.width - 1);
+                    let y = usize::min(src_y as usize, self.height - 1);
+
+                    let idx = (x + y * self.width) * stride;
+                    target.copy_from_slice(&self.data[idx..idx + stride]);
+                }
+                FilterMode::Linear | FilterMode::Trilinear => {
+                    let x = f32::max(src_x - 0.5, 0.0);
+                    let y = f32::max(src_y - 0.5, 0.0);
+
+                    let x0 = usize::min(x as usize, self.width - 1);
+                    let y0 = usize::min(y as usize, self.height - 1);
+                    let x1 = usize::min(x0 + 1, self.width - 1);
+                    let y1 = usize::min(y0 + 1, self.height - 1);
+
+                    let tx = x - x0 as f32;
+                    let ty = y - y0 as f32;
+
+                    let pixel = |x: usize, y: usize| {
+                        let idx = (x + y * self.width) * stride;
+                        read_color(self.format, &self.data[idx..idx + stride])
+                    };
+
+                    let top = Color::lerp(pixel(x0, y0), pixel(x1, y0), tx);
+                    let bottom = Color::lerp(pixel(x0, y1), pixel(x1, y1), tx);
+
+                    write_color(self.format, Color::lerp(top, bottom, ty), target);
+                }
+            }
+        }
+
+        ImageData {
+            data,
+            width,
+            height,
+            format: self.format,
+        }
+    }
+
     /// Creates a new [`Texture`] from the stored data.
     ///
     /// # Errors
@@ -302,6 +593,255 @@ impl ImageData {
     pub fn premultiply(&mut self) {
         self.transform(|_, color| color.to_premultiplied())
     }
+
+    /// Generates a normal map from the image data, treating it as a heightfield.
+    ///
+    /// For images with the [`TextureFormat::R8`] format, the red channel will be used as the
+    /// height of each pixel. For all other formats, the luminance of the pixel's color will
+    /// be used instead. The gradient of the heightfield is calculated via a
+    /// [Sobel filter](https://en.wikipedia.org/wiki/Sobel_operator) - pixels on the edge
+    /// of the image are treated as if the edge pixels extend infinitely outwards.
+    ///
+    /// `strength` controls how steep the generated surface will be - higher values will
+    /// produce more pronounced normals, while a value of zero will produce a flat surface.
+    /// The `convention` parameter controls which direction the green channel of the normal
+    /// map points in, as different lighting shaders expect different conventions.
+    ///
+    /// The returned image will always have the [`TextureFormat::Rgba8`] format, with the
+    /// alpha channel set to one.
+    pub fn to_normal_map(&self, strength: f32, convention: NormalMapConvention) -> ImageData {
+        let stride = self.format.stride();
+        let mut data = vec![0; self.width * self.height * TextureFormat::Rgba8.stride()];
+
+        let height_at = |x: isize, y: isize| {
+            let x = x.clamp(0, self.width as isize - 1) as usize;
+            let y = y.clamp(0, self.height as isize - 1) as usize;
+
+            let idx = (x + y * self.width) * stride;
+            let color = read_color(self.format, &self.data[idx..idx + stride]);
+
+            match self.format {
+                TextureFormat::R8 => color.r,
+                _ => 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b,
+            }
+        };
+
+        for (i, target) in data
+            .chunks_exact_mut(TextureFormat::Rgba8.stride())
+            .enumerate()
+        {
+            let x = (i % self.width) as isize;
+            let y = (i / self.width) as isize;
+
+            let top_left = height_at(x - 1, y - 1);
+            let top = height_at(x, y - 1);
+            let top_right = height_at(x + 1, y - 1);
+            let left = height_at(x - 1, y);
+            let right = height_at(x + 1, y);
+            let bottom_left = height_at(x - 1, y + 1);
+            let bottom = height_at(x, y + 1);
+            let bottom_right = height_at(x + 1, y + 1);
+
+            let dx =
+                (top_right + 2.0 * right + bottom_right) - (top_left + 2.0 * left + bottom_left);
+            let dy =
+                (bottom_left + 2.0 * bottom + bottom_right) - (top_left + 2.0 * top + top_right);
+
+            // The gradient is calculated in image space, where Y points downwards.
+            let normal = Vec3::new(-dx * strength, -dy * strength, 1.0).normalized();
+
+            let normal_y = match convention {
+                NormalMapConvention::YUp => -normal.y,
+                NormalMapConvention::YDown => normal.y,
+            };
+
+            let color = Color::rgb(
+                normal.x * 0.5 + 0.5,
+                normal_y * 0.5 + 0.5,
+                normal.z * 0.5 + 0.5,
+            );
+
+            write_color(TextureFormat::Rgba8, color, target);
+        }
+
+        ImageData {
+            data,
+            width: self.width,
+            height: self.height,
+            format: TextureFormat::Rgba8,
+        }
+    }
+
+    /// Converts the image data to a different [`TextureFormat`].
+    ///
+    /// Any channels that are not supported by the new format will be discarded, and any
+    /// channels that are missing from the original format will be filled in as described
+    /// in [`get_pixel_color`](Self::get_pixel_color). Values outside of the `0.0` to `1.0`
+    /// range will be clamped when converting to an 8-bit format.
+    ///
+    /// If you are converting high dynamic range data (e.g. [`TextureFormat::Rgba16F`])
+    /// to an 8-bit format, [`to_format_dithered`](Self::to_format_dithered) can be used
+    /// to avoid banding.
+    pub fn to_format(&self, format: TextureFormat) -> ImageData {
+        self.to_format_dithered(format, DitherMode::None)
+    }
+
+    /// Converts the image data to a different [`TextureFormat`], applying dithering
+    /// to each channel before it is quantized.
+    ///
+    /// This can be used to reduce the banding that appears in smooth gradients when
+    /// converting high precision data (e.g. [`TextureFormat::Rgba16F`]) to an 8-bit format.
+    /// Dithering is deterministic, so the same input will always give the same output.
+    ///
+    /// Dithering only has an effect when converting to an 8-bit format - otherwise, this
+    /// method behaves the same as [`to_format`](Self::to_format). 8-bit data that is
+    /// converted to another 8-bit format will not be changed by the dithering.
+    pub fn to_format_dithered(&self, format: TextureFormat, dither: DitherMode) -> ImageData {
+        let source_stride = self.format.stride();
+        let target_stride = format.stride();
+
+        let mut data = vec![0; self.width * self.height * target_stride];
+
+        let quantized = matches!(
+            format,
+            TextureFormat::Rgba8 | TextureFormat::R8 | TextureFormat::Rg8
+        );
+
+        for (i, (source, target)) in self
+            .data
+            .chunks_exact(source_stride)
+            .zip(data.chunks_exact_mut(target_stride))
+            .enumerate()
+        {
+            let color = read_color(self.format, source);
+
+            let threshold = if quantized {
+                dither.threshold(i % self.width, i / self.width)
+            } else {
+                None
+            };
+
+            match threshold {
+                Some(threshold) => {
+                    let byte_data = [
+                        quantize(color.r, threshold),
+                        quantize(color.g, threshold),
+                        quantize(color.b, threshold),
+                        quantize(color.a, threshold),
+                    ];
+
+                    target.copy_from_slice(&byte_data[..target_stride]);
+                }
+
+                None => write_color(format, color, target),
+            }
+        }
+
+        ImageData {
+            data,
+            width: self.width,
+            height: self.height,
+            format,
+        }
+    }
+}
+
+/// How pixels should be combined when blitting one image onto another, via
+/// [`ImageData::blit_with_mode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BlitMode {
+    /// The source pixels will be blended with the existing pixels, based on the
+    /// source's alpha channel.
+    AlphaBlend,
+
+    /// The source pixels will replace the existing pixels, including their alpha
+    /// channel.
+    Overwrite,
+}
+
+/// Dithering algorithms that can be used when converting image data to a lower precision
+/// format, via [`ImageData::to_format_dithered`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DitherMode {
+    /// No dithering will be applied.
+    None,
+
+    /// Ordered dithering, using a 4x4 Bayer matrix.
+    Ordered4x4,
+
+    /// Ordered dithering, using an 8x8 Bayer matrix. This gives smoother results than
+    /// [`DitherMode::Ordered4x4`], at the cost of a slightly more visible pattern.
+    Ordered8x8,
+}
+
+impl DitherMode {
+    /// Returns the offset that should be added to a value (in the range `0.0` to `255.0`)
+    /// before it is truncated, or `None` if no dithering should be applied.
+    fn threshold(self, x: usize, y: usize) -> Option<f32> {
+        let (value, size) = match self {
+            DitherMode::None => return None,
+            DitherMode::Ordered4x4 => (BAYER_4X4[y % 4][x % 4], 4),
+            DitherMode::Ordered8x8 => (BAYER_8X8[y % 8][x % 8], 8),
+        };
+
+        Some((value as f32 + 0.5) / (size * size) as f32)
+    }
+}
+
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+fn quantize(value: f32, threshold: f32) -> u8 {
+    // NaN will be converted to zero by the cast.
+    (value.clamp(0.0, 1.0) * 255.0 + threshold)
+        .floor()
+        .min(255.0) as u8
+}
+
+/// The direction that the Y axis points in, when generating a normal map.
+///
+/// Different lighting shaders expect normal maps to be encoded differently,
+/// so you should pick whichever matches the shader you are using.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum NormalMapConvention {
+    /// The green channel points upwards. This is the convention used by OpenGL,
+    /// and by most 2D lighting shaders.
+    YUp,
+
+    /// The green channel points downwards. This is the convention used by DirectX,
+    /// and matches the direction of the Y axis in screen space.
+    YDown,
+}
+
+/// Composites a non-premultiplied source color over a non-premultiplied
+/// destination color.
+fn blend_over(source: Color, destination: Color) -> Color {
+    let dst_weight = destination.a * (1.0 - source.a);
+    let alpha = source.a + dst_weight;
+
+    if alpha <= 0.0 {
+        return Color::rgba(0.0, 0.0, 0.0, 0.0);
+    }
+
+    Color::rgba(
+        (source.r * source.a + destination.r * dst_weight) / alpha,
+        (source.g * source.a + destination.g * dst_weight) / alpha,
+        (source.b * source.a + destination.b * dst_weight) / alpha,
+        alpha,
+    )
 }
 
 fn read_color(format: TextureFormat, data: &[u8]) -> Color {
@@ -848,4 +1388,584 @@ mod tests {
             bytemuck::cast_slice(&output),
         );
     }
+
+    fn normal_map_test(
+        width: usize,
+        height: usize,
+        input: &[u8],
+        convention: NormalMapConvention,
+        output: &[u8],
+    ) {
+        let image =
+            ImageData::from_data(width as i32, height as i32, TextureFormat::R8, input).unwrap();
+
+        let normal_map = image.to_normal_map(0.625, convention);
+
+        assert_eq!(normal_map.format(), TextureFormat::Rgba8);
+        assert_eq!(normal_map.size(), (width as i32, height as i32));
+        assert_eq!(normal_map.as_bytes(), output);
+    }
+
+    #[test]
+    fn normal_map_flat() {
+        let image = ImageData::from_data(2, 2, TextureFormat::Rgba8, [0x66; 16]).unwrap();
+
+        let normal_map = image.to_normal_map(1.0, NormalMapConvention::YUp);
+
+        assert_eq!(normal_map.as_bytes(), &[0x7F, 0x7F, 0xFF, 0xFF].repeat(4));
+    }
+
+    #[test]
+    fn normal_map_horizontal_gradient() {
+        // Heights are 0.0, 0.2 and 0.4. With edge clamping, the horizontal Sobel
+        // gradients are 0.8, 1.6 and 0.8, which gives normals of (-0.5, 0.0, 1.0),
+        // (-1.0, 0.0, 1.0) and (-0.5, 0.0, 1.0) before normalization.
+        let expected = [
+            0x46, 0x7F, 0xF1, 0xFF, // Pixel 1
+            0x25, 0x7F, 0xD9, 0xFF, // Pixel 2
+            0x46, 0x7F, 0xF1, 0xFF, // Pixel 3
+        ];
+
+        normal_map_test(
+            3,
+            1,
+            &[0x00, 0x33, 0x66],
+            NormalMapConvention::YUp,
+            &expected,
+        );
+
+        normal_map_test(
+            3,
+            1,
+            &[0x00, 0x33, 0x66],
+            NormalMapConvention::YDown,
+            &expected,
+        );
+    }
+
+    #[test]
+    fn normal_map_vertical_gradient() {
+        // Same as above, but with the heights increasing downwards (in image space).
+        normal_map_test(
+            1,
+            3,
+            &[0x00, 0x33, 0x66],
+            NormalMapConvention::YUp,
+            &[
+                0x7F, 0xB8, 0xF1, 0xFF, // Pixel 1
+                0x7F, 0xD9, 0xD9, 0xFF, // Pixel 2
+                0x7F, 0xB8, 0xF1, 0xFF, // Pixel 3
+            ],
+        );
+
+        normal_map_test(
+            1,
+            3,
+            &[0x00, 0x33, 0x66],
+            NormalMapConvention::YDown,
+            &[
+                0x7F, 0x46, 0xF1, 0xFF, // Pixel 1
+                0x7F, 0x25, 0xD9, 0xFF, // Pixel 2
+                0x7F, 0x46, 0xF1, 0xFF, // Pixel 3
+            ],
+        );
+    }
+
+    #[test]
+    fn to_format_rgba16f_to_rgba8() {
+        let input = f16_vec![0.0, 0.5, 1.0, 1.0, -1.0, 2.0, 0.25, 0.0];
+
+        let image =
+            ImageData::from_data(2, 1, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let output = image.to_format(TextureFormat::Rgba8);
+
+        assert_eq!(output.format(), TextureFormat::Rgba8);
+        assert_eq!(
+            output.as_bytes(),
+            &[0x00, 0x7F, 0xFF, 0xFF, 0x00, 0xFF, 0x3F, 0x00]
+        );
+    }
+
+    #[test]
+    fn to_format_rgba8_to_r8() {
+        let image =
+            ImageData::from_data(2, 1, TextureFormat::Rgba8, [1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
+
+        let output = image.to_format(TextureFormat::R8);
+
+        assert_eq!(output.as_bytes(), &[1, 5]);
+    }
+
+    #[test]
+    fn bayer_matrices_are_permutations() {
+        let mut values_4x4: Vec<u8> = BAYER_4X4.iter().flatten().copied().collect();
+        values_4x4.sort_unstable();
+        assert_eq!(values_4x4, (0..16).collect::<Vec<u8>>());
+
+        let mut values_8x8: Vec<u8> = BAYER_8X8.iter().flatten().copied().collect();
+        values_8x8.sort_unstable();
+        assert_eq!(values_8x8, (0..64).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn dithering_preserves_8bit_data() {
+        let input: Vec<u8> = (0..=255).collect();
+        let image = ImageData::from_data(8, 8, TextureFormat::Rgba8, input.as_slice()).unwrap();
+
+        for mode in [DitherMode::Ordered4x4, DitherMode::Ordered8x8] {
+            let output = image.to_format_dithered(TextureFormat::Rgba8, mode);
+            assert_eq!(output.as_bytes(), image.as_bytes());
+        }
+    }
+
+    #[test]
+    fn dithering_clamps_out_of_range_values() {
+        let input = f16_vec![-4.0, 8.0, -0.5, 1.5];
+
+        let image =
+            ImageData::from_data(1, 1, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let output = image.to_format_dithered(TextureFormat::Rgba8, DitherMode::Ordered8x8);
+
+        assert_eq!(output.as_bytes(), &[0x00, 0xFF, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn dithering_mean_error_is_bounded() {
+        for (mode, size) in [(DitherMode::Ordered4x4, 4), (DitherMode::Ordered8x8, 8)] {
+            for value in [0.1, 0.3337, 0.5, 0.6875, 0.999] {
+                let value = f16::from_f32(value);
+                let input = vec![value; size * size * 4];
+
+                let image = ImageData::from_data(
+                    size as i32,
+                    size as i32,
+                    TextureFormat::Rgba16F,
+                    bytemuck::cast_slice(&input),
+                )
+                .unwrap();
+
+                let output = image.to_format_dithered(TextureFormat::Rgba8, mode);
+
+                let mean = output.as_bytes().iter().map(|&b| b as f32).sum::<f32>()
+                    / output.as_bytes().len() as f32;
+
+                let expected = value.to_f32() * 255.0;
+                let bound = 1.0 / (size * size) as f32;
+
+                assert!(
+                    (mean - expected).abs() <= bound,
+                    "mean {} too far from {} with {:?}",
+                    mean,
+                    expected,
+                    mode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dithering_is_deterministic() {
+        let input: Vec<f16> = (0..64 * 4)
+            .map(|i| f16::from_f32(i as f32 / 256.0))
+            .collect();
+
+        let image =
+            ImageData::from_data(8, 8, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let a = image.to_format_dithered(TextureFormat::Rgba8, DitherMode::Ordered8x8);
+        let b = image.to_format_dithered(TextureFormat::Rgba8, DitherMode::Ordered8x8);
+
+        assert_eq!(a.as_bytes(), b.as_bytes());
+    }
+
+    #[test]
+    fn flip_rows() {
+        let mut odd = ImageData::from_data(1, 3, TextureFormat::Rg8, [1, 2, 3, 4, 5, 6]).unwrap();
+        odd.flip_vertical();
+        assert_eq!(odd.as_bytes(), [5, 6, 3, 4, 1, 2]);
+
+        let mut even = ImageData::from_data(2, 2, TextureFormat::R8, [1, 2, 3, 4]).unwrap();
+        even.flip_vertical();
+        assert_eq!(even.as_bytes(), [3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn flip_horizontal_odd_width() {
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, [1, 2, 3, 4, 5, 6]).unwrap();
+        image.flip_horizontal();
+        assert_eq!(image.as_bytes(), [3, 2, 1, 6, 5, 4]);
+
+        let mut image = ImageData::from_data(
+            3,
+            1,
+            TextureFormat::Rgba8,
+            [
+                0x00, 0x01, 0x02, 0x03, // Pixel 1
+                0x04, 0x05, 0x06, 0x07, // Pixel 2
+                0x08, 0x09, 0x0A, 0x0B, // Pixel 3
+            ],
+        )
+        .unwrap();
+
+        image.flip_horizontal();
+
+        assert_eq!(
+            image.as_bytes(),
+            [
+                0x08, 0x09, 0x0A, 0x0B, // Pixel 3
+                0x04, 0x05, 0x06, 0x07, // Pixel 2
+                0x00, 0x01, 0x02, 0x03, // Pixel 1
+            ]
+        );
+    }
+
+    #[test]
+    fn flip_rgba16f() {
+        let input = f16_vec![
+            0.0, 0.1, 0.2, 0.3, // Pixel 1
+            0.4, 0.5, 0.6, 0.7, // Pixel 2
+            0.8, 0.9, 1.0, 1.1, // Pixel 3
+        ];
+
+        let mut image =
+            ImageData::from_data(3, 1, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        image.flip_horizontal();
+
+        let expected = f16_vec![
+            0.8, 0.9, 1.0, 1.1, // Pixel 3
+            0.4, 0.5, 0.6, 0.7, // Pixel 2
+            0.0, 0.1, 0.2, 0.3, // Pixel 1
+        ];
+
+        assert_eq!(image.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+
+        let mut image =
+            ImageData::from_data(1, 3, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        image.flip_vertical();
+
+        assert_eq!(image.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+    }
+
+    #[test]
+    fn rotate90_odd_dimensions() {
+        // 1 2 3
+        // 4 5 6
+        let input = [1, 2, 3, 4, 5, 6];
+
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, input).unwrap();
+        image.rotate90(1);
+        assert_eq!(image.size(), (2, 3));
+        assert_eq!(image.as_bytes(), [4, 1, 5, 2, 6, 3]);
+
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, input).unwrap();
+        image.rotate90(2);
+        assert_eq!(image.size(), (3, 2));
+        assert_eq!(image.as_bytes(), [6, 5, 4, 3, 2, 1]);
+
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, input).unwrap();
+        image.rotate90(3);
+        assert_eq!(image.size(), (2, 3));
+        assert_eq!(image.as_bytes(), [3, 6, 2, 5, 1, 4]);
+
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, input).unwrap();
+        image.rotate90(4);
+        assert_eq!(image.size(), (3, 2));
+        assert_eq!(image.as_bytes(), input);
+
+        let mut image = ImageData::from_data(3, 2, TextureFormat::R8, input).unwrap();
+        image.rotate90(1);
+        image.rotate90(3);
+        assert_eq!(image.size(), (3, 2));
+        assert_eq!(image.as_bytes(), input);
+    }
+
+    #[test]
+    fn rotate90_rg8() {
+        let mut image = ImageData::from_data(3, 1, TextureFormat::Rg8, [1, 2, 3, 4, 5, 6]).unwrap();
+
+        image.rotate90(1);
+
+        assert_eq!(image.size(), (1, 3));
+        assert_eq!(image.as_bytes(), [1, 2, 3, 4, 5, 6]);
+
+        image.rotate90(1);
+
+        assert_eq!(image.size(), (3, 1));
+        assert_eq!(image.as_bytes(), [5, 6, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn resized_nearest() {
+        let image = ImageData::from_data(3, 1, TextureFormat::R8, [1, 2, 3]).unwrap();
+
+        let larger = image.resized(6, 2, FilterMode::Nearest);
+        assert_eq!(larger.size(), (6, 2));
+        assert_eq!(larger.as_bytes(), [1, 1, 2, 2, 3, 3, 1, 1, 2, 2, 3, 3]);
+
+        let smaller = image.resized(1, 1, FilterMode::Nearest);
+        assert_eq!(smaller.as_bytes(), [2]);
+
+        let input = f16_vec![
+            0.0, 0.1, 0.2, 0.3, // Pixel 1
+            0.4, 0.5, 0.6, 0.7, // Pixel 2
+        ];
+
+        let image =
+            ImageData::from_data(2, 1, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let larger = image.resized(4, 1, FilterMode::Nearest);
+
+        let expected = f16_vec![
+            0.0, 0.1, 0.2, 0.3, // Pixel 1
+            0.0, 0.1, 0.2, 0.3, // Pixel 1
+            0.4, 0.5, 0.6, 0.7, // Pixel 2
+            0.4, 0.5, 0.6, 0.7, // Pixel 2
+        ];
+
+        assert_eq!(larger.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+    }
+
+    #[test]
+    fn resized_linear() {
+        let image = ImageData::from_data(3, 1, TextureFormat::R8, [0, 100, 200]).unwrap();
+
+        // Sampling exactly on the pixel centers gives back the original values.
+        let same = image.resized(3, 1, FilterMode::Linear);
+        assert_eq!(same.as_bytes(), [0, 100, 200]);
+
+        // The edges are clamped, and the samples in between are blended.
+        let larger = image.resized(6, 1, FilterMode::Linear);
+        assert_eq!(larger.as_bytes(), [0, 25, 75, 125, 175, 200]);
+
+        let input = f16_vec![
+            0.0, 0.0, 0.0, 0.0, // Pixel 1
+            1.0, 0.5, 0.25, 1.0, // Pixel 2
+        ];
+
+        let image =
+            ImageData::from_data(1, 2, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let smaller = image.resized(1, 1, FilterMode::Linear);
+        let expected = f16_vec![0.5, 0.25, 0.125, 0.5];
+
+        assert_eq!(smaller.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+    }
+
+    #[test]
+    fn resized_to_zero() {
+        let image = ImageData::from_data(2, 2, TextureFormat::Rgba8, [0; 16]).unwrap();
+        let empty = image.resized(0, 3, FilterMode::Linear);
+
+        assert_eq!(empty.size(), (0, 3));
+        assert!(empty.as_bytes().is_empty());
+    }
+
+    #[test]
+    fn filled() {
+        let image = ImageData::filled(2, 1, TextureFormat::Rg8, Color::rgba8(10, 20, 30, 40));
+
+        assert_eq!(image.size(), (2, 1));
+        assert_eq!(image.as_bytes(), [10, 20, 10, 20]);
+
+        let image = ImageData::filled(
+            1,
+            1,
+            TextureFormat::Rgba16F,
+            Color::rgba(1.0, 0.5, 0.25, 1.0),
+        );
+        let expected = f16_vec![1.0, 0.5, 0.25, 1.0];
+
+        assert_eq!(image.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+
+        assert!(ImageData::filled(0, 4, TextureFormat::R8, Color::WHITE)
+            .as_bytes()
+            .is_empty());
+    }
+
+    #[test]
+    fn blit_is_clipped_at_edges() {
+        let source = ImageData::from_data(2, 2, TextureFormat::R8, [1, 2, 3, 4]).unwrap();
+
+        let blit = |position| {
+            let mut target = ImageData::filled(3, 3, TextureFormat::R8, Color::BLACK);
+            target.blit_with_mode(&source, position, BlitMode::Overwrite);
+            target.into_bytes()
+        };
+
+        // Left
+        assert_eq!(blit(Vec2::new(-1, 0)), [2, 0, 0, 4, 0, 0, 0, 0, 0]);
+
+        // Right
+        assert_eq!(blit(Vec2::new(2, 1)), [0, 0, 0, 0, 0, 1, 0, 0, 3]);
+
+        // Top
+        assert_eq!(blit(Vec2::new(1, -1)), [0, 3, 4, 0, 0, 0, 0, 0, 0]);
+
+        // Bottom
+        assert_eq!(blit(Vec2::new(0, 2)), [0, 0, 0, 0, 0, 0, 1, 2, 0]);
+
+        // Corner
+        assert_eq!(blit(Vec2::new(-1, -1)), [4, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        // Entirely outside
+        assert_eq!(blit(Vec2::new(3, 0)), [0; 9]);
+        assert_eq!(blit(Vec2::new(0, -2)), [0; 9]);
+        assert_eq!(blit(Vec2::new(i32::MIN, i32::MAX)), [0; 9]);
+    }
+
+    #[test]
+    fn blit_alpha_blends() {
+        let source =
+            ImageData::from_data(2, 1, TextureFormat::Rgba8, [255, 0, 0, 255, 255, 0, 0, 0])
+                .unwrap();
+
+        let mut target = ImageData::filled(2, 1, TextureFormat::Rgba8, Color::BLUE);
+        target.blit(&source, Vec2::zero());
+
+        // Opaque pixels replace the target, transparent pixels leave it unchanged.
+        assert_eq!(target.as_bytes(), [255, 0, 0, 255, 0, 0, 255, 255]);
+
+        let mut target = ImageData::filled(2, 1, TextureFormat::Rgba8, Color::BLUE);
+        target.blit_with_mode(&source, Vec2::zero(), BlitMode::Overwrite);
+
+        assert_eq!(target.as_bytes(), source.as_bytes());
+    }
+
+    #[test]
+    fn blit_onto_rgba16f() {
+        let input = f16_vec![
+            1.0, 0.0, 0.0, 0.5, // Pixel 1
+            0.0, 1.0, 0.0, 0.0, // Pixel 2
+        ];
+
+        let source =
+            ImageData::from_data(2, 1, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        let mut target = ImageData::filled(2, 1, TextureFormat::Rgba16F, Color::BLUE);
+        target.blit(&source, Vec2::zero());
+
+        let expected = f16_vec![
+            0.5, 0.0, 0.5, 1.0, // Pixel 1
+            0.0, 0.0, 1.0, 1.0, // Pixel 2
+        ];
+
+        assert_eq!(target.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+
+        // Blitting from a different format converts via `Color`.
+        let source = ImageData::from_data(1, 1, TextureFormat::R8, [255]).unwrap();
+
+        let mut target = ImageData::filled(2, 1, TextureFormat::Rgba16F, Color::BLUE);
+        target.blit(&source, Vec2::new(1, 0));
+
+        let expected = f16_vec![
+            0.0, 0.0, 1.0, 1.0, // Pixel 1
+            1.0, 0.0, 0.0, 1.0, // Pixel 2
+        ];
+
+        assert_eq!(target.as_bytes(), bytemuck::cast_slice::<_, u8>(&expected));
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tetra-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn save_round_trips_png() {
+        let path = temp_path("round_trip.png");
+
+        let image = ImageData::from_data(
+            2,
+            1,
+            TextureFormat::Rgba8,
+            [255, 0, 0, 255, 0, 128, 255, 64],
+        )
+        .unwrap();
+
+        image.save(&path).unwrap();
+        let loaded = ImageData::new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.size(), (2, 1));
+        assert_eq!(loaded.as_bytes(), image.as_bytes());
+    }
+
+    #[test]
+    fn save_converts_other_formats() {
+        let path = temp_path("converted.png");
+
+        let image = ImageData::from_data(1, 1, TextureFormat::R8, [200]).unwrap();
+        image.save(&path).unwrap();
+        let loaded = ImageData::new(&path).unwrap();
+
+        assert_eq!(loaded.as_bytes(), [200, 0, 0, 255]);
+
+        let float_data = f16_vec![1.0, 2.0, -1.0, 1.0];
+
+        let image = ImageData::from_data(
+            1,
+            1,
+            TextureFormat::Rgba16F,
+            bytemuck::cast_slice(&float_data),
+        )
+        .unwrap();
+
+        image.save(&path).unwrap();
+        let loaded = ImageData::new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.as_bytes(), [255, 255, 0, 255]);
+    }
+
+    #[test]
+    fn save_dithers_high_precision_data() {
+        let path = temp_path("dithered.png");
+
+        let input: Vec<f16> = (0..64 * 4)
+            .map(|i| f16::from_f32(0.5 + i as f32 / 1024.0))
+            .collect();
+
+        let image =
+            ImageData::from_data(8, 8, TextureFormat::Rgba16F, bytemuck::cast_slice(&input))
+                .unwrap();
+
+        image.save(&path).unwrap();
+        let loaded = ImageData::new(&path).unwrap();
+
+        let expected = image.to_format_dithered(TextureFormat::Rgba8, DitherMode::Ordered8x8);
+        assert_eq!(loaded.as_bytes(), expected.as_bytes());
+
+        image.save_dithered(&path, DitherMode::None).unwrap();
+        let loaded = ImageData::new(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let expected = image.to_format(TextureFormat::Rgba8);
+        assert_eq!(loaded.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn save_reports_errors() {
+        let image = ImageData::from_data(1, 1, TextureFormat::Rgba8, [0, 0, 0, 0]).unwrap();
+
+        assert!(matches!(
+            image.save(temp_path("unknown.xyz")),
+            Err(TetraError::FailedToEncodeImage(_))
+        ));
+
+        assert!(matches!(
+            image.save(temp_path("missing").join("image.png")),
+            Err(TetraError::FailedToSaveAsset { .. })
+        ));
+    }
 }
@@ -9,19 +9,22 @@
 
 pub use lyon_tessellation::path::builder::BorderRadii;
 
+use std::f32::consts::TAU;
 use std::rc::Rc;
 
 use bytemuck::{Pod, Zeroable};
 use lyon_tessellation::geom::euclid::Point2D;
+use lyon_tessellation::geom::{Arc, CubicBezierSegment, QuadraticBezierSegment};
 use lyon_tessellation::math::{Angle, Box2D, Point, Vector};
-use lyon_tessellation::path::{Polygon, Winding};
+use lyon_tessellation::path::path::Builder as PathBuilder;
+use lyon_tessellation::path::{Path, Polygon, Winding};
 use lyon_tessellation::{
     BuffersBuilder, FillOptions, FillTessellator, FillVertex, FillVertexConstructor, StrokeOptions,
     StrokeTessellator, StrokeVertex, StrokeVertexConstructor, VertexBuffers,
 };
 
-use crate::graphics::{self, Color, DrawParams, Rectangle, Texture};
-use crate::math::Vec2;
+use crate::graphics::{self, Color, DrawParams, Rectangle, Shader, Texture};
+use crate::math::{Mat3, Vec2, Vec3};
 use crate::platform::{RawIndexBuffer, RawVertexBuffer};
 use crate::Context;
 use crate::{Result, TetraError};
@@ -46,15 +49,22 @@ pub struct Vertex {
     /// This will be multiplied by the `color` of the `DrawParams` when drawing a
     /// mesh.
     pub color: Color,
+
+    /// The depth of the vertex, between `-1.0` and `1.0`.
+    ///
+    /// This is only used if a [depth test](crate::graphics::set_depth_test) is active,
+    /// in which case lower values are treated as being closer to the screen.
+    pub depth: f32,
 }
 
 impl Vertex {
-    /// Creates a new vertex.
+    /// Creates a new vertex, with a depth of `0.0`.
     pub fn new(position: Vec2<f32>, uv: Vec2<f32>, color: Color) -> Vertex {
         Vertex {
             position,
             uv,
             color,
+            depth: 0.0,
         }
     }
 }
@@ -65,6 +75,73 @@ impl Vertex {
 unsafe impl Pod for Vertex {}
 unsafe impl Zeroable for Vertex {}
 
+/// An individual piece of per-instance data, for use with an [`InstanceBuffer`].
+///
+/// In the [default instanced shader](super::shader::DEFAULT_INSTANCED_VERTEX_SHADER), the transform is applied to the vertex positions before the
+/// transform matrix and projection, and the color is multiplied with the vertex color.
+/// Custom shaders can access this data via the `a_instance_transform` and
+/// `a_instance_color` attributes (see the [`Shader`] docs for more details).
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Instance {
+    /// A 2D transformation matrix for the instance.
+    ///
+    /// This can be created via [`Instance::from_params`], or built manually using the
+    /// 2D methods on [`Mat3`] (e.g. [`Mat3::translation_2d`]).
+    pub transform: Mat3<f32>,
+
+    /// The color of the instance.
+    pub color: Color,
+}
+
+impl Instance {
+    /// Creates a new instance.
+    pub fn new(transform: Mat3<f32>, color: Color) -> Instance {
+        Instance { transform, color }
+    }
+
+    /// Creates a new instance, positioned and colored using [`DrawParams`].
+    ///
+    /// The `filter_mode` of the params is ignored.
+    pub fn from_params<P>(params: P) -> Instance
+    where
+        P: Into<DrawParams>,
+    {
+        let params = params.into();
+        let matrix = params.to_matrix();
+
+        // The 2D parts of the matrix are the X and Y axes, plus the translation.
+        let transform = Mat3::from_col_arrays([
+            [matrix.cols.x.x, matrix.cols.x.y, 0.0],
+            [matrix.cols.y.x, matrix.cols.y.y, 0.0],
+            [matrix.cols.w.x, matrix.cols.w.y, 1.0],
+        ]);
+
+        Instance {
+            transform,
+            color: params.color,
+        }
+    }
+
+    /// Applies the instance's transform to a point.
+    pub fn transform_point(&self, point: Vec2<f32>) -> Vec2<f32> {
+        (self.transform * Vec3::new(point.x, point.y, 1.0)).xy()
+    }
+}
+
+impl Default for Instance {
+    fn default() -> Instance {
+        Instance {
+            transform: Mat3::identity(),
+            color: Color::WHITE,
+        }
+    }
+}
+
+// SAFETY: See the equivalent impl for `Vertex`.
+unsafe impl Pod for Instance {}
+unsafe impl Zeroable for Instance {}
+
 /// The expected usage of a GPU buffer.
 ///
 /// The GPU may optionally use this to optimize data storage and access.
@@ -116,7 +193,7 @@ impl VertexWinding {
 ///
 #[derive(Clone, Debug, PartialEq)]
 pub struct VertexBuffer {
-    handle: Rc<RawVertexBuffer>,
+    pub(crate) handle: Rc<RawVertexBuffer>,
 }
 
 impl VertexBuffer {
@@ -173,6 +250,88 @@ impl VertexBuffer {
     }
 }
 
+/// Per-instance data, stored in GPU memory.
+///
+/// An instance buffer can be attached to a [`Mesh`] (via [`Mesh::set_instance_buffer`]), so
+/// that each instance drawn by [`Mesh::draw_instanced`] can have its own transform and color.
+/// This allows you to draw large numbers of objects that share the same geometry in a
+/// single draw call.
+///
+/// Per-instance data requires OpenGL 3.3, or the `ARB_instanced_arrays` extension - this is
+/// supported by almost all hardware that can run Tetra.
+///
+/// # Performance
+///
+/// When you create or modify an instance buffer, you are effectively 'uploading' data to the GPU,
+/// which can be relatively slow. That said, it is usually still much faster than drawing each
+/// object individually, even if the data changes every frame - in that case, make sure you
+/// create the buffer with the [`BufferUsage::Stream`] usage hint.
+///
+/// You can clone an instance buffer cheaply, as it is a [reference-counted](https://doc.rust-lang.org/std/rc/struct.Rc.html)
+/// handle to a GPU resource. However, this does mean that modifying a buffer (e.g.
+/// calling `set_data`) will also affect any clones that exist of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstanceBuffer {
+    pub(crate) handle: Rc<RawVertexBuffer>,
+}
+
+impl InstanceBuffer {
+    /// Creates a new instance buffer.
+    ///
+    /// The buffer will be created with the [`BufferUsage::Dynamic`] usage hint - this can
+    /// be overridden via the [`with_usage`](Self::with_usage) constructor.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the underlying
+    ///   graphics API encounters an error, or if per-instance data is not supported.
+    pub fn new(ctx: &mut Context, instances: &[Instance]) -> Result<InstanceBuffer> {
+        InstanceBuffer::with_usage(ctx, instances, BufferUsage::Dynamic)
+    }
+
+    /// Creates a new instance buffer, with the specified usage hint.
+    ///
+    /// The GPU may optionally use the usage hint to optimize data storage and access.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the underlying
+    ///   graphics API encounters an error, or if per-instance data is not supported.
+    pub fn with_usage(
+        ctx: &mut Context,
+        instances: &[Instance],
+        usage: BufferUsage,
+    ) -> Result<InstanceBuffer> {
+        let buffer = ctx.device.new_instance_buffer(instances.len(), usage)?;
+
+        ctx.device.set_vertex_buffer_data(&buffer, instances, 0);
+
+        Ok(InstanceBuffer {
+            handle: Rc::new(buffer),
+        })
+    }
+
+    /// Uploads new instance data to the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is out of bounds.
+    pub fn set_data(&self, ctx: &mut Context, instances: &[Instance], offset: usize) {
+        ctx.device
+            .set_vertex_buffer_data(&self.handle, instances, offset);
+    }
+
+    /// Returns the number of instances that the buffer can hold.
+    pub fn len(&self) -> usize {
+        self.handle.count()
+    }
+
+    /// Returns `true` if the buffer has no space for instances.
+    pub fn is_empty(&self) -> bool {
+        self.handle.count() == 0
+    }
+}
+
 /// Index data, stored in GPU memory.
 ///
 /// An index buffer can be used as part of a [`Mesh`], in order to describe which vertex data should be drawn,
@@ -198,7 +357,7 @@ impl VertexBuffer {
 /// calling `set_data`) will also affect any clones that exist of it.
 #[derive(Clone, Debug, PartialEq)]
 pub struct IndexBuffer {
-    handle: Rc<RawIndexBuffer>,
+    pub(crate) handle: Rc<RawIndexBuffer>,
 }
 
 impl IndexBuffer {
@@ -254,6 +413,129 @@ struct DrawRange {
     count: usize,
 }
 
+/// The state needed to submit a single draw call to the graphics hardware, via
+/// [`graphics::draw_buffers`](crate::graphics::draw_buffers).
+///
+/// This is a lower level alternative to [`Mesh`], for when you are managing your own
+/// buffers and do not want to bundle them together. [`DrawCall::new`] provides the same
+/// defaults as [`Mesh::new`], and the rest of the fields can be overridden using
+/// struct update syntax.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tetra::graphics::{self, mesh::{DrawCall, VertexBuffer}, DrawParams};
+/// # use tetra::Context;
+/// # fn draw(ctx: &mut Context, particles: &VertexBuffer) {
+/// graphics::draw_buffers(
+///     ctx,
+///     DrawCall {
+///         range: Some((0, 300)),
+///         backface_culling: false,
+///         ..DrawCall::new(particles)
+///     },
+///     DrawParams::new(),
+/// );
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawCall<'a> {
+    /// The vertex data to draw.
+    pub vertex_buffer: &'a VertexBuffer,
+
+    /// The index buffer that determines which vertices are drawn, and in what order.
+    ///
+    /// If this is `None`, the vertices will be drawn in the order they are stored.
+    pub index_buffer: Option<&'a IndexBuffer>,
+
+    /// The per-instance data to draw with.
+    ///
+    /// If this is set, the number of instances drawn will be limited to the size of
+    /// the buffer.
+    pub instance_buffer: Option<&'a InstanceBuffer>,
+
+    /// The texture that the vertices will sample from.
+    ///
+    /// If this is `None`, the geometry will be drawn untextured (i.e. in white).
+    pub texture: Option<&'a Texture>,
+
+    /// The shader to draw with.
+    ///
+    /// If this is `None`, the [currently active shader](crate::graphics::set_shader) will
+    /// be used (or the default shader, if none is active).
+    pub shader: Option<&'a Shader>,
+
+    /// The range of vertices (or indices, if an index buffer is provided) to draw, as
+    /// a start position and a count.
+    ///
+    /// If this is `None`, all of the data in the buffer will be drawn.
+    pub range: Option<(usize, usize)>,
+
+    /// Which winding order represents front-facing geometry.
+    pub winding: VertexWinding,
+
+    /// Whether back-facing geometry should be culled (not rendered).
+    pub backface_culling: bool,
+
+    /// The number of instances to draw.
+    pub instances: usize,
+}
+
+impl<'a> DrawCall<'a> {
+    /// Creates a new draw call for the given vertex buffer, with the default settings:
+    ///
+    /// * No index buffer, instance buffer, texture or custom shader
+    /// * All of the data in the buffer is drawn
+    /// * [`VertexWinding::CounterClockwise`] is front-facing, and back faces are culled
+    /// * A single instance is drawn
+    pub fn new(vertex_buffer: &'a VertexBuffer) -> DrawCall<'a> {
+        DrawCall {
+            vertex_buffer,
+            index_buffer: None,
+            instance_buffer: None,
+            texture: None,
+            shader: None,
+            range: None,
+            winding: VertexWinding::CounterClockwise,
+            backface_culling: true,
+            instances: 1,
+        }
+    }
+
+    /// Returns the start position and count of the data that will be drawn.
+    pub(crate) fn resolve_range(&self) -> (usize, usize) {
+        resolve_range(
+            self.range,
+            self.index_buffer.map(|i| i.handle.count()),
+            self.vertex_buffer.handle.count(),
+        )
+    }
+
+    /// Returns the number of instances that will be drawn.
+    pub(crate) fn resolve_instances(&self) -> usize {
+        resolve_instances(self.instances, self.instance_buffer.map(|i| i.len()))
+    }
+}
+
+fn resolve_instances(instances: usize, instance_count: Option<usize>) -> usize {
+    match instance_count {
+        Some(count) => usize::min(instances, count),
+        None => instances,
+    }
+}
+
+fn resolve_range(
+    range: Option<(usize, usize)>,
+    index_count: Option<usize>,
+    vertex_count: usize,
+) -> (usize, usize) {
+    match (range, index_count) {
+        (Some(range), _) => range,
+        (_, Some(count)) => (0, count),
+        (_, None) => (0, vertex_count),
+    }
+}
+
 /// Ways of drawing a shape.
 #[derive(Copy, Clone, Debug)]
 pub enum ShapeStyle {
@@ -263,6 +545,37 @@ pub enum ShapeStyle {
     Stroke(f32),
 }
 
+/// Ways of generating texture co-ordinates for the vertices created by a [`GeometryBuilder`].
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum UvMode {
+    /// All vertices will have UVs of `(0.0, 0.0)`.
+    ///
+    /// This is the default, and is fine for untextured shapes.
+    #[default]
+    None,
+
+    /// The given rectangle will be mapped onto the range of `0.0` to `1.0` on both axes,
+    /// with the top-left corner mapping to `(0.0, 0.0)`. This works the same way as
+    /// drawing a texture, so the texture will be stretched over the rectangle.
+    ///
+    /// The rectangle is specified in the same co-ordinate space as the shapes. Points
+    /// outside of the rectangle will have UVs outside of the `0.0` to `1.0` range, and
+    /// so will be affected by the texture's wrap mode.
+    Rect(Rectangle),
+}
+
+impl UvMode {
+    fn map(&self, position: Vec2<f32>) -> Vec2<f32> {
+        match self {
+            UvMode::None => Vec2::zero(),
+            UvMode::Rect(rectangle) => Vec2::new(
+                (position.x - rectangle.x) / rectangle.width,
+                (position.y - rectangle.y) / rectangle.height,
+            ),
+        }
+    }
+}
+
 /// A 2D mesh that can be drawn to the screen.
 ///
 /// A `Mesh` is a wrapper for a [`VertexBuffer`], which allows it to be drawn in combination with several
@@ -270,6 +583,7 @@ pub enum ShapeStyle {
 ///
 /// * A [`Texture`] that individual vertices can sample from.
 /// * An [`IndexBuffer`] that can be used to modify the order/subset of vertices that are drawn.
+/// * An [`InstanceBuffer`] that provides per-instance data when drawing multiple instances.
 /// * A winding order, which determines which side of the geometry is front-facing.
 /// * A backface culling flag, which determines whether back-facing geometry should be drawn.
 /// * A draw range, which can be used to draw subsections of the mesh.
@@ -299,6 +613,7 @@ pub enum ShapeStyle {
 pub struct Mesh {
     vertex_buffer: VertexBuffer,
     index_buffer: Option<IndexBuffer>,
+    instance_buffer: Option<InstanceBuffer>,
     texture: Option<Texture>,
     draw_range: Option<DrawRange>,
     winding: VertexWinding,
@@ -311,6 +626,7 @@ impl Mesh {
         Mesh {
             vertex_buffer,
             index_buffer: None,
+            instance_buffer: None,
             texture: None,
             draw_range: None,
             winding: VertexWinding::CounterClockwise,
@@ -323,6 +639,7 @@ impl Mesh {
         Mesh {
             vertex_buffer,
             index_buffer: Some(index_buffer),
+            instance_buffer: None,
             texture: None,
             winding: VertexWinding::CounterClockwise,
             draw_range: None,
@@ -341,11 +658,15 @@ impl Mesh {
     /// Draws multiple instances of the mesh to the screen (or to a canvas,
     /// if one is enabled).
     ///
-    /// You will need to use a custom [`Shader`](crate::graphics::Shader) in order to pass unique
-    /// properties to each instance. Currently, the easiest way of doing this is via uniform
-    /// arrays - however, there is a hardware-determined limit on how many uniform locations
-    /// an individual shader can use, so this may not work if you're rendering a large
-    /// number of objects.
+    /// To give each instance a unique transform and color, attach an [`InstanceBuffer`] via
+    /// [`set_instance_buffer`](Self::set_instance_buffer) - the default shader will apply this
+    /// data automatically, and the number of instances drawn will be limited to the size of
+    /// the buffer.
+    ///
+    /// For anything more complex, you will need to use a custom [`Shader`](crate::graphics::Shader).
+    /// Data can be passed to the shader via uniform arrays - however, there is a
+    /// hardware-determined limit on how many uniform locations an individual shader can use,
+    /// so this may not work if you're rendering a large number of objects.
     ///
     /// This should usually only be used for complex meshes - instancing can be inefficient
     /// for simple geometry (e.g. quads). That said, as with all things performance-related,
@@ -354,54 +675,33 @@ impl Mesh {
     where
         P: Into<DrawParams>,
     {
-        graphics::flush(ctx);
-
-        let texture = self
-            .texture
-            .as_ref()
-            .unwrap_or(&ctx.graphics.default_texture);
-
-        let shader = ctx
-            .graphics
-            .shader
-            .as_ref()
-            .unwrap_or(&ctx.graphics.default_shader);
-
-        let params = params.into();
-        let model_matrix = params.to_matrix();
-
-        // TODO: Failing to apply the defaults should be handled more gracefully than this,
-        // but we can't do that without breaking changes.
-        let _ = shader.set_default_uniforms(
-            &mut ctx.device,
-            ctx.graphics.projection_matrix * ctx.graphics.transform_matrix * model_matrix,
-            params.color,
+        graphics::draw_buffers(
+            ctx,
+            DrawCall {
+                instances,
+                ..self.to_draw_call()
+            },
+            params,
         );
+    }
 
-        ctx.device.cull_face(self.backface_culling);
-
-        // Because canvas rendering is effectively done upside-down, the winding order is the opposite
-        // of what you'd expect in that case.
-        ctx.device.front_face(match &ctx.graphics.canvas {
-            None => self.winding,
-            Some(_) => self.winding.flipped(),
-        });
-
-        let (start, count) = match (self.draw_range, &self.index_buffer) {
-            (Some(d), _) => (d.start, d.count),
-            (_, Some(i)) => (0, i.handle.count()),
-            (_, None) => (0, self.vertex_buffer.handle.count()),
-        };
-
-        ctx.device.draw_instanced(
-            &self.vertex_buffer.handle,
-            self.index_buffer.as_ref().map(|i| &*i.handle),
-            &texture.data.handle,
-            &shader.data.handle,
-            start,
-            count,
-            instances,
-        );
+    /// Returns a [`DrawCall`] that is equivalent to drawing this mesh.
+    ///
+    /// This can be used to draw the mesh's data via
+    /// [`graphics::draw_buffers`](crate::graphics::draw_buffers) with some of the
+    /// settings overridden (e.g. a different shader), without having to modify the mesh.
+    pub fn to_draw_call(&self) -> DrawCall<'_> {
+        DrawCall {
+            vertex_buffer: &self.vertex_buffer,
+            index_buffer: self.index_buffer.as_ref(),
+            instance_buffer: self.instance_buffer.as_ref(),
+            texture: self.texture.as_ref(),
+            shader: None,
+            range: self.draw_range.map(|d| (d.start, d.count)),
+            winding: self.winding,
+            backface_culling: self.backface_culling,
+            instances: 1,
+        }
     }
 
     /// Gets a reference to the vertex buffer contained within this mesh.
@@ -431,6 +731,25 @@ impl Mesh {
         self.index_buffer = None;
     }
 
+    /// Gets a reference to the instance buffer contained within this mesh.
+    ///
+    /// Returns [`None`] if this mesh does not currently have an instance buffer attatched.
+    pub fn instance_buffer(&self) -> Option<&InstanceBuffer> {
+        self.instance_buffer.as_ref()
+    }
+
+    /// Sets the instance buffer that will be used when drawing the mesh.
+    ///
+    /// See [`draw_instanced`](Self::draw_instanced) for more details.
+    pub fn set_instance_buffer(&mut self, instance_buffer: InstanceBuffer) {
+        self.instance_buffer = Some(instance_buffer);
+    }
+
+    /// Resets the mesh to no longer use per-instance data.
+    pub fn reset_instance_buffer(&mut self) {
+        self.instance_buffer = None;
+    }
+
     /// Gets a reference to the texture contained within this mesh.
     ///
     /// Returns [`None`] if this mesh does not currently have an texture attatched.
@@ -635,24 +954,367 @@ fn to_box2d(rectangle: Rectangle) -> Box2D {
     )
 }
 
-struct TetraVertexConstructor(Color);
+struct TetraVertexConstructor(Color, UvMode);
 
 impl FillVertexConstructor<Vertex> for TetraVertexConstructor {
     fn new_vertex(&mut self, vertex: FillVertex) -> Vertex {
         let position = vertex.position();
+        let position = Vec2::new(position.x, position.y);
 
-        Vertex::new(Vec2::new(position.x, position.y), Vec2::zero(), self.0)
+        Vertex::new(position, self.1.map(position), self.0)
     }
 }
 
 impl StrokeVertexConstructor<Vertex> for TetraVertexConstructor {
     fn new_vertex(&mut self, vertex: StrokeVertex) -> Vertex {
         let position = vertex.position();
+        let position = Vec2::new(position.x, position.y);
+
+        Vertex::new(position, self.1.map(position), self.0)
+    }
+}
+
+/// A list of shapes that can be used for hit-testing.
+///
+/// A `ShapeList` is recorded automatically by [`GeometryBuilder`] as shapes are added to it,
+/// and can be retrieved via [`GeometryBuilder::shapes`]. It stores the original
+/// dimensions of each shape, rather than the generated geometry, so the tests are exact
+/// (even for curved shapes), and it can be kept around after the builder has been cleared or
+/// turned into a mesh.
+///
+/// Outlined shapes only contain the points that are covered by their stroke.
+#[derive(Debug, Clone, Default)]
+pub struct ShapeList {
+    shapes: Vec<RecordedShape>,
+}
+
+#[derive(Debug, Clone)]
+struct RecordedShape {
+    outline: Outline,
+    style: ShapeStyle,
+}
+
+#[derive(Debug, Clone)]
+enum Outline {
+    Rectangle(Rectangle),
+    RoundedRectangle(Rectangle, BorderRadii),
+    Circle(Vec2<f32>, f32),
+    Ellipse(Vec2<f32>, Vec2<f32>),
+    Polygon(Vec<Vec2<f32>>),
+    Arc(Vec2<f32>, f32, f32, f32),
+    RingSegment(Vec2<f32>, f32, f32, f32, f32),
+    Polyline(Vec<Vec2<f32>>),
+}
+
+impl ShapeList {
+    /// Creates a new, empty shape list.
+    pub fn new() -> ShapeList {
+        ShapeList { shapes: Vec::new() }
+    }
+
+    /// Returns `true` if the point is inside any of the shapes in the list.
+    pub fn contains_point(&self, point: Vec2<f32>) -> bool {
+        self.shapes.iter().any(|shape| shape.contains_point(point))
+    }
+
+    /// Returns `true` if the point is inside any of the shapes in the list, when the
+    /// shapes are drawn with the given params.
+    ///
+    /// This allows you to hit-test a mesh that has been moved, rotated or scaled, by
+    /// passing in the same params that you use to draw it.
+    ///
+    /// If the params' skew flattens the shapes onto a line (i.e. `skew.x * skew.y == 1.0`),
+    /// no point will be considered to be inside them.
+    pub fn contains_point_transformed<P>(&self, point: Vec2<f32>, params: P) -> bool
+    where
+        P: Into<DrawParams>,
+    {
+        untransform_point(point, &params.into()).is_some_and(|point| self.contains_point(point))
+    }
+
+    /// Returns the index of the shape that contains the point.
+    ///
+    /// If multiple shapes contain the point, the one that was added last (and
+    /// would therefore be drawn on top) is returned.
+    pub fn find(&self, point: Vec2<f32>) -> Option<usize> {
+        self.shapes
+            .iter()
+            .rposition(|shape| shape.contains_point(point))
+    }
+
+    /// Returns the index of the shape that contains the point, when the shapes are drawn
+    /// with the given params.
+    ///
+    /// If multiple shapes contain the point, the one that was added last (and
+    /// would therefore be drawn on top) is returned. As with
+    /// [`contains_point_transformed`](ShapeList::contains_point_transformed), shapes
+    /// that have been flattened by the params' skew never contain a point.
+    pub fn find_transformed<P>(&self, point: Vec2<f32>, params: P) -> Option<usize>
+    where
+        P: Into<DrawParams>,
+    {
+        untransform_point(point, &params.into()).and_then(|point| self.find(point))
+    }
+
+    /// Returns the number of shapes in the list.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Returns `true` if the list does not contain any shapes.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// Removes all of the shapes from the list.
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    fn push(&mut self, outline: Outline, style: ShapeStyle) {
+        self.shapes.push(RecordedShape { outline, style });
+    }
+}
+
+impl RecordedShape {
+    fn contains_point(&self, point: Vec2<f32>) -> bool {
+        let distance = self.outline.signed_distance(point);
+
+        match self.style {
+            ShapeStyle::Fill => distance <= 0.0,
+            ShapeStyle::Stroke(width) => distance.abs() <= width / 2.0,
+        }
+    }
+}
+
+impl Outline {
+    /// Returns the distance from the point to the edge of the shape. This is negative if
+    /// the point is inside the shape.
+    fn signed_distance(&self, point: Vec2<f32>) -> f32 {
+        match self {
+            Outline::Rectangle(rectangle) => {
+                rounded_rectangle_distance(*rectangle, BorderRadii::new(0.0), point)
+            }
+
+            Outline::RoundedRectangle(rectangle, radii) => {
+                rounded_rectangle_distance(*rectangle, *radii, point)
+            }
+
+            Outline::Circle(center, radius) => point.distance(*center) - radius,
+
+            Outline::Ellipse(center, radii) => {
+                // This is an approximation, but it's exact on the edge of the ellipse
+                // (which is what matters for fills), and close enough for strokes.
+                let p = point - *center;
+                let k0 = (p / *radii).magnitude();
+                let k1 = (p / (*radii * *radii)).magnitude();
+
+                if k1 == 0.0 {
+                    -f32::min(radii.x, radii.y)
+                } else {
+                    k0 * (k0 - 1.0) / k1
+                }
+            }
+
+            Outline::Polygon(points) => {
+                let closing = points.last().zip(points.first());
+
+                let distance = points
+                    .windows(2)
+                    .map(|w| (w[0], w[1]))
+                    .chain(closing.map(|(a, b)| (*a, *b)))
+                    .map(|(a, b)| segment_distance(a, b, point))
+                    .fold(f32::INFINITY, f32::min);
+
+                if polygon_contains(points, point) {
+                    -distance
+                } else {
+                    distance
+                }
+            }
+
+            Outline::Polyline(points) => points
+                .windows(2)
+                .map(|w| segment_distance(w[0], w[1], point))
+                .fold(f32::INFINITY, f32::min),
+
+            Outline::Arc(center, radius, start_angle, sweep_angle) => {
+                arc_distance(*center, *radius, *start_angle, *sweep_angle, point)
+            }
+
+            Outline::RingSegment(center, inner_radius, outer_radius, start_angle, sweep_angle) => {
+                let (center, start_angle, sweep_angle) = (*center, *start_angle, *sweep_angle);
+
+                let mut distance = f32::min(
+                    arc_distance(center, *inner_radius, start_angle, sweep_angle, point),
+                    arc_distance(center, *outer_radius, start_angle, sweep_angle, point),
+                );
+
+                if sweep_angle.abs() < TAU {
+                    let end_angle = start_angle + sweep_angle;
+
+                    for angle in [start_angle, end_angle] {
+                        let direction = Vec2::new(angle.cos(), angle.sin());
+
+                        distance = f32::min(
+                            distance,
+                            segment_distance(
+                                center + direction * *inner_radius,
+                                center + direction * *outer_radius,
+                                point,
+                            ),
+                        );
+                    }
+                }
+
+                let offset = point - center;
+                let length = offset.magnitude();
+
+                if length >= *inner_radius
+                    && length <= *outer_radius
+                    && sweep_contains(offset, start_angle, sweep_angle)
+                {
+                    -distance
+                } else {
+                    distance
+                }
+            }
+        }
+    }
+}
+
+/// Applies the inverse of the params' transformation to a point.
+///
+/// Returns `None` if the skew is degenerate, as the transformation can't be inverted.
+fn untransform_point(point: Vec2<f32>, params: &DrawParams) -> Option<Vec2<f32>> {
+    let (sin, cos) = (-params.rotation).sin_cos();
+    let p = point - params.position;
+    let mut rotated = Vec2::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos);
+
+    if let Some(skew) = params.skew {
+        let det = 1.0 - skew.x * skew.y;
+
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        rotated = Vec2::new(
+            (rotated.x - skew.x * rotated.y) / det,
+            (rotated.y - skew.y * rotated.x) / det,
+        );
+    }
+
+    Some(rotated / params.scale + params.origin)
+}
+
+fn rounded_rectangle_distance(rectangle: Rectangle, radii: BorderRadii, point: Vec2<f32>) -> f32 {
+    let half_size = Vec2::new(rectangle.width, rectangle.height) / 2.0;
+    let p = point - rectangle.center();
+
+    let radius = match (p.x < 0.0, p.y < 0.0) {
+        (true, true) => radii.top_left,
+        (false, true) => radii.top_right,
+        (true, false) => radii.bottom_left,
+        (false, false) => radii.bottom_right,
+    };
+
+    let radius = radius.clamp(0.0, f32::min(half_size.x, half_size.y));
+
+    let q = p.map(f32::abs) - half_size + Vec2::broadcast(radius);
+
+    let outside = Vec2::new(f32::max(q.x, 0.0), f32::max(q.y, 0.0));
+
+    outside.magnitude() + f32::min(f32::max(q.x, q.y), 0.0) - radius
+}
+
+fn segment_distance(a: Vec2<f32>, b: Vec2<f32>, point: Vec2<f32>) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.magnitude_squared();
 
-        Vertex::new(Vec2::new(position.x, position.y), Vec2::zero(), self.0)
+    let t = if length_squared == 0.0 {
+        0.0
+    } else {
+        ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+    };
+
+    point.distance(a + ab * t)
+}
+
+/// Tests whether the direction of an offset from a center point falls within an arc's sweep.
+fn sweep_contains(offset: Vec2<f32>, start_angle: f32, sweep_angle: f32) -> bool {
+    if sweep_angle.abs() >= TAU {
+        return true;
+    }
+
+    let (start_angle, sweep_angle) = if sweep_angle < 0.0 {
+        (start_angle + sweep_angle, -sweep_angle)
+    } else {
+        (start_angle, sweep_angle)
+    };
+
+    (offset.y.atan2(offset.x) - start_angle).rem_euclid(TAU) <= sweep_angle
+}
+
+/// Returns the (unsigned) distance from a point to the curve of a circular arc.
+fn arc_distance(
+    center: Vec2<f32>,
+    radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+    point: Vec2<f32>,
+) -> f32 {
+    let offset = point - center;
+
+    if sweep_contains(offset, start_angle, sweep_angle) {
+        (offset.magnitude() - radius).abs()
+    } else {
+        let end_angle = start_angle + sweep_angle;
+        let start = center + Vec2::new(start_angle.cos(), start_angle.sin()) * radius;
+        let end = center + Vec2::new(end_angle.cos(), end_angle.sin()) * radius;
+
+        f32::min(point.distance(start), point.distance(end))
     }
 }
 
+/// Adds the curve of a circular arc to a path, via quadratic bezier segments.
+fn add_arc(
+    path: &mut PathBuilder,
+    center: Vec2<f32>,
+    radius: f32,
+    start_angle: f32,
+    sweep_angle: f32,
+) {
+    let arc = Arc {
+        center: Point::new(center.x, center.y),
+        radii: Vector::new(radius, radius),
+        start_angle: Angle::radians(start_angle),
+        sweep_angle: Angle::radians(sweep_angle),
+        x_rotation: Angle::radians(0.0),
+    };
+
+    arc.for_each_quadratic_bezier(&mut |segment| {
+        path.quadratic_bezier_to(segment.ctrl, segment.to);
+    });
+}
+
+/// Tests whether a point is inside a polygon, using the even-odd rule (which matches
+/// how polygons are filled).
+fn polygon_contains(points: &[Vec2<f32>], point: Vec2<f32>) -> bool {
+    let mut inside = false;
+
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
 /// A builder for creating primitive shape geometry, and associated buffers/meshes.
 ///
 /// # Performance
@@ -670,10 +1332,17 @@ impl StrokeVertexConstructor<Vertex> for TetraVertexConstructor {
 /// The [`shapes`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/shapes.rs) example demonstrates
 /// how to draw primitive shapes, both through the simplified API on [`Mesh`], and the more powerful
 /// `GeometryBuilder` API.  
+///
+/// # Hit-testing
+///
+/// As shapes are added, the builder also records them into a [`ShapeList`], which can be
+/// used to check whether a point (e.g. the mouse position) is inside the geometry.
 #[derive(Debug, Clone)]
 pub struct GeometryBuilder {
     data: VertexBuffers<Vertex, u32>,
     color: Color,
+    uv_mode: UvMode,
+    shapes: ShapeList,
 }
 
 impl GeometryBuilder {
@@ -682,6 +1351,8 @@ impl GeometryBuilder {
         GeometryBuilder {
             data: VertexBuffers::new(),
             color: Color::WHITE,
+            uv_mode: UvMode::None,
+            shapes: ShapeList::new(),
         }
     }
 
@@ -696,7 +1367,10 @@ impl GeometryBuilder {
         style: ShapeStyle,
         rectangle: Rectangle,
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
         match style {
             ShapeStyle::Fill => {
@@ -716,6 +1390,8 @@ impl GeometryBuilder {
             }
         }
 
+        self.shapes.push(Outline::Rectangle(rectangle), style);
+
         Ok(self)
     }
 
@@ -731,7 +1407,10 @@ impl GeometryBuilder {
         rectangle: Rectangle,
         radii: BorderRadii,
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
         match style {
             ShapeStyle::Fill => {
@@ -751,6 +1430,9 @@ impl GeometryBuilder {
             }
         }
 
+        self.shapes
+            .push(Outline::RoundedRectangle(rectangle, radii), style);
+
         Ok(self)
     }
 
@@ -766,7 +1448,10 @@ impl GeometryBuilder {
         center: Vec2<f32>,
         radius: f32,
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
         match style {
             ShapeStyle::Fill => {
@@ -798,6 +1483,8 @@ impl GeometryBuilder {
             }
         }
 
+        self.shapes.push(Outline::Circle(center, radius), style);
+
         Ok(self)
     }
 
@@ -813,7 +1500,10 @@ impl GeometryBuilder {
         center: Vec2<f32>,
         radii: Vec2<f32>,
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
         match style {
             ShapeStyle::Fill => {
@@ -849,6 +1539,8 @@ impl GeometryBuilder {
             }
         }
 
+        self.shapes.push(Outline::Ellipse(center, radii), style);
+
         Ok(self)
     }
 
@@ -863,15 +1555,18 @@ impl GeometryBuilder {
         style: ShapeStyle,
         points: &[Vec2<f32>],
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
-        let points: Vec<Point> = points
+        let lyon_points: Vec<Point> = points
             .iter()
             .map(|point| Point::new(point.x, point.y))
             .collect();
 
         let polygon = Polygon {
-            points: &points,
+            points: &lyon_points,
             closed: true,
         };
 
@@ -895,6 +1590,8 @@ impl GeometryBuilder {
             }
         }
 
+        self.shapes.push(Outline::Polygon(points.to_vec()), style);
+
         Ok(self)
     }
 
@@ -909,15 +1606,18 @@ impl GeometryBuilder {
         stroke_width: f32,
         points: &[Vec2<f32>],
     ) -> Result<&mut GeometryBuilder> {
-        let mut builder = BuffersBuilder::new(&mut self.data, TetraVertexConstructor(self.color));
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
 
-        let points: Vec<Point> = points
+        let lyon_points: Vec<Point> = points
             .iter()
             .map(|point| Point::new(point.x, point.y))
             .collect();
 
         let polygon = Polygon {
-            points: &points,
+            points: &lyon_points,
             closed: false,
         };
 
@@ -928,9 +1628,218 @@ impl GeometryBuilder {
             .tessellate_polygon(polygon, &options, &mut builder)
             .map_err(TetraError::TessellationError)?;
 
+        self.shapes.push(
+            Outline::Polyline(points.to_vec()),
+            ShapeStyle::Stroke(stroke_width),
+        );
+
+        Ok(self)
+    }
+
+    /// Adds a circular arc.
+    ///
+    /// Filled arcs are drawn as a pie slice (i.e. with straight edges back to the center),
+    /// while stroked arcs only draw the curve itself.
+    ///
+    /// Angles are in radians, with `0.0` pointing to the right, and positive sweep angles winding
+    /// clockwise (matching [`DrawParams::rotation`]). Negative sweep angles wind in the opposite
+    /// direction. Sweep angles of a full turn or more will draw a full circle.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::TessellationError`](crate::TetraError::TessellationError) will be returned if the shape
+    ///   could not be turned into vertex data.
+    pub fn arc(
+        &mut self,
+        style: ShapeStyle,
+        center: Vec2<f32>,
+        radius: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+    ) -> Result<&mut GeometryBuilder> {
+        if sweep_angle.abs() >= TAU {
+            return self.circle(style, center, radius);
+        }
+
+        let start = center + Vec2::new(start_angle.cos(), start_angle.sin()) * radius;
+
+        let mut path = Path::builder();
+
+        match style {
+            ShapeStyle::Fill => {
+                path.begin(Point::new(center.x, center.y));
+                path.line_to(Point::new(start.x, start.y));
+                add_arc(&mut path, center, radius, start_angle, sweep_angle);
+                path.end(true);
+            }
+
+            ShapeStyle::Stroke(_) => {
+                path.begin(Point::new(start.x, start.y));
+                add_arc(&mut path, center, radius, start_angle, sweep_angle);
+                path.end(false);
+            }
+        }
+
+        self.tessellate_path(style, &path.build())?;
+
+        let outline = match style {
+            ShapeStyle::Fill => Outline::RingSegment(center, 0.0, radius, start_angle, sweep_angle),
+            ShapeStyle::Stroke(_) => Outline::Arc(center, radius, start_angle, sweep_angle),
+        };
+
+        self.shapes.push(outline, style);
+
+        Ok(self)
+    }
+
+    /// Adds a segment of a ring (i.e. a circular arc with a hole in the middle).
+    ///
+    /// This can be useful for things like circular progress bars and radial menus.
+    ///
+    /// Angles work the same way as in [`arc`](Self::arc). Sweep angles of a full turn or
+    /// more will draw a full ring.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::TessellationError`](crate::TetraError::TessellationError) will be returned if the shape
+    ///   could not be turned into vertex data.
+    pub fn ring_segment(
+        &mut self,
+        style: ShapeStyle,
+        center: Vec2<f32>,
+        inner_radius: f32,
+        outer_radius: f32,
+        start_angle: f32,
+        sweep_angle: f32,
+    ) -> Result<&mut GeometryBuilder> {
+        let mut path = Path::builder();
+
+        if sweep_angle.abs() >= TAU {
+            let lyon_center = Point::new(center.x, center.y);
+
+            path.add_circle(lyon_center, outer_radius, Winding::Positive);
+            path.add_circle(lyon_center, inner_radius, Winding::Negative);
+        } else {
+            let end_angle = start_angle + sweep_angle;
+            let start = center + Vec2::new(start_angle.cos(), start_angle.sin()) * outer_radius;
+            let end = center + Vec2::new(end_angle.cos(), end_angle.sin()) * inner_radius;
+
+            path.begin(Point::new(start.x, start.y));
+            add_arc(&mut path, center, outer_radius, start_angle, sweep_angle);
+            path.line_to(Point::new(end.x, end.y));
+            add_arc(&mut path, center, inner_radius, end_angle, -sweep_angle);
+            path.end(true);
+        }
+
+        self.tessellate_path(style, &path.build())?;
+
+        self.shapes.push(
+            Outline::RingSegment(center, inner_radius, outer_radius, start_angle, sweep_angle),
+            style,
+        );
+
+        Ok(self)
+    }
+
+    /// Adds a bezier curve.
+    ///
+    /// Three points will create a quadratic curve (start, control point, end), and four
+    /// points will create a cubic curve (start, two control points, end).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::TessellationError`](crate::TetraError::TessellationError) will be returned if the shape
+    ///   could not be turned into vertex data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of points is not three or four.
+    pub fn bezier(
+        &mut self,
+        stroke_width: f32,
+        points: &[Vec2<f32>],
+    ) -> Result<&mut GeometryBuilder> {
+        assert!(
+            points.len() == 3 || points.len() == 4,
+            "bezier curves must have three or four points, but {} were provided",
+            points.len()
+        );
+
+        let lyon_points: Vec<Point> = points
+            .iter()
+            .map(|point| Point::new(point.x, point.y))
+            .collect();
+
+        let mut path = Path::builder();
+        path.begin(lyon_points[0]);
+
+        // The curve is also flattened for hit-testing, at the same tolerance that
+        // the tessellator uses.
+        let tolerance = StrokeOptions::DEFAULT_TOLERANCE;
+        let mut flattened = vec![points[0]];
+
+        match lyon_points[..] {
+            [from, ctrl, to] => {
+                path.quadratic_bezier_to(ctrl, to);
+
+                let segment = QuadraticBezierSegment { from, ctrl, to };
+                flattened.extend(segment.flattened(tolerance).map(|p| Vec2::new(p.x, p.y)));
+            }
+
+            [from, ctrl1, ctrl2, to] => {
+                path.cubic_bezier_to(ctrl1, ctrl2, to);
+
+                let segment = CubicBezierSegment {
+                    from,
+                    ctrl1,
+                    ctrl2,
+                    to,
+                };
+                flattened.extend(segment.flattened(tolerance).map(|p| Vec2::new(p.x, p.y)));
+            }
+
+            _ => unreachable!(),
+        }
+
+        path.end(false);
+
+        let style = ShapeStyle::Stroke(stroke_width);
+
+        self.tessellate_path(style, &path.build())?;
+        self.shapes.push(Outline::Polyline(flattened), style);
+
         Ok(self)
     }
 
+    fn tessellate_path(&mut self, style: ShapeStyle, path: &Path) -> Result {
+        let mut builder = BuffersBuilder::new(
+            &mut self.data,
+            TetraVertexConstructor(self.color, self.uv_mode),
+        );
+
+        match style {
+            ShapeStyle::Fill => {
+                let options = FillOptions::default();
+                let mut tessellator = FillTessellator::new();
+
+                tessellator
+                    .tessellate_path(path, &options, &mut builder)
+                    .map_err(TetraError::TessellationError)?;
+            }
+
+            ShapeStyle::Stroke(width) => {
+                let options = StrokeOptions::default().with_line_width(width);
+                let mut tessellator = StrokeTessellator::new();
+
+                tessellator
+                    .tessellate_path(path, &options, &mut builder)
+                    .map_err(TetraError::TessellationError)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Sets the color that will be used for subsequent shapes.
     ///
     /// You can also use [`DrawParams::color`](super::DrawParams) to tint an entire mesh -
@@ -941,14 +1850,42 @@ impl GeometryBuilder {
         self
     }
 
+    /// Sets how texture co-ordinates will be generated for subsequent shapes.
+    ///
+    /// By default, all vertices have UVs of `(0.0, 0.0)`, so setting a texture on the
+    /// resulting mesh will only sample a single texel. Use [`UvMode::Rect`] if you want to
+    /// texture the generated geometry.
+    pub fn set_uv_mode(&mut self, uv_mode: UvMode) -> &mut GeometryBuilder {
+        self.uv_mode = uv_mode;
+        self
+    }
+
     /// Clears the geometry builder's data.
     pub fn clear(&mut self) -> &mut GeometryBuilder {
         self.data.vertices.clear();
         self.data.indices.clear();
+        self.shapes.clear();
 
         self
     }
 
+    /// Returns the shapes that have been added to the builder, for use in hit-testing.
+    ///
+    /// The list can be cloned if you need to keep it around after the builder is cleared
+    /// or dropped.
+    pub fn shapes(&self) -> &ShapeList {
+        &self.shapes
+    }
+
+    /// Returns `true` if the point is inside any of the shapes that have been added
+    /// to the builder.
+    ///
+    /// This is equivalent to calling [`ShapeList::contains_point`] on the
+    /// builder's [`shapes`](Self::shapes).
+    pub fn contains_point(&self, point: Vec2<f32>) -> bool {
+        self.shapes.contains_point(point)
+    }
+
     /// Returns a view of the generated vertex data.
     pub fn vertices(&self) -> &[Vertex] {
         &self.data.vertices
@@ -999,3 +1936,268 @@ impl Default for GeometryBuilder {
         GeometryBuilder::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_instances, resolve_range, BorderRadii, Instance, Outline, ShapeList};
+    use super::{GeometryBuilder, ShapeStyle, UvMode, TAU};
+    use crate::graphics::{Color, DrawParams, Rectangle};
+    use crate::math::{Vec2, Vec4};
+
+    #[test]
+    fn draw_range_defaults_to_whole_buffer() {
+        assert_eq!((0, 12), resolve_range(None, None, 12));
+        assert_eq!((0, 18), resolve_range(None, Some(18), 12));
+    }
+
+    #[test]
+    fn explicit_draw_range_is_used() {
+        assert_eq!((3, 6), resolve_range(Some((3, 6)), None, 12));
+        assert_eq!((3, 6), resolve_range(Some((3, 6)), Some(18), 12));
+    }
+
+    #[test]
+    fn instances_are_limited_by_buffer() {
+        assert_eq!(10, resolve_instances(10, None));
+        assert_eq!(4, resolve_instances(10, Some(4)));
+        assert_eq!(3, resolve_instances(3, Some(4)));
+    }
+
+    #[test]
+    fn instance_from_params_matches_matrix() {
+        let params = DrawParams::new()
+            .position(Vec2::new(100.0, 50.0))
+            .origin(Vec2::new(8.0, 8.0))
+            .scale(Vec2::new(2.0, 3.0))
+            .rotation(0.5)
+            .color(Color::RED);
+
+        let instance = Instance::from_params(params.clone());
+        let matrix = params.to_matrix();
+
+        for point in [Vec2::zero(), Vec2::new(16.0, 0.0), Vec2::new(5.0, 12.0)] {
+            let expected = (matrix * Vec4::new(point.x, point.y, 0.0, 1.0)).xy();
+            let actual = instance.transform_point(point);
+
+            assert!((expected - actual).magnitude() < 0.001);
+        }
+
+        assert_eq!(Color::RED, instance.color);
+    }
+
+    #[test]
+    fn shape_list_hit_testing() {
+        let mut shapes = ShapeList::new();
+
+        shapes.push(
+            Outline::Rectangle(Rectangle::new(0.0, 0.0, 100.0, 50.0)),
+            ShapeStyle::Fill,
+        );
+        shapes.push(
+            Outline::Circle(Vec2::new(200.0, 200.0), 10.0),
+            ShapeStyle::Stroke(2.0),
+        );
+        shapes.push(
+            Outline::Polygon(vec![
+                Vec2::new(300.0, 0.0),
+                Vec2::new(400.0, 0.0),
+                Vec2::new(300.0, 100.0),
+            ]),
+            ShapeStyle::Fill,
+        );
+
+        assert_eq!(shapes.find(Vec2::new(50.0, 25.0)), Some(0));
+        assert_eq!(shapes.find(Vec2::new(150.0, 25.0)), None);
+
+        assert_eq!(shapes.find(Vec2::new(210.5, 200.0)), Some(1));
+        assert_eq!(shapes.find(Vec2::new(200.0, 200.0)), None);
+
+        assert_eq!(shapes.find(Vec2::new(310.0, 10.0)), Some(2));
+        assert_eq!(shapes.find(Vec2::new(390.0, 90.0)), None);
+    }
+
+    #[test]
+    fn rounded_corners_are_excluded() {
+        let mut shapes = ShapeList::new();
+
+        shapes.push(
+            Outline::RoundedRectangle(
+                Rectangle::new(0.0, 0.0, 100.0, 100.0),
+                BorderRadii::new(20.0),
+            ),
+            ShapeStyle::Fill,
+        );
+
+        assert!(shapes.contains_point(Vec2::new(50.0, 1.0)));
+        assert!(shapes.contains_point(Vec2::new(10.0, 10.0)));
+        assert!(!shapes.contains_point(Vec2::new(2.0, 2.0)));
+        assert!(!shapes.contains_point(Vec2::new(98.0, 98.0)));
+    }
+
+    #[test]
+    fn ellipse_hit_testing() {
+        let mut shapes = ShapeList::new();
+
+        shapes.push(
+            Outline::Ellipse(Vec2::new(0.0, 0.0), Vec2::new(20.0, 10.0)),
+            ShapeStyle::Fill,
+        );
+
+        assert!(shapes.contains_point(Vec2::zero()));
+        assert!(shapes.contains_point(Vec2::new(19.0, 0.0)));
+        assert!(!shapes.contains_point(Vec2::new(0.0, 11.0)));
+        assert!(!shapes.contains_point(Vec2::new(15.0, 8.0)));
+    }
+
+    #[test]
+    fn rect_uv_mode_maps_to_unit_square() {
+        let rectangle = Rectangle::new(10.0, 20.0, 30.0, 40.0);
+        let mut builder = GeometryBuilder::new();
+
+        builder
+            .set_uv_mode(UvMode::Rect(rectangle))
+            .rectangle(ShapeStyle::Fill, rectangle)
+            .unwrap();
+
+        for vertex in builder.vertices() {
+            let expected = Vec2::new(
+                if vertex.position.x == 10.0 { 0.0 } else { 1.0 },
+                if vertex.position.y == 20.0 { 0.0 } else { 1.0 },
+            );
+
+            assert_eq!(expected, vertex.uv);
+        }
+
+        builder.set_uv_mode(UvMode::None).clear();
+        builder.circle(ShapeStyle::Fill, Vec2::zero(), 5.0).unwrap();
+
+        assert!(builder.vertices().iter().all(|v| v.uv == Vec2::zero()));
+    }
+
+    #[test]
+    fn arc_hit_testing() {
+        let mut builder = GeometryBuilder::new();
+
+        // A quarter circle, from the right to the bottom.
+        builder
+            .arc(ShapeStyle::Fill, Vec2::zero(), 10.0, 0.0, TAU / 4.0)
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(5.0, 5.0)));
+        assert!(!builder.contains_point(Vec2::new(5.0, -5.0)));
+        assert!(!builder.contains_point(Vec2::new(8.0, 8.0)));
+
+        // Negative sweeps wind the other way.
+        builder.clear();
+        builder
+            .arc(ShapeStyle::Fill, Vec2::zero(), 10.0, 0.0, -TAU / 4.0)
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(5.0, -5.0)));
+        assert!(!builder.contains_point(Vec2::new(5.0, 5.0)));
+
+        // Stroked arcs only cover the curve.
+        builder.clear();
+        builder
+            .arc(ShapeStyle::Stroke(2.0), Vec2::zero(), 10.0, 0.0, TAU / 2.0)
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(0.0, 10.5)));
+        assert!(!builder.contains_point(Vec2::new(0.0, 5.0)));
+        assert!(!builder.contains_point(Vec2::new(0.0, -10.0)));
+    }
+
+    #[test]
+    fn ring_segment_hit_testing() {
+        let mut builder = GeometryBuilder::new();
+
+        builder
+            .ring_segment(ShapeStyle::Fill, Vec2::zero(), 5.0, 10.0, 0.0, TAU / 2.0)
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(0.0, 7.0)));
+        assert!(!builder.contains_point(Vec2::new(0.0, 2.0)));
+        assert!(!builder.contains_point(Vec2::new(0.0, -7.0)));
+
+        // Sweeps of a full turn or more close into a full ring.
+        builder.clear();
+        builder
+            .ring_segment(ShapeStyle::Fill, Vec2::zero(), 5.0, 10.0, 1.0, TAU * 1.5)
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(0.0, 7.0)));
+        assert!(builder.contains_point(Vec2::new(0.0, -7.0)));
+        assert!(!builder.contains_point(Vec2::zero()));
+    }
+
+    #[test]
+    fn bezier_hit_testing() {
+        let mut builder = GeometryBuilder::new();
+
+        builder
+            .bezier(
+                2.0,
+                &[
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(50.0, 100.0),
+                    Vec2::new(100.0, 0.0),
+                ],
+            )
+            .unwrap();
+
+        // The midpoint of a quadratic curve is halfway between the control point and
+        // the midpoint of the endpoints.
+        assert!(builder.contains_point(Vec2::new(50.0, 50.0)));
+        assert!(!builder.contains_point(Vec2::new(50.0, 0.0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bezier_requires_three_or_four_points() {
+        let _ = GeometryBuilder::new().bezier(1.0, &[Vec2::zero(), Vec2::one()]);
+    }
+
+    #[test]
+    fn transformed_hit_testing() {
+        let mut builder = GeometryBuilder::new();
+
+        builder
+            .rectangle(ShapeStyle::Fill, Rectangle::new(0.0, 0.0, 10.0, 10.0))
+            .unwrap();
+
+        assert!(builder.contains_point(Vec2::new(5.0, 5.0)));
+
+        let params = DrawParams::new()
+            .position(Vec2::new(100.0, 100.0))
+            .scale(Vec2::new(2.0, 2.0));
+
+        let shapes = builder.shapes();
+
+        assert!(shapes.contains_point_transformed(Vec2::new(115.0, 115.0), params.clone()));
+        assert!(!shapes.contains_point_transformed(Vec2::new(5.0, 5.0), params.clone()));
+
+        // The inverse transform should match the one used for drawing.
+        let params = params.rotation(1.0).origin(Vec2::new(5.0, 5.0));
+        let corner = (params.to_matrix() * Vec4::new(9.0, 9.0, 0.0, 1.0)).xy();
+
+        assert!(shapes.contains_point_transformed(corner, params.clone()));
+        assert!(!shapes.contains_point_transformed(Vec2::new(100.0, 120.0), params.clone()));
+
+        let params = params.skew(Vec2::new(0.5, -0.25));
+        let corner = (params.to_matrix() * Vec4::new(9.0, 1.0, 0.0, 1.0)).xy();
+        let outside = (params.to_matrix() * Vec4::new(11.0, 1.0, 0.0, 1.0)).xy();
+
+        assert!(shapes.contains_point_transformed(corner, params.clone()));
+        assert!(!shapes.contains_point_transformed(outside, params.clone()));
+
+        // A skew that flattens the shape can't be inverted.
+        let params = params.skew(Vec2::new(2.0, 0.5));
+
+        assert!(!shapes.contains_point_transformed(params.position, params.clone()));
+        assert_eq!(shapes.find_transformed(params.position, params), None);
+
+        builder.clear();
+
+        assert!(builder.shapes().is_empty());
+    }
+}
@@ -0,0 +1,463 @@
+//! Functions and types relating to particle effects.
+
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use crate::graphics::{Color, DrawParams, Texture};
+use crate::math::Vec2;
+use crate::time;
+use crate::Context;
+
+/// The seed that is used if one is not specified via [`ParticleEmitter::set_seed`].
+const DEFAULT_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// An emitter that spawns, simulates and draws simple particles.
+///
+/// Each particle is drawn using the emitter's texture, centered on the particle's position.
+/// Over its lifetime, the particle's color and scale will be interpolated between the
+/// configured start and end values.
+///
+/// Calling [`update`](Self::update) or [`advance_by`](Self::advance_by) within
+/// [`State::update`](crate::State::update) will move the existing particles, remove
+/// those that have expired, and spawn new ones based on the spawn rate. Particles can
+/// also be spawned immediately via [`emit_burst`](Self::emit_burst).
+///
+/// # Randomness
+///
+/// The lifetime, speed and direction of each particle are randomized within the configured
+/// ranges. An emitter uses its own random number generator, so as long as you set the same
+/// [seed](Self::set_seed) and advance it by the same amounts, it will always produce the
+/// same particles. This can be useful for replays or networked games.
+///
+/// # Performance
+///
+/// Particles are drawn via [`Texture::draw`], so they will be batched together with any other
+/// draw calls that use the same texture. Every particle is re-submitted each frame, which is
+/// fine for a few thousand particles - if you need more than that, you may want to write a
+/// custom implementation using an [`InstanceBuffer`](crate::graphics::mesh::InstanceBuffer).
+///
+/// # Examples
+///
+/// The [`particles`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/particles.rs)
+/// example demonstrates how to use a `ParticleEmitter` for a continuous effect and for bursts.
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    texture: Texture,
+    position: Vec2<f32>,
+    settings: EmitterSettings,
+
+    particles: Vec<Particle>,
+    rng: Rng,
+    spawn_timer: f32,
+    emitting: bool,
+}
+
+impl ParticleEmitter {
+    /// Creates a new particle emitter, which will spawn particles at the given position.
+    ///
+    /// By default, the emitter will spawn 10 white particles per second, which fly upwards
+    /// and fade out over one second.
+    pub fn new(texture: Texture, position: Vec2<f32>) -> ParticleEmitter {
+        ParticleEmitter {
+            texture,
+            position,
+            settings: EmitterSettings::default(),
+
+            particles: Vec::new(),
+            rng: Rng::new(DEFAULT_SEED),
+            spawn_timer: 0.0,
+            emitting: true,
+        }
+    }
+
+    /// Advances the emitter's timer, updating and spawning particles.
+    ///
+    /// This method uses the current [delta time](crate::time::get_delta_time)
+    /// to calculate how much time has passed.
+    pub fn update(&mut self, ctx: &Context) {
+        self.advance_by(time::get_delta_time(ctx));
+    }
+
+    /// Advances the emitter's timer by a specified amount, updating and spawning particles.
+    pub fn advance_by(&mut self, duration: Duration) {
+        let delta = duration.as_secs_f32();
+
+        step_particles(&mut self.particles, self.settings.gravity, delta);
+
+        if self.emitting && self.settings.spawn_rate > 0.0 {
+            self.spawn_timer += delta * self.settings.spawn_rate;
+
+            while self.spawn_timer >= 1.0 {
+                self.spawn_timer -= 1.0;
+                self.spawn();
+            }
+        }
+    }
+
+    /// Spawns the given number of particles immediately.
+    ///
+    /// This works even if the emitter is not currently [emitting](Self::set_emitting).
+    pub fn emit_burst(&mut self, count: usize) {
+        self.particles.reserve(count);
+
+        for _ in 0..count {
+            self.spawn();
+        }
+    }
+
+    /// Draws the particles to the screen (or to a canvas, if one is enabled).
+    pub fn draw(&self, ctx: &mut Context) {
+        let origin = Vec2::new(
+            self.texture.width() as f32 / 2.0,
+            self.texture.height() as f32 / 2.0,
+        );
+
+        for particle in &self.particles {
+            let t = particle.progress();
+
+            self.texture.draw(
+                ctx,
+                DrawParams::new()
+                    .position(particle.position)
+                    .origin(origin)
+                    .scale(Vec2::lerp(
+                        self.settings.start_scale,
+                        self.settings.end_scale,
+                        t,
+                    ))
+                    .color(self.settings.start_color.lerp(self.settings.end_color, t)),
+            );
+        }
+    }
+
+    /// Removes all of the particles that are currently alive.
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.spawn_timer = 0.0;
+    }
+
+    /// Returns the number of particles that are currently alive.
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+
+    /// Returns a reference to the texture used to draw the particles.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Sets the texture used to draw the particles.
+    pub fn set_texture(&mut self, texture: Texture) {
+        self.texture = texture;
+    }
+
+    /// Returns the position that new particles will be spawned at.
+    pub fn position(&self) -> Vec2<f32> {
+        self.position
+    }
+
+    /// Sets the position that new particles will be spawned at.
+    ///
+    /// Particles that have already been spawned will not be moved.
+    pub fn set_position(&mut self, position: Vec2<f32>) {
+        self.position = position;
+    }
+
+    /// Returns whether the emitter is continuously spawning particles.
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+
+    /// Sets whether the emitter should continuously spawn particles.
+    ///
+    /// When this is set to `false`, existing particles will still be updated until they
+    /// expire, and bursts can still be emitted.
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+    }
+
+    /// Sets the seed for the emitter's random number generator.
+    ///
+    /// This resets the generator, so calling this with the same seed will cause the emitter
+    /// to repeat the same sequence of particles.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// Returns the number of particles spawned per second.
+    pub fn spawn_rate(&self) -> f32 {
+        self.settings.spawn_rate
+    }
+
+    /// Sets the number of particles spawned per second.
+    pub fn set_spawn_rate(&mut self, spawn_rate: f32) {
+        self.settings.spawn_rate = spawn_rate;
+    }
+
+    /// Returns the minimum and maximum lifetime of each particle.
+    pub fn lifetime(&self) -> (Duration, Duration) {
+        (self.settings.min_lifetime, self.settings.max_lifetime)
+    }
+
+    /// Sets the minimum and maximum lifetime of each particle.
+    pub fn set_lifetime(&mut self, min: Duration, max: Duration) {
+        self.settings.min_lifetime = min;
+        self.settings.max_lifetime = max;
+    }
+
+    /// Returns the minimum and maximum initial speed of each particle, in pixels per second.
+    pub fn speed(&self) -> (f32, f32) {
+        (self.settings.min_speed, self.settings.max_speed)
+    }
+
+    /// Sets the minimum and maximum initial speed of each particle, in pixels per second.
+    pub fn set_speed(&mut self, min: f32, max: f32) {
+        self.settings.min_speed = min;
+        self.settings.max_speed = max;
+    }
+
+    /// Returns the direction that particles are fired in, in radians.
+    pub fn direction(&self) -> f32 {
+        self.settings.direction
+    }
+
+    /// Sets the direction that particles are fired in, in radians.
+    ///
+    /// An angle of zero points to the right, and positive angles rotate clockwise.
+    pub fn set_direction(&mut self, direction: f32) {
+        self.settings.direction = direction;
+    }
+
+    /// Returns the total angle (in radians) that particle directions are spread across.
+    pub fn spread(&self) -> f32 {
+        self.settings.spread
+    }
+
+    /// Sets the total angle (in radians) that particle directions are spread across.
+    ///
+    /// The spread is centered on the [direction](Self::set_direction) - for example, a spread
+    /// of `PI / 2.0` will fire particles up to 45 degrees either side of it, and a spread of
+    /// `TAU` will fire them in every direction.
+    pub fn set_spread(&mut self, spread: f32) {
+        self.settings.spread = spread;
+    }
+
+    /// Returns the acceleration applied to every particle, in pixels per second squared.
+    pub fn gravity(&self) -> Vec2<f32> {
+        self.settings.gravity
+    }
+
+    /// Sets the acceleration applied to every particle, in pixels per second squared.
+    pub fn set_gravity(&mut self, gravity: Vec2<f32>) {
+        self.settings.gravity = gravity;
+    }
+
+    /// Returns the colors that particles start and end their lifetime with.
+    pub fn colors(&self) -> (Color, Color) {
+        (self.settings.start_color, self.settings.end_color)
+    }
+
+    /// Sets the colors that particles start and end their lifetime with.
+    pub fn set_colors(&mut self, start: Color, end: Color) {
+        self.settings.start_color = start;
+        self.settings.end_color = end;
+    }
+
+    /// Returns the scales that particles start and end their lifetime with.
+    pub fn scales(&self) -> (Vec2<f32>, Vec2<f32>) {
+        (self.settings.start_scale, self.settings.end_scale)
+    }
+
+    /// Sets the scales that particles start and end their lifetime with.
+    pub fn set_scales(&mut self, start: Vec2<f32>, end: Vec2<f32>) {
+        self.settings.start_scale = start;
+        self.settings.end_scale = end;
+    }
+
+    fn spawn(&mut self) {
+        let particle = self.settings.spawn(&mut self.rng, self.position);
+        self.particles.push(particle);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct EmitterSettings {
+    spawn_rate: f32,
+    min_lifetime: Duration,
+    max_lifetime: Duration,
+    min_speed: f32,
+    max_speed: f32,
+    direction: f32,
+    spread: f32,
+    gravity: Vec2<f32>,
+    start_color: Color,
+    end_color: Color,
+    start_scale: Vec2<f32>,
+    end_scale: Vec2<f32>,
+}
+
+impl EmitterSettings {
+    fn spawn(&self, rng: &mut Rng, position: Vec2<f32>) -> Particle {
+        let lifetime = rng.range(
+            self.min_lifetime.as_secs_f32(),
+            self.max_lifetime.as_secs_f32(),
+        );
+
+        let speed = rng.range(self.min_speed, self.max_speed);
+        let angle = self.direction + rng.range(-self.spread / 2.0, self.spread / 2.0);
+
+        Particle {
+            position,
+            velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+            age: 0.0,
+            lifetime,
+        }
+    }
+}
+
+impl Default for EmitterSettings {
+    fn default() -> EmitterSettings {
+        EmitterSettings {
+            spawn_rate: 10.0,
+            min_lifetime: Duration::from_secs(1),
+            max_lifetime: Duration::from_secs(1),
+            min_speed: 50.0,
+            max_speed: 100.0,
+            direction: -PI / 2.0,
+            spread: PI / 4.0,
+            gravity: Vec2::zero(),
+            start_color: Color::WHITE,
+            end_color: Color::WHITE.with_alpha(0.0),
+            start_scale: Vec2::one(),
+            end_scale: Vec2::one(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Particle {
+    position: Vec2<f32>,
+    velocity: Vec2<f32>,
+    age: f32,
+    lifetime: f32,
+}
+
+impl Particle {
+    fn progress(&self) -> f32 {
+        if self.lifetime > 0.0 {
+            (self.age / self.lifetime).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Moves the particles forward in time, removing any that have expired.
+fn step_particles(particles: &mut Vec<Particle>, gravity: Vec2<f32>, delta: f32) {
+    particles.retain_mut(|particle| {
+        particle.age += delta;
+
+        if particle.age >= particle.lifetime {
+            return false;
+        }
+
+        particle.velocity += gravity * delta;
+        particle.position += particle.velocity * delta;
+
+        true
+    });
+}
+
+/// A small, fast random number generator (SplitMix64), so that particle effects (and other
+/// randomized effects, such as camera shake) are reproducible without pulling in an external
+/// dependency.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a value in the range `0.0..1.0`.
+    fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fit exactly into an f32's mantissa.
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub(crate) fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(particles: &[Particle]) -> Vec<Vec2<f32>> {
+        particles.iter().map(|p| p.position).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_same_particles() {
+        let settings = EmitterSettings {
+            spread: PI * 2.0,
+            ..EmitterSettings::default()
+        };
+
+        let run = |seed| {
+            let mut rng = Rng::new(seed);
+
+            let mut particles: Vec<Particle> = (0..10)
+                .map(|_| settings.spawn(&mut rng, Vec2::zero()))
+                .collect();
+
+            step_particles(&mut particles, Vec2::new(0.0, 100.0), 0.5);
+            positions(&particles)
+        };
+
+        assert_eq!(run(1234), run(1234));
+        assert_ne!(run(1234), run(4321));
+    }
+
+    #[test]
+    fn random_values_stay_in_range() {
+        let mut rng = Rng::new(0);
+
+        for _ in 0..1000 {
+            let value = rng.range(-2.0, 3.0);
+            assert!((-2.0..3.0).contains(&value));
+        }
+    }
+
+    #[test]
+    fn expired_particles_are_removed() {
+        let particle = |lifetime| Particle {
+            position: Vec2::zero(),
+            velocity: Vec2::new(10.0, 0.0),
+            age: 0.0,
+            lifetime,
+        };
+
+        let mut particles = vec![particle(0.5), particle(1.0), particle(2.0)];
+
+        step_particles(&mut particles, Vec2::zero(), 0.75);
+        assert_eq!(particles.len(), 2);
+        assert_eq!(particles[0].position, Vec2::new(7.5, 0.0));
+
+        step_particles(&mut particles, Vec2::zero(), 0.25);
+        assert_eq!(particles.len(), 1);
+        assert_eq!(particles[0].progress(), 0.5);
+    }
+}
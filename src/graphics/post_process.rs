@@ -0,0 +1,233 @@
+use crate::error::Result;
+use crate::graphics::{
+    self, BlendFactor, BlendOperation, BlendState, Canvas, CanvasBuilder, Color, DrawParams,
+    Shader, Texture,
+};
+use crate::math::{Mat4, Vec2};
+use crate::Context;
+
+/// Overwrites the target with the output of the shader, so that transparent pixels
+/// in the source are carried through each pass unchanged.
+const REPLACE: BlendState = BlendState {
+    color_operation: BlendOperation::Add,
+    color_src: BlendFactor::One,
+    color_dst: BlendFactor::Zero,
+
+    alpha_operation: BlendOperation::Add,
+    alpha_src: BlendFactor::One,
+    alpha_dst: BlendFactor::Zero,
+};
+
+/// A pair of canvases that can be used to apply a series of shaders to an image, one
+/// after another.
+///
+/// Each call to [`apply`](PostProcessChain::apply) draws its source into whichever
+/// canvas is not currently holding the result, using the given shader, and then swaps
+/// the canvases around. This means that multi-pass effects (e.g. a blur, followed by a
+/// bloom composite, followed by color grading) can be built up without having to keep
+/// track of which canvas is which.
+///
+/// Canvases are always drawn the right way up, so the [result](PostProcessChain::result)
+/// will have the same orientation as the original source, no matter how many passes
+/// are applied.
+///
+/// # Uniforms
+///
+/// Each pass is drawn as soon as `apply` is called, so you can change the uniforms of a
+/// shader between passes (e.g. to switch the direction of a two-pass blur), and each pass
+/// will see the values that were set at the time.
+///
+/// # Examples
+///
+/// The [`post_process`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/post_process.rs)
+/// example demonstrates how to apply a multi-pass effect to a scene.
+#[derive(Debug, Clone)]
+pub struct PostProcessChain {
+    canvases: [Canvas; 2],
+    current: usize,
+}
+
+impl PostProcessChain {
+    /// Creates a new post-processing chain, with canvases of the given size.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn new(ctx: &mut Context, width: i32, height: i32) -> Result<PostProcessChain> {
+        PostProcessChain::from_builder(ctx, &CanvasBuilder::new(width, height))
+    }
+
+    /// Creates a new post-processing chain, with both canvases created from the given
+    /// builder.
+    ///
+    /// This can be used to configure the canvases' texture format (e.g. to use a
+    /// floating point format for HDR effects).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn from_builder(ctx: &mut Context, builder: &CanvasBuilder) -> Result<PostProcessChain> {
+        Ok(PostProcessChain {
+            canvases: [builder.build(ctx)?, builder.build(ctx)?],
+            current: 0,
+        })
+    }
+
+    /// Draws the source texture into the chain, using the given shader.
+    ///
+    /// The source will be stretched to fill the chain's canvases, so it can be a
+    /// different size (e.g. when blurring a scene at a lower resolution). The output
+    /// replaces the previous result, including its alpha channel.
+    ///
+    /// The render target, shader, blend state and transform matrix that were active
+    /// before this method was called will be restored afterwards.
+    pub fn apply(&mut self, ctx: &mut Context, shader: &Shader, source: &Texture) {
+        let target = self.canvases[1 - self.current].clone();
+
+        let previous_canvas = ctx.graphics.canvas.clone();
+        let previous_shader = ctx.graphics.shader.clone();
+        let previous_blend_state = ctx.graphics.blend_state;
+        let previous_transform = graphics::get_transform_matrix(ctx);
+
+        graphics::set_canvas(ctx, &target);
+        graphics::set_shader(ctx, shader);
+        graphics::set_blend_state(ctx, REPLACE);
+        graphics::set_transform_matrix(ctx, Mat4::identity());
+
+        graphics::clear(ctx, Color::rgba(0.0, 0.0, 0.0, 0.0));
+
+        source.draw(
+            ctx,
+            DrawParams::new().scale(Vec2::new(
+                target.width() as f32 / source.width() as f32,
+                target.height() as f32 / source.height() as f32,
+            )),
+        );
+
+        graphics::set_transform_matrix(ctx, previous_transform);
+        graphics::set_blend_state(ctx, previous_blend_state);
+        graphics::set_shader_ex(ctx, previous_shader.as_ref());
+        graphics::set_canvas_ex(ctx, previous_canvas.as_ref());
+
+        self.current = 1 - self.current;
+    }
+
+    /// Draws the current result back into the chain, using the given shader.
+    ///
+    /// This is equivalent to calling [`apply`](PostProcessChain::apply) with the
+    /// [result](PostProcessChain::result) as the source.
+    pub fn apply_to_result(&mut self, ctx: &mut Context, shader: &Shader) {
+        let source = self.result().texture().clone();
+        self.apply(ctx, shader, &source);
+    }
+
+    /// Returns the canvas that holds the output of the most recent pass.
+    ///
+    /// If no passes have been applied yet, the canvas will be empty.
+    pub fn result(&self) -> &Canvas {
+        &self.canvases[self.current]
+    }
+
+    /// Draws the output of the most recent pass to the screen (or to a canvas, if
+    /// one is enabled).
+    pub fn draw<P>(&self, ctx: &mut Context, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        self.result().draw(ctx, params);
+    }
+
+    /// Returns the width of the chain's canvases.
+    pub fn width(&self) -> i32 {
+        self.canvases[0].width()
+    }
+
+    /// Returns the height of the chain's canvases.
+    pub fn height(&self) -> i32 {
+        self.canvases[0].height()
+    }
+
+    /// Returns the size of the chain's canvases.
+    pub fn size(&self) -> (i32, i32) {
+        self.canvases[0].size()
+    }
+
+    /// Resizes both of the chain's canvases.
+    ///
+    /// This will clear the result.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn resize(&self, ctx: &mut Context, width: i32, height: i32) -> Result {
+        for canvas in &self.canvases {
+            canvas.resize(ctx, width, height)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{TextureFormat, DEFAULT_FRAGMENT_SHADER};
+    use crate::ContextBuilder;
+
+    #[test]
+    #[ignore = "requires a display and an OpenGL context"]
+    fn result_alternates_between_canvases() {
+        let mut ctx = ContextBuilder::new("result_alternates_between_canvases", 64, 64)
+            .build()
+            .unwrap();
+
+        let shader = Shader::from_fragment_string(&mut ctx, DEFAULT_FRAGMENT_SHADER).unwrap();
+        let mut chain = PostProcessChain::new(&mut ctx, 4, 4).unwrap();
+
+        assert_eq!(&chain.canvases[0], chain.result());
+
+        let colors = [Color::RED, Color::GREEN, Color::BLUE, Color::WHITE];
+
+        for (pass, color) in colors.into_iter().enumerate() {
+            let source = Texture::from_data(
+                &mut ctx,
+                1,
+                1,
+                TextureFormat::Rgba8,
+                &<[u8; 4]>::from(color),
+            )
+            .unwrap();
+
+            chain.apply(&mut ctx, &shader, &source);
+
+            // Odd-numbered passes (counting from one) write to the second canvas,
+            // and even-numbered passes write back to the first.
+            let expected = if pass % 2 == 0 { 1 } else { 0 };
+
+            assert_eq!(&chain.canvases[expected], chain.result());
+            assert_eq!(
+                color,
+                chain
+                    .result()
+                    .get_data(&mut ctx)
+                    .get_pixel_color(Vec2::new(1, 1))
+            );
+        }
+
+        // Re-applying the result should read from the canvas that was just written,
+        // rather than the stale one.
+        chain.apply_to_result(&mut ctx, &shader);
+
+        assert_eq!(&chain.canvases[1], chain.result());
+        assert_eq!(
+            Color::WHITE,
+            chain
+                .result()
+                .get_data(&mut ctx)
+                .get_pixel_color(Vec2::new(1, 1))
+        );
+    }
+}
@@ -1,20 +1,29 @@
-use std::ops::{Add, AddAssign, Div, Sub};
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
 
-use num_traits::One;
+use num_traits::{One, Zero};
 
 use crate::math::Vec2;
 
 /// A rectangle, represented by a top-left position, a width and a height.
 ///
+/// # Edges and Negative Sizes
+///
+/// The geometric helper methods (such as [`intersects`](Rectangle::intersects) and
+/// [`contains_point`](Rectangle::contains_point)) treat the left and top edges of a
+/// rectangle as being inside it, and the right and bottom edges as being outside it.
+/// This means that rectangles which only touch along an edge do not intersect, and
+/// rectangles with a width or height of zero do not contain any points.
+///
+/// These methods expect the width and height of the rectangle to be positive. If this
+/// is not the case, you can call [`normalized`](Rectangle::normalized) to get an
+/// equivalent rectangle that can be used with them.
+///
 /// # Serde
 ///
 /// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
 /// can be enabled via the `serde` feature.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Hash)]
-#[cfg_attr(
-    feature = "serde",
-    derive(serde::Serialize, serde::Deserialize)
-)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rectangle<T = f32> {
     /// The X co-ordinate of the rectangle.
     pub x: T,
@@ -124,6 +133,75 @@ where
             && point.y < self.y + self.height
     }
 
+    /// Returns the area where `self` and `other` overlap, or `None` if they do
+    /// not [intersect](Rectangle::intersects).
+    pub fn intersection(&self, other: &Rectangle<T>) -> Option<Rectangle<T>>
+    where
+        T: Add<Output = T> + Sub<Output = T> + PartialOrd,
+    {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        let x = if self.x > other.x { self.x } else { other.x };
+        let y = if self.y > other.y { self.y } else { other.y };
+
+        let right = if self.right() < other.right() {
+            self.right()
+        } else {
+            other.right()
+        };
+
+        let bottom = if self.bottom() < other.bottom() {
+            self.bottom()
+        } else {
+            other.bottom()
+        };
+
+        Some(Rectangle {
+            x,
+            y,
+            width: right - x,
+            height: bottom - y,
+        })
+    }
+
+    /// Returns an equivalent rectangle with a non-negative width and height.
+    ///
+    /// If the width or height is negative, the position will be moved to the opposite
+    /// edge, so that the rectangle covers the same area.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(10.0, 10.0, -4.0, 8.0);
+    ///
+    /// assert_eq!(Rectangle::new(6.0, 10.0, 4.0, 8.0), rect.normalized());
+    /// ```
+    pub fn normalized(&self) -> Rectangle<T>
+    where
+        T: Add<Output = T> + Sub<Output = T> + PartialOrd + Zero,
+    {
+        let (x, width) = if self.width < T::zero() {
+            (self.x + self.width, T::zero() - self.width)
+        } else {
+            (self.x, self.width)
+        };
+
+        let (y, height) = if self.height < T::zero() {
+            (self.y + self.height, T::zero() - self.height)
+        } else {
+            (self.y, self.height)
+        };
+
+        Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
     /// Returns a rectangle that contains both `self` and `other`.
     pub fn combine(&self, other: &Rectangle<T>) -> Rectangle<T>
     where
@@ -152,6 +230,109 @@ where
         }
     }
 
+    /// Returns a copy of the rectangle, moved by the given offset.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// # use tetra::math::Vec2;
+    /// let rect = Rectangle::new(16.0, 16.0, 32.0, 32.0);
+    ///
+    /// assert_eq!(Rectangle::new(20.0, 8.0, 32.0, 32.0), rect.translated(Vec2::new(4.0, -8.0)));
+    /// ```
+    pub fn translated(&self, offset: Vec2<T>) -> Rectangle<T>
+    where
+        T: Add<Output = T>,
+    {
+        Rectangle {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Returns a copy of the rectangle, with both its position and size multiplied
+    /// by the given factor.
+    ///
+    /// This scales the rectangle relative to the origin, which is useful for converting
+    /// between co-ordinate spaces (e.g. from tiles to pixels). To keep the rectangle in
+    /// the same place, use [`scaled_from_center`](Self::scaled_from_center) instead.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(1, 2, 3, 4);
+    ///
+    /// assert_eq!(Rectangle::new(16, 32, 48, 64), rect.scaled(16));
+    /// ```
+    pub fn scaled(&self, factor: T) -> Rectangle<T>
+    where
+        T: Mul<Output = T>,
+    {
+        Rectangle {
+            x: self.x * factor,
+            y: self.y * factor,
+            width: self.width * factor,
+            height: self.height * factor,
+        }
+    }
+
+    /// Returns a copy of the rectangle, with its size multiplied by the given factor
+    /// and its [center](Self::center) left in the same place.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(10.0, 10.0, 20.0, 40.0);
+    ///
+    /// assert_eq!(Rectangle::new(15.0, 20.0, 10.0, 20.0), rect.scaled_from_center(0.5));
+    /// assert_eq!(rect.center(), rect.scaled_from_center(0.5).center());
+    /// ```
+    pub fn scaled_from_center(&self, factor: T) -> Rectangle<T>
+    where
+        T: One + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+    {
+        let width = self.width * factor;
+        let height = self.height * factor;
+        let two = T::one() + T::one();
+
+        Rectangle {
+            x: self.x + (self.width - width) / two,
+            y: self.y + (self.height - height) / two,
+            width,
+            height,
+        }
+    }
+
+    /// Returns a copy of the rectangle, with each side moved outwards by the given
+    /// amount.
+    ///
+    /// Passing a negative amount will shrink the rectangle instead. If the rectangle
+    /// is shrunk by more than half of its width or height, the resulting size will be
+    /// negative - you can use [`normalized`](Self::normalized) to get a rectangle that
+    /// can be used with the geometric helper methods.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(8, 8, 16, 16);
+    ///
+    /// assert_eq!(Rectangle::new(6, 6, 20, 20), rect.inflated(2));
+    /// assert_eq!(Rectangle::new(12, 12, 8, 8), rect.inflated(-4));
+    /// ```
+    pub fn inflated(&self, amount: T) -> Rectangle<T>
+    where
+        T: Add<Output = T> + Sub<Output = T>,
+    {
+        Rectangle {
+            x: self.x - amount,
+            y: self.y - amount,
+            width: self.width + amount + amount,
+            height: self.height + amount + amount,
+        }
+    }
+
     /// Returns the X co-ordinate of the left side of the rectangle.
     ///
     /// You can also obtain this via the `x` field - this method is provided for
@@ -225,6 +406,58 @@ where
     }
 }
 
+impl Rectangle<i32> {
+    /// Converts the rectangle to use `f32` co-ordinates.
+    ///
+    /// Values with a magnitude larger than 2<sup>24</sup> cannot be represented
+    /// exactly by an `f32`, and will be rounded to the nearest representable value.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(1, 2, 3, 4);
+    ///
+    /// assert_eq!(Rectangle::new(1.0, 2.0, 3.0, 4.0), rect.as_f32());
+    /// ```
+    pub fn as_f32(&self) -> Rectangle<f32> {
+        Rectangle {
+            x: self.x as f32,
+            y: self.y as f32,
+            width: self.width as f32,
+            height: self.height as f32,
+        }
+    }
+}
+
+impl Rectangle<f32> {
+    /// Converts the rectangle to use `i32` co-ordinates, rounding each field to the
+    /// nearest whole number.
+    ///
+    /// The position and size are rounded independently, with values that are halfway
+    /// between two integers being rounded away from zero (as with [`f32::round`]).
+    /// Values that are out of the range of an `i32` will be clamped, and `NaN` will
+    /// be converted to zero.
+    ///
+    /// If you want to truncate the values instead, you can convert the fields manually
+    /// with `as`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use tetra::graphics::Rectangle;
+    /// let rect = Rectangle::new(1.4, 1.5, -2.5, 3.6);
+    ///
+    /// assert_eq!(Rectangle::new(1, 2, -3, 4), rect.as_i32_round());
+    /// ```
+    pub fn as_i32_round(&self) -> Rectangle<i32> {
+        Rectangle {
+            x: self.x.round() as i32,
+            y: self.y.round() as i32,
+            width: self.width.round() as i32,
+            height: self.height.round() as i32,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct RectangleRow<T> {
     next_rect: Rectangle<T>,
@@ -326,4 +559,108 @@ mod tests {
             Rectangle::new(8.0, 0.0, 40.0, 72.0),
         )
     }
+
+    #[test]
+    fn intersection() {
+        let base = Rectangle::new(2.0, 2.0, 4.0, 4.0);
+
+        assert_eq!(Some(base), base.intersection(&base));
+
+        assert_eq!(
+            Some(Rectangle::new(3.0, 3.0, 3.0, 3.0)),
+            base.intersection(&Rectangle::new(3.0, 3.0, 4.0, 4.0))
+        );
+
+        assert_eq!(
+            Some(Rectangle::new(2.5, 2.5, 2.0, 2.0)),
+            base.intersection(&Rectangle::new(2.5, 2.5, 2.0, 2.0))
+        );
+
+        assert_eq!(
+            None,
+            base.intersection(&Rectangle::new(20.0, 20.0, 4.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn touching_edges_do_not_intersect() {
+        let base = Rectangle::new(0, 0, 4, 4);
+
+        for other in [
+            Rectangle::new(4, 0, 4, 4),
+            Rectangle::new(-4, 0, 4, 4),
+            Rectangle::new(0, 4, 4, 4),
+            Rectangle::new(0, -4, 4, 4),
+            Rectangle::new(4, 4, 4, 4),
+        ] {
+            assert!(!base.intersects(&other));
+            assert!(!other.intersects(&base));
+            assert_eq!(None, base.intersection(&other));
+        }
+    }
+
+    #[test]
+    fn zero_sized_rectangles() {
+        let base = Rectangle::new(0.0, 0.0, 4.0, 4.0);
+        let empty = Rectangle::new(2.0, 2.0, 0.0, 0.0);
+
+        assert!(!empty.contains_point(Vec2::new(2.0, 2.0)));
+        assert!(base.contains(&empty));
+        assert!(!empty.intersects(&empty));
+
+        assert_eq!(
+            Rectangle::new(0.0, 0.0, 4.0, 4.0),
+            base.combine(&Rectangle::new(4.0, 4.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn normalized() {
+        let rect = Rectangle::new(10, 10, -4, -8);
+
+        assert_eq!(Rectangle::new(6, 2, 4, 8), rect.normalized());
+        assert_eq!(rect.normalized(), rect.normalized().normalized());
+
+        assert!(rect.normalized().contains_point(Vec2::new(7, 5)));
+        assert!(!rect.contains_point(Vec2::new(7, 5)));
+    }
+
+    #[test]
+    fn transforms_preserve_negative_sizes() {
+        let rect = Rectangle::new(10, 10, -4, -8);
+
+        assert_eq!(
+            Rectangle::new(12, 11, -4, -8),
+            rect.translated(Vec2::new(2, 1))
+        );
+        assert_eq!(Rectangle::new(20, 20, -8, -16), rect.scaled(2));
+        assert_eq!(Rectangle::new(8, 8, 0, -4), rect.inflated(2));
+        assert_eq!(Rectangle::new(12, 12, -8, -12), rect.inflated(-2));
+
+        assert_eq!(
+            Rectangle::new(9.0, 8.0, -2.0, -4.0),
+            rect.as_f32().scaled_from_center(0.5)
+        );
+    }
+
+    #[test]
+    fn float_conversions() {
+        let rect = Rectangle::new(-3, 7, 100, 0);
+
+        assert_eq!(rect, rect.as_f32().as_i32_round());
+
+        assert_eq!(
+            Rectangle::new(i32::MAX, i32::MIN, 0, -1),
+            Rectangle::new(f32::INFINITY, f32::NEG_INFINITY, f32::NAN, -0.5).as_i32_round()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let rect = Rectangle::new(1, 2, 3, 4);
+        let json = serde_json::to_string(&rect).unwrap();
+
+        assert_eq!(rect, serde_json::from_str(&json).unwrap());
+    }
 }
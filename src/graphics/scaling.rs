@@ -1,7 +1,7 @@
 //! Functions and types relating to screen scaling.
 
 use crate::error::Result;
-use crate::graphics::{self, Canvas, DrawParams, Rectangle};
+use crate::graphics::{self, Canvas, Color, DrawParams, Rectangle};
 use crate::input;
 use crate::math::Vec2;
 use crate::window;
@@ -23,6 +23,8 @@ pub struct ScreenScaler {
     inner_height: i32,
     outer_width: i32,
     outer_height: i32,
+    letterbox_color: Color,
+    clear_letterbox: bool,
 }
 
 impl ScreenScaler {
@@ -36,9 +38,12 @@ impl ScreenScaler {
         outer_height: i32,
         mode: ScalingMode,
     ) -> Result<ScreenScaler> {
-        let canvas = Canvas::new(ctx, inner_width, inner_height)?;
+        let (canvas_width, canvas_height) =
+            get_inner_size(mode, inner_width, inner_height, outer_width, outer_height);
+
+        let canvas = Canvas::new(ctx, canvas_width, canvas_height)?;
         let screen_rect =
-            get_screen_rect(mode, inner_width, inner_height, outer_width, outer_height);
+            get_screen_rect(mode, canvas_width, canvas_height, outer_width, outer_height);
 
         Ok(ScreenScaler {
             canvas,
@@ -48,11 +53,16 @@ impl ScreenScaler {
             inner_height,
             outer_width,
             outer_height,
+            letterbox_color: Color::BLACK,
+            clear_letterbox: true,
         })
     }
 
     /// Returns a new `ScreenScaler`, using the given canvas for drawing.
     /// The mode will determine how the image is scaled to fit the screen.
+    ///
+    /// If the mode is [`FitHeight`](ScalingMode::FitHeight) or [`FitWidth`](ScalingMode::FitWidth),
+    /// the canvas will not be resized until the next time the outer size or the mode is changed.
     pub fn from_canvas(
         canvas: Canvas,
         outer_width: i32,
@@ -72,6 +82,8 @@ impl ScreenScaler {
             inner_height,
             outer_width,
             outer_height,
+            letterbox_color: Color::BLACK,
+            clear_letterbox: true,
         })
     }
 
@@ -96,7 +108,37 @@ impl ScreenScaler {
     }
 
     /// Draws the scaled image to the screen.
+    ///
+    /// If [`clear_letterbox`](Self::clear_letterbox) is enabled (which it is by default),
+    /// the letterbox bars will be filled with the [`letterbox_color`](Self::letterbox_color)
+    /// first. Only the bars are filled, so anything that was previously drawn underneath the
+    /// scaled image is left alone.
     pub fn draw(&self, ctx: &mut Context) {
+        if self.clear_letterbox {
+            let texture = ctx.graphics.default_texture.clone();
+            graphics::set_texture(ctx, &texture);
+
+            let params = DrawParams::new().color(self.letterbox_color);
+
+            // The bars share their edges with the screen rect, so in the pixel perfect
+            // modes (where the screen rect is always aligned to whole pixels), there
+            // will be no seam or overlap between the bars and the image.
+            for bar in self.letterbox_rects() {
+                graphics::push_quad(
+                    ctx,
+                    bar.x,
+                    bar.y,
+                    bar.right(),
+                    bar.bottom(),
+                    0.0,
+                    0.0,
+                    1.0,
+                    1.0,
+                    &params,
+                );
+            }
+        }
+
         graphics::set_texture(ctx, &self.canvas.texture);
 
         graphics::push_quad(
@@ -113,21 +155,55 @@ impl ScreenScaler {
         );
     }
 
+    /// Draws the scaled image to the screen, and then calls the provided function once for each
+    /// of the letterbox bars that surround it.
+    ///
+    /// The function is passed the area of the bar, in window co-ordinates. This can be used to
+    /// draw decorative borders, rather than leaving the bars empty.
+    ///
+    /// This is equivalent to calling [`draw`](Self::draw) and then drawing to each of the
+    /// [`letterbox_rects`](Self::letterbox_rects) yourself - anything drawn after the scaler
+    /// (while the window is the active render target) will always be drawn in window co-ordinates,
+    /// on top of the scaled image.
+    pub fn draw_with_border<F>(&self, ctx: &mut Context, mut draw_bar: F)
+    where
+        F: FnMut(&mut Context, Rectangle),
+    {
+        self.draw(ctx);
+
+        for bar in self.letterbox_rects() {
+            draw_bar(ctx, bar);
+        }
+    }
+
     /// Updates the scaler's outer size (i.e. the size of the box that the screen will be scaled to
     /// fit within).
-    pub fn set_outer_size(&mut self, outer_width: i32, outer_height: i32) {
+    ///
+    /// If the scaler is filling the window, this should usually be called with the
+    /// `logical_size` from [`Event::WindowMetricsChanged`](crate::Event::WindowMetricsChanged),
+    /// as it is fired once per frame with consistent size and scale values.
+    ///
+    /// If the mode is [`FitHeight`](ScalingMode::FitHeight) or [`FitWidth`](ScalingMode::FitWidth),
+    /// this may [resize](Canvas::resize) the scaler's canvas, which will clear its contents.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   canvas needs to be resized, and the underlying graphics API encounters an error.
+    pub fn set_outer_size(
+        &mut self,
+        ctx: &mut Context,
+        outer_width: i32,
+        outer_height: i32,
+    ) -> Result {
         if outer_width != self.outer_width || outer_height != self.outer_height {
             self.outer_width = outer_width;
             self.outer_height = outer_height;
 
-            self.screen_rect = get_screen_rect(
-                self.mode,
-                self.canvas().width(),
-                self.canvas().height(),
-                outer_width,
-                outer_height,
-            );
+            self.update(ctx)?;
         }
+
+        Ok(())
     }
 
     /// Returns the scaler's outer size  (i.e. the size of the box that the screen will be scaled to
@@ -139,20 +215,80 @@ impl ScreenScaler {
 
     /// Returns the scaler's inner size (i.e. the logical screen size).  
     /// The format is (width, height).
+    ///
+    /// For [`FitHeight`](ScalingMode::FitHeight) and [`FitWidth`](ScalingMode::FitWidth),
+    /// this is the currently visible area, which may differ from the size that the scaler
+    /// was created with.
     pub fn inner_size(&self) -> (i32, i32) {
-        (self.inner_width, self.inner_height)
+        self.canvas.size()
     }
 
     /// Returns the optimal scale factor for the current `ScalingMode` and configured sizes.  
     /// This can be used for simple use cases where scaling by [canvas](Self::canvas) is not
     /// feasible (e.g. 3rd party UI libraries).
+    ///
+    /// For [`ShowAllPixelPerfect`](ScalingMode::ShowAllPixelPerfect) and
+    /// [`CropPixelPerfect`](ScalingMode::CropPixelPerfect), this will always be a whole number.
     pub fn scale_factor(&self) -> f32 {
         f32::min(
-            self.screen_rect.width / self.inner_width as f32,
-            self.screen_rect.height / self.inner_height as f32,
+            self.screen_rect.width / self.canvas.width() as f32,
+            self.screen_rect.height / self.canvas.height() as f32,
         )
     }
 
+    /// Returns the area that the scaled image is drawn to, in window co-ordinates.
+    ///
+    /// For the cropping modes, this may extend outside of the outer bounds. Use
+    /// [`visible_rect`](Self::visible_rect) if you only want the part that can be seen.
+    pub fn screen_rect(&self) -> Rectangle {
+        self.screen_rect
+    }
+
+    /// Returns the part of the scaled image that is visible within the outer bounds,
+    /// in window co-ordinates.
+    ///
+    /// Anything outside of this rectangle (but within the outer bounds) is part of
+    /// the letterboxing.
+    pub fn visible_rect(&self) -> Rectangle {
+        get_visible_rect(self.screen_rect, self.outer_width, self.outer_height)
+    }
+
+    /// Returns the areas of the letterbox bars that surround the scaled image, in
+    /// window co-ordinates.
+    ///
+    /// Bars with a width or height of zero are not included, so this will be empty if
+    /// the image fills the outer bounds.
+    pub fn letterbox_rects(&self) -> Vec<Rectangle> {
+        get_letterbox_rects(self.screen_rect, self.outer_width, self.outer_height)
+    }
+
+    /// Returns the color that the letterbox bars are filled with.
+    pub fn letterbox_color(&self) -> Color {
+        self.letterbox_color
+    }
+
+    /// Sets the color that the letterbox bars are filled with.
+    ///
+    /// The bars will only be filled if [`clear_letterbox`](Self::clear_letterbox) is
+    /// enabled. Defaults to black.
+    pub fn set_letterbox_color(&mut self, color: Color) {
+        self.letterbox_color = color;
+    }
+
+    /// Returns whether the letterbox bars will be filled when the scaler is drawn.
+    pub fn clear_letterbox(&self) -> bool {
+        self.clear_letterbox
+    }
+
+    /// Sets whether the letterbox bars will be filled when the scaler is drawn.
+    ///
+    /// This can be disabled if you want to draw your own content into the bars before
+    /// drawing the scaler, rather than afterwards (as with [`draw_with_border`](Self::draw_with_border)).
+    /// Defaults to `true`.
+    pub fn set_clear_letterbox(&mut self, clear_letterbox: bool) {
+        self.clear_letterbox = clear_letterbox;
+    }
+
     /// Returns a reference to the canvas that is being scaled.
     pub fn canvas(&self) -> &Canvas {
         &self.canvas
@@ -164,15 +300,17 @@ impl ScreenScaler {
     }
 
     /// Sets the scaling mode that should be used.
-    pub fn set_mode(&mut self, mode: ScalingMode) {
+    ///
+    /// If the new mode requires a different inner size, the scaler's canvas will be
+    /// resized, as described in [`set_outer_size`](Self::set_outer_size).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   canvas needs to be resized, and the underlying graphics API encounters an error.
+    pub fn set_mode(&mut self, ctx: &mut Context, mode: ScalingMode) -> Result {
         self.mode = mode;
-        self.screen_rect = get_screen_rect(
-            self.mode,
-            self.canvas().width(),
-            self.canvas().height(),
-            self.outer_width,
-            self.outer_height,
-        );
+        self.update(ctx)
     }
 
     /// Converts a point from window co-ordinates to scaled screen co-ordinates.
@@ -249,6 +387,77 @@ impl ScreenScaler {
             height as f32,
         )
     }
+
+    fn update(&mut self, ctx: &mut Context) -> Result {
+        let (width, height) = get_inner_size(
+            self.mode,
+            self.inner_width,
+            self.inner_height,
+            self.outer_width,
+            self.outer_height,
+        );
+
+        if (width, height) != self.canvas.size() {
+            self.canvas.resize(ctx, width, height)?;
+        }
+
+        self.screen_rect = get_screen_rect(
+            self.mode,
+            width,
+            height,
+            self.outer_width,
+            self.outer_height,
+        );
+
+        Ok(())
+    }
+}
+
+fn get_visible_rect(screen_rect: Rectangle, outer_width: i32, outer_height: i32) -> Rectangle {
+    let outer = Rectangle::new(0.0, 0.0, outer_width as f32, outer_height as f32);
+
+    screen_rect.intersection(&outer).unwrap_or_default()
+}
+
+fn get_letterbox_rects(
+    screen_rect: Rectangle,
+    outer_width: i32,
+    outer_height: i32,
+) -> Vec<Rectangle> {
+    let visible = get_visible_rect(screen_rect, outer_width, outer_height);
+    let outer_width = outer_width as f32;
+    let outer_height = outer_height as f32;
+
+    if visible.width <= 0.0 || visible.height <= 0.0 {
+        return if outer_width > 0.0 && outer_height > 0.0 {
+            vec![Rectangle::new(0.0, 0.0, outer_width, outer_height)]
+        } else {
+            Vec::new()
+        };
+    }
+
+    // The left and right bars cover the full height of the window, and the top and
+    // bottom bars fill the gaps between them, so that the bars never overlap.
+    let bars = [
+        Rectangle::new(0.0, 0.0, visible.x, outer_height),
+        Rectangle::new(
+            visible.right(),
+            0.0,
+            outer_width - visible.right(),
+            outer_height,
+        ),
+        Rectangle::new(visible.x, 0.0, visible.width, visible.y),
+        Rectangle::new(
+            visible.x,
+            visible.bottom(),
+            visible.width,
+            outer_height - visible.bottom(),
+        ),
+    ];
+
+    bars.into_iter()
+        .filter(|bar| bar.width > 0.0 && bar.height > 0.0)
+        .collect()
 }
 
 fn project_impl(window_pos: f32, rect_pos: f32, rect_size: f32, real_size: f32) -> f32 {
@@ -285,6 +494,56 @@ pub enum ScalingMode {
 
     /// Works the same as Crop, but will only scale by integer values.
     CropPixelPerfect,
+
+    /// The height of the screen will stay fixed, and the width will be adjusted to match
+    /// the aspect ratio of the window. The screen will fill the entire window, without
+    /// letterboxing or distortion.
+    ///
+    /// This resizes the underlying canvas, so more (or less) of the game world will be
+    /// visible at the sides of the screen as the window's width changes.
+    FitHeight,
+
+    /// The width of the screen will stay fixed, and the height will be adjusted to match
+    /// the aspect ratio of the window. The screen will fill the entire window, without
+    /// letterboxing or distortion.
+    ///
+    /// This resizes the underlying canvas, so more (or less) of the game world will be
+    /// visible at the top and bottom of the screen as the window's height changes.
+    FitWidth,
+}
+
+/// Calculates the size that a screen should be for the given mode, when being scaled to fit
+/// in the given bounds.
+///
+/// For [`FitHeight`](ScalingMode::FitHeight) and [`FitWidth`](ScalingMode::FitWidth), one of
+/// the dimensions will be adjusted to match the aspect ratio of the outer bounds. For all other
+/// modes, the inner size is returned unchanged.
+///
+/// This function may be useful if you want to use Tetra's scaling algorithms, but
+/// the built-in [`ScreenScaler`] abstraction does not fit your needs.
+pub fn get_inner_size(
+    mode: ScalingMode,
+    inner_width: i32,
+    inner_height: i32,
+    outer_width: i32,
+    outer_height: i32,
+) -> (i32, i32) {
+    // If the window has been minimized, there's no sensible aspect ratio to match.
+    if outer_width <= 0 || outer_height <= 0 {
+        return (inner_width, inner_height);
+    }
+
+    match mode {
+        ScalingMode::FitHeight => {
+            let width = (inner_height as f32 * outer_width as f32 / outer_height as f32).ceil();
+            (i32::max(width as i32, 1), inner_height)
+        }
+        ScalingMode::FitWidth => {
+            let height = (inner_width as f32 * outer_height as f32 / outer_width as f32).ceil();
+            (inner_width, i32::max(height as i32, 1))
+        }
+        _ => (inner_width, inner_height),
+    }
 }
 
 /// Converts a screen's dimensions into a rectangle that is scaled to fit in the given bounds.
@@ -392,5 +651,132 @@ pub fn get_screen_rect(
                 screen_height as f32,
             )
         }
+        ScalingMode::FitHeight => {
+            let scale_factor = f_outer_height / f_inner_height;
+
+            let screen_width = (f_inner_width * scale_factor).ceil();
+            let screen_x = ((f_outer_width - screen_width) / 2.0).ceil();
+
+            Rectangle::new(screen_x, 0.0, screen_width, f_outer_height)
+        }
+        ScalingMode::FitWidth => {
+            let scale_factor = f_outer_width / f_inner_width;
+
+            let screen_height = (f_inner_height * scale_factor).ceil();
+            let screen_y = ((f_outer_height - screen_height) / 2.0).ceil();
+
+            Rectangle::new(0.0, screen_y, f_outer_width, screen_height)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_projects_out_of_bounds() {
+        // A 4:3 screen in a 16:9 window gets bars on the left and right.
+        let rect = get_screen_rect(ScalingMode::ShowAll, 320, 240, 1280, 720);
+        assert_eq!(rect, Rectangle::new(160.0, 0.0, 960.0, 720.0));
+
+        assert_eq!(project_impl(160.0, rect.x, rect.width, 320.0), 0.0);
+        assert_eq!(project_impl(1120.0, rect.x, rect.width, 320.0), 320.0);
+
+        // Points in the bars are not clamped.
+        assert!(project_impl(80.0, rect.x, rect.width, 320.0) < 0.0);
+        assert!(project_impl(1200.0, rect.x, rect.width, 320.0) > 320.0);
+    }
+
+    #[test]
+    fn unproject_is_inverse_of_project() {
+        let rect = get_screen_rect(ScalingMode::ShowAllPixelPerfect, 320, 180, 1000, 800);
+
+        for x in [-50.0, 0.0, 123.0, 320.0, 400.0] {
+            let window = unproject_impl(x, rect.x, rect.width, 320.0);
+            assert_eq!(project_impl(window, rect.x, rect.width, 320.0), x);
+        }
+    }
+
+    #[test]
+    fn fit_modes_match_outer_aspect_ratio() {
+        assert_eq!(
+            get_inner_size(ScalingMode::FitHeight, 320, 240, 1280, 720),
+            (427, 240)
+        );
+
+        assert_eq!(
+            get_inner_size(ScalingMode::FitWidth, 320, 240, 1280, 720),
+            (320, 180)
+        );
+
+        // Other modes never change the inner size.
+        assert_eq!(
+            get_inner_size(ScalingMode::ShowAll, 320, 240, 1280, 720),
+            (320, 240)
+        );
+
+        // A minimized window shouldn't produce an empty canvas.
+        assert_eq!(
+            get_inner_size(ScalingMode::FitHeight, 320, 240, 0, 0),
+            (320, 240)
+        );
+    }
+
+    #[test]
+    fn fit_modes_fill_the_window() {
+        let (width, height) = get_inner_size(ScalingMode::FitWidth, 320, 240, 1280, 720);
+        let rect = get_screen_rect(ScalingMode::FitWidth, width, height, 1280, 720);
+        assert_eq!(rect, Rectangle::new(0.0, 0.0, 1280.0, 720.0));
+
+        // Rounding the width up means the screen can overhang the window slightly.
+        let (width, height) = get_inner_size(ScalingMode::FitHeight, 320, 240, 1280, 720);
+        let rect = get_screen_rect(ScalingMode::FitHeight, width, height, 1280, 720);
+        assert_eq!(rect.y, 0.0);
+        assert_eq!(rect.height, 720.0);
+        assert!(rect.x <= 0.0 && rect.right() >= 1280.0);
+
+        assert_eq!(project_impl(0.0, rect.y, rect.height, height as f32), 0.0);
+        assert_eq!(
+            project_impl(720.0, rect.y, rect.height, height as f32),
+            240.0
+        );
+    }
+
+    #[test]
+    fn letterbox_rects_surround_screen() {
+        let rect = get_screen_rect(ScalingMode::ShowAll, 320, 240, 1280, 720);
+
+        assert_eq!(get_visible_rect(rect, 1280, 720), rect);
+        assert_eq!(
+            get_letterbox_rects(rect, 1280, 720),
+            vec![
+                Rectangle::new(0.0, 0.0, 160.0, 720.0),
+                Rectangle::new(1120.0, 0.0, 160.0, 720.0),
+            ]
+        );
+
+        let rect = get_screen_rect(ScalingMode::Fixed, 320, 240, 640, 480);
+
+        assert_eq!(
+            get_letterbox_rects(rect, 640, 480),
+            vec![
+                Rectangle::new(0.0, 0.0, 160.0, 480.0),
+                Rectangle::new(480.0, 0.0, 160.0, 480.0),
+                Rectangle::new(160.0, 0.0, 320.0, 120.0),
+                Rectangle::new(160.0, 360.0, 320.0, 120.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn cropped_screen_has_no_letterbox() {
+        let rect = get_screen_rect(ScalingMode::Crop, 320, 240, 1280, 720);
+
+        assert_eq!(
+            get_visible_rect(rect, 1280, 720),
+            Rectangle::new(0.0, 0.0, 1280.0, 720.0)
+        );
+        assert!(get_letterbox_rects(rect, 1280, 720).is_empty());
     }
 }
@@ -1,24 +1,35 @@
 //! Functions and types relating to shader programs.
 
+mod preprocess;
+
 use std::cell::{Cell, RefCell};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::slice;
 
 use hashbrown::HashMap;
 
-use crate::error::Result;
+use crate::error::{Result, TetraError};
 use crate::fs;
-use crate::graphics::{Color, Texture};
+use crate::graphics::{self, Color, Texture, WarmUpItem};
 use crate::math::{Mat2, Mat3, Mat4, Vec2, Vec3, Vec4};
 use crate::platform::{GraphicsDevice, RawShader};
 use crate::Context;
 
+use preprocess::{Preprocessor, ProcessedSource};
+
 /// The default vertex shader.
 ///
 /// The source code for this shader is available in [`src/resources/shader.vert`](https://github.com/17cupsofcoffee/tetra/blob/main/src/resources/shader.vert).
 pub const DEFAULT_VERTEX_SHADER: &str = include_str!("../resources/shader.vert");
 
+/// The default vertex shader for meshes with an [instance buffer](super::mesh::InstanceBuffer).
+///
+/// This is used in place of [`DEFAULT_VERTEX_SHADER`] when no custom shader is set.
+///
+/// The source code for this shader is available in [`src/resources/instanced.vert`](https://github.com/17cupsofcoffee/tetra/blob/main/src/resources/instanced.vert).
+pub const DEFAULT_INSTANCED_VERTEX_SHADER: &str = include_str!("../resources/instanced.vert");
+
 /// The default fragment shader.
 ///
 /// The source code for this shader is available in [`src/resources/shader.vert`](https://github.com/17cupsofcoffee/tetra/blob/main/src/resources/shader.frag).
@@ -32,14 +43,145 @@ pub(crate) struct Sampler {
 
 #[derive(Debug)]
 pub(crate) struct ShaderSharedData {
-    pub(crate) handle: RawShader,
+    // The program gets swapped out when the shader is reloaded, so that every clone
+    // of the shader picks up the new version.
+    pub(crate) handle: RefCell<RawShader>,
     pub(crate) samplers: RefCell<HashMap<String, Sampler>>,
-    pub(crate) next_unit: Cell<u32>,
+    pub(crate) uniforms: RefCell<HashMap<String, UniformInfo>>,
+    pub(crate) sources: Option<ShaderSources>,
+    pub(crate) values: RefCell<HashMap<String, UniformData>>,
+    pub(crate) warmed_up: Cell<bool>,
+}
+
+impl ShaderSharedData {
+    /// Returns the texture unit that will be used for the given sampler uniform, if it
+    /// is set via [`Shader::set_uniform`].
+    ///
+    /// If the uniform already has a unit, that will be reused - otherwise, the lowest
+    /// free unit is chosen. Returns `None` if there are no free units.
+    fn sampler_unit(&self, name: &str, max_units: u32) -> Option<u32> {
+        let samplers = self.samplers.borrow();
+
+        if let Some(sampler) = samplers.get(name) {
+            return Some(sampler.unit);
+        }
+
+        lowest_free_unit(samplers.values().map(|sampler| sampler.unit), max_units)
+    }
+
+    /// Stores the value of a uniform, so that it can be restored if the shader is reloaded.
+    ///
+    /// This is skipped for shaders that can't be reloaded, to avoid the extra allocations.
+    fn record_uniform(&self, name: &str, value: impl FnOnce() -> UniformData) {
+        if self.sources.is_some() {
+            self.values.borrow_mut().insert(name.to_owned(), value());
+        }
+    }
+}
+
+/// Adds a list of the files that each shader was built from to a compiler error, so that
+/// the source string numbers set by the preprocessor's `#line` directives can be
+/// matched up with the right file.
+fn annotate_error(msg: String, vertex: &ProcessedSource, fragment: &ProcessedSource) -> String {
+    if vertex.files.len() <= 1 && fragment.files.len() <= 1 {
+        return msg;
+    }
+
+    let mut annotated = msg;
+
+    for (stage, source) in [("vertex", vertex), ("fragment", fragment)] {
+        if source.files.is_empty() {
+            continue;
+        }
+
+        annotated.push_str(&format!("\nSource strings in the {} shader:", stage));
+
+        for (i, file) in source.files.iter().enumerate() {
+            annotated.push_str(&format!("\n  {}: {}", i, file));
+        }
+    }
+
+    annotated
+}
+
+/// Finds the lowest texture unit that is not in the given list of used units.
+fn lowest_free_unit(used: impl Iterator<Item = u32> + Clone, max_units: u32) -> Option<u32> {
+    // Unit 0 is reserved for the texture that is currently being drawn.
+    (1..max_units).find(|&unit| used.clone().all(|used| used != unit))
+}
+
+/// Where the code for one stage of a shader comes from.
+#[derive(Debug, Clone, Default, PartialEq)]
+enum StageSource {
+    #[default]
+    Default,
+    File(PathBuf),
+    String(String),
+}
+
+impl StageSource {
+    fn read(
+        &self,
+        default: &str,
+        name: &str,
+        preprocessor: Option<&Preprocessor>,
+    ) -> Result<ProcessedSource> {
+        match (self, preprocessor) {
+            (StageSource::Default, _) => Ok(ProcessedSource {
+                code: default.to_owned(),
+                files: Vec::new(),
+            }),
+
+            (StageSource::File(path), Some(preprocessor)) => preprocessor.process_file(path),
+
+            (StageSource::File(path), None) => Ok(ProcessedSource {
+                code: fs::read_to_string(path)?,
+                files: vec![path.display().to_string()],
+            }),
+
+            (StageSource::String(source), Some(preprocessor)) => {
+                preprocessor.process_string(name, source)
+            }
+
+            (StageSource::String(source), None) => Ok(ProcessedSource {
+                code: source.clone(),
+                files: Vec::new(),
+            }),
+        }
+    }
+}
+
+/// The sources that a shader was loaded from, so that it can be reloaded.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ShaderSources {
+    vertex: StageSource,
+    fragment: StageSource,
+    preprocessor: Option<Preprocessor>,
+}
+
+impl ShaderSources {
+    fn read(&self) -> Result<(ProcessedSource, ProcessedSource)> {
+        let preprocessor = self.preprocessor.as_ref();
+
+        let vertex = self
+            .vertex
+            .read(DEFAULT_VERTEX_SHADER, "vertex shader", preprocessor)?;
+
+        let fragment =
+            self.fragment
+                .read(DEFAULT_FRAGMENT_SHADER, "fragment shader", preprocessor)?;
+
+        Ok((vertex, fragment))
+    }
+
+    fn is_reloadable(&self) -> bool {
+        matches!(self.vertex, StageSource::File(_)) || matches!(self.fragment, StageSource::File(_))
+    }
 }
 
 impl PartialEq for ShaderSharedData {
     fn eq(&self, other: &ShaderSharedData) -> bool {
-        self.handle.eq(&other.handle)
+        *self.handle.borrow() == *other.handle.borrow()
     }
 }
 
@@ -57,6 +199,20 @@ impl PartialEq for ShaderSharedData {
 /// * `a_uv` - A `vec2` representing the texture co-ordinates that are associated with the vertex.
 /// * `a_color` - A `vec4` representing the color of the vertex. This will be multiplied by
 ///   `u_diffuse` and the color sampled from `u_texture` (see 'Uniforms' below).
+/// * `a_depth` - A `float` representing the depth of the vertex, as set by [`DrawParams::depth`](super::DrawParams::depth).
+///   The default shader writes this to the Z component of `gl_Position`.
+///
+/// Two additional attributes are available, containing [per-instance data](super::mesh::InstanceBuffer):
+///
+/// * `a_instance_transform` - A `mat3` representing the 2D transform of the instance. This should be
+///   applied to `a_position`.
+/// * `a_instance_color` - A `vec4` representing the color of the instance. This should be multiplied
+///   by `a_color`.
+///
+/// When no instance buffer is being used, these will be set to an identity matrix and white respectively,
+/// so it is safe to use them in shaders that are used for all kinds of rendering. The default vertex
+/// shader ignores them - a separate [default instanced shader](DEFAULT_INSTANCED_VERTEX_SHADER) is used
+/// for meshes that have an instance buffer attached.
 ///
 /// Position data should be output as a `vec4` to the built-in `gl_Position` variable.
 ///
@@ -76,6 +232,8 @@ impl PartialEq for ShaderSharedData {
 ///   otherwise be set to [`Color::WHITE`].
 ///
 /// You can also set data into your own uniform variables via the [`set_uniform`](Shader::set_uniform) method.
+/// Information about the uniforms declared in the shader (such as the length of an array) can be
+/// retrieved via the [`get_uniform_info`](Shader::get_uniform_info) method.
 ///
 /// Bear in mind that there is a hardware-defined limit on how many uniform locations can be used
 /// per shader. OpenGL 3.0 guarantees there will be at least 1024 of these locations available,
@@ -116,10 +274,13 @@ impl Shader {
     where
         P: AsRef<Path>,
     {
-        Shader::with_device(
+        Shader::from_sources(
             &mut ctx.device,
-            &fs::read_to_string(vertex_path)?,
-            &fs::read_to_string(fragment_path)?,
+            ShaderSources {
+                vertex: StageSource::File(vertex_path.as_ref().to_owned()),
+                fragment: StageSource::File(fragment_path.as_ref().to_owned()),
+                preprocessor: None,
+            },
         )
     }
 
@@ -139,10 +300,13 @@ impl Shader {
     where
         P: AsRef<Path>,
     {
-        Shader::with_device(
+        Shader::from_sources(
             &mut ctx.device,
-            &fs::read_to_string(path)?,
-            DEFAULT_FRAGMENT_SHADER,
+            ShaderSources {
+                vertex: StageSource::File(path.as_ref().to_owned()),
+                fragment: StageSource::Default,
+                preprocessor: None,
+            },
         )
     }
 
@@ -162,10 +326,13 @@ impl Shader {
     where
         P: AsRef<Path>,
     {
-        Shader::with_device(
+        Shader::from_sources(
             &mut ctx.device,
-            DEFAULT_VERTEX_SHADER,
-            &fs::read_to_string(path)?,
+            ShaderSources {
+                vertex: StageSource::Default,
+                fragment: StageSource::File(path.as_ref().to_owned()),
+                preprocessor: None,
+            },
         )
     }
 
@@ -217,27 +384,249 @@ impl Shader {
         device: &mut GraphicsDevice,
         vertex_shader: &str,
         fragment_shader: &str,
+    ) -> Result<Shader> {
+        Shader::compile(device, vertex_shader, fragment_shader, None)
+    }
+
+    /// Creates a new [`ShaderBuilder`], which can be used to preprocess the shader's
+    /// code before it is compiled.
+    pub fn builder() -> ShaderBuilder {
+        ShaderBuilder::new()
+    }
+
+    fn from_sources(device: &mut GraphicsDevice, sources: ShaderSources) -> Result<Shader> {
+        let (vertex_shader, fragment_shader) = sources.read()?;
+
+        let sources = if sources.is_reloadable() {
+            Some(sources)
+        } else {
+            None
+        };
+
+        Shader::compile(device, &vertex_shader.code, &fragment_shader.code, sources).map_err(|e| {
+            match e {
+                TetraError::InvalidShader(msg) => {
+                    TetraError::InvalidShader(annotate_error(msg, &vertex_shader, &fragment_shader))
+                }
+                e => e,
+            }
+        })
+    }
+
+    fn compile(
+        device: &mut GraphicsDevice,
+        vertex_shader: &str,
+        fragment_shader: &str,
+        sources: Option<ShaderSources>,
     ) -> Result<Shader> {
         let handle = device.new_shader(vertex_shader, fragment_shader)?;
 
+        let uniforms = device
+            .get_active_uniforms(&handle)
+            .into_iter()
+            .map(|info| (info.name.clone(), info))
+            .collect();
+
         Ok(Shader {
             data: Rc::new(ShaderSharedData {
-                handle,
+                handle: RefCell::new(handle),
                 samplers: RefCell::new(HashMap::new()),
-                next_unit: Cell::new(1),
+                uniforms: RefCell::new(uniforms),
+                sources,
+                values: RefCell::new(HashMap::new()),
+                warmed_up: Cell::new(false),
             }),
         })
     }
 
+    /// Reloads the shader from the files that it was originally loaded from.
+    ///
+    /// This is useful for iterating on a shader while your game is running - for example,
+    /// you could call this when a key is pressed, or when you detect that the file has
+    /// changed.
+    ///
+    /// Any uniforms (including textures) that were previously set on the shader will be
+    /// set again on the reloaded version, as long as they still exist.
+    ///
+    /// The new version is shared by every clone of the shader, including the one that is
+    /// currently [active](crate::graphics::set_shader) (if any). If the shader is active,
+    /// anything that has already been drawn with it will be flushed first.
+    ///
+    /// If the shader was created from strings rather than files, this method has no effect.
+    ///
+    /// # Errors
+    ///
+    /// If an error is returned, the shader will be left unchanged, so that your game can
+    /// continue running with the old version.
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   underlying graphics API encounters an error.
+    /// * [`TetraError::FailedToLoadAsset`](crate::TetraError::FailedToLoadAsset) will be returned
+    ///   if the files could not be loaded.
+    /// * [`TetraError::InvalidShader`](crate::TetraError::InvalidShader) will be returned if the
+    ///   shader could not be compiled.
+    pub fn reload(&self, ctx: &mut Context) -> Result {
+        let sources = match &self.data.sources {
+            Some(sources) => sources.clone(),
+            None => return Ok(()),
+        };
+
+        let reloaded = Shader::from_sources(&mut ctx.device, sources)?;
+        let reloaded = Rc::try_unwrap(reloaded.data).expect("reloaded shader should be unique");
+        let handle = reloaded.handle.into_inner();
+
+        for (name, sampler) in self.data.samplers.borrow().iter() {
+            let location = ctx.device.get_uniform_location(&handle, name);
+
+            ctx.device
+                .set_uniform_i32(&handle, location.as_ref(), &[sampler.unit as i32]);
+        }
+
+        for (name, value) in self.data.values.borrow().iter() {
+            value.apply(&mut ctx.device, &handle, name);
+        }
+
+        // Anything that was batched with the old program needs to be drawn before it
+        // gets deleted.
+        let is_active =
+            matches!(&ctx.graphics.shader, Some(active) if Rc::ptr_eq(&active.data, &self.data));
+
+        if is_active {
+            graphics::flush(ctx);
+        }
+
+        *self.data.handle.borrow_mut() = handle;
+        *self.data.uniforms.borrow_mut() = reloaded.uniforms.into_inner();
+        self.data.warmed_up.set(false);
+
+        Ok(())
+    }
+
     /// Sets the value of the specifed uniform parameter.
     ///
     /// See the [`UniformValue`] trait's docs for a list of which types can be used as a uniform,
     /// and what their corresponding GLSL types are.
-    pub fn set_uniform<V>(&self, ctx: &mut Context, name: &str, value: V)
+    ///
+    /// If the uniform does not exist in the shader (or has been optimized out by the
+    /// compiler), this method will have no effect.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::InvalidUniform`](crate::TetraError::InvalidUniform) will be returned
+    ///   if more values are provided than the uniform can hold (e.g. passing a slice of
+    ///   64 elements to a `vec2[32]`).
+    /// * [`TetraError::UniformTypeMismatch`](crate::TetraError::UniformTypeMismatch) will be
+    ///   returned if the value's type does not match the type that the uniform was declared
+    ///   with (e.g. passing a `Vec3` to a `vec2`).
+    /// * [`TetraError::InvalidTextureUnit`](crate::TetraError::InvalidTextureUnit) will be
+    ///   returned if a texture is provided, but all of the texture units supported by the
+    ///   graphics hardware are already in use.
+    pub fn set_uniform<V>(&self, ctx: &mut Context, name: &str, value: V) -> Result
     where
         V: UniformValue,
     {
-        value.set_uniform(ctx, self, name)
+        if let Some(info) = self.data.uniforms.borrow().get(name) {
+            validate_uniform(info, value.uniform_len(), value.uniform_type())?;
+        }
+
+        value.check_uniform(ctx, self, name)?;
+        value.set_uniform(ctx, self, name);
+
+        Ok(())
+    }
+
+    /// Sets the value of a `sampler2D` uniform, binding the texture to the specified
+    /// texture unit.
+    ///
+    /// When a texture is passed to [`set_uniform`](Self::set_uniform), it is automatically
+    /// assigned to the lowest texture unit that is not already in use by another of the
+    /// shader's sampler uniforms. This method allows you to choose the unit yourself
+    /// instead, which can be useful if your shader code relies on a specific layout.
+    ///
+    /// Each unit can only be used by one uniform at a time - if you want to sample the
+    /// same texture from two different uniforms, bind it to two different units.
+    /// Unit 0 is reserved for the texture that is currently being drawn (i.e. `u_texture`).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::InvalidTextureUnit`](crate::TetraError::InvalidTextureUnit) will be
+    ///   returned if the unit is reserved, if it is already being used by a different
+    ///   uniform, or if it is higher than the graphics hardware supports.
+    pub fn set_uniform_texture_unit(
+        &self,
+        ctx: &mut Context,
+        name: &str,
+        texture: &Texture,
+        unit: u32,
+    ) -> Result {
+        {
+            let mut samplers = self.data.samplers.borrow_mut();
+
+            let unit_in_use = samplers
+                .iter()
+                .any(|(other, sampler)| other != name && sampler.unit == unit);
+
+            if unit == 0 || unit >= ctx.device.max_texture_units() || unit_in_use {
+                return Err(TetraError::InvalidTextureUnit {
+                    name: name.to_owned(),
+                    unit,
+                });
+            }
+
+            let previous = samplers.insert(
+                name.to_owned(),
+                Sampler {
+                    texture: texture.clone(),
+                    unit,
+                },
+            );
+
+            if matches!(previous, Some(sampler) if sampler.unit == unit) {
+                return Ok(());
+            }
+        }
+
+        // Sampler uniforms have to be set via glUniform1i
+        (unit as i32).set_uniform(ctx, self, name);
+
+        Ok(())
+    }
+
+    /// Returns the texture unit that a `sampler2D` uniform has been bound to.
+    ///
+    /// Returns `None` if no texture has been set for the uniform yet.
+    pub fn get_uniform_texture_unit(&self, name: &str) -> Option<u32> {
+        self.data
+            .samplers
+            .borrow()
+            .get(name)
+            .map(|sampler| sampler.unit)
+    }
+
+    /// Returns information about a uniform, as declared in the compiled shader program.
+    ///
+    /// Arrays should be looked up without an index (e.g. `u_offsets` rather than
+    /// `u_offsets[0]`).
+    ///
+    /// Returns `None` if the uniform does not exist in the shader. Note that the compiler
+    /// may optimize out any uniforms that are not used.
+    pub fn get_uniform_info(&self, name: &str) -> Option<UniformInfo> {
+        self.data.uniforms.borrow().get(name).cloned()
+    }
+
+    /// Prepares the shader for drawing, so that using it for the first time does not
+    /// cause a hitch.
+    ///
+    /// This is a shortcut for calling [`graphics::warm_up`] with a single shader. If the
+    /// shader has already been warmed up, this does nothing. Note that reloading a shader
+    /// creates a new program, which will need to be warmed up again.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   underlying graphics API encounters an error.
+    pub fn warm_up(&self, ctx: &mut Context) -> Result {
+        graphics::warm_up(ctx, &[WarmUpItem::Shader(self)])
     }
 
     pub(crate) fn set_default_uniforms(
@@ -252,18 +641,19 @@ impl Shader {
             device.attach_texture_to_sampler(&sampler.texture.data.handle, sampler.unit)?;
         }
 
-        let projection_location = device.get_uniform_location(&self.data.handle, "u_projection");
+        let projection_location =
+            device.get_uniform_location(&self.data.handle.borrow(), "u_projection");
 
         device.set_uniform_mat4(
-            &self.data.handle,
+            &self.data.handle.borrow(),
             projection_location.as_ref(),
             &[projection],
         );
 
-        let diffuse_location = device.get_uniform_location(&self.data.handle, "u_diffuse");
+        let diffuse_location = device.get_uniform_location(&self.data.handle.borrow(), "u_diffuse");
 
         device.set_uniform_vec4(
-            &self.data.handle,
+            &self.data.handle.borrow(),
             diffuse_location.as_ref(),
             &[diffuse.into()],
         );
@@ -272,6 +662,141 @@ impl Shader {
     }
 }
 
+/// A builder for creating shaders that share code between files, or that need to be
+/// configured before they are compiled.
+///
+/// # Includes
+///
+/// Lines of the form `#include "file.glsl"` will be replaced with the contents of the
+/// named file before the shader is compiled. Includes in a shader that was loaded from a
+/// file are resolved relative to that file's directory - includes in strings (and
+/// includes that can't be found on disk) are looked up in the virtual files that were
+/// registered via [`include_string`](ShaderBuilder::include_string). Included files can
+/// include other files, but an [`InvalidShader`](crate::TetraError::InvalidShader) error
+/// will be returned if a file ends up including itself.
+///
+/// Each included file is marked with a `#line` directive, so line numbers in compiler
+/// errors will refer to the original files. The files are identified by number rather
+/// than by name (as GLSL does not support file names in `#line`), so when a shader that
+/// uses includes fails to compile, a list of which number refers to which file will be
+/// added to the end of the error message.
+///
+/// # Defines
+///
+/// Values that are passed to [`define`](ShaderBuilder::define) will be injected into
+/// each stage of the shader as `#define` directives, directly after the `#version` line.
+/// This can be used to toggle features of a shader without having to maintain multiple
+/// copies of it.
+///
+/// # Reloading
+///
+/// If either stage of the shader was loaded from a file, [`Shader::reload`] will re-read
+/// the files (including any included files) and run them through the preprocessor again.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderBuilder {
+    vertex: StageSource,
+    fragment: StageSource,
+    preprocessor: Preprocessor,
+}
+
+impl ShaderBuilder {
+    /// Creates a new shader builder.
+    ///
+    /// If no code is provided for one of the stages, the default shader will be used.
+    /// You can also use [`Shader::builder`] as a shortcut for this, if you want to avoid
+    /// the extra import.
+    pub fn new() -> ShaderBuilder {
+        ShaderBuilder::default()
+    }
+
+    /// Sets the file that the vertex shader should be loaded from.
+    pub fn vertex_file<P>(&mut self, path: P) -> &mut ShaderBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.vertex = StageSource::File(path.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the file that the fragment shader should be loaded from.
+    pub fn fragment_file<P>(&mut self, path: P) -> &mut ShaderBuilder
+    where
+        P: AsRef<Path>,
+    {
+        self.fragment = StageSource::File(path.as_ref().to_owned());
+        self
+    }
+
+    /// Sets the code for the vertex shader.
+    pub fn vertex_string<S>(&mut self, source: S) -> &mut ShaderBuilder
+    where
+        S: Into<String>,
+    {
+        self.vertex = StageSource::String(source.into());
+        self
+    }
+
+    /// Sets the code for the fragment shader.
+    pub fn fragment_string<S>(&mut self, source: S) -> &mut ShaderBuilder
+    where
+        S: Into<String>,
+    {
+        self.fragment = StageSource::String(source.into());
+        self
+    }
+
+    /// Adds a `#define` to both stages of the shader.
+    ///
+    /// Defines are injected in the order that they are added.
+    pub fn define<N, V>(&mut self, name: N, value: V) -> &mut ShaderBuilder
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.preprocessor.defines.push((name.into(), value.into()));
+
+        self
+    }
+
+    /// Registers a virtual file, which can be included by name from any stage of
+    /// the shader.
+    ///
+    /// This is required for includes to work in shaders that were created from strings.
+    pub fn include_string<N, S>(&mut self, name: N, source: S) -> &mut ShaderBuilder
+    where
+        N: Into<String>,
+        S: Into<String>,
+    {
+        self.preprocessor
+            .virtual_files
+            .insert(name.into(), source.into());
+
+        self
+    }
+
+    /// Builds the shader.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if the
+    ///   underlying graphics API encounters an error.
+    /// * [`TetraError::FailedToLoadAsset`](crate::TetraError::FailedToLoadAsset) will be returned
+    ///   if the shader's files (or any files that they include) could not be loaded.
+    /// * [`TetraError::InvalidShader`](crate::TetraError::InvalidShader) will be returned if an
+    ///   include could not be resolved, if the includes form a cycle, or if the shader could
+    ///   not be compiled.
+    pub fn build(&self, ctx: &mut Context) -> Result<Shader> {
+        Shader::from_sources(
+            &mut ctx.device,
+            ShaderSources {
+                vertex: self.vertex.clone(),
+                fragment: self.fragment.clone(),
+                preprocessor: Some(self.preprocessor.clone()),
+            },
+        )
+    }
+}
+
 /// Implemented for types that can be passed as a uniform value to a shader.
 ///
 /// As the implementation of this trait currently interacts directly with the platform layer,
@@ -279,10 +804,144 @@ impl Shader {
 pub trait UniformValue {
     #[doc(hidden)]
     fn set_uniform(&self, ctx: &mut Context, shader: &Shader, name: &str);
+
+    #[doc(hidden)]
+    fn uniform_len(&self) -> usize {
+        1
+    }
+
+    #[doc(hidden)]
+    fn uniform_type(&self) -> Option<UniformType> {
+        None
+    }
+
+    #[doc(hidden)]
+    fn check_uniform(&self, _ctx: &Context, _shader: &Shader, _name: &str) -> Result {
+        Ok(())
+    }
+}
+
+/// Checks that a value can be assigned to a uniform, based on the info that was
+/// reported by the shader program.
+fn validate_uniform(info: &UniformInfo, len: usize, value_type: Option<UniformType>) -> Result {
+    if len > info.size {
+        return Err(TetraError::InvalidUniform {
+            name: info.name.clone(),
+            expected: info.size,
+            actual: len,
+        });
+    }
+
+    if let Some(value_type) = value_type {
+        // Samplers are set via integers, and types that we don't recognize (e.g. `bool`)
+        // are left for the graphics driver to validate.
+        let compatible = match (info.uniform_type, value_type) {
+            (UniformType::Other, _) => true,
+            (UniformType::Sampler2D, UniformType::Int) => true,
+            (expected, actual) => expected == actual,
+        };
+
+        if !compatible {
+            return Err(TetraError::UniformTypeMismatch {
+                name: info.name.clone(),
+                expected: info.uniform_type,
+                actual: value_type,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Information about a uniform that is declared in a shader program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformInfo {
+    /// The name of the uniform. For arrays, this will not include the index.
+    pub name: String,
+
+    /// The number of elements in the uniform. This will be `1`, unless the
+    /// uniform is an array.
+    pub size: usize,
+
+    /// The type of the uniform (or of the array's elements, if it is an array).
+    pub uniform_type: UniformType,
+}
+
+/// The GLSL types that a uniform can have.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniformType {
+    /// An `int`.
+    Int,
+
+    /// A `uint`.
+    UInt,
+
+    /// A `float`.
+    Float,
+
+    /// A `vec2`.
+    Vec2,
+
+    /// A `vec3`.
+    Vec3,
+
+    /// A `vec4`.
+    Vec4,
+
+    /// A `mat2`.
+    Mat2,
+
+    /// A `mat3`.
+    Mat3,
+
+    /// A `mat4`.
+    Mat4,
+
+    /// A `sampler2D`.
+    Sampler2D,
+
+    /// A type that Tetra does not currently support setting directly.
+    Other,
+}
+
+/// A copy of a uniform's value, so that it can be re-applied after a shader is reloaded.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum UniformData {
+    I32(Vec<i32>),
+    U32(Vec<u32>),
+    F32(Vec<f32>),
+    Vec2(Vec<Vec2<f32>>),
+    Vec3(Vec<Vec3<f32>>),
+    Vec4(Vec<Vec4<f32>>),
+    Mat2(Vec<Mat2<f32>>),
+    Mat3(Vec<Mat3<f32>>),
+    Mat4(Vec<Mat4<f32>>),
+    Color(Vec<Color>),
+}
+
+impl UniformData {
+    fn apply(&self, device: &mut GraphicsDevice, shader: &RawShader, name: &str) {
+        let location = device.get_uniform_location(shader, name);
+        let location = location.as_ref();
+
+        match self {
+            UniformData::I32(values) => device.set_uniform_i32(shader, location, values),
+            UniformData::U32(values) => device.set_uniform_u32(shader, location, values),
+            UniformData::F32(values) => device.set_uniform_f32(shader, location, values),
+            UniformData::Vec2(values) => device.set_uniform_vec2(shader, location, values),
+            UniformData::Vec3(values) => device.set_uniform_vec3(shader, location, values),
+            UniformData::Vec4(values) => device.set_uniform_vec4(shader, location, values),
+            UniformData::Mat2(values) => device.set_uniform_mat2(shader, location, values),
+            UniformData::Mat3(values) => device.set_uniform_mat3(shader, location, values),
+            UniformData::Mat4(values) => device.set_uniform_mat4(shader, location, values),
+            UniformData::Color(values) => device.set_uniform_color(shader, location, values),
+        }
+    }
 }
 
 macro_rules! simple_uniforms {
-    ($($t:ty => $f:ident, $doc:expr, $arraydoc:expr),* $(,)?) => {
+    ($($t:ty => $f:ident, $variant:ident, $utype:ident, $doc:expr, $arraydoc:expr),* $(,)?) => {
         $(
             #[doc = $doc]
             impl UniformValue for $t {
@@ -293,8 +952,14 @@ macro_rules! simple_uniforms {
                     shader: &Shader,
                     name: &str,
                 ) {
-                    let location = ctx.device.get_uniform_location(&shader.data.handle, name);
-                    ctx.device.$f(&shader.data.handle, location.as_ref(), slice::from_ref(self));
+                    let location = ctx.device.get_uniform_location(&shader.data.handle.borrow(), name);
+                    ctx.device.$f(&shader.data.handle.borrow(), location.as_ref(), slice::from_ref(self));
+                    shader.data.record_uniform(name, || UniformData::$variant(vec![*self]));
+                }
+
+                #[doc(hidden)]
+                fn uniform_type(&self) -> Option<UniformType> {
+                    Some(UniformType::$utype)
                 }
             }
 
@@ -307,8 +972,19 @@ macro_rules! simple_uniforms {
                     shader: &Shader,
                     name: &str,
                 ) {
-                    let location = ctx.device.get_uniform_location(&shader.data.handle, name);
-                    ctx.device.$f(&shader.data.handle, location.as_ref(), self);
+                    let location = ctx.device.get_uniform_location(&shader.data.handle.borrow(), name);
+                    ctx.device.$f(&shader.data.handle.borrow(), location.as_ref(), self);
+                    shader.data.record_uniform(name, || UniformData::$variant(self.to_vec()));
+                }
+
+                #[doc(hidden)]
+                fn uniform_len(&self) -> usize {
+                    self.len()
+                }
+
+                #[doc(hidden)]
+                fn uniform_type(&self) -> Option<UniformType> {
+                    Some(UniformType::$utype)
                 }
             }
 
@@ -321,8 +997,19 @@ macro_rules! simple_uniforms {
                     shader: &Shader,
                     name: &str,
                 ) {
-                    let location = ctx.device.get_uniform_location(&shader.data.handle, name);
-                    ctx.device.$f(&shader.data.handle, location.as_ref(), self);
+                    let location = ctx.device.get_uniform_location(&shader.data.handle.borrow(), name);
+                    ctx.device.$f(&shader.data.handle.borrow(), location.as_ref(), self);
+                    shader.data.record_uniform(name, || UniformData::$variant(self.to_vec()));
+                }
+
+                #[doc(hidden)]
+                fn uniform_len(&self) -> usize {
+                    N
+                }
+
+                #[doc(hidden)]
+                fn uniform_type(&self) -> Option<UniformType> {
+                    Some(UniformType::$utype)
                 }
             }
         )*
@@ -330,22 +1017,32 @@ macro_rules! simple_uniforms {
 }
 
 simple_uniforms! {
-    i32 => set_uniform_i32, "Can be accessed as an `int` in your shader.", "Can be accessed as an array of `int`s in your shader.",
-    u32 => set_uniform_u32, "Can be accessed as a `uint` in your shader.", "Can be accessed as an array of `uint`s in your shader.",
-    f32 => set_uniform_f32, "Can be accessed as a `float` in your shader.", "Can be accessed as an array of `float`s in your shader.",
-    Vec2<f32> => set_uniform_vec2, "Can be accessed as a `vec2` in your shader.", "Can be accessed as an array of `vec2`s in your shader.",
-    Vec3<f32> => set_uniform_vec3, "Can be accessed as a `vec3` in your shader.", "Can be accessed as an array of `vec3`s in your shader.",
-    Vec4<f32> => set_uniform_vec4, "Can be accessed as a `vec4` in your shader.", "Can be accessed as an array of `vec4`s in your shader.",
-    Mat2<f32> => set_uniform_mat2, "Can be accessed as a `mat2` in your shader.", "Can be accessed as an array of `mat2`s in your shader.",
-    Mat3<f32> => set_uniform_mat3, "Can be accessed as a `mat3` in your shader.", "Can be accessed as an array of `mat3`s in your shader.",
-    Mat4<f32> => set_uniform_mat4, "Can be accessed as a `mat4` in your shader.", "Can be accessed as an array of `mat4`s in your shader.",
-    Color => set_uniform_color, "Can be accessed as a `vec4` in your shader.", "Can be accessed as an array of `vec4`s in your shader.",
+    i32 => set_uniform_i32, I32, Int, "Can be accessed as an `int` in your shader.", "Can be accessed as an array of `int`s in your shader.",
+    u32 => set_uniform_u32, U32, UInt, "Can be accessed as a `uint` in your shader.", "Can be accessed as an array of `uint`s in your shader.",
+    f32 => set_uniform_f32, F32, Float, "Can be accessed as a `float` in your shader.", "Can be accessed as an array of `float`s in your shader.",
+    Vec2<f32> => set_uniform_vec2, Vec2, Vec2, "Can be accessed as a `vec2` in your shader.", "Can be accessed as an array of `vec2`s in your shader.",
+    Vec3<f32> => set_uniform_vec3, Vec3, Vec3, "Can be accessed as a `vec3` in your shader.", "Can be accessed as an array of `vec3`s in your shader.",
+    Vec4<f32> => set_uniform_vec4, Vec4, Vec4, "Can be accessed as a `vec4` in your shader.", "Can be accessed as an array of `vec4`s in your shader.",
+    Mat2<f32> => set_uniform_mat2, Mat2, Mat2, "Can be accessed as a `mat2` in your shader.", "Can be accessed as an array of `mat2`s in your shader.",
+    Mat3<f32> => set_uniform_mat3, Mat3, Mat3, "Can be accessed as a `mat3` in your shader.", "Can be accessed as an array of `mat3`s in your shader.",
+    Mat4<f32> => set_uniform_mat4, Mat4, Mat4, "Can be accessed as a `mat4` in your shader.", "Can be accessed as an array of `mat4`s in your shader.",
+    Color => set_uniform_color, Color, Vec4, "Can be accessed as a `vec4` in your shader.", "Can be accessed as an array of `vec4`s in your shader.",
 }
 
 /// Can be accessed via a `sampler2D` in your shader.
+///
+/// Each sampler uniform is assigned to the lowest texture unit that is not already in use
+/// by the shader, the first time that it is set. Unit 0 is reserved for `u_texture`, so
+/// the first sampler will use unit 1, the second will use unit 2, and so on. To choose
+/// the unit yourself, use [`Shader::set_uniform_texture_unit`].
 impl UniformValue for Texture {
     #[doc(hidden)]
     fn set_uniform(&self, ctx: &mut Context, shader: &Shader, name: &str) {
+        let unit = shader
+            .data
+            .sampler_unit(name, ctx.device.max_texture_units())
+            .expect("texture unit should have been checked");
+
         let mut samplers = shader.data.samplers.borrow_mut();
 
         if let Some(sampler) = samplers.get_mut(name) {
@@ -353,26 +1050,40 @@ impl UniformValue for Texture {
                 sampler.texture = self.clone();
             }
         } else {
-            let next_unit = shader.data.next_unit.get();
-
             samplers.insert(
                 name.to_owned(),
                 Sampler {
                     texture: self.clone(),
-                    unit: next_unit,
+                    unit,
                 },
             );
 
             // Sampler uniforms have to be set via glUniform1i
-            (next_unit as i32).set_uniform(ctx, shader, name);
+            (unit as i32).set_uniform(ctx, shader, name);
+        }
+    }
 
-            shader.data.next_unit.set(next_unit + 1);
+    #[doc(hidden)]
+    fn uniform_type(&self) -> Option<UniformType> {
+        Some(UniformType::Sampler2D)
+    }
+
+    #[doc(hidden)]
+    fn check_uniform(&self, ctx: &Context, shader: &Shader, name: &str) -> Result {
+        let max_units = ctx.device.max_texture_units();
+
+        match shader.data.sampler_unit(name, max_units) {
+            Some(_) => Ok(()),
+            None => Err(TetraError::InvalidTextureUnit {
+                name: name.to_owned(),
+                unit: max_units,
+            }),
         }
     }
 }
 
 /// Any type that can be passed by value to a shader can also be passed by reference.
-impl<'a, T> UniformValue for &'a T
+impl<T> UniformValue for &T
 where
     T: UniformValue,
 {
@@ -383,4 +1094,184 @@ where
             inner.set_uniform(ctx, shader, name);
         }
     }
+
+    #[doc(hidden)]
+    fn uniform_len(&self) -> usize {
+        (*self).uniform_len()
+    }
+
+    #[doc(hidden)]
+    fn uniform_type(&self) -> Option<UniformType> {
+        (*self).uniform_type()
+    }
+
+    #[doc(hidden)]
+    fn check_uniform(&self, ctx: &Context, shader: &Shader, name: &str) -> Result {
+        (*self).check_uniform(ctx, shader, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowest_free_unit_fills_gaps() {
+        assert_eq!(lowest_free_unit([].into_iter(), 16), Some(1));
+        assert_eq!(lowest_free_unit([1, 2].into_iter(), 16), Some(3));
+        assert_eq!(lowest_free_unit([1, 3].into_iter(), 16), Some(2));
+        assert_eq!(lowest_free_unit([1, 2, 3].into_iter(), 4), None);
+    }
+
+    fn info(size: usize, uniform_type: UniformType) -> UniformInfo {
+        UniformInfo {
+            name: "u_value".into(),
+            size,
+            uniform_type,
+        }
+    }
+
+    #[test]
+    fn oversized_uniform_values_are_rejected() {
+        let offsets = info(32, UniformType::Vec2);
+
+        assert!(validate_uniform(&offsets, 1, Some(UniformType::Vec2)).is_ok());
+        assert!(validate_uniform(&offsets, 32, Some(UniformType::Vec2)).is_ok());
+
+        match validate_uniform(&offsets, 64, Some(UniformType::Vec2)) {
+            Err(TetraError::InvalidUniform {
+                name,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(name, "u_value");
+                assert_eq!(expected, 32);
+                assert_eq!(actual, 64);
+            }
+            other => panic!("expected InvalidUniform, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn mismatched_uniform_types_are_rejected() {
+        let vec2 = info(1, UniformType::Vec2);
+
+        match validate_uniform(&vec2, 1, Some(UniformType::Vec3)) {
+            Err(TetraError::UniformTypeMismatch {
+                name,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(name, "u_value");
+                assert_eq!(expected, UniformType::Vec2);
+                assert_eq!(actual, UniformType::Vec3);
+            }
+            other => panic!("expected UniformTypeMismatch, got {:?}", other),
+        }
+
+        // Size is checked before type.
+        assert!(matches!(
+            validate_uniform(&vec2, 2, Some(UniformType::Vec3)),
+            Err(TetraError::InvalidUniform { .. })
+        ));
+    }
+
+    #[test]
+    fn compatible_uniform_types_are_accepted() {
+        let sampler = info(1, UniformType::Sampler2D);
+        let unknown = info(1, UniformType::Other);
+
+        assert!(validate_uniform(&sampler, 1, Some(UniformType::Sampler2D)).is_ok());
+        assert!(validate_uniform(&sampler, 1, Some(UniformType::Int)).is_ok());
+        assert!(validate_uniform(&unknown, 1, Some(UniformType::Float)).is_ok());
+        assert!(validate_uniform(&info(1, UniformType::Mat4), 1, None).is_ok());
+    }
+
+    #[test]
+    fn uniform_values_report_their_types() {
+        assert_eq!(1.0f32.uniform_type(), Some(UniformType::Float));
+        assert_eq!(Color::WHITE.uniform_type(), Some(UniformType::Vec4));
+        assert_eq!(
+            [Vec2::<f32>::zero(); 4].uniform_type(),
+            Some(UniformType::Vec2)
+        );
+        assert_eq!((&&[1u32, 2][..]).uniform_type(), Some(UniformType::UInt));
+        assert_eq!((&&[1u32, 2][..]).uniform_len(), 2);
+    }
+
+    #[test]
+    fn missing_sources_use_defaults() {
+        let sources = ShaderSources {
+            vertex: StageSource::Default,
+            fragment: StageSource::Default,
+            preprocessor: None,
+        };
+
+        let (vertex, fragment) = sources.read().unwrap();
+
+        assert_eq!(DEFAULT_VERTEX_SHADER, vertex.code);
+        assert_eq!(DEFAULT_FRAGMENT_SHADER, fragment.code);
+    }
+
+    #[test]
+    fn builder_sources_are_preprocessed() {
+        let mut builder = ShaderBuilder::new();
+
+        builder
+            .fragment_string("#version 150\n#include \"common.glsl\"")
+            .include_string("common.glsl", "float common() {}")
+            .define("FEATURE_X", "1");
+
+        let sources = ShaderSources {
+            vertex: builder.vertex.clone(),
+            fragment: builder.fragment.clone(),
+            preprocessor: Some(builder.preprocessor.clone()),
+        };
+
+        let (vertex, fragment) = sources.read().unwrap();
+
+        // The default shader is left untouched.
+        assert_eq!(DEFAULT_VERTEX_SHADER, vertex.code);
+
+        assert!(fragment.code.contains("#define FEATURE_X 1"));
+        assert!(fragment.code.contains("float common() {}"));
+        assert_eq!(fragment.files, ["fragment shader", "common.glsl"]);
+
+        // Shaders built only from strings can't be reloaded.
+        assert!(!sources.is_reloadable());
+    }
+
+    #[test]
+    fn compile_errors_list_included_files() {
+        let single = ProcessedSource {
+            code: String::new(),
+            files: vec!["shader.vert".into()],
+        };
+
+        let multiple = ProcessedSource {
+            code: String::new(),
+            files: vec!["shader.frag".into(), "common.glsl".into()],
+        };
+
+        assert_eq!(annotate_error("error".into(), &single, &single), "error");
+
+        assert_eq!(
+            annotate_error("0:1(1): error".into(), &single, &multiple),
+            "0:1(1): error\nSource strings in the vertex shader:\n  0: shader.vert\nSource strings in the fragment shader:\n  0: shader.frag\n  1: common.glsl"
+        );
+    }
+
+    #[test]
+    fn missing_files_fail_to_load() {
+        let sources = ShaderSources {
+            vertex: StageSource::Default,
+            fragment: StageSource::File(PathBuf::from("./does/not/exist.frag")),
+            preprocessor: None,
+        };
+
+        assert!(matches!(
+            sources.read(),
+            Err(TetraError::FailedToLoadAsset { .. })
+        ));
+    }
 }
@@ -0,0 +1,442 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use hashbrown::HashMap;
+
+use crate::error::{Result, TetraError};
+use crate::fs;
+
+/// The maximum depth of nested includes. This is a backstop for cycles that can't be
+/// detected by comparing paths (e.g. due to symlinks).
+const MAX_INCLUDE_DEPTH: usize = 32;
+
+/// Settings for expanding `#include` directives and injecting `#define`s into shader
+/// source code, before it is compiled.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Preprocessor {
+    pub(crate) defines: Vec<(String, String)>,
+    pub(crate) virtual_files: HashMap<String, String>,
+}
+
+/// Shader source code that has been run through the [`Preprocessor`].
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProcessedSource {
+    pub(crate) code: String,
+
+    /// The names of the files that the code was built from. The index of each file
+    /// is used as its source string number in `#line` directives, and so will appear
+    /// in the compiler's error messages.
+    pub(crate) files: Vec<String>,
+}
+
+impl Preprocessor {
+    pub(crate) fn process_file(&self, path: &Path) -> Result<ProcessedSource> {
+        let source = fs::read_to_string(path)?;
+        self.process(Origin::File(path.to_owned()), &source)
+    }
+
+    pub(crate) fn process_string(&self, name: &str, source: &str) -> Result<ProcessedSource> {
+        self.process(Origin::String(name.to_owned()), source)
+    }
+
+    fn process(&self, origin: Origin, source: &str) -> Result<ProcessedSource> {
+        let version = source.lines().find_map(parse_version);
+
+        let mut expansion = Expansion {
+            preprocessor: self,
+            // Before GLSL 3.30, `#line` set the number of the line that the directive
+            // was on, rather than the line after it.
+            legacy_line_numbers: version.is_none_or(|v| v < 330),
+            code: String::with_capacity(source.len()),
+            files: Vec::new(),
+            stack: Vec::new(),
+        };
+
+        if version.is_none() {
+            expansion.push_defines();
+            expansion.push_line_directive(1, 0);
+        }
+
+        expansion.expand(origin, source, true)?;
+
+        Ok(ProcessedSource {
+            code: expansion.code,
+            files: expansion.files,
+        })
+    }
+}
+
+/// Where a piece of shader source code came from.
+#[derive(Debug, Clone)]
+enum Origin {
+    File(PathBuf),
+    String(String),
+    Virtual(String),
+}
+
+impl Origin {
+    fn name(&self) -> String {
+        match self {
+            Origin::File(path) => path.display().to_string(),
+            Origin::String(name) | Origin::Virtual(name) => name.clone(),
+        }
+    }
+
+    /// Returns a key that identifies the source, for the purposes of detecting cycles.
+    fn key(&self) -> String {
+        match self {
+            Origin::File(path) => std::fs::canonicalize(path)
+                .unwrap_or_else(|_| path.clone())
+                .display()
+                .to_string(),
+            Origin::String(name) => format!("string:{}", name),
+            Origin::Virtual(name) => format!("virtual:{}", name),
+        }
+    }
+}
+
+struct Expansion<'a> {
+    preprocessor: &'a Preprocessor,
+    legacy_line_numbers: bool,
+    code: String,
+    files: Vec<String>,
+    stack: Vec<Origin>,
+}
+
+impl Expansion<'_> {
+    fn expand(&mut self, origin: Origin, source: &str, is_root: bool) -> Result {
+        let key = origin.key();
+
+        if let Some(start) = self.stack.iter().position(|other| other.key() == key) {
+            let chain: Vec<String> = self.stack[start..]
+                .iter()
+                .chain(std::iter::once(&origin))
+                .map(Origin::name)
+                .collect();
+
+            return Err(TetraError::InvalidShader(format!(
+                "include cycle detected: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        if self.stack.len() >= MAX_INCLUDE_DEPTH {
+            return Err(TetraError::InvalidShader(format!(
+                "includes are nested more than {} levels deep (in {})",
+                MAX_INCLUDE_DEPTH,
+                origin.name()
+            )));
+        }
+
+        let index = self.file_index(&origin);
+
+        if !is_root {
+            self.push_line_directive(1, index);
+        }
+
+        self.stack.push(origin.clone());
+
+        let mut injected_defines = false;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+
+            if is_root && !injected_defines && parse_version(line).is_some() {
+                self.push_line(line);
+                self.push_defines();
+                self.push_line_directive(line_number + 1, index);
+
+                injected_defines = true;
+                continue;
+            }
+
+            match parse_include(line) {
+                Some(Ok(name)) => {
+                    let (included, included_source) = self.resolve(&origin, name)?;
+
+                    self.expand(included, &included_source, false)?;
+                    self.push_line_directive(line_number + 1, index);
+                }
+
+                Some(Err(())) => {
+                    return Err(TetraError::InvalidShader(format!(
+                        "malformed #include on line {} of {}",
+                        line_number,
+                        origin.name()
+                    )));
+                }
+
+                None => self.push_line(line),
+            }
+        }
+
+        self.stack.pop();
+
+        Ok(())
+    }
+
+    /// Finds the source code for an included file.
+    ///
+    /// Includes in files are resolved relative to the file's directory, falling back
+    /// to the virtual files if there is no file at that path. Includes in strings and
+    /// virtual files can only be resolved from the virtual files.
+    fn resolve(&self, from: &Origin, name: &str) -> Result<(Origin, String)> {
+        let virtual_file = self.preprocessor.virtual_files.get(name);
+
+        if let Origin::File(path) = from {
+            let relative = path.parent().unwrap_or(Path::new("")).join(name);
+
+            return match fs::read_to_string(&relative) {
+                Ok(source) => Ok((Origin::File(relative), source)),
+                Err(e) => match virtual_file {
+                    Some(source) => Ok((Origin::Virtual(name.to_owned()), source.clone())),
+                    None => Err(e),
+                },
+            };
+        }
+
+        match virtual_file {
+            Some(source) => Ok((Origin::Virtual(name.to_owned()), source.clone())),
+            None => Err(TetraError::InvalidShader(format!(
+                "could not find file \"{}\" (included from {})",
+                name,
+                from.name()
+            ))),
+        }
+    }
+
+    fn file_index(&mut self, origin: &Origin) -> usize {
+        let name = origin.name();
+
+        match self.files.iter().position(|file| *file == name) {
+            Some(index) => index,
+            None => {
+                self.files.push(name);
+                self.files.len() - 1
+            }
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        self.code.push_str(line);
+        self.code.push('\n');
+    }
+
+    fn push_defines(&mut self) {
+        for (name, value) in &self.preprocessor.defines {
+            let _ = writeln!(self.code, "#define {} {}", name, value);
+        }
+    }
+
+    /// Makes the next line be reported as `line` of the given source string.
+    fn push_line_directive(&mut self, line: usize, source: usize) {
+        let line = if self.legacy_line_numbers {
+            line - 1
+        } else {
+            line
+        };
+
+        let _ = writeln!(self.code, "#line {} {}", line, source);
+    }
+}
+
+/// Returns the version number if the line is a `#version` directive.
+fn parse_version(line: &str) -> Option<u32> {
+    let rest = parse_directive(line, "version")?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+/// Returns the file name if the line is an `#include` directive, or an error if the
+/// directive is malformed.
+fn parse_include(line: &str) -> Option<std::result::Result<&str, ()>> {
+    let rest = parse_directive(line, "include")?;
+
+    let name = rest
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|name| !name.is_empty() && !name.contains('"'));
+
+    Some(name.ok_or(()))
+}
+
+/// Returns the rest of the line if it is a preprocessor directive with the given name.
+fn parse_directive<'a>(line: &'a str, directive: &str) -> Option<&'a str> {
+    let rest = line.trim().strip_prefix('#')?.trim_start();
+    let rest = rest.strip_prefix(directive)?;
+
+    // Make sure that this isn't just the start of a longer word.
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    Some(rest.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocessor(virtual_files: &[(&str, &str)]) -> Preprocessor {
+        Preprocessor {
+            defines: Vec::new(),
+            virtual_files: virtual_files
+                .iter()
+                .map(|(name, source)| (name.to_string(), source.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn defines_are_injected_after_version() {
+        let mut preprocessor = preprocessor(&[]);
+        preprocessor.defines.push(("FEATURE_X".into(), "1".into()));
+        preprocessor.defines.push(("SAMPLES".into(), "4".into()));
+
+        let output = preprocessor
+            .process_string("main", "// Comment\n#version 330 core\nvoid main() {}")
+            .unwrap();
+
+        assert_eq!(
+            output.code,
+            "// Comment\n#version 330 core\n#define FEATURE_X 1\n#define SAMPLES 4\n#line 3 0\nvoid main() {}\n"
+        );
+
+        assert_eq!(output.files, ["main"]);
+    }
+
+    #[test]
+    fn defines_are_injected_at_start_without_version() {
+        let mut preprocessor = preprocessor(&[]);
+        preprocessor.defines.push(("FEATURE_X".into(), "1".into()));
+
+        let output = preprocessor
+            .process_string("main", "void main() {}")
+            .unwrap();
+
+        // GLSL 1.10 is the default, which uses the old `#line` behaviour.
+        assert_eq!(
+            output.code,
+            "#define FEATURE_X 1\n#line 0 0\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn includes_are_expanded() {
+        let preprocessor = preprocessor(&[
+            ("common.glsl", "#include \"noise.glsl\"\nfloat common() {}"),
+            ("noise.glsl", "float noise() {}"),
+        ]);
+
+        let output = preprocessor
+            .process_string(
+                "main",
+                "#version 330\n#include \"common.glsl\"\nvoid main() {}",
+            )
+            .unwrap();
+
+        assert_eq!(
+            output.code,
+            "#version 330\n#line 2 0\n#line 1 1\n#line 1 2\nfloat noise() {}\n#line 2 1\nfloat common() {}\n#line 3 0\nvoid main() {}\n"
+        );
+
+        assert_eq!(output.files, ["main", "common.glsl", "noise.glsl"]);
+    }
+
+    #[test]
+    fn legacy_line_numbers_are_offset() {
+        let preprocessor = preprocessor(&[("common.glsl", "float common() {}")]);
+
+        let output = preprocessor
+            .process_string(
+                "main",
+                "#version 150\n#include \"common.glsl\"\nvoid main() {}",
+            )
+            .unwrap();
+
+        assert_eq!(
+            output.code,
+            "#version 150\n#line 1 0\n#line 0 1\nfloat common() {}\n#line 2 0\nvoid main() {}\n"
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_reported() {
+        let preprocessor = preprocessor(&[
+            ("a.glsl", "#include \"b.glsl\""),
+            ("b.glsl", "#include \"c.glsl\""),
+            ("c.glsl", "#include \"a.glsl\""),
+        ]);
+
+        let error = preprocessor
+            .process_string("main", "#include \"a.glsl\"")
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            TetraError::InvalidShader(msg) if msg == "include cycle detected: a.glsl -> b.glsl -> c.glsl -> a.glsl"
+        ));
+    }
+
+    #[test]
+    fn repeated_includes_are_not_cycles() {
+        let preprocessor = preprocessor(&[("common.glsl", "float common() {}")]);
+
+        let output = preprocessor
+            .process_string("main", "#include \"common.glsl\"\n#include \"common.glsl\"")
+            .unwrap();
+
+        assert_eq!(output.files, ["main", "common.glsl"]);
+    }
+
+    #[test]
+    fn invalid_includes_are_reported() {
+        let preprocessor = preprocessor(&[]);
+
+        assert!(matches!(
+            preprocessor.process_string("main", "#include \"missing.glsl\""),
+            Err(TetraError::InvalidShader(_))
+        ));
+
+        assert!(matches!(
+            preprocessor.process_string("main", "#include <common.glsl>"),
+            Err(TetraError::InvalidShader(_))
+        ));
+
+        // Similarly named directives are left alone.
+        assert!(preprocessor
+            .process_string("main", "#included\n#extension GL_foo : enable")
+            .is_ok());
+    }
+
+    #[test]
+    fn file_includes_are_relative() {
+        let dir = std::env::temp_dir().join(format!("tetra-{}-shader-include", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib")).unwrap();
+
+        std::fs::write(
+            dir.join("main.frag"),
+            "#version 330\n#include \"lib/common.glsl\"\nvoid main() {}",
+        )
+        .unwrap();
+
+        std::fs::write(
+            dir.join("lib").join("common.glsl"),
+            "#include \"virtual.glsl\"\n#include \"noise.glsl\"",
+        )
+        .unwrap();
+
+        std::fs::write(dir.join("lib").join("noise.glsl"), "float noise() {}").unwrap();
+
+        let preprocessor = preprocessor(&[("virtual.glsl", "float virtual_fn() {}")]);
+        let output = preprocessor.process_file(&dir.join("main.frag"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let output = output.unwrap();
+
+        assert!(output.code.contains("float noise() {}"));
+        assert!(output.code.contains("float virtual_fn() {}"));
+        assert_eq!(output.files.len(), 4);
+        assert_eq!(output.files[2], "virtual.glsl");
+    }
+}
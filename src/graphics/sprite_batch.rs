@@ -0,0 +1,350 @@
+use std::ops::Range;
+
+use crate::error::Result;
+use crate::graphics::mesh::{BufferUsage, IndexBuffer, Mesh, Vertex, VertexBuffer};
+use crate::graphics::{self, DrawParams, Rectangle, Texture};
+use crate::Context;
+
+/// A collection of sprites that share a texture, stored in GPU memory so that they can be
+/// drawn repeatedly without being re-uploaded every frame.
+///
+/// Tetra already batches together sprites that are drawn with the same texture, but these
+/// batches are rebuilt from scratch every frame. For large amounts of geometry that rarely
+/// changes (e.g. a tile map), this can be wasteful - a `SpriteBatch` allows you to build
+/// the geometry once, and then only re-upload the parts that have changed.
+///
+/// Each sprite is positioned using [`DrawParams`], in the same way as if you were drawing
+/// the texture directly. The `filter_mode` of these params is ignored - instead, the params
+/// passed to [`draw`](SpriteBatch::draw) will be used for the whole batch.
+///
+/// # Performance
+///
+/// Drawing a `SpriteBatch` will trigger a [`flush`](crate::graphics::flush), and then draw
+/// the entire batch in a single draw call.
+///
+/// Modifying the batch does not update the GPU immediately - any changed sprites will
+/// be uploaded the next time the batch is drawn (or when [`upload`](SpriteBatch::upload)
+/// is called). Inserting or removing a sprite will require every sprite after it to be
+/// re-uploaded, so if you need to frequently add and remove sprites, you may want to add
+/// them to the end of the batch instead.
+///
+/// If the number of sprites grows beyond the capacity of the batch's buffers, they will be
+/// recreated with a larger size.
+#[derive(Debug)]
+pub struct SpriteBatch {
+    texture: Texture,
+    vertices: Vec<Vertex>,
+    mesh: Option<Mesh>,
+    capacity: usize,
+    dirty: Option<Range<usize>>,
+}
+
+impl SpriteBatch {
+    /// Creates a new, empty sprite batch.
+    ///
+    /// No GPU resources will be allocated until the batch is first drawn or uploaded.
+    pub fn new(texture: Texture) -> SpriteBatch {
+        SpriteBatch {
+            texture,
+            vertices: Vec::new(),
+            mesh: None,
+            capacity: 0,
+            dirty: None,
+        }
+    }
+
+    /// Creates a new sprite batch, with enough space in the GPU buffers to store
+    /// the given number of sprites.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn with_capacity(
+        ctx: &mut Context,
+        texture: Texture,
+        capacity: usize,
+    ) -> Result<SpriteBatch> {
+        let mut batch = SpriteBatch::new(texture);
+        batch.vertices.reserve(capacity * 4);
+
+        if capacity > 0 {
+            batch.allocate(ctx, capacity)?;
+        }
+
+        Ok(batch)
+    }
+
+    /// Adds a sprite that displays the entire texture, returning its index.
+    pub fn add<P>(&mut self, params: P) -> usize
+    where
+        P: Into<DrawParams>,
+    {
+        let index = self.len();
+        self.insert(index, params);
+        index
+    }
+
+    /// Adds a sprite that displays a region of the texture, returning its index.
+    pub fn add_region<P>(&mut self, region: Rectangle, params: P) -> usize
+    where
+        P: Into<DrawParams>,
+    {
+        let index = self.len();
+        self.insert_region(index, region, params);
+        index
+    }
+
+    /// Inserts a sprite that displays the entire texture at the given index, shifting
+    /// all of the sprites after it along by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert<P>(&mut self, index: usize, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        self.insert_region(index, self.full_region(), params);
+    }
+
+    /// Inserts a sprite that displays a region of the texture at the given index, shifting
+    /// all of the sprites after it along by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert_region<P>(&mut self, index: usize, region: Rectangle, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        assert!(index <= self.len(), "sprite index out of bounds");
+
+        let quad = self.quad(region, &params.into());
+        let start = index * 4;
+
+        self.vertices.splice(start..start, quad);
+        self.mark_dirty(index..self.len());
+    }
+
+    /// Replaces the sprite at the given index with one that displays the entire texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set<P>(&mut self, index: usize, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        self.set_region(index, self.full_region(), params);
+    }
+
+    /// Replaces the sprite at the given index with one that displays a region of the texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn set_region<P>(&mut self, index: usize, region: Rectangle, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        assert!(index < self.len(), "sprite index out of bounds");
+
+        let quad = self.quad(region, &params.into());
+        let start = index * 4;
+
+        self.vertices[start..start + 4].copy_from_slice(&quad);
+        self.mark_dirty(index..index + 1);
+    }
+
+    /// Removes the sprite at the given index, shifting all of the sprites after it
+    /// back by one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, index: usize) {
+        assert!(index < self.len(), "sprite index out of bounds");
+
+        let start = index * 4;
+
+        self.vertices.drain(start..start + 4);
+        self.mark_dirty(index..self.len());
+    }
+
+    /// Removes all of the sprites from the batch.
+    ///
+    /// The GPU buffers will not be deallocated, so they can be reused.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.dirty = None;
+    }
+
+    /// Returns the number of sprites in the batch.
+    pub fn len(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    /// Returns `true` if the batch does not contain any sprites.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Returns the number of sprites that can be stored before the GPU buffers need
+    /// to be reallocated.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns a reference to the texture used by the batch.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Uploads any changed sprites to the GPU.
+    ///
+    /// This is called automatically by [`draw`](SpriteBatch::draw), so you only need to
+    /// call it yourself if you want to control when the upload happens.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error while reallocating the buffers.
+    pub fn upload(&mut self, ctx: &mut Context) -> Result {
+        let len = self.len();
+
+        if len > self.capacity {
+            self.allocate(ctx, usize::max(len, self.capacity * 2))?;
+        }
+
+        if let Some(mesh) = &self.mesh {
+            if let Some(range) = upload_range(self.dirty.take(), len) {
+                mesh.vertex_buffer()
+                    .set_data(ctx, &self.vertices[range.clone()], range.start);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Draws the batch to the screen (or to a canvas, if one is enabled).
+    ///
+    /// The params will be applied to the batch as a whole, on top of the params
+    /// of each individual sprite.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error while uploading the sprites.
+    ///   Nothing will be drawn in this case.
+    pub fn draw<P>(&mut self, ctx: &mut Context, params: P) -> Result
+    where
+        P: Into<DrawParams>,
+    {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        self.upload(ctx)?;
+
+        if let Some(mesh) = &mut self.mesh {
+            mesh.set_draw_range(0, self.vertices.len() / 4 * 6);
+            mesh.draw(ctx, params);
+        }
+
+        Ok(())
+    }
+
+    fn allocate(&mut self, ctx: &mut Context, capacity: usize) -> Result {
+        let mut vertices = self.vertices.clone();
+        vertices.resize(capacity * 4, Vertex::default());
+
+        let indices: Vec<u32> = (0..capacity as u32)
+            .flat_map(|i| graphics::INDEX_ARRAY.iter().map(move |v| v + i * 4))
+            .collect();
+
+        let vertex_buffer = VertexBuffer::with_usage(ctx, &vertices, BufferUsage::Dynamic)?;
+        let index_buffer = IndexBuffer::with_usage(ctx, &indices, BufferUsage::Static)?;
+
+        let mut mesh = Mesh::indexed(vertex_buffer, index_buffer);
+        mesh.set_texture(self.texture.clone());
+
+        self.mesh = Some(mesh);
+        self.capacity = capacity;
+
+        // All of the existing data was uploaded as part of creating the buffer.
+        self.dirty = None;
+
+        Ok(())
+    }
+
+    fn full_region(&self) -> Rectangle {
+        let (width, height) = self.texture.size();
+        Rectangle::new(0.0, 0.0, width as f32, height as f32)
+    }
+
+    fn quad(&self, region: Rectangle, params: &DrawParams) -> [Vertex; 4] {
+        graphics::region_vertices(self.texture.size(), region, params)
+    }
+
+    fn mark_dirty(&mut self, range: Range<usize>) {
+        self.dirty = merge_ranges(self.dirty.take(), range);
+    }
+}
+
+fn merge_ranges(existing: Option<Range<usize>>, new: Range<usize>) -> Option<Range<usize>> {
+    match existing {
+        Some(existing) => {
+            Some(usize::min(existing.start, new.start)..usize::max(existing.end, new.end))
+        }
+
+        None if new.is_empty() => None,
+        None => Some(new),
+    }
+}
+
+/// Converts a range of dirty sprites into the range of vertices that needs to be uploaded,
+/// ignoring any sprites that have since been removed.
+fn upload_range(dirty: Option<Range<usize>>, len: usize) -> Option<Range<usize>> {
+    let dirty = dirty?;
+    let end = usize::min(dirty.end, len);
+
+    if dirty.start < end {
+        Some(dirty.start * 4..end * 4)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_ranges, upload_range};
+
+    #[test]
+    fn merge_into_empty() {
+        assert_eq!(merge_ranges(None, 2..3), Some(2..3));
+        assert_eq!(merge_ranges(None, 3..3), None);
+    }
+
+    #[test]
+    fn merge_covers_both_ranges() {
+        assert_eq!(merge_ranges(Some(2..3), 5..6), Some(2..6));
+        assert_eq!(merge_ranges(Some(5..6), 2..3), Some(2..6));
+        assert_eq!(merge_ranges(Some(1..10), 4..5), Some(1..10));
+    }
+
+    #[test]
+    fn upload_only_covers_dirty_sprites() {
+        assert_eq!(upload_range(None, 10), None);
+        assert_eq!(upload_range(Some(2..3), 10), Some(8..12));
+        assert_eq!(upload_range(Some(0..10), 10), Some(0..40));
+    }
+
+    #[test]
+    fn upload_skips_removed_sprites() {
+        // Removing the last sprites leaves a dirty range that extends past the end.
+        assert_eq!(upload_range(Some(3..6), 4), Some(12..16));
+        assert_eq!(upload_range(Some(4..6), 4), None);
+        assert_eq!(upload_range(Some(3..6), 0), None);
+    }
+}
@@ -8,12 +8,14 @@ mod vector;
 
 use std::cell::RefCell;
 use std::fmt::{self, Debug, Formatter};
+use std::ops::Range;
 use std::path::Path;
 use std::rc::Rc;
 
 use crate::error::Result;
-use crate::graphics::text::cache::{FontCache, TextGeometry};
-use crate::graphics::{self, DrawParams, Rectangle};
+use crate::graphics::text::cache::{FontCache, GlyphAtlas, LayoutOptions, TextGeometry, TextQuad};
+use crate::graphics::{self, Color, DrawParams, Rectangle};
+use crate::math::{Vec2, Vec4};
 use crate::Context;
 
 #[cfg(feature = "font_ttf")]
@@ -37,6 +39,122 @@ pub enum FontTextureStyle {
     Premultiplied,
 }
 
+/// Points within the bounds of a piece of text, which can be used as the origin
+/// when positioning or rotating it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Anchor {
+    /// The top left corner of the text.
+    TopLeft,
+
+    /// The center of the top edge of the text.
+    TopCenter,
+
+    /// The top right corner of the text.
+    TopRight,
+
+    /// The center of the left edge of the text.
+    CenterLeft,
+
+    /// The center of the text.
+    Center,
+
+    /// The center of the right edge of the text.
+    CenterRight,
+
+    /// The bottom left corner of the text.
+    BottomLeft,
+
+    /// The center of the bottom edge of the text.
+    BottomCenter,
+
+    /// The bottom right corner of the text.
+    BottomRight,
+}
+
+impl Anchor {
+    fn point_in(self, bounds: Rectangle) -> Vec2<f32> {
+        let x = match self {
+            Anchor::TopLeft | Anchor::CenterLeft | Anchor::BottomLeft => bounds.left(),
+            Anchor::TopCenter | Anchor::Center | Anchor::BottomCenter => bounds.center().x,
+            Anchor::TopRight | Anchor::CenterRight | Anchor::BottomRight => bounds.right(),
+        };
+
+        let y = match self {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => bounds.top(),
+            Anchor::CenterLeft | Anchor::Center | Anchor::CenterRight => bounds.center().y,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => bounds.bottom(),
+        };
+
+        Vec2::new(x, y)
+    }
+}
+
+/// How the lines of a [`Text`] should be aligned horizontally.
+///
+/// If the text has a [max width](Text::set_max_width), the lines will be aligned within
+/// that width. Otherwise, they will be aligned relative to the widest line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum TextAlignment {
+    /// Each line starts at the left edge.
+    #[default]
+    Left,
+
+    /// Each line is centered.
+    Center,
+
+    /// Each line ends at the right edge.
+    Right,
+
+    /// The spaces between words are stretched, so that each line fills the full width.
+    ///
+    /// The last line of each paragraph is left aligned.
+    Justify,
+}
+
+/// How a [`Text`] should be truncated when it exceeds its [max height](Text::set_max_height).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum TextOverflow {
+    /// Any lines that do not fit will be cut off.
+    #[default]
+    Clip,
+
+    /// Any lines that do not fit will be cut off, and an ellipsis (`...`) will be added
+    /// to the end of the last line. If there is a max width, characters will be removed
+    /// from the end of the last line to make space for the ellipsis.
+    Ellipsis,
+}
+
+/// The position of a single character within a laid out [`Text`].
+///
+/// All of the positions are in the same co-ordinate space as [`Text::get_bounds`] - that is,
+/// relative to the text's origin, without any [`DrawParams`] applied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct GlyphPosition {
+    /// The byte index of the character within the text's content.
+    pub index: usize,
+
+    /// The character itself.
+    pub character: char,
+
+    /// The space that the character occupies on its line.
+    ///
+    /// This spans the full height of the line, and is as wide as the character's
+    /// advance, which makes it suitable for positioning a caret or drawing a selection
+    /// highlight. Control characters (such as newlines) have a width of zero, and are
+    /// placed at the end of the line they appear on.
+    pub bounds: Rectangle,
+
+    /// The area that the character's glyph will be drawn to, if it has one.
+    ///
+    /// This will be `None` for characters that do not render anything, such as
+    /// whitespace and control characters.
+    pub quad: Option<Rectangle>,
+}
+
 /// A font with an associated size, cached on the GPU.
 ///
 /// # Performance
@@ -158,10 +276,15 @@ impl Font {
     ///
     /// Note that changing the filter mode of a font will affect all [`Text`] objects
     /// that use that font, including existing ones. This is due to the fact that
-    /// each font has a shared texture atlas.
+    /// each font has a shared texture atlas. If the font was created in a
+    /// [`FontAtlas`], this will also affect every other font in that atlas.
     pub fn set_filter_mode(&mut self, ctx: &mut Context, filter_mode: FilterMode) {
         self.data.borrow_mut().set_filter_mode(ctx, filter_mode);
     }
+
+    pub(crate) fn line_height(&self) -> f32 {
+        self.data.borrow().line_height()
+    }
 }
 
 impl Debug for Font {
@@ -170,6 +293,96 @@ impl Debug for Font {
     }
 }
 
+/// A texture atlas that can be shared between multiple fonts.
+///
+/// By default, each [`Font`] caches its glyphs in its own texture. This means that drawing
+/// [`Text`] with different fonts requires switching textures, which breaks up the
+/// batch and results in extra draw calls. Fonts that are created in the same `FontAtlas`
+/// pack their glyphs into the same texture, so text using any of them can be drawn
+/// in a single batch.
+///
+/// When the atlas runs out of space, it will be resized (which evicts all of the
+/// cached glyphs, causing them to be re-rendered the next time they are drawn). If a
+/// maximum size is set, the atlas will stop growing once it reaches that size, and
+/// will evict the existing glyphs to make space instead.
+///
+/// You can clone an atlas cheaply, as it is [reference-counted](https://doc.rust-lang.org/std/rc/struct.Rc.html)
+/// internally.
+///
+/// # Examples
+///
+/// The [`text_atlas`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/text_atlas.rs)
+/// example demonstrates how sharing an atlas reduces the number of draw calls.
+#[derive(Clone)]
+pub struct FontAtlas {
+    data: Rc<RefCell<GlyphAtlas>>,
+}
+
+impl FontAtlas {
+    /// Creates a new, empty atlas, which will grow as needed.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if
+    ///   the atlas's texture could not be created.
+    pub fn new(ctx: &mut Context) -> Result<FontAtlas> {
+        FontAtlas::create(ctx, None)
+    }
+
+    /// Creates a new, empty atlas, which will not grow beyond the given width and height.
+    ///
+    /// If a piece of text needs more space than this, even after the atlas has been
+    /// cleared, any glyphs that do not fit will not be drawn.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if
+    ///   the atlas's texture could not be created.
+    pub fn with_max_size(ctx: &mut Context, max_size: i32) -> Result<FontAtlas> {
+        FontAtlas::create(ctx, Some(max_size.max(1)))
+    }
+
+    fn create(ctx: &mut Context, max_size: Option<i32>) -> Result<FontAtlas> {
+        let atlas = GlyphAtlas::new(&mut ctx.device, ctx.graphics.default_filter_mode, max_size)?;
+
+        Ok(FontAtlas {
+            data: Rc::new(RefCell::new(atlas)),
+        })
+    }
+
+    /// Returns the size of the atlas's texture.
+    pub fn size(&self) -> (i32, i32) {
+        self.data.borrow().texture().size()
+    }
+
+    /// Evicts all of the glyphs from the atlas.
+    ///
+    /// Any text using fonts from this atlas will be re-rendered the next time it is drawn.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned if
+    ///   the atlas's texture could not be recreated.
+    pub fn clear(&self, ctx: &mut Context) -> Result {
+        self.data.borrow_mut().clear(&mut ctx.device)
+    }
+
+    pub(crate) fn create_font(&self, rasterizer: Box<dyn cache::Rasterizer>) -> Font {
+        Font {
+            data: Rc::new(RefCell::new(FontCache::with_atlas(
+                rasterizer,
+                Rc::clone(&self.data),
+            ))),
+        }
+    }
+}
+
+impl Debug for FontAtlas {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FontAtlas").finish()
+    }
+}
+
 /// A piece of text that can be rendered.
 ///
 /// # Performance
@@ -179,6 +392,13 @@ impl Debug for Font {
 /// the same from frame to frame, reusing the `Text` object will be much
 /// faster than recreating it.
 ///
+/// Changing the [color of a span](Text::set_span_color) does not require the text to be
+/// laid out again, and the whole text is still drawn as a single batch.
+///
+/// A [shadow](Text::set_shadow) reuses the text's cached geometry, and an
+/// [outline](Text::set_outline) is rasterized into the font's cache, so neither
+/// effect requires the text to be drawn multiple times.
+///
 /// # Examples
 ///
 /// The [`text`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/text.rs)
@@ -187,7 +407,10 @@ impl Debug for Font {
 pub struct Text {
     content: String,
     font: Font,
-    max_width: Option<f32>,
+    layout: LayoutOptions,
+    spans: Vec<(Range<usize>, Color)>,
+    shadow: Option<(Vec2<f32>, Color)>,
+    outline: Option<(f32, Color)>,
     geometry: Option<TextGeometry>,
 }
 
@@ -200,7 +423,10 @@ impl Text {
         Text {
             content: content.into(),
             font,
-            max_width: None,
+            layout: LayoutOptions::default(),
+            spans: Vec::new(),
+            shadow: None,
+            outline: None,
             geometry: None,
         }
     }
@@ -218,12 +444,21 @@ impl Text {
         Text {
             content: content.into(),
             font,
-            max_width: Some(max_width),
+            layout: LayoutOptions {
+                max_width: Some(max_width),
+                ..LayoutOptions::default()
+            },
+            spans: Vec::new(),
+            shadow: None,
+            outline: None,
             geometry: None,
         }
     }
 
     /// Draws the text to the screen (or to a canvas, if one is enabled).
+    ///
+    /// If the font's atlas has a [maximum size](FontAtlas::with_max_size) and the text
+    /// does not fit into it, the glyphs that do not fit will be skipped.
     pub fn draw<P>(&mut self, ctx: &mut Context, params: P)
     where
         P: Into<DrawParams>,
@@ -239,23 +474,49 @@ impl Text {
             .as_ref()
             .expect("geometry should have been generated");
 
-        graphics::set_texture(ctx, texture);
-        let (texture_width, texture_height) = texture.size();
+        graphics::set_texture(ctx, &texture);
+        let texture_size = texture.size();
+
+        if let Some((offset, color)) = self.shadow {
+            // If the text is outlined, the shadow should be cast by the outline too.
+            let quads = if self.outline.is_some() {
+                &geometry.outline_quads
+            } else {
+                &geometry.quads
+            };
+
+            let shadow_params = DrawParams {
+                color: params.color * color,
+                ..params.clone()
+            };
+
+            push_text_quads(ctx, quads, texture_size, offset, &shadow_params, None);
+        }
+
+        if let Some((_, color)) = self.outline {
+            let outline_params = DrawParams {
+                color: params.color * color,
+                ..params.clone()
+            };
 
-        for quad in &geometry.quads {
-            graphics::push_quad(
+            push_text_quads(
                 ctx,
-                quad.position.x,
-                quad.position.y,
-                quad.position.x + quad.region.width,
-                quad.position.y + quad.region.height,
-                quad.region.x / (texture_width as f32),
-                quad.region.y / (texture_height as f32),
-                quad.region.right() / (texture_width as f32),
-                quad.region.bottom() / (texture_height as f32),
-                &params,
+                &geometry.outline_quads,
+                texture_size,
+                Vec2::zero(),
+                &outline_params,
+                None,
             );
         }
+
+        push_text_quads(
+            ctx,
+            &geometry.quads,
+            texture_size,
+            Vec2::zero(),
+            &params,
+            Some(&self.spans),
+        );
     }
 
     /// Returns a reference to the content of the text.
@@ -265,6 +526,9 @@ impl Text {
 
     /// Sets the content of the text.
     ///
+    /// Any [span colors](Text::set_span_color) will be cleared, as they would no longer
+    /// line up with the new content.
+    ///
     /// Calling this function will cause a re-layout of the text the next time it
     /// is rendered.
     pub fn set_content<C>(&mut self, content: C)
@@ -272,9 +536,100 @@ impl Text {
         C: Into<String>,
     {
         self.geometry.take();
+        self.spans.clear();
         self.content = content.into();
     }
 
+    /// Sets the color of a span of the text.
+    ///
+    /// The range is made up of **byte** indices into the text's content, in the same way as
+    /// when slicing a `str` (and as used by [`GlyphPosition::index`]). Any character that
+    /// starts within the range will be affected. If spans overlap, the one that was set
+    /// most recently will take priority.
+    ///
+    /// The span's color is multiplied with the color passed via [`DrawParams`] when the text
+    /// is drawn, so you can still fade or tint the whole text as normal. Spans stay attached
+    /// to the same characters when the text is re-wrapped, and are cleared when
+    /// [`set_content`](Text::set_content) is called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tetra::graphics::text::{Font, Text};
+    /// # use tetra::graphics::Color;
+    /// # fn make(font: Font) -> Text {
+    /// let content = "Press A to jump";
+    /// let start = content.find('A').unwrap();
+    ///
+    /// let mut text = Text::new(content, font);
+    /// text.set_span_color(start..start + 1, Color::rgb(1.0, 0.9, 0.2));
+    /// # text
+    /// # }
+    /// ```
+    pub fn set_span_color(&mut self, range: Range<usize>, color: Color) {
+        self.spans.push((range, color));
+    }
+
+    /// Removes all of the span colors from the text.
+    pub fn clear_span_colors(&mut self) {
+        self.spans.clear();
+    }
+
+    /// Gets the offset and color of the text's shadow, if one is set.
+    pub fn shadow(&self) -> Option<(Vec2<f32>, Color)> {
+        self.shadow
+    }
+
+    /// Sets a drop shadow to be drawn behind the text.
+    ///
+    /// The offset is applied before any [`DrawParams`], so the shadow will be scaled and
+    /// rotated along with the text. The shadow's color is multiplied with the color passed
+    /// via `DrawParams` when the text is drawn, so it will fade out along with the text.
+    ///
+    /// If the text has an [outline](Text::set_outline), the shadow will be cast by the
+    /// outline rather than by the text itself.
+    ///
+    /// Calling this function will not cause a re-layout of the text.
+    pub fn set_shadow(&mut self, offset: Vec2<f32>, color: Color) {
+        self.shadow = Some((offset, color));
+    }
+
+    /// Removes the text's shadow.
+    pub fn clear_shadow(&mut self) {
+        self.shadow = None;
+    }
+
+    /// Gets the thickness and color of the text's outline, if one is set.
+    pub fn outline(&self) -> Option<(f32, Color)> {
+        self.outline
+    }
+
+    /// Sets an outline to be drawn around the text.
+    ///
+    /// The outline is rasterized into the font's texture cache (with the thickness rounded
+    /// to the nearest tenth of a pixel), so it is drawn as a single extra quad per character.
+    /// The outline's color is multiplied with the color passed via [`DrawParams`] when the
+    /// text is drawn, so it will fade out along with the text.
+    ///
+    /// The outline is not included in the text's [bounds](Text::get_bounds).
+    ///
+    /// Changing the thickness of the outline will cause a re-layout of the text the next
+    /// time it is rendered, but changing the color will not.
+    pub fn set_outline(&mut self, thickness: f32, color: Color) {
+        if self.outline.map(|(t, _)| t) != Some(thickness) {
+            self.geometry.take();
+        }
+
+        self.outline = Some((thickness, color));
+    }
+
+    /// Removes the text's outline.
+    pub fn clear_outline(&mut self) {
+        if self.outline.take().is_some() {
+            self.geometry.take();
+        }
+    }
+
     /// Gets the font of the text.
     pub fn font(&self) -> &Font {
         &self.font
@@ -295,7 +650,7 @@ impl Text {
     /// [`get_bounds`](Text::get_bounds) if you need to find the actual bounds
     /// of the text.
     pub fn max_width(&self) -> Option<f32> {
-        self.max_width
+        self.layout.max_width
     }
 
     /// Sets the maximum width of the text.
@@ -311,7 +666,62 @@ impl Text {
     /// is rendered.
     pub fn set_max_width(&mut self, max_width: Option<f32>) {
         self.geometry.take();
-        self.max_width = max_width;
+        self.layout.max_width = max_width;
+    }
+
+    /// Gets the maximum height of the text, if one is set.
+    pub fn max_height(&self) -> Option<f32> {
+        self.layout.max_height
+    }
+
+    /// Sets the maximum height of the text.
+    ///
+    /// If `Some` is passed, any lines that do not fit within this height will be
+    /// truncated, as specified by the [overflow mode](Text::set_overflow). At least
+    /// one line will always be displayed, even if it does not fit. If `None` is passed,
+    /// the text will not be truncated.
+    ///
+    /// Truncated characters are still included in [`glyph_positions`](Text::glyph_positions),
+    /// but they will have no quad, and will be placed at the end of the last line.
+    ///
+    /// Calling this function will cause a re-layout of the text the next time it
+    /// is rendered.
+    pub fn set_max_height(&mut self, max_height: Option<f32>) {
+        self.geometry.take();
+        self.layout.max_height = max_height;
+    }
+
+    /// Gets how the text will be truncated if it exceeds its max height.
+    pub fn overflow(&self) -> TextOverflow {
+        self.layout.overflow
+    }
+
+    /// Sets how the text will be truncated if it exceeds its [max height](Text::set_max_height).
+    ///
+    /// Calling this function will cause a re-layout of the text the next time it
+    /// is rendered.
+    pub fn set_overflow(&mut self, overflow: TextOverflow) {
+        self.geometry.take();
+        self.layout.overflow = overflow;
+    }
+
+    /// Gets the horizontal alignment of the text.
+    pub fn alignment(&self) -> TextAlignment {
+        self.layout.alignment
+    }
+
+    /// Sets the horizontal alignment of the text.
+    ///
+    /// If the text has a max width, the lines will be aligned within that width.
+    /// Otherwise, they will be aligned relative to the widest line. In both cases,
+    /// the text's origin stays at the top left of the box, so the
+    /// [bounds](Text::get_bounds) may not start at `(0.0, 0.0)`.
+    ///
+    /// Calling this function will cause a re-layout of the text the next time it
+    /// is rendered.
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        self.geometry.take();
+        self.layout.alignment = alignment;
     }
 
     /// Appends the given character to the end of the text.
@@ -345,9 +755,11 @@ impl Text {
 
     /// Get the outer bounds of the text when rendered to the screen.
     ///
-    /// If the text's layout needs calculating, this method will do so.
+    /// If the text's layout needs calculating, this method will do so, so the bounds can
+    /// be retrieved before the text is drawn for the first time.
     ///
     /// Note that this method will not take into account the positioning applied to the text via [`DrawParams`].
+    /// If you need to take rotation into account, use [`get_rotated_bounds`](Text::get_rotated_bounds).
     pub fn get_bounds(&mut self, ctx: &mut Context) -> Option<Rectangle> {
         self.update_geometry(ctx);
 
@@ -357,6 +769,96 @@ impl Text {
             .bounds
     }
 
+    /// Get the corners of the text's bounds, after rotating them around the given origin.
+    ///
+    /// The origin and the returned points are in the same co-ordinate space as
+    /// [`get_bounds`](Text::get_bounds), and the rotation is applied in the same way as
+    /// when drawing the text with [`DrawParams::rotation`]. The corners are returned in
+    /// clockwise order, starting from the corner that was originally the top left.
+    ///
+    /// If the text's layout needs calculating, this method will do so. If the text has
+    /// no bounds (e.g. because it is empty), `None` will be returned.
+    pub fn get_rotated_bounds(
+        &mut self,
+        ctx: &mut Context,
+        rotation: f32,
+        origin: Vec2<f32>,
+    ) -> Option<[Vec2<f32>; 4]> {
+        self.get_bounds(ctx)
+            .map(|bounds| rotate_bounds(bounds, rotation, origin))
+    }
+
+    /// Get the position of an anchor point within the bounds of the text.
+    ///
+    /// This can be passed to [`DrawParams::origin`] in order to position or rotate the
+    /// text relative to that point. For example, using [`Anchor::Center`] will allow you
+    /// to rotate the text around its visual center.
+    ///
+    /// If the text's layout needs calculating, this method will do so. If the text has
+    /// no bounds (e.g. because it is empty), this will return `(0.0, 0.0)`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use tetra::graphics::text::{Anchor, Text};
+    /// # use tetra::graphics::DrawParams;
+    /// # use tetra::math::Vec2;
+    /// # use tetra::Context;
+    /// # fn draw(ctx: &mut Context, text: &mut Text) {
+    /// let origin = text.get_anchor_point(ctx, Anchor::Center);
+    ///
+    /// text.draw(
+    ///     ctx,
+    ///     DrawParams::new()
+    ///         .position(Vec2::new(320.0, 240.0))
+    ///         .origin(origin)
+    ///         .rotation(0.5),
+    /// );
+    /// # }
+    /// ```
+    pub fn get_anchor_point(&mut self, ctx: &mut Context, anchor: Anchor) -> Vec2<f32> {
+        self.get_bounds(ctx)
+            .map(|bounds| anchor.point_in(bounds))
+            .unwrap_or_else(Vec2::zero)
+    }
+
+    /// Get the position of each character in the text.
+    ///
+    /// There will be one entry for every character in the text's content (including
+    /// whitespace and newlines), in the same order as they appear in the string. An empty
+    /// string will return an empty slice. To find where a character would be placed after
+    /// the end of the text (e.g. for a caret), use [`end_position`](Text::end_position).
+    ///
+    /// If the text's layout needs calculating, this method will do so, so the positions
+    /// can be retrieved before the text is drawn for the first time. The layout will be
+    /// recalculated if the content, font or any of the layout settings are changed.
+    pub fn glyph_positions(&mut self, ctx: &mut Context) -> &[GlyphPosition] {
+        self.update_geometry(ctx);
+
+        &self
+            .geometry
+            .as_ref()
+            .expect("geometry should have been generated")
+            .glyphs
+    }
+
+    /// Get the position of the top left corner of the space after the last character
+    /// in the text.
+    ///
+    /// This is where the next character would be placed if it was appended to the
+    /// content. If the text ends with a newline, this will be at the start of the next
+    /// line, and if the text is empty, it will be `(0.0, 0.0)`.
+    ///
+    /// If the text's layout needs calculating, this method will do so.
+    pub fn end_position(&mut self, ctx: &mut Context) -> Vec2<f32> {
+        self.update_geometry(ctx);
+
+        self.geometry
+            .as_ref()
+            .expect("geometry should have been generated")
+            .end
+    }
+
     fn update_geometry(&mut self, ctx: &mut Context) {
         let mut data = self.font.data.borrow_mut();
 
@@ -366,8 +868,158 @@ impl Text {
         };
 
         if needs_render {
-            let new_geometry = data.render(&mut ctx.device, &self.content, self.max_width);
+            let new_geometry = data.render(
+                &mut ctx.device,
+                &self.content,
+                &self.layout,
+                self.outline.map(|(thickness, _)| thickness),
+            );
             self.geometry = Some(new_geometry);
         }
     }
 }
+
+/// Pushes the quads for a piece of text to the batch.
+///
+/// If spans are provided, they will be used to color each quad - otherwise, the color
+/// from the params will be used for all of them.
+fn push_text_quads(
+    ctx: &mut Context,
+    quads: &[(usize, TextQuad)],
+    (texture_width, texture_height): (i32, i32),
+    offset: Vec2<f32>,
+    params: &DrawParams,
+    spans: Option<&[(Range<usize>, Color)]>,
+) {
+    let mut quad_params = params.clone();
+
+    for (index, quad) in quads {
+        if let Some(spans) = spans {
+            quad_params.color = match span_color(spans, *index) {
+                Some(color) => params.color * color,
+                None => params.color,
+            };
+        }
+
+        let position = quad.position + offset;
+
+        graphics::push_quad(
+            ctx,
+            position.x,
+            position.y,
+            position.x + quad.region.width,
+            position.y + quad.region.height,
+            quad.region.x / (texture_width as f32),
+            quad.region.y / (texture_height as f32),
+            quad.region.right() / (texture_width as f32),
+            quad.region.bottom() / (texture_height as f32),
+            &quad_params,
+        );
+    }
+}
+
+/// Finds the color of the most recently set span that covers the given byte index.
+fn span_color(spans: &[(Range<usize>, Color)], index: usize) -> Option<Color> {
+    spans
+        .iter()
+        .rev()
+        .find(|(range, _)| range.contains(&index))
+        .map(|(_, color)| *color)
+}
+
+fn rotate_bounds(bounds: Rectangle, rotation: f32, origin: Vec2<f32>) -> [Vec2<f32>; 4] {
+    // Using the same matrix as drawing ensures the rotation direction always matches.
+    let matrix = DrawParams::new()
+        .position(origin)
+        .origin(origin)
+        .rotation(rotation)
+        .to_matrix();
+
+    [
+        bounds.top_left(),
+        bounds.top_right(),
+        bounds.bottom_right(),
+        bounds.bottom_left(),
+    ]
+    .map(|corner| (matrix * Vec4::new(corner.x, corner.y, 0.0, 1.0)).xy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(expected: Vec2<f32>, actual: Vec2<f32>) {
+        assert!(
+            (expected - actual).magnitude() < 1e-4,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    fn rotate_manually(point: Vec2<f32>, rotation: f32, origin: Vec2<f32>) -> Vec2<f32> {
+        let (sin, cos) = rotation.sin_cos();
+        let offset = point - origin;
+
+        origin
+            + Vec2::new(
+                offset.x * cos - offset.y * sin,
+                offset.x * sin + offset.y * cos,
+            )
+    }
+
+    #[test]
+    fn rotated_bounds_match_manual_transform() {
+        let bounds = Rectangle::new(2.0, 4.0, 60.0, 20.0);
+
+        for rotation in [0.0, 0.5, std::f32::consts::FRAC_PI_2, -2.0] {
+            for origin in [Vec2::zero(), bounds.center(), Vec2::new(-10.0, 5.0)] {
+                let corners = rotate_bounds(bounds, rotation, origin);
+
+                let expected = [
+                    bounds.top_left(),
+                    bounds.top_right(),
+                    bounds.bottom_right(),
+                    bounds.bottom_left(),
+                ];
+
+                for (corner, unrotated) in corners.iter().zip(expected) {
+                    assert_near(rotate_manually(unrotated, rotation, origin), *corner);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotating_around_center_keeps_center() {
+        let bounds = Rectangle::new(0.0, 0.0, 40.0, 10.0);
+        let center = Anchor::Center.point_in(bounds);
+
+        let corners = rotate_bounds(bounds, 1.2, center);
+        let rotated_center = (corners[0] + corners[2]) / 2.0;
+
+        assert_near(center, rotated_center);
+    }
+
+    #[test]
+    fn later_spans_take_priority() {
+        let spans = vec![(0..10, Color::RED), (4..6, Color::BLUE)];
+
+        assert_eq!(span_color(&spans, 0), Some(Color::RED));
+        assert_eq!(span_color(&spans, 4), Some(Color::BLUE));
+        assert_eq!(span_color(&spans, 6), Some(Color::RED));
+        assert_eq!(span_color(&spans, 10), None);
+        assert_eq!(span_color(&[], 0), None);
+    }
+
+    #[test]
+    fn anchor_points() {
+        let bounds = Rectangle::new(10.0, 20.0, 40.0, 10.0);
+
+        assert_eq!(Vec2::new(10.0, 20.0), Anchor::TopLeft.point_in(bounds));
+        assert_eq!(Vec2::new(30.0, 25.0), Anchor::Center.point_in(bounds));
+        assert_eq!(Vec2::new(50.0, 25.0), Anchor::CenterRight.point_in(bounds));
+        assert_eq!(Vec2::new(30.0, 30.0), Anchor::BottomCenter.point_in(bounds));
+        assert_eq!(Vec2::new(50.0, 30.0), Anchor::BottomRight.point_in(bounds));
+    }
+}
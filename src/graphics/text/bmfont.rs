@@ -12,7 +12,7 @@ use crate::{fs, Context};
 use crate::{Result, TetraError};
 
 use super::cache::FontCache;
-use super::Font;
+use super::{Font, FontAtlas};
 
 struct BmFontGlyph {
     x: u32,
@@ -62,6 +62,7 @@ pub struct BmFontBuilder {
     font: String,
     image_dir: Option<PathBuf>,
     pages: HashMap<u32, ImageData>,
+    fallback: Option<char>,
 }
 
 impl BmFontBuilder {
@@ -87,6 +88,7 @@ impl BmFontBuilder {
             font,
             image_dir: Some(image_dir),
             pages: HashMap::new(),
+            fallback: None,
         })
     }
 
@@ -102,6 +104,7 @@ impl BmFontBuilder {
             font: data.into(),
             image_dir: None,
             pages: HashMap::new(),
+            fallback: None,
         }
     }
 
@@ -189,6 +192,16 @@ impl BmFontBuilder {
         self
     }
 
+    /// Sets the character that should be displayed in place of any characters that
+    /// are missing from the font (e.g. `'?'`).
+    ///
+    /// By default, no fallback is used, and missing characters will not be displayed.
+    pub fn with_fallback_char(mut self, fallback: char) -> BmFontBuilder {
+        self.fallback = Some(fallback);
+
+        self
+    }
+
     /// Builds the font.
     ///
     /// Any pages that have not had their images manually set will be loaded from the path
@@ -199,19 +212,14 @@ impl BmFontBuilder {
     /// * [`TetraError::FailedToLoadAsset`] will be returned if a file could not be loaded.
     /// * [`TetraError::InvalidTexture`] will be returned if some of the image data was invalid.
     /// * [`TetraError::InvalidFont`] will be returned if the font definition was invalid,
-    ///   or if there was no path specified for one of the image files.
+    ///   if there was no path specified for one of the image files, if a character refers
+    ///   to a page that does not exist, or if the fallback character is not in the font.
     /// * [`TetraError::PlatformError`] will be returned if the GPU cache for the font
     ///   could not be created.
     pub fn build(self, ctx: &mut Context) -> Result<Font> {
-        let rasterizer: Box<dyn Rasterizer> = Box::new(BmFontRasterizer::new(
-            &self.font,
-            self.image_dir,
-            self.pages,
-        )?);
-
         let cache = FontCache::new(
             &mut ctx.device,
-            rasterizer,
+            self.rasterizer()?,
             ctx.graphics.default_filter_mode,
         )?;
 
@@ -219,6 +227,33 @@ impl BmFontBuilder {
             data: Rc::new(RefCell::new(cache)),
         })
     }
+
+    /// Builds the font, storing its glyphs in a shared [`FontAtlas`].
+    ///
+    /// Text drawn using fonts from the same atlas can be batched together.
+    ///
+    /// Any pages that have not had their images manually set will be loaded from the path
+    /// specified by [`with_image_dir`](Self::with_image_dir).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if a file could not be loaded.
+    /// * [`TetraError::InvalidTexture`] will be returned if some of the image data was invalid.
+    /// * [`TetraError::InvalidFont`] will be returned if the font definition was invalid,
+    ///   if there was no path specified for one of the image files, if a character refers
+    ///   to a page that does not exist, or if the fallback character is not in the font.
+    pub fn build_in_atlas(self, atlas: &FontAtlas) -> Result<Font> {
+        Ok(atlas.create_font(self.rasterizer()?))
+    }
+
+    fn rasterizer(self) -> Result<Box<dyn Rasterizer>> {
+        Ok(Box::new(BmFontRasterizer::new(
+            &self.font,
+            self.image_dir,
+            self.pages,
+            self.fallback,
+        )?))
+    }
 }
 
 pub struct BmFontRasterizer {
@@ -228,6 +263,7 @@ pub struct BmFontRasterizer {
     pages: HashMap<u32, ImageData>,
     glyphs: HashMap<u32, BmFontGlyph>,
     kerning: HashMap<(u32, u32), i32>,
+    fallback: Option<u32>,
 }
 
 impl BmFontRasterizer {
@@ -235,6 +271,7 @@ impl BmFontRasterizer {
         font: &str,
         image_path: Option<PathBuf>,
         mut pages: HashMap<u32, ImageData>,
+        fallback: Option<char>,
     ) -> Result<BmFontRasterizer> {
         let mut line_height = None;
         let mut base = None;
@@ -302,19 +339,45 @@ impl BmFontRasterizer {
             }
         }
 
+        if glyphs
+            .values()
+            .any(|glyph| !pages.contains_key(&glyph.page))
+        {
+            return Err(TetraError::InvalidFont);
+        }
+
+        let fallback = fallback.map(|ch| ch as u32);
+
+        if matches!(fallback, Some(id) if !glyphs.contains_key(&id)) {
+            return Err(TetraError::InvalidFont);
+        }
+
         Ok(BmFontRasterizer {
             line_height: line_height.ok_or(TetraError::InvalidFont)?,
             base: base.ok_or(TetraError::InvalidFont)?,
             pages,
             glyphs,
             kerning,
+            fallback,
         })
     }
+
+    /// Returns the ID of the glyph that should be used to display the given character,
+    /// taking the fallback character into account.
+    fn glyph_id(&self, ch: char) -> Option<u32> {
+        let id = ch as u32;
+
+        if self.glyphs.contains_key(&id) {
+            Some(id)
+        } else {
+            self.fallback
+        }
+    }
 }
 
 impl Rasterizer for BmFontRasterizer {
     fn rasterize(&self, glyph: char, _: Vec2<f32>) -> Option<RasterizedGlyph> {
-        if let Some(bmglyph) = self.glyphs.get(&(glyph as u32)) {
+        if let Some(bmglyph) = self.glyph_id(glyph).and_then(|id| self.glyphs.get(&id)) {
             let page = self.pages.get(&bmglyph.page)?;
 
             let region = page.region(Rectangle::new(
@@ -342,8 +405,8 @@ impl Rasterizer for BmFontRasterizer {
     }
 
     fn advance(&self, glyph: char) -> f32 {
-        self.glyphs
-            .get(&(glyph as u32))
+        self.glyph_id(glyph)
+            .and_then(|id| self.glyphs.get(&id))
             .map(|bmchar| bmchar.x_advance as f32)
             .unwrap_or(0.0)
     }
@@ -357,10 +420,12 @@ impl Rasterizer for BmFontRasterizer {
     }
 
     fn kerning(&self, previous: char, current: char) -> f32 {
-        self.kerning
-            .get(&(previous as u32, current as u32))
-            .copied()
-            .unwrap_or(0) as f32
+        match (self.glyph_id(previous), self.glyph_id(current)) {
+            (Some(previous), Some(current)) => {
+                self.kerning.get(&(previous, current)).copied().unwrap_or(0) as f32
+            }
+            _ => 0.0,
+        }
     }
 }
 
@@ -494,4 +559,45 @@ mod tests {
 
         parse_attributes(rest).unwrap();
     }
+
+    const TEST_FONT: &str = r#"common lineHeight=10 base=8
+page id=0 file="test.png"
+char id=63 x=0 y=0 width=2 height=2 xoffset=0 yoffset=0 xadvance=5 page=0
+char id=65 x=2 y=0 width=2 height=2 xoffset=0 yoffset=0 xadvance=6 page=0
+char id=86 x=0 y=2 width=2 height=2 xoffset=0 yoffset=0 xadvance=7 page=0
+kerning first=65 second=86 amount=-2
+kerning first=63 second=65 amount=-1"#;
+
+    fn test_pages() -> HashMap<u32, ImageData> {
+        let mut pages = HashMap::new();
+        pages.insert(
+            0,
+            ImageData::from_data(4, 4, TextureFormat::Rgba8, vec![0; 4 * 4 * 4]).unwrap(),
+        );
+        pages
+    }
+
+    #[test]
+    fn missing_glyphs_use_fallback() {
+        let font = BmFontRasterizer::new(TEST_FONT, None, test_pages(), None).unwrap();
+
+        assert_eq!(font.advance('Z'), 0.0);
+        assert!(font.rasterize('Z', Vec2::zero()).is_none());
+        assert_eq!(font.kerning('A', 'V'), -2.0);
+
+        let font = BmFontRasterizer::new(TEST_FONT, None, test_pages(), Some('?')).unwrap();
+
+        assert_eq!(font.advance('Z'), 5.0);
+        assert!(font.rasterize('Z', Vec2::zero()).is_some());
+        assert_eq!(font.kerning('Z', 'A'), -1.0);
+    }
+
+    #[test]
+    fn invalid_fallback_or_page_is_rejected() {
+        assert!(BmFontRasterizer::new(TEST_FONT, None, test_pages(), Some('!')).is_err());
+
+        // Page 1 isn't defined, so the 'V' glyph can't be displayed.
+        let font = TEST_FONT.replace("xadvance=7 page=0", "xadvance=7 page=1");
+        assert!(BmFontRasterizer::new(&font, None, test_pages(), None).is_err());
+    }
 }
@@ -1,8 +1,13 @@
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+
 use hashbrown::hash_map::Entry;
 use hashbrown::HashMap;
 use xi_unicode::LineBreakIterator;
 
 use crate::graphics::text::packer::ShelfPacker;
+use crate::graphics::text::{FontTextureStyle, GlyphPosition, TextAlignment, TextOverflow};
 use crate::graphics::{FilterMode, Rectangle, Texture};
 use crate::math::Vec2;
 use crate::platform::GraphicsDevice;
@@ -60,6 +65,10 @@ struct CacheKey {
 
     /// The glyph's vertical subpixel offset (stored as a rounded integer).
     subpixel_y: u32,
+
+    /// The thickness of the glyph's outline, in tenths of a pixel, or zero if this
+    /// is the glyph itself rather than its outline.
+    outline: u32,
 }
 
 /// Implemented for types that can rasterize characters, and provide information
@@ -82,72 +91,275 @@ pub(crate) trait Rasterizer {
 
     /// The amount of kerning that should be applied between the given glyphs.
     fn kerning(&self, previous: char, current: char) -> f32;
+
+    /// The style of texture data that is generated by the rasterizer.
+    ///
+    /// This is used to generate outlines in a matching style.
+    fn texture_style(&self) -> FontTextureStyle {
+        FontTextureStyle::Normal
+    }
 }
 
 /// The geometry that can be used to render a piece of text.
 #[derive(Debug, Clone)]
 pub(crate) struct TextGeometry {
-    pub quads: Vec<TextQuad>,
+    /// The quads that make up the text, each paired with the byte index of the character
+    /// it was generated from.
+    pub quads: Vec<(usize, TextQuad)>,
+
+    /// The quads that make up the outline of the text, if one was requested.
+    pub outline_quads: Vec<(usize, TextQuad)>,
+    pub glyphs: Vec<GlyphPosition>,
+    pub end: Vec2<f32>,
     pub bounds: Option<Rectangle>,
     pub resize_count: usize,
 }
 
+/// The settings that control how a piece of text is laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct LayoutOptions {
+    pub max_width: Option<f32>,
+    pub max_height: Option<f32>,
+    pub alignment: TextAlignment,
+    pub overflow: TextOverflow,
+}
+
+/// The position of each character in a piece of text, before any glyphs are rasterized.
+struct TextLayout {
+    glyphs: Vec<GlyphPosition>,
+
+    /// The number of glyphs that should be rendered. Any glyphs after this have been
+    /// cut off by the max height.
+    visible_glyphs: usize,
+
+    lines: Vec<Line>,
+
+    /// The ellipsis that was added to the end of the text, if it was truncated.
+    ellipsis: Option<Ellipsis>,
+
+    /// The position that the next character would be placed at, if one was added.
+    end: Vec2<f32>,
+}
+
+/// A single line within a `TextLayout`.
+struct Line {
+    /// The indices of the glyphs that are on the line.
+    glyphs: Range<usize>,
+
+    /// The position of the top of the line.
+    y: f32,
+
+    /// Whether the line was ended by a newline (or the end of the text), rather than
+    /// by word wrapping.
+    hard_break: bool,
+}
+
+/// An ellipsis that has been added to the end of a truncated `TextLayout`.
+struct Ellipsis {
+    /// The byte index of the first character that was cut off.
+    index: usize,
+
+    /// The position of each dot, in the same co-ordinate space as the glyph bounds.
+    dots: Vec<Vec2<f32>>,
+
+    /// The position of the right edge of the ellipsis.
+    end_x: f32,
+}
+
+/// A texture atlas that glyphs can be packed into.
+///
+/// This may be owned by a single `FontCache`, or shared between several of them, so that
+/// text rendered with different fonts can be drawn in a single batch.
+pub(crate) struct GlyphAtlas {
+    packer: ShelfPacker,
+    max_size: Option<i32>,
+    resize_count: usize,
+}
+
+impl GlyphAtlas {
+    /// Creates a new atlas.
+    ///
+    /// If a maximum size is given, the atlas will not grow beyond it - instead, the
+    /// existing glyphs will be evicted to make space.
+    pub fn new(
+        device: &mut GraphicsDevice,
+        filter_mode: FilterMode,
+        max_size: Option<i32>,
+    ) -> Result<GlyphAtlas> {
+        let initial_size = max_size.map_or(128, |max| max.clamp(1, 128));
+
+        Ok(GlyphAtlas {
+            packer: ShelfPacker::new(device, initial_size, initial_size, filter_mode)?,
+            max_size,
+            resize_count: 0,
+        })
+    }
+
+    /// Returns the current texture.
+    pub fn texture(&self) -> &Texture {
+        self.packer.texture()
+    }
+
+    /// Returns the number of times that the atlas has been resized or cleared.
+    ///
+    /// Any glyphs that were cached before this number changed are no longer valid.
+    pub fn resize_count(&self) -> usize {
+        self.resize_count
+    }
+
+    pub fn filter_mode(&self) -> FilterMode {
+        self.packer.filter_mode()
+    }
+
+    pub fn set_filter_mode(&mut self, ctx: &mut Context, filter_mode: FilterMode) {
+        self.packer.set_filter_mode(ctx, filter_mode);
+    }
+
+    /// Makes space in the atlas, by doubling its size (or by evicting every glyph,
+    /// if it has reached its maximum size).
+    ///
+    /// Either way, a new texture is created, so that any quads which have already been
+    /// batched with the old texture will still render correctly.
+    pub fn grow(&mut self, device: &mut GraphicsDevice) -> Result {
+        let (width, height) = self.packer.texture().size();
+        let (new_width, new_height) = next_atlas_size(width, height, self.max_size);
+
+        self.packer.resize(device, new_width, new_height)?;
+        self.resize_count += 1;
+
+        Ok(())
+    }
+
+    /// Evicts every glyph from the atlas.
+    pub fn clear(&mut self, device: &mut GraphicsDevice) -> Result {
+        let (width, height) = self.packer.texture().size();
+
+        self.packer.resize(device, width, height)?;
+        self.resize_count += 1;
+
+        Ok(())
+    }
+
+    /// Returns whether the atlas can grow any further.
+    fn is_at_max_size(&self) -> bool {
+        let (width, height) = self.packer.texture().size();
+        next_atlas_size(width, height, self.max_size) == (width, height)
+    }
+}
+
+/// Calculates the size of an atlas after it has grown.
+fn next_atlas_size(width: i32, height: i32, max_size: Option<i32>) -> (i32, i32) {
+    match max_size {
+        Some(max) => ((width * 2).min(max), (height * 2).min(max)),
+        None => (width * 2, height * 2),
+    }
+}
+
 /// Renders text using a generated texture atlas.
 pub(crate) struct FontCache {
     rasterizer: Box<dyn Rasterizer>,
-    packer: ShelfPacker,
+    atlas: Rc<RefCell<GlyphAtlas>>,
     glyphs: HashMap<CacheKey, Option<TextQuad>>,
-    resize_count: usize,
+
+    /// The atlas's resize count at the time that the glyphs were cached.
+    glyphs_resize_count: usize,
 }
 
 impl FontCache {
-    /// Creates a new cache, using the given rasterizer.
+    /// Creates a new cache with its own texture atlas, using the given rasterizer.
     pub fn new(
         device: &mut GraphicsDevice,
         rasterizer: Box<dyn Rasterizer>,
         filter_mode: FilterMode,
     ) -> Result<FontCache> {
-        Ok(FontCache {
+        let atlas = GlyphAtlas::new(device, filter_mode, None)?;
+
+        Ok(FontCache::with_atlas(
+            rasterizer,
+            Rc::new(RefCell::new(atlas)),
+        ))
+    }
+
+    /// Creates a new cache which stores its glyphs in the given texture atlas, using
+    /// the given rasterizer.
+    pub fn with_atlas(
+        rasterizer: Box<dyn Rasterizer>,
+        atlas: Rc<RefCell<GlyphAtlas>>,
+    ) -> FontCache {
+        let glyphs_resize_count = atlas.borrow().resize_count();
+
+        FontCache {
             rasterizer,
-            packer: ShelfPacker::new(device, 128, 128, filter_mode)?,
+            atlas,
             glyphs: HashMap::new(),
-            resize_count: 0,
-        })
+            glyphs_resize_count,
+        }
     }
 
     /// Returns the current texture atlas.
-    pub fn texture(&self) -> &Texture {
-        self.packer.texture()
+    pub fn texture(&self) -> Texture {
+        self.atlas.borrow().texture().clone()
     }
 
-    /// Returns the number of times that the cache has been resized.
+    /// Returns the number of times that the cache's texture atlas has been resized.
     ///
     /// This can be compared against the `resize_count` of the `TextGeometry` to determine
     /// if that struct's data is stale.
     pub fn resize_count(&self) -> usize {
-        self.resize_count
+        self.atlas.borrow().resize_count()
     }
 
     pub fn filter_mode(&self) -> FilterMode {
-        self.packer.filter_mode()
+        self.atlas.borrow().filter_mode()
     }
 
     pub fn set_filter_mode(&mut self, ctx: &mut Context, filter_mode: FilterMode) {
-        self.packer.set_filter_mode(ctx, filter_mode);
+        self.atlas.borrow_mut().set_filter_mode(ctx, filter_mode);
+    }
+
+    /// Returns the distance between the top of one line and the next, as used by the
+    /// layout.
+    pub fn line_height(&self) -> f32 {
+        self.rasterizer.line_height().round()
     }
 
     /// Generates the geometry for the given string, resizing the texture atlas if needed.
+    ///
+    /// If an outline thickness is given, the outline of each glyph will also be
+    /// rasterized into the cache.
+    ///
+    /// If the text does not fit into the texture atlas, even after it has been grown
+    /// to its maximum size and cleared, any glyphs that do not fit will be skipped.
     pub fn render(
         &mut self,
         device: &mut GraphicsDevice,
         input: &str,
-        max_width: Option<f32>,
+        options: &LayoutOptions,
+        outline: Option<f32>,
     ) -> TextGeometry {
+        let mut cleared = false;
+
         loop {
-            match self.try_render(device, input, max_width) {
+            // If another font has resized the shared atlas, our cached glyphs are gone.
+            let atlas_resize_count = self.resize_count();
+
+            if self.glyphs_resize_count != atlas_resize_count {
+                self.glyphs.clear();
+                self.glyphs_resize_count = atlas_resize_count;
+            }
+
+            match self.try_render(device, input, options, outline, cleared) {
                 Ok(new_geometry) => return new_geometry,
                 Err(CacheError::OutOfSpace) => {
-                    self.resize(device).expect("Failed to resize font texture");
+                    let mut atlas = self.atlas.borrow_mut();
+
+                    // If we've already tried again with an empty atlas, the next
+                    // attempt will skip whatever doesn't fit.
+                    if atlas.is_at_max_size() {
+                        cleared = true;
+                    }
+
+                    atlas.grow(device).expect("Failed to resize font texture");
                 }
             }
         }
@@ -155,104 +367,112 @@ impl FontCache {
 
     /// Generates the geometry for the given string, returning an error if the texture atlas
     /// is out of space.
+    ///
+    /// If `skip_overflow` is true, glyphs that do not fit into the atlas will be left out
+    /// of the geometry instead.
     fn try_render(
         &mut self,
         device: &mut GraphicsDevice,
         input: &str,
-        max_width: Option<f32>,
+        options: &LayoutOptions,
+        outline: Option<f32>,
+        skip_overflow: bool,
     ) -> std::result::Result<TextGeometry, CacheError> {
-        let line_height = self.rasterizer.line_height().round();
+        let ascent = self.rasterizer.ascent().round();
 
-        let mut quads = Vec::new();
+        let TextLayout {
+            mut glyphs,
+            visible_glyphs,
+            ellipsis,
+            end,
+            ..
+        } = layout(&*self.rasterizer, input, options);
 
-        let mut cursor = Vec2::new(0.0, self.rasterizer.ascent().round());
-        let mut last_glyph: Option<char> = None;
+        let outline = outline.map(outline_key).filter(|&key| key > 0);
+
+        let mut quads = Vec::new();
+        let mut outline_quads = Vec::new();
         let mut text_bounds: Option<Rectangle> = None;
-        let mut words_on_line = 0;
-
-        for (word, _) in UnicodeLineBreaks::new(input) {
-            if let Some(max_width) = max_width {
-                // We only allow wrapping to take place after the first word on each line,
-                // to avoid extra line breaks appearing when a word is too long to fit on
-                // a single line.
-                if words_on_line > 0 && cursor.x + self.measure_word(word) > max_width {
-                    cursor.x = 0.0;
-                    cursor.y += line_height;
-                    last_glyph = None;
-                    words_on_line = 0;
-                }
+
+        // The dots of the ellipsis don't have an entry in the glyph list, so they're
+        // attributed to the first character that was cut off.
+        let glyph_chars = glyphs[..visible_glyphs].iter().enumerate().map(|(i, g)| {
+            (
+                Some(i),
+                g.index,
+                g.character,
+                Vec2::new(g.bounds.x, g.bounds.y),
+            )
+        });
+
+        let ellipsis_chars = ellipsis.iter().flat_map(|e| {
+            e.dots
+                .iter()
+                .map(move |&position| (None, e.index, '.', position))
+        });
+
+        let chars: Vec<_> = glyph_chars.chain(ellipsis_chars).collect();
+
+        for (glyph_index, index, ch, position) in chars {
+            if ch.is_control() {
+                continue;
             }
 
-            words_on_line += 1;
+            let cursor = Vec2::new(position.x, position.y + ascent);
 
-            for ch in word.chars() {
-                if ch.is_control() {
-                    if ch == '\n' {
-                        cursor.x = 0.0;
-                        cursor.y += line_height;
-                        last_glyph = None;
-                        words_on_line = 0;
-                    }
+            if let Some(outline) = outline {
+                let outline_quad = match self.rasterize_char(device, ch, cursor, outline) {
+                    Err(CacheError::OutOfSpace) if skip_overflow => None,
+                    result => result?,
+                };
 
-                    continue;
+                if let Some(quad) = outline_quad {
+                    outline_quads.push((index, quad));
                 }
+            }
 
-                if let Some(last_glyph) = last_glyph {
-                    cursor.x += self.rasterizer.kerning(last_glyph, ch);
-                }
+            let quad = match self.rasterize_char(device, ch, cursor, 0) {
+                Err(CacheError::OutOfSpace) if skip_overflow => None,
+                result => result?,
+            };
 
-                if let Some(quad) = self.rasterize_char(device, ch, cursor)? {
-                    // Expand the cached bounds of the text geometry:
-                    match &mut text_bounds {
-                        Some(existing) => *existing = quad.bounds().combine(existing),
-                        None => {
-                            text_bounds.replace(quad.bounds());
-                        }
+            if let Some(quad) = quad {
+                // Expand the cached bounds of the text geometry:
+                match &mut text_bounds {
+                    Some(existing) => *existing = quad.bounds().combine(existing),
+                    None => {
+                        text_bounds.replace(quad.bounds());
                     }
-
-                    quads.push(quad);
                 }
 
-                cursor.x += self.rasterizer.advance(ch);
+                if let Some(glyph_index) = glyph_index {
+                    glyphs[glyph_index].quad = Some(quad.bounds());
+                }
 
-                last_glyph = Some(ch);
+                quads.push((index, quad));
             }
         }
 
         Ok(TextGeometry {
             quads,
-            resize_count: self.resize_count,
+            outline_quads,
+            glyphs,
+            end,
+            resize_count: self.glyphs_resize_count,
             bounds: text_bounds,
         })
     }
 
-    /// Measures the width of a word, not including any trailing whitespace.
-    ///
-    /// This is mainly used to determine if a word needs to break onto a
-    /// new line.
-    fn measure_word(&self, word: &str) -> f32 {
-        let mut last_glyph = None;
-        let mut word_width = 0.0;
-
-        for ch in word.trim_end().chars() {
-            word_width += self.rasterizer.advance(ch);
-
-            if let Some(last) = last_glyph {
-                word_width += self.rasterizer.kerning(last, ch);
-            }
-
-            last_glyph = Some(ch);
-        }
-
-        word_width
-    }
-
     /// Rasterizes a character with a given position, or pull it from the texture cache.
+    ///
+    /// If `outline` is non-zero, the character's outline (with the thickness given in
+    /// tenths of a pixel) will be rasterized instead.
     fn rasterize_char(
         &mut self,
         device: &mut GraphicsDevice,
         ch: char,
         position: Vec2<f32>,
+        outline: u32,
     ) -> std::result::Result<Option<TextQuad>, CacheError> {
         // This is a bit of a hack to allow us to hash the subpixel offset:
         //
@@ -273,17 +493,30 @@ impl FontCache {
             glyph: ch,
             subpixel_x,
             subpixel_y,
+            outline,
         };
 
         let cached_quad = match self.glyphs.entry(cache_key) {
             Entry::Occupied(e) => e.into_mut(),
             Entry::Vacant(e) => {
-                let outline = match self.rasterizer.rasterize(ch, position) {
-                    Some(r) => Some(add_glyph_to_texture(device, &mut self.packer, &r)?),
+                let glyph = self.rasterizer.rasterize(ch, position).map(|r| {
+                    if outline > 0 {
+                        dilate_glyph(&r, outline as f32 / 10.0, self.rasterizer.texture_style())
+                    } else {
+                        r
+                    }
+                });
+
+                let quad = match glyph {
+                    Some(r) => Some(add_glyph_to_texture(
+                        device,
+                        &mut self.atlas.borrow_mut().packer,
+                        &r,
+                    )?),
                     None => None,
                 };
 
-                e.insert(outline)
+                e.insert(quad)
             }
         };
 
@@ -297,20 +530,409 @@ impl FontCache {
             Ok(None)
         }
     }
+}
 
-    /// Resizes the texture atlas, clearing any cached data.
-    fn resize(&mut self, device: &mut GraphicsDevice) -> Result {
-        let (texture_width, texture_height) = self.packer.texture().size();
+/// Calculates where each character in the given string should be placed, based on
+/// the given options.
+fn layout(rasterizer: &dyn Rasterizer, input: &str, options: &LayoutOptions) -> TextLayout {
+    let mut layout = break_lines(rasterizer, input, options.max_width);
 
-        let new_width = texture_width * 2;
-        let new_height = texture_height * 2;
+    if let Some(max_height) = options.max_height {
+        truncate_lines(&mut layout, rasterizer, max_height, options);
+    }
 
-        self.packer.resize(device, new_width, new_height)?;
-        self.glyphs.clear();
+    align_lines(&mut layout, options);
 
-        self.resize_count += 1;
+    layout
+}
 
-        Ok(())
+/// Places each character in the given string on a line, wrapping words onto new lines
+/// if they would exceed the `max_width`.
+fn break_lines(rasterizer: &dyn Rasterizer, input: &str, max_width: Option<f32>) -> TextLayout {
+    let line_height = rasterizer.line_height().round();
+    let ascent = rasterizer.ascent().round();
+
+    let mut glyphs = Vec::new();
+    let mut lines = Vec::new();
+
+    let mut cursor = Vec2::new(0.0, ascent);
+    let mut last_glyph: Option<char> = None;
+    let mut words_on_line = 0;
+    let mut word_start = 0;
+    let mut line_start = 0;
+
+    for (word, _) in UnicodeLineBreaks::new(input) {
+        if let Some(max_width) = max_width {
+            // We only allow wrapping to take place after the first word on each line,
+            // to avoid extra line breaks appearing when a word is too long to fit on
+            // a single line.
+            if words_on_line > 0 && cursor.x + measure_word(rasterizer, word) > max_width {
+                lines.push(Line {
+                    glyphs: line_start..glyphs.len(),
+                    y: cursor.y - ascent,
+                    hard_break: false,
+                });
+
+                line_start = glyphs.len();
+
+                cursor.x = 0.0;
+                cursor.y += line_height;
+                last_glyph = None;
+                words_on_line = 0;
+            }
+        }
+
+        words_on_line += 1;
+
+        for (offset, ch) in word.char_indices() {
+            let index = word_start + offset;
+
+            if ch.is_control() {
+                // Control characters don't take up any space, but they still get an
+                // entry, so that every character in the input can be located.
+                glyphs.push(GlyphPosition {
+                    index,
+                    character: ch,
+                    bounds: Rectangle::new(cursor.x, cursor.y - ascent, 0.0, line_height),
+                    quad: None,
+                });
+
+                if ch == '\n' {
+                    lines.push(Line {
+                        glyphs: line_start..glyphs.len(),
+                        y: cursor.y - ascent,
+                        hard_break: true,
+                    });
+
+                    line_start = glyphs.len();
+
+                    cursor.x = 0.0;
+                    cursor.y += line_height;
+                    last_glyph = None;
+                    words_on_line = 0;
+                }
+
+                continue;
+            }
+
+            if let Some(last_glyph) = last_glyph {
+                cursor.x += rasterizer.kerning(last_glyph, ch);
+            }
+
+            let advance = rasterizer.advance(ch);
+
+            glyphs.push(GlyphPosition {
+                index,
+                character: ch,
+                bounds: Rectangle::new(cursor.x, cursor.y - ascent, advance, line_height),
+                quad: None,
+            });
+
+            cursor.x += advance;
+
+            last_glyph = Some(ch);
+        }
+
+        word_start += word.len();
+    }
+
+    lines.push(Line {
+        glyphs: line_start..glyphs.len(),
+        y: cursor.y - ascent,
+        hard_break: true,
+    });
+
+    TextLayout {
+        visible_glyphs: glyphs.len(),
+        glyphs,
+        lines,
+        ellipsis: None,
+        end: Vec2::new(cursor.x, cursor.y - ascent),
+    }
+}
+
+/// Removes any lines that do not fit within the `max_height`, adding an ellipsis to
+/// the end of the last remaining line if requested.
+///
+/// At least one line will always be kept.
+fn truncate_lines(
+    layout: &mut TextLayout,
+    rasterizer: &dyn Rasterizer,
+    max_height: f32,
+    options: &LayoutOptions,
+) {
+    let line_height = rasterizer.line_height().round();
+    let max_lines = ((max_height / line_height).floor() as usize).max(1);
+
+    if layout.lines.len() <= max_lines {
+        return;
+    }
+
+    layout.lines.truncate(max_lines);
+
+    let last_line = layout
+        .lines
+        .last_mut()
+        .expect("at least one line should be kept");
+
+    // The last line has been cut short, so it shouldn't be justified.
+    last_line.hard_break = true;
+
+    let has_hidden_content = layout.glyphs[last_line.glyphs.end..]
+        .iter()
+        .any(|g| !is_blank(g.character));
+
+    let mut visible = last_line.glyphs.end;
+
+    let mut end_x = if visible > last_line.glyphs.start {
+        layout.glyphs[visible - 1].bounds.right()
+    } else {
+        0.0
+    };
+
+    if options.overflow == TextOverflow::Ellipsis && has_hidden_content {
+        let dot_advance = rasterizer.advance('.') + rasterizer.kerning('.', '.');
+        let ellipsis_width = dot_advance * 2.0 + rasterizer.advance('.');
+
+        // Characters are removed from the end of the line until the ellipsis fits.
+        let content_end = loop {
+            match last_content_glyph(&layout.glyphs, last_line.glyphs.start..visible) {
+                Some(i) => {
+                    let content_end = layout.glyphs[i].bounds.right();
+
+                    let fits = match options.max_width {
+                        Some(max_width) => content_end + ellipsis_width <= max_width,
+                        None => true,
+                    };
+
+                    if fits {
+                        visible = i + 1;
+                        break content_end;
+                    }
+
+                    visible = i;
+                }
+
+                None => {
+                    visible = last_line.glyphs.start;
+                    break 0.0;
+                }
+            }
+        };
+
+        end_x = content_end + ellipsis_width;
+
+        layout.ellipsis = Some(Ellipsis {
+            index: layout.glyphs[visible].index,
+            dots: (0..3)
+                .map(|i| Vec2::new(content_end + dot_advance * i as f32, last_line.y))
+                .collect(),
+            end_x,
+        });
+    }
+
+    // Hidden characters are placed at the end of the last line, in the same way as
+    // control characters, so that they can still be located.
+    for glyph in &mut layout.glyphs[visible..] {
+        glyph.bounds = Rectangle::new(end_x, last_line.y, 0.0, line_height);
+        glyph.quad = None;
+    }
+
+    last_line.glyphs.end = layout.glyphs.len();
+    layout.visible_glyphs = visible;
+    layout.end = Vec2::new(end_x, last_line.y);
+}
+
+/// Moves each line horizontally to match the alignment.
+///
+/// If there is no `max_width`, the lines are aligned relative to the widest line.
+fn align_lines(layout: &mut TextLayout, options: &LayoutOptions) {
+    if options.alignment == TextAlignment::Left {
+        return;
+    }
+
+    let last_line = layout.lines.len() - 1;
+
+    let widths: Vec<f32> = layout
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let visible = line.glyphs.start..line.glyphs.end.min(layout.visible_glyphs);
+
+            let content_end = last_content_glyph(&layout.glyphs, visible)
+                .map_or(0.0, |i| layout.glyphs[i].bounds.right());
+
+            match &layout.ellipsis {
+                Some(ellipsis) if i == last_line => f32::max(content_end, ellipsis.end_x),
+                _ => content_end,
+            }
+        })
+        .collect();
+
+    let box_width = options
+        .max_width
+        .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+
+    for (i, (line, width)) in layout.lines.iter().zip(&widths).enumerate() {
+        let free_space = f32::max(box_width - width, 0.0);
+        let visible = line.glyphs.start..line.glyphs.end.min(layout.visible_glyphs);
+        let content_end = last_content_glyph(&layout.glyphs, visible.clone());
+
+        let (offset, spacing) = match options.alignment {
+            TextAlignment::Left => (0.0, 0.0),
+            TextAlignment::Center => ((free_space / 2.0).round(), 0.0),
+            TextAlignment::Right => (free_space.round(), 0.0),
+            TextAlignment::Justify => {
+                // Lines that end a paragraph are left aligned, like in a word processor.
+                let gaps = match content_end {
+                    Some(content_end) if !line.hard_break => layout.glyphs
+                        [visible.start..content_end]
+                        .iter()
+                        .filter(|g| is_space(g.character))
+                        .count(),
+                    _ => 0,
+                };
+
+                if gaps > 0 {
+                    (0.0, free_space / gaps as f32)
+                } else {
+                    (0.0, 0.0)
+                }
+            }
+        };
+
+        let mut shift = offset;
+
+        for (j, glyph) in layout.glyphs[line.glyphs.clone()].iter_mut().enumerate() {
+            glyph.bounds.x += shift;
+
+            let is_gap = content_end.is_some_and(|end| line.glyphs.start + j < end);
+
+            if spacing > 0.0 && is_gap && is_space(glyph.character) {
+                glyph.bounds.width += spacing;
+                shift += spacing;
+            }
+        }
+
+        // The end of the text and the ellipsis (if there is one) are always on the
+        // last line.
+        if i == last_line {
+            layout.end.x += offset;
+
+            if let Some(ellipsis) = &mut layout.ellipsis {
+                for dot in &mut ellipsis.dots {
+                    dot.x += offset;
+                }
+
+                ellipsis.end_x += offset;
+            }
+        }
+    }
+}
+
+/// Finds the last glyph in the given range that isn't whitespace or a control character.
+fn last_content_glyph(glyphs: &[GlyphPosition], range: Range<usize>) -> Option<usize> {
+    range.rev().find(|&i| !is_blank(glyphs[i].character))
+}
+
+fn is_blank(ch: char) -> bool {
+    ch.is_whitespace() || ch.is_control()
+}
+
+/// Returns whether a character is a space that can be stretched when justifying text.
+fn is_space(ch: char) -> bool {
+    ch.is_whitespace() && !ch.is_control()
+}
+
+/// Measures the width of a word, not including any trailing whitespace.
+///
+/// This is mainly used to determine if a word needs to break onto a
+/// new line.
+fn measure_word(rasterizer: &dyn Rasterizer, word: &str) -> f32 {
+    let mut last_glyph = None;
+    let mut word_width = 0.0;
+
+    for ch in word.trim_end().chars() {
+        word_width += rasterizer.advance(ch);
+
+        if let Some(last) = last_glyph {
+            word_width += rasterizer.kerning(last, ch);
+        }
+
+        last_glyph = Some(ch);
+    }
+
+    word_width
+}
+
+/// Converts an outline thickness into the form used in cache keys (tenths of a pixel).
+fn outline_key(thickness: f32) -> u32 {
+    (thickness.max(0.0) * 10.0).round() as u32
+}
+
+/// Generates the outline of a rasterized glyph, by dilating its coverage by the
+/// given thickness.
+///
+/// The result is a solid silhouette, which is larger than the original glyph by
+/// `thickness` pixels (rounded up) on each side, so that it can be drawn underneath it.
+fn dilate_glyph(
+    glyph: &RasterizedGlyph,
+    thickness: f32,
+    style: FontTextureStyle,
+) -> RasterizedGlyph {
+    let src_width = glyph.bounds.width as i32;
+    let src_height = glyph.bounds.height as i32;
+
+    let padding = thickness.ceil() as i32;
+    let width = src_width + padding * 2;
+    let height = src_height + padding * 2;
+
+    let coverage_at = |x: i32, y: i32| -> f32 {
+        if x < 0 || y < 0 || x >= src_width || y >= src_height {
+            0.0
+        } else {
+            glyph.data[((y * src_width + x) * 4 + 3) as usize] as f32 / 255.0
+        }
+    };
+
+    let mut data = Vec::with_capacity((width * height * 4) as usize);
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut coverage: f32 = 0.0;
+
+            for dy in -padding..=padding {
+                for dx in -padding..=padding {
+                    // Pixels at the edge of the outline are partially covered, which
+                    // gives a smooth result for fractional thicknesses.
+                    let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                    let weight = (thickness + 1.0 - distance).clamp(0.0, 1.0);
+
+                    if weight > 0.0 {
+                        let source = coverage_at(x - padding + dx, y - padding + dy);
+                        coverage = coverage.max(source * weight);
+                    }
+                }
+            }
+
+            let coverage = (coverage * 255.0).round() as u8;
+
+            data.extend_from_slice(&match style {
+                FontTextureStyle::Normal => [255, 255, 255, coverage],
+                FontTextureStyle::Premultiplied => [coverage, coverage, coverage, coverage],
+            });
+        }
+    }
+
+    RasterizedGlyph {
+        bounds: Rectangle::new(
+            glyph.bounds.x - padding as f32,
+            glyph.bounds.y - padding as f32,
+            width as f32,
+            height as f32,
+        ),
+        data,
     }
 }
 
@@ -375,3 +997,286 @@ impl<'a> Iterator for UnicodeLineBreaks<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A monospaced rasterizer, with each glyph being 10 pixels wide and 20 pixels tall.
+    struct TestRasterizer;
+
+    impl Rasterizer for TestRasterizer {
+        fn rasterize(&self, _: char, _: Vec2<f32>) -> Option<RasterizedGlyph> {
+            None
+        }
+
+        fn advance(&self, _: char) -> f32 {
+            10.0
+        }
+
+        fn line_height(&self) -> f32 {
+            20.0
+        }
+
+        fn ascent(&self) -> f32 {
+            16.0
+        }
+
+        fn kerning(&self, _: char, _: char) -> f32 {
+            0.0
+        }
+    }
+
+    fn wrap(max_width: f32) -> LayoutOptions {
+        LayoutOptions {
+            max_width: Some(max_width),
+            ..LayoutOptions::default()
+        }
+    }
+
+    fn bounds(layout: &TextLayout) -> Vec<(usize, char, Rectangle)> {
+        layout
+            .glyphs
+            .iter()
+            .map(|g| (g.index, g.character, g.bounds))
+            .collect()
+    }
+
+    #[test]
+    fn layout_places_every_character() {
+        let layout = layout(&TestRasterizer, "a b", &LayoutOptions::default());
+
+        assert_eq!(
+            bounds(&layout),
+            [
+                (0, 'a', Rectangle::new(0.0, 0.0, 10.0, 20.0)),
+                (1, ' ', Rectangle::new(10.0, 0.0, 10.0, 20.0)),
+                (2, 'b', Rectangle::new(20.0, 0.0, 10.0, 20.0)),
+            ]
+        );
+
+        assert_eq!(layout.end, Vec2::new(30.0, 0.0));
+    }
+
+    #[test]
+    fn layout_of_empty_string() {
+        let layout = layout(&TestRasterizer, "", &LayoutOptions::default());
+
+        assert!(layout.glyphs.is_empty());
+        assert_eq!(layout.end, Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn layout_with_trailing_newline() {
+        let layout = layout(&TestRasterizer, "ab\n", &LayoutOptions::default());
+
+        assert_eq!(
+            bounds(&layout)[2],
+            (2, '\n', Rectangle::new(20.0, 0.0, 0.0, 20.0))
+        );
+
+        assert_eq!(layout.end, Vec2::new(0.0, 20.0));
+    }
+
+    #[test]
+    fn layout_uses_byte_indices() {
+        let layout = layout(&TestRasterizer, "é!", &LayoutOptions::default());
+
+        let indices: Vec<_> = layout.glyphs.iter().map(|g| g.index).collect();
+        assert_eq!(indices, [0, 2]);
+    }
+
+    #[test]
+    fn layout_wraps_words() {
+        let layout = layout(&TestRasterizer, "aa bb", &wrap(40.0));
+
+        assert_eq!(
+            bounds(&layout)[3],
+            (3, 'b', Rectangle::new(0.0, 20.0, 10.0, 20.0))
+        );
+
+        // The space stays at the end of the first line.
+        assert_eq!(
+            bounds(&layout)[2],
+            (2, ' ', Rectangle::new(20.0, 0.0, 10.0, 20.0))
+        );
+    }
+
+    #[test]
+    fn layout_clips_to_max_height() {
+        let options = LayoutOptions {
+            max_height: Some(45.0),
+            ..wrap(40.0)
+        };
+
+        let layout = layout(&TestRasterizer, "aa bb cc", &options);
+
+        assert_eq!(layout.lines.len(), 2);
+        assert_eq!(layout.visible_glyphs, 6);
+        assert!(layout.ellipsis.is_none());
+
+        // The hidden characters are placed at the end of the last line.
+        assert_eq!(
+            bounds(&layout)[6],
+            (6, 'c', Rectangle::new(30.0, 20.0, 0.0, 20.0))
+        );
+
+        assert_eq!(layout.end, Vec2::new(30.0, 20.0));
+    }
+
+    #[test]
+    fn layout_always_keeps_one_line() {
+        let options = LayoutOptions {
+            max_height: Some(0.0),
+            ..LayoutOptions::default()
+        };
+
+        let layout = layout(&TestRasterizer, "a\nb", &options);
+
+        assert_eq!(layout.lines.len(), 1);
+        assert_eq!(layout.visible_glyphs, 2);
+    }
+
+    #[test]
+    fn layout_adds_ellipsis_within_max_width() {
+        let options = LayoutOptions {
+            max_height: Some(20.0),
+            overflow: TextOverflow::Ellipsis,
+            ..wrap(60.0)
+        };
+
+        let layout = layout(&TestRasterizer, "aaaa bb", &options);
+
+        // 'aaaa' plus the ellipsis would be 70 pixels wide, so the last 'a' is removed.
+        assert_eq!(layout.visible_glyphs, 3);
+
+        let ellipsis = layout.ellipsis.as_ref().unwrap();
+
+        assert_eq!(ellipsis.index, 3);
+        assert_eq!(
+            ellipsis.dots,
+            [
+                Vec2::new(30.0, 0.0),
+                Vec2::new(40.0, 0.0),
+                Vec2::new(50.0, 0.0)
+            ]
+        );
+
+        assert_eq!(layout.end, Vec2::new(60.0, 0.0));
+    }
+
+    #[test]
+    fn layout_does_not_add_ellipsis_for_trailing_whitespace() {
+        let options = LayoutOptions {
+            max_height: Some(20.0),
+            overflow: TextOverflow::Ellipsis,
+            ..LayoutOptions::default()
+        };
+
+        let layout = layout(&TestRasterizer, "aa\n", &options);
+
+        assert!(layout.ellipsis.is_none());
+    }
+
+    #[test]
+    fn layout_aligns_within_max_width() {
+        let options = LayoutOptions {
+            alignment: TextAlignment::Center,
+            ..wrap(40.0)
+        };
+
+        let centered = layout(&TestRasterizer, "aa bb", &options);
+
+        // The trailing space isn't counted when centering the first line.
+        assert_eq!(bounds(&centered)[0].2.x, 10.0);
+        assert_eq!(bounds(&centered)[3].2.x, 10.0);
+
+        let options = LayoutOptions {
+            alignment: TextAlignment::Right,
+            ..wrap(40.0)
+        };
+
+        let right = layout(&TestRasterizer, "aa bb", &options);
+
+        assert_eq!(bounds(&right)[0].2.x, 20.0);
+        assert_eq!(right.end, Vec2::new(40.0, 20.0));
+    }
+
+    #[test]
+    fn layout_aligns_to_widest_line_without_max_width() {
+        let options = LayoutOptions {
+            alignment: TextAlignment::Right,
+            ..LayoutOptions::default()
+        };
+
+        let layout = layout(&TestRasterizer, "aaaa\nb", &options);
+
+        assert_eq!(bounds(&layout)[0].2.x, 0.0);
+        assert_eq!(bounds(&layout)[5].2.x, 30.0);
+    }
+
+    #[test]
+    fn layout_justifies_wrapped_lines() {
+        let options = LayoutOptions {
+            alignment: TextAlignment::Justify,
+            ..wrap(70.0)
+        };
+
+        let layout = layout(&TestRasterizer, "a b c dddd", &options);
+
+        // The first line ('a b c') is 50 pixels wide, so each of the two gaps grows by 10.
+        assert_eq!(
+            bounds(&layout)[..5],
+            [
+                (0, 'a', Rectangle::new(0.0, 0.0, 10.0, 20.0)),
+                (1, ' ', Rectangle::new(10.0, 0.0, 20.0, 20.0)),
+                (2, 'b', Rectangle::new(30.0, 0.0, 10.0, 20.0)),
+                (3, ' ', Rectangle::new(40.0, 0.0, 20.0, 20.0)),
+                (4, 'c', Rectangle::new(60.0, 0.0, 10.0, 20.0)),
+            ]
+        );
+
+        // The last line of the paragraph is left aligned.
+        assert_eq!(bounds(&layout)[6].2.x, 0.0);
+    }
+
+    #[test]
+    fn dilated_glyph_is_padded() {
+        // A single fully covered pixel.
+        let glyph = RasterizedGlyph {
+            bounds: Rectangle::new(2.0, -4.0, 1.0, 1.0),
+            data: vec![255, 255, 255, 255],
+        };
+
+        let outline = dilate_glyph(&glyph, 1.0, FontTextureStyle::Normal);
+
+        assert_eq!(outline.bounds, Rectangle::new(1.0, -5.0, 3.0, 3.0));
+
+        let alpha: Vec<u8> = outline.data.chunks(4).map(|p| p[3]).collect();
+
+        // Orthogonal neighbours are fully covered, and the diagonals are partially covered.
+        assert_eq!(alpha[1], 255);
+        assert_eq!(alpha[3], 255);
+        assert_eq!(alpha[4], 255);
+        assert!(alpha[0] > 0 && alpha[0] < 255);
+
+        let premultiplied = dilate_glyph(&glyph, 1.0, FontTextureStyle::Premultiplied);
+        assert_eq!(&premultiplied.data[0..3], &[alpha[0]; 3]);
+    }
+
+    #[test]
+    fn atlas_growth_respects_max_size() {
+        assert_eq!((256, 256), next_atlas_size(128, 128, None));
+        assert_eq!((256, 256), next_atlas_size(128, 128, Some(1024)));
+        assert_eq!((300, 300), next_atlas_size(256, 256, Some(300)));
+        assert_eq!((300, 300), next_atlas_size(300, 300, Some(300)));
+    }
+
+    #[test]
+    fn outline_thickness_is_quantized() {
+        assert_eq!(outline_key(1.0), 10);
+        assert_eq!(outline_key(1.04), 10);
+        assert_eq!(outline_key(0.0), 0);
+        assert_eq!(outline_key(-2.0), 0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::graphics::{FilterMode, Rectangle, Texture};
+use crate::graphics::{FilterMode, Rectangle, Texture, WrapMode};
 use crate::platform::GraphicsDevice;
 use crate::{Context, Result};
 
@@ -32,6 +32,7 @@ impl ShelfPacker {
                 texture_width,
                 texture_height,
                 filter_mode,
+                WrapMode::Clamp,
             )?,
             shelves: Vec::new(),
             next_y: 0,
@@ -63,6 +64,7 @@ impl ShelfPacker {
             texture_width,
             texture_height,
             self.texture.filter_mode(),
+            WrapMode::Clamp,
         )?;
 
         self.shelves.clear();
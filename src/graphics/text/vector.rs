@@ -7,7 +7,7 @@ use ab_glyph::{Font as AbFont, FontRef, FontVec, PxScale, ScaleFont};
 use crate::error::{Result, TetraError};
 use crate::fs;
 use crate::graphics::text::cache::{FontCache, RasterizedGlyph, Rasterizer};
-use crate::graphics::text::{Font, FontTextureStyle};
+use crate::graphics::text::{Font, FontAtlas, FontTextureStyle};
 use crate::graphics::Rectangle;
 use crate::math::Vec2;
 use crate::Context;
@@ -105,6 +105,10 @@ where
             scaled_font.glyph_id(current),
         )
     }
+
+    fn texture_style(&self) -> FontTextureStyle {
+        self.texture_type
+    }
 }
 
 /// Abstracts over the two Font types provided by ab_glyph.
@@ -186,7 +190,27 @@ impl VectorFontBuilder {
     /// * [`TetraError::PlatformError`] will be returned if the GPU cache for the font
     ///   could not be created.
     pub fn with_size(&self, ctx: &mut Context, size: f32) -> Result<Font> {
-        let rasterizer: Box<dyn Rasterizer> = match &self.data {
+        let cache = FontCache::new(
+            &mut ctx.device,
+            self.rasterizer(size),
+            ctx.graphics.default_filter_mode,
+        )?;
+
+        Ok(Font {
+            data: Rc::new(RefCell::new(cache)),
+        })
+    }
+
+    /// Creates a `Font` with the given size, which stores its glyphs in a shared
+    /// [`FontAtlas`].
+    ///
+    /// Text drawn using fonts from the same atlas can be batched together.
+    pub fn with_size_in_atlas(&self, size: f32, atlas: &FontAtlas) -> Font {
+        atlas.create_font(self.rasterizer(size))
+    }
+
+    fn rasterizer(&self, size: f32) -> Box<dyn Rasterizer> {
+        match &self.data {
             VectorFontData::Owned(f) => Box::new(VectorRasterizer::new(
                 Rc::clone(f),
                 size,
@@ -197,16 +221,6 @@ impl VectorFontBuilder {
                 size,
                 self.texture_style,
             )),
-        };
-
-        let cache = FontCache::new(
-            &mut ctx.device,
-            rasterizer,
-            ctx.graphics.default_filter_mode,
-        )?;
-
-        Ok(Font {
-            data: Rc::new(RefCell::new(cache)),
-        })
+        }
     }
 }
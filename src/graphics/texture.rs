@@ -4,8 +4,8 @@ use std::cell::Cell;
 use std::path::Path;
 use std::rc::Rc;
 
-use crate::error::Result;
-use crate::graphics::{self, DrawParams, ImageData, Rectangle};
+use crate::error::{Result, TetraError};
+use crate::graphics::{self, DrawParams, ImageData, Rectangle, WarmUpItem};
 use crate::platform::{GraphicsDevice, RawTexture};
 use crate::Context;
 
@@ -13,12 +13,14 @@ use crate::Context;
 pub(crate) struct TextureSharedData {
     pub(crate) handle: RawTexture,
     filter_mode: Cell<FilterMode>,
+    wrap_mode: Cell<WrapMode>,
+    pub(crate) warmed_up: Cell<bool>,
 }
 
 impl PartialEq for TextureSharedData {
     fn eq(&self, other: &TextureSharedData) -> bool {
-        // filter_mode should always match what's set on the GPU,
-        // so we can ignore it for equality checks.
+        // filter_mode and wrap_mode should always match what's set on the GPU,
+        // so we can ignore them for equality checks.
 
         self.handle.eq(&other.handle)
     }
@@ -87,6 +89,30 @@ impl Texture {
         Texture::from_image_data(ctx, &data)
     }
 
+    /// Creates a new texture from the given file, with mipmaps generated and
+    /// the filter mode set to [`FilterMode::Trilinear`].
+    ///
+    /// This is a shortcut for calling [`Texture::new`], [`Texture::generate_mipmaps`]
+    /// and [`Texture::set_filter_mode`].
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    /// * [`TetraError::FailedToLoadAsset`](crate::TetraError::FailedToLoadAsset) will be
+    ///   returned if the file could not be loaded.
+    /// * [`TetraError::InvalidTexture`](crate::TetraError::InvalidTexture) will be returned
+    ///   if the texture data was invalid.
+    pub fn with_mipmaps<P>(ctx: &mut Context, path: P) -> Result<Texture>
+    where
+        P: AsRef<Path>,
+    {
+        let mut texture = Texture::new(ctx, path)?;
+        texture.generate_mipmaps(ctx);
+        texture.set_filter_mode(ctx, FilterMode::Trilinear);
+        Ok(texture)
+    }
+
     /// Creates a new texture from a slice of pixel data.
     ///
     /// This is useful if you wish to create a texture at runtime.
@@ -116,6 +142,7 @@ impl Texture {
             data,
             format,
             ctx.graphics.default_filter_mode,
+            ctx.graphics.default_wrap_mode,
         )
     }
 
@@ -157,11 +184,74 @@ impl Texture {
         )
     }
 
-    pub(crate) fn from_raw(handle: RawTexture, filter_mode: FilterMode) -> Texture {
+    /// Creates a texture that wraps an existing OpenGL texture object.
+    ///
+    /// This allows textures that were created by other libraries (e.g. a video decoder
+    /// or a UI renderer) to be drawn via Tetra's renderer. Tetra does not take ownership
+    /// of the texture - it will not be deleted when the returned `Texture` (and all of
+    /// its clones) are dropped.
+    ///
+    /// The texture is assumed to have the [`TextureFormat::Rgba8`] format. Its filter
+    /// mode and wrap mode will be set to the [default filter mode](graphics::set_default_filter_mode)
+    /// and [default wrap mode](graphics::set_default_wrap_mode), as Tetra needs to know
+    /// their values - they can be changed afterwards via the usual methods.
+    ///
+    /// Note that Tetra will modify the state of the texture object: the filter and wrap
+    /// parameters are set when it is wrapped and whenever they are changed, and calling
+    /// [`generate_mipmaps`](Texture::generate_mipmaps) will set `GL_TEXTURE_MAX_LEVEL`
+    /// and regenerate the texture's mipmap levels. If other code also uses the texture,
+    /// it may need to restore these parameters.
+    ///
+    /// # Safety
+    ///
+    /// * `texture_id` must be the name of a valid `GL_TEXTURE_2D` texture object, created
+    ///   in the same OpenGL context as Tetra (or in a context that shares objects with it).
+    /// * The texture must have storage allocated for at least `width` by `height` pixels.
+    /// * The texture must not be deleted until the returned `Texture` and all of its
+    ///   clones have been dropped.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::InvalidSize`](crate::TetraError::InvalidSize) will be returned
+    ///   if the width or height is zero or negative.
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if `texture_id` is zero.
+    pub unsafe fn from_raw_gl(
+        ctx: &mut Context,
+        texture_id: u32,
+        width: i32,
+        height: i32,
+    ) -> Result<Texture> {
+        if width <= 0 || height <= 0 {
+            return Err(TetraError::InvalidSize { width, height });
+        }
+
+        let filter_mode = ctx.graphics.default_filter_mode;
+        let wrap_mode = ctx.graphics.default_wrap_mode;
+
+        let handle = ctx.device.wrap_texture(
+            texture_id,
+            width,
+            height,
+            TextureFormat::Rgba8,
+            filter_mode,
+            wrap_mode,
+        )?;
+
+        Ok(Texture::from_raw(handle, filter_mode, wrap_mode))
+    }
+
+    pub(crate) fn from_raw(
+        handle: RawTexture,
+        filter_mode: FilterMode,
+        wrap_mode: WrapMode,
+    ) -> Texture {
         Texture {
             data: Rc::new(TextureSharedData {
                 handle,
                 filter_mode: Cell::new(filter_mode),
+                wrap_mode: Cell::new(wrap_mode),
+                warmed_up: Cell::new(false),
             }),
         }
     }
@@ -173,8 +263,9 @@ impl Texture {
         data: &[u8],
         format: TextureFormat,
         filter_mode: FilterMode,
+        wrap_mode: WrapMode,
     ) -> Result<Texture> {
-        let handle = device.new_texture(width, height, format, filter_mode)?;
+        let handle = device.new_texture(width, height, format, filter_mode, wrap_mode)?;
 
         device.set_texture_data(&handle, data, 0, 0, width, height)?;
 
@@ -182,6 +273,8 @@ impl Texture {
             data: Rc::new(TextureSharedData {
                 handle,
                 filter_mode: Cell::new(filter_mode),
+                wrap_mode: Cell::new(wrap_mode),
+                warmed_up: Cell::new(false),
             }),
         })
     }
@@ -191,6 +284,7 @@ impl Texture {
         width: i32,
         height: i32,
         filter_mode: FilterMode,
+        wrap_mode: WrapMode,
     ) -> Result<Texture> {
         // TODO: There's probably more efficient ways of doing this, but it seems fast enough
         // for now.
@@ -203,6 +297,7 @@ impl Texture {
             &data,
             TextureFormat::Rgba8,
             filter_mode,
+            wrap_mode,
         )
     }
 
@@ -229,32 +324,43 @@ impl Texture {
     }
 
     /// Draws a region of the texture to the screen (or to a canvas, if one is enabled).
+    ///
+    /// The region is drawn as if it were a texture of its own - the
+    /// [origin](DrawParams::origin) is relative to the top-left corner of the region
+    /// rather than the texture, and scaling/rotation are applied based on the region's size.
+    /// This makes it a convenient way of drawing individual sprites from a spritesheet.
     pub fn draw_region<P>(&self, ctx: &mut Context, region: Rectangle, params: P)
     where
         P: Into<DrawParams>,
     {
         let params = params.into();
 
-        let texture_width = self.width() as f32;
-        let texture_height = self.height() as f32;
-
         graphics::set_texture(ctx, self);
-        graphics::push_quad(
-            ctx,
-            0.0,
-            0.0,
-            region.width,
-            region.height,
-            region.x / texture_width,
-            region.y / texture_height,
-            region.right() / texture_width,
-            region.bottom() / texture_height,
-            &params,
-        );
+        graphics::push_region(ctx, self.size(), region, &params);
+    }
+
+    /// Creates a [`TextureRegion`], representing a section of this texture.
+    ///
+    /// This is a cheap operation, as the region holds a reference-counted handle
+    /// to the texture, rather than copying the underlying data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any part of the region is outside the bounds of the texture.
+    pub fn region(&self, region: Rectangle) -> TextureRegion {
+        let bounds = Rectangle::new(0.0, 0.0, self.width() as f32, self.height() as f32);
+
+        TextureRegion {
+            texture: self.clone(),
+            region: offset_region(bounds, region),
+        }
     }
 
     /// Draws a region of the texture by splitting it into nine slices, allowing it to be stretched or
     /// squashed without distorting the borders.
+    ///
+    /// If the target size is smaller than the combined size of the borders, the borders will be
+    /// scaled down proportionally so that they do not overlap.
     pub fn draw_nine_slice<P>(
         &self,
         ctx: &mut Context,
@@ -270,12 +376,11 @@ impl Texture {
         let texture_width = self.width() as f32;
         let texture_height = self.height() as f32;
 
+        let (x2, x3) = nine_slice_edges(config.left, config.right, width);
+        let (y2, y3) = nine_slice_edges(config.top, config.bottom, height);
+
         let x1 = 0.0;
         let y1 = 0.0;
-        let x2 = config.left;
-        let y2 = config.top;
-        let x3 = width - config.right;
-        let y3 = height - config.bottom;
         let x4 = width;
         let y4 = height;
 
@@ -338,6 +443,27 @@ impl Texture {
         self.data.handle.format()
     }
 
+    /// Returns an estimate of how much GPU memory is used by the texture, in bytes.
+    ///
+    /// This is calculated from the texture's size and [format](TextureFormat::stride).
+    /// The actual amount used may be higher, depending on how the graphics driver
+    /// stores the data.
+    ///
+    /// Clones of a texture share the same GPU memory, so they will all return the
+    /// same value.
+    pub fn gpu_memory_estimate(&self) -> usize {
+        self.data.handle.size_in_bytes()
+    }
+
+    /// Returns the OpenGL name of the underlying texture object.
+    ///
+    /// This can be used to sample a Tetra texture from an external renderer that shares
+    /// the same OpenGL context. Deleting the texture, or changing its size or format,
+    /// via the raw handle will cause undefined behaviour in Tetra.
+    pub fn raw_gl_handle(&self) -> u32 {
+        self.data.handle.raw_id()
+    }
+
     /// Returns the filter mode being used by the texture.
     pub fn filter_mode(&self) -> FilterMode {
         self.data.filter_mode.get()
@@ -351,6 +477,45 @@ impl Texture {
         self.data.filter_mode.set(filter_mode);
     }
 
+    /// Generates mipmaps for the texture.
+    ///
+    /// Mipmaps are progressively smaller copies of the texture, which the GPU can sample
+    /// from when the texture is drawn at a reduced size. They are used when the texture's
+    /// filter mode is set to [`FilterMode::Trilinear`].
+    ///
+    /// Once a texture has mipmaps, they will be regenerated automatically whenever
+    /// the texture's data is replaced via [`set_data`](Self::set_data) or
+    /// [`replace_data`](Self::replace_data).
+    ///
+    /// Mipmaps are not currently supported for canvases - calling this on a canvas's
+    /// texture will generate mipmaps for its current contents, but they will not be
+    /// updated when the canvas is drawn to.
+    pub fn generate_mipmaps(&self, ctx: &mut Context) {
+        ctx.device.generate_mipmaps(&self.data.handle);
+    }
+
+    /// Returns whether or not the texture has mipmaps.
+    pub fn has_mipmaps(&self) -> bool {
+        self.data.handle.has_mipmaps()
+    }
+
+    /// Returns the wrap mode being used by the texture.
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.data.wrap_mode.get()
+    }
+
+    /// Sets the wrap mode that should be used by the texture.
+    ///
+    /// This controls what happens when the texture is sampled outside of the `0.0` to `1.0`
+    /// range - for example, a [`Mesh`](crate::graphics::mesh::Mesh) with UVs that go from
+    /// `0.0` to `4.0` will tile the texture four times if this is set to [`WrapMode::Repeat`].
+    pub fn set_wrap_mode(&mut self, ctx: &mut Context, wrap_mode: WrapMode) {
+        ctx.device
+            .set_texture_wrap_mode(&self.data.handle, wrap_mode);
+
+        self.data.wrap_mode.set(wrap_mode);
+    }
+
     /// Gets the texture's data from the GPU.
     ///
     /// This can be useful if you need to do some image processing on the CPU,
@@ -419,6 +584,180 @@ impl Texture {
         let (width, height) = self.size();
         self.set_data(ctx, 0, 0, width, height, data)
     }
+
+    /// Writes the contents of an [`ImageData`] to the texture, with its top left corner
+    /// at the specified position.
+    ///
+    /// If the image's [`TextureFormat`] does not match the texture's, the data will be
+    /// converted, as long as this can be done without losing any information (e.g.
+    /// [`R8`](TextureFormat::R8) data can be written to an [`Rgba8`](TextureFormat::Rgba8)
+    /// texture). The conversion works the same way as [`ImageData::to_format`] - channels
+    /// that are missing from the image will be set to zero, apart from the alpha channel,
+    /// which will be set to one.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::TextureFormatMismatch`](crate::TetraError::TextureFormatMismatch)
+    ///   will be returned if the image's data cannot be converted to the texture's format
+    ///   without losing information.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any part of the image would be outside the bounds of the texture.
+    pub fn set_region_from_image_data(
+        &self,
+        ctx: &mut Context,
+        x: i32,
+        y: i32,
+        data: &ImageData,
+    ) -> Result {
+        let (width, height) = data.size();
+
+        assert!(
+            x >= 0 && y >= 0 && x + width <= self.width() && y + height <= self.height(),
+            "tried to write outside of texture bounds"
+        );
+
+        let format = self.format();
+
+        if data.format() == format {
+            return self.set_data(ctx, x, y, width, height, data.as_bytes());
+        }
+
+        if !data.format().converts_losslessly_to(format) {
+            return Err(TetraError::TextureFormatMismatch {
+                expected: format,
+                actual: data.format(),
+            });
+        }
+
+        let converted = data.to_format(format);
+        self.set_data(ctx, x, y, width, height, converted.as_bytes())
+    }
+
+    /// Prepares the texture for drawing, so that using it for the first time does not
+    /// cause a hitch.
+    ///
+    /// This is a shortcut for calling [`graphics::warm_up`] with a single texture. If the
+    /// texture has already been warmed up, this does nothing.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn warm_up(&self, ctx: &mut Context) -> Result {
+        graphics::warm_up(ctx, &[WarmUpItem::Texture(self)])
+    }
+}
+
+/// A section of a [`Texture`].
+///
+/// This can be used to pass around individual sprites from a spritesheet or atlas,
+/// without having to keep track of the texture and the source rectangle separately.
+/// Texture regions can be created via [`Texture::region`] or
+/// [`TextureRegion::sub_region`].
+///
+/// # Performance
+///
+/// Creating or cloning a texture region is a cheap operation, as it only stores
+/// a [reference-counted](https://doc.rust-lang.org/std/rc/struct.Rc.html) handle to the
+/// underlying texture, alongside the bounds of the region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureRegion {
+    texture: Texture,
+    region: Rectangle,
+}
+
+impl TextureRegion {
+    /// Draws the region to the screen (or to a canvas, if one is enabled).
+    pub fn draw<P>(&self, ctx: &mut Context, params: P)
+    where
+        P: Into<DrawParams>,
+    {
+        self.texture.draw_region(ctx, self.region, params);
+    }
+
+    /// Creates a new region, representing a section of this region.
+    ///
+    /// The position of the new region is relative to the top left of this region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any part of the new region is outside the bounds of this region.
+    pub fn sub_region(&self, region: Rectangle) -> TextureRegion {
+        TextureRegion {
+            texture: self.texture.clone(),
+            region: offset_region(self.region, region),
+        }
+    }
+
+    /// Returns a reference to the texture that this region is a section of.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the bounds of the region, in pixels.
+    ///
+    /// This is relative to the top left of the underlying texture.
+    pub fn bounds(&self) -> Rectangle {
+        self.region
+    }
+
+    /// Returns the bounds of the region, in normalized texture co-ordinates.
+    ///
+    /// This can be useful when building vertex data for a [`Mesh`](super::mesh::Mesh).
+    pub fn uv(&self) -> Rectangle {
+        let texture_width = self.texture.width() as f32;
+        let texture_height = self.texture.height() as f32;
+
+        Rectangle::new(
+            self.region.x / texture_width,
+            self.region.y / texture_height,
+            self.region.width / texture_width,
+            self.region.height / texture_height,
+        )
+    }
+
+    /// Returns the width of the region.
+    pub fn width(&self) -> f32 {
+        self.region.width
+    }
+
+    /// Returns the height of the region.
+    pub fn height(&self) -> f32 {
+        self.region.height
+    }
+
+    /// Returns the size of the region.
+    pub fn size(&self) -> (f32, f32) {
+        (self.region.width, self.region.height)
+    }
+}
+
+/// Returns whether a region lies within an area of the given size, starting at the origin.
+pub(crate) fn region_fits(region: Rectangle, width: f32, height: f32) -> bool {
+    region.x >= 0.0
+        && region.y >= 0.0
+        && region.width >= 0.0
+        && region.height >= 0.0
+        && region.right() <= width
+        && region.bottom() <= height
+}
+
+/// Converts a region relative to `bounds` into an absolute region, checking that
+/// it does not lie outside of `bounds`.
+fn offset_region(bounds: Rectangle, region: Rectangle) -> Rectangle {
+    assert!(
+        region_fits(region, bounds.width, bounds.height),
+        "region was out of bounds"
+    );
+
+    Rectangle::new(
+        bounds.x + region.x,
+        bounds.y + region.y,
+        region.width,
+        region.height,
+    )
 }
 
 /// In-memory data formats for textures.
@@ -450,13 +789,37 @@ impl TextureFormat {
             TextureFormat::Rgba16F => 8,
         }
     }
+
+    /// Returns whether data in this format can be converted to the target format
+    /// without losing any information.
+    fn converts_losslessly_to(self, target: TextureFormat) -> bool {
+        match (self, target) {
+            (a, b) if a == b => true,
+
+            // Every 8-bit value can be represented exactly enough by a 16-bit float
+            // to round trip.
+            (_, TextureFormat::Rgba16F) => true,
+
+            (TextureFormat::R8, TextureFormat::Rg8)
+            | (TextureFormat::R8, TextureFormat::Rgba8)
+            | (TextureFormat::Rg8, TextureFormat::Rgba8) => true,
+
+            _ => false,
+        }
+    }
 }
 
 /// Filtering algorithms that can be used when scaling an image.
 ///
 /// Tetra currently defaults to using `Nearest` for all newly created textures.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FilterMode {
     /// Nearest-neighbor interpolation. This preserves hard edges and details, but may look pixelated.
     ///
@@ -465,6 +828,38 @@ pub enum FilterMode {
 
     /// Linear interpolation. This smooths images when scaling them up or down.
     Linear,
+
+    /// Trilinear interpolation. This smooths images like `Linear`, but also blends
+    /// between mipmap levels, which reduces shimmering when a texture is drawn much
+    /// smaller than its original size.
+    ///
+    /// This requires the texture to have mipmaps (see [`Texture::generate_mipmaps`]).
+    /// If it does not, this will behave the same as `Linear`.
+    Trilinear,
+}
+
+/// Algorithms that can be used when a texture is sampled outside of its bounds.
+///
+/// Tetra currently defaults to using `Clamp` for all newly created textures.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WrapMode {
+    /// The edge pixels of the texture will be stretched out infinitely.
+    Clamp,
+
+    /// The texture will repeat.
+    ///
+    /// This is useful for things like tiled or scrolling backgrounds.
+    Repeat,
+
+    /// The texture will repeat, with every other repetition being mirrored.
+    MirroredRepeat,
 }
 
 /// Information on how to slice a texture so that it can be stretched or squashed without
@@ -517,3 +912,82 @@ impl NineSlice {
         }
     }
 }
+
+/// Calculates where the inner edges of a nine slice should be placed along one axis,
+/// clamping the borders if they won't fit in the target size.
+fn nine_slice_edges(start: f32, end: f32, size: f32) -> (f32, f32) {
+    let total = start + end;
+
+    if total > size && total > 0.0 {
+        let edge = start * (size.max(0.0) / total);
+        (edge, edge)
+    } else {
+        (start, size - end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_region_nested() {
+        let texture = Rectangle::new(0.0, 0.0, 128.0, 128.0);
+
+        let outer = offset_region(texture, Rectangle::new(32.0, 16.0, 64.0, 64.0));
+        assert_eq!(outer, Rectangle::new(32.0, 16.0, 64.0, 64.0));
+
+        let inner = offset_region(outer, Rectangle::new(8.0, 4.0, 16.0, 16.0));
+        assert_eq!(inner, Rectangle::new(40.0, 20.0, 16.0, 16.0));
+
+        let innermost = offset_region(inner, Rectangle::new(0.0, 0.0, 16.0, 16.0));
+        assert_eq!(innermost, inner);
+    }
+
+    #[test]
+    #[should_panic]
+    fn offset_region_out_of_bounds() {
+        let outer = Rectangle::new(32.0, 16.0, 64.0, 64.0);
+
+        // This would fit within the texture, but not within the outer region.
+        offset_region(outer, Rectangle::new(48.0, 0.0, 32.0, 32.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn offset_region_negative_position() {
+        let outer = Rectangle::new(32.0, 16.0, 64.0, 64.0);
+
+        offset_region(outer, Rectangle::new(-1.0, 0.0, 16.0, 16.0));
+    }
+
+    #[test]
+    fn lossless_format_conversions() {
+        use TextureFormat::*;
+
+        assert!(R8.converts_losslessly_to(R8));
+        assert!(R8.converts_losslessly_to(Rg8));
+        assert!(R8.converts_losslessly_to(Rgba8));
+        assert!(Rg8.converts_losslessly_to(Rgba8));
+        assert!(Rgba8.converts_losslessly_to(Rgba16F));
+
+        assert!(!Rgba8.converts_losslessly_to(R8));
+        assert!(!Rgba8.converts_losslessly_to(Rg8));
+        assert!(!Rg8.converts_losslessly_to(R8));
+        assert!(!Rgba16F.converts_losslessly_to(Rgba8));
+    }
+
+    #[test]
+    fn nine_slice_edges_fit() {
+        assert_eq!(nine_slice_edges(4.0, 6.0, 32.0), (4.0, 26.0));
+        assert_eq!(nine_slice_edges(4.0, 6.0, 10.0), (4.0, 4.0));
+    }
+
+    #[test]
+    fn nine_slice_edges_clamp() {
+        assert_eq!(nine_slice_edges(4.0, 4.0, 4.0), (2.0, 2.0));
+        assert_eq!(nine_slice_edges(2.0, 6.0, 4.0), (1.0, 1.0));
+        assert_eq!(nine_slice_edges(4.0, 4.0, 0.0), (0.0, 0.0));
+        assert_eq!(nine_slice_edges(0.0, 0.0, 0.0), (0.0, 0.0));
+    }
+}
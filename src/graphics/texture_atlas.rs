@@ -0,0 +1,360 @@
+use std::path::Path;
+
+use hashbrown::HashMap;
+
+use crate::error::{Result, TetraError};
+use crate::graphics::{ImageData, Rectangle, Texture, TextureFormat, TextureRegion};
+use crate::Context;
+
+/// A builder for packing multiple images into a single [`TextureAtlas`].
+///
+/// Drawing lots of different textures can be slow, as switching between them triggers a
+/// [flush](crate::graphics::flush). Packing your sprites into a single texture allows them
+/// to be batched together, even if you are drawing lots of different images.
+///
+/// The images are packed using a simple shelf-packing algorithm, which works best when
+/// most of the images are a similar height. The resulting texture will be just large enough
+/// to hold all of the images.
+///
+/// # Bleeding
+///
+/// When an atlas is drawn with [`FilterMode::Linear`](crate::graphics::FilterMode::Linear),
+/// or when it is drawn at non-integer positions or scales, pixels from neighbouring images
+/// can 'bleed' into the edges of a sprite. Adding [padding](Self::padding) between the
+/// images avoids sampling from other images, and [extruding the edges](Self::extrude_edges)
+/// avoids sampling from the transparent padding.
+#[derive(Debug, Clone)]
+pub struct TextureAtlasBuilder {
+    entries: Vec<(String, ImageData)>,
+    max_width: i32,
+    max_height: i32,
+    padding: i32,
+    extrude_edges: bool,
+}
+
+impl TextureAtlasBuilder {
+    /// Creates a new texture atlas builder.
+    ///
+    /// The atlas will never be larger than the given maximum size. Most graphics cards
+    /// support textures of at least 4096x4096, but if you are targeting older hardware, you
+    /// may want to stick to 2048x2048.
+    pub fn new(max_width: i32, max_height: i32) -> TextureAtlasBuilder {
+        TextureAtlasBuilder {
+            entries: Vec::new(),
+            max_width,
+            max_height,
+            padding: 0,
+            extrude_edges: false,
+        }
+    }
+
+    /// Adds an image to the atlas, with the given name.
+    ///
+    /// If an image with the same name has already been added, it will be replaced.
+    pub fn add<S>(&mut self, name: S, data: ImageData) -> &mut TextureAtlasBuilder
+    where
+        S: Into<String>,
+    {
+        let name = name.into();
+
+        match self.entries.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = data,
+            None => self.entries.push((name, data)),
+        }
+
+        self
+    }
+
+    /// Loads an image from the given file, and adds it to the atlas with the given name.
+    ///
+    /// If an image with the same name has already been added, it will be replaced.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::FailedToLoadAsset`] will be returned if the file could not be loaded.
+    /// * [`TetraError::InvalidTexture`] will be returned if the image data was invalid.
+    pub fn add_file<S, P>(&mut self, name: S, path: P) -> Result<&mut TextureAtlasBuilder>
+    where
+        S: Into<String>,
+        P: AsRef<Path>,
+    {
+        let data = ImageData::new(path)?;
+        Ok(self.add(name, data))
+    }
+
+    /// Sets the amount of empty space (in pixels) that should be left around each side
+    /// of each image.
+    ///
+    /// Defaults to `0`.
+    pub fn padding(&mut self, padding: i32) -> &mut TextureAtlasBuilder {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets whether the edge pixels of each image should be duplicated into the
+    /// surrounding padding.
+    ///
+    /// This prevents the transparent padding from bleeding into the edges of sprites when
+    /// linear filtering is used. The duplicated border is one pixel wide, so if this is
+    /// enabled, the padding will be increased to one pixel if it is currently smaller.
+    ///
+    /// Defaults to `false`.
+    pub fn extrude_edges(&mut self, enabled: bool) -> &mut TextureAtlasBuilder {
+        self.extrude_edges = enabled;
+        self
+    }
+
+    /// Packs the images and uploads the resulting texture.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::AtlasFull`] will be returned if the images do not all fit within
+    ///   the maximum size of the atlas.
+    /// * [`TetraError::PlatformError`] will be returned if the underlying graphics API
+    ///   encounters an error.
+    pub fn build(&self, ctx: &mut Context) -> Result<TextureAtlas> {
+        let padding = if self.extrude_edges {
+            i32::max(self.padding, 1)
+        } else {
+            i32::max(self.padding, 0)
+        };
+
+        let sizes: Vec<(i32, i32)> = self
+            .entries
+            .iter()
+            .map(|(_, data)| (data.width() + padding * 2, data.height() + padding * 2))
+            .collect();
+
+        let (positions, width, height) = pack(&sizes, self.max_width, self.max_height)
+            .map_err(|index| TetraError::AtlasFull(self.entries[index].0.clone()))?;
+
+        let mut buffer = vec![0; width as usize * height as usize * 4];
+        let mut regions = HashMap::with_capacity(self.entries.len());
+
+        for ((name, data), position) in self.entries.iter().zip(positions) {
+            let x = position.x + padding;
+            let y = position.y + padding;
+
+            let converted;
+
+            let data = if data.format() == TextureFormat::Rgba8 {
+                data
+            } else {
+                converted = data.to_format(TextureFormat::Rgba8);
+                &converted
+            };
+
+            blit(&mut buffer, width, data, x, y, self.extrude_edges);
+
+            regions.insert(
+                name.clone(),
+                Rectangle::new(
+                    x as f32,
+                    y as f32,
+                    data.width() as f32,
+                    data.height() as f32,
+                ),
+            );
+        }
+
+        let texture = Texture::from_data(ctx, width, height, TextureFormat::Rgba8, &buffer)?;
+
+        Ok(TextureAtlas { texture, regions })
+    }
+}
+
+/// A texture containing multiple images, created via a [`TextureAtlasBuilder`].
+///
+/// The regions that each image occupies can be passed to
+/// [`Texture::draw_region`], or you can use [`region`](Self::region) to get
+/// a [`TextureRegion`] that can be drawn directly.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    texture: Texture,
+    regions: HashMap<String, Rectangle>,
+}
+
+impl TextureAtlas {
+    /// Returns a reference to the atlas' texture.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Returns the bounds of the image with the given name, in pixels.
+    ///
+    /// Returns `None` if no image was added with that name.
+    pub fn get(&self, name: &str) -> Option<Rectangle> {
+        self.regions.get(name).copied()
+    }
+
+    /// Returns a [`TextureRegion`] for the image with the given name.
+    ///
+    /// Returns `None` if no image was added with that name.
+    pub fn region(&self, name: &str) -> Option<TextureRegion> {
+        self.get(name).map(|bounds| self.texture.region(bounds))
+    }
+
+    /// Returns an iterator over the names and bounds of every image in the atlas.
+    ///
+    /// The images are returned in an arbitrary order.
+    pub fn regions(&self) -> impl Iterator<Item = (&str, Rectangle)> {
+        self.regions
+            .iter()
+            .map(|(name, bounds)| (name.as_str(), *bounds))
+    }
+
+    /// Returns the number of images in the atlas.
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Returns `true` if the atlas does not contain any images.
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+}
+
+/// Packs rectangles of the given sizes into shelves, returning the position of each one
+/// (in the same order as the input) and the total size that was used.
+///
+/// If one of the rectangles doesn't fit, its index is returned as the error.
+fn pack(
+    sizes: &[(i32, i32)],
+    max_width: i32,
+    max_height: i32,
+) -> std::result::Result<(Vec<Rectangle<i32>>, i32, i32), usize> {
+    // Placing the tallest images first keeps the shelves as full as possible.
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut positions = vec![Rectangle::default(); sizes.len()];
+
+    let mut shelf_x = 0;
+    let mut shelf_y = 0;
+    let mut shelf_height = 0;
+    let mut used_width = 0;
+
+    for index in order {
+        let (width, height) = sizes[index];
+
+        if width > max_width {
+            return Err(index);
+        }
+
+        if shelf_x + width > max_width {
+            shelf_y += shelf_height;
+            shelf_x = 0;
+            shelf_height = 0;
+        }
+
+        if shelf_y + height > max_height {
+            return Err(index);
+        }
+
+        positions[index] = Rectangle::new(shelf_x, shelf_y, width, height);
+
+        shelf_x += width;
+        shelf_height = i32::max(shelf_height, height);
+        used_width = i32::max(used_width, shelf_x);
+    }
+
+    // A texture can't be zero-sized, so an empty atlas is a single transparent pixel.
+    Ok((
+        positions,
+        i32::max(used_width, 1),
+        i32::max(shelf_y + shelf_height, 1),
+    ))
+}
+
+/// Copies RGBA8 image data into the atlas buffer, optionally duplicating its edge pixels
+/// into the surrounding one pixel border.
+///
+/// Empty images have no edge pixels to extrude, so nothing will be copied for them.
+fn blit(buffer: &mut [u8], buffer_width: i32, data: &ImageData, x: i32, y: i32, extrude: bool) {
+    let src = data.as_bytes();
+    let (width, height) = data.size();
+
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let border = if extrude { 1 } else { 0 };
+
+    for dst_y in y - border..y + height + border {
+        let src_y = (dst_y - y).clamp(0, height - 1);
+
+        for dst_x in x - border..x + width + border {
+            let src_x = (dst_x - x).clamp(0, width - 1);
+
+            let src_index = (src_y * width + src_x) as usize * 4;
+            let dst_index = (dst_y * buffer_width + dst_x) as usize * 4;
+
+            buffer[dst_index..dst_index + 4].copy_from_slice(&src[src_index..src_index + 4]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_fills_shelves_tallest_first() {
+        let (positions, width, height) = pack(&[(10, 5), (10, 10), (10, 8)], 25, 100).unwrap();
+
+        assert_eq!(positions[1], Rectangle::new(0, 0, 10, 10));
+        assert_eq!(positions[2], Rectangle::new(10, 0, 10, 8));
+        assert_eq!(positions[0], Rectangle::new(0, 10, 10, 5));
+        assert_eq!((width, height), (20, 15));
+    }
+
+    #[test]
+    fn pack_reports_entries_that_do_not_fit() {
+        assert_eq!(pack(&[(10, 10), (30, 10)], 25, 100), Err(1));
+        assert_eq!(pack(&[(10, 10), (20, 10), (20, 10)], 25, 20), Err(2));
+        assert!(pack(&[(10, 10), (20, 10)], 25, 20).is_ok());
+    }
+
+    #[test]
+    fn pack_empty() {
+        assert_eq!(pack(&[], 100, 100), Ok((vec![], 1, 1)));
+    }
+
+    #[test]
+    fn blit_extrudes_edges() {
+        // A 2x1 image, red on the left and green on the right, in a 4x3 buffer with
+        // a one pixel border.
+        let red = [255, 0, 0, 255];
+        let green = [0, 255, 0, 255];
+
+        let data = ImageData::from_data(2, 1, TextureFormat::Rgba8, [red, green].concat()).unwrap();
+
+        let mut buffer = vec![0; 4 * 3 * 4];
+        blit(&mut buffer, 4, &data, 1, 1, true);
+
+        let pixel = |x: usize, y: usize| -> &[u8] { &buffer[(y * 4 + x) * 4..(y * 4 + x) * 4 + 4] };
+
+        for y in 0..3 {
+            assert_eq!(pixel(0, y), red);
+            assert_eq!(pixel(1, y), red);
+            assert_eq!(pixel(2, y), green);
+            assert_eq!(pixel(3, y), green);
+        }
+
+        let mut buffer = vec![0; 4 * 3 * 4];
+        blit(&mut buffer, 4, &data, 1, 1, false);
+
+        assert_eq!(&buffer[0..4], [0, 0, 0, 0]);
+        assert_eq!(&buffer[(4 + 1) * 4..(4 + 1) * 4 + 4], red);
+    }
+
+    #[test]
+    fn blit_skips_empty_images() {
+        let data = ImageData::from_data(0, 0, TextureFormat::Rgba8, []).unwrap();
+
+        let mut buffer = vec![0; 2 * 2 * 4];
+        blit(&mut buffer, 2, &data, 1, 1, true);
+
+        assert!(buffer.iter().all(|&b| b == 0));
+    }
+}
@@ -0,0 +1,182 @@
+//! Functions and types relating to HDR tonemapping.
+
+use crate::error::Result;
+use crate::graphics::{self, Canvas, DrawParams, Shader};
+use crate::Context;
+
+const TONEMAP_FRAGMENT_SHADER: &str = include_str!("../resources/tonemap.frag");
+
+/// The curves that a [`Tonemapper`] can use to map HDR colors into the displayable range.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TonemapOperator {
+    /// The simple Reinhard operator (`color / (1 + color)`).
+    ///
+    /// This never clips, but tends to look washed out compared to filmic curves.
+    Reinhard,
+
+    /// An approximation of the ACES filmic curve, which gives more contrast and
+    /// saturation than Reinhard. Very bright colors will be clipped to white.
+    AcesFitted,
+}
+
+/// Draws HDR canvases with tonemapping applied, so that colors brighter than `1.0`
+/// are compressed into the displayable range rather than being clamped.
+///
+/// This is intended to be used with canvases that have a
+/// [`TextureFormat::Rgba16F`](crate::graphics::TextureFormat::Rgba16F) format. The
+/// tonemapped output can be drawn either to the screen, or to another canvas.
+///
+/// The exposure is passed to the shader as a uniform, so it can be changed every
+/// frame (e.g. for eye adaptation) without any extra cost.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tetra::graphics::{Canvas, TextureFormat};
+/// # use tetra::graphics::tonemap::{TonemapOperator, Tonemapper};
+/// # use tetra::math::Vec2;
+/// # use tetra::Context;
+/// # fn run(ctx: &mut Context) -> tetra::Result {
+/// let scene = Canvas::builder(640, 480)
+///     .texture_format(TextureFormat::Rgba16F)
+///     .build(ctx)?;
+///
+/// let mut tonemapper = Tonemapper::new(ctx, TonemapOperator::AcesFitted)?;
+/// tonemapper.set_exposure(1.5);
+///
+/// // ...draw the scene to the canvas, and then:
+/// tonemapper.draw(ctx, &scene, Vec2::zero())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Tonemapper {
+    shader: Shader,
+    operator: TonemapOperator,
+    exposure: f32,
+}
+
+impl Tonemapper {
+    /// Creates a new tonemapper, using the given operator and an exposure of `1.0`.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn new(ctx: &mut Context, operator: TonemapOperator) -> Result<Tonemapper> {
+        Ok(Tonemapper {
+            shader: Shader::from_fragment_string(ctx, TONEMAP_FRAGMENT_SHADER)?,
+            operator,
+            exposure: 1.0,
+        })
+    }
+
+    /// Returns the operator that is used to map colors.
+    pub fn operator(&self) -> TonemapOperator {
+        self.operator
+    }
+
+    /// Sets the operator that is used to map colors.
+    pub fn set_operator(&mut self, operator: TonemapOperator) {
+        self.operator = operator;
+    }
+
+    /// Returns the exposure that is applied before tonemapping.
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Sets the exposure that is applied before tonemapping.
+    ///
+    /// Colors are multiplied by this value before the operator is applied, so values
+    /// above `1.0` will brighten the image, and values below `1.0` will darken it.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    /// Draws a canvas to the current render target (the screen, or another canvas),
+    /// with tonemapping applied.
+    ///
+    /// The shader that was active before this method was called will be restored
+    /// afterwards. If an error is returned, the active shader will not have been changed.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn draw<P>(&self, ctx: &mut Context, canvas: &Canvas, params: P) -> Result
+    where
+        P: Into<DrawParams>,
+    {
+        // The uniforms are applied to the shader immediately, so anything that is
+        // already batched needs to be drawn with the old values first.
+        graphics::flush(ctx);
+
+        // The uniforms are set before switching shaders, so that the previous shader is
+        // still active if this fails.
+        self.shader.set_uniform(ctx, "u_exposure", self.exposure)?;
+        self.shader
+            .set_uniform(ctx, "u_operator", operator_index(self.operator))?;
+
+        let previous_shader = ctx.graphics.shader.clone();
+
+        graphics::set_shader(ctx, &self.shader);
+
+        canvas.draw(ctx, params);
+
+        graphics::set_shader_ex(ctx, previous_shader.as_ref());
+
+        Ok(())
+    }
+}
+
+fn operator_index(operator: TonemapOperator) -> i32 {
+    match operator {
+        TonemapOperator::Reinhard => 0,
+        TonemapOperator::AcesFitted => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContextBuilder;
+
+    #[test]
+    fn shader_declares_tonemapper_uniforms() {
+        assert!(TONEMAP_FRAGMENT_SHADER.contains("uniform float u_exposure;"));
+        assert!(TONEMAP_FRAGMENT_SHADER.contains("uniform int u_operator;"));
+    }
+
+    #[test]
+    fn operator_indices_match_shader() {
+        // The shader treats 0 as Reinhard, and anything else as ACES.
+        assert_eq!(operator_index(TonemapOperator::Reinhard), 0);
+        assert_ne!(operator_index(TonemapOperator::AcesFitted), 0);
+    }
+
+    #[test]
+    #[ignore = "requires a display and an OpenGL context"]
+    fn draw_restores_previous_shader() {
+        let mut ctx = ContextBuilder::new("draw_restores_previous_shader", 64, 64)
+            .build()
+            .unwrap();
+
+        let canvas = Canvas::new(&mut ctx, 8, 8).unwrap();
+        let tonemapper = Tonemapper::new(&mut ctx, TonemapOperator::Reinhard).unwrap();
+
+        tonemapper
+            .draw(&mut ctx, &canvas, DrawParams::new())
+            .unwrap();
+        assert_eq!(ctx.graphics.shader, None);
+
+        let shader = Shader::from_fragment_string(&mut ctx, TONEMAP_FRAGMENT_SHADER).unwrap();
+        graphics::set_shader(&mut ctx, &shader);
+
+        tonemapper
+            .draw(&mut ctx, &canvas, DrawParams::new())
+            .unwrap();
+        assert_eq!(ctx.graphics.shader, Some(shader));
+    }
+}
@@ -0,0 +1,375 @@
+//! Functions and types relating to screen transitions.
+
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::graphics::{self, Color, DrawParams, Rectangle, Shader, Texture, WrapMode};
+use crate::math::easing;
+use crate::math::{Mat4, Vec2};
+use crate::time;
+use crate::window;
+use crate::Context;
+
+const IRIS_FRAGMENT_SHADER: &str = include_str!("../resources/iris.frag");
+
+/// The different visual styles that a [`Transition`] can use.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TransitionStyle {
+    /// Fades in from a solid color.
+    FadeColor(Color),
+
+    /// Fades from the previous frame to the current one.
+    CrossFade,
+
+    /// Wipes the previous frame away, with the edge moving from right to left.
+    WipeLeft,
+
+    /// Wipes the previous frame away, with the edge moving from left to right.
+    WipeRight,
+
+    /// Reveals the current frame through a circle that expands from the center
+    /// of the screen, covering up the previous frame.
+    CircleIris,
+}
+
+impl TransitionStyle {
+    fn needs_snapshot(self) -> bool {
+        !matches!(self, TransitionStyle::FadeColor(_))
+    }
+}
+
+/// A transition effect, which can be drawn over the top of your game while
+/// switching between scenes.
+///
+/// To use a transition, call [`start`](Transition::start) when you want the
+/// effect to begin, [`update`](Transition::update) in your
+/// [`State::update`](crate::State::update) method, and then [`draw`](Transition::draw)
+/// at the end of your [`State::draw`](crate::State::draw) method, after
+/// everything else has been drawn.
+///
+/// The effect will be drawn over the full size of the current render target
+/// (either the screen, or the canvas that is currently active), ignoring the
+/// current transform matrix.
+///
+/// # Performance
+///
+/// All of the styles other than [`TransitionStyle::FadeColor`] need to
+/// know what the previous frame looked like. To achieve this, the transition
+/// will copy the render target into an internal texture whenever `draw` is
+/// called while the transition is not running. This is fairly cheap, as the
+/// copy happens entirely on the GPU, but it does mean that you should avoid
+/// calling `draw` if you are not planning to use the transition.
+///
+/// # Examples
+///
+/// The [`tetras`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/tetras.rs)
+/// example uses a transition when switching between scenes.
+#[derive(Debug, Clone)]
+pub struct Transition {
+    style: TransitionStyle,
+    duration: Duration,
+    elapsed: Duration,
+    easing: fn(f32) -> f32,
+    snapshot: Option<Texture>,
+    iris_shader: Shader,
+}
+
+impl Transition {
+    /// Creates a new transition, with the given style.
+    ///
+    /// The transition will not be running until [`start`](Transition::start) is called.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn new(ctx: &mut Context, style: TransitionStyle) -> Result<Transition> {
+        Ok(Transition {
+            style,
+            duration: Duration::from_secs(0),
+            elapsed: Duration::from_secs(0),
+            easing: easing::linear,
+            snapshot: None,
+            iris_shader: Shader::from_fragment_string(ctx, IRIS_FRAGMENT_SHADER)?,
+        })
+    }
+
+    /// Starts the transition, running it over the specified duration.
+    ///
+    /// If the transition is already running, it will restart from the beginning.
+    pub fn start(&mut self, duration: Duration) {
+        self.duration = duration;
+        self.elapsed = Duration::from_secs(0);
+    }
+
+    /// Stops the transition, skipping straight to the end.
+    pub fn stop(&mut self) {
+        self.elapsed = self.duration;
+    }
+
+    /// Advances the transition's timer.
+    ///
+    /// This method uses the current [delta time](crate::time::get_delta_time)
+    /// to calculate how much time has passed.
+    pub fn update(&mut self, ctx: &Context) {
+        self.advance_by(time::get_delta_time(ctx));
+    }
+
+    /// Advances the transition's timer by a specified amount.
+    pub fn advance_by(&mut self, duration: Duration) {
+        self.elapsed = (self.elapsed + duration).min(self.duration);
+    }
+
+    /// Draws the transition over the top of the current render target.
+    ///
+    /// If the transition is not running, nothing will be drawn. However, if the style
+    /// requires a snapshot of the previous frame, it will be captured at this point.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error while capturing a snapshot
+    ///   of the render target.
+    pub fn draw(&mut self, ctx: &mut Context) -> Result {
+        if !self.is_running() {
+            if self.style.needs_snapshot() {
+                self.capture(ctx)?;
+            }
+
+            return Ok(());
+        }
+
+        let (width, height) = target_size(ctx);
+        let progress = (self.easing)(self.progress()).clamp(0.0, 1.0);
+
+        // Uniforms are set before any state is changed, so that nothing is left
+        // behind if setting them fails.
+        if let (TransitionStyle::CircleIris, Some(snapshot)) = (self.style, &self.snapshot) {
+            // The shader works in physical pixels, so we use the size of the snapshot
+            // rather than the size of the target.
+            let center = Vec2::new(snapshot.width() as f32, snapshot.height() as f32) / 2.0;
+            let radius = center.magnitude() * progress;
+
+            self.iris_shader.set_uniform(ctx, "u_center", center)?;
+            self.iris_shader.set_uniform(ctx, "u_radius", radius)?;
+        }
+
+        let previous_transform = graphics::get_transform_matrix(ctx);
+        graphics::set_transform_matrix(ctx, Mat4::identity());
+
+        match self.style {
+            TransitionStyle::FadeColor(color) => {
+                let texture = ctx.graphics.default_texture.clone();
+
+                graphics::set_texture(ctx, &texture);
+                graphics::push_quad(
+                    ctx,
+                    0.0,
+                    0.0,
+                    width,
+                    height,
+                    0.0,
+                    0.0,
+                    1.0,
+                    1.0,
+                    &DrawParams::new().color(color.with_alpha(color.a * (1.0 - progress))),
+                );
+            }
+
+            TransitionStyle::CrossFade => {
+                if let Some(snapshot) = &self.snapshot {
+                    snapshot.draw(
+                        ctx,
+                        DrawParams::new()
+                            .scale(snapshot_scale(snapshot, width, height))
+                            .color(Color::WHITE.with_alpha(1.0 - progress)),
+                    );
+                }
+            }
+
+            TransitionStyle::WipeLeft | TransitionStyle::WipeRight => {
+                if let Some(snapshot) = &self.snapshot {
+                    let scale = snapshot_scale(snapshot, width, height);
+
+                    let snapshot_width = snapshot.width() as f32;
+                    let snapshot_height = snapshot.height() as f32;
+                    let remaining = snapshot_width * (1.0 - progress);
+
+                    let x = match self.style {
+                        TransitionStyle::WipeRight => snapshot_width - remaining,
+                        _ => 0.0,
+                    };
+
+                    snapshot.draw_region(
+                        ctx,
+                        Rectangle::new(x, 0.0, remaining, snapshot_height),
+                        DrawParams::new()
+                            .position(Vec2::new(x * scale.x, 0.0))
+                            .scale(scale),
+                    );
+                }
+            }
+
+            TransitionStyle::CircleIris => {
+                if let Some(snapshot) = &self.snapshot {
+                    let previous_shader = ctx.graphics.shader.clone();
+
+                    graphics::set_shader(ctx, &self.iris_shader);
+
+                    snapshot.draw(
+                        ctx,
+                        DrawParams::new().scale(snapshot_scale(snapshot, width, height)),
+                    );
+
+                    graphics::set_shader_ex(ctx, previous_shader.as_ref());
+                }
+            }
+        }
+
+        graphics::set_transform_matrix(ctx, previous_transform);
+
+        Ok(())
+    }
+
+    /// Returns the style of the transition.
+    pub fn style(&self) -> TransitionStyle {
+        self.style
+    }
+
+    /// Sets the style of the transition.
+    ///
+    /// Changing the style while the transition is running may cause visual glitches,
+    /// as the previous frame may not have been captured.
+    pub fn set_style(&mut self, style: TransitionStyle) {
+        self.style = style;
+    }
+
+    /// Returns the easing function used by the transition.
+    pub fn easing(&self) -> fn(f32) -> f32 {
+        self.easing
+    }
+
+    /// Sets the easing function used by the transition.
+    ///
+    /// Any of the functions from the [`easing`](crate::math::easing) module can be used.
+    /// Curves that overshoot (such as [`back_out`](crate::math::easing::back_out)) will
+    /// be clamped, so that the effect never goes past its start or end point.
+    ///
+    /// By default, the transition will use [`linear`](crate::math::easing::linear) easing.
+    pub fn set_easing(&mut self, easing: fn(f32) -> f32) {
+        self.easing = easing;
+    }
+
+    /// Returns how far through the transition is, from `0.0` to `1.0`.
+    ///
+    /// This value does not have the transition's [easing](Transition::easing) applied.
+    ///
+    /// If the transition has finished (or has never been started), this will return `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+        }
+    }
+
+    /// Returns `true` if the transition is currently running.
+    pub fn is_running(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /// Returns `true` if the transition has finished (or has never been started).
+    pub fn is_finished(&self) -> bool {
+        !self.is_running()
+    }
+
+    /// Returns `true` if input should not be processed, due to the transition running.
+    ///
+    /// Tetra does not block input automatically - this is just a hint that you can use in
+    /// your own code, to prevent the player from interacting with a scene before it has
+    /// fully appeared.
+    pub fn is_blocking_input(&self) -> bool {
+        self.is_running()
+    }
+
+    fn capture(&mut self, ctx: &mut Context) -> Result {
+        graphics::flush(ctx);
+
+        let canvas = ctx.graphics.canvas.clone();
+
+        let (width, height) = match &canvas {
+            Some(canvas) => canvas.size(),
+            None => window::get_physical_size(ctx),
+        };
+
+        let reuse = matches!(&self.snapshot, Some(s) if s.size() == (width, height));
+
+        if !reuse {
+            self.snapshot = Some(Texture::with_device_empty(
+                &mut ctx.device,
+                width,
+                height,
+                ctx.graphics.default_filter_mode,
+                WrapMode::Clamp,
+            )?);
+        }
+
+        if let Some(snapshot) = &self.snapshot {
+            ctx.device.copy_to_texture(
+                canvas.as_ref().map(|c| &*c.handle),
+                &snapshot.data.handle,
+                graphics::target_origin(ctx).needs_flip(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn target_size(ctx: &Context) -> (f32, f32) {
+    let (width, height) = match (graphics::get_viewport(ctx), &ctx.graphics.canvas) {
+        (Some(viewport), _) => (viewport.width, viewport.height),
+        (None, Some(canvas)) => canvas.size(),
+        (None, None) => window::get_size(ctx),
+    };
+
+    (width as f32, height as f32)
+}
+
+fn snapshot_scale(snapshot: &Texture, width: f32, height: f32) -> Vec2<f32> {
+    Vec2::new(
+        width / snapshot.width() as f32,
+        height / snapshot.height() as f32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContextBuilder;
+
+    #[test]
+    #[ignore = "requires an OpenGL context"]
+    fn iris_restores_previous_state() {
+        let mut ctx = ContextBuilder::new("iris_restores_previous_state", 64, 64)
+            .headless(true)
+            .build()
+            .unwrap();
+
+        let mut transition = Transition::new(&mut ctx, TransitionStyle::CircleIris).unwrap();
+
+        // The first draw captures the snapshot, and the second draws the iris.
+        transition.draw(&mut ctx).unwrap();
+        transition.start(Duration::from_secs(1));
+        transition.advance_by(Duration::from_millis(500));
+
+        let transform = Mat4::translation_2d(Vec2::new(10.0, 20.0));
+        graphics::set_transform_matrix(&mut ctx, transform);
+
+        transition.draw(&mut ctx).unwrap();
+
+        assert_eq!(ctx.graphics.shader, None);
+        assert_eq!(graphics::get_transform_matrix(&ctx), transform);
+    }
+}
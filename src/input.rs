@@ -20,18 +20,26 @@
 //! The [`gamepad`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/gamepad.rs)
 //! example demonstrates how to handle gamepad input.
 //!
+//! The [`action_map`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/action_map.rs)
+//! example demonstrates how to map multiple input devices to game-specific actions.
+//!
 //! The [`text_input`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/text_input.rs)
 //! example demonstrates how to handle text entry.
 
+mod actions;
 mod gamepad;
 mod keyboard;
 mod mouse;
 
-use hashbrown::HashSet;
+use std::time::{Duration, Instant};
+
+use hashbrown::{HashMap, HashSet};
 
+use crate::graphics::Rectangle;
 use crate::math::Vec2;
 use crate::{Context, Result};
 
+pub use actions::*;
 pub use gamepad::*;
 pub use keyboard::*;
 pub use mouse::*;
@@ -46,12 +54,20 @@ pub(crate) struct InputContext {
     mouse_buttons_down: HashSet<MouseButton>,
     mouse_buttons_pressed: HashSet<MouseButton>,
     mouse_buttons_released: HashSet<MouseButton>,
+    mouse_buttons_double_clicked: HashSet<MouseButton>,
+    last_mouse_clicks: HashMap<MouseButton, Instant>,
+    double_click_interval: Duration,
     mouse_position: Vec2<f32>,
-    mouse_wheel_movement: Vec2<i32>,
+    mouse_wheel_movement: Vec2<f32>,
+    mouse_delta: Vec2<f32>,
+
+    focused: bool,
 
     current_text_input: Option<String>,
 
     pads: Vec<Option<GamepadState>>,
+    default_gamepad_deadzone: f32,
+    gamepad_deadzones: HashMap<usize, f32>,
 }
 
 impl InputContext {
@@ -66,29 +82,130 @@ impl InputContext {
             mouse_buttons_down: HashSet::new(),
             mouse_buttons_pressed: HashSet::new(),
             mouse_buttons_released: HashSet::new(),
+            mouse_buttons_double_clicked: HashSet::new(),
+            last_mouse_clicks: HashMap::new(),
+            double_click_interval: DEFAULT_DOUBLE_CLICK_INTERVAL,
             mouse_position: Vec2::zero(),
             mouse_wheel_movement: Vec2::zero(),
+            mouse_delta: Vec2::zero(),
+
+            focused: true,
 
             current_text_input: None,
 
             pads: Vec::new(),
+            default_gamepad_deadzone: 0.0,
+            gamepad_deadzones: HashMap::new(),
         }
     }
-}
 
-pub(crate) fn clear(ctx: &mut Context) {
-    ctx.input.keys_pressed.clear();
-    ctx.input.keys_released.clear();
-    ctx.input.mouse_buttons_pressed.clear();
-    ctx.input.mouse_buttons_released.clear();
-    ctx.input.mouse_wheel_movement = Vec2::zero();
+    fn press_key(&mut self, key: Key, key_repeat_enabled: bool) -> bool {
+        let was_up = self.keys_down.insert(key);
+
+        if was_up || key_repeat_enabled {
+            self.keys_pressed.insert(key);
+        }
+
+        was_up
+    }
+
+    fn release_key(&mut self, key: Key) -> bool {
+        let was_down = self.keys_down.remove(&key);
+
+        if was_down {
+            self.keys_released.insert(key);
+        }
+
+        was_down
+    }
 
-    ctx.input.current_text_input = None;
+    fn is_key_chord_pressed(&self, keys: &[Key]) -> bool {
+        match keys.split_last() {
+            Some((last, held)) => {
+                self.keys_pressed.contains(last)
+                    && held.iter().all(|key| self.keys_down.contains(key))
+            }
 
-    for pad in ctx.input.pads.iter_mut().flatten() {
-        pad.buttons_pressed.clear();
-        pad.buttons_released.clear();
+            None => false,
+        }
+    }
+
+    fn press_mouse_button(&mut self, button: MouseButton, now: Instant) -> bool {
+        let was_up = self.mouse_buttons_down.insert(button);
+
+        if was_up {
+            self.mouse_buttons_pressed.insert(button);
+
+            let is_double_click = self.last_mouse_clicks.get(&button).is_some_and(|&last| {
+                now.saturating_duration_since(last) <= self.double_click_interval
+            });
+
+            // A double click consumes the first click, so that a triple click
+            // does not get reported as two double clicks.
+            if is_double_click {
+                self.mouse_buttons_double_clicked.insert(button);
+                self.last_mouse_clicks.remove(&button);
+            } else {
+                self.last_mouse_clicks.insert(button, now);
+            }
+        }
+
+        was_up
     }
+
+    fn release_mouse_button(&mut self, button: MouseButton) -> bool {
+        let was_down = self.mouse_buttons_down.remove(&button);
+
+        if was_down {
+            self.mouse_buttons_released.insert(button);
+        }
+
+        was_down
+    }
+
+    fn apply_mouse_motion(&mut self, delta: Vec2<f32>) {
+        if self.focused {
+            self.mouse_delta += delta;
+        }
+    }
+
+    fn apply_mouse_wheel_movement(&mut self, amount: Vec2<f32>) {
+        self.mouse_wheel_movement += amount;
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+
+        if !focused {
+            self.mouse_delta = Vec2::zero();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.mouse_buttons_pressed.clear();
+        self.mouse_buttons_released.clear();
+        self.mouse_buttons_double_clicked.clear();
+        self.mouse_wheel_movement = Vec2::zero();
+        self.mouse_delta = Vec2::zero();
+
+        self.current_text_input = None;
+
+        for pad in self.pads.iter_mut().flatten() {
+            pad.buttons_pressed.clear();
+            pad.buttons_released.clear();
+            pad.previous_axis_state.clone_from(&pad.current_axis_state);
+        }
+    }
+}
+
+pub(crate) fn clear(ctx: &mut Context) {
+    ctx.input.clear();
+}
+
+pub(crate) fn set_focused(ctx: &mut Context, focused: bool) {
+    ctx.input.set_focused(focused);
 }
 
 /// Returns the text that the user entered since the last update.
@@ -97,6 +214,41 @@ pub fn get_text_input(ctx: &Context) -> Option<&str> {
     ctx.input.current_text_input.as_deref()
 }
 
+/// Starts accepting text input.
+///
+/// While text input is active, [`TextInput`](crate::Event::TextInput) and
+/// [`TextEditing`](crate::Event::TextEditing) events will be fired, and
+/// [`get_text_input`] will return the text that was entered. On some platforms, this
+/// will also display an on-screen keyboard or an input method editor (IME).
+///
+/// Text input is active by default, so you only need to call this if you have
+/// previously called [`stop_text_input`].
+pub fn start_text_input(ctx: &mut Context) {
+    ctx.window.start_text_input();
+}
+
+/// Stops accepting text input.
+///
+/// This can be used to hide the on-screen keyboard or IME when the player is not typing
+/// into a text field, and to stop IMEs from capturing keystrokes that are meant for
+/// controlling the game.
+pub fn stop_text_input(ctx: &mut Context) {
+    ctx.window.stop_text_input();
+}
+
+/// Returns whether or not text input is currently active.
+pub fn is_text_input_active(ctx: &Context) -> bool {
+    ctx.window.is_text_input_active()
+}
+
+/// Sets the area of the window where text is being entered, in window co-ordinates.
+///
+/// Input method editors (IMEs) use this to position their candidate list next to the
+/// text, rather than covering it up.
+pub fn set_text_input_rect(ctx: &mut Context, rect: Rectangle<i32>) {
+    ctx.window.set_text_input_rect(rect);
+}
+
 /// Gets the text currently stored in the system's clipboard.
 ///
 /// # Errors
@@ -123,3 +275,192 @@ pub(crate) fn push_text_input(ctx: &mut Context, text: &str) {
         x @ None => *x = Some(text.to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_delta_accumulates_until_cleared() {
+        let mut input = InputContext::new();
+
+        input.apply_mouse_motion(Vec2::new(3.0, -1.0));
+        input.apply_mouse_motion(Vec2::new(2.0, 4.0));
+
+        assert_eq!(Vec2::new(5.0, 3.0), input.mouse_delta);
+
+        input.clear();
+
+        assert_eq!(Vec2::zero(), input.mouse_delta);
+    }
+
+    #[test]
+    fn mouse_delta_is_zero_while_unfocused() {
+        let mut input = InputContext::new();
+
+        input.apply_mouse_motion(Vec2::new(3.0, -1.0));
+        input.set_focused(false);
+
+        assert_eq!(Vec2::zero(), input.mouse_delta);
+
+        input.apply_mouse_motion(Vec2::new(2.0, 4.0));
+
+        assert_eq!(Vec2::zero(), input.mouse_delta);
+
+        input.set_focused(true);
+        input.apply_mouse_motion(Vec2::new(2.0, 4.0));
+
+        assert_eq!(Vec2::new(2.0, 4.0), input.mouse_delta);
+    }
+
+    #[test]
+    fn mouse_wheel_movement_is_rounded() {
+        let mut input = InputContext::new();
+
+        input.apply_mouse_wheel_movement(Vec2::new(0.0, 0.25));
+        input.apply_mouse_wheel_movement(Vec2::new(-0.75, 0.5));
+
+        assert_eq!(Vec2::new(-0.75, 0.75), input.mouse_wheel_movement);
+        assert_eq!(
+            Vec2::new(-1, 1),
+            round_wheel_movement(input.mouse_wheel_movement)
+        );
+
+        input.clear();
+
+        assert_eq!(Vec2::zero(), input.mouse_wheel_movement);
+    }
+
+    #[test]
+    fn key_chord_is_only_pressed_by_last_key() {
+        let mut input = InputContext::new();
+        let chord = [Key::LeftCtrl, Key::S];
+
+        input.press_key(Key::LeftCtrl, false);
+
+        assert!(!input.is_key_chord_pressed(&chord));
+
+        input.clear();
+        input.press_key(Key::S, false);
+
+        assert!(input.is_key_chord_pressed(&chord));
+
+        // The chord should only fire on the frame that it was completed.
+        input.clear();
+
+        assert!(!input.is_key_chord_pressed(&chord));
+
+        // Pressing the keys in the wrong order should not fire the chord.
+        input.release_key(Key::LeftCtrl);
+        input.clear();
+        input.press_key(Key::LeftCtrl, false);
+
+        assert!(!input.is_key_chord_pressed(&chord));
+
+        // Pressing all of the keys at once should fire the chord.
+        input.release_key(Key::LeftCtrl);
+        input.release_key(Key::S);
+        input.clear();
+        input.press_key(Key::LeftCtrl, false);
+        input.press_key(Key::S, false);
+
+        assert!(input.is_key_chord_pressed(&chord));
+        assert!(!input.is_key_chord_pressed(&[]));
+    }
+
+    #[test]
+    fn double_click_is_detected_within_interval() {
+        let mut input = InputContext::new();
+        let start = Instant::now();
+
+        let click = |input: &mut InputContext, offset_ms| {
+            input.clear();
+            input.press_mouse_button(MouseButton::Left, start + Duration::from_millis(offset_ms));
+            input.release_mouse_button(MouseButton::Left);
+
+            input
+                .mouse_buttons_double_clicked
+                .contains(&MouseButton::Left)
+        };
+
+        assert!(!click(&mut input, 0));
+        assert!(click(&mut input, 200));
+
+        // A third click should start a new double click, rather than completing another.
+        assert!(!click(&mut input, 400));
+        assert!(click(&mut input, 700));
+
+        // Clicks that are too far apart should not count.
+        assert!(!click(&mut input, 1500));
+        assert!(!click(&mut input, 1801));
+
+        input.double_click_interval = Duration::from_millis(500);
+
+        assert!(click(&mut input, 2200));
+
+        input.clear();
+
+        assert!(input.mouse_buttons_double_clicked.is_empty());
+    }
+
+    #[test]
+    fn double_click_tracks_buttons_separately() {
+        let mut input = InputContext::new();
+        let start = Instant::now();
+
+        input.press_mouse_button(MouseButton::Left, start);
+        input.release_mouse_button(MouseButton::Left);
+        input.clear();
+
+        input.press_mouse_button(MouseButton::Right, start + Duration::from_millis(100));
+
+        assert!(input.mouse_buttons_double_clicked.is_empty());
+
+        input.press_mouse_button(MouseButton::Left, start + Duration::from_millis(150));
+
+        assert!(input
+            .mouse_buttons_double_clicked
+            .contains(&MouseButton::Left));
+        assert!(!input
+            .mouse_buttons_double_clicked
+            .contains(&MouseButton::Right));
+    }
+
+    #[test]
+    fn key_modifier_state_reports_modifiers() {
+        let state = KeyModifierState {
+            ctrl: true,
+            alt: false,
+            shift: true,
+        };
+
+        assert!(state.is_down(KeyModifier::Ctrl));
+        assert!(!state.is_down(KeyModifier::Alt));
+        assert!(state.is_down(KeyModifier::Shift));
+        assert!(!KeyModifierState::default().is_down(KeyModifier::Ctrl));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn input_enums_serde_round_trip() {
+        let json = serde_json::to_string(&(
+            Key::A,
+            KeyLabel::Escape,
+            MouseButton::X1,
+            GamepadButton::Start,
+            GamepadAxis::LeftTrigger,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            (
+                Key::A,
+                KeyLabel::Escape,
+                MouseButton::X1,
+                GamepadButton::Start,
+                GamepadAxis::LeftTrigger,
+            ),
+            serde_json::from_str(&json).unwrap()
+        );
+    }
+}
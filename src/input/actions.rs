@@ -0,0 +1,451 @@
+use crate::Context;
+
+use super::{
+    get_gamepad, is_gamepad_button_down, is_gamepad_button_pressed, is_gamepad_button_released,
+    is_key_down, is_key_pressed, is_key_released, is_mouse_button_down, is_mouse_button_pressed,
+    is_mouse_button_released, GamepadAxis, GamepadButton, Key, MouseButton,
+};
+
+/// An input that can trigger an action in an [`ActionMap`].
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Binding {
+    /// A key on the keyboard.
+    Key(Key),
+
+    /// A button on the mouse.
+    MouseButton(MouseButton),
+
+    /// A button on a gamepad.
+    GamepadButton {
+        /// The ID of the gamepad.
+        gamepad_id: usize,
+
+        /// The button.
+        button: GamepadButton,
+    },
+
+    /// An axis on a gamepad, which is treated as being down when it moves past
+    /// the threshold.
+    ///
+    /// If the threshold is positive, the axis must be moved past it in the positive
+    /// direction. If the threshold is negative, the axis must be moved past it in the
+    /// negative direction. This allows you to bind actions to each direction of a stick,
+    /// or to the triggers.
+    GamepadAxis {
+        /// The ID of the gamepad.
+        gamepad_id: usize,
+
+        /// The axis.
+        axis: GamepadAxis,
+
+        /// The position that the axis must move past.
+        threshold: f32,
+    },
+}
+
+/// An input that can control an axis in an [`ActionMap`].
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AxisBinding {
+    /// A pair of keys on the keyboard.
+    Keys {
+        /// The key that moves the axis in the negative direction.
+        negative: Key,
+
+        /// The key that moves the axis in the positive direction.
+        positive: Key,
+    },
+
+    /// A pair of buttons on a gamepad (e.g. the D-pad).
+    GamepadButtons {
+        /// The ID of the gamepad.
+        gamepad_id: usize,
+
+        /// The button that moves the axis in the negative direction.
+        negative: GamepadButton,
+
+        /// The button that moves the axis in the positive direction.
+        positive: GamepadButton,
+    },
+
+    /// An analog axis on a gamepad.
+    ///
+    /// The [deadzone](ActionMap::set_deadzone) of the action map will be applied to
+    /// this axis.
+    GamepadAxis {
+        /// The ID of the gamepad.
+        gamepad_id: usize,
+
+        /// The axis.
+        axis: GamepadAxis,
+    },
+}
+
+/// A mapping from game-specific actions and axes to the inputs that control them.
+///
+/// Rather than checking for specific keys or buttons in your game code, you can
+/// bind each action to one or more inputs, and then query the state of the action
+/// via [`is_action_down`], [`get_axis`], etc. This makes it easy to support multiple
+/// input devices at once, and to allow players to rebind their controls.
+///
+/// The type parameter will usually be an enum defined by your game, but any type that
+/// can be compared for equality can be used. Actions and axes are stored separately,
+/// so the same type (or even the same value) can be used for both.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature. This can be used to save the player's
+/// bindings to a config file.
+///
+/// # Examples
+///
+/// The [`action_map`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/action_map.rs)
+/// example demonstrates how to use an action map to handle input from a keyboard
+/// and a gamepad at the same time.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActionMap<T> {
+    actions: Vec<(T, Vec<Binding>)>,
+    axes: Vec<(T, Vec<AxisBinding>)>,
+    deadzone: f32,
+}
+
+impl<T> ActionMap<T>
+where
+    T: PartialEq,
+{
+    /// Creates a new, empty action map.
+    ///
+    /// The deadzone defaults to `0.2`.
+    pub fn new() -> ActionMap<T> {
+        ActionMap {
+            actions: Vec::new(),
+            axes: Vec::new(),
+            deadzone: 0.2,
+        }
+    }
+
+    /// Binds an input to an action.
+    ///
+    /// An action can have multiple bindings - it will be considered down if any of them
+    /// are down.
+    pub fn bind_action(&mut self, action: T, binding: Binding) -> &mut ActionMap<T> {
+        match self.actions.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, bindings)) => bindings.push(binding),
+            None => self.actions.push((action, vec![binding])),
+        }
+
+        self
+    }
+
+    /// Binds an input to an axis.
+    ///
+    /// An axis can have multiple bindings - the one that is moved the furthest from the
+    /// center will be used.
+    pub fn bind_axis(&mut self, axis: T, binding: AxisBinding) -> &mut ActionMap<T> {
+        match self.axes.iter_mut().find(|(a, _)| *a == axis) {
+            Some((_, bindings)) => bindings.push(binding),
+            None => self.axes.push((axis, vec![binding])),
+        }
+
+        self
+    }
+
+    /// Removes all of the bindings for an action.
+    pub fn unbind_action(&mut self, action: &T) {
+        self.actions.retain(|(a, _)| a != action);
+    }
+
+    /// Removes all of the bindings for an axis.
+    pub fn unbind_axis(&mut self, axis: &T) {
+        self.axes.retain(|(a, _)| a != axis);
+    }
+
+    /// Returns the bindings for an action.
+    pub fn get_action_bindings(&self, action: &T) -> &[Binding] {
+        self.actions
+            .iter()
+            .find(|(a, _)| a == action)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the bindings for an axis.
+    pub fn get_axis_bindings(&self, axis: &T) -> &[AxisBinding] {
+        self.axes
+            .iter()
+            .find(|(a, _)| a == axis)
+            .map(|(_, bindings)| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the deadzone that is applied to analog axis bindings.
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Sets the deadzone that is applied to analog axis bindings.
+    ///
+    /// Axis movement smaller than the deadzone will be ignored, and the remaining range
+    /// will be rescaled so that the axis still moves smoothly from `0.0` to `1.0`.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 1.0);
+    }
+}
+
+impl<T> Default for ActionMap<T>
+where
+    T: PartialEq,
+{
+    fn default() -> ActionMap<T> {
+        ActionMap::new()
+    }
+}
+
+/// Returns true if any of the inputs bound to the specified action are currently down.
+pub fn is_action_down<T>(ctx: &Context, map: &ActionMap<T>, action: T) -> bool
+where
+    T: PartialEq,
+{
+    map.get_action_bindings(&action)
+        .iter()
+        .any(|b| is_binding_down(ctx, b))
+}
+
+/// Returns true if none of the inputs bound to the specified action are currently down.
+pub fn is_action_up<T>(ctx: &Context, map: &ActionMap<T>, action: T) -> bool
+where
+    T: PartialEq,
+{
+    !is_action_down(ctx, map, action)
+}
+
+/// Returns true if the specified action was pressed since the last update.
+///
+/// If the action was already being held down via a different input, pressing another
+/// one will not trigger this.
+pub fn is_action_pressed<T>(ctx: &Context, map: &ActionMap<T>, action: T) -> bool
+where
+    T: PartialEq,
+{
+    let bindings = map.get_action_bindings(&action);
+
+    bindings.iter().any(|b| is_binding_pressed(ctx, b))
+        && !bindings.iter().any(|b| was_binding_down(ctx, b))
+}
+
+/// Returns true if the specified action was released since the last update.
+///
+/// If the action is still being held down via a different input, releasing another
+/// one will not trigger this.
+pub fn is_action_released<T>(ctx: &Context, map: &ActionMap<T>, action: T) -> bool
+where
+    T: PartialEq,
+{
+    let bindings = map.get_action_bindings(&action);
+
+    bindings.iter().any(|b| is_binding_released(ctx, b))
+        && !bindings.iter().any(|b| is_binding_down(ctx, b))
+}
+
+/// Returns the current position of the specified axis, between `-1.0` and `1.0`.
+///
+/// If multiple inputs are bound to the axis, the one that is furthest from the center
+/// will be used.
+pub fn get_axis<T>(ctx: &Context, map: &ActionMap<T>, axis: T) -> f32
+where
+    T: PartialEq,
+{
+    map.get_axis_bindings(&axis)
+        .iter()
+        .map(|b| match *b {
+            AxisBinding::Keys { negative, positive } => {
+                digital_axis(is_key_down(ctx, negative), is_key_down(ctx, positive))
+            }
+
+            AxisBinding::GamepadButtons {
+                gamepad_id,
+                negative,
+                positive,
+            } => digital_axis(
+                is_gamepad_button_down(ctx, gamepad_id, negative),
+                is_gamepad_button_down(ctx, gamepad_id, positive),
+            ),
+
+            AxisBinding::GamepadAxis { gamepad_id, axis } => {
+                apply_deadzone(axis_position(ctx, gamepad_id, axis, false), map.deadzone)
+            }
+        })
+        .fold(0.0, |furthest, value| {
+            if value.abs() > f32::abs(furthest) {
+                value
+            } else {
+                furthest
+            }
+        })
+}
+
+fn is_binding_down(ctx: &Context, binding: &Binding) -> bool {
+    match *binding {
+        Binding::Key(key) => is_key_down(ctx, key),
+        Binding::MouseButton(button) => is_mouse_button_down(ctx, button),
+        Binding::GamepadButton { gamepad_id, button } => {
+            is_gamepad_button_down(ctx, gamepad_id, button)
+        }
+        Binding::GamepadAxis {
+            gamepad_id,
+            axis,
+            threshold,
+        } => past_threshold(axis_position(ctx, gamepad_id, axis, false), threshold),
+    }
+}
+
+fn is_binding_pressed(ctx: &Context, binding: &Binding) -> bool {
+    match *binding {
+        Binding::Key(key) => is_key_pressed(ctx, key),
+        Binding::MouseButton(button) => is_mouse_button_pressed(ctx, button),
+        Binding::GamepadButton { gamepad_id, button } => {
+            is_gamepad_button_pressed(ctx, gamepad_id, button)
+        }
+        Binding::GamepadAxis {
+            gamepad_id,
+            axis,
+            threshold,
+        } => {
+            past_threshold(axis_position(ctx, gamepad_id, axis, false), threshold)
+                && !past_threshold(axis_position(ctx, gamepad_id, axis, true), threshold)
+        }
+    }
+}
+
+fn is_binding_released(ctx: &Context, binding: &Binding) -> bool {
+    match *binding {
+        Binding::Key(key) => is_key_released(ctx, key),
+        Binding::MouseButton(button) => is_mouse_button_released(ctx, button),
+        Binding::GamepadButton { gamepad_id, button } => {
+            is_gamepad_button_released(ctx, gamepad_id, button)
+        }
+        Binding::GamepadAxis {
+            gamepad_id,
+            axis,
+            threshold,
+        } => {
+            !past_threshold(axis_position(ctx, gamepad_id, axis, false), threshold)
+                && past_threshold(axis_position(ctx, gamepad_id, axis, true), threshold)
+        }
+    }
+}
+
+/// Returns whether the binding was down before the current update.
+fn was_binding_down(ctx: &Context, binding: &Binding) -> bool {
+    match *binding {
+        Binding::GamepadAxis {
+            gamepad_id,
+            axis,
+            threshold,
+        } => past_threshold(axis_position(ctx, gamepad_id, axis, true), threshold),
+
+        _ => {
+            (is_binding_down(ctx, binding) && !is_binding_pressed(ctx, binding))
+                || is_binding_released(ctx, binding)
+        }
+    }
+}
+
+fn axis_position(ctx: &Context, gamepad_id: usize, axis: GamepadAxis, previous: bool) -> f32 {
+    let state = get_gamepad(ctx, gamepad_id).map(|pad| {
+        if previous {
+            &pad.previous_axis_state
+        } else {
+            &pad.current_axis_state
+        }
+    });
+
+    state
+        .and_then(|state| state.get(&axis))
+        .copied()
+        .unwrap_or(0.0)
+}
+
+fn past_threshold(position: f32, threshold: f32) -> bool {
+    if threshold < 0.0 {
+        position <= threshold
+    } else {
+        position >= threshold
+    }
+}
+
+fn digital_axis(negative: bool, positive: bool) -> f32 {
+    match (negative, positive) {
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        _ => 0.0,
+    }
+}
+
+fn apply_deadzone(position: f32, deadzone: f32) -> f32 {
+    if position.abs() <= deadzone {
+        0.0
+    } else {
+        position.signum() * (position.abs() - deadzone) / (1.0 - deadzone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Action {
+        Jump,
+        Shoot,
+    }
+
+    #[test]
+    fn bindings_are_grouped_by_action() {
+        let mut map = ActionMap::new();
+
+        map.bind_action(Action::Jump, Binding::Key(Key::Space))
+            .bind_action(Action::Shoot, Binding::MouseButton(MouseButton::Left))
+            .bind_action(
+                Action::Jump,
+                Binding::GamepadButton {
+                    gamepad_id: 0,
+                    button: GamepadButton::A,
+                },
+            );
+
+        assert_eq!(map.get_action_bindings(&Action::Jump).len(), 2);
+        assert_eq!(map.get_action_bindings(&Action::Shoot).len(), 1);
+
+        map.unbind_action(&Action::Jump);
+
+        assert!(map.get_action_bindings(&Action::Jump).is_empty());
+        assert_eq!(map.get_action_bindings(&Action::Shoot).len(), 1);
+    }
+
+    #[test]
+    fn thresholds_and_deadzones() {
+        assert!(past_threshold(0.6, 0.5));
+        assert!(!past_threshold(0.4, 0.5));
+        assert!(past_threshold(-0.6, -0.5));
+        assert!(!past_threshold(0.6, -0.5));
+
+        assert_eq!(apply_deadzone(0.1, 0.2), 0.0);
+        assert_eq!(apply_deadzone(-0.2, 0.2), 0.0);
+        assert_eq!(apply_deadzone(1.0, 0.2), 1.0);
+        assert_eq!(apply_deadzone(-0.625, 0.25), -0.5);
+    }
+}
@@ -1,6 +1,6 @@
 use hashbrown::{HashMap, HashSet};
 
-use crate::math::Vec2;
+use crate::math::{Vec2, Vec3};
 use crate::Context;
 
 pub(crate) struct GamepadState {
@@ -9,6 +9,8 @@ pub(crate) struct GamepadState {
     pub buttons_pressed: HashSet<GamepadButton>,
     pub buttons_released: HashSet<GamepadButton>,
     pub current_axis_state: HashMap<GamepadAxis, f32>,
+    pub previous_axis_state: HashMap<GamepadAxis, f32>,
+    pub sensor_data: HashMap<GamepadSensor, Vec3<f32>>,
 }
 
 impl GamepadState {
@@ -19,6 +21,8 @@ impl GamepadState {
             buttons_pressed: HashSet::new(),
             buttons_released: HashSet::new(),
             current_axis_state: HashMap::new(),
+            previous_axis_state: HashMap::new(),
+            sensor_data: HashMap::new(),
         }
     }
 
@@ -45,6 +49,10 @@ impl GamepadState {
     pub(crate) fn set_axis_position(&mut self, axis: GamepadAxis, value: f32) {
         self.current_axis_state.insert(axis, value);
     }
+
+    pub(crate) fn set_sensor_data(&mut self, sensor: GamepadSensor, data: Vec3<f32>) {
+        self.sensor_data.insert(sensor, data);
+    }
 }
 
 /// A button on a gamepad.
@@ -111,6 +119,98 @@ pub enum GamepadStick {
     RightStick,
 }
 
+/// A motion sensor on a gamepad.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadSensor {
+    /// The gyroscope, which measures the rate of rotation around each axis, in
+    /// radians per second.
+    Gyro,
+
+    /// The accelerometer, which measures the acceleration along each axis (including
+    /// gravity), in meters per second squared.
+    Accelerometer,
+}
+
+/// The stage of a touch on a gamepad's touchpad.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamepadTouchPhase {
+    /// A finger was placed on the touchpad.
+    Started,
+
+    /// A finger was moved while touching the touchpad.
+    Moved,
+
+    /// A finger was lifted from the touchpad.
+    Ended,
+}
+
+/// The type of a gamepad.
+///
+/// This can be used to pick which button glyphs to display.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamepadType {
+    /// An Xbox 360 controller.
+    Xbox360,
+
+    /// An Xbox One or Xbox Series controller.
+    XboxOne,
+
+    /// A PlayStation 3 controller.
+    PlayStation3,
+
+    /// A PlayStation 4 (DualShock 4) controller.
+    PlayStation4,
+
+    /// A PlayStation 5 (DualSense) controller.
+    PlayStation5,
+
+    /// A Nintendo Switch Pro controller.
+    NintendoSwitchPro,
+
+    /// One or two Nintendo Switch Joy-Cons.
+    NintendoSwitchJoyCon,
+
+    /// The type of the gamepad could not be determined.
+    Unknown,
+}
+
+/// How a gamepad is being powered.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GamepadPowerInfo {
+    /// The gamepad is connected via a cable, and is not running on battery power.
+    Wired,
+
+    /// The gamepad is running on battery power.
+    Battery(GamepadBatteryLevel),
+}
+
+/// The approximate charge level of a gamepad's battery.
+///
+/// Most platforms do not report the exact charge level, so it is grouped into buckets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GamepadBatteryLevel {
+    /// The battery is almost empty (5% or less).
+    Empty,
+
+    /// The battery is low (20% or less).
+    Low,
+
+    /// The battery is partially charged (70% or less).
+    Medium,
+
+    /// The battery is full, or almost full (more than 70%).
+    Full,
+}
+
 /// Returns true if the specified gamepad is currently connected.
 pub fn is_gamepad_connected(ctx: &Context, gamepad_id: usize) -> bool {
     get_gamepad(ctx, gamepad_id).is_some()
@@ -123,6 +223,102 @@ pub fn get_gamepad_name(ctx: &Context, gamepad_id: usize) -> Option<String> {
         .map(|id| ctx.window.get_gamepad_name(id))
 }
 
+/// Returns the type of the specified gamepad, or [`None`] if it is not connected.
+pub fn get_gamepad_type(ctx: &Context, gamepad_id: usize) -> Option<GamepadType> {
+    get_gamepad(ctx, gamepad_id)
+        .map(|g| g.platform_id)
+        .map(|id| ctx.window.get_gamepad_type(id))
+}
+
+/// Returns how the specified gamepad is being powered, and its battery level if
+/// it is wireless.
+///
+/// If the gamepad is disconnected, or the platform cannot determine its power
+/// state, this will return [`None`].
+pub fn get_gamepad_power_info(ctx: &Context, gamepad_id: usize) -> Option<GamepadPowerInfo> {
+    get_gamepad(ctx, gamepad_id)
+        .map(|g| g.platform_id)
+        .and_then(|id| ctx.window.get_gamepad_power_info(id))
+}
+
+/// Returns the player index of the specified gamepad, or [`None`] if it is
+/// not connected or does not have a player index.
+///
+/// Note that this is not necessarily the same as the `gamepad_id`.
+pub fn get_gamepad_player_index(ctx: &Context, gamepad_id: usize) -> Option<usize> {
+    get_gamepad(ctx, gamepad_id)
+        .map(|g| g.platform_id)
+        .and_then(|id| ctx.window.get_gamepad_player_index(id))
+}
+
+/// Sets the player index of the specified gamepad, or clears it if `None` is passed.
+///
+/// On gamepads that have player indicator LEDs (e.g. Xbox 360, PlayStation 5 and
+/// Switch controllers), this will light up the LED for that player. Indexes are
+/// zero-based, so an index of `0` will show as player 1.
+///
+/// If the gamepad is disconnected, this will have no effect.
+pub fn set_gamepad_player_index(ctx: &mut Context, gamepad_id: usize, index: Option<usize>) {
+    if let Some(platform_id) = get_gamepad(ctx, gamepad_id).map(|g| g.platform_id) {
+        ctx.window.set_gamepad_player_index(platform_id, index);
+    }
+}
+
+/// Returns true if the specified gamepad has the given motion sensor.
+///
+/// If the gamepad is disconnected, this will always return `false`.
+pub fn is_gamepad_sensor_supported(
+    ctx: &Context,
+    gamepad_id: usize,
+    sensor: GamepadSensor,
+) -> bool {
+    get_gamepad(ctx, gamepad_id)
+        .map(|g| g.platform_id)
+        .map(|id| ctx.window.is_gamepad_sensor_supported(id, sensor))
+        .unwrap_or(false)
+}
+
+/// Sets whether the given motion sensor on the specified gamepad should report data.
+///
+/// Sensors are disabled by default, as they send a lot of updates and use extra
+/// battery power on wireless controllers. Once a sensor is enabled, its latest reading
+/// can be retrieved via [`get_gamepad_sensor_data`].
+///
+/// If the gamepad is disconnected, or does not have the sensor, this will have no effect.
+pub fn set_gamepad_sensor_enabled(
+    ctx: &mut Context,
+    gamepad_id: usize,
+    sensor: GamepadSensor,
+    enabled: bool,
+) {
+    if let Some(platform_id) = get_gamepad(ctx, gamepad_id).map(|g| g.platform_id) {
+        ctx.window
+            .set_gamepad_sensor_enabled(platform_id, sensor, enabled);
+
+        if !enabled {
+            if let Some(pad) = get_gamepad_mut(ctx, gamepad_id) {
+                pad.sensor_data.remove(&sensor);
+            }
+        }
+    }
+}
+
+/// Returns the latest reading from the given motion sensor on the specified gamepad.
+///
+/// The axes are relative to the gamepad, when it is held in front of the player:
+/// X points to the right, Y points upwards and Z points towards the player. See [`GamepadSensor`] for the units that each sensor uses.
+///
+/// This will return [`None`] if the gamepad is disconnected, if it does not have the
+/// sensor, if the sensor has not been [enabled](set_gamepad_sensor_enabled), or if no
+/// data has been received from the sensor yet.
+pub fn get_gamepad_sensor_data(
+    ctx: &Context,
+    gamepad_id: usize,
+    sensor: GamepadSensor,
+) -> Option<Vec3<f32>> {
+    get_gamepad(ctx, gamepad_id).and_then(|pad| pad.sensor_data.get(&sensor).copied())
+}
+
 /// Returns true if the specified gamepad button is currently down.
 ///
 /// If the gamepad is disconnected, this will always return `false`.
@@ -235,10 +431,35 @@ pub fn get_gamepad_buttons_released(
     }
 }
 
-/// Returns the current position of the specified gamepad axis.
+/// Returns the current position of the specified gamepad axis, with the gamepad's
+/// [deadzone](set_gamepad_axis_deadzone) applied.
+///
+/// If the axis is part of a control stick, the deadzone is applied to the stick as a
+/// whole, so this will match the corresponding component of [`get_gamepad_stick_position`].
 ///
 /// If the gamepad is disconnected, this will always return `0.0`.
 pub fn get_gamepad_axis_position(ctx: &Context, gamepad_id: usize, axis: GamepadAxis) -> f32 {
+    let deadzone = get_gamepad_axis_deadzone(ctx, gamepad_id);
+
+    match axis {
+        GamepadAxis::LeftStickX | GamepadAxis::RightStickX => {
+            get_gamepad_stick_position(ctx, gamepad_id, axis_stick(axis)).x
+        }
+        GamepadAxis::LeftStickY | GamepadAxis::RightStickY => {
+            get_gamepad_stick_position(ctx, gamepad_id, axis_stick(axis)).y
+        }
+        GamepadAxis::LeftTrigger | GamepadAxis::RightTrigger => trigger_deadzone(
+            get_gamepad_axis_position_raw(ctx, gamepad_id, axis),
+            deadzone,
+        ),
+    }
+}
+
+/// Returns the current position of the specified gamepad axis, without any deadzone
+/// applied.
+///
+/// If the gamepad is disconnected, this will always return `0.0`.
+pub fn get_gamepad_axis_position_raw(ctx: &Context, gamepad_id: usize, axis: GamepadAxis) -> f32 {
     if let Some(pad) = get_gamepad(ctx, gamepad_id) {
         if let Some(value) = pad.current_axis_state.get(&axis) {
             *value
@@ -250,13 +471,29 @@ pub fn get_gamepad_axis_position(ctx: &Context, gamepad_id: usize, axis: Gamepad
     }
 }
 
-/// Returns the current position of the specified gamepad control stick.
+/// Returns the current position of the specified gamepad control stick, with the
+/// gamepad's [deadzone](set_gamepad_axis_deadzone) applied.
 ///
 /// If the gamepad is disconnected, this will always return `(0.0, 0.0)`.
 pub fn get_gamepad_stick_position(
     ctx: &Context,
     gamepad_id: usize,
     stick: GamepadStick,
+) -> Vec2<f32> {
+    radial_deadzone(
+        get_gamepad_stick_position_raw(ctx, gamepad_id, stick),
+        get_gamepad_axis_deadzone(ctx, gamepad_id),
+    )
+}
+
+/// Returns the current position of the specified gamepad control stick, without any
+/// deadzone applied.
+///
+/// If the gamepad is disconnected, this will always return `(0.0, 0.0)`.
+pub fn get_gamepad_stick_position_raw(
+    ctx: &Context,
+    gamepad_id: usize,
+    stick: GamepadStick,
 ) -> Vec2<f32> {
     let (x_axis, y_axis) = match stick {
         GamepadStick::LeftStick => (GamepadAxis::LeftStickX, GamepadAxis::LeftStickY),
@@ -264,11 +501,82 @@ pub fn get_gamepad_stick_position(
     };
 
     Vec2::new(
-        get_gamepad_axis_position(ctx, gamepad_id, x_axis),
-        get_gamepad_axis_position(ctx, gamepad_id, y_axis),
+        get_gamepad_axis_position_raw(ctx, gamepad_id, x_axis),
+        get_gamepad_axis_position_raw(ctx, gamepad_id, y_axis),
     )
 }
 
+/// Returns the deadzone that is applied to the axes of the specified gamepad.
+///
+/// If a deadzone has not been set for this gamepad via [`set_gamepad_axis_deadzone`],
+/// the [default deadzone](set_default_gamepad_axis_deadzone) will be returned.
+pub fn get_gamepad_axis_deadzone(ctx: &Context, gamepad_id: usize) -> f32 {
+    ctx.input
+        .gamepad_deadzones
+        .get(&gamepad_id)
+        .copied()
+        .unwrap_or(ctx.input.default_gamepad_deadzone)
+}
+
+/// Sets the deadzone that is applied to the axes of the specified gamepad.
+///
+/// For control sticks, this is a scaled radial deadzone - if the stick is closer to
+/// the center than the deadzone, its position will be reported as zero, and the rest of
+/// the range will be rescaled so that the position still smoothly reaches `1.0`. The
+/// deadzone is applied to both axes of the stick together, so diagonal movement is not
+/// affected.
+///
+/// For triggers, any value below the deadzone will be reported as zero.
+///
+/// The deadzone will be clamped between `0.0` and `1.0`. This setting is kept if the
+/// gamepad is disconnected.
+pub fn set_gamepad_axis_deadzone(ctx: &mut Context, gamepad_id: usize, deadzone: f32) {
+    ctx.input
+        .gamepad_deadzones
+        .insert(gamepad_id, deadzone.clamp(0.0, 1.0));
+}
+
+/// Returns the deadzone that is applied to the axes of gamepads that do not have their own
+/// deadzone set.
+pub fn get_default_gamepad_axis_deadzone(ctx: &Context) -> f32 {
+    ctx.input.default_gamepad_deadzone
+}
+
+/// Sets the deadzone that is applied to the axes of gamepads that do not have their own
+/// deadzone set.
+///
+/// See [`set_gamepad_axis_deadzone`] for details of how the deadzone is applied. This
+/// defaults to `0.0`, meaning no deadzone is applied.
+pub fn set_default_gamepad_axis_deadzone(ctx: &mut Context, deadzone: f32) {
+    ctx.input.default_gamepad_deadzone = deadzone.clamp(0.0, 1.0);
+}
+
+fn axis_stick(axis: GamepadAxis) -> GamepadStick {
+    match axis {
+        GamepadAxis::LeftStickX | GamepadAxis::LeftStickY => GamepadStick::LeftStick,
+        _ => GamepadStick::RightStick,
+    }
+}
+
+fn radial_deadzone(position: Vec2<f32>, deadzone: f32) -> Vec2<f32> {
+    let magnitude = position.magnitude();
+
+    if magnitude <= deadzone {
+        Vec2::zero()
+    } else {
+        let scaled = f32::min((magnitude - deadzone) / (1.0 - deadzone), 1.0);
+        position * (scaled / magnitude)
+    }
+}
+
+fn trigger_deadzone(position: f32, deadzone: f32) -> f32 {
+    if position < deadzone {
+        0.0
+    } else {
+        position
+    }
+}
+
 /// Returns true if the specified gamepad supports vibration.
 ///
 /// If the gamepad is disconnected, this will always return `false`.
@@ -280,6 +588,19 @@ pub fn is_gamepad_vibration_supported(ctx: &Context, gamepad_id: usize) -> bool
     }
 }
 
+/// Returns true if the specified gamepad has vibration motors in its triggers
+/// (e.g. an Xbox One controller).
+///
+/// If the gamepad is disconnected, this will always return `false`.
+pub fn is_gamepad_trigger_vibration_supported(ctx: &Context, gamepad_id: usize) -> bool {
+    if let Some(pad) = get_gamepad(ctx, gamepad_id) {
+        ctx.window
+            .is_gamepad_trigger_vibration_supported(pad.platform_id)
+    } else {
+        false
+    }
+}
+
 /// Sets the specified gamepad's motors to vibrate indefinitely.
 pub fn set_gamepad_vibration(ctx: &mut Context, gamepad_id: usize, strength: f32) {
     if let Some(platform_id) = get_gamepad(ctx, gamepad_id).map(|g| g.platform_id) {
@@ -289,10 +610,30 @@ pub fn set_gamepad_vibration(ctx: &mut Context, gamepad_id: usize, strength: f32
 
 /// Sets the specified gamepad's motors to vibrate for a set duration, specified in milliseconds.
 /// After this time has passed, the vibration will automatically stop.
+///
+/// Both of the gamepad's motors will vibrate with the same strength - use
+/// [`start_gamepad_vibration_ex`] if you want to control them separately.
 pub fn start_gamepad_vibration(ctx: &mut Context, gamepad_id: usize, strength: f32, duration: u32) {
+    start_gamepad_vibration_ex(ctx, gamepad_id, strength, strength, duration);
+}
+
+/// Sets the specified gamepad's motors to vibrate for a set duration, specified in milliseconds,
+/// with a different strength for each motor. After this time has passed, the vibration will
+/// automatically stop.
+///
+/// Most gamepads have a low frequency motor (usually on the left), which gives a heavy rumble,
+/// and a high frequency motor (usually on the right), which gives a lighter buzz. The strengths
+/// should be between `0.0` and `1.0`.
+pub fn start_gamepad_vibration_ex(
+    ctx: &mut Context,
+    gamepad_id: usize,
+    low_frequency: f32,
+    high_frequency: f32,
+    duration: u32,
+) {
     if let Some(platform_id) = get_gamepad(ctx, gamepad_id).map(|g| g.platform_id) {
         ctx.window
-            .start_gamepad_vibration(platform_id, strength, duration);
+            .start_gamepad_vibration(platform_id, low_frequency, high_frequency, duration);
     }
 }
 
@@ -336,3 +677,48 @@ pub(crate) fn get_gamepad_mut(ctx: &mut Context, gamepad_id: usize) -> Option<&m
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radial_deadzone_is_scaled() {
+        assert_eq!(radial_deadzone(Vec2::new(0.1, 0.1), 0.25), Vec2::zero());
+        assert_eq!(
+            radial_deadzone(Vec2::new(0.0, -1.0), 0.25),
+            Vec2::new(0.0, -1.0)
+        );
+        assert_eq!(
+            radial_deadzone(Vec2::new(0.625, 0.0), 0.25),
+            Vec2::new(0.5, 0.0)
+        );
+
+        // Diagonals should keep their direction, rather than being snapped to an axis.
+        let diagonal = radial_deadzone(Vec2::new(0.5, 0.5), 0.25);
+        assert!(diagonal.x > 0.0);
+        assert_eq!(diagonal.x, diagonal.y);
+    }
+
+    #[test]
+    fn trigger_deadzone_is_a_threshold() {
+        assert_eq!(trigger_deadzone(0.1, 0.25), 0.0);
+        assert_eq!(trigger_deadzone(0.5, 0.25), 0.5);
+        assert_eq!(trigger_deadzone(0.5, 0.0), 0.5);
+    }
+
+    #[test]
+    fn sensor_data_is_cached_per_sensor() {
+        let mut pad = GamepadState::new(0);
+
+        pad.set_sensor_data(GamepadSensor::Gyro, Vec3::new(1.0, 2.0, 3.0));
+        pad.set_sensor_data(GamepadSensor::Gyro, Vec3::new(4.0, 5.0, 6.0));
+
+        assert_eq!(
+            pad.sensor_data.get(&GamepadSensor::Gyro),
+            Some(&Vec3::new(4.0, 5.0, 6.0))
+        );
+
+        assert_eq!(pad.sensor_data.get(&GamepadSensor::Accelerometer), None);
+    }
+}
@@ -482,13 +482,39 @@ impl Display for KeyModifier {
     }
 }
 
-#[derive(Default, Debug)]
-pub(crate) struct KeyModifierState {
+/// The state of the key modifiers on the keyboard.
+///
+/// This can be retrieved via [`get_key_modifier_state`], and is useful for storing
+/// keyboard shortcuts alongside the [`Key`] that triggers them.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyModifierState {
+    /// Whether either Ctrl key is down.
     pub ctrl: bool,
+
+    /// Whether either Alt key is down.
     pub alt: bool,
+
+    /// Whether either Shift key is down.
     pub shift: bool,
 }
 
+impl KeyModifierState {
+    /// Returns true if the specified key modifier is down in this state.
+    pub fn is_down(&self, key_modifier: KeyModifier) -> bool {
+        match key_modifier {
+            KeyModifier::Ctrl => self.ctrl,
+            KeyModifier::Alt => self.alt,
+            KeyModifier::Shift => self.shift,
+        }
+    }
+}
+
 /// Returns true if the specified key is currently down.
 pub fn is_key_down(ctx: &Context, key: Key) -> bool {
     ctx.input.keys_down.contains(&key)
@@ -509,37 +535,56 @@ pub fn is_key_released(ctx: &Context, key: Key) -> bool {
     ctx.input.keys_released.contains(&key)
 }
 
+/// Returns true if the specified key chord was pressed since the last update.
+///
+/// A chord is pressed when the last key in the slice is pressed while all of the
+/// other keys are being held down. For example, `&[Key::LeftCtrl, Key::S]` will be
+/// pressed when S is pressed while Ctrl is held, but not when Ctrl is pressed while
+/// S is held.
+///
+/// If the slice is empty, this will always return false.
+pub fn is_key_chord_pressed(ctx: &Context, keys: &[Key]) -> bool {
+    ctx.input.is_key_chord_pressed(keys)
+}
+
 /// Returns true if the specified key modifier is currently down.
 pub fn is_key_modifier_down(ctx: &Context, key_modifier: KeyModifier) -> bool {
-    match key_modifier {
-        KeyModifier::Ctrl => ctx.input.key_modifier_state.ctrl,
-        KeyModifier::Alt => ctx.input.key_modifier_state.alt,
-        KeyModifier::Shift => ctx.input.key_modifier_state.shift,
-    }
+    ctx.input.key_modifier_state.is_down(key_modifier)
 }
 
 /// Returns true if the specified key modifier is currently up.
 pub fn is_key_modifier_up(ctx: &Context, key_modifier: KeyModifier) -> bool {
-    match key_modifier {
-        KeyModifier::Ctrl => !ctx.input.key_modifier_state.ctrl,
-        KeyModifier::Alt => !ctx.input.key_modifier_state.alt,
-        KeyModifier::Shift => !ctx.input.key_modifier_state.shift,
-    }
+    !ctx.input.key_modifier_state.is_down(key_modifier)
+}
+
+/// Returns the current state of all of the key modifiers.
+pub fn get_key_modifier_state(ctx: &Context) -> KeyModifierState {
+    ctx.input.key_modifier_state
 }
 
 /// Returns an iterator of the keys that are currently down.
-pub fn get_keys_down(ctx: &Context) -> impl Iterator<Item = &Key> {
-    ctx.input.keys_down.iter()
+///
+/// The keys are returned in an arbitrary order. To get the label of each key
+/// in the current keyboard layout, use [`get_key_label`].
+pub fn get_keys_down(ctx: &Context) -> impl Iterator<Item = Key> + '_ {
+    ctx.input.keys_down.iter().copied()
 }
 
 /// Returns an iterator of the keys that were pressed since the last update.
-pub fn get_keys_pressed(ctx: &Context) -> impl Iterator<Item = &Key> {
-    ctx.input.keys_pressed.iter()
+///
+/// This is useful for implementing key rebinding (e.g. 'press the key you want to use
+/// for this action'). The keys are returned in an arbitrary order. To get the label of
+/// each key in the current keyboard layout, use [`get_key_label`].
+pub fn get_keys_pressed(ctx: &Context) -> impl Iterator<Item = Key> + '_ {
+    ctx.input.keys_pressed.iter().copied()
 }
 
 /// Returns an iterator of the keys that were released since the last update.
-pub fn get_keys_released(ctx: &Context) -> impl Iterator<Item = &Key> {
-    ctx.input.keys_released.iter()
+///
+/// The keys are returned in an arbitrary order. To get the label of each key
+/// in the current keyboard layout, use [`get_key_label`].
+pub fn get_keys_released(ctx: &Context) -> impl Iterator<Item = Key> + '_ {
+    ctx.input.keys_released.iter().copied()
 }
 
 /// Returns the key that has the specified label in the current keyboard layout.
@@ -577,23 +622,12 @@ pub fn get_key_label(ctx: &Context, physical_key: Key) -> Option<KeyLabel> {
 }
 
 pub(crate) fn set_key_down(ctx: &mut Context, key: Key) -> bool {
-    let was_up = ctx.input.keys_down.insert(key);
-
-    if was_up || ctx.window.is_key_repeat_enabled() {
-        ctx.input.keys_pressed.insert(key);
-    }
-
-    was_up
+    let key_repeat_enabled = ctx.window.is_key_repeat_enabled();
+    ctx.input.press_key(key, key_repeat_enabled)
 }
 
 pub(crate) fn set_key_up(ctx: &mut Context, key: Key) -> bool {
-    let was_down = ctx.input.keys_down.remove(&key);
-
-    if was_down {
-        ctx.input.keys_released.insert(key);
-    }
-
-    was_down
+    ctx.input.release_key(key)
 }
 
 pub(crate) fn set_key_modifier_state(ctx: &mut Context, state: KeyModifierState) {
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::math::Vec2;
 use crate::Context;
 
@@ -19,6 +21,8 @@ pub enum MouseButton {
     X2,
 }
 
+pub(crate) const DEFAULT_DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(300);
+
 /// Returns true if the specified mouse button is currently down.
 pub fn is_mouse_button_down(ctx: &Context, button: MouseButton) -> bool {
     ctx.input.mouse_buttons_down.contains(&button)
@@ -39,6 +43,33 @@ pub fn is_mouse_button_released(ctx: &Context, button: MouseButton) -> bool {
     ctx.input.mouse_buttons_released.contains(&button)
 }
 
+/// Returns true if the specified mouse button was double clicked since the last update.
+///
+/// A double click is registered when the button is pressed twice within the
+/// [double click interval](set_double_click_interval) (300 milliseconds by default).
+/// After a double click, the next press will start counting again, so a triple click
+/// will only be reported once.
+///
+/// The time between clicks is measured using real time rather than by counting
+/// updates, so the result will not be affected by the game's tick rate.
+pub fn is_mouse_button_double_clicked(ctx: &Context, button: MouseButton) -> bool {
+    ctx.input.mouse_buttons_double_clicked.contains(&button)
+}
+
+/// Gets the maximum amount of time that can pass between two clicks for them to be
+/// counted as a double click.
+pub fn get_double_click_interval(ctx: &Context) -> Duration {
+    ctx.input.double_click_interval
+}
+
+/// Sets the maximum amount of time that can pass between two clicks for them to be
+/// counted as a double click.
+///
+/// The default interval is 300 milliseconds.
+pub fn set_double_click_interval(ctx: &mut Context, interval: Duration) {
+    ctx.input.double_click_interval = interval;
+}
+
 /// Returns true if the user scrolled up since the last update.
 pub fn is_mouse_scrolled_up(ctx: &Context) -> bool {
     get_mouse_wheel_movement(ctx).y > 0
@@ -71,34 +102,62 @@ pub fn get_mouse_position(ctx: &Context) -> Vec2<f32> {
 ///
 /// Positive values correspond to scrolling up/right, negative values correspond to scrolling
 /// down/left.
+///
+/// Some devices (e.g. touchpads and free-spinning mouse wheels) can scroll by fractional
+/// amounts - the value returned by this function is rounded to the nearest whole number.
+/// Use [`get_precise_mouse_wheel_movement`] if you need the exact value (e.g. for smooth
+/// zooming).
 pub fn get_mouse_wheel_movement(ctx: &Context) -> Vec2<i32> {
-    ctx.input.mouse_wheel_movement
+    round_wheel_movement(ctx.input.mouse_wheel_movement)
 }
 
-pub(crate) fn set_mouse_button_down(ctx: &mut Context, btn: MouseButton) -> bool {
-    let was_up = ctx.input.mouse_buttons_down.insert(btn);
+/// Get the precise amount that the mouse wheel moved since the last update.
+///
+/// This works the same as [`get_mouse_wheel_movement`], but does not round the result,
+/// so fractional movements from touchpads and high resolution mouse wheels are preserved.
+pub fn get_precise_mouse_wheel_movement(ctx: &Context) -> Vec2<f32> {
+    ctx.input.mouse_wheel_movement
+}
 
-    if was_up {
-        ctx.input.mouse_buttons_pressed.insert(btn);
-    }
+/// Get the total distance that the mouse moved since the last update.
+///
+/// Unlike [`get_mouse_position`], this works while
+/// [relative mouse mode](crate::window::set_relative_mouse_mode) is enabled, so it can be
+/// used for control schemes where the mouse needs to be able to move infinitely in any
+/// direction (e.g. FPS-style camera movement). It is equivalent to adding up the `delta`
+/// of every [`Event::MouseMoved`](crate::Event::MouseMoved) since the last update.
+///
+/// While the window does not have focus, this will always return zero.
+pub fn get_mouse_delta(ctx: &Context) -> Vec2<f32> {
+    ctx.input.mouse_delta
+}
 
-    was_up
+pub(crate) fn set_mouse_button_down(ctx: &mut Context, btn: MouseButton) -> bool {
+    // Clicks are timestamped with the start of the current frame, so that double
+    // clicks are measured by the same clock as the rest of the game loop.
+    let now = ctx.time.last_time;
+    ctx.input.press_mouse_button(btn, now)
 }
 
 pub(crate) fn set_mouse_button_up(ctx: &mut Context, btn: MouseButton) -> bool {
-    let was_down = ctx.input.mouse_buttons_down.remove(&btn);
-
-    if was_down {
-        ctx.input.mouse_buttons_released.insert(btn);
-    }
-
-    was_down
+    ctx.input.release_mouse_button(btn)
 }
 
 pub(crate) fn set_mouse_position(ctx: &mut Context, position: Vec2<f32>) {
     ctx.input.mouse_position = position;
 }
 
-pub(crate) fn apply_mouse_wheel_movement(ctx: &mut Context, wheel_movement: Vec2<i32>) {
-    ctx.input.mouse_wheel_movement += wheel_movement;
+pub(crate) fn apply_mouse_motion(ctx: &mut Context, delta: Vec2<f32>) {
+    ctx.input.apply_mouse_motion(delta);
+}
+
+pub(crate) fn apply_mouse_wheel_movement(ctx: &mut Context, wheel_movement: Vec2<f32>) {
+    ctx.input.apply_mouse_wheel_movement(wheel_movement);
+}
+
+pub(crate) fn round_wheel_movement(wheel_movement: Vec2<f32>) -> Vec2<i32> {
+    Vec2::new(
+        wheel_movement.x.round() as i32,
+        wheel_movement.y.round() as i32,
+    )
 }
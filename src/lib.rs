@@ -80,8 +80,9 @@ mod lifecycle;
 pub mod math;
 mod platform;
 pub mod time;
+pub mod ui;
 pub mod window;
 
-pub use crate::context::{Context, ContextBuilder};
+pub use crate::context::{Context, ContextBuilder, LoopStatus};
 pub use crate::error::{Result, TetraError};
 pub use crate::lifecycle::{Event, State};
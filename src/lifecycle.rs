@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::input::{GamepadAxis, GamepadButton, GamepadStick, Key, MouseButton};
+use crate::input::{GamepadAxis, GamepadButton, GamepadStick, GamepadTouchPhase, Key, MouseButton};
 use crate::math::Vec2;
 use crate::{Context, TetraError};
 
@@ -54,6 +54,12 @@ pub trait State<E = TetraError> {
 #[derive(Debug, Clone)]
 pub enum Event {
     /// The game window was resized.
+    ///
+    /// If the resize also changed the window's [metrics](Event::WindowMetricsChanged),
+    /// that event will be fired before this one, so the graphics viewport will already
+    /// have been updated by the time this is received. For anything that depends on
+    /// the DPI scale as well as the size (e.g. a [`ScreenScaler`](crate::graphics::scaling::ScreenScaler)),
+    /// prefer handling [`Event::WindowMetricsChanged`] instead.
     Resized {
         /// The new width of the game window.
         width: i32,
@@ -62,6 +68,49 @@ pub enum Event {
         height: i32,
     },
 
+    /// The size or DPI scale of the game window changed.
+    ///
+    /// Changes that are reported separately by the operating system (for example, when
+    /// the window is dragged onto a monitor with a different scale factor) are combined,
+    /// so that this event is only fired once per frame, with consistent values. It is
+    /// always fired before any other events from the same frame.
+    WindowMetricsChanged {
+        /// The new size of the window, in logical pixels.
+        logical_size: (i32, i32),
+
+        /// The new size of the window's drawable area, in physical pixels.
+        physical_size: (i32, i32),
+
+        /// The new ratio of physical pixels to logical pixels.
+        dpi_scale: f32,
+    },
+
+    /// The DPI scale of the game window changed (for example, because it was moved onto
+    /// a monitor with a different scale factor).
+    ///
+    /// This is fired straight after the [`WindowMetricsChanged`](Event::WindowMetricsChanged)
+    /// event that contains the new scale, so [`window::get_dpi_scale`](crate::window::get_dpi_scale)
+    /// will already return the new value by the time this is received. It can be used to
+    /// reload assets (such as fonts) at a resolution that suits the new scale.
+    DpiScaleChanged {
+        /// The new ratio of physical pixels to logical pixels.
+        scale: f32,
+    },
+
+    /// A monitor was connected to the system.
+    DisplayConnected {
+        /// The index of the monitor that was connected.
+        index: i32,
+    },
+
+    /// A monitor was disconnected from the system.
+    ///
+    /// The indices of the remaining monitors may change as a result of this.
+    DisplayDisconnected {
+        /// The index that the monitor had before it was disconnected.
+        index: i32,
+    },
+
     /// The game window was restored to normal size and position by the user, either by
     /// un-minimizing or un-maximizing.
     Restored,
@@ -72,6 +121,13 @@ pub enum Event {
     /// The game window was maximized by the user.
     Maximized,
 
+    /// The player asked to quit the game (e.g. by clicking the window's close button).
+    ///
+    /// By default, the game will quit after this event has been handled. This can be
+    /// prevented by calling [`window::cancel_quit`](crate::window::cancel_quit) while
+    /// handling the event.
+    QuitRequested,
+
     /// The game window was focused by the user.
     FocusGained,
 
@@ -124,7 +180,16 @@ pub enum Event {
         ///
         /// Positive values correspond to scrolling up/right, negative values correspond to scrolling
         /// down/left.
+        ///
+        /// If the device can scroll by fractional amounts, this will only change once a
+        /// whole 'notch' of movement has built up - use `precise_amount` for smooth scrolling.
         amount: Vec2<i32>,
+
+        /// The precise amount that the wheel was moved, including any fractional movement
+        /// from touchpads and high resolution mouse wheels.
+        ///
+        /// This uses the same directions as `amount`.
+        precise_amount: Vec2<f32>,
     },
 
     /// A gamepad was connected to the system.
@@ -181,12 +246,61 @@ pub enum Event {
         position: Vec2<f32>,
     },
 
+    /// A finger touched, moved on or was lifted from a gamepad's touchpad (e.g. on a
+    /// PlayStation 4 or PlayStation 5 controller).
+    ///
+    /// Gamepads without a touchpad will never fire this event.
+    GamepadTouchpad {
+        /// The ID of the gamepad.
+        id: usize,
+
+        /// The index of the touchpad, for gamepads that have more than one.
+        touchpad: usize,
+
+        /// The index of the finger, which can be used to track multiple touches
+        /// at once.
+        finger: usize,
+
+        /// The position of the finger, normalized so that `(0.0, 0.0)` is the top left
+        /// of the touchpad and `(1.0, 1.0)` is the bottom right.
+        position: Vec2<f32>,
+
+        /// The pressure of the touch, between `0.0` and `1.0`.
+        pressure: f32,
+
+        /// Whether the finger was placed, moved or lifted.
+        phase: GamepadTouchPhase,
+    },
+
     /// The user typed some text.
     TextInput {
         /// The text that was typed by the user.
         text: String,
     },
 
+    /// The user is composing text via an input method editor (IME), but has not yet
+    /// committed it.
+    ///
+    /// This is used when typing languages that need multiple keystrokes to produce a
+    /// character (e.g. Chinese, Japanese or Korean). You should display the text
+    /// alongside whatever the user is typing into, replacing any previous composition
+    /// text - once the user commits, the final text will be delivered via a
+    /// [`TextInput`](Event::TextInput) event, and an empty `TextEditing` event will
+    /// usually be fired to clear the composition.
+    ///
+    /// See [`input::set_text_input_rect`](crate::input::set_text_input_rect) for how
+    /// to tell the IME where the text is being displayed.
+    TextEditing {
+        /// The text that is being composed.
+        text: String,
+
+        /// The position of the cursor within the composition, in characters.
+        start: usize,
+
+        /// The number of characters that are selected within the composition.
+        length: usize,
+    },
+
     /// The user dropped a file into the window.
     ///
     /// This event will be fired multiple times if the user dropped multiple files at the
@@ -199,4 +313,31 @@ pub enum Event {
         /// The path of the file that was dropped.
         path: PathBuf,
     },
+
+    /// The user dropped one or more files into the window.
+    ///
+    /// Unlike [`FileDropped`](Event::FileDropped), this event is fired once per drop, after
+    /// all of the individual `FileDropped` events. The paths are in the order that they were
+    /// received, and if the same file was dropped more than once, it will appear multiple times.
+    ///
+    /// The same platform requirements apply as for `FileDropped`.
+    FilesDropped {
+        /// The paths of the files that were dropped.
+        paths: Vec<PathBuf>,
+    },
+
+    /// The user dropped some text into the window (e.g. by dragging a selection from
+    /// a text editor or web browser).
+    TextDropped {
+        /// The text that was dropped.
+        text: String,
+    },
+
+    /// The system's default audio output device changed, and playback was moved
+    /// over to the new device.
+    ///
+    /// See the docs for [`SoundInstance`](crate::audio::SoundInstance) for details
+    /// of how this affects sounds that were already playing.
+    #[cfg(feature = "audio")]
+    AudioDeviceChanged,
 }
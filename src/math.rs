@@ -13,5 +13,7 @@
 //! re-exported at the top level - you don't need to dig down into the submodules
 //! when importing things.
 
+pub mod easing;
+
 #[doc(no_inline)]
 pub use vek::*;
@@ -0,0 +1,417 @@
+//! Easing functions, for smoothing out animations and transitions.
+//!
+//! All of the functions in this module map a progress value from `0.0` to `1.0`
+//! onto an eased value, which will start at `0.0` and finish at `1.0`. Some of the
+//! curves (such as [`back_in`] and [`elastic_out`]) will overshoot that range along
+//! the way.
+//!
+//! These are the standard set of curves described by Robert Penner - you can see
+//! a visual comparison of them on [easings.net](https://easings.net/).
+//!
+//! To apply an easing function to a value, use [`ease`]:
+//!
+//! ```
+//! use tetra::math::easing::{self, ease};
+//! use tetra::math::Vec2;
+//!
+//! let from = Vec2::new(0.0, 0.0);
+//! let to = Vec2::new(100.0, 50.0);
+//!
+//! let position = ease(from, to, 0.5, easing::cubic_in_out);
+//! assert_eq!(position, Vec2::new(50.0, 25.0));
+//! ```
+
+use std::f32::consts::PI;
+
+use crate::math::Lerp;
+
+/// Interpolates between two values, using an easing function to shape the curve.
+///
+/// `t` is the progress of the interpolation, which will be clamped to the range of
+/// `0.0` to `1.0` before it is passed to the easing function. The eased value will
+/// not be clamped, so curves that overshoot will also overshoot `from` and `to`.
+///
+/// Any type that implements [`Lerp`] can be used - this includes [`f32`],
+/// [`Vec2`](crate::math::Vec2), [`Vec3`](crate::math::Vec3) and
+/// [`Color`](crate::graphics::Color).
+pub fn ease<T>(from: T, to: T, t: f32, f: fn(f32) -> f32) -> T
+where
+    T: Lerp<f32, Output = T>,
+{
+    T::lerp_unclamped(from, to, f(t.clamp(0.0, 1.0)))
+}
+
+/// No easing.
+pub fn linear(t: f32) -> f32 {
+    t
+}
+
+/// Quadratic easing, accelerating from zero velocity.
+pub fn quad_in(t: f32) -> f32 {
+    t * t
+}
+
+/// Quadratic easing, decelerating to zero velocity.
+pub fn quad_out(t: f32) -> f32 {
+    1.0 - (1.0 - t) * (1.0 - t)
+}
+
+/// Quadratic easing, accelerating until halfway, then decelerating.
+pub fn quad_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+/// Cubic easing, accelerating from zero velocity.
+pub fn cubic_in(t: f32) -> f32 {
+    t * t * t
+}
+
+/// Cubic easing, decelerating to zero velocity.
+pub fn cubic_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+/// Cubic easing, accelerating until halfway, then decelerating.
+pub fn cubic_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+    }
+}
+
+/// Quartic easing, accelerating from zero velocity.
+pub fn quart_in(t: f32) -> f32 {
+    t.powi(4)
+}
+
+/// Quartic easing, decelerating to zero velocity.
+pub fn quart_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(4)
+}
+
+/// Quartic easing, accelerating until halfway, then decelerating.
+pub fn quart_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        8.0 * t.powi(4)
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(4) / 2.0
+    }
+}
+
+/// Quintic easing, accelerating from zero velocity.
+pub fn quint_in(t: f32) -> f32 {
+    t.powi(5)
+}
+
+/// Quintic easing, decelerating to zero velocity.
+pub fn quint_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(5)
+}
+
+/// Quintic easing, accelerating until halfway, then decelerating.
+pub fn quint_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        16.0 * t.powi(5)
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(5) / 2.0
+    }
+}
+
+/// Sinusoidal easing, accelerating from zero velocity.
+pub fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+/// Sinusoidal easing, decelerating to zero velocity.
+pub fn sine_out(t: f32) -> f32 {
+    (t * PI / 2.0).sin()
+}
+
+/// Sinusoidal easing, accelerating until halfway, then decelerating.
+pub fn sine_in_out(t: f32) -> f32 {
+    -((PI * t).cos() - 1.0) / 2.0
+}
+
+/// Exponential easing, accelerating from zero velocity.
+pub fn expo_in(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2f32.powf(10.0 * t - 10.0)
+    }
+}
+
+/// Exponential easing, decelerating to zero velocity.
+pub fn expo_out(t: f32) -> f32 {
+    if t >= 1.0 {
+        1.0
+    } else {
+        1.0 - 2f32.powf(-10.0 * t)
+    }
+}
+
+/// Exponential easing, accelerating until halfway, then decelerating.
+pub fn expo_in_out(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else if t < 0.5 {
+        2f32.powf(20.0 * t - 10.0) / 2.0
+    } else {
+        (2.0 - 2f32.powf(-20.0 * t + 10.0)) / 2.0
+    }
+}
+
+/// Circular easing, accelerating from zero velocity.
+pub fn circ_in(t: f32) -> f32 {
+    1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+/// Circular easing, decelerating to zero velocity.
+pub fn circ_out(t: f32) -> f32 {
+    (1.0 - (t - 1.0) * (t - 1.0)).max(0.0).sqrt()
+}
+
+/// Circular easing, accelerating until halfway, then decelerating.
+pub fn circ_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        (1.0 - (1.0 - (2.0 * t).powi(2)).max(0.0).sqrt()) / 2.0
+    } else {
+        ((1.0 - (-2.0 * t + 2.0).powi(2)).max(0.0).sqrt() + 1.0) / 2.0
+    }
+}
+
+const BACK_C1: f32 = 1.70158;
+const BACK_C2: f32 = BACK_C1 * 1.525;
+const BACK_C3: f32 = BACK_C1 + 1.0;
+
+/// Easing that pulls back slightly before accelerating.
+pub fn back_in(t: f32) -> f32 {
+    BACK_C3 * t * t * t - BACK_C1 * t * t
+}
+
+/// Easing that overshoots the target slightly before settling.
+pub fn back_out(t: f32) -> f32 {
+    1.0 + BACK_C3 * (t - 1.0).powi(3) + BACK_C1 * (t - 1.0).powi(2)
+}
+
+/// Easing that pulls back slightly at the start, and overshoots slightly at the end.
+pub fn back_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        ((2.0 * t).powi(2) * ((BACK_C2 + 1.0) * 2.0 * t - BACK_C2)) / 2.0
+    } else {
+        ((2.0 * t - 2.0).powi(2) * ((BACK_C2 + 1.0) * (t * 2.0 - 2.0) + BACK_C2) + 2.0) / 2.0
+    }
+}
+
+/// Easing that oscillates with increasing strength before accelerating, like a spring.
+pub fn elastic_in(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+    }
+}
+
+/// Easing that overshoots the target and then oscillates around it, like a spring.
+pub fn elastic_out(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else {
+        2f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+    }
+}
+
+/// Easing that oscillates at both the start and the end, like a spring.
+pub fn elastic_in_out(t: f32) -> f32 {
+    const C5: f32 = 2.0 * PI / 4.5;
+
+    if t <= 0.0 {
+        0.0
+    } else if t >= 1.0 {
+        1.0
+    } else if t < 0.5 {
+        -(2f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * C5).sin()) / 2.0
+    } else {
+        (2f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * C5).sin()) / 2.0 + 1.0
+    }
+}
+
+/// Easing that bounces off the start a few times before accelerating.
+pub fn bounce_in(t: f32) -> f32 {
+    1.0 - bounce_out(1.0 - t)
+}
+
+/// Easing that bounces off the target a few times before settling, like a dropped ball.
+pub fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+/// Easing that bounces at both the start and the end.
+pub fn bounce_in_out(t: f32) -> f32 {
+    if t < 0.5 {
+        (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+    } else {
+        (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Color;
+    use crate::math::{Vec2, Vec3};
+
+    const EPSILON: f32 = 1e-5;
+
+    type Curve = (&'static str, fn(f32) -> f32);
+    type Family = (fn(f32) -> f32, fn(f32) -> f32, fn(f32) -> f32);
+
+    const MONOTONIC: &[Curve] = &[
+        ("linear", linear),
+        ("quad_in", quad_in),
+        ("quad_out", quad_out),
+        ("quad_in_out", quad_in_out),
+        ("cubic_in", cubic_in),
+        ("cubic_out", cubic_out),
+        ("cubic_in_out", cubic_in_out),
+        ("quart_in", quart_in),
+        ("quart_out", quart_out),
+        ("quart_in_out", quart_in_out),
+        ("quint_in", quint_in),
+        ("quint_out", quint_out),
+        ("quint_in_out", quint_in_out),
+        ("sine_in", sine_in),
+        ("sine_out", sine_out),
+        ("sine_in_out", sine_in_out),
+        ("expo_in", expo_in),
+        ("expo_out", expo_out),
+        ("expo_in_out", expo_in_out),
+        ("circ_in", circ_in),
+        ("circ_out", circ_out),
+        ("circ_in_out", circ_in_out),
+    ];
+
+    const NON_MONOTONIC: &[Curve] = &[
+        ("back_in", back_in),
+        ("back_out", back_out),
+        ("back_in_out", back_in_out),
+        ("elastic_in", elastic_in),
+        ("elastic_out", elastic_out),
+        ("elastic_in_out", elastic_in_out),
+        ("bounce_in", bounce_in),
+        ("bounce_out", bounce_out),
+        ("bounce_in_out", bounce_in_out),
+    ];
+
+    // (in, out, in_out)
+    const FAMILIES: &[Family] = &[
+        (quad_in, quad_out, quad_in_out),
+        (cubic_in, cubic_out, cubic_in_out),
+        (quart_in, quart_out, quart_in_out),
+        (quint_in, quint_out, quint_in_out),
+        (sine_in, sine_out, sine_in_out),
+        (expo_in, expo_out, expo_in_out),
+        (circ_in, circ_out, circ_in_out),
+        (back_in, back_out, back_in_out),
+        (elastic_in, elastic_out, elastic_in_out),
+        (bounce_in, bounce_out, bounce_in_out),
+    ];
+
+    fn samples() -> impl Iterator<Item = f32> {
+        (0..=100).map(|i| i as f32 / 100.0)
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for (name, f) in MONOTONIC.iter().chain(NON_MONOTONIC) {
+            assert!(f(0.0).abs() < EPSILON, "{}(0.0) = {}", name, f(0.0));
+            assert!((f(1.0) - 1.0).abs() < EPSILON, "{}(1.0) = {}", name, f(1.0));
+        }
+    }
+
+    #[test]
+    fn curves_are_monotonic() {
+        for (name, f) in MONOTONIC {
+            let mut previous = f(0.0);
+
+            for t in samples() {
+                let value = f(t);
+                assert!(value >= previous - EPSILON, "{} decreased at {}", name, t);
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn in_out_curves_are_symmetric() {
+        for &(ease_in, ease_out, ease_in_out) in FAMILIES {
+            assert!((ease_in_out(0.5) - 0.5).abs() < EPSILON);
+
+            for t in samples() {
+                assert!((ease_out(t) - (1.0 - ease_in(1.0 - t))).abs() < 1e-4);
+                assert!((ease_in_out(t) - (1.0 - ease_in_out(1.0 - t))).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn overshooting_curves_overshoot() {
+        assert!(samples().any(|t| back_in(t) < 0.0));
+        assert!(samples().any(|t| back_out(t) > 1.0));
+        assert!(samples().any(|t| elastic_out(t) > 1.0));
+
+        for t in samples() {
+            let value = bounce_out(t);
+            assert!((-EPSILON..=1.0 + EPSILON).contains(&value));
+        }
+    }
+
+    #[test]
+    fn ease_supports_lerpable_types() {
+        assert_eq!(ease(10.0, 20.0, 0.5, linear), 15.0);
+        assert_eq!(ease(10.0, 20.0, 2.0, linear), 20.0);
+        assert_eq!(ease(10.0, 20.0, -1.0, quad_in), 10.0);
+
+        assert_eq!(
+            ease(Vec2::new(0.0, 0.0), Vec2::new(10.0, 20.0), 0.5, quad_in),
+            Vec2::new(2.5, 5.0)
+        );
+
+        assert_eq!(
+            ease(Vec3::<f32>::zero(), Vec3::one(), 1.0, bounce_out),
+            Vec3::one()
+        );
+
+        assert_eq!(
+            ease(Color::BLACK, Color::WHITE, 0.5, linear),
+            Color::rgb(0.5, 0.5, 0.5)
+        );
+
+        assert!(ease(0.0, 1.0, 0.5, back_in) < 0.0);
+    }
+}
@@ -1,8 +1,11 @@
 mod device_gl;
+#[cfg(target_os = "macos")]
+mod macos;
+mod resources;
 mod window_sdl;
 
 pub use device_gl::{
     GraphicsDevice, RawCanvas, RawIndexBuffer, RawRenderbuffer, RawShader, RawTexture,
     RawVertexBuffer,
 };
-pub use window_sdl::{handle_events, Window};
+pub use window_sdl::{handle_events, RawCursor, Window};
@@ -1,5 +1,6 @@
 use std::cell::Cell;
 use std::mem;
+use std::num::NonZeroU32;
 use std::rc::Rc;
 use std::slice;
 
@@ -7,14 +8,15 @@ use glow::{Context as GlowContext, HasContext, PixelPackData, PixelUnpackData};
 
 use crate::error::{Result, TetraError};
 use crate::graphics::{
-    mesh::{BufferUsage, Vertex, VertexWinding},
+    mesh::{BufferUsage, Instance, Vertex, VertexWinding},
     StencilState, StencilTest,
 };
 use crate::graphics::{
-    BlendFactor, BlendOperation, BlendState, Color, FilterMode, GraphicsDeviceInfo, StencilAction,
-    TextureFormat,
+    BlendFactor, BlendOperation, BlendState, Color, DepthTest, FilterMode, GraphicsDeviceInfo,
+    RenderStats, ResourceStats, StencilAction, TextureFormat, UniformInfo, UniformType, WrapMode,
 };
 use crate::math::{Mat2, Mat3, Mat4, Vec2, Vec3, Vec4};
+use crate::platform::resources::{self, Allocation, ResourceCounters, ResourceKind};
 
 type BufferId = <GlowContext as HasContext>::Buffer;
 type ProgramId = <GlowContext as HasContext>::Program;
@@ -40,10 +42,14 @@ struct GraphicsState {
     resolve_framebuffer: FramebufferId,
 
     max_samples: u8,
+    instanced_arrays: bool,
+
+    resources: Rc<ResourceCounters>,
 }
 
 pub struct GraphicsDevice {
     state: Rc<GraphicsState>,
+    render_stats: RenderStats,
 }
 
 impl GraphicsDevice {
@@ -77,6 +83,14 @@ impl GraphicsDevice {
 
             let max_samples = gl.get_parameter_i32(glow::MAX_SAMPLES) as u8;
 
+            // Per-instance attributes are core in GL 3.3, but we only ask for a 3.2
+            // context, so we have to check for the extension on older drivers.
+            let version = gl.version();
+            let instanced_arrays = (version.major, version.minor) >= (3, 3)
+                || gl
+                    .supported_extensions()
+                    .contains("GL_ARB_instanced_arrays");
+
             let state = GraphicsState {
                 gl,
 
@@ -92,10 +106,14 @@ impl GraphicsDevice {
                 resolve_framebuffer,
 
                 max_samples,
+                instanced_arrays,
+
+                resources: Rc::new(ResourceCounters::default()),
             };
 
             Ok(GraphicsDevice {
                 state: Rc::new(state),
+                render_stats: RenderStats::default(),
             })
         }
     }
@@ -114,6 +132,22 @@ impl GraphicsDevice {
         }
     }
 
+    pub fn resource_stats(&self) -> ResourceStats {
+        self.state.resources.stats()
+    }
+
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats
+    }
+
+    pub fn render_stats_mut(&mut self) -> &mut RenderStats {
+        &mut self.render_stats
+    }
+
+    pub fn reset_render_stats(&mut self) {
+        self.render_stats = RenderStats::default();
+    }
+
     pub fn clear(&mut self, color: Color) {
         unsafe {
             self.state
@@ -183,6 +217,25 @@ impl GraphicsDevice {
         }
     }
 
+    pub fn set_depth_test(&mut self, test: DepthTest) {
+        unsafe {
+            match test.to_gl_enum() {
+                Some(func) => {
+                    self.state.gl.enable(glow::DEPTH_TEST);
+                    self.state.gl.depth_func(func);
+                }
+                None => self.state.gl.disable(glow::DEPTH_TEST),
+            }
+        }
+    }
+
+    pub fn clear_depth(&mut self, value: f32) {
+        unsafe {
+            self.state.gl.clear_depth_f32(value);
+            self.state.gl.clear(glow::DEPTH_BUFFER_BIT);
+        }
+    }
+
     pub fn set_color_mask(&mut self, red: bool, green: bool, blue: bool, alpha: bool) {
         unsafe {
             self.state.gl.color_mask(red, green, blue, alpha);
@@ -193,6 +246,41 @@ impl GraphicsDevice {
         &mut self,
         count: usize,
         usage: BufferUsage,
+    ) -> Result<RawVertexBuffer> {
+        self.new_buffer(
+            count,
+            mem::size_of::<Vertex>(),
+            usage,
+            ResourceKind::VertexBuffer,
+        )
+    }
+
+    pub fn new_instance_buffer(
+        &mut self,
+        count: usize,
+        usage: BufferUsage,
+    ) -> Result<RawVertexBuffer> {
+        if !self.state.instanced_arrays {
+            return Err(TetraError::PlatformError(
+                "per-instance data requires OpenGL 3.3 or the ARB_instanced_arrays extension"
+                    .into(),
+            ));
+        }
+
+        self.new_buffer(
+            count,
+            mem::size_of::<Instance>(),
+            usage,
+            ResourceKind::InstanceBuffer,
+        )
+    }
+
+    fn new_buffer(
+        &mut self,
+        count: usize,
+        stride: usize,
+        usage: BufferUsage,
+        kind: ResourceKind,
     ) -> Result<RawVertexBuffer> {
         unsafe {
             let id = self
@@ -205,6 +293,8 @@ impl GraphicsDevice {
                 state: Rc::clone(&self.state),
                 id,
                 count,
+                stride,
+                _allocation: Allocation::new(&self.state.resources, kind, count * stride),
             };
 
             self.bind_vertex_buffer(Some(buffer.id));
@@ -228,12 +318,16 @@ impl GraphicsDevice {
         }
     }
 
-    pub fn set_vertex_buffer_data(
-        &mut self,
-        buffer: &RawVertexBuffer,
-        data: &[Vertex],
-        offset: usize,
-    ) {
+    pub fn set_vertex_buffer_data<T>(&mut self, buffer: &RawVertexBuffer, data: &[T], offset: usize)
+    where
+        T: bytemuck::Pod,
+    {
+        debug_assert_eq!(
+            mem::size_of::<T>(),
+            buffer.stride(),
+            "buffer data does not match the buffer's stride"
+        );
+
         self.bind_vertex_buffer(Some(buffer.id));
 
         assert!(
@@ -250,9 +344,15 @@ impl GraphicsDevice {
                 bytemuck::cast_slice(data),
             );
         }
+
+        self.render_stats.vertex_bytes_uploaded += mem::size_of_val(data);
     }
 
-    fn set_vertex_attributes(&mut self, buffer: &RawVertexBuffer) {
+    fn set_vertex_attributes(
+        &mut self,
+        buffer: &RawVertexBuffer,
+        instance_buffer: Option<&RawVertexBuffer>,
+    ) {
         // TODO: This only works because we don't let the user set custom
         // attribute bindings - will need a rethink at that point!
         unsafe {
@@ -285,9 +385,56 @@ impl GraphicsDevice {
                 16,
             );
 
+            self.state.gl.vertex_attrib_pointer_f32(
+                7,
+                1,
+                glow::FLOAT,
+                false,
+                buffer.stride() as i32,
+                32,
+            );
+
             self.state.gl.enable_vertex_attrib_array(0);
             self.state.gl.enable_vertex_attrib_array(1);
             self.state.gl.enable_vertex_attrib_array(2);
+            self.state.gl.enable_vertex_attrib_array(7);
+
+            match instance_buffer {
+                Some(instance_buffer) => {
+                    self.bind_vertex_buffer(Some(instance_buffer.id));
+
+                    // The transform is a `mat3`, which takes up three attribute locations
+                    // (one for each column), followed by the color.
+                    for (i, offset) in [(3, 0), (4, 12), (5, 24), (6, 36)] {
+                        let size = if i == 6 { 4 } else { 3 };
+
+                        self.state.gl.vertex_attrib_pointer_f32(
+                            i,
+                            size,
+                            glow::FLOAT,
+                            false,
+                            instance_buffer.stride() as i32,
+                            offset,
+                        );
+
+                        self.state.gl.vertex_attrib_divisor(i, 1);
+                        self.state.gl.enable_vertex_attrib_array(i);
+                    }
+                }
+
+                None => {
+                    // When there's no instance data, the attributes are set to constant
+                    // values, so that shaders which use them still work.
+                    for i in 3..=6 {
+                        self.state.gl.disable_vertex_attrib_array(i);
+                    }
+
+                    self.state.gl.vertex_attrib_3_f32(3, 1.0, 0.0, 0.0);
+                    self.state.gl.vertex_attrib_3_f32(4, 0.0, 1.0, 0.0);
+                    self.state.gl.vertex_attrib_3_f32(5, 0.0, 0.0, 1.0);
+                    self.state.gl.vertex_attrib_4_f32(6, 1.0, 1.0, 1.0, 1.0);
+                }
+            }
         }
     }
 
@@ -303,6 +450,11 @@ impl GraphicsDevice {
                 state: Rc::clone(&self.state),
                 id,
                 count,
+                _allocation: Allocation::new(
+                    &self.state.resources,
+                    ResourceKind::IndexBuffer,
+                    count * mem::size_of::<u32>(),
+                ),
             };
 
             self.bind_index_buffer(Some(buffer.id));
@@ -359,6 +511,13 @@ impl GraphicsDevice {
                 .bind_attrib_location(program_id, 0, "a_position");
             self.state.gl.bind_attrib_location(program_id, 1, "a_uv");
             self.state.gl.bind_attrib_location(program_id, 2, "a_color");
+            self.state
+                .gl
+                .bind_attrib_location(program_id, 3, "a_instance_transform");
+            self.state
+                .gl
+                .bind_attrib_location(program_id, 6, "a_instance_color");
+            self.state.gl.bind_attrib_location(program_id, 7, "a_depth");
 
             let vertex_id = self
                 .state
@@ -406,6 +565,7 @@ impl GraphicsDevice {
             let shader = RawShader {
                 state: Rc::clone(&self.state),
                 id: program_id,
+                _allocation: Allocation::new(&self.state.resources, ResourceKind::Shader, 0),
             };
 
             let sampler_location = self.get_uniform_location(&shader, "u_texture");
@@ -415,6 +575,30 @@ impl GraphicsDevice {
         }
     }
 
+    pub fn get_active_uniforms(&self, shader: &RawShader) -> Vec<UniformInfo> {
+        unsafe {
+            let count = self.state.gl.get_active_uniforms(shader.id);
+
+            (0..count)
+                .filter_map(|i| self.state.gl.get_active_uniform(shader.id, i))
+                .map(|uniform| {
+                    // Arrays are reported with the index of the first element attached
+                    // to the name, which isn't what you'd use when setting the uniform.
+                    let name = match uniform.name.strip_suffix("[0]") {
+                        Some(name) => name.to_owned(),
+                        None => uniform.name,
+                    };
+
+                    UniformInfo {
+                        name,
+                        size: uniform.size.max(0) as usize,
+                        uniform_type: UniformType::from_gl_enum(uniform.utype),
+                    }
+                })
+                .collect()
+        }
+    }
+
     pub fn get_uniform_location(&self, shader: &RawShader, name: &str) -> Option<UniformLocation> {
         unsafe { self.state.gl.get_uniform_location(shader.id, name) }
     }
@@ -613,8 +797,8 @@ impl GraphicsDevice {
         height: i32,
         format: TextureFormat,
         filter_mode: FilterMode,
+        wrap_mode: WrapMode,
     ) -> Result<RawTexture> {
-        // TODO: I don't think we need mipmaps?
         unsafe {
             let id = self
                 .state
@@ -626,9 +810,17 @@ impl GraphicsDevice {
                 state: Rc::clone(&self.state),
 
                 id,
-                width,
-                height,
+                width: Cell::new(width),
+                height: Cell::new(height),
                 format,
+                mipmapped: Cell::new(false),
+                owned: true,
+
+                allocation: Allocation::new(
+                    &self.state.resources,
+                    ResourceKind::Texture,
+                    resources::texture_bytes(width, height, format),
+                ),
             };
 
             self.bind_default_texture(Some(texture.id));
@@ -636,31 +828,33 @@ impl GraphicsDevice {
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_MIN_FILTER,
-                filter_mode.to_gl_enum() as i32,
+                filter_mode.to_gl_min_enum() as i32,
             );
 
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_MAG_FILTER,
-                filter_mode.to_gl_enum() as i32,
+                filter_mode.to_gl_mag_enum() as i32,
             );
 
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_WRAP_S,
-                glow::CLAMP_TO_EDGE as i32,
+                wrap_mode.to_gl_enum() as i32,
             );
 
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_WRAP_T,
-                glow::CLAMP_TO_EDGE as i32,
+                wrap_mode.to_gl_enum() as i32,
             );
 
             self.state
                 .gl
                 .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_BASE_LEVEL, 0);
 
+            // Mipmaps are only allocated if `generate_mipmaps` is called, so until
+            // then, the texture is complete with just the base level.
             self.state
                 .gl
                 .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAX_LEVEL, 0);
@@ -690,6 +884,50 @@ impl GraphicsDevice {
         }
     }
 
+    /// Wraps a texture that was created outside of Tetra.
+    ///
+    /// The texture will not be deleted when the returned `RawTexture` is dropped, and
+    /// its memory is not included in the resource stats, as it is not owned by Tetra.
+    ///
+    /// # Safety
+    ///
+    /// The ID must refer to a valid 2D texture in the current OpenGL context, with
+    /// the given size and format, and the texture must outlive the returned
+    /// `RawTexture`.
+    pub unsafe fn wrap_texture(
+        &mut self,
+        id: u32,
+        width: i32,
+        height: i32,
+        format: TextureFormat,
+        filter_mode: FilterMode,
+        wrap_mode: WrapMode,
+    ) -> Result<RawTexture> {
+        let id = NonZeroU32::new(id)
+            .map(glow::NativeTexture)
+            .ok_or_else(|| TetraError::PlatformError("0 is not a valid texture ID".into()))?;
+
+        let texture = RawTexture {
+            state: Rc::clone(&self.state),
+
+            id,
+            width: Cell::new(width),
+            height: Cell::new(height),
+            format,
+            mipmapped: Cell::new(false),
+            owned: false,
+
+            allocation: Allocation::new(&self.state.resources, ResourceKind::Texture, 0),
+        };
+
+        // Tetra keeps track of the filter and wrap mode on the CPU side, so they
+        // have to be set to known values.
+        self.set_texture_filter_mode(&texture, filter_mode);
+        self.set_texture_wrap_mode(&texture, wrap_mode);
+
+        Ok(texture)
+    }
+
     pub fn set_texture_data(
         &mut self,
         texture: &RawTexture,
@@ -700,7 +938,7 @@ impl GraphicsDevice {
         height: i32,
     ) -> Result {
         assert!(
-            x >= 0 && y >= 0 && x + width <= texture.width && y + height <= texture.height,
+            x >= 0 && y >= 0 && x + width <= texture.width() && y + height <= texture.height(),
             "tried to write outside of texture bounds"
         );
 
@@ -740,14 +978,46 @@ impl GraphicsDevice {
             }
         }
 
+        // The smaller levels are derived from the base level, so they need to be
+        // regenerated to reflect the new data.
+        if texture.mipmapped.get() {
+            self.generate_mipmaps(texture);
+        }
+
         Ok(())
     }
 
+    pub fn generate_mipmaps(&mut self, texture: &RawTexture) {
+        self.bind_default_texture(Some(texture.id));
+
+        unsafe {
+            self.state.gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_MAX_LEVEL,
+                resources::mip_levels(texture.width(), texture.height()) as i32 - 1,
+            );
+
+            self.state.gl.generate_mipmap(glow::TEXTURE_2D);
+        }
+
+        // Wrapped textures aren't included in the resource stats, so their
+        // allocation stays empty.
+        if !texture.mipmapped.replace(true) && texture.owned {
+            texture
+                .allocation
+                .set_bytes(resources::mipmapped_texture_bytes(
+                    texture.width(),
+                    texture.height(),
+                    texture.format,
+                ));
+        }
+    }
+
     pub fn get_texture_data(&mut self, texture: &RawTexture) -> Vec<u8> {
         self.bind_default_texture(Some(texture.id));
 
         let mut buffer =
-            vec![0; (texture.width * texture.height) as usize * texture.format.stride()];
+            vec![0; (texture.width() * texture.height()) as usize * texture.format.stride()];
 
         unsafe {
             self.state.gl.get_tex_image(
@@ -769,13 +1039,31 @@ impl GraphicsDevice {
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_MIN_FILTER,
-                filter_mode.to_gl_enum() as i32,
+                filter_mode.to_gl_min_enum() as i32,
             );
 
             self.state.gl.tex_parameter_i32(
                 glow::TEXTURE_2D,
                 glow::TEXTURE_MAG_FILTER,
-                filter_mode.to_gl_enum() as i32,
+                filter_mode.to_gl_mag_enum() as i32,
+            );
+        }
+    }
+
+    pub fn set_texture_wrap_mode(&mut self, texture: &RawTexture, wrap_mode: WrapMode) {
+        self.bind_default_texture(Some(texture.id));
+
+        unsafe {
+            self.state.gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_S,
+                wrap_mode.to_gl_enum() as i32,
+            );
+
+            self.state.gl.tex_parameter_i32(
+                glow::TEXTURE_2D,
+                glow::TEXTURE_WRAP_T,
+                wrap_mode.to_gl_enum() as i32,
             );
         }
     }
@@ -784,14 +1072,20 @@ impl GraphicsDevice {
         self.bind_texture(Some(texture.id), unit)
     }
 
+    pub fn max_texture_units(&self) -> u32 {
+        self.state.current_textures.len() as u32
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn new_canvas(
         &mut self,
         width: i32,
         height: i32,
         format: TextureFormat,
         filter_mode: FilterMode,
+        wrap_mode: WrapMode,
         samples: u8,
-        with_stencil_buffer: bool,
+        with_depth_stencil_buffer: bool,
     ) -> Result<RawCanvasWithAttachments> {
         unsafe {
             let previous_read = self.state.current_read_framebuffer.get();
@@ -806,11 +1100,12 @@ impl GraphicsDevice {
             let canvas = RawCanvas {
                 state: Rc::clone(&self.state),
                 id,
+                _allocation: Allocation::new(&self.state.resources, ResourceKind::Canvas, 0),
             };
 
             self.bind_framebuffer(Some(canvas.id));
 
-            let color = self.new_texture(width, height, format, filter_mode)?;
+            let color = self.new_texture(width, height, format, filter_mode, wrap_mode)?;
 
             self.state.gl.framebuffer_texture_2d(
                 glow::FRAMEBUFFER,
@@ -842,7 +1137,7 @@ impl GraphicsDevice {
                 None
             };
 
-            let depth_stencil = if with_stencil_buffer {
+            let depth_stencil = if with_depth_stencil_buffer {
                 let renderbuffer =
                     self.new_depth_stencil_renderbuffer(width, height, actual_samples)?;
 
@@ -854,7 +1149,7 @@ impl GraphicsDevice {
                 );
 
                 self.clear_stencil(0);
-                // TODO: Clear the depth buffer, if we start using it
+                self.clear_depth(1.0);
 
                 Some(renderbuffer)
             } else {
@@ -884,16 +1179,121 @@ impl GraphicsDevice {
         }
     }
 
+    /// Reallocates the storage for a canvas' attachments at a new size.
+    ///
+    /// The GPU objects themselves are kept, so any handles to them remain valid. The
+    /// contents of the canvas will be cleared.
+    pub fn resize_canvas(
+        &mut self,
+        canvas: &RawCanvas,
+        color: &RawTexture,
+        multisample_color: Option<&RawRenderbuffer>,
+        depth_stencil: Option<&RawRenderbuffer>,
+        width: i32,
+        height: i32,
+    ) -> Result {
+        unsafe {
+            let previous_read = self.state.current_read_framebuffer.get();
+            let previous_draw = self.state.current_draw_framebuffer.get();
+
+            self.bind_default_texture(Some(color.id));
+            self.clear_errors();
+
+            self.state.gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                color.format.to_gl_internal_format() as i32,
+                width,
+                height,
+                0,
+                color.format.to_gl_format(),
+                color.format.to_gl_data_type(),
+                PixelUnpackData::Slice(None),
+            );
+
+            if let Some(e) = self.get_error() {
+                return Err(TetraError::PlatformError(format_gl_error(
+                    "failed to resize canvas",
+                    e,
+                )));
+            }
+
+            color.width.set(width);
+            color.height.set(height);
+            color
+                .allocation
+                .set_bytes(resources::texture_bytes(width, height, color.format));
+
+            self.bind_framebuffer(Some(canvas.id));
+
+            // The texture is always cleared, even if it's hidden behind a multisample
+            // buffer, to match the state of a newly created canvas.
+            self.state.gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(color.id),
+                0,
+            );
+
+            self.clear(Color::rgba(0.0, 0.0, 0.0, 0.0));
+
+            if let Some(renderbuffer) = multisample_color {
+                self.allocate_renderbuffer(renderbuffer, width, height);
+
+                self.state.gl.framebuffer_renderbuffer(
+                    glow::FRAMEBUFFER,
+                    glow::COLOR_ATTACHMENT0,
+                    glow::RENDERBUFFER,
+                    Some(renderbuffer.id),
+                );
+
+                self.clear(Color::rgba(0.0, 0.0, 0.0, 0.0));
+            }
+
+            if let Some(renderbuffer) = depth_stencil {
+                self.allocate_renderbuffer(renderbuffer, width, height);
+                self.clear_stencil(0);
+                self.clear_depth(1.0);
+            }
+
+            let status = self.state.gl.check_framebuffer_status(glow::FRAMEBUFFER);
+
+            self.bind_read_framebuffer(previous_read);
+            self.bind_draw_framebuffer(previous_draw);
+
+            if status != glow::FRAMEBUFFER_COMPLETE {
+                return Err(TetraError::PlatformError(format_gl_framebuffer_status(
+                    "failed to resize canvas",
+                    status,
+                )));
+            }
+
+            Ok(())
+        }
+    }
+
     pub fn set_canvas(&mut self, canvas: Option<&RawCanvas>) {
         self.bind_framebuffer(canvas.map(|f| f.id));
     }
 
     pub fn resolve(&mut self, canvas: &RawCanvas, texture: &RawTexture) {
+        self.copy_to_texture(Some(canvas), texture, false);
+    }
+
+    /// Copies the contents of a framebuffer (or the backbuffer, if `canvas` is `None`)
+    /// into a texture of the same size.
+    pub fn copy_to_texture(
+        &mut self,
+        canvas: Option<&RawCanvas>,
+        texture: &RawTexture,
+        flip: bool,
+    ) {
         unsafe {
             let previous_read = self.state.current_read_framebuffer.get();
             let previous_draw = self.state.current_draw_framebuffer.get();
 
-            self.bind_read_framebuffer(Some(canvas.id));
+            self.bind_read_framebuffer(canvas.map(|c| c.id));
             self.bind_draw_framebuffer(Some(self.state.resolve_framebuffer));
 
             self.state.gl.framebuffer_texture_2d(
@@ -904,15 +1304,18 @@ impl GraphicsDevice {
                 0,
             );
 
+            let (width, height) = (texture.width(), texture.height());
+            let (dst_y0, dst_y1) = if flip { (height, 0) } else { (0, height) };
+
             self.state.gl.blit_framebuffer(
                 0,
                 0,
-                texture.width,
-                texture.height,
-                0,
+                width,
+                height,
                 0,
-                texture.width,
-                texture.height,
+                dst_y0,
+                width,
+                dst_y1,
                 glow::COLOR_BUFFER_BIT,
                 glow::NEAREST,
             );
@@ -929,7 +1332,13 @@ impl GraphicsDevice {
         format: TextureFormat,
         samples: u8,
     ) -> Result<RawRenderbuffer> {
-        self.new_renderbuffer(width, height, format.to_gl_internal_format(), samples)
+        self.new_renderbuffer(
+            width,
+            height,
+            format.to_gl_internal_format(),
+            format.stride(),
+            samples,
+        )
     }
 
     pub fn new_depth_stencil_renderbuffer(
@@ -938,7 +1347,8 @@ impl GraphicsDevice {
         height: i32,
         samples: u8,
     ) -> Result<RawRenderbuffer> {
-        self.new_renderbuffer(width, height, glow::DEPTH24_STENCIL8, samples)
+        // DEPTH24_STENCIL8 is packed into 4 bytes per pixel.
+        self.new_renderbuffer(width, height, glow::DEPTH24_STENCIL8, 4, samples)
     }
 
     fn new_renderbuffer(
@@ -946,6 +1356,7 @@ impl GraphicsDevice {
         width: i32,
         height: i32,
         format: u32,
+        bytes_per_pixel: usize,
         samples: u8,
     ) -> Result<RawRenderbuffer> {
         unsafe {
@@ -958,26 +1369,52 @@ impl GraphicsDevice {
             let renderbuffer = RawRenderbuffer {
                 state: Rc::clone(&self.state),
                 id,
+                format,
+                bytes_per_pixel,
+                samples,
+                allocation: Allocation::new(
+                    &self.state.resources,
+                    ResourceKind::Renderbuffer,
+                    resources::renderbuffer_bytes(width, height, bytes_per_pixel, samples),
+                ),
             };
 
+            self.allocate_renderbuffer(&renderbuffer, width, height);
+
+            Ok(renderbuffer)
+        }
+    }
+
+    fn allocate_renderbuffer(&mut self, renderbuffer: &RawRenderbuffer, width: i32, height: i32) {
+        unsafe {
             self.bind_renderbuffer(Some(renderbuffer.id));
 
-            if samples > 0 {
+            if renderbuffer.samples > 0 {
                 self.state.gl.renderbuffer_storage_multisample(
                     glow::RENDERBUFFER,
-                    samples.into(),
-                    format,
+                    renderbuffer.samples.into(),
+                    renderbuffer.format,
                     width,
                     height,
                 );
             } else {
-                self.state
-                    .gl
-                    .renderbuffer_storage(glow::RENDERBUFFER, format, width, height);
+                self.state.gl.renderbuffer_storage(
+                    glow::RENDERBUFFER,
+                    renderbuffer.format,
+                    width,
+                    height,
+                );
             }
-
-            Ok(renderbuffer)
         }
+
+        renderbuffer
+            .allocation
+            .set_bytes(resources::renderbuffer_bytes(
+                width,
+                height,
+                renderbuffer.bytes_per_pixel,
+                renderbuffer.samples,
+            ));
     }
 
     pub fn viewport(&mut self, x: i32, y: i32, width: i32, height: i32) {
@@ -998,6 +1435,7 @@ impl GraphicsDevice {
         self.draw_instanced(
             vertex_buffer,
             index_buffer,
+            None,
             texture,
             shader,
             offset,
@@ -1011,16 +1449,18 @@ impl GraphicsDevice {
         &mut self,
         vertex_buffer: &RawVertexBuffer,
         index_buffer: Option<&RawIndexBuffer>,
+        instance_buffer: Option<&RawVertexBuffer>,
         texture: &RawTexture,
         shader: &RawShader,
         offset: usize,
         count: usize,
         instances: usize,
     ) {
-        self.bind_vertex_buffer(Some(vertex_buffer.id));
         self.bind_default_texture(Some(texture.id));
         self.bind_program(Some(shader.id));
-        self.set_vertex_attributes(vertex_buffer);
+        self.set_vertex_attributes(vertex_buffer, instance_buffer);
+
+        self.render_stats.draw_calls += 1;
 
         match index_buffer {
             Some(index_buffer) => {
@@ -1221,10 +1661,48 @@ impl VertexWinding {
 
 #[doc(hidden)]
 impl FilterMode {
-    fn to_gl_enum(self) -> u32 {
+    fn to_gl_min_enum(self) -> u32 {
         match self {
             FilterMode::Nearest => glow::NEAREST,
             FilterMode::Linear => glow::LINEAR,
+            FilterMode::Trilinear => glow::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    fn to_gl_mag_enum(self) -> u32 {
+        match self {
+            FilterMode::Nearest => glow::NEAREST,
+            FilterMode::Linear | FilterMode::Trilinear => glow::LINEAR,
+        }
+    }
+}
+
+#[doc(hidden)]
+impl UniformType {
+    fn from_gl_enum(value: u32) -> UniformType {
+        match value {
+            glow::INT => UniformType::Int,
+            glow::UNSIGNED_INT => UniformType::UInt,
+            glow::FLOAT => UniformType::Float,
+            glow::FLOAT_VEC2 => UniformType::Vec2,
+            glow::FLOAT_VEC3 => UniformType::Vec3,
+            glow::FLOAT_VEC4 => UniformType::Vec4,
+            glow::FLOAT_MAT2 => UniformType::Mat2,
+            glow::FLOAT_MAT3 => UniformType::Mat3,
+            glow::FLOAT_MAT4 => UniformType::Mat4,
+            glow::SAMPLER_2D => UniformType::Sampler2D,
+            _ => UniformType::Other,
+        }
+    }
+}
+
+#[doc(hidden)]
+impl WrapMode {
+    fn to_gl_enum(self) -> u32 {
+        match self {
+            WrapMode::Clamp => glow::CLAMP_TO_EDGE,
+            WrapMode::Repeat => glow::REPEAT,
+            WrapMode::MirroredRepeat => glow::MIRRORED_REPEAT,
         }
     }
 }
@@ -1284,6 +1762,23 @@ impl StencilTest {
     }
 }
 
+#[doc(hidden)]
+impl DepthTest {
+    fn to_gl_enum(self) -> Option<u32> {
+        match self {
+            DepthTest::Off => None,
+            DepthTest::Never => Some(glow::NEVER),
+            DepthTest::Less => Some(glow::LESS),
+            DepthTest::LessOrEqual => Some(glow::LEQUAL),
+            DepthTest::Equal => Some(glow::EQUAL),
+            DepthTest::NotEqual => Some(glow::NOTEQUAL),
+            DepthTest::Greater => Some(glow::GREATER),
+            DepthTest::GreaterOrEqual => Some(glow::GEQUAL),
+            DepthTest::Always => Some(glow::ALWAYS),
+        }
+    }
+}
+
 #[doc(hidden)]
 impl BlendOperation {
     fn to_gl_enum(self) -> u32 {
@@ -1340,17 +1835,20 @@ pub struct RawVertexBuffer {
     id: BufferId,
 
     count: usize,
+    stride: usize,
+
+    _allocation: Allocation,
 }
 
 impl RawVertexBuffer {
-    /// The number of vertices in the buffer.
+    /// The number of vertices (or instances) in the buffer.
     pub fn count(&self) -> usize {
         self.count
     }
 
-    // The size of each vertex, in bytes.
+    // The size of each vertex (or instance), in bytes.
     pub fn stride(&self) -> usize {
-        std::mem::size_of::<Vertex>()
+        self.stride
     }
 
     /// The size of the buffer, in bytes.
@@ -1383,6 +1881,8 @@ pub struct RawIndexBuffer {
     id: BufferId,
 
     count: usize,
+
+    _allocation: Allocation,
 }
 
 impl RawIndexBuffer {
@@ -1424,6 +1924,8 @@ impl Drop for RawIndexBuffer {
 pub struct RawShader {
     state: Rc<GraphicsState>,
     id: ProgramId,
+
+    _allocation: Allocation,
 }
 
 impl PartialEq for RawShader {
@@ -1449,23 +1951,44 @@ pub struct RawTexture {
     state: Rc<GraphicsState>,
     id: TextureId,
 
-    width: i32,
-    height: i32,
+    // These can change if the texture is the color attachment of a canvas that
+    // gets resized.
+    width: Cell<i32>,
+    height: Cell<i32>,
     format: TextureFormat,
+    mipmapped: Cell<bool>,
+
+    // Textures that were created outside of Tetra are not deleted on drop.
+    owned: bool,
+
+    allocation: Allocation,
 }
 
 impl RawTexture {
     pub fn width(&self) -> i32 {
-        self.width
+        self.width.get()
     }
 
     pub fn height(&self) -> i32 {
-        self.height
+        self.height.get()
     }
 
     pub fn format(&self) -> TextureFormat {
         self.format
     }
+
+    pub fn has_mipmaps(&self) -> bool {
+        self.mipmapped.get()
+    }
+
+    pub fn raw_id(&self) -> u32 {
+        self.id.0.get()
+    }
+
+    /// The estimated amount of GPU memory used by the texture, in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.allocation.bytes()
+    }
 }
 
 impl PartialEq for RawTexture {
@@ -1483,7 +2006,9 @@ impl Drop for RawTexture {
                 }
             }
 
-            self.state.gl.delete_texture(self.id);
+            if self.owned {
+                self.state.gl.delete_texture(self.id);
+            }
         }
     }
 }
@@ -1492,6 +2017,8 @@ impl Drop for RawTexture {
 pub struct RawCanvas {
     state: Rc<GraphicsState>,
     id: FramebufferId,
+
+    _allocation: Allocation,
 }
 
 impl PartialEq for RawCanvas {
@@ -1527,6 +2054,19 @@ pub struct RawCanvasWithAttachments {
 pub struct RawRenderbuffer {
     state: Rc<GraphicsState>,
     id: RenderbufferId,
+
+    format: u32,
+    bytes_per_pixel: usize,
+    samples: u8,
+
+    allocation: Allocation,
+}
+
+impl RawRenderbuffer {
+    /// The estimated amount of GPU memory used by the renderbuffer, in bytes.
+    pub fn size_in_bytes(&self) -> usize {
+        self.allocation.bytes()
+    }
 }
 
 impl PartialEq for RawRenderbuffer {
@@ -0,0 +1,53 @@
+//! MacOS-specific functionality that is not exposed by SDL.
+
+use objc2::msg_send;
+use objc2::runtime::{AnyClass, AnyObject};
+
+use crate::error::{Result, TetraError};
+use crate::window::ActivationPolicy;
+
+pub fn set_activation_policy(policy: ActivationPolicy) -> Result {
+    // These values match `NSApplicationActivationPolicy`.
+    let policy: isize = match policy {
+        ActivationPolicy::Regular => 0,
+        ActivationPolicy::Accessory => 1,
+        ActivationPolicy::Prohibited => 2,
+    };
+
+    let class = AnyClass::get("NSApplication")
+        .ok_or_else(|| TetraError::PlatformError("could not find NSApplication class".into()))?;
+
+    // SAFETY: The return and argument types match the declarations of the
+    // corresponding `NSApplication` methods.
+    unsafe {
+        let app: *mut AnyObject = msg_send![class, sharedApplication];
+
+        if app.is_null() {
+            return Err(TetraError::PlatformError(
+                "could not get shared NSApplication".into(),
+            ));
+        }
+
+        let success: bool = msg_send![app, setActivationPolicy: policy];
+
+        if success {
+            Ok(())
+        } else {
+            Err(TetraError::PlatformError(
+                "failed to set activation policy".into(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[ignore = "requires a display"]
+    fn activation_policy_can_be_changed() {
+        set_activation_policy(ActivationPolicy::Accessory).unwrap();
+        set_activation_policy(ActivationPolicy::Regular).unwrap();
+    }
+}
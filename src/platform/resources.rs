@@ -0,0 +1,227 @@
+//! Tracking for the GPU resources created by the graphics device.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::graphics::{ResourceStats, TextureFormat};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResourceKind {
+    Texture,
+    VertexBuffer,
+    InstanceBuffer,
+    IndexBuffer,
+    Renderbuffer,
+    Canvas,
+    Shader,
+}
+
+impl ResourceKind {
+    const COUNT: usize = 7;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ResourceCounters {
+    counts: [Cell<usize>; ResourceKind::COUNT],
+    bytes: [Cell<usize>; ResourceKind::COUNT],
+}
+
+impl ResourceCounters {
+    pub fn stats(&self) -> ResourceStats {
+        let count = |kind: ResourceKind| self.counts[kind.index()].get();
+        let bytes = |kind: ResourceKind| self.bytes[kind.index()].get();
+
+        ResourceStats {
+            textures: count(ResourceKind::Texture),
+            texture_bytes: bytes(ResourceKind::Texture),
+            vertex_buffers: count(ResourceKind::VertexBuffer),
+            vertex_buffer_bytes: bytes(ResourceKind::VertexBuffer),
+            instance_buffers: count(ResourceKind::InstanceBuffer),
+            instance_buffer_bytes: bytes(ResourceKind::InstanceBuffer),
+            index_buffers: count(ResourceKind::IndexBuffer),
+            index_buffer_bytes: bytes(ResourceKind::IndexBuffer),
+            renderbuffers: count(ResourceKind::Renderbuffer),
+            renderbuffer_bytes: bytes(ResourceKind::Renderbuffer),
+            canvases: count(ResourceKind::Canvas),
+            shaders: count(ResourceKind::Shader),
+        }
+    }
+}
+
+/// Records a resource in the counters for as long as it is alive.
+///
+/// This should be stored alongside the handle to the underlying resource, so that
+/// it gets dropped at the same time.
+#[derive(Debug)]
+pub struct Allocation {
+    counters: Rc<ResourceCounters>,
+    kind: ResourceKind,
+    bytes: Cell<usize>,
+}
+
+impl Allocation {
+    pub fn new(counters: &Rc<ResourceCounters>, kind: ResourceKind, bytes: usize) -> Allocation {
+        let index = kind.index();
+
+        counters.counts[index].set(counters.counts[index].get() + 1);
+        counters.bytes[index].set(counters.bytes[index].get() + bytes);
+
+        Allocation {
+            counters: Rc::clone(counters),
+            kind,
+            bytes: Cell::new(bytes),
+        }
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes.get()
+    }
+
+    /// Updates the size of the allocation (e.g. after a resource's storage has been
+    /// reallocated in place).
+    pub fn set_bytes(&self, bytes: usize) {
+        let total = &self.counters.bytes[self.kind.index()];
+
+        total.set(total.get().saturating_sub(self.bytes.get()) + bytes);
+        self.bytes.set(bytes);
+    }
+}
+
+impl Drop for Allocation {
+    fn drop(&mut self) {
+        let index = self.kind.index();
+
+        let counts = &self.counters.counts[index];
+        let bytes = &self.counters.bytes[index];
+
+        counts.set(counts.get().saturating_sub(1));
+        bytes.set(bytes.get().saturating_sub(self.bytes.get()));
+    }
+}
+
+pub fn texture_bytes(width: i32, height: i32, format: TextureFormat) -> usize {
+    pixel_count(width, height) * format.stride()
+}
+
+/// Returns the number of mipmap levels in a full chain for a texture of the given size,
+/// including the base level.
+pub fn mip_levels(width: i32, height: i32) -> u32 {
+    let largest = width.max(height).max(1) as u32;
+
+    u32::BITS - largest.leading_zeros()
+}
+
+/// Returns the size of a texture, including a full chain of mipmaps.
+pub fn mipmapped_texture_bytes(width: i32, height: i32, format: TextureFormat) -> usize {
+    (0..mip_levels(width, height))
+        .map(|level| texture_bytes((width >> level).max(1), (height >> level).max(1), format))
+        .sum()
+}
+
+pub fn renderbuffer_bytes(width: i32, height: i32, bytes_per_pixel: usize, samples: u8) -> usize {
+    pixel_count(width, height) * bytes_per_pixel * usize::from(samples.max(1))
+}
+
+fn pixel_count(width: i32, height: i32) -> usize {
+    width.max(0) as usize * height.max(0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocations_are_tracked_until_dropped() {
+        let counters = Rc::new(ResourceCounters::default());
+
+        let a = Allocation::new(&counters, ResourceKind::Texture, 64);
+        let b = Allocation::new(&counters, ResourceKind::Texture, 16);
+        let c = Allocation::new(&counters, ResourceKind::Shader, 0);
+
+        let stats = counters.stats();
+        assert_eq!(2, stats.textures);
+        assert_eq!(80, stats.texture_bytes);
+        assert_eq!(1, stats.shaders);
+        assert_eq!(0, stats.vertex_buffers);
+
+        drop(a);
+
+        let stats = counters.stats();
+        assert_eq!(1, stats.textures);
+        assert_eq!(16, stats.texture_bytes);
+
+        drop(b);
+        drop(c);
+
+        assert_eq!(ResourceStats::default(), counters.stats());
+    }
+
+    #[test]
+    fn resized_allocations_update_totals() {
+        let counters = Rc::new(ResourceCounters::default());
+
+        let a = Allocation::new(&counters, ResourceKind::Texture, 64);
+        let _b = Allocation::new(&counters, ResourceKind::Texture, 16);
+
+        a.set_bytes(256);
+
+        let stats = counters.stats();
+        assert_eq!(2, stats.textures);
+        assert_eq!(272, stats.texture_bytes);
+
+        drop(a);
+        assert_eq!(16, counters.stats().texture_bytes);
+    }
+
+    #[test]
+    fn allocations_for_different_kinds_are_separate() {
+        let counters = Rc::new(ResourceCounters::default());
+
+        let _vertices = Allocation::new(&counters, ResourceKind::VertexBuffer, 100);
+        let _indices = Allocation::new(&counters, ResourceKind::IndexBuffer, 24);
+        let _renderbuffer = Allocation::new(&counters, ResourceKind::Renderbuffer, 400);
+
+        let stats = counters.stats();
+        assert_eq!((1, 100), (stats.vertex_buffers, stats.vertex_buffer_bytes));
+        assert_eq!((1, 24), (stats.index_buffers, stats.index_buffer_bytes));
+        assert_eq!((1, 400), (stats.renderbuffers, stats.renderbuffer_bytes));
+        assert_eq!(524, stats.total_bytes());
+    }
+
+    #[test]
+    fn texture_sizes_account_for_format() {
+        assert_eq!(4 * 4 * 4, texture_bytes(4, 4, TextureFormat::Rgba8));
+        assert_eq!(4 * 4, texture_bytes(4, 4, TextureFormat::R8));
+        assert_eq!(4 * 4 * 2, texture_bytes(4, 4, TextureFormat::Rg8));
+        assert_eq!(4 * 4 * 8, texture_bytes(4, 4, TextureFormat::Rgba16F));
+        assert_eq!(0, texture_bytes(0, 4, TextureFormat::Rgba8));
+    }
+
+    #[test]
+    fn mipmap_chains_go_down_to_one_pixel() {
+        assert_eq!(1, mip_levels(1, 1));
+        assert_eq!(9, mip_levels(256, 256));
+        assert_eq!(9, mip_levels(256, 16));
+        assert_eq!(9, mip_levels(300, 2));
+
+        // 4x4 + 2x2 + 1x1
+        assert_eq!(
+            (16 + 4 + 1) * 4,
+            mipmapped_texture_bytes(4, 4, TextureFormat::Rgba8)
+        );
+
+        // 4x1 + 2x1 + 1x1
+        assert_eq!(4 + 2 + 1, mipmapped_texture_bytes(4, 1, TextureFormat::R8));
+    }
+
+    #[test]
+    fn renderbuffer_sizes_account_for_samples() {
+        assert_eq!(10 * 10 * 4, renderbuffer_bytes(10, 10, 4, 0));
+        assert_eq!(10 * 10 * 4, renderbuffer_bytes(10, 10, 4, 1));
+        assert_eq!(10 * 10 * 4 * 8, renderbuffer_bytes(10, 10, 4, 8));
+    }
+}
@@ -1,37 +1,82 @@
 // TODO: This file is getting way too huge.
+use std::fmt::{self, Debug, Formatter};
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::result;
 
 use glow::Context as GlowContext;
 use hashbrown::HashMap;
 use sdl2::controller::{Axis as SdlGamepadAxis, Button as SdlGamepadButton, GameController};
-use sdl2::event::{Event as SdlEvent, WindowEvent};
+use sdl2::event::{DisplayEvent, Event as SdlEvent, WindowEvent};
 use sdl2::keyboard::{Keycode, Mod, Scancode};
-use sdl2::mouse::{MouseButton as SdlMouseButton, MouseWheelDirection};
-use sdl2::pixels::PixelMasks;
+use sdl2::mouse::{
+    Cursor as SdlCursor, MouseButton as SdlMouseButton, MouseWheelDirection,
+    SystemCursor as SdlSystemCursor,
+};
+use sdl2::pixels::{PixelFormatEnum, PixelMasks};
+use sdl2::rect::Rect;
 use sdl2::surface::Surface;
-use sdl2::sys::SDL_WINDOWPOS_CENTERED_MASK;
+use sdl2::sys::{SDL_bool, SDL_WINDOWPOS_CENTERED_MASK};
 use sdl2::video::{
-    FullscreenType, GLContext as SdlGlContext, GLProfile, SwapInterval, Window as SdlWindow,
-    WindowPos,
+    DisplayMode as SdlDisplayMode, FullscreenType, GLContext as SdlGlContext, GLProfile,
+    SwapInterval, Window as SdlWindow, WindowPos,
 };
 use sdl2::{EventPump, GameControllerSubsystem, JoystickSubsystem, Sdl, VideoSubsystem};
 
+#[cfg(feature = "audio")]
+use sdl2::AudioSubsystem;
+
 use crate::error::{Result, TetraError};
-use crate::graphics::{self, ImageData};
+use crate::graphics::{self, ImageData, Rectangle, TextureFormat};
 use crate::input::{
-    self, GamepadAxis, GamepadButton, GamepadStick, Key, KeyLabel, KeyModifierState, MouseButton,
+    self, GamepadAxis, GamepadBatteryLevel, GamepadButton, GamepadPowerInfo, GamepadSensor,
+    GamepadStick, GamepadTouchPhase, GamepadType, Key, KeyLabel, KeyModifierState, MouseButton,
+};
+use crate::math::{Vec2, Vec3};
+use crate::window::{
+    self, ActivationPolicy, DisplayMode, FullscreenMode, PendingWindowMetrics, SystemCursor,
+    VsyncMode, WindowMetrics, WindowPosition,
 };
-use crate::math::Vec2;
-use crate::window::WindowPosition;
 use crate::{Context, ContextBuilder, Event, State};
 
+#[cfg(target_os = "macos")]
+use crate::platform::macos;
+
 struct SdlController {
     controller: GameController,
     slot: usize,
     supports_rumble: bool,
 }
 
+pub struct RawCursor {
+    cursor: SdlCursor,
+}
+
+/// The state of the window before it entered fullscreen, so that it can be restored
+/// afterwards.
+struct WindowedGeometry {
+    bounds: Rectangle<i32>,
+    maximized: bool,
+}
+
+impl WindowedGeometry {
+    fn new(sdl_window: &SdlWindow) -> WindowedGeometry {
+        let (x, y) = sdl_window.position();
+        let (width, height) = sdl_window.size();
+
+        WindowedGeometry {
+            bounds: Rectangle::new(x, y, width as i32, height as i32),
+            maximized: sdl_window.is_maximized(),
+        }
+    }
+}
+
+impl Debug for RawCursor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawCursor").finish_non_exhaustive()
+    }
+}
+
 pub struct Window {
     sdl: Sdl,
     sdl_window: SdlWindow,
@@ -40,23 +85,64 @@ pub struct Window {
     video_sys: VideoSubsystem,
     controller_sys: GameControllerSubsystem,
     _joystick_sys: JoystickSubsystem,
+    #[cfg(feature = "audio")]
+    _audio_sys: Option<AudioSubsystem>,
     _gl_sys: SdlGlContext,
 
     controllers: HashMap<u32, SdlController>,
 
     window_visible: bool,
+    headless: bool,
 
     key_repeat: bool,
+
+    // SDL does not keep track of whether a cursor is still in use, so we hold a
+    // reference to the active one to stop it being freed.
+    cursor: Option<Rc<RawCursor>>,
+
+    metrics: PendingWindowMetrics,
+
+    windowed_geometry: Option<WindowedGeometry>,
+
+    aspect_ratio: Option<(f32, f32)>,
+
+    // The size from the most recent `Resized` event, so that SDL re-reporting a size
+    // (e.g. after we resize the window to fit the aspect ratio) doesn't fire it twice.
+    last_resize: Option<(i32, i32)>,
+
+    // The files that have been dropped since SDL reported the start of a drop,
+    // so that they can be reported as a single batch.
+    dropped_files: Option<Vec<PathBuf>>,
 }
 
 impl Window {
     pub fn new(settings: &ContextBuilder) -> Result<(Window, GlowContext, i32, i32)> {
+        // SDL reads these from the environment (rather than via hints) when the video
+        // subsystem is initialized, so they must be set before anything else happens.
+        if let Some(app_id) = &settings.app_id {
+            for name in app_id_env_vars(|name| std::env::var_os(name).is_some()) {
+                std::env::set_var(name, app_id);
+            }
+        }
+
+        // The offscreen driver doesn't need a display, but still supports OpenGL (via EGL).
+        // Setting the SDL_VIDEODRIVER environment variable will override this.
+        if settings.headless {
+            sdl2::hint::set("SDL_VIDEODRIVER", "offscreen");
+        }
+
         let sdl = sdl2::init().map_err(TetraError::PlatformError)?;
         let event_pump = sdl.event_pump().map_err(TetraError::PlatformError)?;
         let video_sys = sdl.video().map_err(TetraError::PlatformError)?;
         let joystick_sys = sdl.joystick().map_err(TetraError::PlatformError)?;
         let controller_sys = sdl.game_controller().map_err(TetraError::PlatformError)?;
 
+        // Audio playback is handled by Rodio rather than SDL, but initializing SDL's
+        // audio subsystem allows us to get notified when audio devices are added or
+        // removed. If this fails, device changes just won't be detected.
+        #[cfg(feature = "audio")]
+        let audio_sys = sdl.audio().ok();
+
         sdl2::hint::set("SDL_JOYSTICK_ALLOW_BACKGROUND_EVENTS", "1");
 
         let gl_attr = video_sys.gl_attr();
@@ -78,6 +164,10 @@ impl Window {
             gl_attr.set_stencil_size(8);
         }
 
+        if settings.depth_buffer {
+            gl_attr.set_depth_size(24);
+        }
+
         if settings.screen_saver_enabled {
             video_sys.enable_screen_saver();
         } else {
@@ -138,7 +228,11 @@ impl Window {
             window_height = size.1 as i32;
         }
 
+        let mut windowed_geometry = None;
+
         if settings.fullscreen {
+            windowed_geometry = Some(WindowedGeometry::new(&sdl_window));
+
             sdl_window
                 .display_mode()
                 .and_then(|m| {
@@ -149,6 +243,24 @@ impl Window {
                 .map_err(TetraError::FailedToChangeDisplayMode)?;
         }
 
+        if let Some((min, max)) = settings.aspect_ratio {
+            window::validate_aspect_ratio(min, max)?;
+
+            if !settings.maximized && !settings.minimized && !settings.fullscreen {
+                let (width, height) =
+                    window::constrain_aspect_ratio((window_width, window_height), min, max);
+
+                if (width, height) != (window_width, window_height) {
+                    sdl_window
+                        .set_size(width as u32, height as u32)
+                        .map_err(|e| TetraError::FailedToChangeDisplayMode(e.to_string()))?;
+
+                    window_width = width;
+                    window_height = height;
+                }
+            }
+        }
+
         let gl_sys = sdl_window
             .gl_create_context()
             .map_err(TetraError::PlatformError)?;
@@ -157,11 +269,9 @@ impl Window {
             GlowContext::from_loader_function(|s| video_sys.gl_get_proc_address(s) as *const _)
         };
 
-        let _ = video_sys.gl_set_swap_interval(if settings.vsync {
-            SwapInterval::VSync
-        } else {
-            SwapInterval::Immediate
-        });
+        let _ = set_swap_interval(&video_sys, settings.vsync);
+
+        let metrics = PendingWindowMetrics::new(window_metrics(&sdl_window));
 
         let window = Window {
             sdl,
@@ -171,13 +281,28 @@ impl Window {
             video_sys,
             controller_sys,
             _joystick_sys: joystick_sys,
+            #[cfg(feature = "audio")]
+            _audio_sys: audio_sys,
             _gl_sys: gl_sys,
 
             controllers: HashMap::new(),
 
             window_visible: false,
+            headless: settings.headless,
 
             key_repeat: settings.key_repeat,
+
+            cursor: None,
+
+            metrics,
+
+            windowed_geometry,
+
+            aspect_ratio: settings.aspect_ratio,
+
+            last_resize: None,
+
+            dropped_files: None,
         };
 
         Ok((window, gl_ctx, window_width, window_height))
@@ -199,6 +324,19 @@ impl Window {
         self.sdl_window.raise()
     }
 
+    pub fn set_activation_policy(&mut self, policy: ActivationPolicy) -> Result {
+        #[cfg(target_os = "macos")]
+        {
+            macos::set_activation_policy(policy)
+        }
+
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = policy;
+            Ok(())
+        }
+    }
+
     pub fn get_refresh_rate(&self) -> Result<i32> {
         self.sdl_window
             .display_mode()
@@ -255,6 +393,42 @@ impl Window {
         (width as i32, height as i32)
     }
 
+    pub fn get_aspect_ratio(&self) -> Option<(f32, f32)> {
+        self.aspect_ratio
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: Option<(f32, f32)>) -> Result {
+        if let Some((min, max)) = aspect_ratio {
+            window::validate_aspect_ratio(min, max)?;
+        }
+
+        self.aspect_ratio = aspect_ratio;
+
+        let (width, height) = self.get_window_size();
+
+        match self.constrained_size(width, height) {
+            Some((width, height)) => self.set_window_size(width, height),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the size that the window should be changed to in order to fit the
+    /// aspect ratio constraint, or `None` if no change is needed.
+    fn constrained_size(&self, width: i32, height: i32) -> Option<(i32, i32)> {
+        let (min, max) = self.aspect_ratio?;
+
+        // The window manager is in control of the size in these states, so
+        // trying to override it would just cause flickering.
+        if self.is_fullscreen() || self.sdl_window.is_maximized() || self.sdl_window.is_minimized()
+        {
+            return None;
+        }
+
+        let constrained = window::constrain_aspect_ratio((width, height), min, max);
+
+        (constrained != (width, height)).then_some(constrained)
+    }
+
     pub fn set_position(&mut self, x: WindowPosition, y: WindowPosition) {
         self.sdl_window.set_position(x.into(), y.into());
     }
@@ -267,6 +441,34 @@ impl Window {
         self.sdl_window.set_bordered(bordered);
     }
 
+    pub fn get_opacity(&self) -> Result<f32> {
+        self.sdl_window.opacity().map_err(TetraError::PlatformError)
+    }
+
+    pub fn set_opacity(&mut self, opacity: f32) -> Result {
+        self.sdl_window
+            .set_opacity(opacity)
+            .map_err(TetraError::PlatformError)
+    }
+
+    pub fn is_always_on_top(&self) -> bool {
+        self.sdl_window.is_always_on_top()
+    }
+
+    pub fn set_always_on_top(&mut self, always_on_top: bool) -> Result {
+        self.sdl_window.set_always_on_top(always_on_top);
+
+        // SDL doesn't report whether this succeeded, so we have to check the
+        // window flags to find out if the platform ignored the request.
+        if self.is_always_on_top() == always_on_top {
+            Ok(())
+        } else {
+            Err(TetraError::PlatformError(
+                "the platform does not support always-on-top windows".into(),
+            ))
+        }
+    }
+
     pub fn set_icon(&mut self, data: &mut ImageData) -> Result {
         let (width, height) = data.size();
 
@@ -295,6 +497,11 @@ impl Window {
     }
 
     pub fn set_visible(&mut self, visible: bool) {
+        // A headless window is never shown.
+        if self.headless {
+            return;
+        }
+
         if visible {
             self.sdl_window.show();
         } else {
@@ -308,6 +515,15 @@ impl Window {
         self.sdl_window.drawable_size().0 as f32 / self.sdl_window.size().0 as f32
     }
 
+    fn invalidate_metrics(&mut self) {
+        self.metrics.invalidate();
+    }
+
+    fn resolve_metrics(&mut self) -> Option<WindowMetrics> {
+        let sdl_window = &self.sdl_window;
+        self.metrics.resolve(|| window_metrics(sdl_window))
+    }
+
     pub fn get_monitor_count(&self) -> Result<i32> {
         self.video_sys
             .num_video_displays()
@@ -329,28 +545,67 @@ impl Window {
         Ok((display_mode.w, display_mode.h))
     }
 
+    pub fn get_monitor_usable_bounds(&self, monitor_index: i32) -> Result<Rectangle<i32>> {
+        let bounds = self
+            .video_sys
+            .display_usable_bounds(monitor_index)
+            .map_err(TetraError::PlatformError)?;
+
+        Ok(Rectangle::new(
+            bounds.x(),
+            bounds.y(),
+            bounds.width() as i32,
+            bounds.height() as i32,
+        ))
+    }
+
+    pub fn get_border_size(&self) -> (i32, i32) {
+        // Not every platform can report the size of the decorations, in which case
+        // the best we can do is assume there are none.
+        match self.sdl_window.border_size() {
+            Ok((top, left, bottom, right)) => (i32::from(left + right), i32::from(top + bottom)),
+            Err(_) => (0, 0),
+        }
+    }
+
+    pub fn get_display_modes(&self, monitor_index: i32) -> Result<Vec<DisplayMode>> {
+        let count = self
+            .video_sys
+            .num_display_modes(monitor_index)
+            .map_err(TetraError::PlatformError)?;
+
+        (0..count)
+            .map(|mode_index| {
+                self.video_sys
+                    .display_mode(monitor_index, mode_index)
+                    .map(DisplayMode::from)
+                    .map_err(TetraError::PlatformError)
+            })
+            .collect()
+    }
+
     pub fn get_current_monitor(&self) -> Result<i32> {
         self.sdl_window
             .display_index()
             .map_err(TetraError::PlatformError)
     }
 
-    pub fn set_vsync(&mut self, vsync: bool) -> Result {
-        self.video_sys
-            .gl_set_swap_interval(if vsync {
-                SwapInterval::VSync
-            } else {
-                SwapInterval::Immediate
-            })
-            .map_err(TetraError::FailedToChangeDisplayMode)
+    pub fn set_vsync_mode(&mut self, mode: VsyncMode) -> Result {
+        set_swap_interval(&self.video_sys, mode).map_err(TetraError::FailedToChangeDisplayMode)
     }
 
-    pub fn is_vsync_enabled(&self) -> bool {
-        self.video_sys.gl_get_swap_interval() != SwapInterval::Immediate
+    pub fn get_vsync_mode(&self) -> VsyncMode {
+        match self.video_sys.gl_get_swap_interval() {
+            SwapInterval::Immediate => VsyncMode::Off,
+            SwapInterval::VSync => VsyncMode::On,
+            SwapInterval::LateSwapTearing => VsyncMode::Adaptive,
+        }
     }
 
     pub fn set_fullscreen(&mut self, fullscreen: bool) -> Result {
         if fullscreen {
+            self.save_windowed_geometry();
+
             self.sdl_window
                 .display_mode()
                 .map_err(TetraError::FailedToChangeDisplayMode)
@@ -365,17 +620,116 @@ impl Window {
             self.sdl_window
                 .set_fullscreen(FullscreenType::Off)
                 .map_err(TetraError::FailedToChangeDisplayMode)
-                .and_then(|_| {
-                    let size = self.sdl_window.drawable_size();
-                    self.set_window_size(size.0 as i32, size.1 as i32)
-                })
+                .and_then(|_| self.restore_windowed_geometry())
+        }
+    }
+
+    fn save_windowed_geometry(&mut self) {
+        // If we're switching between fullscreen modes, the geometry from before the
+        // first switch is the one we want to keep.
+        if self.windowed_geometry.is_none()
+            && self.sdl_window.fullscreen_state() == FullscreenType::Off
+        {
+            self.windowed_geometry = Some(WindowedGeometry::new(&self.sdl_window));
         }
     }
 
+    fn restore_windowed_geometry(&mut self) -> Result {
+        let geometry = match self.windowed_geometry.take() {
+            Some(geometry) => geometry,
+            None => {
+                let size = self.sdl_window.drawable_size();
+                return self.set_window_size(size.0 as i32, size.1 as i32);
+            }
+        };
+
+        self.set_window_size(geometry.bounds.width, geometry.bounds.height)?;
+
+        // The monitor layout may have changed while we were in fullscreen, so make sure
+        // the window doesn't end up somewhere the player can't see it.
+        let displays: Vec<Rectangle<i32>> = (0..self.get_monitor_count().unwrap_or(0))
+            .filter_map(|i| self.video_sys.display_bounds(i).ok())
+            .map(|r| Rectangle::new(r.x(), r.y(), r.width() as i32, r.height() as i32))
+            .collect();
+
+        let current_display = self
+            .sdl_window
+            .display_index()
+            .ok()
+            .and_then(|i| self.video_sys.display_bounds(i).ok())
+            .map(|r| Rectangle::new(r.x(), r.y(), r.width() as i32, r.height() as i32));
+
+        let (x, y) = visible_position(geometry.bounds, &displays, current_display);
+
+        self.sdl_window
+            .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+
+        if geometry.maximized {
+            self.sdl_window.maximize();
+        }
+
+        Ok(())
+    }
+
     pub fn is_fullscreen(&self) -> bool {
         self.sdl_window.fullscreen_state() != FullscreenType::Off
     }
 
+    pub fn set_fullscreen_mode(&mut self, mode: FullscreenMode) -> Result {
+        match mode {
+            FullscreenMode::Windowed | FullscreenMode::Desktop => {
+                let previous = self.sdl_window.fullscreen_state();
+
+                // Make sure the monitor goes back to its native resolution if we're
+                // leaving exclusive fullscreen.
+                if previous == FullscreenType::True {
+                    self.sdl_window
+                        .set_fullscreen(FullscreenType::Off)
+                        .map_err(TetraError::FailedToChangeDisplayMode)?;
+                }
+
+                self.sdl_window
+                    .set_display_mode(None)
+                    .map_err(TetraError::FailedToChangeDisplayMode)?;
+
+                match mode {
+                    FullscreenMode::Desktop => self.set_fullscreen(true)?,
+                    _ if previous == FullscreenType::Desktop => self.set_fullscreen(false)?,
+                    _ if previous == FullscreenType::True => self.restore_windowed_geometry()?,
+                    _ => {}
+                }
+            }
+
+            FullscreenMode::Exclusive(display_mode) => {
+                self.save_windowed_geometry();
+
+                self.sdl_window
+                    .set_display_mode(SdlDisplayMode::from(display_mode))
+                    .map_err(TetraError::FailedToChangeDisplayMode)?;
+
+                self.sdl_window
+                    .set_fullscreen(FullscreenType::True)
+                    .map_err(TetraError::FailedToChangeDisplayMode)?;
+            }
+        }
+
+        self.invalidate_metrics();
+
+        Ok(())
+    }
+
+    pub fn get_fullscreen_mode(&self) -> Result<FullscreenMode> {
+        match self.sdl_window.fullscreen_state() {
+            FullscreenType::Off => Ok(FullscreenMode::Windowed),
+            FullscreenType::Desktop => Ok(FullscreenMode::Desktop),
+            FullscreenType::True => self
+                .sdl_window
+                .display_mode()
+                .map(|m| FullscreenMode::Exclusive(DisplayMode::from(m)))
+                .map_err(TetraError::FailedToChangeDisplayMode),
+        }
+    }
+
     pub fn set_mouse_visible(&mut self, mouse_visible: bool) -> Result {
         self.sdl.mouse().show_cursor(mouse_visible);
         Ok(())
@@ -385,6 +739,60 @@ impl Window {
         self.sdl.mouse().is_cursor_showing()
     }
 
+    pub fn new_cursor(&mut self, data: &ImageData, hotspot: Vec2<i32>) -> Result<RawCursor> {
+        let (width, height) = data.size();
+
+        // SDL wants a mutable buffer to build the surface from, even though it
+        // copies the data when creating the cursor. The masks below describe RGBA8
+        // data, so other formats need converting first.
+        let mut pixels = if data.format() == TextureFormat::Rgba8 {
+            data.as_bytes().to_vec()
+        } else {
+            data.to_format(TextureFormat::Rgba8).into_bytes()
+        };
+
+        let surface = Surface::from_data_pixelmasks(
+            &mut pixels,
+            width as u32,
+            height as u32,
+            width as u32 * 4,
+            &PixelMasks {
+                bpp: 32,
+                rmask: 0x000000FF,
+                gmask: 0x0000FF00,
+                bmask: 0x00FF0000,
+                amask: 0xFF000000,
+            },
+        )
+        .map_err(TetraError::PlatformError)?;
+
+        let cursor = SdlCursor::from_surface(surface, hotspot.x, hotspot.y)
+            .map_err(TetraError::PlatformError)?;
+
+        Ok(RawCursor { cursor })
+    }
+
+    pub fn new_system_cursor(&mut self, cursor: SystemCursor) -> Result<RawCursor> {
+        let cursor = SdlCursor::from_system(cursor.into()).map_err(TetraError::PlatformError)?;
+
+        Ok(RawCursor { cursor })
+    }
+
+    pub fn set_cursor(&mut self, cursor: &Rc<RawCursor>) {
+        cursor.cursor.set();
+        self.cursor = Some(Rc::clone(cursor));
+    }
+
+    pub fn reset_cursor(&mut self) {
+        // SAFETY: The default cursor is owned by SDL, and is never freed while the
+        // video subsystem is running.
+        unsafe {
+            sdl2::sys::SDL_SetCursor(sdl2::sys::SDL_GetDefaultCursor());
+        }
+
+        self.cursor = None;
+    }
+
     pub fn set_mouse_grabbed(&mut self, mouse_grabbed: bool) {
         self.sdl_window.set_grab(mouse_grabbed);
     }
@@ -403,6 +811,27 @@ impl Window {
         self.sdl.mouse().relative_mouse_mode()
     }
 
+    pub fn start_text_input(&mut self) {
+        self.video_sys.text_input().start();
+    }
+
+    pub fn stop_text_input(&mut self) {
+        self.video_sys.text_input().stop();
+    }
+
+    pub fn is_text_input_active(&self) -> bool {
+        self.video_sys.text_input().is_active()
+    }
+
+    pub fn set_text_input_rect(&mut self, rect: Rectangle<i32>) {
+        self.video_sys.text_input().set_rect(Rect::new(
+            rect.x,
+            rect.y,
+            rect.width.max(0) as u32,
+            rect.height.max(0) as u32,
+        ));
+    }
+
     pub fn get_clipboard_text(&self) -> Result<String> {
         self.video_sys
             .clipboard()
@@ -418,7 +847,9 @@ impl Window {
     }
 
     pub fn swap_buffers(&self) {
-        self.sdl_window.gl_swap_window();
+        if !self.headless {
+            self.sdl_window.gl_swap_window();
+        }
     }
 
     pub fn get_gamepad_name(&self, platform_id: u32) -> String {
@@ -432,19 +863,34 @@ impl Window {
             .unwrap_or(false)
     }
 
+    pub fn is_gamepad_trigger_vibration_supported(&self, platform_id: u32) -> bool {
+        self.controllers
+            .get(&platform_id)
+            .map(|c| c.controller.has_rumble_triggers())
+            .unwrap_or(false)
+    }
+
     pub fn set_gamepad_vibration(&mut self, platform_id: u32, strength: f32) {
-        self.start_gamepad_vibration(platform_id, strength, 0);
+        self.start_gamepad_vibration(platform_id, strength, strength, 0);
     }
 
-    pub fn start_gamepad_vibration(&mut self, platform_id: u32, strength: f32, duration: u32) {
+    pub fn start_gamepad_vibration(
+        &mut self,
+        platform_id: u32,
+        low_frequency: f32,
+        high_frequency: f32,
+        duration: u32,
+    ) {
         if let Some(controller) = self
             .controllers
             .get_mut(&platform_id)
             .map(|c| &mut c.controller)
         {
-            let int_strength = ((u16::MAX as f32) * strength) as u16;
-
-            let _ = controller.set_rumble(int_strength, int_strength, duration);
+            let _ = controller.set_rumble(
+                to_sdl_rumble_strength(low_frequency),
+                to_sdl_rumble_strength(high_frequency),
+                duration,
+            );
         }
     }
 
@@ -458,6 +904,166 @@ impl Window {
         }
     }
 
+    pub fn get_gamepad_power_info(&self, platform_id: u32) -> Option<GamepadPowerInfo> {
+        use sdl2::sys::SDL_JoystickPowerLevel as PowerLevel;
+
+        let controller = self.get_raw_controller(platform_id)?;
+
+        let level = unsafe {
+            sdl2::sys::SDL_JoystickCurrentPowerLevel(sdl2::sys::SDL_GameControllerGetJoystick(
+                controller,
+            ))
+        };
+
+        match level {
+            PowerLevel::SDL_JOYSTICK_POWER_WIRED => Some(GamepadPowerInfo::Wired),
+            PowerLevel::SDL_JOYSTICK_POWER_EMPTY => {
+                Some(GamepadPowerInfo::Battery(GamepadBatteryLevel::Empty))
+            }
+            PowerLevel::SDL_JOYSTICK_POWER_LOW => {
+                Some(GamepadPowerInfo::Battery(GamepadBatteryLevel::Low))
+            }
+            PowerLevel::SDL_JOYSTICK_POWER_MEDIUM => {
+                Some(GamepadPowerInfo::Battery(GamepadBatteryLevel::Medium))
+            }
+            PowerLevel::SDL_JOYSTICK_POWER_FULL | PowerLevel::SDL_JOYSTICK_POWER_MAX => {
+                Some(GamepadPowerInfo::Battery(GamepadBatteryLevel::Full))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_gamepad_type(&self, platform_id: u32) -> GamepadType {
+        use sdl2::sys::SDL_GameControllerType as ControllerType;
+
+        let controller = match self.get_raw_controller(platform_id) {
+            Some(controller) => controller,
+            None => return GamepadType::Unknown,
+        };
+
+        match unsafe { sdl2::sys::SDL_GameControllerGetType(controller) } {
+            ControllerType::SDL_CONTROLLER_TYPE_XBOX360 => GamepadType::Xbox360,
+            ControllerType::SDL_CONTROLLER_TYPE_XBOXONE => GamepadType::XboxOne,
+            ControllerType::SDL_CONTROLLER_TYPE_PS3 => GamepadType::PlayStation3,
+            ControllerType::SDL_CONTROLLER_TYPE_PS4 => GamepadType::PlayStation4,
+            ControllerType::SDL_CONTROLLER_TYPE_PS5 => GamepadType::PlayStation5,
+            ControllerType::SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_PRO => {
+                GamepadType::NintendoSwitchPro
+            }
+            ControllerType::SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_JOYCON_LEFT
+            | ControllerType::SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_JOYCON_RIGHT
+            | ControllerType::SDL_CONTROLLER_TYPE_NINTENDO_SWITCH_JOYCON_PAIR => {
+                GamepadType::NintendoSwitchJoyCon
+            }
+            _ => GamepadType::Unknown,
+        }
+    }
+
+    pub fn get_gamepad_player_index(&self, platform_id: u32) -> Option<usize> {
+        let controller = self.get_raw_controller(platform_id)?;
+        let index = unsafe { sdl2::sys::SDL_GameControllerGetPlayerIndex(controller) };
+
+        usize::try_from(index).ok()
+    }
+
+    pub fn set_gamepad_player_index(&mut self, platform_id: u32, index: Option<usize>) {
+        if let Some(controller) = self.get_raw_controller(platform_id) {
+            // SDL uses -1 to mean 'no player index'.
+            let index = index.and_then(|i| i32::try_from(i).ok()).unwrap_or(-1);
+
+            unsafe {
+                sdl2::sys::SDL_GameControllerSetPlayerIndex(controller, index);
+            }
+        }
+    }
+
+    pub fn is_gamepad_sensor_supported(&self, platform_id: u32, sensor: GamepadSensor) -> bool {
+        match self.get_raw_controller(platform_id) {
+            Some(controller) => unsafe {
+                sdl2::sys::SDL_GameControllerHasSensor(controller, to_sdl_sensor_type(sensor))
+                    == SDL_bool::SDL_TRUE
+            },
+            None => false,
+        }
+    }
+
+    pub fn set_gamepad_sensor_enabled(
+        &mut self,
+        platform_id: u32,
+        sensor: GamepadSensor,
+        enabled: bool,
+    ) {
+        if let Some(controller) = self.get_raw_controller(platform_id) {
+            let enabled = if enabled {
+                SDL_bool::SDL_TRUE
+            } else {
+                SDL_bool::SDL_FALSE
+            };
+
+            // This fails if the controller doesn't have the sensor, in which case
+            // there's nothing to do.
+            unsafe {
+                sdl2::sys::SDL_GameControllerSetSensorEnabled(
+                    controller,
+                    to_sdl_sensor_type(sensor),
+                    enabled,
+                );
+            }
+        }
+    }
+
+    /// Reads the latest data from each enabled gamepad sensor.
+    ///
+    /// The sdl2 crate only exposes sensor events when its `hidapi` feature is enabled,
+    /// so the sensors are polled after the events have been pumped instead.
+    fn read_gamepad_sensors(&self) -> Vec<(usize, GamepadSensor, Vec3<f32>)> {
+        let mut readings = Vec::new();
+
+        for (&platform_id, controller) in &self.controllers {
+            let raw = match self.get_raw_controller(platform_id) {
+                Some(raw) => raw,
+                None => continue,
+            };
+
+            for sensor in [GamepadSensor::Gyro, GamepadSensor::Accelerometer] {
+                let sensor_type = to_sdl_sensor_type(sensor);
+                let mut data = [0.0; 3];
+
+                unsafe {
+                    if sdl2::sys::SDL_GameControllerIsSensorEnabled(raw, sensor_type)
+                        == SDL_bool::SDL_TRUE
+                        && sdl2::sys::SDL_GameControllerGetSensorData(
+                            raw,
+                            sensor_type,
+                            data.as_mut_ptr(),
+                            3,
+                        ) == 0
+                    {
+                        readings.push((controller.slot, sensor, Vec3::from(data)));
+                    }
+                }
+            }
+        }
+
+        readings
+    }
+
+    fn get_raw_controller(&self, platform_id: u32) -> Option<*mut sdl2::sys::SDL_GameController> {
+        if !self.controllers.contains_key(&platform_id) {
+            return None;
+        }
+
+        // The controller is kept open for as long as it is in the map, so the
+        // pointer will remain valid until it is removed.
+        let controller = unsafe { sdl2::sys::SDL_GameControllerFromInstanceID(platform_id as i32) };
+
+        if controller.is_null() {
+            None
+        } else {
+            Some(controller)
+        }
+    }
+
     pub fn set_screen_saver_enabled(&self, screen_saver_enabled: bool) {
         if screen_saver_enabled {
             self.video_sys.enable_screen_saver()
@@ -491,18 +1097,185 @@ impl Window {
     }
 }
 
+#[cfg(feature = "raw_window_handle")]
+impl raw_window_handle::HasWindowHandle for Window {
+    fn window_handle(
+        &self,
+    ) -> result::Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        raw_window_handle::HasWindowHandle::window_handle(&self.sdl_window)
+    }
+}
+
+#[cfg(feature = "raw_window_handle")]
+impl raw_window_handle::HasDisplayHandle for Window {
+    fn display_handle(
+        &self,
+    ) -> result::Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        raw_window_handle::HasDisplayHandle::display_handle(&self.sdl_window)
+    }
+}
+
+/// Returns the environment variables that SDL reads the app ID from, skipping any
+/// that have already been set by the user.
+fn app_id_env_vars(is_set: impl Fn(&str) -> bool) -> Vec<&'static str> {
+    ["SDL_VIDEO_X11_WMCLASS", "SDL_VIDEO_WAYLAND_WMCLASS"]
+        .into_iter()
+        .filter(|name| !is_set(name))
+        .collect()
+}
+
+fn to_sdl_sensor_type(sensor: GamepadSensor) -> sdl2::sys::SDL_SensorType {
+    match sensor {
+        GamepadSensor::Gyro => sdl2::sys::SDL_SensorType::SDL_SENSOR_GYRO,
+        GamepadSensor::Accelerometer => sdl2::sys::SDL_SensorType::SDL_SENSOR_ACCEL,
+    }
+}
+
+fn to_sdl_rumble_strength(strength: f32) -> u16 {
+    ((u16::MAX as f32) * strength.clamp(0.0, 1.0)) as u16
+}
+
+/// Returns the position that a window should be restored to, moving it onto a display if
+/// it would otherwise be completely off-screen.
+fn visible_position(
+    window: Rectangle<i32>,
+    displays: &[Rectangle<i32>],
+    fallback: Option<Rectangle<i32>>,
+) -> (i32, i32) {
+    if displays.iter().any(|display| display.intersects(&window)) {
+        return (window.x, window.y);
+    }
+
+    match fallback.or_else(|| displays.first().copied()) {
+        Some(display) => (
+            window.x.min(display.right() - window.width).max(display.x),
+            window
+                .y
+                .min(display.bottom() - window.height)
+                .max(display.y),
+        ),
+        None => (window.x, window.y),
+    }
+}
+
+fn window_metrics(sdl_window: &SdlWindow) -> WindowMetrics {
+    let (width, height) = sdl_window.size();
+    let (physical_width, physical_height) = sdl_window.drawable_size();
+
+    WindowMetrics {
+        logical_size: (width as i32, height as i32),
+        physical_size: (physical_width as i32, physical_height as i32),
+        dpi_scale: physical_width as f32 / width as f32,
+    }
+}
+
 pub fn handle_events<S, E>(ctx: &mut Context, state: &mut S) -> result::Result<(), E>
 where
     S: State<E>,
     E: From<TetraError>,
 {
-    while let Some(event) = ctx.window.event_pump.poll_event() {
+    let mut events: Vec<SdlEvent> = ctx.window.event_pump.poll_iter().collect();
+
+    // Aspect ratio constraints are emulated by resizing the window before the new
+    // size is reported, so that the game never sees a size that doesn't fit. If
+    // the resize fails, the original size is reported instead.
+    for event in &mut events {
+        if let SdlEvent::Window {
+            win_event: WindowEvent::SizeChanged(width, height),
+            ..
+        } = event
+        {
+            if let Some((new_width, new_height)) = ctx.window.constrained_size(*width, *height) {
+                if ctx.window.set_window_size(new_width, new_height).is_ok() {
+                    *width = new_width;
+                    *height = new_height;
+                }
+            }
+        }
+    }
+
+    // SDL can report a change in size and a change in scale as separate events (and
+    // sometimes on separate frames), so we coalesce them and apply the final values
+    // before anything else gets dispatched.
+    for event in &events {
+        if let SdlEvent::Window {
+            win_event:
+                WindowEvent::SizeChanged(..)
+                | WindowEvent::Resized(..)
+                | WindowEvent::DisplayChanged(..),
+            ..
+        } = event
+        {
+            ctx.window.invalidate_metrics();
+        }
+    }
+
+    let previous_dpi_scale = ctx.window.metrics.applied().dpi_scale;
+
+    if let Some(metrics) = ctx.window.resolve_metrics() {
+        graphics::set_viewport_size(ctx);
+
+        state.event(
+            ctx,
+            Event::WindowMetricsChanged {
+                logical_size: metrics.logical_size,
+                physical_size: metrics.physical_size,
+                dpi_scale: metrics.dpi_scale,
+            },
+        )?;
+
+        if metrics.dpi_scale != previous_dpi_scale {
+            state.event(
+                ctx,
+                Event::DpiScaleChanged {
+                    scale: metrics.dpi_scale,
+                },
+            )?;
+        }
+    }
+
+    for event in events {
         match event {
-            SdlEvent::Quit { .. } => ctx.running = false, // TODO: Add a way to override this
+            SdlEvent::Quit { .. } => {
+                ctx.quit_cancelled = false;
+
+                state.event(ctx, Event::QuitRequested)?;
+
+                if !ctx.quit_cancelled {
+                    ctx.running = false;
+                }
+            }
+
+            SdlEvent::Display {
+                display_index,
+                display_event,
+                ..
+            } => match display_event {
+                DisplayEvent::Connected => {
+                    state.event(
+                        ctx,
+                        Event::DisplayConnected {
+                            index: display_index,
+                        },
+                    )?;
+                }
+
+                DisplayEvent::Disconnected => {
+                    state.event(
+                        ctx,
+                        Event::DisplayDisconnected {
+                            index: display_index,
+                        },
+                    )?;
+                }
+
+                _ => {}
+            },
 
             SdlEvent::Window { win_event, .. } => match win_event {
-                WindowEvent::SizeChanged(width, height) => {
-                    graphics::set_viewport_size(ctx);
+                WindowEvent::SizeChanged(width, height)
+                    if window::is_new_size(&mut ctx.window.last_resize, (width, height)) =>
+                {
                     state.event(ctx, Event::Resized { width, height })?;
                 }
 
@@ -519,10 +1292,12 @@ where
                 }
 
                 WindowEvent::FocusGained => {
+                    input::set_focused(ctx, true);
                     state.event(ctx, Event::FocusGained)?;
                 }
 
                 WindowEvent::FocusLost => {
+                    input::set_focused(ctx, false);
                     state.event(ctx, Event::FocusLost)?;
                 }
 
@@ -534,20 +1309,18 @@ where
                 repeat,
                 keymod,
                 ..
-            } => {
-                if !repeat || ctx.window.is_key_repeat_enabled() {
-                    input::set_key_modifier_state(ctx, from_sdl_keymod(keymod));
+            } if !repeat || ctx.window.is_key_repeat_enabled() => {
+                input::set_key_modifier_state(ctx, from_sdl_keymod(keymod));
 
-                    if let Scancode::Escape = scancode {
-                        if ctx.quit_on_escape {
-                            ctx.running = false;
-                        }
+                if let Scancode::Escape = scancode {
+                    if ctx.quit_on_escape {
+                        ctx.running = false;
                     }
+                }
 
-                    if let Some(key) = from_sdl_scancode(scancode) {
-                        input::set_key_down(ctx, key);
-                        state.event(ctx, Event::KeyPressed { key })?;
-                    }
+                if let Some(key) = from_sdl_scancode(scancode) {
+                    input::set_key_down(ctx, key);
+                    state.event(ctx, Event::KeyPressed { key })?;
                 }
             }
 
@@ -587,19 +1360,33 @@ where
                 let delta = Vec2::new(xrel as f32, yrel as f32);
 
                 input::set_mouse_position(ctx, position);
+                input::apply_mouse_motion(ctx, delta);
                 state.event(ctx, Event::MouseMoved { position, delta })?;
             }
 
             SdlEvent::MouseWheel {
-                x, y, direction, ..
+                x,
+                y,
+                precise_x,
+                precise_y,
+                direction,
+                ..
             } => {
-                let amount = match direction {
-                    MouseWheelDirection::Flipped => Vec2::new(-x, -y),
-                    _ => Vec2::new(x, y),
+                let (amount, precise_amount) = match direction {
+                    MouseWheelDirection::Flipped => {
+                        (Vec2::new(-x, -y), Vec2::new(-precise_x, -precise_y))
+                    }
+                    _ => (Vec2::new(x, y), Vec2::new(precise_x, precise_y)),
                 };
 
-                input::apply_mouse_wheel_movement(ctx, amount);
-                state.event(ctx, Event::MouseWheelMoved { amount })?
+                input::apply_mouse_wheel_movement(ctx, precise_amount);
+                state.event(
+                    ctx,
+                    Event::MouseWheelMoved {
+                        amount,
+                        precise_amount,
+                    },
+                )?
             }
 
             SdlEvent::TextInput { text, .. } => {
@@ -607,15 +1394,68 @@ where
                 state.event(ctx, Event::TextInput { text })?;
             }
 
-            SdlEvent::DropFile { filename, .. } => {
+            SdlEvent::TextEditing {
+                text,
+                start,
+                length,
+                ..
+            } => {
                 state.event(
                     ctx,
-                    Event::FileDropped {
-                        path: PathBuf::from(filename),
+                    Event::TextEditing {
+                        text,
+                        start: start.max(0) as usize,
+                        length: length.max(0) as usize,
                     },
                 )?;
             }
 
+            SdlEvent::DropBegin { .. } => {
+                ctx.window.dropped_files = Some(Vec::new());
+            }
+
+            SdlEvent::DropFile { filename, .. } => {
+                let path = PathBuf::from(filename);
+
+                // Not every platform brackets drops with begin/complete events, so
+                // files that arrive outside of a batch are reported on their own.
+                let batch_path = match &mut ctx.window.dropped_files {
+                    Some(batch) => {
+                        batch.push(path.clone());
+                        None
+                    }
+                    None => Some(path.clone()),
+                };
+
+                state.event(ctx, Event::FileDropped { path })?;
+
+                if let Some(path) = batch_path {
+                    state.event(ctx, Event::FilesDropped { paths: vec![path] })?;
+                }
+            }
+
+            SdlEvent::DropText { filename, .. } => {
+                state.event(ctx, Event::TextDropped { text: filename })?;
+            }
+
+            SdlEvent::DropComplete { .. } => {
+                if let Some(paths) = ctx.window.dropped_files.take() {
+                    if !paths.is_empty() {
+                        state.event(ctx, Event::FilesDropped { paths })?;
+                    }
+                }
+            }
+
+            #[cfg(feature = "audio")]
+            SdlEvent::AudioDeviceAdded {
+                iscapture: false, ..
+            }
+            | SdlEvent::AudioDeviceRemoved {
+                iscapture: false, ..
+            } if ctx.audio.refresh_device() => {
+                state.event(ctx, Event::AudioDeviceChanged)?;
+            }
+
             SdlEvent::ControllerDeviceAdded { which, .. } => {
                 let mut controller = ctx
                     .window
@@ -676,6 +1516,54 @@ where
                 }
             }
 
+            SdlEvent::ControllerTouchpadDown {
+                which,
+                touchpad,
+                finger,
+                x,
+                y,
+                pressure,
+                ..
+            }
+            | SdlEvent::ControllerTouchpadMotion {
+                which,
+                touchpad,
+                finger,
+                x,
+                y,
+                pressure,
+                ..
+            }
+            | SdlEvent::ControllerTouchpadUp {
+                which,
+                touchpad,
+                finger,
+                x,
+                y,
+                pressure,
+                ..
+            } => {
+                if let Some(slot) = ctx.window.controllers.get(&which).map(|c| c.slot) {
+                    let phase = match event {
+                        SdlEvent::ControllerTouchpadDown { .. } => GamepadTouchPhase::Started,
+                        SdlEvent::ControllerTouchpadMotion { .. } => GamepadTouchPhase::Moved,
+                        _ => GamepadTouchPhase::Ended,
+                    };
+
+                    state.event(
+                        ctx,
+                        Event::GamepadTouchpad {
+                            id: slot,
+                            touchpad: touchpad as usize,
+                            finger: finger as usize,
+                            position: Vec2::new(x, y),
+                            pressure,
+                            phase,
+                        },
+                    )?;
+                }
+            }
+
             SdlEvent::ControllerAxisMotion {
                 which, axis, value, ..
             } => {
@@ -724,7 +1612,7 @@ where
                             Event::GamepadAxisMoved {
                                 id: slot,
                                 axis,
-                                position: mapped_value,
+                                position: input::get_gamepad_axis_position(ctx, slot, axis),
                             },
                         )?;
 
@@ -756,6 +1644,12 @@ where
         }
     }
 
+    for (slot, sensor, data) in ctx.window.read_gamepad_sensors() {
+        if let Some(pad) = input::get_gamepad_mut(ctx, slot) {
+            pad.set_sensor_data(sensor, data);
+        }
+    }
+
     Ok(())
 }
 
@@ -987,6 +1881,26 @@ key_mappings! {
     }
 }
 
+fn set_swap_interval(video_sys: &VideoSubsystem, mode: VsyncMode) -> result::Result<(), String> {
+    match mode {
+        VsyncMode::Off => video_sys.gl_set_swap_interval(SwapInterval::Immediate),
+        VsyncMode::On => video_sys.gl_set_swap_interval(SwapInterval::VSync),
+
+        // Drivers are allowed to reject late swap tearing, in which case we fall back
+        // to regular vsync. `get_vsync_mode` will report the mode that was applied.
+        VsyncMode::Adaptive => video_sys
+            .gl_set_swap_interval(SwapInterval::LateSwapTearing)
+            .or_else(|e| {
+                eprintln!(
+                    "Warning: adaptive vsync is not supported ({}), falling back to regular vsync",
+                    e
+                );
+
+                video_sys.gl_set_swap_interval(SwapInterval::VSync)
+            }),
+    }
+}
+
 fn from_sdl_keymod(keymod: Mod) -> KeyModifierState {
     KeyModifierState {
         ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
@@ -1015,6 +1929,48 @@ fn into_gamepad_button(button: SdlGamepadButton) -> Option<GamepadButton> {
         _ => None,
     }
 }
+
+impl From<SdlDisplayMode> for DisplayMode {
+    fn from(mode: SdlDisplayMode) -> DisplayMode {
+        DisplayMode {
+            width: mode.w,
+            height: mode.h,
+            refresh_rate: mode.refresh_rate,
+        }
+    }
+}
+
+impl From<DisplayMode> for SdlDisplayMode {
+    fn from(mode: DisplayMode) -> SdlDisplayMode {
+        // Leaving the format unspecified tells SDL to match the desktop.
+        SdlDisplayMode::new(
+            PixelFormatEnum::Unknown,
+            mode.width,
+            mode.height,
+            mode.refresh_rate,
+        )
+    }
+}
+
+impl From<SystemCursor> for SdlSystemCursor {
+    fn from(cursor: SystemCursor) -> SdlSystemCursor {
+        match cursor {
+            SystemCursor::Arrow => SdlSystemCursor::Arrow,
+            SystemCursor::IBeam => SdlSystemCursor::IBeam,
+            SystemCursor::Wait => SdlSystemCursor::Wait,
+            SystemCursor::Crosshair => SdlSystemCursor::Crosshair,
+            SystemCursor::WaitArrow => SdlSystemCursor::WaitArrow,
+            SystemCursor::ResizeNwSe => SdlSystemCursor::SizeNWSE,
+            SystemCursor::ResizeNeSw => SdlSystemCursor::SizeNESW,
+            SystemCursor::ResizeHorizontal => SdlSystemCursor::SizeWE,
+            SystemCursor::ResizeVertical => SdlSystemCursor::SizeNS,
+            SystemCursor::ResizeAll => SdlSystemCursor::SizeAll,
+            SystemCursor::NotAllowed => SdlSystemCursor::No,
+            SystemCursor::Hand => SdlSystemCursor::Hand,
+        }
+    }
+}
+
 #[doc(hidden)]
 impl From<GamepadAxis> for SdlGamepadAxis {
     fn from(axis: GamepadAxis) -> SdlGamepadAxis {
@@ -1056,3 +2012,52 @@ impl From<WindowPosition> for WindowPos {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_id_does_not_override_user_env_vars() {
+        assert_eq!(
+            app_id_env_vars(|_| false),
+            ["SDL_VIDEO_X11_WMCLASS", "SDL_VIDEO_WAYLAND_WMCLASS"]
+        );
+
+        assert_eq!(
+            app_id_env_vars(|name| name == "SDL_VIDEO_X11_WMCLASS"),
+            ["SDL_VIDEO_WAYLAND_WMCLASS"]
+        );
+
+        assert!(app_id_env_vars(|_| true).is_empty());
+    }
+
+    #[test]
+    fn restored_windows_are_kept_on_screen() {
+        let displays = [
+            Rectangle::new(0, 0, 1920, 1080),
+            Rectangle::new(1920, 0, 1280, 1024),
+        ];
+
+        // Windows that are at least partly visible are left where they are.
+        assert_eq!(
+            visible_position(Rectangle::new(1800, 100, 640, 480), &displays, None),
+            (1800, 100)
+        );
+
+        // The second monitor has been unplugged, so the window is moved onto the first.
+        assert_eq!(
+            visible_position(
+                Rectangle::new(2000, 100, 640, 480),
+                &displays[..1],
+                Some(displays[0])
+            ),
+            (1280, 100)
+        );
+
+        assert_eq!(
+            visible_position(Rectangle::new(-900, -700, 640, 480), &displays, None),
+            (0, 0)
+        );
+    }
+}
@@ -0,0 +1,23 @@
+#version 150
+
+in vec2 a_position;
+in vec2 a_uv;
+in vec4 a_color;
+in float a_depth;
+in mat3 a_instance_transform;
+in vec4 a_instance_color;
+
+uniform mat4 u_projection;
+
+out vec2 v_uv;
+out vec4 v_color;
+
+void main() {
+    v_color = a_color * a_instance_color;
+    v_uv = a_uv;
+
+    vec3 position = a_instance_transform * vec3(a_position, 1.0);
+
+    gl_Position = u_projection * vec4(position.xy, 0.0, 1.0);
+    gl_Position.z = a_depth;
+}
\ No newline at end of file
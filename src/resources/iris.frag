@@ -0,0 +1,19 @@
+#version 150
+
+in vec2 v_uv;
+in vec4 v_color;
+
+uniform sampler2D u_texture;
+uniform vec4 u_diffuse;
+uniform vec2 u_center;
+uniform float u_radius;
+
+out vec4 o_color;
+
+void main() {
+    if (distance(gl_FragCoord.xy, u_center) < u_radius) {
+        discard;
+    }
+
+    o_color = texture(u_texture, v_uv) * u_diffuse * v_color;
+}
\ No newline at end of file
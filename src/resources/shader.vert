@@ -3,6 +3,7 @@
 in vec2 a_position;
 in vec2 a_uv;
 in vec4 a_color;
+in float a_depth;
 
 uniform mat4 u_projection;
 
@@ -14,4 +15,5 @@ void main() {
     v_uv = a_uv;
 
     gl_Position = u_projection * vec4(a_position, 0.0, 1.0);
+    gl_Position.z = a_depth;
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+#version 150
+
+in vec2 v_uv;
+in vec4 v_color;
+
+uniform sampler2D u_texture;
+uniform vec4 u_diffuse;
+uniform float u_exposure;
+uniform int u_operator;
+
+out vec4 o_color;
+
+vec3 reinhard(vec3 color) {
+    return color / (1.0 + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve.
+vec3 aces_fitted(vec3 color) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+}
+
+void main() {
+    vec4 color = texture(u_texture, v_uv) * u_diffuse * v_color;
+    vec3 exposed = color.rgb * u_exposure;
+
+    vec3 mapped;
+
+    if (u_operator == 0) {
+        mapped = reinhard(exposed);
+    } else {
+        mapped = aces_fitted(exposed);
+    }
+
+    o_color = vec4(mapped, clamp(color.a, 0.0, 1.0));
+}
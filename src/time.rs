@@ -1,11 +1,27 @@
 //! Functions and types relating to measuring and manipulating time.
 
-use std::collections::VecDeque;
+mod profiler;
+mod timer;
+
+pub use crate::profile_scope;
+pub use profiler::*;
+pub use timer::*;
 
-use std::time::Duration;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::Context;
 
+/// The maximum number of updates that can build up in the accumulator, to prevent the
+/// game loop from spiralling if updates take longer than the tick rate.
+const MAX_PENDING_TICKS: u32 = 8;
+
+/// How long before the end of a frame the frame limiter should stop sleeping and start
+/// spinning. This makes up for the sleep timer being imprecise on some platforms.
+const FRAME_LIMIT_SPIN_TIME: Duration = Duration::from_millis(2);
+
 /// The different timestep modes that a game can have.
 ///
 /// # Serde
@@ -45,26 +61,32 @@ pub enum Timestep {
 }
 
 pub(crate) struct FpsTracker {
-    buffer: VecDeque<f64>,
+    // Oldest first.
+    buffer: VecDeque<Duration>,
+    capacity: usize,
 }
 
 impl FpsTracker {
-    fn new() -> FpsTracker {
+    fn new(capacity: usize) -> FpsTracker {
+        let capacity = capacity.max(1);
+
         FpsTracker {
-            buffer: VecDeque::with_capacity(200),
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
         }
     }
 
     pub(crate) fn push(&mut self, frame_time: Duration) {
-        if self.buffer.len() == 200 {
+        if self.buffer.len() == self.capacity {
             self.buffer.pop_front();
         }
 
-        self.buffer.push_back(frame_time.as_secs_f64());
+        self.buffer.push_back(frame_time);
     }
 
     fn get_fps(&self) -> f64 {
-        1.0 / (self.buffer.iter().sum::<f64>() / self.buffer.len() as f64)
+        let total: f64 = self.buffer.iter().map(Duration::as_secs_f64).sum();
+        1.0 / (total / self.buffer.len() as f64)
     }
 }
 
@@ -74,10 +96,20 @@ pub(crate) struct TimeContext {
     pub(crate) tick_rate: Option<Duration>,
     pub(crate) delta_time: Duration,
     pub(crate) accumulator: Duration,
+    pub(crate) last_time: Instant,
+    pub(crate) start_time: Instant,
+    pub(crate) tick_count: u64,
+    pub(crate) frame_count: u64,
+    pub(crate) max_frame_rate: Option<f64>,
+    pub(crate) profiler: Rc<RefCell<Profiler>>,
 }
 
 impl TimeContext {
-    pub(crate) fn new(timestep: Timestep) -> TimeContext {
+    pub(crate) fn new(
+        timestep: Timestep,
+        frame_time_history: usize,
+        max_frame_rate: Option<f64>,
+    ) -> TimeContext {
         let ticks_per_second = match timestep {
             Timestep::Fixed(tps) => Some(tps),
             Timestep::Variable => None,
@@ -88,19 +120,99 @@ impl TimeContext {
             Timestep::Variable => None,
         };
 
+        let now = Instant::now();
+
         TimeContext {
-            fps_tracker: FpsTracker::new(),
+            fps_tracker: FpsTracker::new(frame_time_history),
             ticks_per_second,
             tick_rate,
             delta_time: Duration::from_secs(0),
             accumulator: Duration::from_secs(0),
+            last_time: now,
+            start_time: now,
+            tick_count: 0,
+            frame_count: 0,
+            max_frame_rate,
+            profiler: Rc::new(RefCell::new(Profiler::new())),
+        }
+    }
+}
+
+/// Adds the elapsed time to the accumulator, clamping it to avoid too many updates
+/// building up.
+pub(crate) fn accumulate(
+    accumulator: Duration,
+    elapsed: Duration,
+    tick_rate: Duration,
+) -> Duration {
+    (accumulator + elapsed).min(tick_rate * MAX_PENDING_TICKS)
+}
+
+/// Converts the accumulator from one tick rate to another, preserving how far between
+/// updates the game loop currently is.
+fn rescale_accumulator(
+    accumulator: Duration,
+    old_tick_rate: Option<Duration>,
+    new_tick_rate: Option<Duration>,
+) -> Duration {
+    match (old_tick_rate, new_tick_rate) {
+        (Some(old), Some(new)) if !old.is_zero() => accumulator
+            .mul_f64(new.as_secs_f64() / old.as_secs_f64())
+            .min(new * MAX_PENDING_TICKS),
+
+        _ => Duration::ZERO,
+    }
+}
+
+/// Returns how much longer the current frame should last for, in order to stay under
+/// the maximum frame rate.
+fn frame_limit_delay(
+    frame_start: Instant,
+    now: Instant,
+    max_frame_rate: Option<f64>,
+) -> Option<Duration> {
+    let max_frame_rate = max_frame_rate.filter(|&fps| fps > 0.0 && fps.is_finite())?;
+
+    // Very small frame rates give a frame length that can't be represented, in which
+    // case there's no meaningful limit to apply.
+    let frame_length = Duration::try_from_secs_f64(1.0 / max_frame_rate).ok()?;
+    let frame_end = frame_start.checked_add(frame_length)?;
+
+    frame_end
+        .checked_duration_since(now)
+        .filter(|delay| !delay.is_zero())
+}
+
+/// Blocks the main thread until the end of the current frame, if a maximum frame rate
+/// has been set.
+///
+/// Returns `true` if the frame rate was limited.
+pub(crate) fn limit_frame_rate(ctx: &Context) -> bool {
+    let frame_start = ctx.time.last_time;
+
+    // If there's no delay at the very start of the frame, there's no valid limit.
+    if frame_limit_delay(frame_start, frame_start, ctx.time.max_frame_rate).is_none() {
+        return false;
+    }
+
+    while let Some(delay) = frame_limit_delay(frame_start, Instant::now(), ctx.time.max_frame_rate)
+    {
+        // Sleeping is only accurate to within a millisecond or two on some platforms, so
+        // the last part of the wait is a spin loop.
+        if delay > FRAME_LIMIT_SPIN_TIME {
+            std::thread::sleep(delay - FRAME_LIMIT_SPIN_TIME);
+        } else {
+            std::hint::spin_loop();
         }
     }
+
+    true
 }
 
 pub(crate) fn reset(ctx: &mut Context) {
     ctx.time.delta_time = Duration::from_secs(0);
     ctx.time.accumulator = Duration::from_secs(0);
+    ctx.time.last_time = Instant::now();
 }
 
 /// Returns the amount of time that has passed since the last update or draw.
@@ -133,14 +245,19 @@ pub fn get_accumulator(ctx: &Context) -> Duration {
 /// For example, if the value is 0.01, an update just happened; if the value is 0.99,
 /// an update is about to happen.
 ///
-/// This can be used to interpolate when rendering.
+/// This can be used to interpolate when rendering. The value is only updated once per frame
+/// (after all of the updates for that frame have run), so it will stay the same for the
+/// whole of [`State::draw`](crate::State::draw), no matter how long drawing takes.
+///
+/// When using a variable time step, this function always returns `0.0`, as updates
+/// and draws happen in lockstep.
 ///
 /// This function returns an [`f32`], which is usually what you want when blending - however,
 /// if you need a more precise representation of the blend factor, you can call
 /// [`get_blend_factor_precise`].
 pub fn get_blend_factor(ctx: &Context) -> f32 {
     match ctx.time.tick_rate {
-        Some(tick_rate) => ctx.time.accumulator.as_secs_f32() / tick_rate.as_secs_f32(),
+        Some(tick_rate) => (ctx.time.accumulator.as_secs_f32() / tick_rate.as_secs_f32()).min(1.0),
         None => 0.0,
     }
 }
@@ -151,14 +268,19 @@ pub fn get_blend_factor(ctx: &Context) -> f32 {
 /// For example, if the value is 0.01, an update just happened; if the value is 0.99,
 /// an update is about to happen.
 ///
-/// This can be used to interpolate when rendering.
+/// This can be used to interpolate when rendering. The value is only updated once per frame
+/// (after all of the updates for that frame have run), so it will stay the same for the
+/// whole of [`State::draw`](crate::State::draw), no matter how long drawing takes.
+///
+/// When using a variable time step, this function always returns `0.0`, as updates
+/// and draws happen in lockstep.
 ///
 /// This function returns an [`f64`], which is a very precise representation of the blend factor,
 /// but often difficult to use in game logic without casting. If you need an [`f32`], call
 /// [`get_blend_factor`] instead.
 pub fn get_blend_factor_precise(ctx: &Context) -> f64 {
     match ctx.time.tick_rate {
-        Some(tick_rate) => ctx.time.accumulator.as_secs_f64() / tick_rate.as_secs_f64(),
+        Some(tick_rate) => (ctx.time.accumulator.as_secs_f64() / tick_rate.as_secs_f64()).min(1.0),
         None => 0.0,
     }
 }
@@ -172,19 +294,288 @@ pub fn get_timestep(ctx: &Context) -> Timestep {
 }
 
 /// Sets the timestep of the application.
+///
+/// This can be called at any time, including during an update - the new timestep will be
+/// used from the next update onwards.
+///
+/// When switching between two fixed timesteps, any time that has built up in the
+/// [accumulator](get_accumulator) will be converted to the new tick rate, so the
+/// [blend factor](get_blend_factor) stays the same. This avoids the game loop running a
+/// burst of updates to catch up (or stalling) after the switch. When switching to or from
+/// a variable timestep, the accumulator will be reset.
 pub fn set_timestep(ctx: &mut Context, timestep: Timestep) {
     ctx.time.ticks_per_second = match timestep {
         Timestep::Fixed(tps) => Some(tps),
         Timestep::Variable => None,
     };
 
-    ctx.time.tick_rate = match timestep {
+    let tick_rate = match timestep {
         Timestep::Fixed(tps) => Some(Duration::from_secs_f64(1.0 / tps)),
         Timestep::Variable => None,
     };
+
+    ctx.time.accumulator = rescale_accumulator(ctx.time.accumulator, ctx.time.tick_rate, tick_rate);
+    ctx.time.tick_rate = tick_rate;
+}
+
+/// Returns the amount of time that has passed since the [`Context`] was created.
+///
+/// This is measured at the start of each frame, so it will stay the same for the whole
+/// of the frame's updates and draw. This makes it useful for animating shaders (e.g. via
+/// a `u_time` uniform), as everything drawn in a frame will see the same value.
+///
+/// Unlike the [accumulator](get_accumulator), this is not affected by changing the
+/// timestep, and will never decrease.
+pub fn get_elapsed_time(ctx: &Context) -> Duration {
+    ctx.time
+        .last_time
+        .saturating_duration_since(ctx.time.start_time)
+}
+
+/// Returns the number of updates that have completed since the game started.
+///
+/// During an update, this can be used as the index of the current update, starting
+/// from zero. When using a [fixed timestep](Timestep::Fixed), this counts each fixed
+/// update (so there may be zero or several per frame) - when using a variable timestep,
+/// there will be one update per frame.
+///
+/// The count is not reset when the timestep is changed.
+pub fn get_tick_count(ctx: &Context) -> u64 {
+    ctx.time.tick_count
+}
+
+/// Returns the number of frames that have been drawn since the game started.
+///
+/// During a draw, this can be used as the index of the current frame, starting
+/// from zero.
+pub fn get_frame_count(ctx: &Context) -> u64 {
+    ctx.time.frame_count
 }
 
-/// Returns the current frame rate, averaged out over the last 200 frames.
+/// Returns the current frame rate, averaged out over the
+/// [frame time history](get_frame_time_history).
+///
+/// If a [maximum frame rate](set_max_frame_rate) has been set, the time spent waiting
+/// for the next frame is included in each frame's time, so this will not go above the
+/// limit. Similarly, if vsync is enabled, this will not go above the monitor's refresh
+/// rate.
 pub fn get_fps(ctx: &Context) -> f64 {
     ctx.time.fps_tracker.get_fps()
 }
+
+/// Returns the amount of time that each of the most recent frames took, starting with the
+/// oldest.
+///
+/// The number of frames that are stored can be configured via
+/// [`ContextBuilder::frame_time_history`](crate::ContextBuilder::frame_time_history),
+/// and defaults to 200.
+///
+/// This can be useful for diagnosing stutter, which often doesn't show up in the average
+/// frame rate returned by [`get_fps`].
+pub fn get_frame_time_history(ctx: &Context) -> impl ExactSizeIterator<Item = Duration> + '_ {
+    ctx.time.fps_tracker.buffer.iter().copied()
+}
+
+/// Returns the frame time at the given percentile of the
+/// [frame time history](get_frame_time_history).
+///
+/// The percentile should be between `0.0` and `1.0` - for example, passing `0.99` will
+/// return a frame time that 99% of the recent frames were faster than (or equal to). Passing
+/// `0.5` will return the median frame time.
+///
+/// If no frames have been recorded yet, this will return `Duration::ZERO`.
+pub fn get_frame_time_percentile(ctx: &Context, percentile: f64) -> Duration {
+    frame_time_percentile(ctx.time.fps_tracker.buffer.iter().copied(), percentile)
+}
+
+/// Returns the maximum number of frames that will be drawn per second, if one has been set.
+pub fn get_max_frame_rate(ctx: &Context) -> Option<f64> {
+    ctx.time.max_frame_rate
+}
+
+/// Sets the maximum number of frames that will be drawn per second.
+///
+/// If a frame finishes early, the main thread will wait until it is time to start the
+/// next one. This can be used to reduce CPU and GPU usage when vsync is disabled, or to
+/// run the game at a lower frame rate than the monitor's refresh rate.
+///
+/// This only limits how often the game loop runs - when using a [fixed timestep](Timestep::Fixed),
+/// the time spent waiting is added to the accumulator as normal, so updates will still happen
+/// at the configured tick rate. If the frame rate limit is lower than the tick rate, multiple
+/// updates will run each frame.
+///
+/// Setting this to `None` (or to a value that is not positive, or too small to give a
+/// representable frame length) will remove the limit.
+/// While a limit is set, the 1 millisecond sleep enabled by
+/// [`ContextBuilder::fps_limit`](crate::ContextBuilder::fps_limit) is skipped, as it
+/// is no longer needed.
+pub fn set_max_frame_rate(ctx: &mut Context, max_frame_rate: Option<f64>) {
+    ctx.time.max_frame_rate = max_frame_rate;
+}
+
+fn frame_time_percentile<I>(frame_times: I, percentile: f64) -> Duration
+where
+    I: IntoIterator<Item = Duration>,
+{
+    let mut sorted: Vec<Duration> = frame_times.into_iter().collect();
+
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    sorted.sort_unstable();
+
+    // Nearest-rank method, so the result is always a frame time that actually happened.
+    let rank = (percentile.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+
+    sorted[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hz(ticks_per_second: f64) -> Duration {
+        Duration::from_secs_f64(1.0 / ticks_per_second)
+    }
+
+    /// Runs the fixed timestep part of the game loop for the given number of frames,
+    /// returning the number of updates that happened.
+    fn run_frames(accumulator: &mut Duration, tick_rate: Duration, frames: u32) -> u32 {
+        let frame_time = Duration::from_secs(1) / 144;
+        let mut updates = 0;
+
+        for _ in 0..frames {
+            *accumulator = accumulate(*accumulator, frame_time, tick_rate);
+
+            while *accumulator >= tick_rate {
+                updates += 1;
+                *accumulator -= tick_rate;
+            }
+        }
+
+        updates
+    }
+
+    #[test]
+    fn frame_limit_delay_waits_for_end_of_frame() {
+        let start = Instant::now();
+
+        assert_eq!(
+            frame_limit_delay(start, start + Duration::from_millis(5), Some(100.0)),
+            Some(Duration::from_millis(5))
+        );
+
+        assert_eq!(
+            frame_limit_delay(start, start + Duration::from_millis(10), Some(100.0)),
+            None
+        );
+
+        assert_eq!(
+            frame_limit_delay(start, start + Duration::from_millis(15), Some(100.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn frame_limit_delay_uncapped() {
+        let start = Instant::now();
+
+        assert_eq!(frame_limit_delay(start, start, None), None);
+        assert_eq!(frame_limit_delay(start, start, Some(0.0)), None);
+        assert_eq!(frame_limit_delay(start, start, Some(-60.0)), None);
+        assert_eq!(frame_limit_delay(start, start, Some(f64::INFINITY)), None);
+        assert_eq!(
+            frame_limit_delay(start, start, Some(f64::MIN_POSITIVE)),
+            None
+        );
+        assert_eq!(frame_limit_delay(start, start, Some(1e-300)), None);
+    }
+
+    #[test]
+    fn frame_time_history_is_bounded() {
+        let mut tracker = FpsTracker::new(3);
+
+        for ms in 1..=5 {
+            tracker.push(Duration::from_millis(ms));
+        }
+
+        assert_eq!(tracker.buffer, [3, 4, 5].map(Duration::from_millis));
+        assert!((tracker.get_fps() - 250.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn frame_time_percentiles() {
+        let frame_times: Vec<Duration> = [5, 1, 4, 2, 3, 10, 6, 8, 7, 9]
+            .iter()
+            .map(|&ms| Duration::from_millis(ms))
+            .collect();
+
+        assert_eq!(
+            frame_time_percentile(frame_times.iter().copied(), 0.5),
+            Duration::from_millis(5)
+        );
+        assert_eq!(
+            frame_time_percentile(frame_times.iter().copied(), 0.99),
+            Duration::from_millis(10)
+        );
+        assert_eq!(
+            frame_time_percentile(frame_times.iter().copied(), 0.0),
+            Duration::from_millis(1)
+        );
+        assert_eq!(frame_time_percentile([], 0.5), Duration::ZERO);
+    }
+
+    #[test]
+    fn switching_tick_rate_changes_update_frequency() {
+        let mut accumulator = Duration::ZERO;
+
+        let fast = run_frames(&mut accumulator, hz(60.0), 144);
+
+        accumulator = rescale_accumulator(accumulator, Some(hz(60.0)), Some(hz(30.0)));
+
+        let slow = run_frames(&mut accumulator, hz(30.0), 144);
+
+        assert!((59..=60).contains(&fast), "{} updates at 60hz", fast);
+        assert!((29..=30).contains(&slow), "{} updates at 30hz", slow);
+    }
+
+    #[test]
+    fn switching_tick_rate_preserves_blend_factor() {
+        let accumulator = Duration::from_millis(40);
+
+        let faster = rescale_accumulator(accumulator, Some(hz(20.0)), Some(hz(60.0)));
+        let slower = rescale_accumulator(accumulator, Some(hz(20.0)), Some(hz(10.0)));
+
+        // Neither switch should cause any extra updates to run immediately.
+        assert!(faster < hz(60.0));
+        assert!(slower < hz(10.0));
+
+        let factor = |acc: Duration, rate: Duration| acc.as_secs_f64() / rate.as_secs_f64();
+
+        assert!((factor(faster, hz(60.0)) - 0.8).abs() < 0.001);
+        assert!((factor(slower, hz(10.0)) - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn switching_to_or_from_variable_resets_accumulator() {
+        let accumulator = Duration::from_millis(10);
+
+        assert_eq!(
+            Duration::ZERO,
+            rescale_accumulator(accumulator, Some(hz(60.0)), None)
+        );
+
+        assert_eq!(
+            Duration::ZERO,
+            rescale_accumulator(accumulator, None, Some(hz(60.0)))
+        );
+    }
+
+    #[test]
+    fn accumulator_is_clamped() {
+        let accumulator = accumulate(Duration::ZERO, Duration::from_secs(5), hz(60.0));
+
+        assert_eq!(hz(60.0) * MAX_PENDING_TICKS, accumulator);
+    }
+}
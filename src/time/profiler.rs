@@ -0,0 +1,422 @@
+//! Functions and types relating to profiling.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::Context;
+
+/// The maximum number of unique scopes that can be recorded in a single frame.
+///
+/// Once this limit has been reached, any new scopes will not be recorded until the
+/// next frame begins.
+pub const MAX_PROFILE_SCOPES: usize = 1024;
+
+/// Records the amount of time spent in the rest of the current block, as part of the
+/// current frame's [profile report](crate::time::get_profile_report).
+///
+/// This is a shortcut for creating a [`ProfileScope`] that lives until the end of the
+/// block.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tetra::{Context, State};
+/// # use tetra::time;
+/// # struct GameState;
+/// # fn find_path() {}
+/// impl State for GameState {
+///     fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+///         time::profile_scope!(ctx, "pathfinding");
+///
+///         find_path();
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! profile_scope {
+    ($ctx:expr, $name:expr) => {
+        let _profile_scope = $crate::time::ProfileScope::new($ctx, $name);
+    };
+}
+
+/// A guard that records the amount of time between its creation and its destruction,
+/// as part of the current frame's [profile report](crate::time::get_profile_report).
+///
+/// Scopes that are created while another scope is alive will be nested underneath it.
+/// Tetra creates root scopes called `"update"`, `"draw"` and `"present"` while running
+/// the game loop, so any scopes you create within [`State`](crate::State) methods will
+/// be nested under those.
+///
+/// If a scope with the same name is created multiple times under the same parent
+/// within a single frame, the timings will be combined.
+///
+/// The [`profile_scope`](crate::time::profile_scope) macro provides a more concise way of
+/// using this type.
+#[must_use = "the scope will end as soon as the guard is dropped"]
+#[derive(Debug)]
+pub struct ProfileScope {
+    profiler: Rc<RefCell<Profiler>>,
+    depth: Option<usize>,
+}
+
+impl ProfileScope {
+    /// Starts a new profiling scope, which will end when the returned guard is dropped.
+    pub fn new(ctx: &Context, name: &'static str) -> ProfileScope {
+        let profiler = Rc::clone(&ctx.time.profiler);
+        let depth = profiler.borrow_mut().enter(name, Instant::now());
+
+        ProfileScope { profiler, depth }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if let Some(depth) = self.depth {
+            self.profiler.borrow_mut().exit(depth, Instant::now());
+        }
+    }
+}
+
+/// Information about a single scope in a [`ProfileReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileScopeInfo {
+    name: &'static str,
+    total_time: Duration,
+    count: u32,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl ProfileScopeInfo {
+    /// Returns the name of the scope.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the total amount of time spent in the scope during the frame.
+    pub fn total_time(&self) -> Duration {
+        self.total_time
+    }
+
+    /// Returns the number of times that the scope was entered during the frame.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Returns the index of the scope's parent, or `None` if it is a root scope.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Returns the indices of the scope's children, in the order they were first entered.
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+}
+
+/// The timings recorded by [`ProfileScope`]s during a single frame.
+///
+/// Scopes are stored in the order that they were first entered, and can be looked
+/// up by index via [`get`](ProfileReport::get).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileReport {
+    scopes: Vec<ProfileScopeInfo>,
+}
+
+impl ProfileReport {
+    /// Returns all of the scopes in the report, in the order they were first entered.
+    pub fn scopes(&self) -> &[ProfileScopeInfo] {
+        &self.scopes
+    }
+
+    /// Returns the scope with the given index, if it exists.
+    pub fn get(&self, index: usize) -> Option<&ProfileScopeInfo> {
+        self.scopes.get(index)
+    }
+
+    /// Returns an iterator of the scopes that do not have a parent.
+    pub fn roots(&self) -> impl Iterator<Item = &ProfileScopeInfo> {
+        self.scopes.iter().filter(|s| s.parent.is_none())
+    }
+
+    /// Returns an iterator of the direct children of a scope.
+    pub fn children<'a>(
+        &'a self,
+        scope: &'a ProfileScopeInfo,
+    ) -> impl Iterator<Item = &'a ProfileScopeInfo> {
+        scope.children.iter().map(move |&i| &self.scopes[i])
+    }
+
+    /// Finds a scope by its path, with each level of nesting separated by a `/`.
+    ///
+    /// For example, `"update/pathfinding"` would find a scope called `"pathfinding"`
+    /// that was nested directly under the root `"update"` scope.
+    pub fn find(&self, path: &str) -> Option<&ProfileScopeInfo> {
+        let mut segments = path.split('/');
+
+        let first = segments.next()?;
+        let mut current = self.roots().find(|s| s.name == first)?;
+
+        for segment in segments {
+            current = self.children(current).find(|s| s.name == segment)?;
+        }
+
+        Some(current)
+    }
+
+    /// Returns `true` if no scopes were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Profiler {
+    current: Vec<ProfileScopeInfo>,
+    stack: Vec<(usize, Instant)>,
+    report: ProfileReport,
+}
+
+impl Profiler {
+    pub(crate) fn new() -> Profiler {
+        Profiler {
+            current: Vec::new(),
+            stack: Vec::new(),
+            report: ProfileReport::default(),
+        }
+    }
+
+    /// Enters a scope, returning the depth of the stack before it was entered
+    /// (or `None` if the scope limit has been reached).
+    fn enter(&mut self, name: &'static str, now: Instant) -> Option<usize> {
+        let parent = self.stack.last().map(|&(i, _)| i);
+
+        let existing = match parent {
+            Some(p) => self.current[p]
+                .children
+                .iter()
+                .copied()
+                .find(|&i| self.current[i].name == name),
+
+            None => self
+                .current
+                .iter()
+                .position(|s| s.parent.is_none() && s.name == name),
+        };
+
+        let index = match existing {
+            Some(i) => i,
+
+            None if self.current.len() < MAX_PROFILE_SCOPES => {
+                let i = self.current.len();
+
+                self.current.push(ProfileScopeInfo {
+                    name,
+                    total_time: Duration::ZERO,
+                    count: 0,
+                    parent,
+                    children: Vec::new(),
+                });
+
+                if let Some(p) = parent {
+                    self.current[p].children.push(i);
+                }
+
+                i
+            }
+
+            None => return None,
+        };
+
+        let depth = self.stack.len();
+
+        self.stack.push((index, now));
+
+        Some(depth)
+    }
+
+    fn exit(&mut self, depth: usize, now: Instant) {
+        // Guards should always be dropped in reverse order, but if one has been
+        // moved somewhere else, any scopes above it get closed too.
+        while self.stack.len() > depth {
+            if let Some((index, started)) = self.stack.pop() {
+                let scope = &mut self.current[index];
+                scope.total_time += now.saturating_duration_since(started);
+                scope.count += 1;
+            }
+        }
+    }
+
+    pub(crate) fn finish_frame(&mut self) {
+        let now = Instant::now();
+
+        let open = std::mem::take(&mut self.stack);
+
+        for &(index, started) in &open {
+            let scope = &mut self.current[index];
+            scope.total_time += now.saturating_duration_since(started);
+            scope.count += 1;
+        }
+
+        self.report.scopes = std::mem::take(&mut self.current);
+
+        // Any scopes that are still open get re-created, so that they can continue
+        // into the next frame at the same depth.
+        for (index, _) in open {
+            let name = self.report.scopes[index].name;
+            self.enter(name, now);
+        }
+    }
+
+    pub(crate) fn report(&self) -> &ProfileReport {
+        &self.report
+    }
+}
+
+/// Returns the timings recorded by [`ProfileScope`]s during the last complete frame.
+///
+/// The report is reset at the end of every frame, after the `"present"` scope has ended.
+pub fn get_profile_report(ctx: &Context) -> ProfileReport {
+    ctx.time.profiler.borrow().report().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn scopes_nest_by_construction_order() {
+        let mut profiler = Profiler::new();
+        let t = Instant::now();
+
+        let update = profiler.enter("update", t).unwrap();
+        let path = profiler.enter("pathfinding", t + ms(1)).unwrap();
+        profiler.exit(path, t + ms(4));
+        let physics = profiler.enter("physics", t + ms(4)).unwrap();
+        profiler.exit(physics, t + ms(6));
+        profiler.exit(update, t + ms(7));
+
+        let draw = profiler.enter("draw", t + ms(7)).unwrap();
+        profiler.exit(draw, t + ms(9));
+
+        profiler.finish_frame();
+
+        let report = profiler.report();
+
+        let roots: Vec<_> = report.roots().map(|s| s.name()).collect();
+        assert_eq!(roots, ["update", "draw"]);
+
+        let update = report.find("update").unwrap();
+        assert_eq!(update.total_time(), ms(7));
+
+        let children: Vec<_> = report.children(update).map(|s| s.name()).collect();
+        assert_eq!(children, ["pathfinding", "physics"]);
+
+        let path = report.find("update/pathfinding").unwrap();
+        assert_eq!(path.total_time(), ms(3));
+        assert_eq!(path.count(), 1);
+        assert_eq!(report.get(path.parent().unwrap()), Some(update));
+
+        assert!(report.find("draw/pathfinding").is_none());
+    }
+
+    #[test]
+    fn repeated_scopes_are_combined() {
+        let mut profiler = Profiler::new();
+        let t = Instant::now();
+
+        for i in 0..3 {
+            let update = profiler.enter("update", t + ms(i * 10)).unwrap();
+            let inner = profiler.enter("inner", t + ms(i * 10)).unwrap();
+            profiler.exit(inner, t + ms(i * 10 + 2));
+            profiler.exit(update, t + ms(i * 10 + 5));
+        }
+
+        profiler.finish_frame();
+
+        let report = profiler.report();
+        assert_eq!(report.scopes().len(), 2);
+
+        let update = report.find("update").unwrap();
+        assert_eq!(update.count(), 3);
+        assert_eq!(update.total_time(), ms(15));
+
+        let inner = report.find("update/inner").unwrap();
+        assert_eq!(inner.count(), 3);
+        assert_eq!(inner.total_time(), ms(6));
+    }
+
+    #[test]
+    fn report_resets_between_frames() {
+        let mut profiler = Profiler::new();
+        let t = Instant::now();
+
+        let a = profiler.enter("a", t).unwrap();
+        profiler.exit(a, t + ms(1));
+        profiler.finish_frame();
+
+        assert!(profiler.report().find("a").is_some());
+
+        let b = profiler.enter("b", t + ms(1)).unwrap();
+        profiler.exit(b, t + ms(2));
+        profiler.finish_frame();
+
+        let report = profiler.report();
+        assert!(report.find("a").is_none());
+        assert_eq!(report.find("b").unwrap().total_time(), ms(1));
+
+        profiler.finish_frame();
+        assert!(profiler.report().is_empty());
+    }
+
+    #[test]
+    fn open_scopes_continue_into_next_frame() {
+        let mut profiler = Profiler::new();
+
+        let outer = profiler.enter("outer", Instant::now()).unwrap();
+        profiler.finish_frame();
+
+        assert_eq!(profiler.report().find("outer").unwrap().count(), 1);
+
+        let inner = profiler.enter("inner", Instant::now()).unwrap();
+        profiler.exit(inner, Instant::now());
+        profiler.exit(outer, Instant::now());
+        profiler.finish_frame();
+
+        assert!(profiler.report().find("outer/inner").is_some());
+    }
+
+    #[test]
+    fn scope_count_is_capped() {
+        let mut profiler = Profiler::new();
+        let t = Instant::now();
+
+        let root = profiler.enter("root", t).unwrap();
+
+        let names: Vec<&'static str> = (0..MAX_PROFILE_SCOPES)
+            .map(|i| &*Box::leak(i.to_string().into_boxed_str()))
+            .collect();
+
+        let recorded = names
+            .iter()
+            .filter_map(|name| {
+                let index = profiler.enter(name, t)?;
+                profiler.exit(index, t);
+                Some(index)
+            })
+            .count();
+
+        profiler.exit(root, t);
+        profiler.finish_frame();
+
+        assert_eq!(recorded, MAX_PROFILE_SCOPES - 1);
+        assert_eq!(profiler.report().scopes().len(), MAX_PROFILE_SCOPES);
+    }
+}
@@ -0,0 +1,264 @@
+//! A timer for cooldowns and timed events.
+
+use std::time::Duration;
+
+use crate::time;
+use crate::Context;
+
+/// A timer that counts up to a fixed duration.
+///
+/// This is useful for things like cooldowns, invulnerability windows and timed events.
+/// Timers are advanced via [`tick`](Self::tick), which uses the
+/// [delta time](time::get_delta_time) of the current update - this means that they
+/// will behave consistently regardless of the game's [`Timestep`](time::Timestep).
+///
+/// By default, a timer will stop once it reaches its duration. Timers created via
+/// [`Timer::repeating`] will instead wrap back around to the start, carrying over any
+/// leftover time.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use tetra::{Context, State};
+/// # use tetra::time::Timer;
+/// struct GameState {
+///     spawn_timer: Timer,
+/// }
+///
+/// impl State for GameState {
+///     fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+///         for _ in 0..self.spawn_timer.tick(ctx) {
+///             // Spawn an enemy...
+///         }
+///
+///         Ok(())
+///     }
+/// }
+/// ```
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timer {
+    duration: Duration,
+    elapsed: Duration,
+    repeating: bool,
+
+    // Whether `advance` has already reported that the timer finished. This is tracked
+    // separately from the elapsed time, so that a timer with a duration of zero still
+    // reports finishing once.
+    #[cfg_attr(feature = "serde", serde(default))]
+    reported_finish: bool,
+}
+
+impl Timer {
+    /// Creates a new timer, which will stop once the given duration has passed.
+    pub fn new(duration: Duration) -> Timer {
+        Timer {
+            duration,
+            elapsed: Duration::ZERO,
+            repeating: false,
+            reported_finish: false,
+        }
+    }
+
+    /// Creates a new timer, which will wrap back around to the start each time the
+    /// given duration passes.
+    pub fn repeating(duration: Duration) -> Timer {
+        Timer {
+            duration,
+            elapsed: Duration::ZERO,
+            repeating: true,
+            reported_finish: false,
+        }
+    }
+
+    /// Advances the timer by the [delta time](time::get_delta_time) of the current
+    /// update or draw.
+    ///
+    /// Returns the number of times that the timer finished during this tick. For a
+    /// non-repeating timer, this will be `1` on the tick where it finishes, and `0`
+    /// otherwise - a timer with a duration of zero finishes on its first tick. For a repeating timer, this can be more than `1` if the delta time is
+    /// longer than the timer's duration (e.g. if the tick rate is low).
+    pub fn tick(&mut self, ctx: &Context) -> u32 {
+        self.advance(time::get_delta_time(ctx))
+    }
+
+    /// Advances the timer by the given amount of time.
+    ///
+    /// This works the same as [`tick`](Self::tick), but allows you to control how much
+    /// time has passed (e.g. to implement slow motion).
+    ///
+    /// A repeating timer with a duration of zero will finish exactly once per call.
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        if self.repeating {
+            if self.duration.is_zero() {
+                return 1;
+            }
+
+            let elapsed = self.elapsed.as_nanos() + delta.as_nanos();
+            let duration = self.duration.as_nanos();
+
+            self.elapsed = Duration::from_nanos((elapsed % duration) as u64);
+
+            u32::try_from(elapsed / duration).unwrap_or(u32::MAX)
+        } else {
+            if self.reported_finish {
+                return 0;
+            }
+
+            self.elapsed = Duration::min(self.elapsed + delta, self.duration);
+
+            if self.is_finished() {
+                self.reported_finish = true;
+                1
+            } else {
+                0
+            }
+        }
+    }
+
+    /// Returns `true` if the timer has reached its duration.
+    ///
+    /// Repeating timers never finish, as they wrap around instead - use the return value
+    /// of [`tick`](Self::tick) to find out when they complete a cycle.
+    pub fn is_finished(&self) -> bool {
+        !self.repeating && self.elapsed >= self.duration
+    }
+
+    /// Returns `true` if the timer wraps back around to the start when it reaches its
+    /// duration.
+    pub fn is_repeating(&self) -> bool {
+        self.repeating
+    }
+
+    /// Sets whether the timer should wrap back around to the start when it reaches its
+    /// duration.
+    pub fn set_repeating(&mut self, repeating: bool) {
+        self.repeating = repeating;
+    }
+
+    /// Returns the amount of time that the timer takes to finish.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Sets the amount of time that the timer takes to finish.
+    ///
+    /// The elapsed time will be clamped to the new duration.
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+        self.elapsed = Duration::min(self.elapsed, duration);
+
+        // If the timer was extended, it will need to finish again.
+        self.reported_finish &= self.elapsed >= duration;
+    }
+
+    /// Returns the amount of time that has passed since the timer was started (or since
+    /// it last wrapped around, for repeating timers).
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Returns the amount of time left until the timer finishes.
+    pub fn remaining(&self) -> Duration {
+        self.duration.saturating_sub(self.elapsed)
+    }
+
+    /// Returns how close the timer is to finishing, from `0.0` to `1.0`.
+    ///
+    /// A timer with a duration of zero is always considered to be complete, and so
+    /// will return `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f64() / self.duration.as_secs_f64()) as f32
+        }
+    }
+
+    /// Resets the timer back to the start.
+    pub fn reset(&mut self) {
+        self.elapsed = Duration::ZERO;
+        self.reported_finish = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timer_finishes_once() {
+        let mut timer = Timer::new(Duration::from_millis(100));
+
+        assert_eq!(timer.advance(Duration::from_millis(60)), 0);
+        assert!(!timer.is_finished());
+        assert_eq!(timer.remaining(), Duration::from_millis(40));
+        assert_eq!(timer.progress(), 0.6);
+
+        assert_eq!(timer.advance(Duration::from_millis(60)), 1);
+        assert!(timer.is_finished());
+        assert_eq!(timer.elapsed(), Duration::from_millis(100));
+        assert_eq!(timer.remaining(), Duration::ZERO);
+        assert_eq!(timer.progress(), 1.0);
+
+        assert_eq!(timer.advance(Duration::from_millis(60)), 0);
+
+        timer.reset();
+
+        assert!(!timer.is_finished());
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+    }
+
+    #[test]
+    fn repeating_timer_wraps_multiple_times() {
+        let mut timer = Timer::repeating(Duration::from_millis(100));
+
+        assert_eq!(timer.advance(Duration::from_millis(50)), 0);
+        assert_eq!(timer.advance(Duration::from_millis(50)), 1);
+        assert_eq!(timer.elapsed(), Duration::ZERO);
+
+        // A long frame can cause the timer to wrap several times at once.
+        assert_eq!(timer.advance(Duration::from_millis(370)), 3);
+        assert_eq!(timer.elapsed(), Duration::from_millis(70));
+        assert!(!timer.is_finished());
+    }
+
+    #[test]
+    fn zero_duration() {
+        let mut timer = Timer::new(Duration::ZERO);
+
+        assert!(timer.is_finished());
+        assert_eq!(timer.progress(), 1.0);
+        assert_eq!(timer.advance(Duration::ZERO), 1);
+        assert_eq!(timer.advance(Duration::from_millis(10)), 0);
+
+        timer.reset();
+        assert_eq!(timer.advance(Duration::from_millis(10)), 1);
+
+        let mut timer = Timer::repeating(Duration::ZERO);
+
+        assert_eq!(timer.advance(Duration::from_millis(10)), 1);
+    }
+
+    #[test]
+    fn changing_duration_after_finishing() {
+        let mut timer = Timer::new(Duration::from_millis(100));
+
+        assert_eq!(timer.advance(Duration::from_millis(100)), 1);
+
+        // Extending the timer means it has to finish again.
+        timer.set_duration(Duration::from_millis(150));
+        assert!(!timer.is_finished());
+        assert_eq!(timer.advance(Duration::from_millis(50)), 1);
+
+        // Shrinking a finished timer doesn't report it a second time.
+        timer.set_duration(Duration::from_millis(50));
+        assert_eq!(timer.advance(Duration::from_millis(50)), 0);
+    }
+}
@@ -0,0 +1,9 @@
+//! Functions and types relating to user interfaces.
+//!
+//! Tetra does not provide a full UI framework, but this module contains some building
+//! blocks that are fiddly to implement from scratch, and that can be composed into
+//! your game's own UI.
+
+mod text_input;
+
+pub use text_input::*;
@@ -0,0 +1,634 @@
+use std::ops::Range;
+
+use crate::error::Result;
+use crate::graphics::mesh::{Mesh, ShapeStyle};
+use crate::graphics::text::{Font, Text};
+use crate::graphics::{Color, DrawParams, Rectangle};
+use crate::input::{self, Key, KeyModifier};
+use crate::math::Vec2;
+use crate::{Context, Event};
+
+const CARET_WIDTH: f32 = 1.0;
+
+/// A single-line text field, with support for cursor movement, selection and the
+/// system clipboard.
+///
+/// A `TextInput` does not poll for input by itself - instead, you should pass the
+/// events that your game receives in [`State::event`](crate::State::event) to
+/// [`handle_event`](TextInput::handle_event). Events are only processed while the
+/// input is [focused](TextInput::set_focused), which allows multiple inputs to be
+/// displayed at once.
+///
+/// The following controls are supported:
+///
+/// | Keys | Action |
+/// | --- | --- |
+/// | Left/Right | Moves the cursor by one character. |
+/// | Ctrl + Left/Right | Moves the cursor by one word. |
+/// | Home/End | Moves the cursor to the start/end of the text. |
+/// | Shift + any of the above | Extends the selection. |
+/// | Backspace/Delete | Deletes the selection, or the previous/next character. |
+/// | Ctrl + Backspace/Delete | Deletes the previous/next word. |
+/// | Ctrl + A | Selects all of the text. |
+/// | Ctrl + X/C/V | Cuts, copies or pastes via the system clipboard. |
+///
+/// Key repeat is disabled by default, so holding down one of these keys will only
+/// perform the action once, unless you enable it via [`ContextBuilder::key_repeat`](crate::ContextBuilder::key_repeat)
+/// or [`window::set_key_repeat_enabled`](crate::window::set_key_repeat_enabled).
+///
+/// # Unicode
+///
+/// The cursor always sits on a `char` boundary, so editing will never split a code point
+/// in half. Grapheme clusters are not taken into account, however - for example, an
+/// emoji that is made up of multiple code points will take multiple presses of Backspace
+/// to delete.
+///
+/// Control characters (including newlines and tabs) are removed from text that is
+/// typed or pasted into the input.
+///
+/// # Examples
+///
+/// The [`text_input_widget`](https://github.com/17cupsofcoffee/tetra/blob/main/examples/text_input_widget.rs)
+/// example demonstrates how to use a `TextInput`.
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    text: Text,
+    buffer: EditBuffer,
+    focused: bool,
+
+    rectangle: Mesh,
+    text_color: Color,
+    selection_color: Color,
+    caret_color: Color,
+}
+
+impl TextInput {
+    /// Creates a new, empty text input, using the given font.
+    ///
+    /// The input will be focused by default.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the underlying graphics API encounters an error.
+    pub fn new(ctx: &mut Context, font: Font) -> Result<TextInput> {
+        Ok(TextInput {
+            text: Text::new("", font),
+            buffer: EditBuffer::default(),
+            focused: true,
+
+            rectangle: Mesh::rectangle(ctx, ShapeStyle::Fill, Rectangle::new(0.0, 0.0, 1.0, 1.0))?,
+            text_color: Color::WHITE,
+            selection_color: Color::rgba(0.2, 0.4, 0.8, 0.6),
+            caret_color: Color::WHITE,
+        })
+    }
+
+    /// Processes an event, updating the input's value, cursor and selection.
+    ///
+    /// Returns `true` if the event was used by the input, or `false` if it was ignored
+    /// (for example, because the input is not focused).
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the system clipboard could not be accessed.
+    pub fn handle_event(&mut self, ctx: &mut Context, event: &Event) -> Result<bool> {
+        if !self.focused {
+            return Ok(false);
+        }
+
+        let handled = match event {
+            Event::TextInput { text } => {
+                self.buffer.insert(text);
+                true
+            }
+
+            Event::KeyPressed { key } => self.handle_key(ctx, *key)?,
+
+            _ => false,
+        };
+
+        if handled {
+            self.sync_text();
+        }
+
+        Ok(handled)
+    }
+
+    fn handle_key(&mut self, ctx: &mut Context, key: Key) -> Result<bool> {
+        let ctrl = input::is_key_modifier_down(ctx, KeyModifier::Ctrl);
+        let shift = input::is_key_modifier_down(ctx, KeyModifier::Shift);
+
+        let unit = if ctrl { Unit::Word } else { Unit::Char };
+
+        match key {
+            Key::Left => self.buffer.move_left(unit, shift),
+            Key::Right => self.buffer.move_right(unit, shift),
+            Key::Home => self.buffer.set_cursor(0, shift),
+            Key::End => self.buffer.set_cursor(self.buffer.value.len(), shift),
+            Key::Backspace => self.buffer.delete_backward(unit),
+            Key::Delete => self.buffer.delete_forward(unit),
+
+            Key::A if ctrl => self.buffer.select_all(),
+
+            Key::C | Key::X if ctrl => {
+                if !self.buffer.selection().is_empty() {
+                    input::set_clipboard_text(ctx, self.buffer.selected_text())?;
+
+                    if key == Key::X {
+                        self.buffer.insert("");
+                    }
+                }
+            }
+
+            Key::V if ctrl => {
+                let pasted = input::get_clipboard_text(ctx)?;
+                self.buffer.insert(&pasted);
+            }
+
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    fn sync_text(&mut self) {
+        if self.text.content() != self.buffer.value {
+            self.text.set_content(self.buffer.value.clone());
+        }
+    }
+
+    /// Draws the input, with the top left corner of the text at the given position.
+    ///
+    /// The selection and caret will only be drawn while the input is focused.
+    pub fn draw(&mut self, ctx: &mut Context, position: Vec2<f32>) {
+        if self.focused && !self.buffer.selection().is_empty() {
+            self.draw_selection(ctx, position);
+        }
+
+        self.text.draw(
+            ctx,
+            DrawParams::new().position(position).color(self.text_color),
+        );
+
+        if self.focused {
+            let caret = self.caret_position(ctx);
+            let height = self.text.font().line_height();
+
+            self.rectangle.draw(
+                ctx,
+                DrawParams::new()
+                    .position(position + caret)
+                    .scale(Vec2::new(CARET_WIDTH, height))
+                    .color(self.caret_color),
+            );
+        }
+    }
+
+    fn draw_selection(&mut self, ctx: &mut Context, position: Vec2<f32>) {
+        let selection = self.buffer.selection();
+        let mut lines: Vec<Rectangle> = Vec::new();
+
+        for glyph in self.text.glyph_positions(ctx) {
+            if !selection.contains(&glyph.index) {
+                continue;
+            }
+
+            match lines.last_mut() {
+                Some(line) if line.y == glyph.bounds.y => {
+                    line.width = glyph.bounds.right() - line.x;
+                }
+
+                _ => lines.push(glyph.bounds),
+            }
+        }
+
+        for line in lines {
+            self.rectangle.draw(
+                ctx,
+                DrawParams::new()
+                    .position(position + Vec2::new(line.x, line.y))
+                    .scale(Vec2::new(line.width, line.height))
+                    .color(self.selection_color),
+            );
+        }
+    }
+
+    /// Returns the position of the caret, relative to the top left corner of the text.
+    ///
+    /// This can be used to position an IME's candidate list via
+    /// [`input::set_text_input_rect`](crate::input::set_text_input_rect).
+    pub fn caret_position(&mut self, ctx: &mut Context) -> Vec2<f32> {
+        let cursor = self.buffer.cursor;
+
+        let glyph = self
+            .text
+            .glyph_positions(ctx)
+            .iter()
+            .find(|glyph| glyph.index == cursor)
+            .map(|glyph| Vec2::new(glyph.bounds.x, glyph.bounds.y));
+
+        match glyph {
+            Some(position) => position,
+            None => self.text.end_position(ctx),
+        }
+    }
+
+    /// Returns the current value of the input.
+    pub fn value(&self) -> &str {
+        &self.buffer.value
+    }
+
+    /// Sets the value of the input.
+    ///
+    /// The cursor will be moved to the end of the new value, and the selection will be
+    /// cleared. The value is not checked against the [maximum length](TextInput::set_max_length),
+    /// and may contain control characters.
+    pub fn set_value<V>(&mut self, value: V)
+    where
+        V: Into<String>,
+    {
+        self.buffer.set_value(value.into());
+        self.sync_text();
+    }
+
+    /// Returns the position of the cursor, as a byte index into the value.
+    pub fn cursor(&self) -> usize {
+        self.buffer.cursor
+    }
+
+    /// Moves the cursor to the given byte index, clearing the selection.
+    ///
+    /// If the index is past the end of the value, the cursor will be moved to the end.
+    /// If the index is not on a `char` boundary, the cursor will be moved to the start
+    /// of the character that contains it.
+    pub fn set_cursor(&mut self, index: usize) {
+        self.buffer.set_cursor(index, false);
+    }
+
+    /// Returns the byte range of the selected text.
+    ///
+    /// If no text is selected, the range will be empty, and will start at the cursor.
+    pub fn selection(&self) -> Range<usize> {
+        self.buffer.selection()
+    }
+
+    /// Returns the selected text.
+    pub fn selected_text(&self) -> &str {
+        self.buffer.selected_text()
+    }
+
+    /// Selects all of the text in the input.
+    pub fn select_all(&mut self) {
+        self.buffer.select_all();
+    }
+
+    /// Returns whether the input is focused.
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Sets whether the input is focused.
+    ///
+    /// Unfocused inputs ignore all events, and do not draw their caret or selection.
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Returns the maximum number of characters that can be entered into the input,
+    /// if one has been set.
+    pub fn max_length(&self) -> Option<usize> {
+        self.buffer.max_length
+    }
+
+    /// Sets the maximum number of characters that can be entered into the input.
+    ///
+    /// Text that is typed or pasted into the input will be truncated to fit. Changing
+    /// the maximum length does not affect the current value.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.buffer.max_length = max_length;
+    }
+
+    /// Returns a reference to the font used by the input.
+    pub fn font(&self) -> &Font {
+        self.text.font()
+    }
+
+    /// Sets the font used by the input.
+    pub fn set_font(&mut self, font: Font) {
+        self.text.set_font(font);
+    }
+
+    /// Returns the color of the input's text.
+    pub fn text_color(&self) -> Color {
+        self.text_color
+    }
+
+    /// Sets the color of the input's text.
+    pub fn set_text_color(&mut self, color: Color) {
+        self.text_color = color;
+    }
+
+    /// Returns the color of the selection highlight.
+    pub fn selection_color(&self) -> Color {
+        self.selection_color
+    }
+
+    /// Sets the color of the selection highlight.
+    pub fn set_selection_color(&mut self, color: Color) {
+        self.selection_color = color;
+    }
+
+    /// Returns the color of the caret.
+    pub fn caret_color(&self) -> Color {
+        self.caret_color
+    }
+
+    /// Sets the color of the caret.
+    pub fn set_caret_color(&mut self, color: Color) {
+        self.caret_color = color;
+    }
+}
+
+/// The distance that the cursor should move in a single step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Char,
+    Word,
+}
+
+/// Characters of the same class are grouped together when moving by word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Whitespace,
+    Punctuation,
+    Word,
+}
+
+impl CharClass {
+    fn of(ch: char) -> CharClass {
+        if ch.is_whitespace() {
+            CharClass::Whitespace
+        } else if ch.is_ascii_punctuation() {
+            CharClass::Punctuation
+        } else {
+            // This includes combining marks and emoji, so that they aren't treated
+            // as word boundaries.
+            CharClass::Word
+        }
+    }
+}
+
+/// The editing logic for a `TextInput`, separated out from the rendering so that it
+/// can be tested without a `Context`.
+///
+/// `cursor` and `anchor` are byte indices, and are always on `char` boundaries. The
+/// selection is the range between them.
+#[derive(Debug, Clone, Default)]
+struct EditBuffer {
+    value: String,
+    cursor: usize,
+    anchor: usize,
+    max_length: Option<usize>,
+}
+
+impl EditBuffer {
+    fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    fn selected_text(&self) -> &str {
+        &self.value[self.selection()]
+    }
+
+    fn set_value(&mut self, value: String) {
+        self.value = value;
+        self.cursor = self.value.len();
+        self.anchor = self.cursor;
+    }
+
+    fn set_cursor(&mut self, index: usize, extend_selection: bool) {
+        let mut index = index.min(self.value.len());
+
+        while !self.value.is_char_boundary(index) {
+            index -= 1;
+        }
+
+        self.cursor = index;
+
+        if !extend_selection {
+            self.anchor = index;
+        }
+    }
+
+    fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.value.len();
+    }
+
+    /// Replaces the selection with the given text.
+    fn insert(&mut self, text: &str) {
+        let selection = self.selection();
+
+        let remaining = match self.max_length {
+            Some(max_length) => {
+                let kept = self.value.chars().count() - self.selected_text().chars().count();
+                max_length.saturating_sub(kept)
+            }
+
+            None => usize::MAX,
+        };
+
+        let text: String = text
+            .chars()
+            .filter(|ch| !ch.is_control())
+            .take(remaining)
+            .collect();
+
+        self.value.replace_range(selection.clone(), &text);
+        self.set_cursor(selection.start + text.len(), false);
+    }
+
+    fn move_left(&mut self, unit: Unit, extend_selection: bool) {
+        let selection = self.selection();
+
+        if !extend_selection && !selection.is_empty() {
+            self.set_cursor(selection.start, false);
+        } else {
+            self.set_cursor(self.prev_boundary(unit), extend_selection);
+        }
+    }
+
+    fn move_right(&mut self, unit: Unit, extend_selection: bool) {
+        let selection = self.selection();
+
+        if !extend_selection && !selection.is_empty() {
+            self.set_cursor(selection.end, false);
+        } else {
+            self.set_cursor(self.next_boundary(unit), extend_selection);
+        }
+    }
+
+    fn delete_backward(&mut self, unit: Unit) {
+        if self.selection().is_empty() {
+            self.anchor = self.prev_boundary(unit);
+        }
+
+        self.insert("");
+    }
+
+    fn delete_forward(&mut self, unit: Unit) {
+        if self.selection().is_empty() {
+            self.anchor = self.next_boundary(unit);
+        }
+
+        self.insert("");
+    }
+
+    /// Finds the position that the cursor would move to if it stepped backwards.
+    fn prev_boundary(&self, unit: Unit) -> usize {
+        let mut chars = self.value[..self.cursor].char_indices().rev().peekable();
+
+        match unit {
+            Unit::Char => chars.next().map_or(0, |(i, _)| i),
+
+            Unit::Word => {
+                while chars
+                    .next_if(|&(_, ch)| CharClass::of(ch) == CharClass::Whitespace)
+                    .is_some()
+                {}
+
+                let mut boundary = 0;
+
+                if let Some(&(_, ch)) = chars.peek() {
+                    let class = CharClass::of(ch);
+
+                    while let Some((i, _)) = chars.next_if(|&(_, ch)| CharClass::of(ch) == class) {
+                        boundary = i;
+                    }
+                }
+
+                boundary
+            }
+        }
+    }
+
+    /// Finds the position that the cursor would move to if it stepped forwards.
+    fn next_boundary(&self, unit: Unit) -> usize {
+        let mut chars = self.value[self.cursor..].char_indices().peekable();
+
+        let offset = match unit {
+            Unit::Char => chars.next().map(|(i, ch)| i + ch.len_utf8()),
+
+            Unit::Word => {
+                while chars
+                    .next_if(|&(_, ch)| CharClass::of(ch) == CharClass::Whitespace)
+                    .is_some()
+                {}
+
+                if let Some(&(_, ch)) = chars.peek() {
+                    let class = CharClass::of(ch);
+                    while chars
+                        .next_if(|&(_, ch)| CharClass::of(ch) == class)
+                        .is_some()
+                    {}
+                }
+
+                chars.peek().map(|&(i, _)| i)
+            }
+        };
+
+        offset.map_or(self.value.len(), |offset| self.cursor + offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EditBuffer, Unit};
+
+    fn buffer(value: &str, cursor: usize) -> EditBuffer {
+        let mut buffer = EditBuffer::default();
+        buffer.set_value(value.into());
+        buffer.set_cursor(cursor, false);
+        buffer
+    }
+
+    #[test]
+    fn editing_respects_char_boundaries() {
+        // 'é' is two bytes, and the emoji is four.
+        let mut buffer = buffer("aé😀b", 7);
+
+        buffer.delete_backward(Unit::Char);
+        assert_eq!("aéb", buffer.value);
+        assert_eq!(3, buffer.cursor);
+
+        buffer.move_left(Unit::Char, false);
+        assert_eq!(1, buffer.cursor);
+
+        buffer.delete_forward(Unit::Char);
+        assert_eq!("ab", buffer.value);
+
+        // Indices inside a character are rounded down to its start.
+        buffer.set_value("a😀".into());
+        buffer.set_cursor(3, false);
+        assert_eq!(1, buffer.cursor);
+    }
+
+    #[test]
+    fn word_movement() {
+        let mut buffer = buffer("hello, world  foo", 0);
+
+        buffer.move_right(Unit::Word, false);
+        assert_eq!(5, buffer.cursor);
+
+        buffer.move_right(Unit::Word, false);
+        assert_eq!(6, buffer.cursor);
+
+        buffer.move_right(Unit::Word, false);
+        assert_eq!(12, buffer.cursor);
+
+        buffer.move_right(Unit::Word, false);
+        assert_eq!(17, buffer.cursor);
+
+        buffer.move_left(Unit::Word, false);
+        assert_eq!(14, buffer.cursor);
+
+        buffer.delete_backward(Unit::Word);
+        assert_eq!("hello, foo", buffer.value);
+        assert_eq!(7, buffer.cursor);
+    }
+
+    #[test]
+    fn selection_is_replaced_by_input() {
+        let mut buffer = buffer("hello world", 0);
+
+        buffer.move_right(Unit::Word, true);
+        assert_eq!(0..5, buffer.selection());
+        assert_eq!("hello", buffer.selected_text());
+
+        buffer.insert("goodbye\n");
+        assert_eq!("goodbye world", buffer.value);
+        assert_eq!(7, buffer.cursor);
+        assert!(buffer.selection().is_empty());
+
+        buffer.select_all();
+        buffer.move_left(Unit::Char, false);
+        assert_eq!(0, buffer.cursor);
+        assert!(buffer.selection().is_empty());
+    }
+
+    #[test]
+    fn max_length_truncates_input() {
+        let mut buffer = buffer("abc", 3);
+        buffer.max_length = Some(5);
+
+        buffer.insert("😀😀😀");
+        assert_eq!("abc😀😀", buffer.value);
+
+        // Replacing a selection frees up space.
+        buffer.select_all();
+        buffer.insert("123456");
+        assert_eq!("12345", buffer.value);
+    }
+}
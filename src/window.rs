@@ -1,15 +1,36 @@
 //! Functions and types relating to the game window, and the environment it is running in.
 
-use crate::{graphics::ImageData, Context, Result};
+use std::rc::Rc;
+
+use crate::graphics::{self, ImageData, Rectangle};
+use crate::math::Vec2;
+use crate::platform::RawCursor;
+use crate::{Context, Result, TetraError};
 
 /// Quits the game, if it is currently running.
 ///
+/// This will always quit the game, even when called in response to an
+/// [`Event::QuitRequested`](crate::Event::QuitRequested) that has been
+/// [cancelled](cancel_quit).
+///
 /// Note that quitting the game does not take effect until the end of the current
 /// cycle of the game loop. This will probably change later.
 pub fn quit(ctx: &mut Context) {
     ctx.running = false;
 }
 
+/// Stops the game from quitting in response to an
+/// [`Event::QuitRequested`](crate::Event::QuitRequested).
+///
+/// This should be called from [`State::event`](crate::State::event) while handling the
+/// event - for example, to show a confirmation prompt if the player has unsaved changes.
+/// If the player then confirms that they want to quit, you can call [`quit`].
+///
+/// Calling this at any other time has no effect.
+pub fn cancel_quit(ctx: &mut Context) {
+    ctx.quit_cancelled = true;
+}
+
 /// Maximizes the window.
 pub fn maximize(ctx: &mut Context) {
     ctx.window.maximize();
@@ -34,6 +55,20 @@ pub fn focus(ctx: &mut Context) {
     ctx.window.focus();
 }
 
+/// Sets the activation policy of the application.
+///
+/// This determines whether the application appears in the Dock and can be switched
+/// to via the application switcher. It is only supported on MacOS - calling this
+/// function on other platforms will have no effect.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the activation policy could not be changed.
+pub fn set_activation_policy(ctx: &mut Context, policy: ActivationPolicy) -> Result {
+    ctx.window.set_activation_policy(policy)
+}
+
 /// Gets the display's refresh rate.
 pub fn get_refresh_rate(ctx: &Context) -> Result<i32> {
     ctx.window.get_refresh_rate()
@@ -101,6 +136,9 @@ pub fn get_size(ctx: &Context) -> (i32, i32) {
 
 /// Sets the size of the window.
 ///
+/// The size is not limited to the size of the monitor - use [`set_size_clamped`]
+/// if you want to make sure that the window fits on the screen.
+///
 /// # Errors
 ///
 /// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
@@ -109,6 +147,45 @@ pub fn set_size(ctx: &mut Context, width: i32, height: i32) -> Result {
     ctx.window.set_window_size(width, height)
 }
 
+/// Sets the size of the window, shrinking it if needed so that it fits within the
+/// usable area of the monitor that it is currently on.
+///
+/// The usable area excludes things like taskbars and docks, and the size of the
+/// window's decorations (e.g. the title bar) is taken into account where the platform
+/// is able to report it. This avoids the title bar ending up off-screen when the
+/// requested size is larger than the desktop.
+///
+/// The size that was actually applied is returned.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the monitor state was inaccessible.
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the game was unable to change the window size.
+pub fn set_size_clamped(ctx: &mut Context, width: i32, height: i32) -> Result<(i32, i32)> {
+    let monitor_index = ctx.window.get_current_monitor()?;
+    let bounds = ctx.window.get_monitor_usable_bounds(monitor_index)?;
+    let borders = ctx.window.get_border_size();
+
+    let (width, height) = clamp_size_to_bounds((width, height), bounds, borders);
+
+    ctx.window.set_window_size(width, height)?;
+
+    Ok((width, height))
+}
+
+fn clamp_size_to_bounds(
+    size: (i32, i32),
+    bounds: Rectangle<i32>,
+    borders: (i32, i32),
+) -> (i32, i32) {
+    let max_width = i32::max(bounds.width - borders.0, 1);
+    let max_height = i32::max(bounds.height - borders.1, 1);
+
+    (i32::min(size.0, max_width), i32::min(size.1, max_height))
+}
+
 /// Returns the width of the window in physical pixels.
 ///
 /// The output of this function may differ from the output of [`get_width`] if
@@ -163,6 +240,83 @@ pub fn get_maximum_size(ctx: &Context) -> (i32, i32) {
     ctx.window.get_maximum_size()
 }
 
+/// Constrains the aspect ratio (width divided by height) of the window, so that
+/// it stays between `min` and `max` when the window is resized.
+///
+/// SDL does not support aspect ratio constraints natively, so they are emulated by
+/// resizing the window as soon as the platform reports a size that does not fit the
+/// constraint, before [`Event::Resized`](crate::Event::Resized) is fired. This means
+/// that your game will only ever observe conforming sizes, but on some platforms the
+/// window may briefly appear at the wrong size while the player is dragging its edges.
+/// When the window is too tall, its height will be reduced, and when the window is
+/// too wide, its width will be reduced.
+///
+/// The constraint is not applied while the window is maximized, minimized or
+/// fullscreen, as the size is controlled by the window manager or the monitor in
+/// those cases, and resizing the window would fight against it. Once the window is
+/// restored, the constraint will be applied again.
+///
+/// If the window's current size does not fit the constraint, it will be resized
+/// immediately.
+///
+/// # Errors
+///
+/// * [`TetraError::InvalidAspectRatio`](crate::TetraError::InvalidAspectRatio) will be returned
+///   if `min` or `max` is not a positive, finite number, or if `min` is greater than `max`.
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the game was unable to change the window size.
+pub fn set_aspect_ratio(ctx: &mut Context, min: f32, max: f32) -> Result {
+    ctx.window.set_aspect_ratio(Some((min, max)))
+}
+
+/// Removes the aspect ratio constraint from the window, if one was set via
+/// [`set_aspect_ratio`] or [`ContextBuilder::aspect_ratio`](crate::ContextBuilder::aspect_ratio).
+pub fn reset_aspect_ratio(ctx: &mut Context) {
+    // Removing the constraint never resizes the window, so this can't fail.
+    let _ = ctx.window.set_aspect_ratio(None);
+}
+
+/// Gets the minimum and maximum aspect ratio of the window, if a constraint has been set.
+pub fn get_aspect_ratio(ctx: &Context) -> Option<(f32, f32)> {
+    ctx.window.get_aspect_ratio()
+}
+
+pub(crate) fn validate_aspect_ratio(min: f32, max: f32) -> Result {
+    let is_valid = |ratio: f32| ratio.is_finite() && ratio > 0.0;
+
+    if is_valid(min) && is_valid(max) && min <= max {
+        Ok(())
+    } else {
+        Err(TetraError::InvalidAspectRatio { min, max })
+    }
+}
+
+/// Records the size reported by a resize event, returning `false` if it is the same as
+/// the previously reported size.
+pub(crate) fn is_new_size(last: &mut Option<(i32, i32)>, size: (i32, i32)) -> bool {
+    last.replace(size) != Some(size)
+}
+
+/// Shrinks the size so that its aspect ratio fits within the given range, as closely as
+/// is possible using whole pixels.
+pub(crate) fn constrain_aspect_ratio(size: (i32, i32), min: f32, max: f32) -> (i32, i32) {
+    let (mut width, mut height) = size;
+
+    if width <= 0 || height <= 0 {
+        return size;
+    }
+
+    if (width as f32 / height as f32) < min {
+        height = i32::max((width as f32 / min).floor() as i32, 1);
+    }
+
+    if (width as f32 / height as f32) > max {
+        width = i32::max((height as f32 * max).floor() as i32, 1);
+    }
+
+    (width, height)
+}
+
 /// Sets the position of the window.
 ///
 /// You can either pass the co-ordinates to this function as `i32`s, or
@@ -176,6 +330,44 @@ where
     ctx.window.set_position(x.into(), y.into());
 }
 
+/// Centers the window on the specified monitor.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the monitor state was inaccessible, or if the monitor index is invalid.
+pub fn center_on_monitor(ctx: &mut Context, monitor_index: i32) -> Result {
+    let monitor_count = ctx.window.get_monitor_count()?;
+
+    if monitor_index < 0 || monitor_index >= monitor_count {
+        return Err(TetraError::PlatformError(format!(
+            "invalid monitor index {} (there are {} monitors connected)",
+            monitor_index, monitor_count
+        )));
+    }
+
+    ctx.window.set_position(
+        WindowPosition::Centered(monitor_index),
+        WindowPosition::Centered(monitor_index),
+    );
+
+    Ok(())
+}
+
+/// Centers the window on the monitor that it is currently on.
+///
+/// If the window spans multiple monitors, the one reported by [`get_current_monitor`]
+/// will be used.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the monitor state was inaccessible.
+pub fn center_on_current_monitor(ctx: &mut Context) -> Result {
+    let monitor_index = ctx.window.get_current_monitor()?;
+    center_on_monitor(ctx, monitor_index)
+}
+
 /// Gets the position of the window.
 pub fn get_position(ctx: &Context) -> (i32, i32) {
     ctx.window.get_position()
@@ -197,6 +389,43 @@ pub fn set_decorated(ctx: &mut Context, bordered: bool) {
     ctx.window.set_decorated(bordered);
 }
 
+/// Gets the opacity of the window, from `0.0` (fully transparent) to `1.0` (fully opaque).
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the platform does not support window opacity.
+pub fn get_opacity(ctx: &Context) -> Result<f32> {
+    ctx.window.get_opacity()
+}
+
+/// Sets the opacity of the window, from `0.0` (fully transparent) to `1.0` (fully opaque).
+///
+/// Values outside of this range will be clamped.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the platform (or the window manager/compositor) does not support window opacity.
+pub fn set_opacity(ctx: &mut Context, opacity: f32) -> Result {
+    ctx.window.set_opacity(opacity)
+}
+
+/// Returns whether the window is set to always be displayed on top of other windows.
+pub fn is_always_on_top(ctx: &Context) -> bool {
+    ctx.window.is_always_on_top()
+}
+
+/// Sets whether the window should always be displayed on top of other windows.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the platform (or the window manager) ignored the request.
+pub fn set_always_on_top(ctx: &mut Context, always_on_top: bool) -> Result {
+    ctx.window.set_always_on_top(always_on_top)
+}
+
 /// Sets the icon for the window.
 ///
 /// Note that the preferred way of setting the icon is as part of packaging your game,
@@ -229,21 +458,52 @@ pub fn set_visible(ctx: &mut Context, visible: bool) {
 
 /// Sets whether the window should be vsynced.
 ///
+/// This is a shortcut for calling [`set_vsync_mode`] with [`VsyncMode::On`] or
+/// [`VsyncMode::Off`].
+///
 /// # Errors
 ///
 /// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
 ///   will be returned if the game was unable to change vsync mode.
 pub fn set_vsync(ctx: &mut Context, vsync: bool) -> Result {
-    ctx.window.set_vsync(vsync)
+    set_vsync_mode(ctx, if vsync { VsyncMode::On } else { VsyncMode::Off })
 }
 
 /// Returns whether or not vsync is enabled.
+///
+/// This will return `true` for both [`VsyncMode::On`] and [`VsyncMode::Adaptive`].
 pub fn is_vsync_enabled(ctx: &Context) -> bool {
-    ctx.window.is_vsync_enabled()
+    get_vsync_mode(ctx) != VsyncMode::Off
+}
+
+/// Sets how the window should be synchronized with the monitor's refresh rate.
+///
+/// If [`VsyncMode::Adaptive`] is requested but is not supported by the graphics
+/// driver, [`VsyncMode::On`] will be used instead. You can check which mode was
+/// actually applied via [`get_vsync_mode`].
+///
+/// # Errors
+///
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the game was unable to change vsync mode.
+pub fn set_vsync_mode(ctx: &mut Context, mode: VsyncMode) -> Result {
+    ctx.window.set_vsync_mode(mode)
+}
+
+/// Returns the vsync mode that is currently being used.
+///
+/// This reports the mode that the graphics driver actually applied, which may differ
+/// from the mode that was requested.
+pub fn get_vsync_mode(ctx: &Context) -> VsyncMode {
+    ctx.window.get_vsync_mode()
 }
 
 /// Sets whether the window should be in fullscreen mode.
 ///
+/// When leaving fullscreen, the window's previous size, position and maximized state will
+/// be restored. If the window would no longer be visible (e.g. because a monitor was
+/// disconnected while the game was fullscreen), it will be moved onto the current monitor.
+///
 /// # Errors
 ///
 /// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
@@ -257,6 +517,53 @@ pub fn is_fullscreen(ctx: &Context) -> bool {
     ctx.window.is_fullscreen()
 }
 
+/// Toggles the window between fullscreen and windowed mode.
+///
+/// When leaving fullscreen, the window's previous size, position and maximized state will
+/// be restored.
+///
+/// # Errors
+///
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the game was unable to enter or exit fullscreen.
+pub fn toggle_fullscreen(ctx: &mut Context) -> Result {
+    let fullscreen = ctx.window.is_fullscreen();
+    ctx.window.set_fullscreen(!fullscreen)
+}
+
+/// Sets the fullscreen mode of the window.
+///
+/// Unlike [`set_fullscreen`], this allows you to enter exclusive fullscreen, which changes
+/// the resolution of the monitor. The available modes can be queried via
+/// [`get_display_modes`] - if the requested mode is not supported, the closest match will
+/// be used instead.
+///
+/// The projection matrix will be updated immediately, and a
+/// [`WindowMetricsChanged`](crate::Event::WindowMetricsChanged) event will be fired at the
+/// start of the next frame.
+///
+/// # Errors
+///
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the game was unable to change the fullscreen mode.
+pub fn set_fullscreen_ex(ctx: &mut Context, mode: FullscreenMode) -> Result {
+    ctx.window.set_fullscreen_mode(mode)?;
+    graphics::set_viewport_size(ctx);
+
+    Ok(())
+}
+
+/// Returns the current fullscreen mode of the window.
+///
+/// # Errors
+///
+/// * [`TetraError::FailedToChangeDisplayMode`](crate::TetraError::FailedToChangeDisplayMode)
+///   will be returned if the window is in exclusive fullscreen, and the display mode
+///   could not be determined.
+pub fn get_fullscreen_mode(ctx: &Context) -> Result<FullscreenMode> {
+    ctx.window.get_fullscreen_mode()
+}
+
 /// Sets whether or not the mouse cursor should be visible.
 ///
 /// # Errors
@@ -272,6 +579,19 @@ pub fn is_mouse_visible(ctx: &Context) -> bool {
     ctx.window.is_mouse_visible()
 }
 
+/// Sets the image that is used for the mouse cursor while it is over the window.
+///
+/// The window will keep the cursor alive for as long as it is active, so it is safe
+/// to drop your copy of it.
+pub fn set_cursor(ctx: &mut Context, cursor: &Cursor) {
+    ctx.window.set_cursor(&cursor.handle);
+}
+
+/// Resets the mouse cursor back to the system's default.
+pub fn reset_cursor(ctx: &mut Context) {
+    ctx.window.reset_cursor();
+}
+
 /// Sets whether or not the mouse is grabbed by the window.
 ///
 /// When this is active, the cursor will not be able to leave the window while it
@@ -366,6 +686,32 @@ pub fn get_monitor_size(ctx: &Context, monitor_index: i32) -> Result<(i32, i32)>
     ctx.window.get_monitor_size(monitor_index)
 }
 
+/// Gets the usable area of a monitor connected to the device, in screen co-ordinates.
+///
+/// This is the area of the monitor that is not covered by things like taskbars and
+/// docks, and so it may be smaller than the size returned by [`get_monitor_size`].
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the monitor state was inaccessible.
+pub fn get_monitor_usable_bounds(ctx: &Context, monitor_index: i32) -> Result<Rectangle<i32>> {
+    ctx.window.get_monitor_usable_bounds(monitor_index)
+}
+
+/// Gets the display modes supported by a monitor connected to the device.
+///
+/// These can be passed to [`set_fullscreen_ex`] in order to change the resolution
+/// of the monitor.
+///
+/// # Errors
+///
+/// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+///   if the monitor state was inaccessible.
+pub fn get_display_modes(ctx: &Context, monitor_index: i32) -> Result<Vec<DisplayMode>> {
+    ctx.window.get_display_modes(monitor_index)
+}
+
 /// Gets the index of the monitor that the window is currently on.
 ///
 /// # Errors
@@ -446,6 +792,167 @@ pub fn is_key_repeat_enabled(ctx: &Context) -> bool {
     ctx.window.is_key_repeat_enabled()
 }
 
+/// Returns a handle to the game's window, which can be passed to libraries that use
+/// [`raw-window-handle`](https://docs.rs/raw-window-handle) to interact with native windows
+/// (e.g. file dialogs, UI renderers, or other graphics APIs).
+///
+/// This function is only available when the `raw_window_handle` feature is enabled.
+#[cfg(feature = "raw_window_handle")]
+pub fn get_window_handle(ctx: &Context) -> WindowHandle<'_> {
+    WindowHandle {
+        window: &ctx.window,
+    }
+}
+
+/// A handle to the game's window, which implements the traits from
+/// [`raw-window-handle`](https://docs.rs/raw-window-handle).
+///
+/// This can be obtained via [`get_window_handle`], and is only available when the
+/// `raw_window_handle` feature is enabled.
+///
+/// # Safety
+///
+/// The underlying native handles are only valid for as long as the [`Context`] lives.
+/// The borrow held by this type ensures that for safe code, but if you extract the raw
+/// handles and store them elsewhere (e.g. in an FFI library), you must ensure that they
+/// are not used after the game has closed.
+#[cfg(feature = "raw_window_handle")]
+#[derive(Clone, Copy)]
+pub struct WindowHandle<'a> {
+    window: &'a crate::platform::Window,
+}
+
+#[cfg(feature = "raw_window_handle")]
+impl std::fmt::Debug for WindowHandle<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WindowHandle").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "raw_window_handle")]
+impl raw_window_handle::HasWindowHandle for WindowHandle<'_> {
+    fn window_handle(
+        &self,
+    ) -> std::result::Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError>
+    {
+        raw_window_handle::HasWindowHandle::window_handle(self.window)
+    }
+}
+
+#[cfg(feature = "raw_window_handle")]
+impl raw_window_handle::HasDisplayHandle for WindowHandle<'_> {
+    fn display_handle(
+        &self,
+    ) -> std::result::Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError>
+    {
+        raw_window_handle::HasDisplayHandle::display_handle(self.window)
+    }
+}
+
+/// A mouse cursor, which can be displayed via [`set_cursor`].
+///
+/// Cursors are drawn by the operating system, rather than by Tetra, so they do not
+/// lag behind the mouse like a sprite drawn at the
+/// [mouse position](crate::input::get_mouse_position) would.
+///
+/// You can clone a cursor cheaply, as it is a [reference-counted](https://doc.rust-lang.org/std/rc/struct.Rc.html)
+/// handle to a platform resource.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    handle: Rc<RawCursor>,
+}
+
+impl Cursor {
+    /// Creates a new cursor from an image.
+    ///
+    /// The hotspot is the point within the image that represents the position of
+    /// the mouse (e.g. the tip of an arrow), measured in pixels from the top-left.
+    ///
+    /// Image data in formats other than [`TextureFormat::Rgba8`](crate::graphics::TextureFormat::Rgba8)
+    /// will be converted before the cursor is created.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the cursor could not be created (for example, if the hotspot is outside
+    ///   of the image).
+    pub fn from_image_data(
+        ctx: &mut Context,
+        data: &ImageData,
+        hotspot: Vec2<i32>,
+    ) -> Result<Cursor> {
+        let handle = ctx.window.new_cursor(data, hotspot)?;
+
+        Ok(Cursor {
+            handle: Rc::new(handle),
+        })
+    }
+
+    /// Creates one of the operating system's standard cursors.
+    ///
+    /// # Errors
+    ///
+    /// * [`TetraError::PlatformError`](crate::TetraError::PlatformError) will be returned
+    ///   if the cursor could not be created.
+    pub fn system(ctx: &mut Context, cursor: SystemCursor) -> Result<Cursor> {
+        let handle = ctx.window.new_system_cursor(cursor)?;
+
+        Ok(Cursor {
+            handle: Rc::new(handle),
+        })
+    }
+}
+
+impl PartialEq for Cursor {
+    fn eq(&self, other: &Cursor) -> bool {
+        Rc::ptr_eq(&self.handle, &other.handle)
+    }
+}
+
+/// The standard cursors that are provided by the operating system.
+///
+/// The exact appearance of these will vary between platforms.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SystemCursor {
+    /// The default arrow cursor.
+    Arrow,
+
+    /// An I-beam, used for selecting text.
+    IBeam,
+
+    /// An hourglass or spinner, used when the application is busy.
+    Wait,
+
+    /// A crosshair.
+    Crosshair,
+
+    /// An arrow combined with an hourglass or spinner, used when the application
+    /// is busy but can still be interacted with.
+    WaitArrow,
+
+    /// A double-headed arrow pointing north-west and south-east.
+    ResizeNwSe,
+
+    /// A double-headed arrow pointing north-east and south-west.
+    ResizeNeSw,
+
+    /// A double-headed arrow pointing west and east.
+    ResizeHorizontal,
+
+    /// A double-headed arrow pointing north and south.
+    ResizeVertical,
+
+    /// A four-headed arrow pointing north, south, east and west.
+    ResizeAll,
+
+    /// A slashed circle, used to indicate that an action is not allowed.
+    NotAllowed,
+
+    /// A pointing hand, used for links and buttons.
+    Hand,
+}
+
 /// Represents the position of a window on the screen.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -468,3 +975,314 @@ impl From<i32> for WindowPosition {
         WindowPosition::Positioned(val)
     }
 }
+
+/// Activation policies for an application, which control how it is presented
+/// by the operating system.
+///
+/// These are currently only supported on MacOS, where they map to the
+/// [`NSApplicationActivationPolicy`](https://developer.apple.com/documentation/appkit/nsapplication/activationpolicy)
+/// enum.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ActivationPolicy {
+    /// The application is an ordinary app, which appears in the Dock and
+    /// can have a user interface.
+    ///
+    /// This is the default.
+    Regular,
+
+    /// The application does not appear in the Dock, but can still have a
+    /// user interface (e.g. for tools and utility windows).
+    Accessory,
+
+    /// The application does not appear in the Dock, and may not create
+    /// windows or be activated.
+    Prohibited,
+}
+
+/// The ways that the window can be synchronized with the monitor's refresh rate.
+///
+/// # Serde
+///
+/// Serialization and deserialization of this type (via [Serde](https://serde.rs/))
+/// can be enabled via the `serde` feature.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VsyncMode {
+    /// Frames are presented as soon as they are ready. This may cause screen tearing.
+    Off,
+
+    /// Frames are presented in sync with the monitor's refresh rate.
+    On,
+
+    /// Frames are presented in sync with the monitor's refresh rate, unless a frame
+    /// is late, in which case it is presented immediately.
+    ///
+    /// This trades a small amount of tearing for less stuttering when the game can't
+    /// keep up with the refresh rate. Some graphics drivers do not support this mode.
+    Adaptive,
+}
+
+/// A resolution and refresh rate that a monitor can be set to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayMode {
+    /// The width of the display, in pixels.
+    pub width: i32,
+
+    /// The height of the display, in pixels.
+    pub height: i32,
+
+    /// The refresh rate of the display, in hertz.
+    ///
+    /// This may be `0` if the refresh rate is unknown.
+    pub refresh_rate: i32,
+}
+
+/// The different ways that the window can be displayed.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FullscreenMode {
+    /// The window is displayed normally.
+    Windowed,
+
+    /// The window covers the whole monitor, at the monitor's current resolution.
+    ///
+    /// This is what [`set_fullscreen`] uses.
+    Desktop,
+
+    /// The window takes exclusive control of the monitor, changing its resolution
+    /// to the given display mode.
+    Exclusive(DisplayMode),
+}
+
+/// A snapshot of the window's size and scale.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct WindowMetrics {
+    pub(crate) logical_size: (i32, i32),
+    pub(crate) physical_size: (i32, i32),
+    pub(crate) dpi_scale: f32,
+}
+
+/// Coalesces changes to the window's metrics, so that a size change and a scale change
+/// that are reported separately by the platform can be applied together.
+///
+/// The platform layer calls [`invalidate`](PendingWindowMetrics::invalidate) whenever it
+/// sees an event that might affect the metrics, and then calls
+/// [`resolve`](PendingWindowMetrics::resolve) once per frame, before any events are
+/// dispatched to the game.
+#[derive(Debug)]
+pub(crate) struct PendingWindowMetrics {
+    applied: WindowMetrics,
+    dirty: bool,
+}
+
+impl PendingWindowMetrics {
+    pub(crate) fn new(initial: WindowMetrics) -> PendingWindowMetrics {
+        PendingWindowMetrics {
+            applied: initial,
+            dirty: false,
+        }
+    }
+
+    /// Returns the metrics that were most recently applied.
+    pub(crate) fn applied(&self) -> WindowMetrics {
+        self.applied
+    }
+
+    /// Marks the metrics as potentially changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// If the metrics have been invalidated since the last call, queries the current
+    /// values and returns them if they differ from the ones that were last applied.
+    pub(crate) fn resolve<F>(&mut self, query: F) -> Option<WindowMetrics>
+    where
+        F: FnOnce() -> WindowMetrics,
+    {
+        if !self.dirty {
+            return None;
+        }
+
+        self.dirty = false;
+
+        let current = query();
+
+        if current == self.applied {
+            None
+        } else {
+            self.applied = current;
+            Some(current)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clamp_size_to_bounds, constrain_aspect_ratio, is_new_size, validate_aspect_ratio,
+        PendingWindowMetrics, WindowMetrics,
+    };
+    use crate::graphics::Rectangle;
+
+    fn metrics(width: i32, height: i32, dpi_scale: f32) -> WindowMetrics {
+        WindowMetrics {
+            logical_size: (width, height),
+            physical_size: (
+                (width as f32 * dpi_scale) as i32,
+                (height as f32 * dpi_scale) as i32,
+            ),
+            dpi_scale,
+        }
+    }
+
+    #[test]
+    fn size_and_scale_changes_are_coalesced() {
+        let mut pending = PendingWindowMetrics::new(metrics(800, 600, 1.0));
+
+        // Moving onto a 150% monitor: SDL reports the new pixel size and the
+        // display change as separate events within the same frame.
+        pending.invalidate();
+        pending.invalidate();
+
+        assert_eq!(
+            pending.resolve(|| metrics(800, 600, 1.5)),
+            Some(metrics(800, 600, 1.5))
+        );
+
+        assert_eq!(pending.resolve(|| metrics(800, 600, 1.5)), None);
+    }
+
+    #[test]
+    fn size_is_clamped_to_usable_bounds() {
+        let bounds = Rectangle::new(0, 40, 1920, 1040);
+
+        assert_eq!(
+            clamp_size_to_bounds((1280, 720), bounds, (2, 32)),
+            (1280, 720)
+        );
+
+        assert_eq!(
+            clamp_size_to_bounds((2560, 1440), bounds, (2, 32)),
+            (1918, 1008)
+        );
+
+        assert_eq!(
+            clamp_size_to_bounds((2560, 720), bounds, (0, 0)),
+            (1920, 720)
+        );
+    }
+
+    #[test]
+    fn size_is_constrained_to_aspect_ratio() {
+        // Already within range.
+        assert_eq!(constrain_aspect_ratio((1280, 720), 1.0, 2.0), (1280, 720));
+
+        // Too tall, so the height is reduced.
+        assert_eq!(constrain_aspect_ratio((800, 1200), 1.0, 2.0), (800, 800));
+
+        // Too wide, so the width is reduced.
+        assert_eq!(constrain_aspect_ratio((2000, 500), 1.0, 2.0), (1000, 500));
+
+        // Rounding never leaves the size outside of the range.
+        let (width, height) = constrain_aspect_ratio((100, 100), 1.5, 1.5);
+        assert_eq!((width, height), (99, 66));
+        assert_eq!(width as f32 / height as f32, 1.5);
+
+        // Constraining is stable, so the window will not keep being resized.
+        assert_eq!(constrain_aspect_ratio((99, 66), 1.5, 1.5), (99, 66));
+
+        // Sizes that can't be constrained are left alone.
+        assert_eq!(constrain_aspect_ratio((0, 100), 1.0, 2.0), (0, 100));
+        assert_eq!(constrain_aspect_ratio((1, 1), 4.0, 4.0), (1, 1));
+    }
+
+    #[test]
+    fn repeated_resizes_are_filtered() {
+        let mut last = None;
+
+        assert!(is_new_size(&mut last, (800, 600)));
+
+        // SDL reports the size again after the window is resized to fit the
+        // aspect ratio.
+        assert!(!is_new_size(&mut last, (800, 600)));
+
+        assert!(is_new_size(&mut last, (640, 480)));
+        assert!(is_new_size(&mut last, (800, 600)));
+    }
+
+    #[test]
+    fn invalid_aspect_ratios_are_rejected() {
+        assert!(validate_aspect_ratio(1.0, 2.0).is_ok());
+        assert!(validate_aspect_ratio(1.5, 1.5).is_ok());
+
+        assert!(matches!(
+            validate_aspect_ratio(2.0, 1.0),
+            Err(crate::TetraError::InvalidAspectRatio { min, max }) if min == 2.0 && max == 1.0
+        ));
+
+        assert!(validate_aspect_ratio(0.0, 1.0).is_err());
+        assert!(validate_aspect_ratio(-1.0, 1.0).is_err());
+        assert!(validate_aspect_ratio(1.0, f32::INFINITY).is_err());
+        assert!(validate_aspect_ratio(f32::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn applied_metrics_are_updated_on_change() {
+        let mut pending = PendingWindowMetrics::new(metrics(800, 600, 1.0));
+
+        pending.invalidate();
+        pending.resolve(|| metrics(800, 600, 2.0));
+
+        assert_eq!(pending.applied().dpi_scale, 2.0);
+
+        // A resize on the same monitor leaves the scale alone.
+        pending.invalidate();
+        pending.resolve(|| metrics(1024, 768, 2.0));
+
+        assert_eq!(pending.applied(), metrics(1024, 768, 2.0));
+    }
+
+    #[test]
+    fn nothing_is_reported_without_invalidation() {
+        let mut pending = PendingWindowMetrics::new(metrics(800, 600, 1.0));
+
+        assert_eq!(
+            pending.resolve(|| panic!("metrics should not be queried")),
+            None
+        );
+    }
+
+    #[test]
+    fn unchanged_metrics_are_not_reported() {
+        let mut pending = PendingWindowMetrics::new(metrics(800, 600, 1.0));
+
+        pending.invalidate();
+        assert_eq!(pending.resolve(|| metrics(800, 600, 1.0)), None);
+
+        // Resizing and then resizing back within a frame has no visible effect.
+        pending.invalidate();
+        pending.invalidate();
+        assert_eq!(pending.resolve(|| metrics(800, 600, 1.0)), None);
+    }
+
+    #[test]
+    fn changes_in_separate_frames_are_reported_separately() {
+        let mut pending = PendingWindowMetrics::new(metrics(800, 600, 1.0));
+
+        pending.invalidate();
+        assert_eq!(
+            pending.resolve(|| metrics(1024, 768, 1.0)),
+            Some(metrics(1024, 768, 1.0))
+        );
+
+        pending.invalidate();
+        assert_eq!(
+            pending.resolve(|| metrics(1024, 768, 2.0)),
+            Some(metrics(1024, 768, 2.0))
+        );
+    }
+}